@@ -427,14 +427,7 @@ impl AccountKey {
         let a: &Scalar = self.view_private_key.as_ref();
 
         // `Hs(a || n)`
-        let Hs: Scalar = {
-            let n = Scalar::from(index);
-            let mut digest = Blake2b512::new();
-            digest.update(SUBADDRESS_DOMAIN_TAG);
-            digest.update(a.as_bytes());
-            digest.update(n.as_bytes());
-            Scalar::from_hash(digest)
-        };
+        let Hs: Scalar = subaddress_hash(a, index);
 
         let b: &Scalar = self.spend_private_key.as_ref();
         RistrettoPrivate::from(Hs + b)
@@ -460,14 +453,7 @@ impl AccountKey {
         let a: &Scalar = self.view_private_key.as_ref();
 
         // `Hs(a || n)`
-        let Hs: Scalar = {
-            let n = Scalar::from(index);
-            let mut digest = Blake2b512::new();
-            digest.update(SUBADDRESS_DOMAIN_TAG);
-            digest.update(a.as_bytes());
-            digest.update(n.as_bytes());
-            Scalar::from_hash(digest)
-        };
+        let Hs: Scalar = subaddress_hash(a, index);
 
         let b: &Scalar = self.spend_private_key.as_ref();
         let c = a * (Hs + b);
@@ -475,6 +461,127 @@ impl AccountKey {
     }
 }
 
+/// View-only account key, containing the private view key and the public
+/// spend key of an account. It can identify and decrypt the outputs sent to
+/// the account and derive its subaddresses, but cannot spend.
+///
+/// Subaddresses derived from a view-only account key have no fog info, since
+/// signing the fog authority requires the subaddress view private key.
+#[derive(Clone, Message)]
+pub struct ViewAccountKey {
+    /// Private key 'a' used for view-key matching.
+    #[prost(message, required, tag = "1")]
+    view_private_key: RistrettoPrivate,
+
+    /// Public key `B` of the spend private key `b`.
+    #[prost(message, required, tag = "2")]
+    spend_public_key: RistrettoPublic,
+}
+
+// Note: Hash, Ord is implemented in terms of default_subaddress() for
+// consistency with AccountKey.
+impl Hash for ViewAccountKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.default_subaddress().hash(state)
+    }
+}
+
+impl Eq for ViewAccountKey {}
+
+impl PartialEq for ViewAccountKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.default_subaddress().eq(&other.default_subaddress())
+    }
+}
+
+impl PartialOrd for ViewAccountKey {
+    fn partial_cmp(&self, other: &ViewAccountKey) -> Option<Ordering> {
+        self.default_subaddress()
+            .partial_cmp(&other.default_subaddress())
+    }
+}
+
+impl Ord for ViewAccountKey {
+    fn cmp(&self, other: &ViewAccountKey) -> Ordering {
+        self.default_subaddress().cmp(&other.default_subaddress())
+    }
+}
+
+impl ViewAccountKey {
+    /// A user's view-only account key.
+    ///
+    /// # Arguments
+    /// * `view_private_key` - The user's private view key `a`.
+    /// * `spend_public_key` - The user's public spend key `B`.
+    #[inline]
+    pub fn new(view_private_key: &RistrettoPrivate, spend_public_key: &RistrettoPublic) -> Self {
+        Self {
+            view_private_key: *view_private_key,
+            spend_public_key: *spend_public_key,
+        }
+    }
+
+    /// Get the view private key.
+    pub fn view_private_key(&self) -> &RistrettoPrivate {
+        &self.view_private_key
+    }
+
+    /// Get the spend public key.
+    pub fn spend_public_key(&self) -> &RistrettoPublic {
+        &self.spend_public_key
+    }
+
+    /// Get the account's default subaddress.
+    #[inline]
+    pub fn default_subaddress(&self) -> PublicAddress {
+        self.subaddress(DEFAULT_SUBADDRESS_INDEX)
+    }
+
+    /// Get the account's change subaddress.
+    #[inline]
+    pub fn change_subaddress(&self) -> PublicAddress {
+        self.subaddress(CHANGE_SUBADDRESS_INDEX)
+    }
+
+    /// Get the account's i^th subaddress, without fog info.
+    pub fn subaddress(&self, index: u64) -> PublicAddress {
+        let spend_public_key = self.subaddress_spend_public(index);
+
+        // The subaddress view public key `C_i = a * D_i`.
+        let a: &Scalar = self.view_private_key.as_ref();
+        let view_public_key = RistrettoPublic::from(a * spend_public_key.as_ref());
+
+        PublicAddress::new(&spend_public_key, &view_public_key)
+    }
+
+    /// The public spend key `D_i = B + Hs(a || i) * G` for the i^th
+    /// subaddress.
+    pub fn subaddress_spend_public(&self, index: u64) -> RistrettoPublic {
+        let a: &Scalar = self.view_private_key.as_ref();
+        let Hs_G = RistrettoPublic::from(&RistrettoPrivate::from(subaddress_hash(a, index)));
+        RistrettoPublic::from(self.spend_public_key.as_ref() + Hs_G.as_ref())
+    }
+}
+
+impl From<&AccountKey> for ViewAccountKey {
+    fn from(account_key: &AccountKey) -> Self {
+        Self::new(
+            account_key.view_private_key(),
+            &RistrettoPublic::from(account_key.spend_private_key()),
+        )
+    }
+}
+
+/// `Hs(a || n)`, the hash used for subaddress derivation.
+fn subaddress_hash(a: &Scalar, index: u64) -> Scalar {
+    let n = Scalar::from(index);
+    let mut digest = Blake2b512::new();
+    digest.update(SUBADDRESS_DOMAIN_TAG);
+    digest.update(a.as_bytes());
+    digest.update(n.as_bytes());
+    Scalar::from_hash(digest)
+}
+
 #[cfg(test)]
 mod account_key_tests {
     use super::*;
@@ -546,6 +653,35 @@ mod account_key_tests {
         );
     }
 
+    #[test]
+    // Subaddresses derived from the view-only account key should match the
+    // subaddresses of the account key.
+    fn test_view_account_key_subaddresses_agree_with_account_key() {
+        mc_util_test_helper::run_with_several_seeds(|mut rng| {
+            let account_key = AccountKey::random(&mut rng);
+            let view_account_key = ViewAccountKey::from(&account_key);
+
+            assert_eq!(
+                view_account_key.default_subaddress(),
+                account_key.default_subaddress()
+            );
+            assert_eq!(
+                view_account_key.change_subaddress(),
+                account_key.change_subaddress()
+            );
+
+            let index = rng.next_u64();
+            assert_eq!(
+                view_account_key.subaddress(index),
+                account_key.subaddress(index)
+            );
+
+            let ser = mc_util_serial::encode(&view_account_key);
+            let result: ViewAccountKey = mc_util_serial::decode(&ser).unwrap();
+            assert_eq!(view_account_key, result);
+        });
+    }
+
     #[test_with_data(DefaultSubaddrKeysFromAcctPrivKeys::from_jsonl("../test-vectors/vectors"))]
     fn default_subaddr_keys_from_acct_priv_keys(case: DefaultSubaddrKeysFromAcctPrivKeys) {
         let spend_private_key = RistrettoPrivate::try_from(&case.spend_private_key).unwrap();
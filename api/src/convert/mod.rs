@@ -42,6 +42,7 @@ mod tx_prefix;
 mod validated_mint_config;
 mod verification_report;
 mod verification_signature;
+mod view_account_key;
 mod watcher;
 
 // printable
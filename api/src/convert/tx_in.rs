@@ -1,7 +1,8 @@
 //! Convert to/from external::TxIn.
 
 use crate::{convert::ConversionError, external};
-use mc_transaction_core::{tx, tx::TxOutMembershipProof, InputRules};
+use mc_crypto_keys::CompressedRistrettoPublic;
+use mc_transaction_core::{tx, tx::TxOutMembershipProof, InputRules, RevealedTxOut};
 use std::convert::TryFrom;
 
 /// Convert tx::TxIn --> external::TxIn.
@@ -73,6 +74,21 @@ impl From<&InputRules> for external::InputRules {
 
         input_rules.set_max_tombstone_block(source.max_tombstone_block);
 
+        let partial_fill_outputs = source
+            .partial_fill_outputs
+            .iter()
+            .map(external::RevealedTxOut::from)
+            .collect();
+        input_rules.set_partial_fill_outputs(partial_fill_outputs);
+
+        if let Some(partial_fill_change) = source.partial_fill_change.as_ref() {
+            input_rules.set_partial_fill_change(partial_fill_change.into());
+        }
+
+        input_rules.set_min_partial_fill_value(source.min_partial_fill_value);
+
+        input_rules.set_block_version(source.block_version);
+
         input_rules
     }
 }
@@ -88,9 +104,45 @@ impl TryFrom<&external::InputRules> for InputRules {
             .map(tx::TxOut::try_from)
             .collect::<Result<Vec<_>, _>>()?;
         let max_tombstone_block = source.max_tombstone_block;
+        let partial_fill_outputs = source
+            .get_partial_fill_outputs()
+            .iter()
+            .map(RevealedTxOut::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let partial_fill_change = source
+            .partial_fill_change
+            .as_ref()
+            .map(RevealedTxOut::try_from)
+            .transpose()?;
         Ok(InputRules {
             required_outputs,
             max_tombstone_block,
+            partial_fill_outputs,
+            partial_fill_change,
+            min_partial_fill_value: source.min_partial_fill_value,
+            block_version: source.block_version,
+        })
+    }
+}
+
+/// Convert RevealedTxOut --> external::RevealedTxOut.
+impl From<&RevealedTxOut> for external::RevealedTxOut {
+    fn from(source: &RevealedTxOut) -> Self {
+        let mut revealed_tx_out = external::RevealedTxOut::new();
+        revealed_tx_out.set_tx_out((&source.tx_out).into());
+        revealed_tx_out.set_shared_secret((&source.shared_secret).into());
+        revealed_tx_out
+    }
+}
+
+/// Convert external::RevealedTxOut --> RevealedTxOut.
+impl TryFrom<&external::RevealedTxOut> for RevealedTxOut {
+    type Error = ConversionError;
+
+    fn try_from(source: &external::RevealedTxOut) -> Result<Self, Self::Error> {
+        Ok(RevealedTxOut {
+            tx_out: tx::TxOut::try_from(source.get_tx_out())?,
+            shared_secret: CompressedRistrettoPublic::try_from(source.get_shared_secret())?,
         })
     }
 }
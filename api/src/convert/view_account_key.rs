@@ -0,0 +1,58 @@
+//! Convert to/from external::ViewAccountKey
+
+use crate::{convert::ConversionError, external};
+use mc_account_keys::ViewAccountKey;
+use mc_crypto_keys::{RistrettoPrivate, RistrettoPublic};
+use std::convert::TryFrom;
+
+impl From<&ViewAccountKey> for external::ViewAccountKey {
+    fn from(src: &ViewAccountKey) -> Self {
+        let mut dst = external::ViewAccountKey::new();
+        dst.set_view_private_key(external::RistrettoPrivate::from(src.view_private_key()));
+        dst.set_spend_public_key(external::CompressedRistretto::from(src.spend_public_key()));
+        dst
+    }
+}
+
+impl TryFrom<&external::ViewAccountKey> for ViewAccountKey {
+    type Error = ConversionError;
+
+    fn try_from(src: &external::ViewAccountKey) -> Result<Self, Self::Error> {
+        let view_private_key = src
+            .view_private_key
+            .as_ref()
+            .ok_or(mc_crypto_keys::KeyError::LengthMismatch(0, 32))
+            .and_then(|key| RistrettoPrivate::try_from(&key.data[..]))?;
+        let spend_public_key = RistrettoPublic::try_from(src.get_spend_public_key())?;
+
+        Ok(ViewAccountKey::new(&view_private_key, &spend_public_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_account_keys::AccountKey;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // Test converting between external::ViewAccountKey and
+    // account_keys::ViewAccountKey
+    #[test]
+    fn test_view_account_key_conversion() {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+        let view_account_key = ViewAccountKey::from(&AccountKey::random(&mut rng));
+
+        let proto = external::ViewAccountKey::from(&view_account_key);
+        assert_eq!(
+            *proto.get_view_private_key(),
+            external::RistrettoPrivate::from(view_account_key.view_private_key())
+        );
+        assert_eq!(
+            *proto.get_spend_public_key(),
+            external::CompressedRistretto::from(view_account_key.spend_public_key())
+        );
+
+        let recovered = ViewAccountKey::try_from(&proto).unwrap();
+        assert_eq!(view_account_key, recovered);
+    }
+}
@@ -67,6 +67,33 @@ impl From<Error> for ProposeTxResult {
             Error::InputRule(InputRuleError::MaxTombstoneBlockExceeded) => {
                 Self::InputRuleMaxTombstoneBlockExceeded
             }
+            Error::InputRule(InputRuleError::ExtendedRulesNotSupported) => {
+                Self::InputRuleExtendedRulesNotSupported
+            }
+            Error::InputRule(InputRuleError::BlockVersionMismatch) => {
+                Self::InputRuleBlockVersionMismatch
+            }
+            Error::InputRule(InputRuleError::MissingPartialFillChange) => {
+                Self::InputRuleMissingPartialFillChange
+            }
+            Error::InputRule(InputRuleError::MissingFractionalOutput) => {
+                Self::InputRuleMissingFractionalOutput
+            }
+            Error::InputRule(InputRuleError::InvalidRevealedAmount) => {
+                Self::InputRuleInvalidRevealedAmount
+            }
+            Error::InputRule(InputRuleError::FractionalOutputTokenIdMismatch) => {
+                Self::InputRuleFractionalOutputTokenIdMismatch
+            }
+            Error::InputRule(InputRuleError::FractionalChangeTooLarge) => {
+                Self::InputRuleFractionalChangeTooLarge
+            }
+            Error::InputRule(InputRuleError::MinPartialFillValueNotMet) => {
+                Self::InputRuleMinPartialFillValueNotMet
+            }
+            Error::InputRule(InputRuleError::FractionalOutputTooSmall) => {
+                Self::InputRuleFractionalOutputTooSmall
+            }
         }
     }
 }
@@ -123,6 +150,33 @@ impl TryInto<Error> for ProposeTxResult {
             Self::InputRuleMaxTombstoneBlockExceeded => {
                 Ok(Error::InputRule(InputRuleError::MaxTombstoneBlockExceeded))
             }
+            Self::InputRuleExtendedRulesNotSupported => {
+                Ok(Error::InputRule(InputRuleError::ExtendedRulesNotSupported))
+            }
+            Self::InputRuleBlockVersionMismatch => {
+                Ok(Error::InputRule(InputRuleError::BlockVersionMismatch))
+            }
+            Self::InputRuleMissingPartialFillChange => {
+                Ok(Error::InputRule(InputRuleError::MissingPartialFillChange))
+            }
+            Self::InputRuleMissingFractionalOutput => {
+                Ok(Error::InputRule(InputRuleError::MissingFractionalOutput))
+            }
+            Self::InputRuleInvalidRevealedAmount => {
+                Ok(Error::InputRule(InputRuleError::InvalidRevealedAmount))
+            }
+            Self::InputRuleFractionalOutputTokenIdMismatch => Ok(Error::InputRule(
+                InputRuleError::FractionalOutputTokenIdMismatch,
+            )),
+            Self::InputRuleFractionalChangeTooLarge => {
+                Ok(Error::InputRule(InputRuleError::FractionalChangeTooLarge))
+            }
+            Self::InputRuleMinPartialFillValueNotMet => {
+                Ok(Error::InputRule(InputRuleError::MinPartialFillValueNotMet))
+            }
+            Self::InputRuleFractionalOutputTooSmall => {
+                Ok(Error::InputRule(InputRuleError::FractionalOutputTooSmall))
+            }
         }
     }
 }
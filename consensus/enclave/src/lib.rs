@@ -5,7 +5,7 @@
 pub use mc_consensus_enclave_api::{
     BlockchainConfig, ConsensusEnclave, ConsensusEnclaveProxy, EnclaveCall, Error, FeeMap,
     FeeMapError, FeePublicKey, FormBlockInputs, GovernorsMap, LocallyEncryptedTx, Result,
-    TxContext, WellFormedEncryptedTx, WellFormedTxContext,
+    TxContext, WellFormedEncryptedTx, WellFormedTxContext, SMALLEST_MINIMUM_FEE_LOG2,
 };
 
 use mc_attest_core::{
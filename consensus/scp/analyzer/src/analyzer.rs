@@ -0,0 +1,538 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Quorum intersection and liveness analysis of a network of quorum sets.
+//!
+//! A quorum is a non-empty set of nodes that contains a slice of each of its
+//! members, i.e. each member's quorum set is satisfied by the other members.
+//! As in `QuorumSet::findQuorum`, a node implicitly belongs to its own slices.
+//!
+//! * The network enjoys quorum intersection if every two quorums have a node in
+//!   common. Without it, disjoint quorums can externalize different values.
+//! * A blocking set intersects every quorum. If its nodes fail, the remaining
+//!   nodes contain no quorum and the network halts.
+//! * A splitting set contains the intersection of two quorums. If its nodes are
+//!   Byzantine, they can lead the two quorums to externalize different values.
+//!
+//! Every quorum contains a minimal quorum, so all three properties are
+//! computed from the minimal quorums. Enumerating them is exponential in the
+//! worst case, but prunes well on realistic topologies.
+
+use crate::node_set::NodeSet;
+use mc_consensus_scp::{GenericNodeId, QuorumSet, QuorumSetMember};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// A quorum set over node indexes. Nodes without a known quorum set never
+/// satisfy it.
+#[derive(Clone, Debug)]
+struct IndexedQuorumSet {
+    threshold: usize,
+    nodes: Vec<Option<usize>>,
+    inner_sets: Vec<IndexedQuorumSet>,
+}
+
+impl IndexedQuorumSet {
+    fn new<ID: GenericNodeId>(quorum_set: &QuorumSet<ID>, indexes: &BTreeMap<ID, usize>) -> Self {
+        let mut nodes = Vec::new();
+        let mut inner_sets = Vec::new();
+        for member in &quorum_set.members {
+            match member {
+                QuorumSetMember::Node(id) => nodes.push(indexes.get(id).copied()),
+                QuorumSetMember::InnerSet(inner) => inner_sets.push(Self::new(inner, indexes)),
+            }
+        }
+        Self {
+            threshold: quorum_set.threshold as usize,
+            nodes,
+            inner_sets,
+        }
+    }
+
+    /// Whether the members of `set` satisfy this quorum set.
+    fn is_satisfied_by(&self, set: &NodeSet) -> bool {
+        let satisfied_nodes = self
+            .nodes
+            .iter()
+            .flatten()
+            .filter(|node| set.contains(**node))
+            .count();
+        if satisfied_nodes >= self.threshold {
+            return true;
+        }
+        let satisfied_inner_sets = self
+            .inner_sets
+            .iter()
+            .filter(|inner| inner.is_satisfied_by(set))
+            .count();
+        satisfied_nodes + satisfied_inner_sets >= self.threshold
+    }
+}
+
+/// Analyzes the quorum sets of a network.
+pub struct QuorumAnalyzer<ID: GenericNodeId> {
+    /// The nodes, by index.
+    nodes: Vec<ID>,
+
+    /// Each node's quorum set, by index.
+    quorum_sets: Vec<IndexedQuorumSet>,
+
+    /// Nodes that appear in a quorum set, but whose own quorum set is unknown.
+    unknown_nodes: BTreeSet<ID>,
+}
+
+/// The outcome of analyzing a network.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct QuorumAnalysis<ID: Ord> {
+    /// Number of nodes with a known quorum set.
+    pub num_nodes: usize,
+
+    /// Nodes that appear in a quorum set, but whose own quorum set is unknown.
+    /// They are assumed to never be available.
+    pub unknown_nodes: BTreeSet<ID>,
+
+    /// The minimal quorums.
+    pub minimal_quorums: Vec<BTreeSet<ID>>,
+
+    /// Whether every two quorums intersect.
+    pub quorum_intersection: bool,
+
+    /// Two disjoint quorums, if the network lacks quorum intersection.
+    pub disjoint_quorums: Option<(BTreeSet<ID>, BTreeSet<ID>)>,
+
+    /// The minimal sets of nodes whose failure halts the network, smallest
+    /// first.
+    pub minimal_blocking_sets: Vec<BTreeSet<ID>>,
+
+    /// The minimal sets of nodes which, if Byzantine, can split the network,
+    /// smallest first. Contains the empty set if the network lacks quorum
+    /// intersection.
+    pub minimal_splitting_sets: Vec<BTreeSet<ID>>,
+}
+
+impl<ID: GenericNodeId> QuorumAnalysis<ID> {
+    /// Whether the network is safe, i.e. enjoys quorum intersection.
+    pub fn is_safe(&self) -> bool {
+        self.quorum_intersection
+    }
+
+    /// Whether the network can make progress, i.e. has a quorum.
+    pub fn has_quorum(&self) -> bool {
+        !self.minimal_quorums.is_empty()
+    }
+
+    /// The largest number of arbitrary nodes that can fail without halting
+    /// the network.
+    pub fn liveness_fault_tolerance(&self) -> usize {
+        self.minimal_blocking_sets
+            .first()
+            .map_or(0, |set| set.len().saturating_sub(1))
+    }
+
+    /// The largest number of arbitrary nodes that can be Byzantine without
+    /// splitting the network.
+    pub fn safety_fault_tolerance(&self) -> usize {
+        self.minimal_splitting_sets
+            .first()
+            .map_or(self.num_nodes, |set| set.len().saturating_sub(1))
+    }
+}
+
+impl<ID: GenericNodeId> fmt::Display for QuorumAnalysis<ID> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let format_set = |set: &BTreeSet<ID>| -> String {
+            let ids = set.iter().map(ToString::to_string).collect::<Vec<_>>();
+            format!("{{{}}}", ids.join(", "))
+        };
+
+        writeln!(f, "Nodes: {}", self.num_nodes)?;
+        if !self.unknown_nodes.is_empty() {
+            writeln!(
+                f,
+                "Nodes without a quorum set (assumed unavailable): {}",
+                format_set(&self.unknown_nodes)
+            )?;
+        }
+
+        writeln!(f, "Minimal quorums: {}", self.minimal_quorums.len())?;
+        for quorum in &self.minimal_quorums {
+            writeln!(f, "  {}", format_set(quorum))?;
+        }
+
+        match &self.disjoint_quorums {
+            None => writeln!(f, "Quorum intersection: yes")?,
+            Some((a, b)) => writeln!(
+                f,
+                "Quorum intersection: NO, {} and {} are disjoint quorums",
+                format_set(a),
+                format_set(b)
+            )?,
+        }
+
+        writeln!(
+            f,
+            "Minimal blocking sets (liveness tolerates {} failures):",
+            self.liveness_fault_tolerance()
+        )?;
+        for set in &self.minimal_blocking_sets {
+            writeln!(f, "  {}", format_set(set))?;
+        }
+
+        writeln!(
+            f,
+            "Minimal splitting sets (safety tolerates {} Byzantine nodes):",
+            self.safety_fault_tolerance()
+        )?;
+        for set in &self.minimal_splitting_sets {
+            writeln!(f, "  {}", format_set(set))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<ID: GenericNodeId> QuorumAnalyzer<ID> {
+    /// Create an analyzer for a network, given the quorum set of each node.
+    pub fn new(quorum_sets: &BTreeMap<ID, QuorumSet<ID>>) -> Self {
+        let nodes = quorum_sets.keys().cloned().collect::<Vec<_>>();
+        let indexes = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, id)| (id.clone(), index))
+            .collect::<BTreeMap<_, _>>();
+
+        let unknown_nodes = quorum_sets
+            .values()
+            .flat_map(|quorum_set| quorum_set.nodes())
+            .filter(|id| !indexes.contains_key(id))
+            .collect();
+
+        let quorum_sets = quorum_sets
+            .values()
+            .map(|quorum_set| IndexedQuorumSet::new(quorum_set, &indexes))
+            .collect();
+
+        Self {
+            nodes,
+            quorum_sets,
+            unknown_nodes,
+        }
+    }
+
+    /// Whether `nodes` is a quorum.
+    pub fn is_quorum(&self, nodes: &BTreeSet<ID>) -> bool {
+        match self.to_node_set(nodes) {
+            Some(set) => !set.is_empty() && self.max_quorum(&set) == set,
+            None => false,
+        }
+    }
+
+    /// Analyze the network.
+    pub fn analyze(&self) -> QuorumAnalysis<ID> {
+        let minimal_quorums = self.minimal_quorums();
+
+        // Two quorums are disjoint if and only if a minimal quorum is disjoint from
+        // another quorum, i.e. the nodes outside of it contain a quorum.
+        let all = NodeSet::full(self.nodes.len());
+        let disjoint_quorums = minimal_quorums.iter().find_map(|quorum| {
+            let other = self.max_quorum(&all.difference(quorum));
+            (!other.is_empty()).then(|| (quorum.clone(), other))
+        });
+
+        let minimal_splitting_sets = if disjoint_quorums.is_some() {
+            vec![NodeSet::default()]
+        } else {
+            // A quorum is paired with itself too, so that a lone minimal quorum is only
+            // split by all of its nodes.
+            let mut intersections = Vec::new();
+            for (index, a) in minimal_quorums.iter().enumerate() {
+                for b in &minimal_quorums[index..] {
+                    intersections.push(a.intersection(b));
+                }
+            }
+            minimal_sets(intersections)
+        };
+
+        let minimal_blocking_sets = self.minimal_blocking_sets(&minimal_quorums);
+
+        QuorumAnalysis {
+            num_nodes: self.nodes.len(),
+            unknown_nodes: self.unknown_nodes.clone(),
+            quorum_intersection: disjoint_quorums.is_none(),
+            disjoint_quorums: disjoint_quorums.map(|(a, b)| (self.to_ids(&a), self.to_ids(&b))),
+            minimal_blocking_sets: self.sorted_ids(minimal_blocking_sets),
+            minimal_splitting_sets: self.sorted_ids(minimal_splitting_sets),
+            minimal_quorums: self.sorted_ids(minimal_quorums),
+        }
+    }
+
+    /// The largest quorum contained in `set`, which is empty if there is none.
+    fn max_quorum(&self, set: &NodeSet) -> NodeSet {
+        let mut set = set.clone();
+        loop {
+            let unsatisfied = set
+                .iter()
+                .filter(|node| !self.quorum_sets[*node].is_satisfied_by(&set))
+                .collect::<Vec<_>>();
+            if unsatisfied.is_empty() {
+                return set;
+            }
+            for node in unsatisfied {
+                set.remove(node);
+            }
+        }
+    }
+
+    /// Whether the quorum `quorum` has no proper subset that is a quorum.
+    fn is_minimal_quorum(&self, quorum: &NodeSet) -> bool {
+        quorum.iter().all(|node| {
+            let mut subset = quorum.clone();
+            subset.remove(node);
+            self.max_quorum(&subset).is_empty()
+        })
+    }
+
+    /// Enumerate the minimal quorums.
+    fn minimal_quorums(&self) -> Vec<NodeSet> {
+        let mut quorums = Vec::new();
+        let nodes = (0..self.nodes.len()).collect::<Vec<_>>();
+        self.find_minimal_quorums(NodeSet::default(), &nodes, &mut quorums);
+        quorums
+    }
+
+    /// Find the minimal quorums that contain `committed` and are contained in
+    /// `committed` and `remaining`.
+    fn find_minimal_quorums(
+        &self,
+        committed: NodeSet,
+        remaining: &[usize],
+        quorums: &mut Vec<NodeSet>,
+    ) {
+        // Any such quorum is contained in the largest quorum of the candidates.
+        let candidates = remaining.iter().fold(committed.clone(), |mut set, node| {
+            set.insert(*node);
+            set
+        });
+        let max_quorum = self.max_quorum(&candidates);
+        if max_quorum.is_empty() || !committed.is_subset(&max_quorum) {
+            return;
+        }
+
+        // A quorum's strict supersets are not minimal.
+        if !committed.is_empty() && self.max_quorum(&committed) == committed {
+            if self.is_minimal_quorum(&committed) {
+                quorums.push(committed);
+            }
+            return;
+        }
+
+        let (node, remaining) = match remaining.split_first() {
+            Some(split) => split,
+            None => return,
+        };
+        if max_quorum.contains(*node) {
+            let mut with_node = committed.clone();
+            with_node.insert(*node);
+            self.find_minimal_quorums(with_node, remaining, quorums);
+        }
+        self.find_minimal_quorums(committed, remaining, quorums);
+    }
+
+    /// The minimal sets of nodes that intersect every minimal quorum.
+    fn minimal_blocking_sets(&self, minimal_quorums: &[NodeSet]) -> Vec<NodeSet> {
+        let mut blocking_sets = BTreeSet::new();
+        if !minimal_quorums.is_empty() {
+            find_hitting_sets(NodeSet::default(), minimal_quorums, &mut blocking_sets);
+        }
+        minimal_sets(blocking_sets.into_iter().collect())
+    }
+
+    fn to_node_set(&self, ids: &BTreeSet<ID>) -> Option<NodeSet> {
+        ids.iter()
+            .map(|id| self.nodes.binary_search(id).ok())
+            .collect()
+    }
+
+    fn to_ids(&self, set: &NodeSet) -> BTreeSet<ID> {
+        set.iter().map(|node| self.nodes[node].clone()).collect()
+    }
+
+    /// Convert sets of node indexes to sets of IDs, smallest first.
+    fn sorted_ids(&self, sets: Vec<NodeSet>) -> Vec<BTreeSet<ID>> {
+        let mut sets = sets.iter().map(|set| self.to_ids(set)).collect::<Vec<_>>();
+        sets.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        sets
+    }
+}
+
+/// Collect the sets that contain `hitting` and a node of each of `sets`.
+fn find_hitting_sets(hitting: NodeSet, sets: &[NodeSet], found: &mut BTreeSet<NodeSet>) {
+    // Branch on the nodes of the smallest set that is not hit yet.
+    match sets
+        .iter()
+        .filter(|set| !set.intersects(&hitting))
+        .min_by_key(|set| set.len())
+    {
+        None => {
+            found.insert(hitting);
+        }
+        Some(set) => {
+            for node in set.iter() {
+                let mut with_node = hitting.clone();
+                with_node.insert(node);
+                // Supersets of a hitting set are not minimal.
+                if !found.iter().any(|other| other.is_subset(&with_node)) {
+                    find_hitting_sets(with_node, sets, found);
+                }
+            }
+        }
+    }
+}
+
+/// Keep the sets that do not contain another set, without duplicates.
+fn minimal_sets(mut sets: Vec<NodeSet>) -> Vec<NodeSet> {
+    sets.sort_by_key(NodeSet::len);
+    let mut minimal: Vec<NodeSet> = Vec::new();
+    for set in sets {
+        if !minimal.iter().any(|other| other.is_subset(&set)) {
+            minimal.push(set);
+        }
+    }
+    minimal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ids: &[u32]) -> BTreeSet<u32> {
+        ids.iter().copied().collect()
+    }
+
+    /// `n` nodes, each requiring `threshold` of the others.
+    fn mesh(n: u32, threshold: u32) -> BTreeMap<u32, QuorumSet<u32>> {
+        (0..n)
+            .map(|id| {
+                let peers = (0..n).filter(|peer| *peer != id).collect();
+                (id, QuorumSet::new_with_node_ids(threshold, peers))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mesh_enjoys_quorum_intersection() {
+        let analysis = QuorumAnalyzer::new(&mesh(4, 2)).analyze();
+
+        assert!(analysis.is_safe());
+        assert!(analysis.has_quorum());
+        assert_eq!(
+            analysis.minimal_quorums,
+            vec![
+                set(&[0, 1, 2]),
+                set(&[0, 1, 3]),
+                set(&[0, 2, 3]),
+                set(&[1, 2, 3])
+            ]
+        );
+        assert_eq!(analysis.minimal_blocking_sets.len(), 6);
+        assert!(analysis.minimal_blocking_sets.iter().all(|s| s.len() == 2));
+        assert_eq!(analysis.minimal_splitting_sets.len(), 6);
+        assert!(analysis.minimal_splitting_sets.iter().all(|s| s.len() == 2));
+        assert_eq!(analysis.liveness_fault_tolerance(), 1);
+        assert_eq!(analysis.safety_fault_tolerance(), 1);
+    }
+
+    #[test]
+    fn single_quorum_is_split_only_by_all_its_nodes() {
+        let analysis = QuorumAnalyzer::new(&mesh(3, 2)).analyze();
+
+        assert!(analysis.is_safe());
+        assert_eq!(analysis.minimal_quorums, vec![set(&[0, 1, 2])]);
+        assert_eq!(analysis.minimal_splitting_sets, vec![set(&[0, 1, 2])]);
+        assert_eq!(analysis.safety_fault_tolerance(), 2);
+        assert_eq!(analysis.liveness_fault_tolerance(), 0);
+    }
+
+    #[test]
+    fn detects_disjoint_quorums() {
+        let quorum_sets = BTreeMap::from([
+            (0, QuorumSet::new_with_node_ids(1, vec![1])),
+            (1, QuorumSet::new_with_node_ids(1, vec![0])),
+            (2, QuorumSet::new_with_node_ids(1, vec![3])),
+            (3, QuorumSet::new_with_node_ids(1, vec![2])),
+        ]);
+        let analysis = QuorumAnalyzer::new(&quorum_sets).analyze();
+
+        assert!(!analysis.is_safe());
+        assert_eq!(
+            analysis.disjoint_quorums,
+            Some((set(&[0, 1]), set(&[2, 3])))
+        );
+        assert_eq!(analysis.minimal_splitting_sets, vec![set(&[])]);
+        assert_eq!(analysis.safety_fault_tolerance(), 0);
+        assert_eq!(
+            analysis.minimal_blocking_sets,
+            vec![set(&[0, 2]), set(&[0, 3]), set(&[1, 2]), set(&[1, 3])]
+        );
+    }
+
+    #[test]
+    fn analyzes_tiered_quorum_sets() {
+        // Nodes 0-2 form a core that requires 2 of its other members. Nodes 3 and 4
+        // require 2 of the core.
+        let core =
+            |id: u32| QuorumSet::new_with_node_ids(2, (0..3).filter(|peer| *peer != id).collect());
+        let edge =
+            QuorumSet::new_with_inner_sets(1, vec![QuorumSet::new_with_node_ids(2, vec![0, 1, 2])]);
+        let quorum_sets = BTreeMap::from([
+            (0, core(0)),
+            (1, core(1)),
+            (2, core(2)),
+            (3, edge.clone()),
+            (4, edge),
+        ]);
+        let analyzer = QuorumAnalyzer::new(&quorum_sets);
+        let analysis = analyzer.analyze();
+
+        assert!(analysis.is_safe());
+        assert_eq!(analysis.minimal_quorums, vec![set(&[0, 1, 2])]);
+        assert_eq!(
+            analysis.minimal_blocking_sets,
+            vec![set(&[0]), set(&[1]), set(&[2])]
+        );
+        assert_eq!(analysis.minimal_splitting_sets, vec![set(&[0, 1, 2])]);
+        assert_eq!(analysis.safety_fault_tolerance(), 2);
+        assert!(analyzer.is_quorum(&set(&[0, 1, 2, 3])));
+        assert!(!analyzer.is_quorum(&set(&[0, 1, 3])));
+        assert!(!analyzer.is_quorum(&set(&[])));
+    }
+
+    #[test]
+    fn reports_unknown_nodes() {
+        let quorum_sets = BTreeMap::from([
+            (0, QuorumSet::new_with_node_ids(1, vec![1, 9])),
+            (1, QuorumSet::new_with_node_ids(1, vec![0, 9])),
+        ]);
+        let analysis = QuorumAnalyzer::new(&quorum_sets).analyze();
+
+        assert_eq!(analysis.unknown_nodes, set(&[9]));
+        assert_eq!(analysis.minimal_quorums, vec![set(&[0, 1])]);
+        assert!(analysis.is_safe());
+    }
+
+    #[test]
+    fn network_without_quorum_halts() {
+        let quorum_sets = BTreeMap::from([
+            (0, QuorumSet::new_with_node_ids(2, vec![1, 9])),
+            (1, QuorumSet::new_with_node_ids(2, vec![0, 9])),
+        ]);
+        let analysis = QuorumAnalyzer::new(&quorum_sets).analyze();
+
+        assert!(!analysis.has_quorum());
+        assert!(analysis.is_safe());
+        assert!(analysis.minimal_blocking_sets.is_empty());
+        assert_eq!(analysis.liveness_fault_tolerance(), 0);
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+#![deny(missing_docs)]
+
+//! Checks the quorum sets of a consensus network for quorum intersection, and
+//! lists the sets of nodes that can halt or split it.
+
+use clap::Parser;
+use mc_common::ResponderId;
+use mc_consensus_scp::QuorumSet;
+use mc_consensus_scp_analyzer::QuorumAnalyzer;
+use mc_consensus_service_config::NetworkConfig;
+use std::{collections::BTreeMap, fs, path::PathBuf, process::exit, str::FromStr};
+
+/// Configurable options.
+#[derive(Debug, Parser)]
+pub struct Config {
+    /// The network configuration (network.toml or network.json) of a node,
+    /// given as `<responder id>=<path>`. Repeat for every node.
+    #[clap(
+        long = "network",
+        parse(try_from_str = parse_network_config),
+        use_value_delimiter = true,
+        env = "MC_NETWORK"
+    )]
+    pub networks: Vec<(ResponderId, PathBuf)>,
+
+    /// A JSON object mapping responder IDs to quorum sets, used in addition to
+    /// `--network`.
+    #[clap(long, parse(from_os_str), env = "MC_QUORUM_SETS")]
+    pub quorum_sets: Option<PathBuf>,
+
+    /// Print the analysis as JSON.
+    #[clap(long, env = "MC_JSON")]
+    pub json: bool,
+
+    /// Fail unless the network tolerates this many Byzantine nodes without
+    /// losing quorum intersection.
+    #[clap(long, default_value = "0", env = "MC_MIN_SAFETY_TOLERANCE")]
+    pub min_safety_tolerance: usize,
+
+    /// Fail unless the network tolerates this many failed nodes without
+    /// halting.
+    #[clap(long, default_value = "0", env = "MC_MIN_LIVENESS_TOLERANCE")]
+    pub min_liveness_tolerance: usize,
+}
+
+fn parse_network_config(src: &str) -> Result<(ResponderId, PathBuf), String> {
+    let (responder_id, path) = src
+        .split_once('=')
+        .ok_or_else(|| format!("Expected <responder id>=<path>, got {}", src))?;
+    let responder_id = ResponderId::from_str(responder_id)
+        .map_err(|err| format!("Invalid responder id {}: {}", responder_id, err))?;
+    Ok((responder_id, PathBuf::from(path)))
+}
+
+fn load_quorum_sets(
+    config: &Config,
+) -> Result<BTreeMap<ResponderId, QuorumSet<ResponderId>>, String> {
+    let mut quorum_sets = match &config.quorum_sets {
+        Some(path) => {
+            let data = fs::read_to_string(path)
+                .map_err(|err| format!("Failed reading {:?}: {}", path, err))?;
+            serde_json::from_str(&data)
+                .map_err(|err| format!("Failed parsing {:?}: {}", path, err))?
+        }
+        None => BTreeMap::new(),
+    };
+
+    for (responder_id, path) in &config.networks {
+        let network = NetworkConfig::load_from_path(path, responder_id)
+            .map_err(|err| format!("Failed loading {:?}: {}", path, err))?;
+        if quorum_sets
+            .insert(responder_id.clone(), network.quorum_set)
+            .is_some()
+        {
+            return Err(format!("Duplicate quorum set for {}", responder_id));
+        }
+    }
+
+    if quorum_sets.is_empty() {
+        return Err("No quorum sets given, use --network or --quorum-sets".to_string());
+    }
+
+    for (responder_id, quorum_set) in &quorum_sets {
+        if !quorum_set.is_valid() {
+            return Err(format!("Invalid quorum set for {}", responder_id));
+        }
+    }
+
+    Ok(quorum_sets)
+}
+
+fn main() {
+    let config = Config::parse();
+
+    let quorum_sets = load_quorum_sets(&config).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        exit(2);
+    });
+
+    let analysis = QuorumAnalyzer::new(&quorum_sets).analyze();
+
+    if config.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&analysis).expect("failed serializing analysis")
+        );
+    } else {
+        print!("{}", analysis);
+    }
+
+    let mut failures = Vec::new();
+    if !analysis.is_safe() {
+        failures.push("the network does not enjoy quorum intersection".to_string());
+    }
+    if !analysis.has_quorum() {
+        failures.push("the network has no quorum".to_string());
+    }
+    if analysis.is_safe() && analysis.safety_fault_tolerance() < config.min_safety_tolerance {
+        failures.push(format!(
+            "the network tolerates only {} Byzantine nodes",
+            analysis.safety_fault_tolerance()
+        ));
+    }
+    if analysis.has_quorum() && analysis.liveness_fault_tolerance() < config.min_liveness_tolerance
+    {
+        failures.push(format!(
+            "the network tolerates only {} failed nodes",
+            analysis.liveness_fault_tolerance()
+        ));
+    }
+
+    if !failures.is_empty() {
+        for failure in failures {
+            eprintln!("UNSAFE CONFIGURATION: {}", failure);
+        }
+        exit(1);
+    }
+}
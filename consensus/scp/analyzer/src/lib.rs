@@ -0,0 +1,10 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+#![doc = include_str!("../README.md")]
+#![deny(missing_docs)]
+
+pub mod analyzer;
+
+mod node_set;
+
+pub use crate::analyzer::{QuorumAnalysis, QuorumAnalyzer};
@@ -0,0 +1,157 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A set of node indexes, stored as a bit set.
+
+/// A set of node indexes.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NodeSet {
+    words: Vec<u64>,
+}
+
+impl NodeSet {
+    /// The set of nodes `0..num_nodes`.
+    pub fn full(num_nodes: usize) -> Self {
+        let mut set = Self::default();
+        for node in 0..num_nodes {
+            set.insert(node);
+        }
+        set
+    }
+
+    /// Add a node to the set.
+    pub fn insert(&mut self, node: usize) {
+        let word = node / 64;
+        if self.words.len() <= word {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1 << (node % 64);
+    }
+
+    /// Remove a node from the set.
+    pub fn remove(&mut self, node: usize) {
+        if let Some(word) = self.words.get_mut(node / 64) {
+            *word &= !(1 << (node % 64));
+        }
+        self.trim();
+    }
+
+    /// Whether the set contains a node.
+    pub fn contains(&self, node: usize) -> bool {
+        self.words
+            .get(node / 64)
+            .map_or(false, |word| word & (1 << (node % 64)) != 0)
+    }
+
+    /// Number of nodes in the set.
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Whether every node of this set is in `other`.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words
+            .iter()
+            .enumerate()
+            .all(|(index, word)| word & !other.words.get(index).copied().unwrap_or_default() == 0)
+    }
+
+    /// Whether this set and `other` have a node in common.
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .any(|(word, other)| word & other != 0)
+    }
+
+    /// The nodes in both this set and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut set = Self {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(word, other)| word & other)
+                .collect(),
+        };
+        set.trim();
+        set
+    }
+
+    /// The nodes in this set that are not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut set = Self {
+            words: self
+                .words
+                .iter()
+                .enumerate()
+                .map(|(index, word)| word & !other.words.get(index).copied().unwrap_or_default())
+                .collect(),
+        };
+        set.trim();
+        set
+    }
+
+    /// The nodes in the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, word)| {
+            (0..64usize)
+                .filter(move |bit| word & (1u64 << bit) != 0)
+                .map(move |bit| index * 64 + bit)
+        })
+    }
+
+    /// Drop trailing empty words, so that equal sets have equal
+    /// representations.
+    fn trim(&mut self) {
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
+}
+
+impl FromIterator<usize> for NodeSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(nodes: I) -> Self {
+        let mut set = Self::default();
+        for node in nodes {
+            set.insert(node);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_operations() {
+        let a = NodeSet::from_iter([1, 3, 70]);
+        let b = NodeSet::from_iter([3, 70, 130]);
+
+        assert_eq!(a.len(), 3);
+        assert!(a.contains(70));
+        assert!(!a.contains(130));
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![1, 3, 70]);
+
+        assert!(a.intersects(&b));
+        assert_eq!(a.intersection(&b), NodeSet::from_iter([3, 70]));
+        assert_eq!(a.difference(&b), NodeSet::from_iter([1]));
+        assert_eq!(b.difference(&a), NodeSet::from_iter([130]));
+        assert!(a.intersection(&b).is_subset(&a));
+        assert!(!a.is_subset(&b));
+
+        let mut c = b.clone();
+        c.remove(130);
+        assert_eq!(c, NodeSet::from_iter([3, 70]));
+        assert!(NodeSet::default().is_empty());
+        assert!(a.difference(&NodeSet::full(71)).is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+#![deny(missing_docs)]
+
+//! Runs an SCP simulation described by a JSON configuration file, and prints
+//! its report as JSON.
+
+use clap::Parser;
+use mc_common::logger::{create_app_logger, log, o};
+use mc_consensus_scp_simulator::{replay, Simulation, SimulationConfig, Trace};
+use std::{fs, path::PathBuf, process::exit};
+
+/// Configurable options.
+#[derive(Debug, Parser)]
+pub struct Config {
+    /// Path to the simulation configuration, in JSON.
+    #[clap(long, parse(from_os_str), env = "MC_SIMULATION_CONFIG")]
+    pub config: PathBuf,
+
+    /// Override the seed of the configuration.
+    #[clap(long, env = "MC_SEED")]
+    pub seed: Option<u64>,
+
+    /// Write the trace of the run to this path, in JSON.
+    #[clap(long, parse(from_os_str), env = "MC_TRACE_OUT")]
+    pub trace_out: Option<PathBuf>,
+
+    /// Replay the trace at this path instead of running a new simulation.
+    #[clap(
+        long,
+        parse(from_os_str),
+        env = "MC_REPLAY",
+        conflicts_with = "trace-out"
+    )]
+    pub replay: Option<PathBuf>,
+}
+
+fn main() {
+    let (logger, _global_logger_guard) = create_app_logger(o!());
+    let config = Config::parse();
+
+    let mut sim_config: SimulationConfig = serde_json::from_slice(
+        &fs::read(&config.config).expect("failed reading simulation config"),
+    )
+    .expect("failed parsing simulation config");
+    if let Some(seed) = config.seed {
+        sim_config.seed = seed;
+    }
+
+    let report = if let Some(path) = &config.replay {
+        let trace: Trace = serde_json::from_slice(&fs::read(path).expect("failed reading trace"))
+            .expect("failed parsing trace");
+        replay(&sim_config, &trace, logger.clone())
+    } else {
+        Simulation::new(sim_config, logger.clone()).map(|simulation| {
+            let (report, trace) = simulation.run();
+            if let Some(path) = &config.trace_out {
+                fs::write(
+                    path,
+                    serde_json::to_vec(&trace).expect("failed serializing trace"),
+                )
+                .expect("failed writing trace");
+            }
+            report
+        })
+    };
+
+    let report = match report {
+        Ok(report) => report,
+        Err(err) => {
+            log::crit!(logger, "Simulation failed: {}", err);
+            exit(1);
+        }
+    };
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report).expect("failed serializing report")
+    );
+
+    if !report.is_safe() {
+        log::error!(
+            logger,
+            "{} safety violations",
+            report.safety_violations.len()
+        );
+        exit(2);
+    }
+    if !report.is_live() {
+        log::warn!(logger, "Nodes {:?} stalled", report.liveness.stalled_nodes);
+    }
+}
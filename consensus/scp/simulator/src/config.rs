@@ -0,0 +1,258 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Simulation parameters.
+
+use mc_consensus_scp::QuorumSet;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Simulated nodes are identified by a small integer. Node `i` uses
+/// `test_utils::test_node_id(i)` as its SCP node ID.
+pub type SimNodeId = u32;
+
+/// The parameters of a simulation run.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SimulationConfig {
+    /// Seed of the random number generator. Runs with the same configuration
+    /// and seed are identical.
+    #[serde(default)]
+    pub seed: u64,
+
+    /// The simulated nodes.
+    pub nodes: Vec<NodeConfig>,
+
+    /// Message delay, loss and reordering.
+    #[serde(default)]
+    pub network: NetworkConfig,
+
+    /// Network partitions, which may overlap in time.
+    #[serde(default)]
+    pub partitions: Vec<Partition>,
+
+    /// Number of slots every honest node should externalize.
+    #[serde(default = "default_slots")]
+    pub slots: u64,
+
+    /// Maximum number of values externalized in a slot.
+    #[serde(default = "default_max_values_per_slot")]
+    pub max_values_per_slot: usize,
+
+    /// Interval at which nodes process SCP timeouts, in milliseconds.
+    #[serde(default = "default_timer_interval_ms")]
+    pub timer_interval_ms: u64,
+
+    /// Interval at which nodes retransmit their latest message, and send
+    /// externalize messages to peers that are behind, in milliseconds.
+    #[serde(default = "default_retransmit_interval_ms")]
+    pub retransmit_interval_ms: u64,
+
+    /// The run ends after this much virtual time, in milliseconds, even if not
+    /// every honest node externalized `slots` slots.
+    #[serde(default = "default_max_duration_ms")]
+    pub max_duration_ms: u64,
+}
+
+/// A simulated node.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NodeConfig {
+    /// The node's ID.
+    pub id: SimNodeId,
+
+    /// The node's quorum set, in terms of simulated node IDs.
+    pub quorum_set: QuorumSet<SimNodeId>,
+
+    /// The nodes this node sends messages to. Defaults to every other node.
+    #[serde(default)]
+    pub peers: Option<Vec<SimNodeId>>,
+
+    /// How the node behaves.
+    #[serde(default)]
+    pub behavior: Behavior,
+}
+
+/// How a simulated node behaves.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Behavior {
+    /// Follows the protocol.
+    Honest,
+
+    /// Never sends any message, e.g. because it crashed.
+    Silent,
+
+    /// Runs two independent instances of the protocol under the same node ID,
+    /// each proposing different values. Half of the node's peers receive the
+    /// messages of one instance, the other half those of the other.
+    Equivocating,
+}
+
+impl Default for Behavior {
+    fn default() -> Self {
+        Self::Honest
+    }
+}
+
+/// Message delay, loss and reordering.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Minimum delay of a message, in milliseconds.
+    pub min_delay_ms: u64,
+
+    /// Maximum delay of a message, in milliseconds.
+    pub max_delay_ms: u64,
+
+    /// Probability that a message is lost.
+    pub loss_probability: f64,
+
+    /// Probability that a message is held back by `reorder_delay_ms`, so that
+    /// messages sent after it overtake it.
+    pub reorder_probability: f64,
+
+    /// Extra delay of reordered messages, in milliseconds.
+    pub reorder_delay_ms: u64,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            min_delay_ms: 10,
+            max_delay_ms: 50,
+            loss_probability: 0.0,
+            reorder_probability: 0.0,
+            reorder_delay_ms: 200,
+        }
+    }
+}
+
+/// A network partition. While it is active, messages between nodes in
+/// different groups are dropped. Nodes not listed in any group form a group of
+/// their own.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Partition {
+    /// Start of the partition, in milliseconds.
+    pub start_ms: u64,
+
+    /// End of the partition, in milliseconds (exclusive).
+    pub end_ms: u64,
+
+    /// The groups of nodes that can communicate with each other.
+    pub groups: Vec<Vec<SimNodeId>>,
+}
+
+impl Partition {
+    /// Whether this partition drops a message from `from` to `to` sent at
+    /// `now_ms`.
+    pub fn separates(&self, from: SimNodeId, to: SimNodeId, now_ms: u64) -> bool {
+        if now_ms < self.start_ms || now_ms >= self.end_ms {
+            return false;
+        }
+        let group_of = |node| self.groups.iter().position(|group| group.contains(&node));
+        group_of(from) != group_of(to)
+    }
+}
+
+fn default_slots() -> u64 {
+    10
+}
+
+fn default_max_values_per_slot() -> usize {
+    100
+}
+
+fn default_timer_interval_ms() -> u64 {
+    100
+}
+
+fn default_retransmit_interval_ms() -> u64 {
+    1000
+}
+
+fn default_max_duration_ms() -> u64 {
+    10 * 60 * 1000
+}
+
+impl SimulationConfig {
+    /// A configuration of honest nodes `0..num_nodes`, each sending messages to
+    /// every other node, and requiring `threshold` of them to agree.
+    pub fn mesh(num_nodes: u32, threshold: u32) -> Self {
+        let nodes = (0..num_nodes)
+            .map(|id| NodeConfig {
+                id,
+                quorum_set: QuorumSet::new_with_node_ids(
+                    threshold,
+                    (0..num_nodes).filter(|peer| *peer != id).collect(),
+                ),
+                peers: None,
+                behavior: Behavior::Honest,
+            })
+            .collect();
+
+        Self {
+            seed: 0,
+            nodes,
+            network: NetworkConfig::default(),
+            partitions: Vec::new(),
+            slots: default_slots(),
+            max_values_per_slot: default_max_values_per_slot(),
+            timer_interval_ms: default_timer_interval_ms(),
+            retransmit_interval_ms: default_retransmit_interval_ms(),
+            max_duration_ms: default_max_duration_ms(),
+        }
+    }
+
+    /// Check that the configuration is consistent.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut ids = HashSet::new();
+        for node in &self.nodes {
+            if !ids.insert(node.id) {
+                return Err(format!("Duplicate node {}", node.id));
+            }
+        }
+
+        for node in &self.nodes {
+            if !node.quorum_set.is_valid() {
+                return Err(format!("Node {} has an invalid quorum set", node.id));
+            }
+            if let Some(unknown) = node.quorum_set.nodes().iter().find(|id| !ids.contains(*id)) {
+                return Err(format!("Node {} trusts unknown node {}", node.id, unknown));
+            }
+            if let Some(unknown) = node
+                .peers
+                .iter()
+                .flatten()
+                .find(|id| !ids.contains(*id) || **id == node.id)
+            {
+                return Err(format!("Node {} has invalid peer {}", node.id, unknown));
+            }
+        }
+
+        if !self
+            .nodes
+            .iter()
+            .any(|node| node.behavior == Behavior::Honest)
+        {
+            return Err("At least one node must be honest".to_string());
+        }
+
+        if self.network.min_delay_ms > self.network.max_delay_ms {
+            return Err("min_delay_ms must not exceed max_delay_ms".to_string());
+        }
+        for probability in [
+            self.network.loss_probability,
+            self.network.reorder_probability,
+        ] {
+            if !(0.0..=1.0).contains(&probability) {
+                return Err(format!("Invalid probability {}", probability));
+            }
+        }
+
+        if self.timer_interval_ms == 0 || self.retransmit_interval_ms == 0 {
+            return Err("Timer intervals must be positive".to_string());
+        }
+        if self.max_values_per_slot == 0 {
+            return Err("max_values_per_slot must be positive".to_string());
+        }
+
+        Ok(())
+    }
+}
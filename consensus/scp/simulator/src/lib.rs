@@ -0,0 +1,18 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+#![doc = include_str!("../README.md")]
+#![deny(missing_docs)]
+
+pub mod config;
+pub mod report;
+pub mod simulation;
+pub mod trace;
+
+mod participant;
+
+pub use crate::{
+    config::{Behavior, NetworkConfig, NodeConfig, Partition, SimNodeId, SimulationConfig},
+    report::{SafetyViolation, SimulationReport},
+    simulation::{replay, Simulation},
+    trace::{Trace, TraceEvent, TraceInput},
+};
@@ -0,0 +1,301 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A simulated node, wrapping one or more SCP node instances.
+
+use crate::{
+    config::{Behavior, NodeConfig, SimNodeId},
+    report::ExternalizedSlot,
+    trace::TraceInput,
+};
+use mc_common::{logger::Logger, NodeID};
+use mc_consensus_scp::{
+    test_utils::{
+        get_bounded_combine_fn, test_node_id, trivial_validity_fn, TransactionValidationError,
+    },
+    ClockFn, Msg, Node, QuorumSet, QuorumSetMember, ScpNode, SlotIndex, Topic,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
+};
+
+/// An SCP node instance. Honest nodes have one, equivocating nodes two.
+struct Instance {
+    /// The SCP node.
+    node: Node<String, TransactionValidationError>,
+
+    /// Suffix distinguishing the values proposed by this instance.
+    value_suffix: &'static str,
+
+    /// The peers this instance sends messages to.
+    recipients: Vec<SimNodeId>,
+
+    /// The latest message emitted for the current slot.
+    last_msg: Option<Msg<String>>,
+
+    /// Externalize messages emitted by this instance, by slot.
+    externalize_msgs: BTreeMap<SlotIndex, Msg<String>>,
+}
+
+/// A message to send to some peers.
+pub struct Outgoing {
+    /// The peers to send the message to.
+    pub recipients: Vec<SimNodeId>,
+
+    /// The message.
+    pub msg: Msg<String>,
+}
+
+/// A simulated node.
+pub struct Participant {
+    /// The simulated node ID.
+    pub id: SimNodeId,
+
+    /// How the node behaves.
+    pub behavior: Behavior,
+
+    /// The node's protocol instances. Empty for silent nodes.
+    instances: Vec<Instance>,
+
+    /// The highest slot index seen in messages from each peer.
+    peer_slots: HashMap<SimNodeId, SlotIndex>,
+
+    /// Values externalized by the first instance, by slot.
+    pub externalized: BTreeMap<SlotIndex, ExternalizedSlot>,
+
+    /// Number of slots the node should externalize.
+    target_slots: u64,
+}
+
+/// Convert a quorum set over simulated node IDs to one over SCP node IDs.
+pub fn to_node_id_quorum_set(quorum_set: &QuorumSet<SimNodeId>) -> QuorumSet {
+    QuorumSet::new(
+        quorum_set.threshold,
+        quorum_set
+            .members
+            .iter()
+            .map(|member| match member {
+                QuorumSetMember::Node(id) => QuorumSetMember::Node(test_node_id(*id)),
+                QuorumSetMember::InnerSet(inner) => {
+                    QuorumSetMember::InnerSet(to_node_id_quorum_set(inner))
+                }
+            })
+            .collect(),
+    )
+}
+
+impl Participant {
+    /// Create a simulated node.
+    ///
+    /// # Arguments
+    /// * `config` - The node's configuration.
+    /// * `peers` - The nodes this node sends messages to.
+    /// * `target_slots` - Number of slots the node should externalize.
+    /// * `max_values_per_slot` - Maximum number of values in a slot.
+    /// * `clock` - The virtual clock.
+    /// * `logger`
+    pub fn new(
+        config: &NodeConfig,
+        peers: Vec<SimNodeId>,
+        target_slots: u64,
+        max_values_per_slot: usize,
+        clock: ClockFn,
+        logger: Logger,
+    ) -> Self {
+        let value_suffixes: &[&'static str] = match config.behavior {
+            Behavior::Honest => &[""],
+            Behavior::Silent => &[],
+            Behavior::Equivocating => &["-a", "-b"],
+        };
+
+        let node_id: NodeID = test_node_id(config.id);
+        let quorum_set = to_node_id_quorum_set(&config.quorum_set);
+        let num_instances = value_suffixes.len();
+
+        let instances = value_suffixes
+            .iter()
+            .enumerate()
+            .map(|(index, value_suffix)| {
+                let node = Node::new_with_clock(
+                    node_id.clone(),
+                    quorum_set.clone(),
+                    Arc::new(trivial_validity_fn::<String>),
+                    Arc::new(get_bounded_combine_fn::<String>(max_values_per_slot)),
+                    0,
+                    clock.clone(),
+                    logger.clone(),
+                );
+                // Instance `i` of `n` sends to every `n`-th peer, starting with the `i`-th.
+                let recipients = peers
+                    .iter()
+                    .skip(index)
+                    .step_by(num_instances)
+                    .copied()
+                    .collect();
+                Instance {
+                    node,
+                    value_suffix,
+                    recipients,
+                    last_msg: None,
+                    externalize_msgs: BTreeMap::new(),
+                }
+            })
+            .collect();
+
+        Self {
+            id: config.id,
+            behavior: config.behavior,
+            instances,
+            peer_slots: HashMap::new(),
+            externalized: BTreeMap::new(),
+            target_slots,
+        }
+    }
+
+    /// Whether the node externalized its target number of slots.
+    pub fn is_done(&self) -> bool {
+        self.externalized.len() as u64 >= self.target_slots
+    }
+
+    /// Number of protocol instances run by the node.
+    pub fn num_instances(&self) -> usize {
+        self.instances.len()
+    }
+
+    /// The values an instance should propose for its current slot, if any.
+    pub fn next_proposal(&self, index: usize) -> Option<TraceInput> {
+        let instance = &self.instances[index];
+        let slot_index = instance.node.current_slot_index();
+        if slot_index >= self.target_slots {
+            return None;
+        }
+        Some(TraceInput::Propose {
+            instance: index,
+            slot_index,
+            values: BTreeSet::from([format!(
+                "n{}-s{}{}",
+                self.id, slot_index, instance.value_suffix
+            )]),
+        })
+    }
+
+    /// Apply an input to the node at virtual time `now_ms`. Returns the
+    /// messages to send, and the instances that advanced to a new slot.
+    pub fn handle(&mut self, input: &TraceInput, now_ms: u64) -> HandleResult {
+        let mut result = HandleResult::default();
+
+        if let TraceInput::Deliver { from, msg } = input {
+            let peer_slot = self.peer_slots.entry(*from).or_default();
+            *peer_slot = (*peer_slot).max(msg.slot_index);
+        }
+
+        for (index, instance) in self.instances.iter_mut().enumerate() {
+            let slot_before = instance.node.current_slot_index();
+
+            let msgs = match input {
+                TraceInput::Propose {
+                    instance: target,
+                    slot_index,
+                    values,
+                } => {
+                    // A proposal for a slot the instance already externalized is stale.
+                    if *target != index || *slot_index != slot_before {
+                        continue;
+                    }
+                    instance
+                        .node
+                        .propose_values(values.clone())
+                        .map(|msg| msg.into_iter().collect())
+                }
+                TraceInput::Deliver { msg, .. } => instance
+                    .node
+                    .handle_message(msg)
+                    .map(|msg| msg.into_iter().collect()),
+                TraceInput::Timeout => Ok(instance.node.process_timeouts()),
+            };
+
+            let msgs: Vec<Msg<String>> = match msgs {
+                Ok(msgs) => msgs,
+                Err(err) => {
+                    result.errors.push(err);
+                    continue;
+                }
+            };
+
+            for msg in msgs {
+                let is_externalize = matches!(msg.topic, Topic::Externalize(_));
+                if is_externalize {
+                    instance
+                        .externalize_msgs
+                        .insert(msg.slot_index, msg.clone());
+                }
+                // Responses to messages for an earlier slot are not retransmitted.
+                if is_externalize || msg.slot_index == instance.node.current_slot_index() {
+                    instance.last_msg = Some(msg.clone());
+                }
+                result.outgoing.push(Outgoing {
+                    recipients: instance.recipients.clone(),
+                    msg,
+                });
+            }
+
+            if instance.node.current_slot_index() > slot_before {
+                result.advanced.push(index);
+                // Only the first instance is recorded, since only honest nodes are
+                // checked for divergence.
+                if index == 0 {
+                    let values = instance
+                        .node
+                        .get_externalized_values(slot_before)
+                        .expect("The slot that was just externalized is stored");
+                    self.externalized.insert(
+                        slot_before,
+                        ExternalizedSlot {
+                            values,
+                            at_ms: now_ms,
+                        },
+                    );
+                }
+            }
+        }
+
+        result
+    }
+
+    /// The messages to retransmit: the latest message of each instance, or the
+    /// externalize message of a slot a peer is still working on.
+    pub fn retransmissions(&self) -> Vec<Outgoing> {
+        let mut outgoing = Vec::new();
+        for instance in &self.instances {
+            let current_slot = instance.node.current_slot_index();
+            for peer in &instance.recipients {
+                let msg = self
+                    .peer_slots
+                    .get(peer)
+                    .filter(|peer_slot| **peer_slot < current_slot)
+                    .and_then(|peer_slot| instance.externalize_msgs.get(peer_slot))
+                    .or(instance.last_msg.as_ref());
+                if let Some(msg) = msg {
+                    outgoing.push(Outgoing {
+                        recipients: vec![*peer],
+                        msg: msg.clone(),
+                    });
+                }
+            }
+        }
+        outgoing
+    }
+}
+
+/// The outcome of applying an input to a node.
+#[derive(Default)]
+pub struct HandleResult {
+    /// Messages to send.
+    pub outgoing: Vec<Outgoing>,
+
+    /// Instances that advanced to a new slot.
+    pub advanced: Vec<usize>,
+
+    /// Errors of instances that failed to handle the input.
+    pub errors: Vec<String>,
+}
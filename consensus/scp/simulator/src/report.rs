@@ -0,0 +1,261 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! The outcome of a simulation run.
+
+use crate::config::SimNodeId;
+use mc_consensus_scp::SlotIndex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Values externalized by a node in a slot.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ExternalizedSlot {
+    /// The externalized values.
+    pub values: Vec<String>,
+
+    /// Virtual time at which the node externalized, in milliseconds.
+    pub at_ms: u64,
+}
+
+/// Two honest nodes externalized different values in the same slot.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SafetyViolation {
+    /// The slot.
+    pub slot_index: SlotIndex,
+
+    /// The first node.
+    pub node_a: SimNodeId,
+
+    /// The values externalized by the first node.
+    pub values_a: Vec<String>,
+
+    /// The second node.
+    pub node_b: SimNodeId,
+
+    /// The values externalized by the second node.
+    pub values_b: Vec<String>,
+}
+
+/// How long honest nodes took to externalize a slot.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SlotLatency {
+    /// The slot.
+    pub slot_index: SlotIndex,
+
+    /// Time at which the first honest node externalized, in milliseconds.
+    pub first_ms: u64,
+
+    /// Time at which the last honest node externalized, in milliseconds, if
+    /// they all did.
+    pub last_ms: Option<u64>,
+}
+
+/// Progress of the honest nodes.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LivenessMetrics {
+    /// Number of slots every honest node should have externalized.
+    pub target_slots: u64,
+
+    /// Number of slots externalized by every honest node.
+    pub slots_externalized_by_all: u64,
+
+    /// Honest nodes that did not externalize `target_slots` slots.
+    pub stalled_nodes: Vec<SimNodeId>,
+
+    /// Externalization times of each slot, in increasing slot order.
+    pub slot_latencies: Vec<SlotLatency>,
+}
+
+/// Message counts.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MessageMetrics {
+    /// Messages sent, including retransmissions.
+    pub sent: u64,
+
+    /// Messages delivered.
+    pub delivered: u64,
+
+    /// Messages dropped by the lossy network.
+    pub lost: u64,
+
+    /// Messages dropped by a partition.
+    pub partitioned: u64,
+
+    /// Messages delivered to silent nodes, which ignore them.
+    pub ignored: u64,
+
+    /// Messages a node failed to handle.
+    pub rejected: u64,
+}
+
+/// The outcome of a simulation run.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SimulationReport {
+    /// Seed of the run.
+    pub seed: u64,
+
+    /// Virtual time at which the run ended, in milliseconds.
+    pub duration_ms: u64,
+
+    /// Values externalized by each honest node, by slot.
+    pub externalized: BTreeMap<SimNodeId, BTreeMap<SlotIndex, ExternalizedSlot>>,
+
+    /// Slots in which honest nodes diverged.
+    pub safety_violations: Vec<SafetyViolation>,
+
+    /// Progress of the honest nodes.
+    pub liveness: LivenessMetrics,
+
+    /// Message counts.
+    pub messages: MessageMetrics,
+}
+
+impl SimulationReport {
+    /// Build a report from the values externalized by each honest node.
+    pub fn new(
+        seed: u64,
+        duration_ms: u64,
+        target_slots: u64,
+        externalized: BTreeMap<SimNodeId, BTreeMap<SlotIndex, ExternalizedSlot>>,
+        messages: MessageMetrics,
+    ) -> Self {
+        let mut safety_violations = Vec::new();
+        let mut slot_latencies = Vec::new();
+
+        let mut slot_indexes = externalized
+            .values()
+            .flat_map(|slots| slots.keys().copied())
+            .collect::<Vec<_>>();
+        slot_indexes.sort_unstable();
+        slot_indexes.dedup();
+
+        for slot_index in slot_indexes {
+            let slots = externalized
+                .iter()
+                .filter_map(|(node, slots)| slots.get(&slot_index).map(|slot| (*node, slot)))
+                .collect::<Vec<_>>();
+
+            // Every node is compared with the first one, so each divergent node is
+            // reported once.
+            let (first_node, first_slot) = slots[0];
+            for (node, slot) in &slots[1..] {
+                if slot.values != first_slot.values {
+                    safety_violations.push(SafetyViolation {
+                        slot_index,
+                        node_a: first_node,
+                        values_a: first_slot.values.clone(),
+                        node_b: *node,
+                        values_b: slot.values.clone(),
+                    });
+                }
+            }
+
+            let times = slots.iter().map(|(_, slot)| slot.at_ms);
+            slot_latencies.push(SlotLatency {
+                slot_index,
+                first_ms: times.clone().min().unwrap_or_default(),
+                last_ms: if slots.len() == externalized.len() {
+                    times.max()
+                } else {
+                    None
+                },
+            });
+        }
+
+        let slots_externalized_by_all = externalized
+            .values()
+            .map(|slots| slots.len() as u64)
+            .min()
+            .unwrap_or_default();
+        let stalled_nodes = externalized
+            .iter()
+            .filter(|(_, slots)| (slots.len() as u64) < target_slots)
+            .map(|(node, _)| *node)
+            .collect();
+
+        Self {
+            seed,
+            duration_ms,
+            externalized,
+            safety_violations,
+            liveness: LivenessMetrics {
+                target_slots,
+                slots_externalized_by_all,
+                stalled_nodes,
+                slot_latencies,
+            },
+            messages,
+        }
+    }
+
+    /// No two honest nodes externalized different values in the same slot.
+    pub fn is_safe(&self) -> bool {
+        self.safety_violations.is_empty()
+    }
+
+    /// Every honest node externalized the target number of slots.
+    pub fn is_live(&self) -> bool {
+        self.liveness.stalled_nodes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::iter::FromIterator;
+
+    fn slot(values: &[&str], at_ms: u64) -> ExternalizedSlot {
+        ExternalizedSlot {
+            values: values.iter().map(|value| value.to_string()).collect(),
+            at_ms,
+        }
+    }
+
+    #[test]
+    fn report_detects_divergence_and_stalls() {
+        let externalized = BTreeMap::from_iter([
+            (
+                0,
+                BTreeMap::from_iter([(0, slot(&["a"], 100)), (1, slot(&["b"], 200))]),
+            ),
+            (
+                1,
+                BTreeMap::from_iter([(0, slot(&["a"], 150)), (1, slot(&["c"], 250))]),
+            ),
+            (2, BTreeMap::from_iter([(0, slot(&["a"], 120))])),
+        ]);
+
+        let report = SimulationReport::new(7, 1000, 2, externalized, Default::default());
+
+        assert!(!report.is_safe());
+        assert_eq!(
+            report.safety_violations,
+            vec![SafetyViolation {
+                slot_index: 1,
+                node_a: 0,
+                values_a: vec!["b".to_string()],
+                node_b: 1,
+                values_b: vec!["c".to_string()],
+            }]
+        );
+
+        assert!(!report.is_live());
+        assert_eq!(report.liveness.stalled_nodes, vec![2]);
+        assert_eq!(report.liveness.slots_externalized_by_all, 1);
+        assert_eq!(
+            report.liveness.slot_latencies,
+            vec![
+                SlotLatency {
+                    slot_index: 0,
+                    first_ms: 100,
+                    last_ms: Some(150),
+                },
+                SlotLatency {
+                    slot_index: 1,
+                    first_ms: 200,
+                    last_ms: None,
+                },
+            ]
+        );
+    }
+}
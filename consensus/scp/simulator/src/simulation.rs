@@ -0,0 +1,521 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A single-threaded, discrete-event simulation of an SCP network.
+//!
+//! All randomness comes from a generator seeded by the configuration, and the
+//! nodes read the time from a virtual clock that only advances between events,
+//! so a run is fully determined by its configuration.
+
+use crate::{
+    config::{Behavior, SimNodeId, SimulationConfig},
+    participant::{Outgoing, Participant},
+    report::{MessageMetrics, SimulationReport},
+    trace::{Trace, TraceEvent, TraceInput},
+};
+use mc_common::logger::{log, o, Logger};
+use mc_consensus_scp::{ClockFn, Msg};
+use rand::{Rng, SeedableRng};
+use rand_hc::Hc128Rng;
+use std::{
+    cmp::{Ordering as CmpOrdering, Reverse},
+    collections::{BTreeMap, BinaryHeap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// Something scheduled to happen in the simulation.
+enum Event {
+    /// Deliver a message.
+    Deliver {
+        from: SimNodeId,
+        to: SimNodeId,
+        msg: Msg<String>,
+    },
+
+    /// A node processes its timeouts.
+    Timer(SimNodeId),
+
+    /// A node retransmits its latest messages.
+    Retransmit(SimNodeId),
+}
+
+/// An event and the time it happens at. Events scheduled for the same time
+/// happen in the order they were scheduled in.
+struct Scheduled {
+    at_ms: u64,
+    seq: u64,
+    event: Event,
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl Eq for Scheduled {}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scheduled {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        (self.at_ms, self.seq).cmp(&(other.at_ms, other.seq))
+    }
+}
+
+/// A clock that reads the simulation's virtual time.
+fn virtual_clock(now_ms: Arc<AtomicU64>) -> ClockFn {
+    let base = Instant::now();
+    Arc::new(move || base + Duration::from_millis(now_ms.load(Ordering::SeqCst)))
+}
+
+/// Create the simulated nodes of a configuration.
+fn participants(
+    config: &SimulationConfig,
+    clock: ClockFn,
+    logger: &Logger,
+) -> BTreeMap<SimNodeId, Participant> {
+    config
+        .nodes
+        .iter()
+        .map(|node| {
+            let peers = node.peers.clone().unwrap_or_else(|| {
+                config
+                    .nodes
+                    .iter()
+                    .map(|peer| peer.id)
+                    .filter(|peer| *peer != node.id)
+                    .collect()
+            });
+            let participant = Participant::new(
+                node,
+                peers,
+                config.slots,
+                config.max_values_per_slot,
+                clock.clone(),
+                logger.new(o!("mc.scp.sim.node" => node.id)),
+            );
+            (node.id, participant)
+        })
+        .collect()
+}
+
+/// Build the report of a run.
+fn report(
+    config: &SimulationConfig,
+    participants: &BTreeMap<SimNodeId, Participant>,
+    duration_ms: u64,
+    messages: MessageMetrics,
+) -> SimulationReport {
+    let externalized = participants
+        .values()
+        .filter(|participant| participant.behavior == Behavior::Honest)
+        .map(|participant| (participant.id, participant.externalized.clone()))
+        .collect();
+    SimulationReport::new(
+        config.seed,
+        duration_ms,
+        config.slots,
+        externalized,
+        messages,
+    )
+}
+
+/// A simulation run.
+pub struct Simulation {
+    /// The parameters of the run.
+    config: SimulationConfig,
+
+    /// The simulated nodes.
+    participants: BTreeMap<SimNodeId, Participant>,
+
+    /// Source of all randomness.
+    rng: Hc128Rng,
+
+    /// Virtual time, in milliseconds, shared with the nodes' clock.
+    now_ms: Arc<AtomicU64>,
+
+    /// Pending events.
+    queue: BinaryHeap<Reverse<Scheduled>>,
+
+    /// Sequence number of the next scheduled event.
+    next_seq: u64,
+
+    /// Inputs of every node, in order.
+    trace: Trace,
+
+    /// Message counts.
+    messages: MessageMetrics,
+
+    /// Logger.
+    logger: Logger,
+}
+
+impl Simulation {
+    /// Create a simulation run.
+    pub fn new(config: SimulationConfig, logger: Logger) -> Result<Self, String> {
+        config.validate()?;
+
+        let now_ms = Arc::new(AtomicU64::new(0));
+        let participants = participants(&config, virtual_clock(now_ms.clone()), &logger);
+
+        Ok(Self {
+            rng: Hc128Rng::seed_from_u64(config.seed),
+            config,
+            participants,
+            now_ms,
+            queue: BinaryHeap::new(),
+            next_seq: 0,
+            trace: Vec::new(),
+            messages: MessageMetrics::default(),
+            logger,
+        })
+    }
+
+    /// Run the simulation until every honest node externalized the target
+    /// number of slots, or the maximum duration elapsed. Returns the report
+    /// of the run and the trace of every node's inputs.
+    pub fn run(mut self) -> (SimulationReport, Trace) {
+        let node_ids = self.participants.keys().copied().collect::<Vec<_>>();
+        for node in node_ids {
+            let participant = &self.participants[&node];
+            if participant.behavior == Behavior::Silent {
+                continue;
+            }
+            for index in 0..participant.num_instances() {
+                if let Some(proposal) = self.participants[&node].next_proposal(index) {
+                    self.apply(node, proposal);
+                }
+            }
+            self.schedule(self.config.timer_interval_ms, Event::Timer(node));
+            self.schedule(self.config.retransmit_interval_ms, Event::Retransmit(node));
+        }
+
+        while !self.is_done() {
+            let scheduled = match self.queue.pop() {
+                Some(Reverse(scheduled)) if scheduled.at_ms <= self.config.max_duration_ms => {
+                    scheduled
+                }
+                _ => break,
+            };
+            self.now_ms.store(scheduled.at_ms, Ordering::SeqCst);
+
+            match scheduled.event {
+                Event::Deliver { from, to, msg } => {
+                    if self.participants[&to].behavior == Behavior::Silent {
+                        self.messages.ignored += 1;
+                    } else {
+                        self.messages.delivered += 1;
+                        self.apply(to, TraceInput::Deliver { from, msg });
+                    }
+                }
+                Event::Timer(node) => {
+                    self.apply(node, TraceInput::Timeout);
+                    self.schedule(self.config.timer_interval_ms, Event::Timer(node));
+                }
+                Event::Retransmit(node) => {
+                    for outgoing in self.participants[&node].retransmissions() {
+                        self.send(node, outgoing);
+                    }
+                    self.schedule(self.config.retransmit_interval_ms, Event::Retransmit(node));
+                }
+            }
+        }
+
+        let now_ms = self.now_ms.load(Ordering::SeqCst);
+        log::info!(
+            self.logger,
+            "Simulation with seed {} ended after {}ms",
+            self.config.seed,
+            now_ms
+        );
+        let report = report(
+            &self.config,
+            &self.participants,
+            now_ms,
+            self.messages.clone(),
+        );
+        (report, self.trace)
+    }
+
+    /// Whether every honest node externalized the target number of slots.
+    fn is_done(&self) -> bool {
+        self.participants
+            .values()
+            .filter(|participant| participant.behavior == Behavior::Honest)
+            .all(Participant::is_done)
+    }
+
+    /// Schedule an event `delay_ms` from now.
+    fn schedule(&mut self, delay_ms: u64, event: Event) {
+        self.queue.push(Reverse(Scheduled {
+            at_ms: self.now_ms.load(Ordering::SeqCst) + delay_ms,
+            seq: self.next_seq,
+            event,
+        }));
+        self.next_seq += 1;
+    }
+
+    /// Apply an input to a node, send the resulting messages, and propose
+    /// values for the slots the node advanced to.
+    fn apply(&mut self, node: SimNodeId, input: TraceInput) {
+        let now_ms = self.now_ms.load(Ordering::SeqCst);
+        let participant = self
+            .participants
+            .get_mut(&node)
+            .expect("events only target known nodes");
+        let result = participant.handle(&input, now_ms);
+
+        let proposals = result
+            .advanced
+            .iter()
+            .filter_map(|index| participant.next_proposal(*index))
+            .collect::<Vec<_>>();
+
+        for err in &result.errors {
+            log::debug!(self.logger, "Node {} failed handling input: {}", node, err);
+        }
+        self.messages.rejected += result.errors.len() as u64;
+        self.trace.push(TraceEvent {
+            at_ms: now_ms,
+            node,
+            input,
+        });
+
+        for outgoing in result.outgoing {
+            self.send(node, outgoing);
+        }
+        for proposal in proposals {
+            self.apply(node, proposal);
+        }
+    }
+
+    /// Send a message through the simulated network.
+    fn send(&mut self, from: SimNodeId, outgoing: Outgoing) {
+        let now_ms = self.now_ms.load(Ordering::SeqCst);
+        let network = &self.config.network;
+
+        for to in outgoing.recipients {
+            self.messages.sent += 1;
+
+            if self
+                .config
+                .partitions
+                .iter()
+                .any(|partition| partition.separates(from, to, now_ms))
+            {
+                self.messages.partitioned += 1;
+                continue;
+            }
+
+            if self.rng.gen_bool(network.loss_probability) {
+                self.messages.lost += 1;
+                continue;
+            }
+
+            let mut delay_ms = self
+                .rng
+                .gen_range(network.min_delay_ms..=network.max_delay_ms);
+            if self.rng.gen_bool(network.reorder_probability) {
+                delay_ms += network.reorder_delay_ms;
+            }
+
+            let event = Event::Deliver {
+                from,
+                to,
+                msg: outgoing.msg.clone(),
+            };
+            self.schedule(delay_ms, event);
+        }
+    }
+}
+
+/// Replay a trace recorded by a run of `config`. Every node is fed the same
+/// inputs at the same virtual times, so the report shows the same
+/// externalized values as the original run. Traces can be edited, e.g. to
+/// drop or reorder messages, to study how a run would have unfolded.
+///
+/// Only the inputs are replayed: messages emitted by the nodes are not sent,
+/// so the message metrics of the report only count delivered and rejected
+/// messages.
+pub fn replay(
+    config: &SimulationConfig,
+    trace: &[TraceEvent],
+    logger: Logger,
+) -> Result<SimulationReport, String> {
+    config.validate()?;
+
+    let now_ms = Arc::new(AtomicU64::new(0));
+    let mut participants = participants(config, virtual_clock(now_ms.clone()), &logger);
+    let mut messages = MessageMetrics::default();
+
+    for event in trace {
+        if event.at_ms < now_ms.load(Ordering::SeqCst) {
+            return Err(format!("Trace goes back in time at {}ms", event.at_ms));
+        }
+        now_ms.store(event.at_ms, Ordering::SeqCst);
+
+        let participant = participants
+            .get_mut(&event.node)
+            .ok_or_else(|| format!("Trace refers to unknown node {}", event.node))?;
+        if let TraceInput::Deliver { .. } = event.input {
+            messages.delivered += 1;
+        }
+        let result = participant.handle(&event.input, event.at_ms);
+        messages.rejected += result.errors.len() as u64;
+    }
+
+    Ok(report(
+        config,
+        &participants,
+        now_ms.load(Ordering::SeqCst),
+        messages,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{NodeConfig, Partition};
+    use mc_common::logger::test_with_logger;
+    use mc_consensus_scp::QuorumSet;
+
+    fn run(config: SimulationConfig, logger: Logger) -> (SimulationReport, Trace) {
+        Simulation::new(config, logger)
+            .expect("invalid config")
+            .run()
+    }
+
+    #[test_with_logger]
+    fn mesh_is_safe_and_live(logger: Logger) {
+        let mut config = SimulationConfig::mesh(4, 2);
+        config.slots = 5;
+        config.network.loss_probability = 0.1;
+        config.network.reorder_probability = 0.1;
+
+        let (report, _trace) = run(config, logger);
+        assert!(report.is_safe(), "{:?}", report.safety_violations);
+        assert!(report.is_live(), "{:?}", report.liveness);
+        assert_eq!(report.liveness.slots_externalized_by_all, 5);
+        assert!(report.messages.lost > 0);
+    }
+
+    #[test_with_logger]
+    fn same_seed_gives_same_report(logger: Logger) {
+        let mut config = SimulationConfig::mesh(4, 2);
+        config.slots = 3;
+        config.seed = 42;
+        config.network.loss_probability = 0.2;
+        config.network.reorder_probability = 0.2;
+
+        let (report_a, trace_a) = run(config.clone(), logger.clone());
+        let (report_b, trace_b) = run(config, logger);
+        assert_eq!(report_a, report_b);
+        assert_eq!(trace_a, trace_b);
+    }
+
+    #[test_with_logger]
+    fn replay_reproduces_run(logger: Logger) {
+        let mut config = SimulationConfig::mesh(4, 2);
+        config.slots = 3;
+        config.network.loss_probability = 0.1;
+
+        let (report, trace) = run(config.clone(), logger.clone());
+        let replayed = replay(&config, &trace, logger).expect("replay failed");
+        assert_eq!(replayed.externalized, report.externalized);
+        assert_eq!(replayed.messages.delivered, report.messages.delivered);
+    }
+
+    #[test_with_logger]
+    fn partition_without_quorum_stalls(logger: Logger) {
+        let mut config = SimulationConfig::mesh(4, 2);
+        config.slots = 2;
+        config.max_duration_ms = 30_000;
+        config.partitions.push(Partition {
+            start_ms: 0,
+            end_ms: u64::MAX,
+            groups: vec![vec![0, 1], vec![2, 3]],
+        });
+
+        let (report, _trace) = run(config, logger);
+        assert!(report.is_safe());
+        assert!(!report.is_live());
+        assert_eq!(report.liveness.slots_externalized_by_all, 0);
+        assert_eq!(report.duration_ms, 30_000);
+        assert!(report.messages.partitioned > 0);
+    }
+
+    #[test_with_logger]
+    fn healed_partition_recovers(logger: Logger) {
+        let mut config = SimulationConfig::mesh(4, 2);
+        config.slots = 3;
+        config.partitions.push(Partition {
+            start_ms: 0,
+            end_ms: 20_000,
+            groups: vec![vec![0, 1], vec![2, 3]],
+        });
+
+        let (report, _trace) = run(config, logger);
+        assert!(report.is_safe());
+        assert!(report.is_live(), "{:?}", report.liveness);
+        assert!(report.liveness.slot_latencies[0].first_ms >= 20_000);
+    }
+
+    #[test_with_logger]
+    fn tolerates_one_equivocating_node(logger: Logger) {
+        let mut config = SimulationConfig::mesh(4, 2);
+        config.slots = 3;
+        config.nodes[3].behavior = Behavior::Equivocating;
+
+        let (report, _trace) = run(config, logger);
+        assert!(report.is_safe(), "{:?}", report.safety_violations);
+        assert!(report.is_live(), "{:?}", report.liveness);
+        assert_eq!(report.externalized.len(), 3);
+    }
+
+    #[test_with_logger]
+    fn tolerates_one_silent_node(logger: Logger) {
+        let mut config = SimulationConfig::mesh(4, 2);
+        config.slots = 3;
+        config.nodes[0].behavior = Behavior::Silent;
+
+        let (report, _trace) = run(config, logger);
+        assert!(report.is_safe());
+        assert!(report.is_live(), "{:?}", report.liveness);
+        assert!(report.messages.ignored > 0);
+    }
+
+    #[test_with_logger]
+    // Without quorum intersection, two halves of the network can externalize
+    // different values.
+    fn detects_divergence_without_quorum_intersection(logger: Logger) {
+        let node = |id, peer| NodeConfig {
+            id,
+            quorum_set: QuorumSet::new_with_node_ids(1, vec![peer]),
+            peers: None,
+            behavior: Behavior::Honest,
+        };
+        let mut config = SimulationConfig::mesh(4, 2);
+        config.slots = 1;
+        config.nodes = vec![node(0, 1), node(1, 0), node(2, 3), node(3, 2)];
+
+        let (report, _trace) = run(config, logger);
+        assert!(report.is_live(), "{:?}", report.liveness);
+        assert!(!report.is_safe());
+        assert_eq!(report.safety_violations[0].slot_index, 0);
+    }
+
+    #[test_with_logger]
+    fn rejects_invalid_config(logger: Logger) {
+        let mut config = SimulationConfig::mesh(3, 2);
+        config.nodes[0].quorum_set = QuorumSet::new_with_node_ids(1, vec![7]);
+        assert!(Simulation::new(config, logger).is_err());
+    }
+}
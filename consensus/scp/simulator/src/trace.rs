@@ -0,0 +1,52 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Recorded inputs of a simulation run, which can be replayed.
+
+use crate::config::SimNodeId;
+use mc_consensus_scp::{Msg, SlotIndex};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Something that happened to a node during a run.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TraceEvent {
+    /// Virtual time of the event, in milliseconds.
+    pub at_ms: u64,
+
+    /// The node the event happened to.
+    pub node: SimNodeId,
+
+    /// The event.
+    pub input: TraceInput,
+}
+
+/// An input to a simulated node.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TraceInput {
+    /// Values were proposed to one of the node's instances.
+    Propose {
+        /// The instance, which is always 0 unless the node equivocates.
+        instance: usize,
+
+        /// The slot the values were proposed for.
+        slot_index: SlotIndex,
+
+        /// The proposed values.
+        values: BTreeSet<String>,
+    },
+
+    /// A message from a peer was delivered to the node.
+    Deliver {
+        /// The sender.
+        from: SimNodeId,
+
+        /// The message.
+        msg: Msg<String>,
+    },
+
+    /// The node processed its timeouts.
+    Timeout,
+}
+
+/// The inputs of every node during a run, in the order they happened.
+pub type Trace = Vec<TraceEvent>;
@@ -17,7 +17,7 @@ mod utils;
 
 #[doc(inline)]
 pub use crate::{
-    core_types::{CombineFn, GenericNodeId, Identifier, SlotIndex, ValidityFn, Value},
+    core_types::{ClockFn, CombineFn, GenericNodeId, Identifier, SlotIndex, ValidityFn, Value},
     msg::{Msg, Topic},
     node::{MockScpNode, Node, ScpNode},
     quorum_set::{QuorumSet, QuorumSetMember},
@@ -3,7 +3,7 @@
 //! A node determines whether transactions are valid, and participates in voting
 //! with the members of its quorum set.
 use crate::{
-    core_types::{CombineFn, SlotIndex, ValidityFn, Value},
+    core_types::{ClockFn, CombineFn, SlotIndex, ValidityFn, Value},
     msg::{ExternalizePayload, Msg, Topic},
     quorum_set::QuorumSet,
     slot::{ScpSlot, Slot, SlotMetrics},
@@ -16,7 +16,8 @@ use mc_common::{
 use std::{
     collections::{BTreeSet, HashMap},
     fmt::Display,
-    time::Duration,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 /// Default limit on number of externalized slots to store.
@@ -46,6 +47,9 @@ pub struct Node<V: Value, ValidationError: Clone + Display> {
     /// deterministic.
     combine_fn: CombineFn<V, ValidationError>,
 
+    /// Source of the current time, passed on to each slot.
+    clock: ClockFn,
+
     /// Logger.
     logger: Logger,
 
@@ -75,7 +79,40 @@ impl<V: Value, ValidationError: Clone + Display + 'static> Node<V, ValidationErr
         current_slot_index: SlotIndex,
         logger: Logger,
     ) -> Self {
-        let slot = Slot::new(
+        Self::new_with_clock(
+            node_id,
+            quorum_set,
+            validity_fn,
+            combine_fn,
+            current_slot_index,
+            Arc::new(Instant::now),
+            logger,
+        )
+    }
+
+    /// Creates a new Node that reads the current time from `clock`, e.g. the
+    /// virtual clock of a simulation.
+    ///
+    /// # Arguments
+    /// * `node_id` - This node's ID.
+    /// * `quorum_set` - This node's quorum set.
+    /// * `validity_fn` - Validates a value.
+    /// * `combine_fn` - Combines a set of values into a composite value (i.e.
+    ///   block).
+    /// * `current_slot_index` - Index of the slot to begin performing consensus
+    ///   on.
+    /// * `clock` - Returns the current time.
+    /// * `logger`
+    pub fn new_with_clock(
+        node_id: NodeID,
+        quorum_set: QuorumSet,
+        validity_fn: ValidityFn<V, ValidationError>,
+        combine_fn: CombineFn<V, ValidationError>,
+        current_slot_index: SlotIndex,
+        clock: ClockFn,
+        logger: Logger,
+    ) -> Self {
+        let mut slot = Slot::new(
             node_id.clone(),
             quorum_set.clone(),
             current_slot_index,
@@ -83,6 +120,7 @@ impl<V: Value, ValidationError: Clone + Display + 'static> Node<V, ValidationErr
             combine_fn.clone(),
             logger.clone(),
         );
+        slot.clock = clock.clone();
 
         Self {
             ID: node_id,
@@ -92,11 +130,26 @@ impl<V: Value, ValidationError: Clone + Display + 'static> Node<V, ValidationErr
             externalized_slots: Vec::new(),
             validity_fn,
             combine_fn,
+            clock,
             logger,
             scp_timebase: Duration::from_millis(1000),
         }
     }
 
+    /// Create a slot with this node's parameters.
+    fn new_slot(&self, slot_index: SlotIndex) -> Box<dyn ScpSlot<V>> {
+        let mut slot = Slot::new(
+            self.ID.clone(),
+            self.Q.clone(),
+            slot_index,
+            self.validity_fn.clone(),
+            self.combine_fn.clone(),
+            self.logger.clone(),
+        );
+        slot.clock = self.clock.clone();
+        Box::new(slot)
+    }
+
     // Record the values externalized by the current slot and advance the current
     // slot.
     fn externalize(&mut self, payload: &ExternalizePayload<V>) -> Result<(), String> {
@@ -116,14 +169,7 @@ impl<V: Value, ValidationError: Clone + Display + 'static> Node<V, ValidationErr
             }
         }
 
-        let next_slot = Box::new(Slot::new(
-            self.ID.clone(),
-            self.Q.clone(),
-            slot_index + 1,
-            self.validity_fn.clone(),
-            self.combine_fn.clone(),
-            self.logger.clone(),
-        ));
+        let next_slot = self.new_slot(slot_index + 1);
 
         // Advance to the next slot.
         let externalized_slot = std::mem::replace(&mut self.current_slot, next_slot);
@@ -303,14 +349,7 @@ impl<V: Value, ValidationError: Clone + Display + 'static> ScpNode<V> for Node<V
         // The slot index should only increase.
         debug_assert!(slot_index > self.current_slot_index());
 
-        self.current_slot = Box::new(Slot::new(
-            self.ID.clone(),
-            self.Q.clone(),
-            slot_index,
-            self.validity_fn.clone(),
-            self.combine_fn.clone(),
-            self.logger.clone(),
-        ));
+        self.current_slot = self.new_slot(slot_index);
 
         self.externalized_slots.clear();
     }
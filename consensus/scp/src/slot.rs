@@ -5,7 +5,7 @@
 //! The transactions validated in this slot determine the values to include in
 //! the next block appended to the ledger.
 use crate::{
-    core_types::{Ballot, CombineFn, SlotIndex, ValidityFn, Value},
+    core_types::{Ballot, ClockFn, CombineFn, SlotIndex, ValidityFn, Value},
     msg::*,
     predicates::{
         BallotRangePredicate, BallotSetPredicate, FuncPredicate, Predicate, ValueSetPredicate,
@@ -152,6 +152,9 @@ pub struct Slot<V: Value, ValidationError: Display> {
     /// deterministic.
     combine_fn: CombineFn<V, ValidationError>,
 
+    /// Source of the current time, used for round and ballot timeouts.
+    pub(crate) clock: ClockFn,
+
     /// List of values that have been checked to be valid for the current slot.
     /// We can cache this and save on validation calls since the ledger doesn't
     /// change during a slot.
@@ -222,7 +225,7 @@ impl<V: Value, ValidationError: Display> ScpSlot<V> for Slot<V, ValidationError>
 
         // Nomination round timeout.
         if self.next_nominate_round_at.is_some()
-            && Instant::now() > self.next_nominate_round_at.unwrap()
+            && (self.clock)() > self.next_nominate_round_at.unwrap()
         {
             timeout_occurred = true;
             // Canceling is required since schedule_next_nomination_round will not schedule
@@ -245,7 +248,7 @@ impl<V: Value, ValidationError: Display> ScpSlot<V> for Slot<V, ValidationError>
         }
 
         // Ballot timeout.
-        if self.next_ballot_at.is_some() && Instant::now() > self.next_ballot_at.unwrap() {
+        if self.next_ballot_at.is_some() && (self.clock)() > self.next_ballot_at.unwrap() {
             log::debug!(
                 self.logger,
                 "Ballot {} timed out in {:?} phase",
@@ -437,6 +440,7 @@ impl<V: Value, ValidationError: Display> Slot<V, ValidationError> {
             next_ballot_at: None,
             validity_fn,
             combine_fn,
+            clock: Arc::new(Instant::now),
             valid_values: BTreeSet::default(),
             logger: logger.new(o!("mc.scp.slot" => slot_index)),
             base_round_interval: Duration::from_millis(1000),
@@ -550,7 +554,7 @@ impl<V: Value, ValidationError: Display> Slot<V, ValidationError> {
     fn schedule_next_nomination_round(&mut self) {
         if self.next_nominate_round_at.is_none() {
             self.next_nominate_round_at =
-                Some(Instant::now() + self.base_round_interval * self.nominate_round);
+                Some((self.clock)() + self.base_round_interval * self.nominate_round);
         }
     }
 
@@ -1199,7 +1203,7 @@ impl<V: Value, ValidationError: Display> Slot<V, ValidationError> {
 
             if !quorum_ids.is_empty() {
                 self.next_ballot_at =
-                    Some(Instant::now() + self.base_ballot_interval * self.B.N.saturating_add(1));
+                    Some((self.clock)() + self.base_ballot_interval * self.B.N.saturating_add(1));
             }
         }
     }
@@ -2403,6 +2407,44 @@ mod nominate_protocol_tests {
             assert_eq!(emitted, expected);
         }
     }
+
+    #[test_with_logger]
+    /// Nomination round timeouts should be measured with the slot's clock.
+    fn test_nomination_timeout_uses_clock(logger: Logger) {
+        let (local_node, _node_2, _node_3) = three_node_cycle();
+
+        let slot_index = 2;
+        let mut slot = Slot::<u32, TransactionValidationError>::new(
+            local_node.0.clone(),
+            local_node.1.clone(),
+            slot_index,
+            Arc::new(trivial_validity_fn),
+            Arc::new(trivial_combine_fn),
+            logger,
+        );
+
+        let start = Instant::now();
+        let now = Arc::new(std::sync::Mutex::new(start));
+        let clock_now = now.clone();
+        slot.clock = Arc::new(move || *clock_now.lock().unwrap());
+
+        slot.max_priority_peers.insert(local_node.0.clone());
+        slot.propose_values(&btreeset! { 1000, 2000})
+            .expect("slot.propose_values failed");
+        assert_eq!(
+            slot.next_nominate_round_at,
+            Some(start + slot.base_round_interval)
+        );
+
+        // The round has not timed out yet.
+        slot.process_timeouts();
+        assert_eq!(slot.nominate_round, 1);
+
+        // Advancing the clock past the deadline starts the next round.
+        *now.lock().unwrap() = start + slot.base_round_interval * 2;
+        slot.process_timeouts();
+        assert_eq!(slot.nominate_round, 2);
+    }
 }
 
 #[cfg(test)]
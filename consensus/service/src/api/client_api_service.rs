@@ -3,17 +3,24 @@
 //! Serves client-to-node gRPC requests.
 
 use crate::{
-    api::grpc_error::ConsensusGrpcError,
-    consensus_service::ProposeTxCallback,
+    api::{
+        grpc_error::ConsensusGrpcError,
+        tx_status::{TrackedTx, TxStatusPoller},
+    },
+    byzantine_ledger::MAX_PENDING_VALUES,
+    consensus_service::{GetNetworkLoadFn, PendingKeyImagesFn, ProposeTxCallback},
     counters,
     mint_tx_manager::MintTxManager,
     tx_manager::{TxManager, TxManagerError},
 };
-use grpcio::{RpcContext, RpcStatus, UnarySink};
+use grpcio::{RpcContext, RpcStatus, ServerStreamingSink, UnarySink};
 use mc_attest_api::attest::Message;
 use mc_common::logger::Logger;
 use mc_consensus_api::{
-    consensus_client::{ProposeMintConfigTxResponse, ProposeMintTxResponse},
+    consensus_client::{
+        FeeEstimateRequest, FeeEstimateResponse, ProposeMintConfigTxResponse,
+        ProposeMintTxResponse, TxStatusRequest, TxStatusResponse,
+    },
     consensus_client_grpc::ConsensusClientApi,
     consensus_common::{ProposeTxResponse, ProposeTxResult},
     consensus_config::{ConsensusNodeConfig, TokenConfig},
@@ -23,14 +30,18 @@ use mc_consensus_enclave::ConsensusEnclave;
 use mc_consensus_service_config::Config;
 use mc_ledger_db::Ledger;
 use mc_peers::ConsensusValue;
-use mc_transaction_core::mint::{MintConfigTx, MintTx};
+use mc_transaction_core::{
+    mint::{MintConfigTx, MintTx},
+    TokenId,
+};
 use mc_util_grpc::{rpc_logger, send_result, Authenticator};
 use mc_util_metrics::{self, SVC_COUNTERS};
 use std::{convert::TryFrom, sync::Arc};
 
 /// Maximum number of pending values for consensus service before rejecting
-/// add_transaction requests.
-const PENDING_LIMIT: i64 = 500;
+/// add_transaction requests, unless they pay more per byte than a pending
+/// transaction.
+const PENDING_LIMIT: i64 = MAX_PENDING_VALUES as i64;
 
 #[derive(Clone)]
 pub struct ClientApiService {
@@ -43,6 +54,11 @@ pub struct ClientApiService {
     propose_tx_callback: ProposeTxCallback,
     /// Returns true if this node is able to process proposed transactions.
     is_serving_fn: Arc<(dyn Fn() -> bool + Sync + Send)>,
+    /// Streams the status of transactions to clients.
+    tx_status_poller: TxStatusPoller,
+    /// Returns the number of pending values and of transactions in recent
+    /// blocks.
+    get_network_load_fn: GetNetworkLoadFn,
     authenticator: Arc<dyn Authenticator + Send + Sync>,
     logger: Logger,
 }
@@ -56,9 +72,13 @@ impl ClientApiService {
         tx_manager: Arc<dyn TxManager + Send + Sync>,
         mint_tx_manager: Arc<dyn MintTxManager + Send + Sync>,
         is_serving_fn: Arc<(dyn Fn() -> bool + Sync + Send)>,
+        pending_key_images_fn: PendingKeyImagesFn,
+        get_network_load_fn: GetNetworkLoadFn,
         authenticator: Arc<dyn Authenticator + Send + Sync>,
         logger: Logger,
     ) -> Self {
+        let tx_status_poller =
+            TxStatusPoller::new(ledger.clone(), pending_key_images_fn, logger.clone());
         Self {
             config,
             enclave,
@@ -67,6 +87,8 @@ impl ClientApiService {
             ledger,
             propose_tx_callback: scp_client_value_sender,
             is_serving_fn,
+            tx_status_poller,
+            get_network_load_fn,
             authenticator,
             logger,
         }
@@ -76,10 +98,12 @@ impl ClientApiService {
     ///
     /// # Arguments
     /// `msg` - An encrypted message from a client to the enclave.
-    /// `logger` - Logger
+    /// `min_fee_per_byte` - If this node is over capacity, the fee per byte the
+    /// transaction must exceed to be accepted.
     fn handle_proposed_tx(
         &mut self,
         msg: Message,
+        min_fee_per_byte: Option<f64>,
     ) -> Result<ProposeTxResponse, ConsensusGrpcError> {
         counters::ADD_TX_INITIATED.inc();
         let tx_context = self.enclave.client_tx_propose(msg.into())?;
@@ -95,6 +119,15 @@ impl ClientApiService {
             err
         })?;
 
+        // When over capacity, the transaction takes the place of a pending one that
+        // pays less per byte.
+        if let Some(min_fee_per_byte) = min_fee_per_byte {
+            let fee_per_byte = self.tx_manager.fee_per_byte(&tx_hash).unwrap_or(0.0);
+            if fee_per_byte <= min_fee_per_byte {
+                return Err(ConsensusGrpcError::OverCapacity);
+            }
+        }
+
         // Validate the transaction.
         // This is done here as a courtesy to give clients immediate feedback about the
         // transaction.
@@ -157,6 +190,40 @@ impl ClientApiService {
         Ok(response)
     }
 
+    /// Estimates the fee of a transaction, based on recent network load.
+    ///
+    /// # Arguments
+    /// `request` - The token the fee is paid in.
+    fn get_fee_estimate_impl(
+        &self,
+        request: FeeEstimateRequest,
+    ) -> Result<FeeEstimateResponse, ConsensusGrpcError> {
+        let token_id = TokenId::from(request.get_token_id());
+        let minimum_fee = self
+            .config
+            .tokens()
+            .get_token_config(&token_id)
+            .and_then(|token_config| token_config.minimum_fee_or_default())
+            .ok_or_else(|| {
+                ConsensusGrpcError::InvalidArgument(format!(
+                    "Unknown token id {}",
+                    request.get_token_id()
+                ))
+            })?;
+
+        let network_load = (self.get_network_load_fn)();
+
+        let mut response = FeeEstimateResponse::new();
+        response.set_minimum_fee(minimum_fee);
+        response.set_estimated_fee(
+            network_load.estimate_fee(minimum_fee, request.get_tx_size() as usize),
+        );
+        response.set_num_pending_values(network_load.num_pending_values() as u64);
+        response.set_recent_block_fill(network_load.recent_block_fill());
+        response.set_block_count(self.ledger.num_blocks()?);
+        Ok(response)
+    }
+
     /// Get the node's configuration.
     fn get_node_config_impl(&self) -> Result<ConsensusNodeConfig, ConsensusGrpcError> {
         let tokens_config = self.config.tokens();
@@ -213,25 +280,33 @@ impl ConsensusClientApi for ClientApiService {
             return send_result(ctx, sink, err.into(), &self.logger);
         }
 
-        let mut result: Result<ProposeTxResponse, RpcStatus> =
-            if counters::CUR_NUM_PENDING_VALUES.get() >= PENDING_LIMIT {
-                // This node is over capacity, and is not accepting proposed transaction.
-                if let Err(e) = self.enclave.client_discard_message(msg.into()) {
-                    ConsensusGrpcError::Enclave(e).into()
-                } else {
-                    ConsensusGrpcError::OverCapacity.into()
-                }
-            } else if !(self.is_serving_fn)() {
-                // This node is unable to process transactions (e.g. is syncing its ledger).
-                if let Err(e) = self.enclave.client_discard_message(msg.into()) {
-                    ConsensusGrpcError::Enclave(e).into()
-                } else {
-                    ConsensusGrpcError::NotServing.into()
-                }
+        // When this node is over capacity, it only accepts transactions which pay more
+        // per byte than one of its pending transactions.
+        let min_fee_per_byte = if counters::CUR_NUM_PENDING_VALUES.get() >= PENDING_LIMIT {
+            Some((self.get_network_load_fn)().lowest_pending_fee_per_byte())
+        } else {
+            None
+        };
+
+        let mut result: Result<ProposeTxResponse, RpcStatus> = if let Some(None) = min_fee_per_byte
+        {
+            // This node is over capacity, and no pending transaction can make room.
+            if let Err(e) = self.enclave.client_discard_message(msg.into()) {
+                ConsensusGrpcError::Enclave(e).into()
             } else {
-                self.handle_proposed_tx(msg)
-                    .or_else(ConsensusGrpcError::into)
-            };
+                ConsensusGrpcError::OverCapacity.into()
+            }
+        } else if !(self.is_serving_fn)() {
+            // This node is unable to process transactions (e.g. is syncing its ledger).
+            if let Err(e) = self.enclave.client_discard_message(msg.into()) {
+                ConsensusGrpcError::Enclave(e).into()
+            } else {
+                ConsensusGrpcError::NotServing.into()
+            }
+        } else {
+            self.handle_proposed_tx(msg, min_fee_per_byte.flatten())
+                .or_else(ConsensusGrpcError::into)
+        };
 
         result = result.and_then(|mut response| {
             let num_blocks = self.ledger.num_blocks().map_err(ConsensusGrpcError::from)?;
@@ -325,6 +400,43 @@ impl ConsensusClientApi for ClientApiService {
             send_result(ctx, sink, result, logger)
         });
     }
+
+    fn get_fee_estimate(
+        &mut self,
+        ctx: RpcContext,
+        request: FeeEstimateRequest,
+        sink: UnarySink<FeeEstimateResponse>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+
+        if let Err(err) = self.authenticator.authenticate_rpc(&ctx) {
+            return send_result(ctx, sink, err.into(), &self.logger);
+        }
+
+        let result = self.get_fee_estimate_impl(request).map_err(RpcStatus::from);
+
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            send_result(ctx, sink, result, logger)
+        });
+    }
+
+    fn get_tx_status_stream(
+        &mut self,
+        ctx: RpcContext,
+        request: TxStatusRequest,
+        sink: ServerStreamingSink<TxStatusResponse>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+
+        let result: Result<TrackedTx, RpcStatus> = self
+            .authenticator
+            .authenticate_rpc(&ctx)
+            .or_else(|err| err.into())
+            .and_then(|_| TrackedTx::try_from(&request).map_err(RpcStatus::from));
+
+        let logger = rpc_logger(&ctx, &self.logger);
+        self.tx_status_poller.watch(&ctx, sink, result, logger);
+    }
 }
 
 #[cfg(test)]
@@ -333,9 +445,12 @@ mod client_api_tests {
         api::client_api_service::{ClientApiService, PENDING_LIMIT},
         counters,
         mint_tx_manager::{MintTxManagerError, MockMintTxManager},
+        network_load::NetworkLoad,
+        pending_key_images::PendingKeyImages,
         tx_manager::{MockTxManager, TxManagerError},
     };
     use clap::Parser;
+    use futures::{executor::block_on, StreamExt};
     use grpcio::{
         ChannelBuilder, Environment, Error as GrpcError, RpcStatusCode, Server, ServerBuilder,
     };
@@ -346,8 +461,12 @@ mod client_api_tests {
         NodeID, ResponderId,
     };
     use mc_consensus_api::{
-        consensus_client::MintValidationResultCode, consensus_client_grpc,
-        consensus_client_grpc::ConsensusClientApiClient, consensus_common::ProposeTxResult,
+        consensus_client::{
+            FeeEstimateRequest, MintValidationResultCode, TxFinalityStatus, TxStatusRequest,
+        },
+        consensus_client_grpc,
+        consensus_client_grpc::ConsensusClientApiClient,
+        consensus_common::ProposeTxResult,
     };
     use mc_consensus_enclave::TxContext;
     use mc_consensus_enclave_mock::MockConsensusEnclave;
@@ -356,8 +475,8 @@ mod client_api_tests {
     use mc_ledger_db::MockLedger;
     use mc_peers::ConsensusValue;
     use mc_transaction_core::{
-        mint::MintValidationError, ring_signature::KeyImage, tx::TxHash,
-        validation::TransactionValidationError, TokenId,
+        mint::MintValidationError, ring_signature::KeyImage, tokens::Mob, tx::TxHash,
+        validation::TransactionValidationError, Token, TokenId,
     };
     use mc_transaction_core_test_utils::{create_mint_config_tx, create_mint_tx};
     use mc_util_from_random::FromRandom;
@@ -366,7 +485,11 @@ mod client_api_tests {
     use rand_hc::Hc128Rng;
     use serial_test::serial;
     use std::{
-        sync::{Arc, Mutex},
+        collections::HashSet,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
         time::Duration,
     };
 
@@ -463,6 +586,8 @@ mod client_api_tests {
             Arc::new(tx_manager),
             Arc::new(MockMintTxManager::new()),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -537,6 +662,8 @@ mod client_api_tests {
             Arc::new(tx_manager),
             Arc::new(MockMintTxManager::new()),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -605,6 +732,8 @@ mod client_api_tests {
             Arc::new(tx_manager),
             Arc::new(MockMintTxManager::new()),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -653,6 +782,8 @@ mod client_api_tests {
             Arc::new(MockTxManager::new()),
             Arc::new(MockMintTxManager::new()),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -703,6 +834,8 @@ mod client_api_tests {
             Arc::new(MockTxManager::new()),
             Arc::new(MockMintTxManager::new()),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -730,6 +863,98 @@ mod client_api_tests {
         counters::CUR_NUM_PENDING_VALUES.set(0);
     }
 
+    #[test_with_logger]
+    #[serial(counters)]
+    // When the node is over capacity, should only accept transactions which pay
+    // more per byte than the lowest paying pending transaction.
+    fn test_client_tx_propose_over_capacity_outbids_lowest_fee(logger: Logger) {
+        // Set the number of pending values to be above the PENDING_LIMIT
+        // This is a global variable, and so affects other unit tests. It must be reset
+        // afterwards :(
+        counters::CUR_NUM_PENDING_VALUES.set(PENDING_LIMIT);
+
+        for (fee_per_byte, expect_accepted) in [(0.5, false), (0.75, true)] {
+            let mut consensus_enclave = MockConsensusEnclave::new();
+            consensus_enclave
+                .expect_client_tx_propose()
+                .times(1)
+                .return_const(Ok(TxContext::default()));
+
+            let proposed_values = Arc::new(Mutex::new(Vec::new()));
+            let scp_client_value_sender = {
+                let proposed_values = proposed_values.clone();
+                Arc::new(
+                    move |value: ConsensusValue,
+                          _node_id: Option<&NodeID>,
+                          _responder_id: Option<&ResponderId>| {
+                        proposed_values.lock().unwrap().push(value);
+                    },
+                )
+            };
+
+            let mut ledger = MockLedger::new();
+            ledger.expect_num_blocks().return_const(Ok(5));
+
+            let mut tx_manager = MockTxManager::new();
+            tx_manager
+                .expect_insert()
+                .times(1)
+                .return_const(Ok(TxHash([1u8; 32])));
+            tx_manager
+                .expect_fee_per_byte()
+                .times(1)
+                .return_const(Some(fee_per_byte));
+            tx_manager
+                .expect_validate()
+                .times(if expect_accepted { 1 } else { 0 })
+                .return_const(Ok(()));
+
+            // The lowest paying pending transaction pays 0.5 per byte.
+            let get_network_load_fn = Arc::new(|| {
+                let mut network_load = NetworkLoad::default();
+                network_load.set_pending_fees_per_byte(vec![1.0, 0.5, f64::INFINITY]);
+                network_load
+            });
+
+            let instance = ClientApiService::new(
+                get_config(),
+                Arc::new(consensus_enclave),
+                scp_client_value_sender,
+                Arc::new(ledger),
+                Arc::new(tx_manager),
+                Arc::new(MockMintTxManager::new()),
+                Arc::new(|| -> bool { true }),
+                Arc::new(Arc::<PendingKeyImages>::default),
+                get_network_load_fn,
+                Arc::new(AnonymousAuthenticator::default()),
+                logger.clone(),
+            );
+
+            let (client, _server) = get_client_server(instance);
+            match client.client_tx_propose(&Message::default()) {
+                Ok(propose_tx_response) => {
+                    assert!(expect_accepted);
+                    assert_eq!(propose_tx_response.get_result(), ProposeTxResult::Ok);
+                }
+                Err(GrpcError::RpcFailure(rpc_status)) => {
+                    assert!(!expect_accepted);
+                    assert_eq!(rpc_status.code(), RpcStatusCode::UNAVAILABLE);
+                }
+                Err(e) => panic!("Unexpected error: {:?}", e),
+            }
+
+            let expected_values: Vec<ConsensusValue> = if expect_accepted {
+                vec![TxHash([1u8; 32]).into()]
+            } else {
+                vec![]
+            };
+            assert_eq!(*proposed_values.lock().unwrap(), expected_values);
+        }
+
+        // This is a global variable. It affects other unit tests, so must be reset :(
+        counters::CUR_NUM_PENDING_VALUES.set(0);
+    }
+
     #[test_with_logger]
     #[serial(counters)]
     fn test_client_tx_propose_rejects_unauthenticated(logger: Logger) {
@@ -757,6 +982,8 @@ mod client_api_tests {
             Arc::new(MockTxManager::new()),
             Arc::new(MockMintTxManager::new()),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -819,6 +1046,8 @@ mod client_api_tests {
             Arc::new(MockTxManager::new()),
             Arc::new(mint_tx_manager),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -889,6 +1118,8 @@ mod client_api_tests {
             Arc::new(MockTxManager::new()),
             Arc::new(mint_tx_manager),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -940,6 +1171,8 @@ mod client_api_tests {
             Arc::new(MockTxManager::new()),
             Arc::new(mint_tx_manager),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -993,6 +1226,8 @@ mod client_api_tests {
             Arc::new(MockTxManager::new()),
             Arc::new(mint_tx_manager),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -1055,6 +1290,8 @@ mod client_api_tests {
             Arc::new(MockTxManager::new()),
             Arc::new(mint_tx_manager),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -1115,6 +1352,8 @@ mod client_api_tests {
             Arc::new(MockTxManager::new()),
             Arc::new(mint_tx_manager),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -1195,6 +1434,8 @@ mod client_api_tests {
             Arc::new(MockTxManager::new()),
             Arc::new(mint_tx_manager),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -1251,6 +1492,8 @@ mod client_api_tests {
             Arc::new(MockTxManager::new()),
             Arc::new(mint_tx_manager),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -1309,6 +1552,8 @@ mod client_api_tests {
             Arc::new(MockTxManager::new()),
             Arc::new(mint_tx_manager),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -1376,6 +1621,8 @@ mod client_api_tests {
             Arc::new(MockTxManager::new()),
             Arc::new(mint_tx_manager),
             is_serving_fn,
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
             Arc::new(authenticator),
             logger,
         );
@@ -1394,4 +1641,139 @@ mod client_api_tests {
 
         assert!(submitted_values.lock().unwrap().is_empty());
     }
+
+    #[test_with_logger]
+    fn test_get_tx_status_stream(logger: Logger) {
+        let key_images = vec![KeyImage::from(1), KeyImage::from(2)];
+        let externalized = Arc::new(AtomicBool::new(false));
+
+        // The key images are spent in block 12 once `externalized` is set.
+        let mut ledger = MockLedger::new();
+        ledger.expect_num_blocks().return_const(Ok(13));
+        let externalized2 = externalized.clone();
+        ledger
+            .expect_check_key_image()
+            .returning(move |_| Ok(externalized2.load(Ordering::SeqCst).then(|| 12)));
+
+        // The key images are pending until then.
+        let pending =
+            Arc::new(PendingKeyImages::default().next(key_images.iter().cloned().collect()));
+        let externalized3 = externalized.clone();
+        let pending_key_images_fn = Arc::new(move || -> Arc<PendingKeyImages> {
+            if externalized3.load(Ordering::SeqCst) {
+                Arc::new(pending.next(HashSet::default()))
+            } else {
+                pending.clone()
+            }
+        });
+
+        let instance = ClientApiService::new(
+            get_config(),
+            Arc::new(MockConsensusEnclave::new()),
+            Arc::new(|_: ConsensusValue, _: Option<&NodeID>, _: Option<&ResponderId>| {}),
+            Arc::new(ledger),
+            Arc::new(MockTxManager::new()),
+            Arc::new(MockMintTxManager::new()),
+            Arc::new(|| -> bool { true }),
+            pending_key_images_fn,
+            Arc::new(NetworkLoad::default),
+            Arc::new(AnonymousAuthenticator::default()),
+            logger,
+        );
+
+        // gRPC client and server.
+        let (client, _server) = get_client_server(instance);
+
+        let mut request = TxStatusRequest::new();
+        request.set_key_images(key_images.iter().map(Into::into).collect());
+        request.set_tombstone_block(20);
+        let mut stream = client.get_tx_status_stream(&request).unwrap();
+
+        let response = block_on(stream.next()).unwrap().unwrap();
+        assert_eq!(response.get_status(), TxFinalityStatus::Pending);
+        assert_eq!(response.get_block_count(), 13);
+
+        externalized.store(true, Ordering::SeqCst);
+        let response = block_on(stream.next()).unwrap().unwrap();
+        assert_eq!(response.get_status(), TxFinalityStatus::Externalized);
+        assert_eq!(response.get_block_index(), 12);
+
+        // The stream ends after a final status.
+        assert!(block_on(stream.next()).is_none());
+    }
+
+    #[test_with_logger]
+    fn test_get_tx_status_stream_requires_key_images(logger: Logger) {
+        let instance = ClientApiService::new(
+            get_config(),
+            Arc::new(MockConsensusEnclave::new()),
+            Arc::new(|_: ConsensusValue, _: Option<&NodeID>, _: Option<&ResponderId>| {}),
+            Arc::new(MockLedger::new()),
+            Arc::new(MockTxManager::new()),
+            Arc::new(MockMintTxManager::new()),
+            Arc::new(|| -> bool { true }),
+            Arc::new(Arc::<PendingKeyImages>::default),
+            Arc::new(NetworkLoad::default),
+            Arc::new(AnonymousAuthenticator::default()),
+            logger,
+        );
+
+        // gRPC client and server.
+        let (client, _server) = get_client_server(instance);
+
+        let mut stream = client
+            .get_tx_status_stream(&TxStatusRequest::new())
+            .unwrap();
+        match block_on(stream.next()) {
+            Some(Err(GrpcError::RpcFailure(rpc_status))) => {
+                assert_eq!(rpc_status.code(), RpcStatusCode::INVALID_ARGUMENT);
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test_with_logger]
+    fn test_get_fee_estimate(logger: Logger) {
+        let mut ledger = MockLedger::new();
+        ledger.expect_num_blocks().return_const(Ok(7));
+
+        // Twice as many pending values as are proposed in a slot.
+        let mut network_load = NetworkLoad::default();
+        network_load.set_num_pending_values(200);
+        let get_network_load_fn = Arc::new(move || network_load.clone());
+
+        let instance = ClientApiService::new(
+            get_config(),
+            Arc::new(MockConsensusEnclave::new()),
+            Arc::new(|_: ConsensusValue, _: Option<&NodeID>, _: Option<&ResponderId>| {}),
+            Arc::new(ledger),
+            Arc::new(MockTxManager::new()),
+            Arc::new(MockMintTxManager::new()),
+            Arc::new(|| -> bool { true }),
+            Arc::new(Arc::<PendingKeyImages>::default),
+            get_network_load_fn,
+            Arc::new(AnonymousAuthenticator::default()),
+            logger,
+        );
+
+        // gRPC client and server.
+        let (client, _server) = get_client_server(instance);
+
+        let mut request = FeeEstimateRequest::new();
+        request.set_token_id(*Mob::ID);
+        let response = client.get_fee_estimate(&request).unwrap();
+        assert_eq!(response.get_minimum_fee(), Mob::MINIMUM_FEE);
+        assert_eq!(response.get_estimated_fee(), 2 * Mob::MINIMUM_FEE);
+        assert_eq!(response.get_num_pending_values(), 200);
+        assert_eq!(response.get_block_count(), 7);
+
+        // The node has no minimum fee for this token.
+        request.set_token_id(1234);
+        match client.get_fee_estimate(&request) {
+            Err(GrpcError::RpcFailure(rpc_status)) => {
+                assert_eq!(rpc_status.code(), RpcStatusCode::INVALID_ARGUMENT);
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
 }
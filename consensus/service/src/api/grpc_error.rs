@@ -117,6 +117,9 @@ impl From<ConsensusGrpcError> for RpcStatus {
                     "Permission Denied (attestation)".into(),
                 )
             }
+            ConsensusGrpcError::InvalidArgument(err) => {
+                RpcStatus::with_message(RpcStatusCode::INVALID_ARGUMENT, err)
+            }
             ConsensusGrpcError::Other(err) => RpcStatus::with_message(RpcStatusCode::INTERNAL, err),
             ConsensusGrpcError::TransactionValidation(err) => {
                 global_log::error!("Attempting to convert a ConsensusGrpcError::TransactionValidation into RpcStatus, this should not happen! Error is: {}", err);
@@ -8,6 +8,7 @@ mod client_api_service;
 mod grpc_error;
 mod peer_api_service;
 mod peer_service_error;
+mod tx_status;
 
 pub use attested_api_service::AttestedApiService;
 pub use blockchain_api_service::BlockchainApiService;
@@ -0,0 +1,627 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Support for the `GetTxStatusStream` RPC.
+//! * `TrackedTx` determines the status of a transaction from the ledger and
+//!   from the pending values of the byzantine ledger.
+//! * `TxStatusPoller` streams each change of that status to clients, polling
+//!   the transactions of all streams from a single thread.
+
+use crate::{
+    api::grpc_error::ConsensusGrpcError, consensus_service::PendingKeyImagesFn,
+    pending_key_images::PendingKeyImages,
+};
+use futures::{FutureExt, TryFutureExt};
+use grpcio::{RpcContext, RpcStatus, RpcStatusCode, ServerStreamingSink};
+use mc_common::logger::{log, Logger};
+use mc_consensus_api::consensus_client::{TxFinalityStatus, TxStatusRequest, TxStatusResponse};
+use mc_crypto_keys::CompressedRistrettoPublic;
+use mc_ledger_db::{Error as LedgerError, Ledger};
+use mc_transaction_core::{
+    constants::{MAX_INPUTS, MAX_OUTPUTS},
+    ring_signature::KeyImage,
+};
+use mc_util_grpc::{streaming_sender, StreamingSendError, StreamingSender};
+use std::{
+    convert::TryFrom,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long the poller waits before checking again whether the block count or
+/// the pending key images changed, which is when the status of the streamed
+/// transactions may change.
+pub const TX_STATUS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a stream may go without sending anything before it sends its
+/// current status again. A stream only notices that its client went away when
+/// sending fails.
+pub const TX_STATUS_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long a client may stream the status of a transaction. After that the
+/// stream fails with `DEADLINE_EXCEEDED`, and the client has to ask again.
+pub const TX_STATUS_MAX_STREAM_DURATION: Duration = Duration::from_secs(600);
+
+/// Maximum number of concurrent streams. Further requests are rejected as over
+/// capacity.
+pub const MAX_TX_STATUS_STREAMS: usize = 1000;
+
+/// Number of responses that may be queued for a client.
+const TX_STATUS_CHANNEL_SIZE: usize = 4;
+
+/// A transaction whose status a client is waiting on.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrackedTx {
+    /// The key images of the transaction's inputs.
+    key_images: Vec<KeyImage>,
+
+    /// The public keys of the transaction's outputs.
+    output_public_keys: Vec<CompressedRistrettoPublic>,
+
+    /// The transaction's tombstone block, or zero if unknown.
+    tombstone_block: u64,
+}
+
+impl TryFrom<&TxStatusRequest> for TrackedTx {
+    type Error = ConsensusGrpcError;
+
+    fn try_from(src: &TxStatusRequest) -> Result<Self, Self::Error> {
+        if src.get_key_images().is_empty() {
+            return Err(ConsensusGrpcError::InvalidArgument(
+                "At least one key image is required".to_string(),
+            ));
+        }
+        if src.get_key_images().len() as u64 > MAX_INPUTS {
+            return Err(ConsensusGrpcError::InvalidArgument(format!(
+                "At most {} key images are allowed",
+                MAX_INPUTS
+            )));
+        }
+        if src.get_output_public_keys().len() as u64 > MAX_OUTPUTS {
+            return Err(ConsensusGrpcError::InvalidArgument(format!(
+                "At most {} output public keys are allowed",
+                MAX_OUTPUTS
+            )));
+        }
+
+        let key_images = src
+            .get_key_images()
+            .iter()
+            .map(KeyImage::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| ConsensusGrpcError::InvalidArgument(format!("{:?}", err)))?;
+        let output_public_keys = src
+            .get_output_public_keys()
+            .iter()
+            .map(CompressedRistrettoPublic::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| ConsensusGrpcError::InvalidArgument(format!("{:?}", err)))?;
+
+        Ok(Self {
+            key_images,
+            output_public_keys,
+            tombstone_block: src.get_tombstone_block(),
+        })
+    }
+}
+
+impl TrackedTx {
+    /// Determine the current status of the transaction.
+    ///
+    /// The transaction is externalized once all its key images are spent in
+    /// the same block, along with all its outputs. Key images spent any other
+    /// way were spent by a conflicting transaction.
+    ///
+    /// # Arguments
+    /// * `ledger` - The local node's ledger.
+    /// * `pending_key_images` - The key images of the pending values of the
+    ///   byzantine ledger.
+    pub fn status(
+        &self,
+        ledger: &dyn Ledger,
+        pending_key_images: &PendingKeyImages,
+    ) -> Result<TxStatusResponse, ConsensusGrpcError> {
+        let block_count = ledger.num_blocks()?;
+        let mut response = TxStatusResponse::new();
+        response.set_block_count(block_count);
+
+        let spent_at = self
+            .key_images
+            .iter()
+            .map(|key_image| ledger.check_key_image(key_image))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Some(first_spent_at) = spent_at.iter().flatten().min().copied() {
+            let all_spent_together = spent_at
+                .iter()
+                .all(|block_index| *block_index == Some(first_spent_at));
+            let all_outputs_together = self
+                .output_public_keys
+                .iter()
+                .map(|public_key| output_block_index(ledger, public_key))
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .all(|block_index| block_index == Some(first_spent_at));
+
+            if all_spent_together && all_outputs_together {
+                response.set_status(TxFinalityStatus::Externalized);
+            } else {
+                response.set_status(TxFinalityStatus::KeyImageSpent);
+            }
+            response.set_block_index(first_spent_at);
+        } else if self.tombstone_block != 0 && block_count >= self.tombstone_block {
+            // The next block would be at or past the tombstone block.
+            response.set_status(TxFinalityStatus::Tombstoned);
+        } else if pending_key_images.contains_all(&self.key_images) {
+            response.set_status(TxFinalityStatus::Pending);
+        } else {
+            response.set_status(TxFinalityStatus::NotFound);
+        }
+
+        Ok(response)
+    }
+}
+
+/// The index of the block containing the output with the given public key, if
+/// it is in the ledger.
+fn output_block_index(
+    ledger: &dyn Ledger,
+    public_key: &CompressedRistrettoPublic,
+) -> Result<Option<u64>, LedgerError> {
+    match ledger.get_tx_out_index_by_public_key(public_key) {
+        Ok(tx_out_index) => ledger
+            .get_block_index_by_tx_out_index(tx_out_index)
+            .map(Some),
+        Err(LedgerError::NotFound) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns true if a transaction cannot leave the given status.
+pub fn is_final(status: TxFinalityStatus) -> bool {
+    match status {
+        TxFinalityStatus::NotFound | TxFinalityStatus::Pending => false,
+        TxFinalityStatus::Externalized
+        | TxFinalityStatus::Tombstoned
+        | TxFinalityStatus::KeyImageSpent => true,
+    }
+}
+
+/// A client streaming the status of a transaction.
+struct TxStatusStream {
+    tx: TrackedTx,
+    sender: StreamingSender<TxStatusResponse>,
+    started_at: Instant,
+    /// The last status and block index sent, and when.
+    last_sent: Option<((TxFinalityStatus, u64), Instant)>,
+    /// True if the last response did not fit in the queue.
+    retry: bool,
+}
+
+impl TxStatusStream {
+    fn new(tx: TrackedTx, sender: StreamingSender<TxStatusResponse>, now: Instant) -> Self {
+        Self {
+            tx,
+            sender,
+            started_at: now,
+            last_sent: None,
+            retry: false,
+        }
+    }
+
+    /// Send the current status of the transaction if it changed, or if the
+    /// keepalive interval elapsed. Returns false once the stream is over.
+    ///
+    /// The status is only looked up if `changed` is set, meaning the block
+    /// count or the pending key images changed since the last poll, or if a
+    /// response is due anyway. A response that does not fit in the queue is
+    /// tried again on the next poll, so that a slow client never blocks the
+    /// other streams.
+    fn poll(
+        &mut self,
+        ledger: &dyn Ledger,
+        pending_key_images: &PendingKeyImages,
+        changed: bool,
+        now: Instant,
+    ) -> bool {
+        if now.saturating_duration_since(self.started_at) >= TX_STATUS_MAX_STREAM_DURATION {
+            let status = RpcStatus::with_message(
+                RpcStatusCode::DEADLINE_EXCEEDED,
+                "Tx status stream lifetime exceeded".into(),
+            );
+            return self.sender.try_fail(status) == Err(StreamingSendError::Full);
+        }
+
+        let keepalive_due = match self.last_sent {
+            Some((_, sent_at)) => {
+                now.saturating_duration_since(sent_at) >= TX_STATUS_KEEPALIVE_INTERVAL
+            }
+            None => true,
+        };
+        if !changed && !keepalive_due && !self.retry {
+            return !self.sender.is_closed();
+        }
+        self.retry = false;
+
+        let response = match self.tx.status(ledger, pending_key_images) {
+            Ok(response) => response,
+            Err(err) => {
+                self.retry = self.sender.try_fail(err.into()) == Err(StreamingSendError::Full);
+                return self.retry;
+            }
+        };
+
+        let status = (response.get_status(), response.get_block_index());
+        let due = keepalive_due
+            || self
+                .last_sent
+                .map_or(true, |(last_status, _)| last_status != status);
+        if due {
+            match self.sender.try_send(response) {
+                Ok(()) => self.last_sent = Some((status, now)),
+                Err(StreamingSendError::Full) => {
+                    self.retry = true;
+                    return true;
+                }
+                Err(StreamingSendError::Disconnected) => return false,
+            }
+        }
+
+        !is_final(status.0) && !self.sender.is_closed()
+    }
+}
+
+/// The streams handed to the poller thread.
+#[derive(Default)]
+struct TxStatusStreams {
+    /// Streams added since the last poll.
+    added: Vec<TxStatusStream>,
+    /// Number of streams the poller thread is polling.
+    num_polled: usize,
+}
+
+/// Streams the status of transactions to clients.
+///
+/// A single thread polls the status of the transaction of every stream, and
+/// sends each change of it until it sends a final status. It exits once all
+/// clones of the poller are dropped.
+#[derive(Clone)]
+pub struct TxStatusPoller {
+    streams: Arc<Mutex<TxStatusStreams>>,
+}
+
+impl TxStatusPoller {
+    /// Start polling.
+    pub fn new(
+        ledger: Arc<dyn Ledger + Send + Sync>,
+        pending_key_images: PendingKeyImagesFn,
+        logger: Logger,
+    ) -> Self {
+        let streams = Arc::new(Mutex::new(TxStatusStreams::default()));
+
+        let weak_streams = Arc::downgrade(&streams);
+        thread::Builder::new()
+            .name("TxStatusPoller".to_owned())
+            .spawn(move || {
+                let mut polled = Vec::<TxStatusStream>::new();
+                // The block count and pending key images version at the last
+                // poll.
+                let mut last_state = None;
+                while let Some(streams) = weak_streams.upgrade() {
+                    {
+                        let mut streams = streams.lock().expect("mutex poisoned");
+                        polled.append(&mut streams.added);
+                        streams.num_polled = polled.len();
+                    }
+
+                    // Ledger lookups happen without holding the lock, so that
+                    // they never hold up new requests.
+                    let pending_key_images = pending_key_images();
+                    let state = ledger
+                        .num_blocks()
+                        .ok()
+                        .map(|num_blocks| (num_blocks, pending_key_images.version()));
+                    let changed = state.is_none() || state != last_state;
+                    last_state = state;
+
+                    let now = Instant::now();
+                    let mut i = 0;
+                    while i < polled.len() {
+                        if polled[i].poll(ledger.as_ref(), &pending_key_images, changed, now) {
+                            i += 1;
+                        } else {
+                            polled.swap_remove(i);
+                        }
+                    }
+
+                    streams.lock().expect("mutex poisoned").num_polled = polled.len();
+                    drop(streams);
+                    thread::sleep(TX_STATUS_POLL_INTERVAL);
+                }
+                log::debug!(logger, "Tx status poller stopped");
+            })
+            .expect("failed starting tx status poller thread");
+
+        Self { streams }
+    }
+
+    /// Stream the status of a transaction to a client.
+    ///
+    /// `tx` is the result of validating the request: either an error to
+    /// report to the client, or the transaction to stream the status of. The
+    /// stream starts with the current status, on the next poll. Requests
+    /// beyond `MAX_TX_STATUS_STREAMS` concurrent streams are rejected.
+    pub fn watch(
+        &self,
+        ctx: &RpcContext,
+        sink: ServerStreamingSink<TxStatusResponse>,
+        tx: Result<TrackedTx, RpcStatus>,
+        logger: Logger,
+    ) {
+        let mut streams = self.streams.lock().expect("mutex poisoned");
+        let result = tx.and_then(|tx| {
+            if streams.added.len() + streams.num_polled >= MAX_TX_STATUS_STREAMS {
+                Err(ConsensusGrpcError::OverCapacity.into())
+            } else {
+                Ok(tx)
+            }
+        });
+
+        match result {
+            Ok(tx) => {
+                let sender = streaming_sender(ctx, sink, TX_STATUS_CHANNEL_SIZE, logger);
+                streams
+                    .added
+                    .push(TxStatusStream::new(tx, sender, Instant::now()));
+            }
+            Err(status) => {
+                let future = sink
+                    .fail(status)
+                    .map_err(move |err| log::error!(logger, "failed to reply: {:?}", err))
+                    .map(|_| ());
+                ctx.spawn(future);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{executor::block_on, StreamExt};
+    use mc_ledger_db::MockLedger;
+    use mc_util_grpc::{streaming_channel, StreamingReceiver};
+    use mockall::predicate::eq;
+
+    fn tracked_tx(tombstone_block: u64) -> TrackedTx {
+        TrackedTx {
+            key_images: vec![KeyImage::from(1), KeyImage::from(2)],
+            output_public_keys: vec![CompressedRistrettoPublic::from(&[3u8; 32])],
+            tombstone_block,
+        }
+    }
+
+    fn not_pending() -> PendingKeyImages {
+        PendingKeyImages::default()
+    }
+
+    fn pending() -> PendingKeyImages {
+        PendingKeyImages::default().next([KeyImage::from(1), KeyImage::from(2)].into())
+    }
+
+    /// A ledger with `num_blocks` blocks, in which the key images and the
+    /// output of `tracked_tx` appear in the given blocks.
+    fn mock_ledger(
+        num_blocks: u64,
+        key_images_at: [Option<u64>; 2],
+        output_at: Option<u64>,
+    ) -> MockLedger {
+        let mut ledger = MockLedger::new();
+        ledger.expect_num_blocks().return_const(Ok(num_blocks));
+        for (i, block_index) in key_images_at.iter().enumerate() {
+            ledger
+                .expect_check_key_image()
+                .with(eq(KeyImage::from(i as u64 + 1)))
+                .return_const(Ok(*block_index));
+        }
+        match output_at {
+            Some(block_index) => {
+                ledger
+                    .expect_get_tx_out_index_by_public_key()
+                    .return_const(Ok(42));
+                ledger
+                    .expect_get_block_index_by_tx_out_index()
+                    .with(eq(42))
+                    .return_const(Ok(block_index));
+            }
+            None => {
+                ledger
+                    .expect_get_tx_out_index_by_public_key()
+                    .return_const(Err(LedgerError::NotFound));
+            }
+        }
+        ledger
+    }
+
+    #[test]
+    fn status_not_found() {
+        let ledger = mock_ledger(10, [None, None], None);
+        let response = tracked_tx(20).status(&ledger, &not_pending()).unwrap();
+        assert_eq!(response.get_status(), TxFinalityStatus::NotFound);
+        assert_eq!(response.get_block_count(), 10);
+    }
+
+    #[test]
+    fn status_pending() {
+        let ledger = mock_ledger(10, [None, None], None);
+        let response = tracked_tx(20).status(&ledger, &pending()).unwrap();
+        assert_eq!(response.get_status(), TxFinalityStatus::Pending);
+    }
+
+    #[test]
+    fn status_externalized() {
+        let ledger = mock_ledger(10, [Some(7), Some(7)], Some(7));
+        let response = tracked_tx(20).status(&ledger, &not_pending()).unwrap();
+        assert_eq!(response.get_status(), TxFinalityStatus::Externalized);
+        assert_eq!(response.get_block_index(), 7);
+    }
+
+    #[test]
+    fn status_key_image_spent_elsewhere() {
+        // Only one of the key images was spent.
+        let ledger = mock_ledger(10, [None, Some(7)], None);
+        let response = tracked_tx(20).status(&ledger, &not_pending()).unwrap();
+        assert_eq!(response.get_status(), TxFinalityStatus::KeyImageSpent);
+        assert_eq!(response.get_block_index(), 7);
+
+        // The key images were spent without creating the output.
+        let ledger = mock_ledger(10, [Some(7), Some(7)], None);
+        let response = tracked_tx(20).status(&ledger, &not_pending()).unwrap();
+        assert_eq!(response.get_status(), TxFinalityStatus::KeyImageSpent);
+    }
+
+    #[test]
+    fn status_tombstoned() {
+        let ledger = mock_ledger(20, [None, None], None);
+        let response = tracked_tx(20).status(&ledger, &pending()).unwrap();
+        assert_eq!(response.get_status(), TxFinalityStatus::Tombstoned);
+
+        // A tombstone block of zero is never reached.
+        let response = tracked_tx(0).status(&ledger, &not_pending()).unwrap();
+        assert_eq!(response.get_status(), TxFinalityStatus::NotFound);
+    }
+
+    /// A stream of the status of `tracked_tx(20)`, started at `now`.
+    fn stream(now: Instant) -> (TxStatusStream, StreamingReceiver<TxStatusResponse>) {
+        let (sender, receiver) = streaming_channel(TX_STATUS_CHANNEL_SIZE);
+        (TxStatusStream::new(tracked_tx(20), sender, now), receiver)
+    }
+
+    #[test]
+    fn stream_sends_changes_and_keepalives() {
+        let ledger = mock_ledger(10, [None, None], None);
+        let now = Instant::now();
+        let (mut stream, mut receiver) = stream(now);
+
+        assert!(stream.poll(&ledger, &not_pending(), true, now));
+        let response = block_on(receiver.next()).unwrap().unwrap();
+        assert_eq!(response.get_status(), TxFinalityStatus::NotFound);
+
+        // Nothing is sent while the status stays the same...
+        assert!(stream.poll(&ledger, &not_pending(), true, now + TX_STATUS_POLL_INTERVAL));
+        assert!(receiver.try_next().is_err());
+
+        // ...until the keepalive interval elapsed.
+        assert!(stream.poll(
+            &ledger,
+            &not_pending(),
+            true,
+            now + TX_STATUS_KEEPALIVE_INTERVAL
+        ));
+        let response = block_on(receiver.next()).unwrap().unwrap();
+        assert_eq!(response.get_status(), TxFinalityStatus::NotFound);
+
+        // The stream is over once a final status is sent.
+        let ledger = mock_ledger(10, [Some(7), Some(7)], Some(7));
+        assert!(!stream.poll(
+            &ledger,
+            &not_pending(),
+            true,
+            now + TX_STATUS_KEEPALIVE_INTERVAL
+        ));
+        let response = block_on(receiver.next()).unwrap().unwrap();
+        assert_eq!(response.get_status(), TxFinalityStatus::Externalized);
+    }
+
+    #[test]
+    fn stream_ends_when_client_goes_away() {
+        let ledger = mock_ledger(10, [None, None], None);
+        let now = Instant::now();
+        let (mut stream, receiver) = stream(now);
+
+        assert!(stream.poll(&ledger, &not_pending(), true, now));
+        drop(receiver);
+        assert!(!stream.poll(&ledger, &not_pending(), true, now));
+    }
+
+    #[test]
+    fn stream_fails_past_max_duration() {
+        let ledger = mock_ledger(10, [None, None], None);
+        let now = Instant::now();
+        let (mut stream, mut receiver) = stream(now);
+
+        assert!(!stream.poll(
+            &ledger,
+            &not_pending(),
+            true,
+            now + TX_STATUS_MAX_STREAM_DURATION
+        ));
+        match block_on(receiver.next()) {
+            Some(Err(status)) => assert_eq!(status.code(), RpcStatusCode::DEADLINE_EXCEEDED),
+            other => panic!("Unexpected item {:?}", other),
+        }
+    }
+
+    #[test]
+    fn request_without_key_images_is_rejected() {
+        assert!(matches!(
+            TrackedTx::try_from(&TxStatusRequest::new()),
+            Err(ConsensusGrpcError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn request_with_too_many_key_images_or_outputs_is_rejected() {
+        let key_image = KeyImage::from(1);
+        let public_key = CompressedRistrettoPublic::from(&[3u8; 32]);
+
+        let mut request = TxStatusRequest::new();
+        request.set_key_images(vec![(&key_image).into(); MAX_INPUTS as usize].into());
+        request.set_output_public_keys(vec![(&public_key).into(); MAX_OUTPUTS as usize].into());
+        assert!(TrackedTx::try_from(&request).is_ok());
+
+        let mut too_many_key_images = request.clone();
+        too_many_key_images
+            .mut_key_images()
+            .push((&key_image).into());
+        assert!(matches!(
+            TrackedTx::try_from(&too_many_key_images),
+            Err(ConsensusGrpcError::InvalidArgument(_))
+        ));
+
+        let mut too_many_outputs = request;
+        too_many_outputs
+            .mut_output_public_keys()
+            .push((&public_key).into());
+        assert!(matches!(
+            TrackedTx::try_from(&too_many_outputs),
+            Err(ConsensusGrpcError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn stream_skips_lookups_while_nothing_changed() {
+        let ledger = mock_ledger(10, [None, None], None);
+        let now = Instant::now();
+        let (mut stream, mut receiver) = stream(now);
+
+        assert!(stream.poll(&ledger, &not_pending(), false, now));
+        let response = block_on(receiver.next()).unwrap().unwrap();
+        assert_eq!(response.get_status(), TxFinalityStatus::NotFound);
+
+        // Any lookup would fail, as this ledger expects none.
+        let unexpected_ledger = MockLedger::new();
+        assert!(stream.poll(
+            &unexpected_ledger,
+            &pending(),
+            false,
+            now + TX_STATUS_POLL_INTERVAL
+        ));
+        assert!(receiver.try_next().is_err());
+
+        // The status is looked up again once something changed.
+        assert!(stream.poll(&ledger, &pending(), true, now + TX_STATUS_POLL_INTERVAL));
+        let response = block_on(receiver.next()).unwrap().unwrap();
+        assert_eq!(response.get_status(), TxFinalityStatus::Pending);
+    }
+}
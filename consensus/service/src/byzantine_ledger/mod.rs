@@ -14,6 +14,8 @@ use crate::{
     byzantine_ledger::{task_message::TaskMessage, worker::ByzantineLedgerWorker},
     counters,
     mint_tx_manager::{MintTxManager, MintTxManagerError},
+    network_load::NetworkLoad,
+    pending_key_images::PendingKeyImages,
     tx_manager::{TxManager, TxManagerError},
 };
 use displaydoc::Display;
@@ -50,6 +52,11 @@ pub const IS_BEHIND_GRACE_PERIOD: Duration = Duration::from_secs(10);
 /// validation than is sometimes required.
 pub const MAX_PENDING_VALUES_TO_NOMINATE: usize = 100;
 
+/// Maximum number of pending values. Beyond it, a transaction submitted by a
+/// client only gets in by evicting the pending transaction with the lowest fee
+/// per byte.
+pub const MAX_PENDING_VALUES: usize = 500;
+
 pub struct ByzantineLedger {
     // Handle to a worker thread.
     worker_handle: Option<JoinHandle<()>>,
@@ -65,6 +72,12 @@ pub struct ByzantineLedger {
 
     // Highest consensus message issued by this node. (Set by the worker)
     highest_issued_msg: Arc<Mutex<Option<ConsensusMsg>>>,
+
+    // Key images of the transactions among this node's pending values. (Set by the worker)
+    pending_key_images: Arc<Mutex<Arc<PendingKeyImages>>>,
+
+    // Number of pending values and of transactions in recent blocks. (Updated by the worker)
+    network_load: Arc<Mutex<NetworkLoad>>,
 }
 
 /// An error type for mc-consensus-scp validation/combine callbacks.
@@ -208,6 +221,8 @@ impl ByzantineLedger {
         let is_behind = Arc::new(AtomicBool::new(false));
         let highest_peer_block = Arc::new(AtomicU64::new(0));
         let highest_issued_msg = Arc::new(Mutex::new(Option::<ConsensusMsg>::None));
+        let pending_key_images = Arc::new(Mutex::new(Arc::default()));
+        let network_load = Arc::new(Mutex::new(NetworkLoad::default()));
 
         // Start worker thread
         let worker_handle = {
@@ -232,6 +247,8 @@ impl ByzantineLedger {
                 is_behind.clone(),
                 highest_peer_block.clone(),
                 highest_issued_msg.clone(),
+                pending_key_images.clone(),
+                network_load.clone(),
                 logger,
             );
 
@@ -254,6 +271,8 @@ impl ByzantineLedger {
             is_behind,
             highest_peer_block,
             highest_issued_msg,
+            pending_key_images,
+            network_load,
         }
     }
 
@@ -303,6 +322,20 @@ impl ByzantineLedger {
     pub fn highest_peer_block(&self) -> u64 {
         self.highest_peer_block.load(Ordering::SeqCst)
     }
+
+    /// Get the key images of the transactions among this node's pending
+    /// values.
+    pub fn pending_key_images(&self) -> Arc<PendingKeyImages> {
+        self.pending_key_images
+            .lock()
+            .expect("mutex poisoned")
+            .clone()
+    }
+
+    /// Get the number of pending values and of transactions in recent blocks.
+    pub fn network_load(&self) -> NetworkLoad {
+        self.network_load.lock().expect("mutex poisoned").clone()
+    }
 }
 
 impl Drop for ByzantineLedger {
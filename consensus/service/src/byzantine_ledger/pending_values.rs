@@ -5,6 +5,7 @@
 use crate::{mint_tx_manager::MintTxManager, tx_manager::TxManager};
 use mc_peers::ConsensusValue;
 use std::{
+    cmp::Ordering,
     collections::{hash_map::Entry::Vacant, HashMap},
     sync::Arc,
     time::Instant,
@@ -119,6 +120,69 @@ impl<TXM: TxManager, MTXM: MintTxManager> PendingValues<TXM, MTXM> {
         self.pending_values.iter()
     }
 
+    /// Get the values to propose, at most `max_values` of them.
+    ///
+    /// While all pending values fit, they are proposed in the order they were
+    /// received. Otherwise, the queue is saturated and the values with the
+    /// highest fee per byte are preferred. Mint transactions, which do not pay
+    /// a fee, come first. Values with the same fee per byte keep the order
+    /// they were received in.
+    pub fn values_to_propose(&self, max_values: usize) -> Vec<ConsensusValue> {
+        if self.pending_values.len() <= max_values {
+            return self.pending_values.clone();
+        }
+
+        self.fees_per_byte()
+            .into_iter()
+            .take(max_values)
+            .map(|(_, value)| value.clone())
+            .collect()
+    }
+
+    /// Get the pending values along with their fee per byte, highest first.
+    /// Mint transactions, which do not pay a fee, have an infinite fee per
+    /// byte. Values with the same fee per byte keep the order they were
+    /// received in.
+    pub fn fees_per_byte(&self) -> Vec<(f64, &ConsensusValue)> {
+        let mut values: Vec<(f64, &ConsensusValue)> = self
+            .pending_values
+            .iter()
+            .map(|value| {
+                let fee_per_byte = match value {
+                    ConsensusValue::TxHash(tx_hash) => {
+                        self.tx_manager.fee_per_byte(tx_hash).unwrap_or(0.0)
+                    }
+                    ConsensusValue::MintConfigTx(_) | ConsensusValue::MintTx(_) => f64::INFINITY,
+                };
+                (fee_per_byte, value)
+            })
+            .collect();
+
+        // Sort by fee per byte, descending. The sort is stable.
+        values.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+        values
+    }
+
+    /// Remove the transaction with the lowest fee per byte, and return it.
+    /// Among transactions with the same fee per byte, the one received last is
+    /// removed. Mint transactions are never removed.
+    pub fn evict_lowest_fee_per_byte(&mut self) -> Option<ConsensusValue> {
+        let evicted = self
+            .fees_per_byte()
+            .into_iter()
+            .rev()
+            .find(|(fee_per_byte, _)| fee_per_byte.is_finite())
+            .map(|(_, value)| value.clone())?;
+
+        self.pending_values_map.remove(&evicted);
+        self.pending_values.retain(|value| value != &evicted);
+
+        // Invariant
+        assert_eq!(self.pending_values_map.len(), self.pending_values.len());
+
+        Some(evicted)
+    }
+
     /// Try and get the timestamp associated with a given value.
     pub fn get_timestamp_for_value(&self, tx_hash: &ConsensusValue) -> Option<Instant> {
         self.pending_values_map.get(tx_hash).cloned().flatten()
@@ -309,4 +373,99 @@ mod tests {
             HashSet::from_iter(expected_pending_values),
         );
     }
+
+    #[test]
+    /// Should propose values in the order they were received while they fit.
+    fn test_values_to_propose_when_not_saturated() {
+        let mut tx_manager = MockTxManager::new();
+        let mint_tx_manager = MockMintTxManager::new();
+
+        let values: Vec<ConsensusValue> = (0..5u8).map(|i| TxHash([i; 32]).into()).collect();
+
+        // All values are considered valid for this test, and fees are not looked at.
+        tx_manager.expect_validate().return_const(Ok(()));
+        tx_manager.expect_fee_per_byte().never();
+
+        let mut pending_values =
+            PendingValues::new(Arc::new(tx_manager), Arc::new(mint_tx_manager));
+        for value in &values {
+            assert!(pending_values.push(value.clone(), None));
+        }
+
+        assert_eq!(pending_values.values_to_propose(5), values);
+        assert_eq!(pending_values.values_to_propose(10), values);
+    }
+
+    #[test]
+    /// Should prefer the values with the highest fee per byte when saturated.
+    fn test_values_to_propose_when_saturated() {
+        let mut tx_manager = MockTxManager::new();
+        let mint_tx_manager = MockMintTxManager::new();
+
+        let tx_hashes: Vec<TxHash> = (0..5u8).map(|i| TxHash([i; 32])).collect();
+        let fees_per_byte = [0.1, 0.3, 0.1, 0.2, 0.3];
+
+        tx_manager.expect_validate().return_const(Ok(()));
+        for (tx_hash, fee_per_byte) in tx_hashes.iter().zip(fees_per_byte) {
+            tx_manager
+                .expect_fee_per_byte()
+                .with(eq(*tx_hash))
+                .return_const(Some(fee_per_byte));
+        }
+
+        let mut pending_values =
+            PendingValues::new(Arc::new(tx_manager), Arc::new(mint_tx_manager));
+        for tx_hash in &tx_hashes {
+            assert!(pending_values.push((*tx_hash).into(), None));
+        }
+
+        // Ties are broken by the order in which values were received.
+        assert_eq!(
+            pending_values.values_to_propose(4),
+            vec![
+                tx_hashes[1].into(),
+                tx_hashes[4].into(),
+                tx_hashes[3].into(),
+                tx_hashes[0].into(),
+            ]
+        );
+    }
+
+    #[test]
+    /// Should evict the transaction with the lowest fee per byte, received
+    /// last.
+    fn test_evict_lowest_fee_per_byte() {
+        let mut tx_manager = MockTxManager::new();
+        let mint_tx_manager = MockMintTxManager::new();
+
+        let tx_hashes: Vec<TxHash> = (0..4u8).map(|i| TxHash([i; 32])).collect();
+        let fees_per_byte = [0.1, 0.3, 0.1, 0.2];
+
+        tx_manager.expect_validate().return_const(Ok(()));
+        for (tx_hash, fee_per_byte) in tx_hashes.iter().zip(fees_per_byte) {
+            tx_manager
+                .expect_fee_per_byte()
+                .with(eq(*tx_hash))
+                .return_const(Some(fee_per_byte));
+        }
+
+        let mut pending_values =
+            PendingValues::new(Arc::new(tx_manager), Arc::new(mint_tx_manager));
+        for tx_hash in &tx_hashes {
+            assert!(pending_values.push((*tx_hash).into(), None));
+        }
+
+        assert_eq!(
+            pending_values.evict_lowest_fee_per_byte(),
+            Some(tx_hashes[2].into())
+        );
+        assert_eq!(
+            pending_values.evict_lowest_fee_per_byte(),
+            Some(tx_hashes[0].into())
+        );
+        assert_eq!(
+            pending_values.iter().cloned().collect::<Vec<_>>(),
+            vec![tx_hashes[1].into(), tx_hashes[3].into()]
+        );
+    }
 }
@@ -3,10 +3,13 @@
 use crate::{
     byzantine_ledger::{
         ledger_sync_state::LedgerSyncState, pending_values::PendingValues,
-        task_message::TaskMessage, IS_BEHIND_GRACE_PERIOD, MAX_PENDING_VALUES_TO_NOMINATE,
+        task_message::TaskMessage, IS_BEHIND_GRACE_PERIOD, MAX_PENDING_VALUES,
+        MAX_PENDING_VALUES_TO_NOMINATE,
     },
     counters,
     mint_tx_manager::MintTxManager,
+    network_load::NetworkLoad,
+    pending_key_images::PendingKeyImages,
     tx_manager::TxManager,
 };
 use mc_common::{
@@ -26,13 +29,13 @@ use mc_peers::{
     Broadcast, ConsensusConnection, ConsensusMsg, ConsensusValue, Error as PeerError,
     RetryableConsensusConnection, VerifiedConsensusMsg,
 };
-use mc_transaction_core::{tx::TxHash, BlockData};
+use mc_transaction_core::{ring_signature::KeyImage, tx::TxHash, BlockData};
 use mc_util_metered_channel::Receiver;
 use mc_util_telemetry::{mark_span_as_active, start_block_span, tracer, Tracer};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use std::{
     cmp::min,
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeSet, HashMap, HashSet},
     sync::{
         atomic::{AtomicBool, AtomicU64, Ordering},
         Arc, Mutex,
@@ -94,6 +97,12 @@ pub struct ByzantineLedgerWorker<
     // Highest consensus message issued by this node.
     highest_issued_msg: Arc<Mutex<Option<ConsensusMsg>>>,
 
+    // The worker sets this to the key images of the transactions among its pending values.
+    pending_key_images: Arc<Mutex<Arc<PendingKeyImages>>>,
+
+    // The worker updates this with the number of pending values and of transactions per block.
+    network_load: Arc<Mutex<NetworkLoad>>,
+
     // Network state, used to track if we've fallen behind.
     network_state: SCPNetworkState,
 
@@ -141,6 +150,10 @@ impl<
     ///   network agrees on.
     /// * `highest_issued_msg` - Worker sets to highest consensus message issued
     ///   by this node.
+    /// * `pending_key_images` - Worker sets to the key images of the
+    ///   transactions among its pending values.
+    /// * `network_load` - Worker updates with the number of pending values and
+    ///   of transactions in each block it appends.
     /// * `logger` - Logger instance.
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -157,6 +170,8 @@ impl<
         is_behind: Arc<AtomicBool>,
         highest_peer_block: Arc<AtomicU64>,
         highest_issued_msg: Arc<Mutex<Option<ConsensusMsg>>>,
+        pending_key_images: Arc<Mutex<Arc<PendingKeyImages>>>,
+        network_load: Arc<Mutex<NetworkLoad>>,
         logger: Logger,
     ) -> Self {
         let current_slot_index = ledger.num_blocks().unwrap();
@@ -171,6 +186,8 @@ impl<
             is_behind,
             highest_peer_block,
             highest_issued_msg,
+            pending_key_images,
+            network_load,
             ledger,
             tx_manager: tx_manager.clone(),
             mint_tx_manager: mint_tx_manager.clone(),
@@ -249,6 +266,8 @@ impl<
                 self.scp_node.reset_slot_index(self.current_slot_index);
                 // Clear any pending values that might no longer be valid.
                 self.pending_values.clear_invalid_values();
+                self.update_pending_key_images();
+                self.update_pending_fees();
                 if !self.pending_values.is_empty() {
                     // These values should be proposed for nomination.
                     self.need_nominate = true;
@@ -314,6 +333,10 @@ impl<
 
         // Update metrics.
         self.update_current_slot_metrics();
+        self.network_load
+            .lock()
+            .expect("mutex poisoned: network_load")
+            .set_num_pending_values(self.pending_values.len());
 
         true
     }
@@ -391,6 +414,7 @@ impl<
     // Reads tasks from the task queue.
     // Returns false if the worker has been asked to stop.
     fn receive_tasks(&mut self) -> bool {
+        let mut pushed_values = false;
         for task_msg in self.tasks.try_iter() {
             match task_msg {
                 // Transactions submitted by clients. These are assumed to be well-formed, but may
@@ -399,6 +423,21 @@ impl<
                     for tx_hash in new_values {
                         if self.pending_values.push(tx_hash, timestamp) {
                             self.need_nominate = true;
+                            pushed_values = true;
+                        }
+                    }
+
+                    // Values submitted to this node by clients are admitted past the limit only
+                    // if they pay more per byte than a pending transaction, which makes room
+                    // for them.
+                    if timestamp.is_some() {
+                        while self.pending_values.len() > MAX_PENDING_VALUES {
+                            match self.pending_values.evict_lowest_fee_per_byte() {
+                                Some(evicted) => {
+                                    log::debug!(self.logger, "Evicted pending value {}", evicted)
+                                }
+                                None => break,
+                            }
                         }
                     }
                 }
@@ -424,21 +463,64 @@ impl<
             self.highest_peer_block.store(peer_block, Ordering::SeqCst);
         }
 
+        if pushed_values {
+            self.update_pending_key_images();
+            self.update_pending_fees();
+        }
+
         true
     }
 
+    // Update pending_key_images from the current pending values.
+    fn update_pending_key_images(&self) {
+        let key_images: HashSet<KeyImage> = self
+            .pending_values
+            .iter()
+            .filter_map(|value| match value {
+                ConsensusValue::TxHash(tx_hash) => self.tx_manager.get_context(tx_hash),
+                ConsensusValue::MintConfigTx(_) | ConsensusValue::MintTx(_) => None,
+            })
+            .flat_map(|context| context.key_images().clone())
+            .collect();
+
+        let mut pending_key_images = self
+            .pending_key_images
+            .lock()
+            .expect("mutex poisoned: pending_key_images");
+        *pending_key_images = Arc::new(pending_key_images.next(key_images));
+    }
+
+    // Update the fees per byte of the pending values in network_load. They only
+    // matter once values compete for being proposed, and are not looked up before.
+    fn update_pending_fees(&self) {
+        let fees_per_byte = if self.pending_values.len() > MAX_PENDING_VALUES_TO_NOMINATE {
+            self.pending_values
+                .fees_per_byte()
+                .into_iter()
+                .map(|(fee_per_byte, _)| fee_per_byte)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        self.network_load
+            .lock()
+            .expect("mutex poisoned: network_load")
+            .set_pending_fees_per_byte(fees_per_byte);
+    }
+
     // Propose pending values for nomination in the current slot.
     fn propose_pending_values(&mut self) {
         assert!(!self.pending_values.is_empty());
 
         // Fairness heuristics:
-        // * Values are proposed in the order that they were received.
+        // * Values are proposed in the order that they were received, unless there are
+        //   too many of them, in which case the highest fee per byte goes first.
         // * Each node limits the total number of values it proposes per slot.
         let values = self
             .pending_values
-            .iter()
-            .take(MAX_PENDING_VALUES_TO_NOMINATE)
-            .cloned()
+            .values_to_propose(MAX_PENDING_VALUES_TO_NOMINATE)
+            .into_iter()
             .collect();
 
         let msg_opt = self
@@ -585,6 +667,15 @@ impl<
 
         counters::TX_EXTERNALIZED_COUNT.inc_by(externalized.len() as u64);
 
+        let num_txs = externalized
+            .iter()
+            .filter(|value| matches!(value, ConsensusValue::TxHash(_)))
+            .count();
+        self.network_load
+            .lock()
+            .expect("mutex poisoned: network_load")
+            .record_block(num_txs);
+
         // Update current slot index.
         self.current_slot_index = {
             let current_slot_index: SlotIndex = self.ledger.num_blocks().unwrap();
@@ -620,6 +711,8 @@ impl<
             pending_values_len_before_purge - pending_values_len_before_clear_invalid,
             pending_values_len_before_clear_invalid - self.pending_values.len(),
         );
+        self.update_pending_key_images();
+        self.update_pending_fees();
 
         // Previous slot metrics.
         counters::PREV_SLOT_NUMBER.set((self.current_slot_index - 1) as i64);
@@ -888,6 +981,8 @@ mod tests {
             IS_BEHIND_GRACE_PERIOD, MAX_PENDING_VALUES_TO_NOMINATE,
         },
         mint_tx_manager::{MintTxManagerImpl, MockMintTxManager},
+        network_load::NetworkLoad,
+        pending_key_images::PendingKeyImages,
         tx_manager::{MockTxManager, TxManager, TxManagerError, TxManagerImpl},
         validators::DefaultTxManagerUntrustedInterfaces,
     };
@@ -897,7 +992,7 @@ mod tests {
         NodeID, ResponderId,
     };
     use mc_connection::ConnectionManager;
-    use mc_consensus_enclave::GovernorsMap;
+    use mc_consensus_enclave::{GovernorsMap, WellFormedTxContext};
     use mc_consensus_enclave_mock::{ConsensusServiceMockEnclave, MockConsensusEnclave};
     use mc_consensus_scp::{
         msg::{NominatePayload, Topic::Nominate},
@@ -911,6 +1006,7 @@ mod tests {
     use mc_peers::{ConsensusMsg, ConsensusValue, MockBroadcast, VerifiedConsensusMsg};
     use mc_peers_test_utils::MockPeerConnection;
     use mc_transaction_core::{
+        ring_signature::KeyImage,
         tx::{Tx, TxHash},
         validation::TransactionValidationError,
         Block, BlockContents, BlockVersion, TokenId,
@@ -925,6 +1021,7 @@ mod tests {
     use rand::rngs::StdRng;
     use rand_core::SeedableRng;
     use std::{
+        collections::HashSet,
         convert::TryFrom,
         ops::Add,
         sync::{
@@ -1026,6 +1123,8 @@ mod tests {
             Arc::new(AtomicBool::new(false)),
             Arc::new(AtomicU64::new(0)),
             Arc::new(Mutex::new(Option::<ConsensusMsg>::None)),
+            Arc::new(Mutex::new(Arc::default())),
+            Arc::new(Mutex::new(NetworkLoad::default())),
             logger,
         );
 
@@ -1078,6 +1177,8 @@ mod tests {
             Arc::new(AtomicBool::new(false)),
             Arc::new(AtomicU64::new(0)),
             Arc::new(Mutex::new(Option::<ConsensusMsg>::None)),
+            Arc::new(Mutex::new(Arc::default())),
+            Arc::new(Mutex::new(NetworkLoad::default())),
             logger,
         );
 
@@ -1197,6 +1298,8 @@ mod tests {
             Arc::new(AtomicBool::new(false)),
             Arc::new(AtomicU64::new(0)),
             Arc::new(Mutex::new(Option::<ConsensusMsg>::None)),
+            Arc::new(Mutex::new(Arc::default())),
+            Arc::new(Mutex::new(NetworkLoad::default())),
             logger,
         );
 
@@ -1258,6 +1361,8 @@ mod tests {
             Arc::new(AtomicBool::new(false)),
             Arc::new(AtomicU64::new(0)),
             Arc::new(Mutex::new(Option::<ConsensusMsg>::None)),
+            Arc::new(Mutex::new(Arc::default())),
+            Arc::new(Mutex::new(NetworkLoad::default())),
             logger,
         );
 
@@ -1359,6 +1464,8 @@ mod tests {
             Arc::new(AtomicBool::new(false)),
             Arc::new(AtomicU64::new(0)),
             Arc::new(Mutex::new(Option::<ConsensusMsg>::None)),
+            Arc::new(Mutex::new(Arc::default())),
+            Arc::new(Mutex::new(NetworkLoad::default())),
             logger,
         );
 
@@ -1400,6 +1507,84 @@ mod tests {
         assert_eq!(worker.pending_consensus_msgs.len(), 1);
     }
 
+    /// Should publish the key images of the transactions among the pending
+    /// values.
+    #[test_with_logger]
+    fn receive_tasks_updates_pending_key_images(logger: Logger) {
+        let (node_id, _local_node_uri, msg_signer_key) = get_local_node_config(11);
+        let mut rng: StdRng = SeedableRng::from_seed([97u8; 32]);
+        let peers = get_peers(&[22, 33], &mut rng);
+        let quorum_set =
+            QuorumSet::new_with_node_ids(2, vec![peers[0].id.clone(), peers[1].id.clone()]);
+
+        let num_blocks = 12;
+        let (enclave, scp_node, ledger, ledger_sync, mut tx_manager, mint_tx_manager, broadcast) =
+            get_mocks(&node_id, &quorum_set, num_blocks);
+
+        let connection_manager = get_connection_manager(&node_id, &peers, &logger);
+        let (task_sender, task_receiver) = get_channel();
+
+        // Transactions submitted by clients, each spending two key images.
+        let tx_contexts: Vec<_> = (0..3u8)
+            .map(|i| {
+                let key_images = vec![KeyImage::from(i as u64), KeyImage::from(100 + i as u64)];
+                WellFormedTxContext::new(0, TxHash([i; 32]), 20, key_images, vec![], vec![])
+            })
+            .collect();
+
+        for tx_context in &tx_contexts {
+            tx_manager
+                .expect_validate()
+                .with(eq(*tx_context.tx_hash()))
+                .return_const(Ok(()));
+            tx_manager
+                .expect_get_context()
+                .with(eq(*tx_context.tx_hash()))
+                .return_const(Some(Arc::new(tx_context.clone())));
+        }
+
+        let pending_key_images = Arc::new(Mutex::new(Arc::default()));
+        let mut worker = ByzantineLedgerWorker::new(
+            enclave,
+            Box::new(scp_node),
+            msg_signer_key,
+            ledger,
+            ledger_sync,
+            connection_manager,
+            Arc::new(tx_manager),
+            Arc::new(mint_tx_manager),
+            Arc::new(Mutex::new(broadcast)),
+            task_receiver,
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(AtomicU64::new(0)),
+            Arc::new(Mutex::new(Option::<ConsensusMsg>::None)),
+            pending_key_images.clone(),
+            Arc::new(Mutex::new(NetworkLoad::default())),
+            logger,
+        );
+
+        // Submit the transactions.
+        let values = tx_contexts
+            .iter()
+            .map(|tx_context| ConsensusValue::TxHash(*tx_context.tx_hash()))
+            .collect();
+        task_sender
+            .send(TaskMessage::Values(Some(Instant::now()), values))
+            .unwrap();
+
+        assert!(worker.receive_tasks());
+        assert_eq!(worker.pending_values.len(), 3);
+
+        let expected: HashSet<KeyImage> = tx_contexts
+            .iter()
+            .flat_map(|tx_context| tx_context.key_images().clone())
+            .collect();
+        assert_eq!(
+            **pending_key_images.lock().unwrap(),
+            PendingKeyImages::default().next(expected)
+        );
+    }
+
     /// Should maintain the invariant that pending_values and pending_values map
     /// only contain tx_hashes corresponding to transactions that are valid
     /// w.r.t the current ledger.
@@ -1445,6 +1630,8 @@ mod tests {
             Arc::new(AtomicBool::new(false)),
             Arc::new(AtomicU64::new(0)),
             Arc::new(Mutex::new(Option::<ConsensusMsg>::None)),
+            Arc::new(Mutex::new(Arc::default())),
+            Arc::new(Mutex::new(NetworkLoad::default())),
             logger,
         );
 
@@ -1520,6 +1707,9 @@ mod tests {
         // `validate` will be called one for each pushed value.
         tx_manager.expect_validate().return_const(Ok(()));
 
+        // There are too many pending values, so they are ordered by fee.
+        tx_manager.expect_fee_per_byte().return_const(Some(1.0));
+
         // Up to MAX_PENDING_VALUES_TO_NOMINATE values should be proposed to the
         // scp_node.
         scp_node
@@ -1542,6 +1732,8 @@ mod tests {
             Arc::new(AtomicBool::new(false)),
             Arc::new(AtomicU64::new(0)),
             Arc::new(Mutex::new(Option::<ConsensusMsg>::None)),
+            Arc::new(Mutex::new(Arc::default())),
+            Arc::new(Mutex::new(NetworkLoad::default())),
             logger,
         );
 
@@ -1697,6 +1889,8 @@ mod tests {
             Arc::new(AtomicBool::new(false)),
             Arc::new(AtomicU64::new(0)),
             Arc::new(Mutex::new(Option::<ConsensusMsg>::None)),
+            Arc::new(Mutex::new(Arc::default())),
+            Arc::new(Mutex::new(NetworkLoad::default())),
             logger,
         );
 
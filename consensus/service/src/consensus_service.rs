@@ -8,7 +8,9 @@ use crate::{
     byzantine_ledger::ByzantineLedger,
     counters,
     mint_tx_manager::MintTxManager,
+    network_load::NetworkLoad,
     peer_keepalive::PeerKeepalive,
+    pending_key_images::PendingKeyImages,
     tx_manager::TxManager,
 };
 use base64::{encode_config, URL_SAFE};
@@ -103,6 +105,13 @@ pub struct IncomingConsensusMsg {
 pub type ProposeTxCallback =
     Arc<dyn Fn(ConsensusValue, Option<&NodeID>, Option<&ResponderId>) + Sync + Send>;
 
+/// A callback returning the key images of the transactions among
+/// ByzantineLedger's pending values.
+pub type PendingKeyImagesFn = Arc<dyn Fn() -> Arc<PendingKeyImages> + Sync + Send>;
+
+/// A callback returning the network load observed by ByzantineLedger.
+pub type GetNetworkLoadFn = Arc<dyn Fn() -> NetworkLoad + Sync + Send>;
+
 pub struct ConsensusService<
     E: ConsensusEnclave + Clone + Send + Sync + 'static,
     R: RaClient + Send + Sync + 'static,
@@ -343,6 +352,8 @@ impl<
                 self.tx_manager.clone(),
                 self.mint_tx_manager.clone(),
                 self.create_is_serving_user_requests_fn(),
+                self.create_pending_key_images_fn(),
+                self.create_get_network_load_fn(),
                 self.client_authenticator.clone(),
                 self.logger.clone(),
             ));
@@ -579,6 +590,40 @@ impl<
         })
     }
 
+    /// Creates a function that returns the key images of the transactions
+    /// that ByzantineLedger is trying to get externalized.
+    fn create_pending_key_images_fn(&self) -> PendingKeyImagesFn {
+        let byzantine_ledger = self
+            .byzantine_ledger
+            .as_ref()
+            .map(Arc::downgrade)
+            .expect("Server was not initialized");
+
+        Arc::new(move || {
+            byzantine_ledger
+                .upgrade()
+                .and_then(|ledger| ledger.get().map(|ledger| ledger.pending_key_images()))
+                .unwrap_or_default()
+        })
+    }
+
+    /// Creates a function that returns the network load observed by
+    /// ByzantineLedger.
+    fn create_get_network_load_fn(&self) -> GetNetworkLoadFn {
+        let byzantine_ledger = self
+            .byzantine_ledger
+            .as_ref()
+            .map(Arc::downgrade)
+            .expect("Server was not initialized");
+
+        Arc::new(move || {
+            byzantine_ledger
+                .upgrade()
+                .and_then(|ledger| ledger.get().map(|ledger| ledger.network_load()))
+                .unwrap_or_default()
+        })
+    }
+
     /// Creates a function that feeds client values into ByzantineLedger and
     /// broadcasts it to our peers.
     fn create_scp_client_value_sender_fn(&self) -> ProposeTxCallback {
@@ -16,4 +16,6 @@ mod api;
 mod background_work_queue;
 mod byzantine_ledger;
 mod counters;
+mod network_load;
 mod peer_keepalive;
+mod pending_key_images;
@@ -0,0 +1,212 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Tracks the load of the consensus network, as seen by this node, and
+//! estimates the fee a transaction should pay to be proposed without delay.
+
+use crate::byzantine_ledger::MAX_PENDING_VALUES_TO_NOMINATE;
+use mc_consensus_enclave::SMALLEST_MINIMUM_FEE_LOG2;
+use mc_transaction_core::constants::MAX_TRANSACTIONS_PER_BLOCK;
+use std::{cmp::Ordering, collections::VecDeque};
+
+/// Number of recent blocks whose fill is averaged.
+pub const RECENT_BLOCKS_WINDOW: usize = 20;
+
+/// Average block fill above which the fee estimate starts rising.
+pub const TARGET_BLOCK_FILL: f64 = 0.5;
+
+/// The fee estimate never exceeds this multiple of the minimum fee.
+pub const MAX_FEE_MULTIPLIER: f64 = 10.0;
+
+/// The load of the network. (Updated by the byzantine ledger worker)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NetworkLoad {
+    /// Number of values this node is trying to get externalized.
+    num_pending_values: usize,
+
+    /// Number of transactions in each of the most recent blocks, oldest first.
+    recent_block_num_txs: VecDeque<usize>,
+
+    /// Fees per byte of the pending values, highest first, while there are
+    /// more of them than this node proposes in a slot. Empty otherwise.
+    pending_fees_per_byte: Vec<f64>,
+}
+
+impl NetworkLoad {
+    /// Number of values this node is trying to get externalized.
+    pub fn num_pending_values(&self) -> usize {
+        self.num_pending_values
+    }
+
+    /// Set the number of values this node is trying to get externalized.
+    pub fn set_num_pending_values(&mut self, num_pending_values: usize) {
+        self.num_pending_values = num_pending_values;
+    }
+
+    /// Set the fees per byte of the pending values, which are sorted here.
+    pub fn set_pending_fees_per_byte(&mut self, mut pending_fees_per_byte: Vec<f64>) {
+        pending_fees_per_byte.sort_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+        self.pending_fees_per_byte = pending_fees_per_byte;
+    }
+
+    /// The lowest fee per byte of a pending transaction, if values compete
+    /// for being proposed. A transaction paying more can take its place when
+    /// there is no room for more pending values.
+    pub fn lowest_pending_fee_per_byte(&self) -> Option<f64> {
+        self.pending_fees_per_byte
+            .iter()
+            .rev()
+            .find(|fee_per_byte| fee_per_byte.is_finite())
+            .cloned()
+    }
+
+    /// Record the number of transactions in a block appended to the ledger.
+    pub fn record_block(&mut self, num_txs: usize) {
+        self.recent_block_num_txs.push_back(num_txs);
+        if self.recent_block_num_txs.len() > RECENT_BLOCKS_WINDOW {
+            self.recent_block_num_txs.pop_front();
+        }
+    }
+
+    /// True if there are more pending values than this node proposes in a
+    /// slot, so that values compete for being proposed.
+    pub fn is_saturated(&self) -> bool {
+        self.num_pending_values > MAX_PENDING_VALUES_TO_NOMINATE
+    }
+
+    /// The average fraction of `MAX_TRANSACTIONS_PER_BLOCK` used by recent
+    /// blocks, or zero if no block was recorded.
+    pub fn recent_block_fill(&self) -> f64 {
+        if self.recent_block_num_txs.is_empty() {
+            return 0.0;
+        }
+        let num_txs: usize = self.recent_block_num_txs.iter().sum();
+        num_txs as f64 / (self.recent_block_num_txs.len() * MAX_TRANSACTIONS_PER_BLOCK) as f64
+    }
+
+    /// Estimate the fee a transaction should pay to be proposed without delay.
+    ///
+    /// The estimate is the minimum fee while the pending values fit in a
+    /// single proposal and recent blocks are at most `TARGET_BLOCK_FILL` full.
+    /// Beyond that, it grows with the excess of pending values and of block
+    /// fill, up to `MAX_FEE_MULTIPLIER` times the minimum fee. It is rounded up
+    /// to the smallest increment which raises the priority of a transaction.
+    ///
+    /// Values compete on fee per byte once there are more of them than fit in
+    /// a proposal, so the estimate is also at least what a transaction of
+    /// `tx_size` bytes must pay to rank among the values proposed in a slot.
+    ///
+    /// # Arguments
+    /// * `minimum_fee` - The minimum fee of the token the fee is paid in.
+    /// * `tx_size` - The size of the transaction in bytes, or zero if unknown.
+    pub fn estimate_fee(&self, minimum_fee: u64, tx_size: usize) -> u64 {
+        let queue_excess = self
+            .num_pending_values
+            .saturating_sub(MAX_PENDING_VALUES_TO_NOMINATE) as f64
+            / MAX_PENDING_VALUES_TO_NOMINATE as f64;
+        let fill_excess =
+            ((self.recent_block_fill() - TARGET_BLOCK_FILL) / (1.0 - TARGET_BLOCK_FILL)).max(0.0);
+        let multiplier = (1.0 + queue_excess + fill_excess).min(MAX_FEE_MULTIPLIER);
+
+        let fee = (minimum_fee as f64 * multiplier).ceil() as u64;
+        let step = (minimum_fee >> SMALLEST_MINIMUM_FEE_LOG2).max(1);
+        let fee = (fee + step - 1) / step * step;
+
+        // The priority of a transaction is its fee in steps, and its rank is its
+        // priority divided by its size. Beat the last value that gets proposed.
+        let competing_fee = self
+            .pending_fees_per_byte
+            .get(MAX_PENDING_VALUES_TO_NOMINATE - 1)
+            .filter(|fee_per_byte| fee_per_byte.is_finite() && tx_size > 0)
+            .map(|fee_per_byte| ((fee_per_byte * tx_size as f64).floor() as u64 + 1) * step)
+            .unwrap_or(0);
+
+        fee.max(competing_fee).max(minimum_fee)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINIMUM_FEE: u64 = 400_000_000;
+
+    #[test]
+    fn estimate_is_minimum_fee_when_idle() {
+        let mut load = NetworkLoad::default();
+        assert_eq!(load.estimate_fee(MINIMUM_FEE, 0), MINIMUM_FEE);
+
+        load.set_num_pending_values(MAX_PENDING_VALUES_TO_NOMINATE);
+        load.record_block(MAX_TRANSACTIONS_PER_BLOCK / 2);
+        assert!(!load.is_saturated());
+        assert_eq!(load.estimate_fee(MINIMUM_FEE, 0), MINIMUM_FEE);
+    }
+
+    #[test]
+    fn estimate_grows_with_pending_values() {
+        let mut load = NetworkLoad::default();
+        load.set_num_pending_values(MAX_PENDING_VALUES_TO_NOMINATE * 2);
+        assert!(load.is_saturated());
+        assert_eq!(load.estimate_fee(MINIMUM_FEE, 0), 2 * MINIMUM_FEE);
+
+        load.set_num_pending_values(MAX_PENDING_VALUES_TO_NOMINATE * 100);
+        assert_eq!(
+            load.estimate_fee(MINIMUM_FEE, 0),
+            MAX_FEE_MULTIPLIER as u64 * MINIMUM_FEE
+        );
+    }
+
+    #[test]
+    fn estimate_grows_with_block_fill() {
+        let mut load = NetworkLoad::default();
+        for _ in 0..RECENT_BLOCKS_WINDOW {
+            load.record_block(MAX_TRANSACTIONS_PER_BLOCK * 3 / 4);
+        }
+        assert_eq!(load.recent_block_fill(), 0.75);
+        assert_eq!(load.estimate_fee(MINIMUM_FEE, 0), MINIMUM_FEE * 3 / 2);
+
+        // Only the most recent blocks count.
+        for _ in 0..RECENT_BLOCKS_WINDOW {
+            load.record_block(0);
+        }
+        assert_eq!(load.recent_block_fill(), 0.0);
+        assert_eq!(load.estimate_fee(MINIMUM_FEE, 0), MINIMUM_FEE);
+    }
+
+    #[test]
+    fn estimate_is_rounded_to_priority_steps() {
+        let mut load = NetworkLoad::default();
+        load.set_num_pending_values(MAX_PENDING_VALUES_TO_NOMINATE + 1);
+
+        let step = MINIMUM_FEE >> SMALLEST_MINIMUM_FEE_LOG2;
+        let fee = load.estimate_fee(MINIMUM_FEE, 0);
+        assert_eq!(fee % step, 0);
+        assert_eq!(fee, MINIMUM_FEE + 2 * step);
+    }
+
+    #[test]
+    fn estimate_grows_with_tx_size() {
+        let step = MINIMUM_FEE >> SMALLEST_MINIMUM_FEE_LOG2;
+        let mut load = NetworkLoad::default();
+        load.set_num_pending_values(MAX_PENDING_VALUES_TO_NOMINATE + 1);
+        let heuristic_fee = load.estimate_fee(MINIMUM_FEE, 0);
+
+        // A minimum fee transaction has a priority of 2^SMALLEST_MINIMUM_FEE_LOG2.
+        // Here, those of 1000 bytes are the last to make it into a proposal.
+        let mut fees_per_byte = vec![1.0; MAX_PENDING_VALUES_TO_NOMINATE - 1];
+        fees_per_byte.push(0.128);
+        fees_per_byte.push(0.1);
+        load.set_pending_fees_per_byte(fees_per_byte);
+        assert_eq!(load.lowest_pending_fee_per_byte(), Some(0.1));
+
+        // Small transactions get the same estimate as before.
+        assert_eq!(load.estimate_fee(MINIMUM_FEE, 1000), heuristic_fee);
+
+        // Large ones must outbid the last proposed value per byte.
+        assert_eq!(load.estimate_fee(MINIMUM_FEE, 10_000), 1281 * step);
+
+        // Without pending fees, only the heuristic counts.
+        load.set_pending_fees_per_byte(Vec::new());
+        assert_eq!(load.lowest_pending_fee_per_byte(), None);
+        assert_eq!(load.estimate_fee(MINIMUM_FEE, 10_000), heuristic_fee);
+    }
+}
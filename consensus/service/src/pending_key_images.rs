@@ -0,0 +1,69 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! The key images of the transactions this node is trying to get externalized.
+
+use mc_transaction_core::ring_signature::KeyImage;
+use std::collections::HashSet;
+
+/// The key images of the transactions among this node's pending values.
+/// (Updated by the byzantine ledger worker)
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PendingKeyImages {
+    /// Incremented each time the key images change.
+    version: u64,
+
+    /// The key images.
+    key_images: HashSet<KeyImage>,
+}
+
+impl PendingKeyImages {
+    /// The pending key images that follow these ones. Keeps the version if
+    /// the key images did not change.
+    pub fn next(&self, key_images: HashSet<KeyImage>) -> Self {
+        let version = if key_images == self.key_images {
+            self.version
+        } else {
+            self.version.wrapping_add(1)
+        };
+        Self {
+            version,
+            key_images,
+        }
+    }
+
+    /// Incremented each time the key images change.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Check if all the given key images are pending.
+    pub fn contains_all(&self, key_images: &[KeyImage]) -> bool {
+        !key_images.is_empty()
+            && key_images
+                .iter()
+                .all(|key_image| self.key_images.contains(key_image))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_bumps_version_only_on_change() {
+        let key_images: HashSet<KeyImage> = [KeyImage::from(1), KeyImage::from(2)].into();
+
+        let pending = PendingKeyImages::default().next(key_images.clone());
+        assert_eq!(pending.version(), 1);
+        assert!(pending.contains_all(&[KeyImage::from(2), KeyImage::from(1)]));
+        assert!(!pending.contains_all(&[KeyImage::from(1), KeyImage::from(3)]));
+        assert!(!pending.contains_all(&[]));
+
+        let same = pending.next(key_images);
+        assert_eq!(same.version(), 1);
+
+        let changed = same.next(HashSet::default());
+        assert_eq!(changed.version(), 2);
+        assert!(!changed.contains_all(&[KeyImage::from(1)]));
+    }
+}
@@ -329,6 +329,21 @@ impl<E: ConsensusEnclave + Send, UI: UntrustedInterfaces + Send> TxManager
             .get(tx_hash)
             .map(|entry| entry.encrypted_tx().clone())
     }
+
+    /// Get the context of the transaction corresponding to the given hash.
+    fn get_context(&self, tx_hash: &TxHash) -> Option<Arc<WellFormedTxContext>> {
+        self.lock_cache()
+            .get(tx_hash)
+            .map(|entry| entry.context().clone())
+    }
+
+    /// Get the fee per byte of the transaction corresponding to the given
+    /// hash.
+    fn fee_per_byte(&self, tx_hash: &TxHash) -> Option<f64> {
+        self.lock_cache().get(tx_hash).map(|entry| {
+            entry.context().priority() as f64 / entry.encrypted_tx().0.len().max(1) as f64
+        })
+    }
 }
 
 #[cfg(test)]
@@ -939,6 +954,39 @@ mod tests {
         assert_eq!(tx_manager.get_encrypted_tx(&TxHash([88u8; 32])), None);
     }
 
+    #[test_with_logger]
+    // Should return the priority of a cached transaction divided by its size.
+    fn test_fee_per_byte(logger: Logger) {
+        let mock_untrusted = MockUntrustedInterfaces::new();
+        let mock_enclave = MockConsensusEnclave::new();
+        let tx_manager = TxManagerImpl::new(mock_enclave, mock_untrusted, logger.clone());
+
+        // Add a transaction to the cache.
+        let tx_hash = TxHash([1u8; 32]);
+        let context = WellFormedTxContext::new(
+            256,
+            tx_hash,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        let cache_entry = CacheEntry {
+            encrypted_tx: WellFormedEncryptedTx(vec![0u8; 1024]),
+            context: Arc::new(context),
+        };
+        tx_manager
+            .cache
+            .lock()
+            .unwrap()
+            .insert(tx_hash, cache_entry);
+
+        assert_eq!(tx_manager.fee_per_byte(&tx_hash), Some(0.25));
+
+        // Get something that is not in the cache.
+        assert_eq!(tx_manager.fee_per_byte(&TxHash([88u8; 32])), None);
+    }
+
     #[test_with_logger]
     // Should return the number of elements in the cache.
     fn test_get_num_entries(logger: Logger) {
@@ -3,8 +3,9 @@
 use crate::tx_manager::TxManagerResult;
 use mc_attest_enclave_api::{EnclaveMessage, PeerSession};
 use mc_common::HashSet;
-use mc_consensus_enclave::{TxContext, WellFormedEncryptedTx};
+use mc_consensus_enclave::{TxContext, WellFormedEncryptedTx, WellFormedTxContext};
 use mc_transaction_core::tx::{TxHash, TxOutMembershipProof};
+use std::sync::Arc;
 
 #[cfg(test)]
 use mockall::*;
@@ -61,4 +62,13 @@ pub trait TxManager: Send {
 
     /// Get the encrypted transaction corresponding to the given hash.
     fn get_encrypted_tx(&self, tx_hash: &TxHash) -> Option<WellFormedEncryptedTx>;
+
+    /// Get the context of the transaction corresponding to the given hash.
+    fn get_context(&self, tx_hash: &TxHash) -> Option<Arc<WellFormedTxContext>>;
+
+    /// Get the fee per byte of the transaction corresponding to the given
+    /// hash. The fee is the transaction's priority, which normalizes fees
+    /// paid in different tokens by their minimum fee, and the size is that of
+    /// the encrypted transaction.
+    fn fee_per_byte(&self, tx_hash: &TxHash) -> Option<f64>;
 }
@@ -37,7 +37,7 @@ const MAX_AUTH_PENDING_REQUESTS: usize = 64;
 const MAX_PEER_SESSIONS: usize = 64;
 
 /// Max number of client sessions.
-const MAX_CLIENT_SESSIONS: usize = 10000;
+pub const MAX_CLIENT_SESSIONS: usize = 10000;
 
 /// Any additional "identities" (e.g. key material) for a given enclave that
 /// needs to become a part of the report. We provide some simple identities, and
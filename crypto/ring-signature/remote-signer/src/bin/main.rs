@@ -0,0 +1,123 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+#![deny(missing_docs)]
+
+//! Ring signer daemon: Hold the keys of an account, and sign input rings for
+//! `RemoteRingSigner`s in other processes.
+//!
+//! Requests are served over a Unix socket if one is given, and otherwise read
+//! from stdin, with responses written to stdout.
+
+use clap::Parser;
+use mc_common::logger::{create_app_logger, log, o, Logger};
+use mc_crypto_ring_signature_remote_signer::serve;
+use mc_crypto_ring_signature_signer::LocalRingSigner;
+use mc_util_keyfile::read_keyfile;
+use std::{
+    fs, io,
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    thread,
+};
+
+/// Command line configuration
+#[derive(Clone, Debug, Parser)]
+pub struct Config {
+    /// The account key file (root entropy or mnemonic JSON) of the account
+    /// to sign for.
+    #[clap(long, parse(from_os_str), env = "MC_KEYFILE")]
+    pub keyfile: PathBuf,
+
+    /// The Unix socket to listen on. Only its owner is allowed to connect.
+    #[clap(long, parse(from_os_str), env = "MC_SOCKET")]
+    pub socket: Option<PathBuf>,
+}
+
+/// Remove a socket file left behind by a previous daemon which is no longer
+/// running, so that it can be bound again.
+fn remove_stale_socket(socket: &Path, logger: &Logger) -> io::Result<()> {
+    match fs::symlink_metadata(socket) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            if UnixStream::connect(socket).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    "another daemon is listening on it",
+                ));
+            }
+            log::info!(logger, "Removing stale socket {:?}", socket);
+            fs::remove_file(socket)
+        }
+        // Anything else is left for bind to fail on.
+        Ok(_) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Bind the socket so that only its owner may connect to it.
+///
+/// The umask is restricted around the bind, so that the socket is never
+/// accessible to others, even briefly.
+fn bind_private(socket: &Path) -> io::Result<UnixListener> {
+    // Safety: umask only swaps the file mode creation mask of the process,
+    // and no other threads are creating files yet.
+    let old_umask = unsafe { libc::umask(0o177) };
+    let result = UnixListener::bind(socket);
+    unsafe { libc::umask(old_umask) };
+    result
+}
+
+fn listen(signer: LocalRingSigner, socket: &Path, logger: &Logger) -> Result<(), String> {
+    remove_stale_socket(socket, logger)
+        .map_err(|err| format!("Failed removing stale socket {:?}: {}", socket, err))?;
+    let listener =
+        bind_private(socket).map_err(|err| format!("Failed binding {:?}: {}", socket, err))?;
+    log::info!(logger, "Listening on {:?}", socket);
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                log::error!(logger, "Failed accepting connection: {}", err);
+                continue;
+            }
+        };
+
+        let signer = signer.clone();
+        let logger = logger.clone();
+        thread::spawn(move || {
+            let result = stream
+                .try_clone()
+                .map_err(Into::into)
+                .and_then(|mut reader| serve(&signer, &mut reader, &mut &stream));
+            if let Err(err) = result {
+                log::warn!(logger, "Connection failed: {}", err);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn main() {
+    // Logging must go to stderr, since stdout may carry responses.
+    std::env::set_var("MC_LOG_STDERR", "1");
+    mc_common::setup_panic_handler();
+    let config = Config::parse();
+    let (logger, _global_logger_guard) = create_app_logger(o!());
+
+    let account_key = read_keyfile(&config.keyfile)
+        .unwrap_or_else(|err| panic!("Failed reading {:?}: {}", config.keyfile, err));
+    let signer = LocalRingSigner::from(&account_key);
+
+    let result = match &config.socket {
+        Some(socket) => listen(signer, socket, &logger),
+        None => serve(&signer, &mut io::stdin().lock(), &mut io::stdout().lock())
+            .map_err(|err| format!("Failed serving stdin: {}", err)),
+    };
+
+    if let Err(err) = result {
+        panic!("{}", err);
+    }
+}
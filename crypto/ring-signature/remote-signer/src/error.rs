@@ -0,0 +1,38 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+use displaydoc::Display;
+use prost::{DecodeError, EncodeError};
+use std::io;
+
+/// An error in the communication with a signer daemon
+#[derive(Debug, Display)]
+pub enum ProtocolError {
+    /// IO error: {0}
+    Io(io::Error),
+    /// Failed encoding message: {0}
+    Encode(EncodeError),
+    /// Failed decoding message: {0}
+    Decode(DecodeError),
+    /// Message of {0} bytes is too long
+    MessageTooLong(usize),
+    /// The connection was closed
+    Closed,
+}
+
+impl From<io::Error> for ProtocolError {
+    fn from(src: io::Error) -> Self {
+        Self::Io(src)
+    }
+}
+
+impl From<EncodeError> for ProtocolError {
+    fn from(src: EncodeError) -> Self {
+        Self::Encode(src)
+    }
+}
+
+impl From<DecodeError> for ProtocolError {
+    fn from(src: DecodeError) -> Self {
+        Self::Decode(src)
+    }
+}
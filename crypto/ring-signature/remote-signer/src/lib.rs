@@ -0,0 +1,23 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A RingSigner which keeps the spend keys of an account out of the wallet
+//! process, by forwarding signing requests to a signer daemon holding them.
+//!
+//! The daemon is reached over a Unix socket, or spawned as a child process
+//! which reads requests from its stdin and writes responses to its stdout.
+//! Requests and responses are protobuf-encoded `SignRequest` and
+//! `SignResponse` messages, each prefixed by its length.
+
+#![deny(missing_docs)]
+
+mod error;
+pub use error::ProtocolError;
+
+mod protocol;
+pub use protocol::{read_message, write_message, SignRequest, SignResponse, MAX_MESSAGE_LEN};
+
+mod remote_ring_signer;
+pub use remote_ring_signer::RemoteRingSigner;
+
+mod server;
+pub use server::serve;
@@ -0,0 +1,203 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! The messages exchanged with a signer daemon, and their framing.
+//!
+//! Each message is written as its length, a big-endian u32, followed by its
+//! protobuf encoding. A client writes one `SignRequest` at a time, and the
+//! daemon answers each with a `SignResponse`.
+
+use crate::ProtocolError;
+use mc_crypto_keys::RistrettoPrivate;
+use mc_crypto_ring_signature::{CryptoRngCore, CurveScalar, ReducedTxOut, RingMLSAG, Scalar};
+use mc_crypto_ring_signature_signer::{
+    Error as SignerError, InputSecret, OneTimeKeyDeriveData, RingSigner, SignableInputRing,
+};
+use mc_transaction_types::{Amount, TokenId};
+use prost::Message;
+use std::io::{self, Read, Write};
+
+/// The longest message accepted, in bytes. This is far more than any ring
+/// needs, and only guards against allocating a buffer for a corrupt length.
+pub const MAX_MESSAGE_LEN: usize = 1 << 20;
+
+/// A request to sign an input ring, holding the arguments of
+/// `RingSigner::sign`.
+#[derive(Clone, Message)]
+pub struct SignRequest {
+    /// The digest of the transaction context to sign.
+    #[prost(bytes, tag = "1")]
+    pub message: Vec<u8>,
+
+    /// The ring members.
+    #[prost(message, repeated, tag = "2")]
+    pub members: Vec<ReducedTxOut>,
+
+    /// The index of the real input among `members`.
+    #[prost(uint64, tag = "3")]
+    pub real_input_index: u64,
+
+    /// The one-time private key of the real input, if the signer cannot derive
+    /// it, e.g. for gift codes. When unset, the signer derives it from
+    /// `subaddress_index`.
+    #[prost(message, optional, tag = "4")]
+    pub onetime_private_key: Option<RistrettoPrivate>,
+
+    /// The subaddress index which owns the real input.
+    #[prost(uint64, tag = "5")]
+    pub subaddress_index: u64,
+
+    /// The value of the real input.
+    #[prost(fixed64, tag = "6")]
+    pub value: u64,
+
+    /// The token id of the real input.
+    #[prost(fixed64, tag = "7")]
+    pub token_id: u64,
+
+    /// The blinding factor of the real input.
+    #[prost(message, required, tag = "8")]
+    pub blinding: CurveScalar,
+
+    /// The desired blinding factor of the pseudo-output.
+    #[prost(message, required, tag = "9")]
+    pub output_blinding: CurveScalar,
+}
+
+/// The answer to a `SignRequest`.
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct SignResponse {
+    /// The signature, if signing succeeded.
+    #[prost(message, optional, tag = "1")]
+    pub signature: Option<RingMLSAG>,
+
+    /// The serialized `mc_crypto_ring_signature_signer::Error`, if signing
+    /// failed.
+    #[prost(bytes, tag = "2")]
+    pub error: Vec<u8>,
+}
+
+impl SignRequest {
+    /// Package the arguments of `RingSigner::sign`.
+    pub fn new(message: &[u8], ring: &SignableInputRing, output_blinding: Scalar) -> Self {
+        let (onetime_private_key, subaddress_index) =
+            match &ring.input_secret.onetime_key_derive_data {
+                OneTimeKeyDeriveData::OneTimeKey(key) => (Some(*key), 0),
+                OneTimeKeyDeriveData::SubaddressIndex(index) => (None, *index),
+            };
+
+        Self {
+            message: message.to_vec(),
+            members: ring.members.clone(),
+            real_input_index: ring.real_input_index as u64,
+            onetime_private_key,
+            subaddress_index,
+            value: ring.input_secret.amount.value,
+            token_id: *ring.input_secret.amount.token_id,
+            blinding: ring.input_secret.blinding.into(),
+            output_blinding: output_blinding.into(),
+        }
+    }
+
+    /// The ring to sign.
+    pub fn signable_ring(&self) -> SignableInputRing {
+        let onetime_key_derive_data = match self.onetime_private_key {
+            Some(key) => OneTimeKeyDeriveData::OneTimeKey(key),
+            None => OneTimeKeyDeriveData::SubaddressIndex(self.subaddress_index),
+        };
+
+        SignableInputRing {
+            members: self.members.clone(),
+            real_input_index: self.real_input_index as usize,
+            input_secret: InputSecret {
+                onetime_key_derive_data,
+                amount: Amount::new(self.value, TokenId::from(self.token_id)),
+                blinding: self.blinding.scalar,
+            },
+        }
+    }
+
+    /// Sign the request with `signer`.
+    pub fn sign<S: RingSigner + ?Sized>(
+        &self,
+        signer: &S,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<RingMLSAG, SignerError> {
+        signer.sign(
+            &self.message,
+            &self.signable_ring(),
+            self.output_blinding.scalar,
+            rng,
+        )
+    }
+}
+
+impl SignResponse {
+    /// The result carried by the response. An error which cannot be
+    /// deserialized is reported as a connection failure.
+    pub fn into_result(self) -> Result<RingMLSAG, SignerError> {
+        match self.signature {
+            Some(signature) => Ok(signature),
+            None => Err(
+                mc_util_serial::deserialize(&self.error).unwrap_or_else(|err| {
+                    SignerError::ConnectionFailed(format!("Invalid response: {}", err))
+                }),
+            ),
+        }
+    }
+}
+
+impl From<Result<RingMLSAG, SignerError>> for SignResponse {
+    fn from(src: Result<RingMLSAG, SignerError>) -> Self {
+        match src {
+            Ok(signature) => Self {
+                signature: Some(signature),
+                error: Vec::new(),
+            },
+            Err(err) => Self {
+                signature: None,
+                error: mc_util_serial::serialize(&err)
+                    .expect("Serializing a signer error cannot fail"),
+            },
+        }
+    }
+}
+
+/// Write a length-prefixed message.
+pub fn write_message<M: Message, W: Write + ?Sized>(
+    writer: &mut W,
+    message: &M,
+) -> Result<(), ProtocolError> {
+    let len = message.encoded_len();
+    if len > MAX_MESSAGE_LEN {
+        return Err(ProtocolError::MessageTooLong(len));
+    }
+
+    let mut buf = Vec::with_capacity(4 + len);
+    buf.extend_from_slice(&(len as u32).to_be_bytes());
+    message.encode(&mut buf)?;
+    writer.write_all(&buf)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read a length-prefixed message. Returns `None` if the writer closed the
+/// connection instead of starting a new message.
+pub fn read_message<M: Message + Default, R: Read + ?Sized>(
+    reader: &mut R,
+) -> Result<Option<M>, ProtocolError> {
+    let mut len_bytes = [0u8; 4];
+    match reader.read_exact(&mut len_bytes) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err.into()),
+    }
+
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    if len > MAX_MESSAGE_LEN {
+        return Err(ProtocolError::MessageTooLong(len));
+    }
+
+    let mut buf = vec![0u8; len];
+    reader.read_exact(&mut buf)?;
+    Ok(Some(M::decode(&buf[..])?))
+}
@@ -0,0 +1,276 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+use crate::{read_message, write_message, ProtocolError, SignRequest, SignResponse};
+use mc_crypto_ring_signature::{CryptoRngCore, RingMLSAG, Scalar};
+use mc_crypto_ring_signature_signer::{Error, RingSigner, SignableInputRing};
+use std::{
+    io::{self, Read, Write},
+    os::unix::net::UnixStream,
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::Mutex,
+};
+
+/// An implementation of RingSigner which holds no keys, and forwards each
+/// signing request to a signer daemon.
+///
+/// Requests are sent one at a time, so the signer can be shared between
+/// threads.
+pub struct RemoteRingSigner {
+    channel: Mutex<Channel>,
+}
+
+/// The two directions of the connection to the daemon.
+struct Channel {
+    reader: Box<dyn Read + Send>,
+    writer: Box<dyn Write + Send>,
+    /// The daemon, if we spawned it.
+    child: Option<Child>,
+}
+
+impl RemoteRingSigner {
+    /// Talk to a signer daemon over an existing connection.
+    pub fn new(reader: impl Read + Send + 'static, writer: impl Write + Send + 'static) -> Self {
+        Self::from_channel(Channel {
+            reader: Box::new(reader),
+            writer: Box::new(writer),
+            child: None,
+        })
+    }
+
+    /// Connect to a signer daemon listening on a Unix socket.
+    pub fn connect(path: impl AsRef<Path>) -> io::Result<Self> {
+        let stream = UnixStream::connect(path)?;
+        Ok(Self::new(stream.try_clone()?, stream))
+    }
+
+    /// Spawn a signer daemon which reads requests from its stdin and writes
+    /// responses to its stdout. The daemon is stopped when the signer is
+    /// dropped.
+    pub fn spawn(command: &mut Command) -> io::Result<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let writer = child.stdin.take().expect("stdin is piped");
+        let reader = child.stdout.take().expect("stdout is piped");
+
+        Ok(Self::from_channel(Channel {
+            reader: Box::new(reader),
+            writer: Box::new(writer),
+            child: Some(child),
+        }))
+    }
+
+    fn from_channel(channel: Channel) -> Self {
+        Self {
+            channel: Mutex::new(channel),
+        }
+    }
+}
+
+impl Channel {
+    fn call(&mut self, request: &SignRequest) -> Result<SignResponse, ProtocolError> {
+        write_message(&mut self.writer, request)?;
+        read_message(&mut self.reader)?.ok_or(ProtocolError::Closed)
+    }
+}
+
+impl Drop for Channel {
+    fn drop(&mut self) {
+        if let Some(child) = self.child.as_mut() {
+            // Closing the daemon's stdin makes it exit.
+            self.writer = Box::new(io::sink());
+            let _ = child.wait();
+        }
+    }
+}
+
+impl RingSigner for RemoteRingSigner {
+    // The daemon supplies its own randomness, so `rng` is unused.
+    fn sign(
+        &self,
+        message: &[u8],
+        signable_ring: &SignableInputRing,
+        output_blinding: Scalar,
+        _rng: &mut dyn CryptoRngCore,
+    ) -> Result<RingMLSAG, Error> {
+        let request = SignRequest::new(message, signable_ring, output_blinding);
+
+        let mut channel = self
+            .channel
+            .lock()
+            .map_err(|_| Error::ConnectionFailed("channel lock poisoned".to_string()))?;
+        let response = channel
+            .call(&request)
+            .map_err(|err| Error::ConnectionFailed(err.to_string()))?;
+
+        response.into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serve;
+    use mc_account_keys::AccountKey;
+    use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPrivate, RistrettoPublic};
+    use mc_crypto_ring_signature::{
+        generators,
+        onetime_keys::{create_tx_out_public_key, create_tx_out_target_key},
+        CompressedCommitment, ReducedTxOut,
+    };
+    use mc_crypto_ring_signature_signer::{InputSecret, LocalRingSigner, OneTimeKeyDeriveData};
+    use mc_transaction_types::{Amount, TokenId};
+    use mc_util_from_random::FromRandom;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+    use std::thread;
+
+    /// A ring of random members, whose real input is sent to `subaddress_index`
+    /// of `account_key`.
+    fn signable_ring(
+        account_key: &AccountKey,
+        subaddress_index: u64,
+        rng: &mut StdRng,
+    ) -> SignableInputRing {
+        let amount = Amount::new(1000, TokenId::from(1));
+        let blinding = Scalar::random(rng);
+        let generator = generators(*amount.token_id);
+
+        let mut members: Vec<ReducedTxOut> = (0..11)
+            .map(|_| ReducedTxOut {
+                public_key: CompressedRistrettoPublic::from_random(rng),
+                target_key: CompressedRistrettoPublic::from_random(rng),
+                commitment: CompressedCommitment::new(
+                    rng.next_u64(),
+                    Scalar::random(rng),
+                    &generator,
+                ),
+            })
+            .collect();
+
+        let recipient = account_key.subaddress(subaddress_index);
+        let tx_private_key = RistrettoPrivate::from_random(rng);
+        members.push(ReducedTxOut {
+            public_key: create_tx_out_public_key(&tx_private_key, recipient.spend_public_key())
+                .into(),
+            target_key: create_tx_out_target_key(&tx_private_key, &recipient).into(),
+            commitment: CompressedCommitment::new(amount.value, blinding, &generator),
+        });
+
+        SignableInputRing {
+            real_input_index: members.len() - 1,
+            members,
+            input_secret: InputSecret {
+                onetime_key_derive_data: OneTimeKeyDeriveData::SubaddressIndex(subaddress_index),
+                amount,
+                blinding,
+            },
+        }
+    }
+
+    /// A remote signer talking to a daemon thread which signs with
+    /// `account_key`.
+    fn remote_signer(account_key: &AccountKey) -> RemoteRingSigner {
+        let (client, server) = UnixStream::pair().unwrap();
+        let local_signer = LocalRingSigner::from(account_key);
+        thread::spawn(move || {
+            let mut reader = server.try_clone().unwrap();
+            let mut writer = server;
+            serve(&local_signer, &mut reader, &mut writer).unwrap();
+        });
+        RemoteRingSigner::new(client.try_clone().unwrap(), client)
+    }
+
+    #[test]
+    fn test_remote_signature_verifies() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let account_key = AccountKey::random(&mut rng);
+        let signer = remote_signer(&account_key);
+
+        for subaddress_index in [0, 1, 7] {
+            let ring = signable_ring(&account_key, subaddress_index, &mut rng);
+            let message = [subaddress_index as u8; 32];
+            let output_blinding = Scalar::random(&mut rng);
+
+            let signature = signer
+                .sign(&message, &ring, output_blinding, &mut rng)
+                .unwrap();
+
+            let output_commitment = CompressedCommitment::new(
+                ring.input_secret.amount.value,
+                output_blinding,
+                &generators(*ring.input_secret.amount.token_id),
+            );
+            signature
+                .verify(&message, &ring.members, &output_commitment)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_remote_signature_with_onetime_key() {
+        let mut rng: StdRng = SeedableRng::from_seed([2u8; 32]);
+        let account_key = AccountKey::random(&mut rng);
+        let mut ring = signable_ring(&account_key, 0, &mut rng);
+
+        // Replace the real input with one whose one-time private key we know.
+        let onetime_private_key = RistrettoPrivate::from_random(&mut rng);
+        let real_input = &mut ring.members[ring.real_input_index];
+        real_input.target_key = RistrettoPublic::from(&onetime_private_key).into();
+        ring.input_secret.onetime_key_derive_data =
+            OneTimeKeyDeriveData::OneTimeKey(onetime_private_key);
+
+        // The daemon holds the keys of another account.
+        let signer = remote_signer(&AccountKey::random(&mut rng));
+        let message = [3u8; 32];
+        let output_blinding = Scalar::random(&mut rng);
+        let signature = signer
+            .sign(&message, &ring, output_blinding, &mut rng)
+            .unwrap();
+
+        let output_commitment = CompressedCommitment::new(
+            ring.input_secret.amount.value,
+            output_blinding,
+            &generators(*ring.input_secret.amount.token_id),
+        );
+        signature
+            .verify(&message, &ring.members, &output_commitment)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_remote_signing_errors_are_forwarded() {
+        let mut rng: StdRng = SeedableRng::from_seed([3u8; 32]);
+        let account_key = AccountKey::random(&mut rng);
+        let signer = remote_signer(&AccountKey::random(&mut rng));
+
+        let ring = signable_ring(&account_key, 0, &mut rng);
+        assert_eq!(
+            signer.sign(&[0u8; 32], &ring, Scalar::random(&mut rng), &mut rng),
+            Err(Error::TrueInputNotOwned)
+        );
+
+        // The connection is still usable after an error.
+        let mut ring = signable_ring(&account_key, 0, &mut rng);
+        ring.real_input_index = ring.members.len();
+        assert_eq!(
+            signer.sign(&[0u8; 32], &ring, Scalar::random(&mut rng), &mut rng),
+            Err(Error::RealInputIndexOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_closed_connection() {
+        let (client, server) = UnixStream::pair().unwrap();
+        drop(server);
+        let signer = RemoteRingSigner::new(client.try_clone().unwrap(), client);
+
+        let mut rng: StdRng = SeedableRng::from_seed([4u8; 32]);
+        let ring = signable_ring(&AccountKey::random(&mut rng), 0, &mut rng);
+        assert!(matches!(
+            signer.sign(&[0u8; 32], &ring, Scalar::random(&mut rng), &mut rng),
+            Err(Error::ConnectionFailed(_))
+        ));
+    }
+}
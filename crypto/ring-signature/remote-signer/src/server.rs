@@ -0,0 +1,24 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+use crate::{read_message, write_message, ProtocolError, SignRequest, SignResponse};
+use mc_crypto_rand::McRng;
+use mc_crypto_ring_signature_signer::RingSigner;
+use std::io::{Read, Write};
+
+/// Answer the signing requests read from `reader` with `signer`, writing the
+/// responses to `writer`, until the client closes the connection.
+///
+/// Signing failures are reported to the client, only failures to read a
+/// request or to write a response end the loop with an error.
+pub fn serve<S: RingSigner + ?Sized, R: Read + ?Sized, W: Write + ?Sized>(
+    signer: &S,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<(), ProtocolError> {
+    let mut rng = McRng::default();
+    while let Some(request) = read_message::<SignRequest, _>(reader)? {
+        let response = SignResponse::from(request.sign(signer, &mut rng));
+        write_message(writer, &response)?;
+    }
+    Ok(())
+}
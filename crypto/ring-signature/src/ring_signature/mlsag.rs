@@ -34,13 +34,16 @@ pub trait CryptoRngCore: RngCore + CryptoRng {}
 impl<T> CryptoRngCore for T where T: RngCore + CryptoRng {}
 
 /// A reduced representation of a TxOut, appropriate for making MLSAG
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq, Message)]
 pub struct ReducedTxOut {
     /// The tx_out.public_key field
+    #[prost(message, required, tag = "1")]
     pub public_key: CompressedRistrettoPublic,
     /// The tx_out.target_key field
+    #[prost(message, required, tag = "2")]
     pub target_key: CompressedRistrettoPublic,
     /// The tx_out.masked_amount.commitment field
+    #[prost(message, required, tag = "3")]
     pub commitment: CompressedCommitment,
 }
 
@@ -345,6 +345,30 @@ fn test_kex_rng_pubkey_round_trip_protobuf() {
     });
 }
 
+/// Test that many random instances of prosty SubscribeRequest round trip with
+/// protobufy SubscribeRequest
+#[test]
+fn fog_view_subscribe_request_round_trip() {
+    {
+        let test_val: mc_fog_types::view::SubscribeRequest = Default::default();
+        round_trip_message::<
+            mc_fog_types::view::SubscribeRequest,
+            mc_fog_api::view::SubscribeRequest,
+        >(&test_val);
+    }
+
+    run_with_several_seeds(|mut rng| {
+        let num_rngs = rng.next_u32() as u8;
+        let test_val = mc_fog_types::view::SubscribeRequest {
+            rngs: (0..num_rngs).map(|_| StoredRng::sample(&mut rng)).collect(),
+        };
+        round_trip_message::<
+            mc_fog_types::view::SubscribeRequest,
+            mc_fog_api::view::SubscribeRequest,
+        >(&test_val);
+    });
+}
+
 // Test that StoredRng is a subset of its proto
 #[test]
 fn test_stored_kex_rng_round_trip() {
@@ -410,6 +434,17 @@ impl Sample for KexRngPubkey {
     }
 }
 
+impl Sample for StoredRng {
+    fn sample<T: RngCore + CryptoRng>(rng: &mut T) -> Self {
+        Self {
+            secret: <[u8; 32]>::sample(rng).to_vec(),
+            buffer: <[u8; 16]>::sample(rng).to_vec(),
+            counter: rng.next_u64(),
+            version: rng.next_u32(),
+        }
+    }
+}
+
 impl Sample for mc_fog_types::view::RngRecord {
     fn sample<T: RngCore + CryptoRng>(rng: &mut T) -> Self {
         Self {
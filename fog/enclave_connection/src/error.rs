@@ -21,6 +21,8 @@ pub enum Error {
     InvalidUri(UriConversionError),
     /// Protobuf deserialization: {0}
     ProtoDecode(DecodeError),
+    /// The connection is not attested
+    NotAttested,
 }
 
 impl Error {
@@ -182,27 +182,7 @@ impl<U: ConnectionUri, G: EnclaveGrpcChannel> EnclaveConnection<U, G> {
         plaintext_request: &RequestMessage,
         aad: &[u8],
     ) -> Result<ResponseMessage, Error> {
-        if !self.is_attested() {
-            let _verification_report = self.attest()?;
-        }
-
-        // Build encrypted request, scope attest_cipher borrow
-        let msg = {
-            let attest_cipher = self
-                .attest_cipher
-                .as_mut()
-                .expect("no enclave_connection even though attest succeeded");
-
-            let mut msg = Message::new();
-            msg.set_channel_id(Vec::from(attest_cipher.binding()));
-            msg.set_aad(aad.to_vec());
-
-            let plaintext_bytes = mc_util_serial::encode(plaintext_request);
-
-            let request_ciphertext = attest_cipher.encrypt(aad, &plaintext_bytes)?;
-            msg.set_data(request_ciphertext);
-            msg
-        };
+        let msg = self.encrypt_request(plaintext_request, aad)?;
 
         // make an attested call to EnclaveGrpcChannel::enclave_request,
         // and handle cookies
@@ -225,17 +205,51 @@ impl<U: ConnectionUri, G: EnclaveGrpcChannel> EnclaveConnection<U, G> {
             Ok(message)
         })?;
 
-        // Decrypt request, scope attest_cipher borrow
-        {
-            let attest_cipher = self
-                .attest_cipher
-                .as_mut()
-                .expect("no enclave_connection even though attest succeeded");
-
-            let plaintext_bytes = attest_cipher.decrypt(message.get_aad(), message.get_data())?;
-            let plaintext_response: ResponseMessage = mc_util_serial::decode(&plaintext_bytes)?;
-            Ok(plaintext_response)
+        self.decrypt_response(&message)
+    }
+
+    /// Encrypt a request for the enclave, attesting first if needed, given
+    /// the plaintext to go to enclave and any aad data.
+    ///
+    /// This is useful for calls that don't fit the request / response shape
+    /// of EnclaveGrpcChannel::enclave_request, such as streaming calls.
+    pub fn encrypt_request<RequestMessage: mc_util_serial::Message>(
+        &mut self,
+        plaintext_request: &RequestMessage,
+        aad: &[u8],
+    ) -> Result<Message, Error> {
+        if !self.is_attested() {
+            let _verification_report = self.attest()?;
         }
+
+        let attest_cipher = self
+            .attest_cipher
+            .as_mut()
+            .expect("no enclave_connection even though attest succeeded");
+
+        let mut msg = Message::new();
+        msg.set_channel_id(Vec::from(attest_cipher.binding()));
+        msg.set_aad(aad.to_vec());
+
+        let plaintext_bytes = mc_util_serial::encode(plaintext_request);
+
+        let request_ciphertext = attest_cipher.encrypt(aad, &plaintext_bytes)?;
+        msg.set_data(request_ciphertext);
+        Ok(msg)
+    }
+
+    /// Decrypt and deserialize a message from the enclave.
+    ///
+    /// Messages must be decrypted in the order the enclave encrypted them.
+    pub fn decrypt_response<ResponseMessage: mc_util_serial::Message + Default>(
+        &mut self,
+        message: &Message,
+    ) -> Result<ResponseMessage, Error> {
+        let attest_cipher = self.attest_cipher.as_mut().ok_or(Error::NotAttested)?;
+
+        let plaintext_bytes = attest_cipher.decrypt(message.get_aad(), message.get_data())?;
+        let plaintext_response: ResponseMessage = mc_util_serial::decode(&plaintext_bytes)?;
+        Ok(plaintext_response)
     }
 
     /// Same as encrypted_enclave_request, but convert result to an
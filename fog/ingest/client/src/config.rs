@@ -100,4 +100,17 @@ pub enum IngestConfigCommand {
         #[clap(short = 'r', long = "include-retired", env = "MC_INCLUDE_RETIRED")]
         should_include_retired_keys: bool,
     },
+
+    /// Prunes ETxOutRecords from the recovery database, according to the
+    /// retention policy the ingest server was configured with, and reports
+    /// on what was pruned.
+    PruneRecoveryDb {
+        /// If set, only report what would be pruned.
+        #[clap(long, env = "MC_DRY_RUN")]
+        dry_run: bool,
+        /// The maximum number of blocks to prune. Zero selects the server
+        /// default.
+        #[clap(long, env = "MC_MAX_BLOCKS", default_value_t)]
+        max_blocks: u64,
+    },
 }
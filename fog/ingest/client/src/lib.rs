@@ -10,8 +10,9 @@ use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_fog_api::{
     empty::Empty,
     ingest::{
-        GetIngressKeyRecordsRequest, IngressPublicKeyRecord, ReportLostIngressKeyRequest,
-        SetPubkeyExpiryWindowRequest, SyncKeysFromRemoteRequest,
+        GetIngressKeyRecordsRequest, IngressPublicKeyRecord, PruneRecoveryDbRequest,
+        PruneRecoveryDbResponse, ReportLostIngressKeyRequest, SetPubkeyExpiryWindowRequest,
+        SyncKeysFromRemoteRequest,
     },
     ingest_common::{IngestSummary, SetPeersRequest},
     ingest_grpc::AccountIngestApiClient,
@@ -218,6 +219,29 @@ impl FogIngestGrpcClient {
         Ok(resp.get_records().to_vec())
     }
 
+    pub fn prune_recovery_db(
+        &self,
+        dry_run: bool,
+        max_blocks: u64,
+    ) -> ClientResult<PruneRecoveryDbResponse> {
+        log::trace!(
+            self.logger,
+            "prune_recovery_db({}, {})",
+            dry_run,
+            max_blocks
+        );
+
+        let mut req = PruneRecoveryDbRequest::new();
+        req.set_dry_run(dry_run);
+        req.set_max_blocks(max_blocks);
+
+        retry(self.get_retries(), || -> Result<_, Error> {
+            Ok(self
+                .ingest_api_client
+                .prune_recovery_db_opt(&req, self.creds.call_option()?)?)
+        })
+    }
+
     // The retry crate works by taking an iterator over durations, and a closure
     // This function returns the iterator over durations consistent with configured
     // policy, which is then used to implement retries for all the grpc calls
@@ -68,6 +68,11 @@ fn main() -> ClientResult<()> {
             should_include_lost_keys,
             should_include_retired_keys,
         ),
+
+        IngestConfigCommand::PruneRecoveryDb {
+            dry_run,
+            max_blocks,
+        } => prune_recovery_db(&logger, &ingest_client, dry_run, max_blocks),
     }
 }
 
@@ -219,6 +224,40 @@ fn get_ingress_key_records(
     Ok(())
 }
 
+fn prune_recovery_db(
+    logger: &Logger,
+    ingest_client: &FogIngestGrpcClient,
+    dry_run: bool,
+    max_blocks: u64,
+) -> ClientResult<()> {
+    let response = ingest_client
+        .prune_recovery_db(dry_run, max_blocks)
+        .expect("Failed pruning recovery db");
+
+    log::info!(
+        logger,
+        "Pruned {} ETxOutRecords from {} blocks (dry run: {})",
+        response.num_pruned_records,
+        response.num_pruned_blocks,
+        response.dry_run
+    );
+
+    println!(
+        "{}",
+        to_string_pretty(&json!({
+            "dry_run": response.dry_run,
+            "num_pruned_blocks": response.num_pruned_blocks,
+            "num_pruned_records": response.num_pruned_records,
+            "first_pruned_block": response.first_pruned_block,
+            "last_pruned_block": response.last_pruned_block,
+            "archive_files": response.get_archive_files(),
+        }))
+        .expect("could not pretty print")
+    );
+
+    Ok(())
+}
+
 fn ingest_summary_to_json(summary: &IngestSummary) -> String {
     to_string_pretty(&json!({
         "mode": format!("{:?}", summary.mode),
@@ -5,19 +5,22 @@
 
 use grpcio::{RpcStatus, RpcStatusCode};
 use mc_attest_net::{Client, RaClient};
-use mc_common::logger::{log, o};
+use mc_common::logger::{log, o, Logger};
 use mc_fog_ingest_enclave::ENCLAVE_FILE;
 use mc_fog_ingest_server::{
     config::IngestConfig,
+    error::IngestServiceError,
     server::{IngestServer, IngestServerConfig},
     state_file::StateFile,
 };
+use mc_fog_lmdb_recovery_db::{is_lmdb_url, LmdbRecoveryDb};
+use mc_fog_recovery_db_iface::{RecoveryDb, ReportDb};
 use mc_fog_sql_recovery_db::SqlRecoveryDb;
 use mc_ledger_db::LedgerDB;
 use mc_util_cli::ParserWithBuildInfo;
 use mc_util_grpc::AdminServer;
 use mc_watcher::watcher_db::WatcherDB;
-use std::{env, sync::Arc};
+use std::{env, path::PathBuf, sync::Arc};
 
 fn main() {
     mc_common::setup_panic_handler();
@@ -42,9 +45,6 @@ fn main() {
 
     log::info!(logger, "State file is {:?}", state_file_path);
 
-    // Create IAS client.
-    let ias_client = Client::new(&config.ias_api_key).expect("Could not create IAS client");
-
     // Get enclave path
     let enclave_path = env::current_exe()
         .expect("Could not get the path of our executable")
@@ -52,12 +52,36 @@ fn main() {
     log::info!(logger, "Enclave path is: {:?}", enclave_path);
 
     // Open databases.
-    let recovery_db = SqlRecoveryDb::new_from_url(
-        &std::env::var("DATABASE_URL").expect("DATABASE_URL environment variable missing"),
-        config.postgres_config.clone(),
-        logger.clone(),
-    )
-    .expect("Failed connecting to database");
+    let database_url =
+        std::env::var("DATABASE_URL").expect("DATABASE_URL environment variable missing");
+    if is_lmdb_url(&database_url) {
+        let recovery_db = LmdbRecoveryDb::new_from_url(&database_url, logger.clone())
+            .expect("Failed opening database");
+        run_server(config, state_file_path, enclave_path, recovery_db, logger)
+    } else {
+        let recovery_db = SqlRecoveryDb::new_from_url(
+            &database_url,
+            config.postgres_config.clone(),
+            logger.clone(),
+        )
+        .expect("Failed connecting to database");
+        run_server(config, state_file_path, enclave_path, recovery_db, logger)
+    }
+}
+
+fn run_server<DB>(
+    config: IngestConfig,
+    state_file_path: PathBuf,
+    enclave_path: PathBuf,
+    recovery_db: DB,
+    logger: Logger,
+) -> !
+where
+    DB: RecoveryDb + ReportDb + Clone + Send + Sync + 'static,
+    IngestServiceError: From<<DB as RecoveryDb>::Error>,
+{
+    // Create IAS client.
+    let ias_client = Client::new(&config.ias_api_key).expect("Could not create IAS client");
 
     let ledger_db = LedgerDB::open(&config.ledger_db).expect("Could not read ledger DB");
 
@@ -79,6 +103,8 @@ fn main() {
         fog_report_id: config.fog_report_id.clone(),
         state_file: Some(StateFile::new(state_file_path)),
         enclave_path,
+        retention_policy: config.retention_policy(),
+        prune_archive_dir: config.prune_archive_dir.clone(),
     };
 
     let mut server = IngestServer::new(
@@ -6,6 +6,7 @@
 use clap::Parser;
 use mc_attest_core::ProviderId;
 use mc_common::ResponderId;
+use mc_fog_recovery_db_iface::RetentionPolicy;
 use mc_fog_sql_recovery_db::SqlRecoveryDbConnectionConfig;
 use mc_fog_uri::{FogIngestUri, IngestPeerUri};
 use mc_util_parse::parse_duration_in_seconds;
@@ -102,11 +103,39 @@ pub struct IngestConfig {
     #[clap(long, env = "MC_STATE_FILE")]
     pub state_file: Option<PathBuf>,
 
+    /// If set, ETxOutRecords of blocks that are more than this many blocks
+    /// older than the highest known block may be pruned from the recovery db.
+    #[clap(long, env = "MC_RETENTION_MAX_BLOCK_AGE")]
+    pub retention_max_block_age: Option<u64>,
+
+    /// If set, ETxOutRecords scanned with a retired or lost ingress key may be
+    /// pruned from the recovery db once this many blocks have passed since the
+    /// key scanned its last block. This is only a block count: users who have
+    /// not synced those blocks by then will not find the pruned TxOuts.
+    #[clap(long, env = "MC_RETENTION_RETIRED_KEY_GRACE_BLOCKS")]
+    pub retention_retired_key_grace_blocks: Option<u64>,
+
+    /// Directory that pruned ETxOutRecords are archived to before they are
+    /// deleted from the recovery db. If omitted, pruned records are not
+    /// archived.
+    #[clap(long, env = "MC_PRUNE_ARCHIVE_DIR")]
+    pub prune_archive_dir: Option<PathBuf>,
+
     /// Postgres config
     #[clap(flatten)]
     pub postgres_config: SqlRecoveryDbConnectionConfig,
 }
 
+impl IngestConfig {
+    /// The retention policy used when pruning the recovery db.
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            max_block_age: self.retention_max_block_age,
+            retired_key_grace_blocks: self.retention_retired_key_grace_blocks,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -125,5 +154,45 @@ mod tests {
       "--admin-listen-uri", "insecure-mca://127.0.0.1:8003/",
       "--pubkey-expiry-window", "100"]).expect("Could not parse command line arguments");
         assert_eq!(config.peers.len(), 2);
+        assert!(!config.retention_policy().is_enabled());
+    }
+
+    #[test]
+    fn ingest_server_config_retention_policy() {
+        let config = IngestConfig::try_parse_from(&[
+            "/usr/bin/fog_ingest_server",
+            "--ledger-db",
+            "/fog-data/ledger",
+            "--watcher-db",
+            "/fog-data/watcher",
+            "--ias-spid",
+            "00000000000000000000000000000000",
+            "--ias-api-key",
+            "00000000000000000000000000000000",
+            "--client-listen-uri",
+            "insecure-fog-ingest://0.0.0.0:3226/",
+            "--peer-listen-uri",
+            "insecure-igp://0.0.0.0:8090/",
+            "--local-node-id",
+            "fogingest2.buildtest.svc.cluster.local:443",
+            "--retention-max-block-age",
+            "1000000",
+            "--retention-retired-key-grace-blocks",
+            "20000",
+            "--prune-archive-dir",
+            "/fog-data/pruned",
+        ])
+        .expect("Could not parse command line arguments");
+        assert_eq!(
+            config.retention_policy(),
+            RetentionPolicy {
+                max_block_age: Some(1000000),
+                retired_key_grace_blocks: Some(20000),
+            }
+        );
+        assert_eq!(
+            config.prune_archive_dir,
+            Some(PathBuf::from("/fog-data/pruned"))
+        );
     }
 }
@@ -7,6 +7,7 @@ use crate::{
     controller_state::{IngestControllerState, StateChangeError},
     counters,
     error::{IngestServiceError as Error, PeerBackupError, RestoreStateError, SetPeersError},
+    pruner::{self, PruneSummary},
     server::IngestServerConfig,
 };
 use mc_attest_enclave_api::{EnclaveMessage, PeerAuthRequest, PeerAuthResponse, PeerSession};
@@ -1141,6 +1142,23 @@ where
         self.recovery_db.get_missed_block_ranges()
     }
 
+    /// Prune ETxOutRecords from the recovery db according to the configured
+    /// retention policy, archiving them first if an archive dir is configured.
+    pub fn prune_recovery_db(
+        &self,
+        dry_run: bool,
+        max_blocks: usize,
+    ) -> Result<PruneSummary, Error> {
+        pruner::prune_recovery_db(
+            &self.recovery_db,
+            &self.config.retention_policy,
+            self.config.prune_archive_dir.as_deref(),
+            dry_run,
+            max_blocks,
+            &self.logger,
+        )
+    }
+
     /// Get the public key of the enclave
     ///
     /// This thin pass-through exists to reduce the need for other components
@@ -11,6 +11,7 @@ use mc_common::ResponderId;
 use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_fog_api::report_parse::ReportParseError;
 use mc_fog_ingest_enclave::Error as EnclaveError;
+use mc_fog_lmdb_recovery_db::Error as LmdbRecoveryDbError;
 use mc_fog_recovery_db_iface::RecoveryDbError;
 use mc_fog_sql_recovery_db::Error as SqlRecoveryDbError;
 use mc_fog_uri::IngestPeerUri;
@@ -82,6 +83,12 @@ impl From<SqlRecoveryDbError> for IngestServiceError {
     }
 }
 
+impl From<LmdbRecoveryDbError> for IngestServiceError {
+    fn from(src: LmdbRecoveryDbError) -> Self {
+        Self::RecoveryDb(Box::new(src))
+    }
+}
+
 impl From<mc_util_serial::encode::Error> for IngestServiceError {
     fn from(_: mc_util_serial::encode::Error) -> Self {
         Self::Serialization
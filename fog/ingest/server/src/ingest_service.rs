@@ -5,6 +5,7 @@
 use crate::{
     controller::IngestController,
     error::{IngestServiceError as Error, PeerBackupError},
+    pruner::DEFAULT_MAX_PRUNED_BLOCKS,
 };
 use grpcio::{RpcContext, RpcStatus, UnarySink};
 use mc_api::external;
@@ -199,6 +200,45 @@ where
         Ok(response)
     }
 
+    /// Prunes ETxOutRecords from the recovery db according to the retention
+    /// policy of this server.
+    pub fn prune_recovery_db_impl(
+        &mut self,
+        request: PruneRecoveryDbRequest,
+        logger: &Logger,
+    ) -> Result<PruneRecoveryDbResponse, RpcStatus> {
+        let max_blocks = match request.max_blocks {
+            0 => DEFAULT_MAX_PRUNED_BLOCKS,
+            max_blocks => max_blocks as usize,
+        };
+
+        let summary = self
+            .controller
+            .prune_recovery_db(request.dry_run, max_blocks)
+            .map_err(|err| match err {
+                Error::RecoveryDb(_) => rpc_database_err(err, logger),
+                _ => rpc_internal_error("prune_recovery_db", err, logger),
+            })?;
+
+        let mut response = PruneRecoveryDbResponse::new();
+        response.set_dry_run(summary.dry_run);
+        response.set_num_pruned_blocks(summary.num_pruned_blocks);
+        response.set_num_pruned_records(summary.num_pruned_records);
+        if let Some((first, last)) = summary.pruned_block_range {
+            response.set_first_pruned_block(first);
+            response.set_last_pruned_block(last);
+        }
+        response.set_archive_files(RepeatedField::from_vec(
+            summary
+                .archive_files
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect(),
+        ));
+
+        Ok(response)
+    }
+
     /// Retrieves a private key from a remote encalve and then sets it as the
     /// current enclave's private key.
     pub fn sync_keys_from_remote_impl(
@@ -401,4 +441,21 @@ where
             )
         })
     }
+
+    fn prune_recovery_db(
+        &mut self,
+        ctx: RpcContext,
+        request: PruneRecoveryDbRequest,
+        sink: UnarySink<PruneRecoveryDbResponse>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            send_result(
+                ctx,
+                sink,
+                self.prune_recovery_db_impl(request, logger),
+                logger,
+            )
+        })
+    }
 }
@@ -20,6 +20,7 @@ pub mod connection_traits;
 pub mod error;
 pub mod ingest_peer_service;
 pub mod ingest_service;
+pub mod pruner;
 pub mod server;
 pub mod state_file;
 
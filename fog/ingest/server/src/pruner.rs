@@ -0,0 +1,333 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Pruning of ETxOutRecords from the recovery database.
+//!
+//! Blocks are selected for pruning by the `RetentionPolicy` the server was
+//! configured with. Before their ETxOutRecords are deleted, they can be written
+//! to archive files in a local directory, so that the data can be restored if
+//! it is needed after all.
+
+use crate::error::IngestServiceError as Error;
+use mc_common::logger::{log, Logger};
+use mc_fog_recovery_db_iface::{PrunableBlock, RecoveryDb, RetentionPolicy};
+use mc_fog_types::ETxOutRecord;
+use prost::Message;
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The number of blocks selected from the database, archived and pruned at
+/// once.
+pub const PRUNE_BATCH_SIZE: usize = 1000;
+
+/// The number of blocks pruned by a single prune operation, if the caller
+/// doesn't specify it.
+pub const DEFAULT_MAX_PRUNED_BLOCKS: usize = 100_000;
+
+/// The result of a prune operation.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PruneSummary {
+    /// Whether this was a dry run, which didn't archive or delete anything.
+    pub dry_run: bool,
+
+    /// The number of (ingress key, block) pairs that were pruned.
+    pub num_pruned_blocks: u64,
+
+    /// The number of ETxOutRecords that were pruned.
+    pub num_pruned_records: u64,
+
+    /// The lowest and highest pruned block index, if anything was pruned.
+    pub pruned_block_range: Option<(u64, u64)>,
+
+    /// The archive files that were written.
+    pub archive_files: Vec<PathBuf>,
+}
+
+impl PruneSummary {
+    fn add_batch(&mut self, batch: &[PrunableBlock], num_pruned_records: u64) {
+        self.num_pruned_blocks += batch.len() as u64;
+        self.num_pruned_records += num_pruned_records;
+        for block in batch {
+            self.pruned_block_range = Some(match self.pruned_block_range {
+                Some((first, last)) => (first.min(block.block_index), last.max(block.block_index)),
+                None => (block.block_index, block.block_index),
+            });
+        }
+    }
+}
+
+/// The ETxOutRecords of an ingested block, as written to an archive file.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct ArchivedBlock {
+    /// The ingress public key the block was scanned with.
+    #[prost(bytes, tag = 1)]
+    pub ingress_public_key: Vec<u8>,
+
+    /// The block index.
+    #[prost(uint64, tag = 2)]
+    pub block_index: u64,
+
+    /// The ingest invocation that scanned the block.
+    #[prost(int64, tag = 3)]
+    pub ingest_invocation_id: i64,
+
+    /// The pruned ETxOutRecords.
+    #[prost(repeated, message, tag = 4)]
+    pub e_tx_out_records: Vec<ETxOutRecord>,
+}
+
+/// The contents of an archive file.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct PrunedBlocksArchive {
+    /// The archived blocks, in increasing block order.
+    #[prost(repeated, message, tag = 1)]
+    pub blocks: Vec<ArchivedBlock>,
+}
+
+impl ArchivedBlock {
+    fn new(block: &PrunableBlock, e_tx_out_records: Vec<ETxOutRecord>) -> Self {
+        Self {
+            ingress_public_key: block.ingress_key.as_bytes().to_vec(),
+            block_index: block.block_index,
+            ingest_invocation_id: *block.ingest_invocation_id,
+            e_tx_out_records,
+        }
+    }
+}
+
+/// Prune ETxOutRecords from the recovery database.
+///
+/// Arguments:
+/// * recovery_db: The database to prune
+/// * retention_policy: Selects the blocks that may be pruned
+/// * archive_dir: If set, pruned records are written to a file in this
+///   directory before they are deleted
+/// * dry_run: If true, only count what would be pruned, without reading the
+///   ETxOutRecords
+/// * max_blocks: The maximum number of blocks to prune
+/// * logger: Logger
+pub fn prune_recovery_db<DB: RecoveryDb>(
+    recovery_db: &DB,
+    retention_policy: &RetentionPolicy,
+    archive_dir: Option<&Path>,
+    dry_run: bool,
+    max_blocks: usize,
+    logger: &Logger,
+) -> Result<PruneSummary, Error>
+where
+    Error: From<<DB as RecoveryDb>::Error>,
+{
+    let mut summary = PruneSummary {
+        dry_run,
+        ..Default::default()
+    };
+
+    if let Some(dir) = archive_dir.filter(|_| !dry_run) {
+        fs::create_dir_all(dir)?;
+    }
+
+    // Each batch resumes after the last block of the previous one, so that the
+    // blocks before it are not scanned again.
+    let mut start_after = None;
+    while (summary.num_pruned_blocks as usize) < max_blocks {
+        let batch_size = PRUNE_BATCH_SIZE.min(max_blocks - summary.num_pruned_blocks as usize);
+        let batch = recovery_db.get_prunable_blocks(retention_policy, start_after, batch_size)?;
+        start_after = match batch.last() {
+            Some(block) => Some((block.block_index, block.ingress_key)),
+            None => break,
+        };
+
+        if dry_run {
+            let num_records = batch.iter().map(|block| block.num_e_tx_out_records).sum();
+            summary.add_batch(&batch, num_records);
+            continue;
+        }
+
+        if let Some(dir) = archive_dir {
+            let path = write_archive(recovery_db, dir, &batch)?;
+            log::info!(
+                logger,
+                "Archived {} blocks to be pruned to {:?}",
+                batch.len(),
+                path
+            );
+            summary.archive_files.push(path);
+        }
+
+        let to_prune: Vec<_> = batch
+            .iter()
+            .map(|block| (block.ingress_key, block.block_index))
+            .collect();
+        let num_records = recovery_db.prune_blocks(&to_prune)?;
+        summary.add_batch(&batch, num_records);
+    }
+
+    log::info!(
+        logger,
+        "Pruned {} ETxOutRecords from {} blocks (block range: {:?}, dry run: {})",
+        summary.num_pruned_records,
+        summary.num_pruned_blocks,
+        summary.pruned_block_range,
+        dry_run
+    );
+    Ok(summary)
+}
+
+/// Write the ETxOutRecords of a batch of blocks to a new archive file, named
+/// after the range of blocks it contains and the time it was written at.
+fn write_archive<DB: RecoveryDb>(
+    recovery_db: &DB,
+    archive_dir: &Path,
+    batch: &[PrunableBlock],
+) -> Result<PathBuf, Error>
+where
+    Error: From<<DB as RecoveryDb>::Error>,
+{
+    let first = batch.first().map(|block| block.block_index).unwrap_or(0);
+    let last = batch.last().map(|block| block.block_index).unwrap_or(0);
+    let written_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or(0);
+    let path = archive_dir.join(format!(
+        "pruned_blocks_{}_{}_{}.pb",
+        first, last, written_at
+    ));
+
+    let blocks = batch
+        .iter()
+        .map(|block| {
+            let e_tx_out_records = recovery_db
+                .get_tx_outs_by_block_and_key(block.ingress_key, block.block_index)?
+                .unwrap_or_default();
+            Ok(ArchivedBlock::new(block, e_tx_out_records))
+        })
+        .collect::<Result<_, Error>>()?;
+    let archive = PrunedBlocksArchive { blocks };
+
+    // Refuse to overwrite an existing archive.
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    file.write_all(&mc_util_serial::encode(&archive))?;
+    file.sync_all()?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_common::logger::test_with_logger;
+    use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
+    use mc_fog_lmdb_recovery_db::LmdbRecoveryDb;
+    use mc_fog_test_infra::db_tests::{random_block, random_kex_rng_pubkey};
+    use mc_util_from_random::FromRandom;
+    use rand_core::SeedableRng;
+    use rand_hc::Hc128Rng;
+    use tempdir::TempDir;
+
+    #[test_with_logger]
+    fn test_prune_recovery_db(logger: Logger) {
+        let mut rng = Hc128Rng::from_seed([7u8; 32]);
+        let db_dir = TempDir::new("recovery_db").unwrap();
+        let archive_dir = TempDir::new("pruned_blocks").unwrap();
+        let db = LmdbRecoveryDb::open(db_dir.path(), logger.clone()).unwrap();
+
+        let ingress_key = CompressedRistrettoPublic::from(RistrettoPublic::from_random(&mut rng));
+        db.new_ingress_key(&ingress_key, 0).unwrap();
+        let invoc_id = db
+            .new_ingest_invocation(None, &ingress_key, &random_kex_rng_pubkey(&mut rng), 0)
+            .unwrap();
+        for block_index in 0..20 {
+            let (block, records) = random_block(&mut rng, block_index, 3);
+            db.add_block_data(&invoc_id, &block, 0, &records).unwrap();
+        }
+
+        let policy = RetentionPolicy {
+            max_block_age: Some(9),
+            retired_key_grace_blocks: None,
+        };
+
+        // A dry run reports what would be pruned, and doesn't touch anything.
+        let summary = prune_recovery_db(
+            &db,
+            &policy,
+            Some(archive_dir.path()),
+            true,
+            DEFAULT_MAX_PRUNED_BLOCKS,
+            &logger,
+        )
+        .unwrap();
+        assert_eq!(
+            summary,
+            PruneSummary {
+                dry_run: true,
+                num_pruned_blocks: 10,
+                num_pruned_records: 30,
+                pruned_block_range: Some((0, 9)),
+                archive_files: vec![],
+            }
+        );
+        assert_eq!(
+            db.get_tx_outs_by_block_and_key(ingress_key, 0)
+                .unwrap()
+                .map(|records| records.len()),
+            Some(3)
+        );
+
+        // Prune at most 4 blocks.
+        let expected_records: Vec<_> = (0..4)
+            .map(|block_index| {
+                db.get_tx_outs_by_block_and_key(ingress_key, block_index)
+                    .unwrap()
+                    .unwrap()
+            })
+            .collect();
+        let summary =
+            prune_recovery_db(&db, &policy, Some(archive_dir.path()), false, 4, &logger).unwrap();
+        assert_eq!(summary.num_pruned_blocks, 4);
+        assert_eq!(summary.num_pruned_records, 12);
+        assert_eq!(summary.pruned_block_range, Some((0, 3)));
+        assert_eq!(summary.archive_files.len(), 1);
+        assert_eq!(
+            db.get_tx_outs_by_block_and_key(ingress_key, 3).unwrap(),
+            Some(vec![])
+        );
+
+        // The archive holds the pruned records.
+        let archive: PrunedBlocksArchive =
+            mc_util_serial::decode(&fs::read(&summary.archive_files[0]).unwrap()).unwrap();
+        assert_eq!(archive.blocks.len(), 4);
+        for (block, records) in archive.blocks.iter().zip(expected_records.iter()) {
+            assert_eq!(block.ingress_public_key, ingress_key.as_bytes().to_vec());
+            assert_eq!(block.ingest_invocation_id, *invoc_id);
+            assert_eq!(&block.e_tx_out_records, records);
+        }
+
+        // Prune the rest.
+        let summary = prune_recovery_db(
+            &db,
+            &policy,
+            None,
+            false,
+            DEFAULT_MAX_PRUNED_BLOCKS,
+            &logger,
+        )
+        .unwrap();
+        assert_eq!(summary.num_pruned_blocks, 6);
+        assert_eq!(summary.num_pruned_records, 18);
+        assert_eq!(summary.pruned_block_range, Some((4, 9)));
+        assert!(summary.archive_files.is_empty());
+        assert_eq!(
+            db.get_tx_outs_by_block_and_key(ingress_key, 10)
+                .unwrap()
+                .map(|records| records.len()),
+            Some(3)
+        );
+    }
+}
@@ -23,7 +23,7 @@ use mc_fog_api::{
     ingest_common::{IngestControllerMode, IngestSummary},
     ingest_grpc, ingest_peer_grpc,
 };
-use mc_fog_recovery_db_iface::{RecoveryDb, ReportDb};
+use mc_fog_recovery_db_iface::{RecoveryDb, ReportDb, RetentionPolicy};
 use mc_fog_uri::{FogIngestUri, IngestPeerUri};
 use mc_ledger_db::{Ledger, LedgerDB};
 use mc_util_grpc::ConnectionUriGrpcioServer;
@@ -95,6 +95,14 @@ pub struct IngestServerConfig {
     /// During cargo tests we use a helper that searches the target/ dir for the
     /// enclave.so file.
     pub enclave_path: PathBuf,
+
+    /// The retention policy used when pruning ETxOutRecords from the recovery
+    /// db. Pruning only happens when requested over the admin API.
+    pub retention_policy: RetentionPolicy,
+
+    /// Optional directory that pruned ETxOutRecords are archived to before
+    /// they are deleted from the recovery db.
+    pub prune_archive_dir: Option<PathBuf>,
 }
 
 /// All of the state and grpcio objects and threads associated to the ingest
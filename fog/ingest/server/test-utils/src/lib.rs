@@ -233,6 +233,8 @@ impl IngestServerTestHelper {
             state_file: Some(StateFile::new(state_file_path.clone())),
             enclave_path: get_enclave_path(mc_fog_ingest_enclave::ENCLAVE_FILE),
             omap_capacity: OMAP_CAPACITY,
+            retention_policy: Default::default(),
+            prune_archive_dir: None,
         };
 
         let ra_client = AttestClient::new("").expect("Failed to create IAS client");
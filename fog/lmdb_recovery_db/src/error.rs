@@ -0,0 +1,80 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+use displaydoc::Display;
+use lmdb::Error as LmdbError;
+use mc_crypto_keys::CompressedRistrettoPublic;
+use mc_fog_recovery_db_iface::{IngestInvocationId, RecoveryDbError};
+use mc_util_lmdb::MetadataStoreError;
+use mc_util_serial::DecodeError;
+use std::io::Error as IoError;
+
+/// Enum for error types.
+#[derive(Display, Debug)]
+pub enum Error {
+    /// LMDB: {0}
+    Lmdb(LmdbError),
+
+    /// Metadata store: {0}
+    MetadataStore(MetadataStoreError),
+
+    /// IO: {0}
+    Io(IoError),
+
+    /// Database url is not an lmdb:// url: {0}
+    InvalidUrl(String),
+
+    /// The following ingress key was not found: {0:?}
+    MissingIngressKey(CompressedRistrettoPublic),
+
+    /// The following ingest invocation was not found: {0}
+    MissingIngestInvocation(IngestInvocationId),
+
+    /// IngressKeys schema violation: {0}
+    IngressKeysSchemaViolation(String),
+
+    /// New ingress key wasn't inserted successfully: {0}
+    IngressKeyUnsuccessfulInsert(String),
+
+    /// IngestedBlock schema violation: {0}
+    IngestedBlockSchemaViolation(String),
+
+    /// UserEvent schema violation on row #{0}: {1}
+    UserEventSchemaViolation(i64, &'static str),
+
+    /// The data in the database could not be decoded: {0}
+    Decode(DecodeError),
+}
+
+impl RecoveryDbError for Error {
+    /// Policy decision, whether the call should be retried.
+    ///
+    /// The database is embedded, so there are no connection issues that could
+    /// go away by retrying.
+    fn should_retry(&self) -> bool {
+        false
+    }
+}
+
+impl From<LmdbError> for Error {
+    fn from(src: LmdbError) -> Self {
+        Self::Lmdb(src)
+    }
+}
+
+impl From<MetadataStoreError> for Error {
+    fn from(src: MetadataStoreError) -> Self {
+        Self::MetadataStore(src)
+    }
+}
+
+impl From<IoError> for Error {
+    fn from(src: IoError) -> Self {
+        Self::Io(src)
+    }
+}
+
+impl From<DecodeError> for Error {
+    fn from(src: DecodeError) -> Self {
+        Self::Decode(src)
+    }
+}
@@ -0,0 +1,1384 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+#![deny(missing_docs)]
+
+//! Recovery db implementation using an embedded LMDB database.
+//!
+//! This allows running fog ingest, view and report servers without a
+//! PostgreSQL server, e.g. in local development stacks and integration tests.
+//! All servers sharing the database need to run on the same machine.
+
+mod error;
+mod records;
+
+use crate::records::{
+    IngestInvocationRecord, IngestedBlockRecord, IngressKeyRecord, ReportRecord,
+    TxOutSearchKeyRecord, UserEventRecord, UserEventType,
+};
+use lmdb::{Cursor, Database, DatabaseFlags, Environment, RwTransaction, Transaction, WriteFlags};
+use mc_attest_core::VerificationReport;
+use mc_common::{
+    logger::{log, Logger},
+    HashMap,
+};
+use mc_crypto_keys::CompressedRistrettoPublic;
+use mc_fog_kex_rng::KexRngPubkey;
+use mc_fog_recovery_db_iface::{
+    AddBlockDataStatus, FogUserEvent, IngestInvocationId, IngestableRange, IngressPublicKeyRecord,
+    IngressPublicKeyRecordFilters, IngressPublicKeyStatus, PrunableBlock, RecoveryDb, ReportData,
+    ReportDb, RetentionPolicy,
+};
+use mc_fog_types::{
+    common::BlockRange,
+    view::{DecommissionedIngestInvocation, RngRecord, TxOutSearchResult, TxOutSearchResultCode},
+    ETxOutRecord,
+};
+use mc_transaction_core::Block;
+use mc_util_lmdb::{MetadataStore, MetadataStoreSettings};
+use mc_util_serial::{decode, encode, Message};
+use std::{
+    cmp::max,
+    convert::TryFrom,
+    fs,
+    path::Path,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub use error::Error;
+
+/// The url scheme selecting this database, e.g.
+/// `lmdb:///var/lib/fog/recovery-db`.
+pub const URL_SCHEME: &str = "lmdb://";
+
+/// LMDB Constant.
+const MAX_LMDB_FILE_SIZE: usize = 1_099_511_627_776; // 1 TB
+
+/// Number of LMDB databases.
+const NUM_LMDB_DATABASES: u32 = 9;
+
+/// Metadata store settings that are used for version control.
+#[derive(Clone, Default, Debug)]
+pub struct LmdbRecoveryDbMetadataStoreSettings;
+impl MetadataStoreSettings for LmdbRecoveryDbMetadataStoreSettings {
+    // Default database version. This should be bumped when breaking changes are
+    // introduced. If this is properly maintained, we could check during ledger
+    // db opening for any incompatibilities, and either refuse to open or
+    // perform a migration.
+    #[allow(clippy::unreadable_literal)]
+    const LATEST_VERSION: u64 = 20220601;
+
+    /// The current crate version that manages the database.
+    const CRATE_VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+    /// LMDB Database name to use for storing the metadata information.
+    const DB_NAME: &'static str = "fog_recovery_db_metadata";
+}
+
+/// Ingress keys database name.
+pub const INGRESS_KEYS_DB_NAME: &str = "fog_recovery_db:ingress_keys";
+
+/// Ingest invocations database name.
+pub const INGEST_INVOCATIONS_DB_NAME: &str = "fog_recovery_db:ingest_invocations";
+
+/// Ingested blocks database name.
+pub const INGESTED_BLOCKS_DB_NAME: &str = "fog_recovery_db:ingested_blocks";
+
+/// Ingress keys by block index database name.
+pub const INGRESS_KEYS_BY_BLOCK_DB_NAME: &str = "fog_recovery_db:ingress_keys_by_block";
+
+/// ETxOutRecords by search key database name.
+pub const TX_OUTS_BY_SEARCH_KEY_DB_NAME: &str = "fog_recovery_db:tx_outs_by_search_key";
+
+/// User events database name.
+pub const USER_EVENTS_DB_NAME: &str = "fog_recovery_db:user_events";
+
+/// Reports database name.
+pub const REPORTS_DB_NAME: &str = "fog_recovery_db:reports";
+
+/// Counters database name.
+pub const COUNTERS_DB_NAME: &str = "fog_recovery_db:counters";
+
+/// Keys used by the `counters` database.
+const COUNTER_LAST_INGEST_INVOCATION_ID: &str = "last_ingest_invocation_id";
+const COUNTER_LAST_USER_EVENT_ID: &str = "last_user_event_id";
+const COUNTER_LAST_REPORT_SEQUENCE: &str = "last_report_sequence";
+const COUNTER_HIGHEST_KNOWN_BLOCK_INDEX: &str = "highest_known_block_index";
+
+/// Returns true if the given database url selects the LMDB recovery db.
+pub fn is_lmdb_url(database_url: &str) -> bool {
+    database_url.starts_with(URL_SCHEME)
+}
+
+/// LMDB-backed recovery database.
+#[derive(Clone)]
+pub struct LmdbRecoveryDb {
+    /// LMDB Environment (database).
+    env: Arc<Environment>,
+
+    /// Ingress public key -> IngressKeyRecord.
+    ingress_keys: Database,
+
+    /// Ingest invocation id -> IngestInvocationRecord.
+    ingest_invocations: Database,
+
+    /// (Ingress public key, block index) -> IngestedBlockRecord.
+    /// Keying by ingress key enforces that each block is scanned at most once
+    /// per key.
+    ingested_blocks: Database,
+
+    /// (Block index, ingress public key) -> empty.
+    /// Allows looking up ingested blocks by block index.
+    ingress_keys_by_block: Database,
+
+    /// ETxOutRecord search key -> TxOutSearchKeyRecord.
+    /// Allows looking up records without scanning the ingested blocks.
+    tx_outs_by_search_key: Database,
+
+    /// User event id -> UserEventRecord.
+    user_events: Database,
+
+    /// Fog report id -> ReportRecord.
+    reports: Database,
+
+    /// Counter name -> u64 (big endian). Used for id allocation and for
+    /// keeping track of the highest known block index.
+    counters: Database,
+
+    /// Logger.
+    logger: Logger,
+}
+
+impl LmdbRecoveryDb {
+    /// Open the database at the given path, creating it if it does not exist.
+    pub fn open(path: &Path, logger: Logger) -> Result<Self, Error> {
+        fs::create_dir_all(path)?;
+
+        let env = Arc::new(
+            Environment::new()
+                .set_max_dbs(NUM_LMDB_DATABASES)
+                .set_map_size(MAX_LMDB_FILE_SIZE)
+                .open(path)?,
+        );
+
+        let metadata_store =
+            MetadataStore::<LmdbRecoveryDbMetadataStoreSettings>::open_or_create(&env)?;
+
+        let db_txn = env.begin_ro_txn()?;
+        let version = metadata_store.get_version(&db_txn)?;
+        log::info!(logger, "Recovery db is currently at version: {:?}", version);
+        db_txn.commit()?;
+
+        version.is_compatible_with_latest()?;
+
+        let ingress_keys = env.create_db(Some(INGRESS_KEYS_DB_NAME), DatabaseFlags::empty())?;
+        let ingest_invocations =
+            env.create_db(Some(INGEST_INVOCATIONS_DB_NAME), DatabaseFlags::empty())?;
+        let ingested_blocks =
+            env.create_db(Some(INGESTED_BLOCKS_DB_NAME), DatabaseFlags::empty())?;
+        let ingress_keys_by_block =
+            env.create_db(Some(INGRESS_KEYS_BY_BLOCK_DB_NAME), DatabaseFlags::empty())?;
+        let tx_outs_by_search_key =
+            env.create_db(Some(TX_OUTS_BY_SEARCH_KEY_DB_NAME), DatabaseFlags::empty())?;
+        let user_events = env.create_db(Some(USER_EVENTS_DB_NAME), DatabaseFlags::empty())?;
+        let reports = env.create_db(Some(REPORTS_DB_NAME), DatabaseFlags::empty())?;
+        let counters = env.create_db(Some(COUNTERS_DB_NAME), DatabaseFlags::empty())?;
+
+        Ok(Self {
+            env,
+            ingress_keys,
+            ingest_invocations,
+            ingested_blocks,
+            ingress_keys_by_block,
+            tx_outs_by_search_key,
+            user_events,
+            reports,
+            counters,
+            logger,
+        })
+    }
+
+    /// Open the database at the path given by an `lmdb://` url, creating it
+    /// if it does not exist.
+    pub fn new_from_url(database_url: &str, logger: Logger) -> Result<Self, Error> {
+        let path = database_url
+            .strip_prefix(URL_SCHEME)
+            .filter(|path| !path.is_empty())
+            .ok_or_else(|| Error::InvalidUrl(database_url.to_string()))?;
+        Self::open(Path::new(path), logger)
+    }
+
+    ////
+    // Helpers that operate on an open transaction.
+    ////
+
+    fn get_counter(&self, db_txn: &impl Transaction, name: &str) -> Result<Option<u64>, Error> {
+        match db_txn.get(self.counters, &name) {
+            Ok(bytes) => Ok(Some(u64_from_be_bytes(bytes)?)),
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn set_counter(&self, db_txn: &mut RwTransaction, name: &str, value: u64) -> Result<(), Error> {
+        db_txn.put(
+            self.counters,
+            &name,
+            &value.to_be_bytes(),
+            WriteFlags::empty(),
+        )?;
+        Ok(())
+    }
+
+    /// Allocate the next id from a counter. Ids start at 1.
+    fn next_id(&self, db_txn: &mut RwTransaction, name: &str) -> Result<u64, Error> {
+        let id = self.get_counter(db_txn, name)?.unwrap_or(0) + 1;
+        self.set_counter(db_txn, name, id)?;
+        Ok(id)
+    }
+
+    fn get_ingress_key_record(
+        &self,
+        db_txn: &impl Transaction,
+        key: &CompressedRistrettoPublic,
+    ) -> Result<Option<IngressKeyRecord>, Error> {
+        get_record(db_txn, self.ingress_keys, key.as_ref())
+    }
+
+    fn put_ingress_key_record(
+        &self,
+        db_txn: &mut RwTransaction,
+        key: &CompressedRistrettoPublic,
+        record: &IngressKeyRecord,
+    ) -> Result<(), Error> {
+        db_txn.put(
+            self.ingress_keys,
+            &key.as_ref(),
+            &encode(record),
+            WriteFlags::empty(),
+        )?;
+        Ok(())
+    }
+
+    fn get_ingest_invocation_record(
+        &self,
+        db_txn: &impl Transaction,
+        ingest_invocation_id: &IngestInvocationId,
+    ) -> Result<Option<IngestInvocationRecord>, Error> {
+        get_record(
+            db_txn,
+            self.ingest_invocations,
+            &ingest_invocation_id_to_key(ingest_invocation_id),
+        )
+    }
+
+    fn put_ingest_invocation_record(
+        &self,
+        db_txn: &mut RwTransaction,
+        ingest_invocation_id: &IngestInvocationId,
+        record: &IngestInvocationRecord,
+    ) -> Result<(), Error> {
+        db_txn.put(
+            self.ingest_invocations,
+            &ingest_invocation_id_to_key(ingest_invocation_id),
+            &encode(record),
+            WriteFlags::empty(),
+        )?;
+        Ok(())
+    }
+
+    fn add_user_event(
+        &self,
+        db_txn: &mut RwTransaction,
+        event: &UserEventRecord,
+    ) -> Result<(), Error> {
+        let id = self.next_id(db_txn, COUNTER_LAST_USER_EVENT_ID)?;
+        db_txn.put(
+            self.user_events,
+            &id.to_be_bytes(),
+            &encode(event),
+            WriteFlags::NO_OVERWRITE,
+        )?;
+        Ok(())
+    }
+
+    /// Mark a given ingest invocation as decommissioned.
+    fn decommission_ingest_invocation_impl(
+        &self,
+        db_txn: &mut RwTransaction,
+        ingest_invocation_id: &IngestInvocationId,
+    ) -> Result<(), Error> {
+        let mut record = self
+            .get_ingest_invocation_record(db_txn, ingest_invocation_id)?
+            .ok_or(Error::MissingIngestInvocation(*ingest_invocation_id))?;
+        record.decommissioned = true;
+        record.last_active_at = now();
+        self.put_ingest_invocation_record(db_txn, ingest_invocation_id, &record)?;
+
+        self.add_user_event(
+            db_txn,
+            &UserEventRecord {
+                event_type: UserEventType::DecommissionIngestInvocation as i32,
+                ingest_invocation_id: Some(**ingest_invocation_id),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn get_ingested_block_records_for_block(
+        &self,
+        db_txn: &impl Transaction,
+        block_index: u64,
+    ) -> Result<Vec<IngestedBlockRecord>, Error> {
+        let block_index_bytes = block_index.to_be_bytes();
+        let keys = {
+            let mut cursor = db_txn.open_ro_cursor(self.ingress_keys_by_block)?;
+            let mut keys = Vec::new();
+            for item in cursor.iter_from(block_index_bytes) {
+                let (key, _value) = item?;
+                if !key.starts_with(&block_index_bytes) {
+                    break;
+                }
+                keys.push(key[block_index_bytes.len()..].to_vec());
+            }
+            keys
+        };
+
+        keys.into_iter()
+            .map(|ingress_key| {
+                get_record(
+                    db_txn,
+                    self.ingested_blocks,
+                    &ingested_block_key(&ingress_key, block_index),
+                )?
+                .ok_or_else(|| {
+                    Error::IngestedBlockSchemaViolation(format!(
+                        "Block {} is indexed but missing for ingress key {:?}",
+                        block_index, ingress_key
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    fn get_ingested_block_record(
+        &self,
+        ingress_key: &CompressedRistrettoPublic,
+        block_index: u64,
+    ) -> Result<Option<IngestedBlockRecord>, Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        get_record(
+            &db_txn,
+            self.ingested_blocks,
+            &ingested_block_key(ingress_key.as_ref(), block_index),
+        )
+    }
+}
+
+/// See trait `fog_recovery_db_iface::RecoveryDb` for documentation.
+impl RecoveryDb for LmdbRecoveryDb {
+    type Error = Error;
+
+    fn get_ingress_key_status(
+        &self,
+        key: &CompressedRistrettoPublic,
+    ) -> Result<Option<IngressPublicKeyStatus>, Self::Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        Ok(self
+            .get_ingress_key_record(&db_txn, key)?
+            .map(|record| ingress_key_status(&record)))
+    }
+
+    fn new_ingress_key(
+        &self,
+        key: &CompressedRistrettoPublic,
+        start_block_count: u64,
+    ) -> Result<u64, Self::Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        if self.get_ingress_key_record(&db_txn, key)?.is_some() {
+            return Err(Error::IngressKeyUnsuccessfulInsert(format!(
+                "Unable to insert ingress key: {:?}",
+                key
+            )));
+        }
+
+        let highest_known_block_count = self
+            .get_counter(&db_txn, COUNTER_HIGHEST_KNOWN_BLOCK_INDEX)?
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        let accepted_start_block_count = max(start_block_count, highest_known_block_count);
+
+        self.put_ingress_key_record(
+            &mut db_txn,
+            key,
+            &IngressKeyRecord {
+                start_block: accepted_start_block_count,
+                ..Default::default()
+            },
+        )?;
+
+        db_txn.commit()?;
+        Ok(accepted_start_block_count)
+    }
+
+    fn retire_ingress_key(
+        &self,
+        key: &CompressedRistrettoPublic,
+        set_retired: bool,
+    ) -> Result<(), Self::Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+        if let Some(mut record) = self.get_ingress_key_record(&db_txn, key)? {
+            record.retired = set_retired;
+            self.put_ingress_key_record(&mut db_txn, key, &record)?;
+        }
+        db_txn.commit()?;
+        Ok(())
+    }
+
+    fn get_last_scanned_block_index(
+        &self,
+        key: &CompressedRistrettoPublic,
+    ) -> Result<Option<u64>, Self::Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        Ok(self
+            .get_ingress_key_record(&db_txn, key)?
+            .and_then(|record| record.last_scanned_block))
+    }
+
+    fn get_ingress_key_records(
+        &self,
+        start_block_at_least: u64,
+        ingress_public_key_record_filters: &IngressPublicKeyRecordFilters,
+    ) -> Result<Vec<IngressPublicKeyRecord>, Self::Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        let mut cursor = db_txn.open_ro_cursor(self.ingress_keys)?;
+
+        let mut results = Vec::new();
+        for item in cursor.iter_start() {
+            let (key_bytes, value_bytes) = item?;
+            let record: IngressKeyRecord = decode(value_bytes)?;
+
+            if record.start_block < start_block_at_least {
+                continue;
+            }
+            if ingress_public_key_record_filters.should_only_include_unexpired_keys
+                && !record
+                    .last_scanned_block
+                    .map(|last_scanned| record.pubkey_expiry > last_scanned)
+                    .unwrap_or(false)
+            {
+                continue;
+            }
+            if !ingress_public_key_record_filters.should_include_lost_keys && record.lost {
+                continue;
+            }
+            if !ingress_public_key_record_filters.should_include_retired_keys && record.retired {
+                continue;
+            }
+
+            let key = CompressedRistrettoPublic::try_from(key_bytes).map_err(|_| {
+                Error::IngressKeysSchemaViolation(format!(
+                    "Invalid ingress key bytes: {:?}",
+                    key_bytes
+                ))
+            })?;
+            results.push(IngressPublicKeyRecord {
+                key,
+                status: ingress_key_status(&record),
+                last_scanned_block: record.last_scanned_block,
+            });
+        }
+
+        Ok(results)
+    }
+
+    fn new_ingest_invocation(
+        &self,
+        prev_ingest_invocation_id: Option<IngestInvocationId>,
+        ingress_public_key: &CompressedRistrettoPublic,
+        egress_public_key: &KexRngPubkey,
+        start_block: u64,
+    ) -> Result<IngestInvocationId, Self::Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        // Optionally decommission old invocation.
+        if let Some(prev_ingest_invocation_id) = prev_ingest_invocation_id {
+            self.decommission_ingest_invocation_impl(&mut db_txn, &prev_ingest_invocation_id)?;
+        }
+
+        if self
+            .get_ingress_key_record(&db_txn, ingress_public_key)?
+            .is_none()
+        {
+            return Err(Error::MissingIngressKey(*ingress_public_key));
+        }
+
+        // Write new invocation.
+        let ingest_invocation_id = IngestInvocationId::from(
+            self.next_id(&mut db_txn, COUNTER_LAST_INGEST_INVOCATION_ID)? as i64,
+        );
+        self.put_ingest_invocation_record(
+            &mut db_txn,
+            &ingest_invocation_id,
+            &IngestInvocationRecord {
+                ingress_public_key: ingress_public_key.as_ref().to_vec(),
+                egress_public_key: egress_public_key.public_key.clone(),
+                rng_version: egress_public_key.version,
+                start_block,
+                decommissioned: false,
+                last_active_at: now(),
+                last_ingested_block: None,
+            },
+        )?;
+
+        // Write a user event.
+        self.add_user_event(
+            &mut db_txn,
+            &UserEventRecord {
+                event_type: UserEventType::NewIngestInvocation as i32,
+                ingest_invocation_id: Some(*ingest_invocation_id),
+                ..Default::default()
+            },
+        )?;
+
+        db_txn.commit()?;
+        Ok(ingest_invocation_id)
+    }
+
+    fn get_ingestable_ranges(&self) -> Result<Vec<IngestableRange>, Self::Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        let mut cursor = db_txn.open_ro_cursor(self.ingest_invocations)?;
+
+        let mut results = Vec::new();
+        for item in cursor.iter_start() {
+            let (key_bytes, value_bytes) = item?;
+            let record: IngestInvocationRecord = decode(value_bytes)?;
+            results.push(IngestableRange {
+                id: IngestInvocationId::from(u64_from_be_bytes(key_bytes)? as i64),
+                start_block: record.start_block,
+                decommissioned: record.decommissioned,
+                last_ingested_block: record.last_ingested_block,
+            });
+        }
+
+        Ok(results)
+    }
+
+    fn decommission_ingest_invocation(
+        &self,
+        ingest_invocation_id: &IngestInvocationId,
+    ) -> Result<(), Self::Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+        self.decommission_ingest_invocation_impl(&mut db_txn, ingest_invocation_id)?;
+        db_txn.commit()?;
+        Ok(())
+    }
+
+    fn add_block_data(
+        &self,
+        ingest_invocation_id: &IngestInvocationId,
+        block: &Block,
+        block_signature_timestamp: u64,
+        txs: &[ETxOutRecord],
+    ) -> Result<AddBlockDataStatus, Self::Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        let mut invocation = self
+            .get_ingest_invocation_record(&db_txn, ingest_invocation_id)?
+            .ok_or(Error::MissingIngestInvocation(*ingest_invocation_id))?;
+        let ingress_key = CompressedRistrettoPublic::try_from(&invocation.ingress_public_key[..])
+            .map_err(|_| {
+            Error::IngressKeysSchemaViolation(format!(
+                "Invalid ingress key bytes for invocation {}",
+                ingest_invocation_id
+            ))
+        })?;
+        let mut key_record = self
+            .get_ingress_key_record(&db_txn, &ingress_key)?
+            .ok_or(Error::MissingIngressKey(ingress_key))?;
+
+        // Add an IngestedBlock record, unless this key already scanned this block.
+        let record = IngestedBlockRecord {
+            ingest_invocation_id: **ingest_invocation_id,
+            cumulative_txo_count: block.cumulative_txo_count,
+            block_signature_timestamp,
+            e_tx_out_records: txs.to_vec(),
+        };
+        match db_txn.put(
+            self.ingested_blocks,
+            &ingested_block_key(ingress_key.as_ref(), block.index),
+            &encode(&record),
+            WriteFlags::NO_OVERWRITE,
+        ) {
+            Ok(()) => {}
+            Err(lmdb::Error::KeyExist) => {
+                return Ok(AddBlockDataStatus {
+                    block_already_scanned_with_this_key: true,
+                })
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        let mut block_index_key = block.index.to_be_bytes().to_vec();
+        block_index_key.extend_from_slice(ingress_key.as_ref());
+        db_txn.put(
+            self.ingress_keys_by_block,
+            &block_index_key,
+            &[],
+            WriteFlags::empty(),
+        )?;
+
+        for e_tx_out_record in txs {
+            db_txn.put(
+                self.tx_outs_by_search_key,
+                &e_tx_out_record.search_key,
+                &encode(&TxOutSearchKeyRecord {
+                    ingress_public_key: ingress_key.as_ref().to_vec(),
+                    block_index: block.index,
+                    payload: e_tx_out_record.payload.clone(),
+                }),
+                WriteFlags::empty(),
+            )?;
+        }
+
+        // Update the progress of the invocation and the key.
+        invocation.last_ingested_block = Some(
+            invocation
+                .last_ingested_block
+                .map_or(block.index, |index| max(index, block.index)),
+        );
+        invocation.last_active_at = now();
+        self.put_ingest_invocation_record(&mut db_txn, ingest_invocation_id, &invocation)?;
+
+        key_record.last_scanned_block = Some(
+            key_record
+                .last_scanned_block
+                .map_or(block.index, |index| max(index, block.index)),
+        );
+        self.put_ingress_key_record(&mut db_txn, &ingress_key, &key_record)?;
+
+        let highest_known_block_index = self
+            .get_counter(&db_txn, COUNTER_HIGHEST_KNOWN_BLOCK_INDEX)?
+            .map_or(block.index, |index| max(index, block.index));
+        self.set_counter(
+            &mut db_txn,
+            COUNTER_HIGHEST_KNOWN_BLOCK_INDEX,
+            highest_known_block_index,
+        )?;
+
+        db_txn.commit()?;
+        Ok(AddBlockDataStatus {
+            block_already_scanned_with_this_key: false,
+        })
+    }
+
+    fn report_lost_ingress_key(
+        &self,
+        lost_ingress_key: CompressedRistrettoPublic,
+    ) -> Result<(), Self::Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        // Find the ingress key and mark it lost
+        let mut record = self
+            .get_ingress_key_record(&db_txn, &lost_ingress_key)?
+            .ok_or(Error::MissingIngressKey(lost_ingress_key))?;
+        record.lost = true;
+        self.put_ingress_key_record(&mut db_txn, &lost_ingress_key, &record)?;
+
+        // The blocks we promised to scan but did not are missed.
+        let missed_block_range = BlockRange {
+            start_block: record
+                .last_scanned_block
+                .map_or(record.start_block, |index| {
+                    max(index + 1, record.start_block)
+                }),
+            end_block: record.pubkey_expiry,
+        };
+
+        // If the missed block range is invalid (empty), we don't have to add it.
+        // This can happen if the ingress key was never actually published to the report
+        // server, and then pubkey_expiry is zero.
+        if missed_block_range.is_valid() {
+            self.add_user_event(
+                &mut db_txn,
+                &UserEventRecord {
+                    event_type: UserEventType::MissingBlocks as i32,
+                    missing_blocks_start: Some(missed_block_range.start_block),
+                    missing_blocks_end: Some(missed_block_range.end_block),
+                    ..Default::default()
+                },
+            )?;
+        }
+
+        db_txn.commit()?;
+        Ok(())
+    }
+
+    fn get_missed_block_ranges(&self) -> Result<Vec<BlockRange>, Self::Error> {
+        let (events, _) = self.search_user_events(0)?;
+        Ok(events
+            .into_iter()
+            .filter_map(|event| match event {
+                FogUserEvent::MissingBlocks(range) => Some(range),
+                _ => None,
+            })
+            .collect())
+    }
+
+    fn search_user_events(
+        &self,
+        start_from_user_event_id: i64,
+    ) -> Result<(Vec<FogUserEvent>, i64), Self::Error> {
+        // Early return if start_from_user_event_id is max
+        if start_from_user_event_id == i64::MAX {
+            return Ok((Default::default(), i64::MAX));
+        }
+
+        let db_txn = self.env.begin_ro_txn()?;
+        let mut cursor = db_txn.open_ro_cursor(self.user_events)?;
+
+        // Event ids start from 1, so "start_from_user_event_id = 0" will capture
+        // everything. If no events are found, return start_from_user_event_id and
+        // not 0.
+        let first_id = max(start_from_user_event_id, 0) as u64 + 1;
+        let mut max_user_event_id = start_from_user_event_id;
+        let mut events = Vec::new();
+        for item in cursor.iter_from(first_id.to_be_bytes()) {
+            let (key_bytes, value_bytes) = item?;
+            let user_event_id = u64_from_be_bytes(key_bytes)? as i64;
+            let record: UserEventRecord = decode(value_bytes)?;
+            max_user_event_id = max(max_user_event_id, user_event_id);
+
+            let event = match UserEventType::from_i32(record.event_type) {
+                Some(UserEventType::NewIngestInvocation) => {
+                    let ingest_invocation_id =
+                        record
+                            .ingest_invocation_id
+                            .ok_or(Error::UserEventSchemaViolation(
+                                user_event_id,
+                                "missing ingest_invocation_id",
+                            ))?;
+                    let invocation = self
+                        .get_ingest_invocation_record(
+                            &db_txn,
+                            &IngestInvocationId::from(ingest_invocation_id),
+                        )?
+                        .ok_or(Error::UserEventSchemaViolation(
+                            user_event_id,
+                            "unknown ingest invocation",
+                        ))?;
+                    FogUserEvent::NewRngRecord(RngRecord {
+                        ingest_invocation_id,
+                        pubkey: mc_fog_types::view::KexRngPubkey {
+                            public_key: invocation.egress_public_key,
+                            version: invocation.rng_version,
+                        },
+                        start_block: invocation.start_block,
+                    })
+                }
+                Some(UserEventType::DecommissionIngestInvocation) => {
+                    let ingest_invocation_id =
+                        record
+                            .ingest_invocation_id
+                            .ok_or(Error::UserEventSchemaViolation(
+                                user_event_id,
+                                "missing ingest_invocation_id",
+                            ))?;
+                    let invocation = self
+                        .get_ingest_invocation_record(
+                            &db_txn,
+                            &IngestInvocationId::from(ingest_invocation_id),
+                        )?
+                        .ok_or(Error::UserEventSchemaViolation(
+                            user_event_id,
+                            "unknown ingest invocation",
+                        ))?;
+                    FogUserEvent::DecommissionIngestInvocation(DecommissionedIngestInvocation {
+                        ingest_invocation_id,
+                        last_ingested_block: invocation.last_ingested_block.unwrap_or(0),
+                    })
+                }
+                Some(UserEventType::MissingBlocks) => FogUserEvent::MissingBlocks(BlockRange {
+                    start_block: record.missing_blocks_start.ok_or(
+                        Error::UserEventSchemaViolation(
+                            user_event_id,
+                            "missing missing_blocks_start",
+                        ),
+                    )?,
+                    end_block: record
+                        .missing_blocks_end
+                        .ok_or(Error::UserEventSchemaViolation(
+                            user_event_id,
+                            "missing missing_blocks_end",
+                        ))?,
+                }),
+                None => {
+                    return Err(Error::UserEventSchemaViolation(
+                        user_event_id,
+                        "unknown event type",
+                    ))
+                }
+            };
+            events.push(event);
+        }
+
+        Ok((events, max_user_event_id))
+    }
+
+    fn get_tx_outs(
+        &self,
+        start_block: u64,
+        search_keys: &[Vec<u8>],
+    ) -> Result<Vec<TxOutSearchResult>, Self::Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+
+        search_keys
+            .iter()
+            .map(|search_key| {
+                let record: Option<TxOutSearchKeyRecord> =
+                    get_record(&db_txn, self.tx_outs_by_search_key, search_key)?;
+                Ok(match record {
+                    Some(record) if record.block_index >= start_block => TxOutSearchResult {
+                        search_key: search_key.clone(),
+                        result_code: TxOutSearchResultCode::Found as u32,
+                        ciphertext: record.payload,
+                    },
+                    _ => TxOutSearchResult {
+                        search_key: search_key.clone(),
+                        result_code: TxOutSearchResultCode::NotFound as u32,
+                        ciphertext: Default::default(),
+                    },
+                })
+            })
+            .collect()
+    }
+
+    fn update_last_active_at(
+        &self,
+        ingest_invocation_id: &IngestInvocationId,
+    ) -> Result<(), Self::Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+        if let Some(mut record) =
+            self.get_ingest_invocation_record(&db_txn, ingest_invocation_id)?
+        {
+            record.last_active_at = now();
+            self.put_ingest_invocation_record(&mut db_txn, ingest_invocation_id, &record)?;
+        }
+        db_txn.commit()?;
+        Ok(())
+    }
+
+    fn get_tx_outs_by_block_and_key(
+        &self,
+        ingress_key: CompressedRistrettoPublic,
+        block_index: u64,
+    ) -> Result<Option<Vec<ETxOutRecord>>, Self::Error> {
+        Ok(self
+            .get_ingested_block_record(&ingress_key, block_index)?
+            .map(|record| record.e_tx_out_records))
+    }
+
+    fn get_invocation_id_by_block_and_key(
+        &self,
+        ingress_key: CompressedRistrettoPublic,
+        block_index: u64,
+    ) -> Result<Option<IngestInvocationId>, Self::Error> {
+        Ok(self
+            .get_ingested_block_record(&ingress_key, block_index)?
+            .map(|record| IngestInvocationId::from(record.ingest_invocation_id)))
+    }
+
+    fn get_cumulative_txo_count_for_block(
+        &self,
+        block_index: u64,
+    ) -> Result<Option<u64>, Self::Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        let counts: Vec<u64> = self
+            .get_ingested_block_records_for_block(&db_txn, block_index)?
+            .into_iter()
+            .map(|record| record.cumulative_txo_count)
+            .collect();
+
+        match counts.first() {
+            None => Ok(None),
+            Some(cumulative_txo_count) if counts.iter().all(|val| val == cumulative_txo_count) => {
+                Ok(Some(*cumulative_txo_count))
+            }
+            Some(_) => Err(Error::IngestedBlockSchemaViolation(format!(
+                "Found multiple cumulative_txo_count values for block {}: {:?}",
+                block_index, counts
+            ))),
+        }
+    }
+
+    fn get_block_signature_timestamp_for_block(
+        &self,
+        block_index: u64,
+    ) -> Result<Option<u64>, Self::Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        Ok(self
+            .get_ingested_block_records_for_block(&db_txn, block_index)?
+            .first()
+            .map(|record| record.block_signature_timestamp))
+    }
+
+    fn get_highest_known_block_index(&self) -> Result<Option<u64>, Self::Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        self.get_counter(&db_txn, COUNTER_HIGHEST_KNOWN_BLOCK_INDEX)
+    }
+
+    fn get_prunable_blocks(
+        &self,
+        retention_policy: &RetentionPolicy,
+        start_after: Option<(u64, CompressedRistrettoPublic)>,
+        max_blocks: usize,
+    ) -> Result<Vec<PrunableBlock>, Self::Error> {
+        let highest_known_block_index = match self.get_highest_known_block_index()? {
+            Some(index) if retention_policy.is_enabled() => index,
+            _ => return Ok(Vec::new()),
+        };
+
+        let key_records: HashMap<Vec<u8>, IngressPublicKeyRecord> = self
+            .get_ingress_key_records(
+                0,
+                &IngressPublicKeyRecordFilters {
+                    should_include_lost_keys: true,
+                    should_include_retired_keys: true,
+                    should_only_include_unexpired_keys: false,
+                },
+            )?
+            .into_iter()
+            .map(|record| (record.key.as_bytes().to_vec(), record))
+            .collect();
+
+        // Keys of the block index are the block index followed by the ingress
+        // key, so resuming after a block is a seek past its key.
+        let start_after_key = start_after.map(|(block_index, ingress_key)| {
+            let mut key = block_index.to_be_bytes().to_vec();
+            key.extend_from_slice(ingress_key.as_ref());
+            key
+        });
+
+        let db_txn = self.env.begin_ro_txn()?;
+        let mut cursor = db_txn.open_ro_cursor(self.ingress_keys_by_block)?;
+        let iter = match start_after_key.as_ref() {
+            Some(key) => cursor.iter_from(key),
+            None => cursor.iter_start(),
+        };
+
+        let mut results = Vec::new();
+        for item in iter {
+            if results.len() >= max_blocks {
+                break;
+            }
+
+            let (index_key, _value) = item?;
+            if Some(index_key) == start_after_key.as_deref() {
+                continue;
+            }
+            if index_key.len() < 8 {
+                return Err(lmdb::Error::Corrupted.into());
+            }
+            let block_index = u64_from_be_bytes(&index_key[..8])?;
+            let ingress_key =
+                CompressedRistrettoPublic::try_from(&index_key[8..]).map_err(|_| {
+                    Error::IngressKeysSchemaViolation(format!(
+                        "Invalid ingress key bytes in block index: {:?}",
+                        index_key
+                    ))
+                })?;
+            let key_record = key_records
+                .get(&index_key[8..])
+                .ok_or(Error::MissingIngressKey(ingress_key))?;
+            if !retention_policy.allows_pruning(block_index, key_record, highest_known_block_index)
+            {
+                continue;
+            }
+
+            let record: IngestedBlockRecord = get_record(
+                &db_txn,
+                self.ingested_blocks,
+                &ingested_block_key(ingress_key.as_ref(), block_index),
+            )?
+            .ok_or_else(|| {
+                Error::IngestedBlockSchemaViolation(format!(
+                    "Block {} is indexed but missing for ingress key {:?}",
+                    block_index, ingress_key
+                ))
+            })?;
+            // Skip blocks that were already pruned, or never had any records.
+            if record.e_tx_out_records.is_empty() {
+                continue;
+            }
+
+            results.push(PrunableBlock {
+                ingress_key,
+                block_index,
+                ingest_invocation_id: IngestInvocationId::from(record.ingest_invocation_id),
+                num_e_tx_out_records: record.e_tx_out_records.len() as u64,
+            });
+        }
+
+        Ok(results)
+    }
+
+    fn prune_blocks(
+        &self,
+        blocks: &[(CompressedRistrettoPublic, u64)],
+    ) -> Result<u64, Self::Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        let mut num_pruned_records = 0;
+        for (ingress_key, block_index) in blocks {
+            let key = ingested_block_key(ingress_key.as_ref(), *block_index);
+            if let Some(mut record) =
+                get_record::<IngestedBlockRecord>(&db_txn, self.ingested_blocks, &key)?
+            {
+                if record.e_tx_out_records.is_empty() {
+                    continue;
+                }
+                num_pruned_records += record.e_tx_out_records.len() as u64;
+                for e_tx_out_record in record.e_tx_out_records.drain(..) {
+                    // Only drop the index entry if no other block has since claimed the search
+                    // key.
+                    let indexed: Option<TxOutSearchKeyRecord> = get_record(
+                        &db_txn,
+                        self.tx_outs_by_search_key,
+                        &e_tx_out_record.search_key,
+                    )?;
+                    if indexed.map_or(false, |indexed| {
+                        indexed.block_index == *block_index
+                            && indexed.ingress_public_key == ingress_key.as_ref()
+                    }) {
+                        db_txn.del(
+                            self.tx_outs_by_search_key,
+                            &e_tx_out_record.search_key,
+                            None,
+                        )?;
+                    }
+                }
+                db_txn.put(
+                    self.ingested_blocks,
+                    &key,
+                    &encode(&record),
+                    WriteFlags::empty(),
+                )?;
+            }
+        }
+
+        db_txn.commit()?;
+        Ok(num_pruned_records)
+    }
+}
+
+/// See trait `fog_recovery_db_iface::ReportDb` for documentation.
+impl ReportDb for LmdbRecoveryDb {
+    type Error = Error;
+
+    fn get_all_reports(&self) -> Result<Vec<(String, ReportData)>, Self::Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        let mut cursor = db_txn.open_ro_cursor(self.reports)?;
+
+        let mut records = Vec::new();
+        for item in cursor.iter_start() {
+            let (key_bytes, value_bytes) = item?;
+            let record: ReportRecord = decode(value_bytes)?;
+            records.push((String::from_utf8_lossy(key_bytes).into_owned(), record));
+        }
+        records.sort_by_key(|(_report_id, record)| record.sequence);
+
+        records
+            .into_iter()
+            .map(|(report_id, record)| {
+                let report: VerificationReport = decode(&record.report)?;
+                Ok((
+                    report_id,
+                    ReportData {
+                        ingest_invocation_id: record
+                            .ingest_invocation_id
+                            .map(IngestInvocationId::from),
+                        report,
+                        pubkey_expiry: record.pubkey_expiry,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    fn set_report(
+        &self,
+        ingress_key: &CompressedRistrettoPublic,
+        report_id: &str,
+        data: &ReportData,
+    ) -> Result<IngressPublicKeyStatus, Self::Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        // First, try to update the pubkey_expiry value on this ingress key, only
+        // allowing it to increase, and only if it is not retired
+        let mut key_record = self
+            .get_ingress_key_record(&db_txn, ingress_key)?
+            .ok_or(Error::MissingIngressKey(*ingress_key))?;
+        if !key_record.retired && key_record.pubkey_expiry < data.pubkey_expiry {
+            key_record.pubkey_expiry = data.pubkey_expiry;
+            self.put_ingress_key_record(&mut db_txn, ingress_key, &key_record)?;
+        }
+
+        let result = ingress_key_status(&key_record);
+        log::info!(self.logger, "Got status for key: {:?}", result);
+        if result.retired {
+            log::info!(self.logger, "Cannot publish key because it is retired");
+            db_txn.commit()?;
+            return Ok(result);
+        }
+
+        // Replace any existing report with this id, keeping its position.
+        let sequence =
+            match get_record::<ReportRecord>(&db_txn, self.reports, report_id.as_bytes())? {
+                Some(existing) => existing.sequence,
+                None => self.next_id(&mut db_txn, COUNTER_LAST_REPORT_SEQUENCE)?,
+            };
+        let record = ReportRecord {
+            sequence,
+            ingress_public_key: ingress_key.as_ref().to_vec(),
+            ingest_invocation_id: data.ingest_invocation_id.map(i64::from),
+            report: encode(&data.report),
+            pubkey_expiry: data.pubkey_expiry,
+        };
+        db_txn.put(
+            self.reports,
+            &report_id,
+            &encode(&record),
+            WriteFlags::empty(),
+        )?;
+
+        db_txn.commit()?;
+        Ok(result)
+    }
+
+    fn remove_report(&self, report_id: &str) -> Result<(), Self::Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+        match db_txn.del(self.reports, &report_id, None) {
+            Ok(()) | Err(lmdb::Error::NotFound) => {}
+            Err(err) => return Err(err.into()),
+        }
+        db_txn.commit()?;
+        Ok(())
+    }
+}
+
+/// Read and decode a record, returning None if the key does not exist.
+fn get_record<T: Message + Default>(
+    db_txn: &impl Transaction,
+    db: Database,
+    key: &[u8],
+) -> Result<Option<T>, Error> {
+    match db_txn.get(db, &key) {
+        Ok(bytes) => Ok(Some(decode(bytes)?)),
+        Err(lmdb::Error::NotFound) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn ingress_key_status(record: &IngressKeyRecord) -> IngressPublicKeyStatus {
+    IngressPublicKeyStatus {
+        start_block: record.start_block,
+        pubkey_expiry: record.pubkey_expiry,
+        retired: record.retired,
+        lost: record.lost,
+    }
+}
+
+fn ingest_invocation_id_to_key(ingest_invocation_id: &IngestInvocationId) -> [u8; 8] {
+    (**ingest_invocation_id as u64).to_be_bytes()
+}
+
+fn ingested_block_key(ingress_key: &[u8], block_index: u64) -> Vec<u8> {
+    let mut key = ingress_key.to_vec();
+    key.extend_from_slice(&block_index.to_be_bytes());
+    key
+}
+
+fn u64_from_be_bytes(bytes: &[u8]) -> Result<u64, Error> {
+    let bytes = <[u8; 8]>::try_from(bytes).map_err(|_| lmdb::Error::Corrupted)?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+/// Seconds since the unix epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_common::logger::test_with_logger;
+    use mc_fog_test_infra::db_tests::{random_block, random_kex_rng_pubkey};
+    use mc_util_from_random::FromRandom;
+    use rand::{rngs::StdRng, SeedableRng};
+    use tempdir::TempDir;
+
+    #[test_with_logger]
+    fn test_reopen_keeps_data(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+        let db_dir = TempDir::new("lmdb_recovery_db").unwrap();
+        let url = format!("{}{}", URL_SCHEME, db_dir.path().to_str().unwrap());
+        assert!(is_lmdb_url(&url));
+
+        let ingress_key = CompressedRistrettoPublic::from_random(&mut rng);
+        let invoc_id = {
+            let db = LmdbRecoveryDb::new_from_url(&url, logger.clone()).unwrap();
+            db.new_ingress_key(&ingress_key, 0).unwrap();
+            let invoc_id = db
+                .new_ingest_invocation(None, &ingress_key, &random_kex_rng_pubkey(&mut rng), 0)
+                .unwrap();
+            let (block, records) = random_block(&mut rng, 0, 5);
+            db.add_block_data(&invoc_id, &block, 10, &records).unwrap();
+            invoc_id
+        };
+
+        let db = LmdbRecoveryDb::new_from_url(&url, logger).unwrap();
+        assert_eq!(db.get_highest_known_block_index().unwrap(), Some(0));
+        assert_eq!(
+            db.get_invocation_id_by_block_and_key(ingress_key, 0)
+                .unwrap(),
+            Some(invoc_id)
+        );
+        assert_eq!(
+            db.get_block_signature_timestamp_for_block(0).unwrap(),
+            Some(10)
+        );
+
+        // Ids keep increasing after reopening.
+        let invoc_id2 = db
+            .new_ingest_invocation(None, &ingress_key, &random_kex_rng_pubkey(&mut rng), 1)
+            .unwrap();
+        assert!(invoc_id2 > invoc_id);
+    }
+
+    #[test_with_logger]
+    fn test_add_block_data_twice(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+        let db_dir = TempDir::new("lmdb_recovery_db").unwrap();
+        let db = LmdbRecoveryDb::open(db_dir.path(), logger).unwrap();
+
+        let ingress_key = CompressedRistrettoPublic::from_random(&mut rng);
+        db.new_ingress_key(&ingress_key, 0).unwrap();
+        let invoc_id1 = db
+            .new_ingest_invocation(None, &ingress_key, &random_kex_rng_pubkey(&mut rng), 0)
+            .unwrap();
+        let invoc_id2 = db
+            .new_ingest_invocation(None, &ingress_key, &random_kex_rng_pubkey(&mut rng), 0)
+            .unwrap();
+
+        let (block, records) = random_block(&mut rng, 0, 5);
+        let status = db.add_block_data(&invoc_id1, &block, 0, &records).unwrap();
+        assert!(!status.block_already_scanned_with_this_key);
+
+        // Scanning the same block with the same key again is a no-op, even from a
+        // different invocation.
+        let (_, other_records) = random_block(&mut rng, 0, 3);
+        let status = db
+            .add_block_data(&invoc_id2, &block, 0, &other_records)
+            .unwrap();
+        assert!(status.block_already_scanned_with_this_key);
+        assert_eq!(
+            db.get_tx_outs_by_block_and_key(ingress_key, 0).unwrap(),
+            Some(records)
+        );
+        assert_eq!(
+            db.get_invocation_id_by_block_and_key(ingress_key, 0)
+                .unwrap(),
+            Some(invoc_id1)
+        );
+
+        let ranges = db.get_ingestable_ranges().unwrap();
+        assert_eq!(ranges[0].last_ingested_block, Some(0));
+        assert_eq!(ranges[1].last_ingested_block, None);
+    }
+
+    #[test_with_logger]
+    fn test_get_tx_outs_after_pruning(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+        let db_dir = TempDir::new("lmdb_recovery_db").unwrap();
+        let db = LmdbRecoveryDb::open(db_dir.path(), logger).unwrap();
+
+        let ingress_key = CompressedRistrettoPublic::from_random(&mut rng);
+        db.new_ingress_key(&ingress_key, 0).unwrap();
+        let invoc_id = db
+            .new_ingest_invocation(None, &ingress_key, &random_kex_rng_pubkey(&mut rng), 0)
+            .unwrap();
+
+        let (block0, records0) = random_block(&mut rng, 0, 2);
+        let (block1, records1) = random_block(&mut rng, 1, 2);
+        db.add_block_data(&invoc_id, &block0, 0, &records0).unwrap();
+        db.add_block_data(&invoc_id, &block1, 0, &records1).unwrap();
+
+        let search_keys = vec![
+            records0[0].search_key.clone(),
+            records1[1].search_key.clone(),
+            vec![7u8; 16],
+        ];
+        let result_codes = |start_block| -> Vec<u32> {
+            db.get_tx_outs(start_block, &search_keys)
+                .unwrap()
+                .into_iter()
+                .map(|result| result.result_code)
+                .collect()
+        };
+        let found = TxOutSearchResultCode::Found as u32;
+        let not_found = TxOutSearchResultCode::NotFound as u32;
+
+        let results = db.get_tx_outs(0, &search_keys).unwrap();
+        assert_eq!(results[0].ciphertext, records0[0].payload);
+        assert_eq!(results[1].ciphertext, records1[1].payload);
+        assert_eq!(result_codes(0), vec![found, found, not_found]);
+        assert_eq!(result_codes(1), vec![not_found, found, not_found]);
+
+        // Pruned records are no longer found.
+        assert_eq!(db.prune_blocks(&[(ingress_key, 0)]).unwrap(), 2);
+        assert_eq!(result_codes(0), vec![not_found, found, not_found]);
+    }
+
+    #[test_with_logger]
+    fn test_reports_db(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+        let db_dir = TempDir::new("lmdb_recovery_db").unwrap();
+        let db = LmdbRecoveryDb::open(db_dir.path(), logger).unwrap();
+
+        let ingress_key = CompressedRistrettoPublic::from_random(&mut rng);
+        db.new_ingress_key(&ingress_key, 0).unwrap();
+
+        let report = |pubkey_expiry| ReportData {
+            ingest_invocation_id: None,
+            report: Default::default(),
+            pubkey_expiry,
+        };
+
+        db.set_report(&ingress_key, "b", &report(10)).unwrap();
+        db.set_report(&ingress_key, "a", &report(20)).unwrap();
+        let status = db.set_report(&ingress_key, "b", &report(5)).unwrap();
+        // pubkey_expiry never decreases.
+        assert_eq!(status.pubkey_expiry, 20);
+
+        assert_eq!(
+            db.get_all_reports().unwrap(),
+            vec![("b".to_string(), report(5)), ("a".to_string(), report(20))]
+        );
+
+        // Retired keys do not get their reports published.
+        db.retire_ingress_key(&ingress_key, true).unwrap();
+        let status = db.set_report(&ingress_key, "c", &report(30)).unwrap();
+        assert!(status.retired);
+        assert_eq!(status.pubkey_expiry, 20);
+
+        db.remove_report("b").unwrap();
+        db.remove_report("does not exist").unwrap();
+        assert_eq!(
+            db.get_all_reports().unwrap(),
+            vec![("a".to_string(), report(20))]
+        );
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Protobuf records stored in the LMDB databases.
+
+use mc_fog_types::ETxOutRecord;
+use prost::{Enumeration, Message};
+
+/// An ingress public key and its status.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct IngressKeyRecord {
+    /// The first block that fog promises to scan with this key.
+    #[prost(uint64, tag = 1)]
+    pub start_block: u64,
+
+    /// The largest pubkey expiry value ever published for this key.
+    #[prost(uint64, tag = 2)]
+    pub pubkey_expiry: u64,
+
+    /// Whether this key is retiring / retired.
+    #[prost(bool, tag = 3)]
+    pub retired: bool,
+
+    /// Whether this key is lost.
+    #[prost(bool, tag = 4)]
+    pub lost: bool,
+
+    /// The last block scanned with this key, if any.
+    #[prost(uint64, optional, tag = 5)]
+    pub last_scanned_block: Option<u64>,
+}
+
+/// An ingest invocation.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct IngestInvocationRecord {
+    /// The ingress public key used by this invocation.
+    #[prost(bytes, tag = 1)]
+    pub ingress_public_key: Vec<u8>,
+
+    /// The kex rng public key emitted by the ingest enclave.
+    #[prost(bytes, tag = 2)]
+    pub egress_public_key: Vec<u8>,
+
+    /// The kex rng version.
+    #[prost(uint32, tag = 3)]
+    pub rng_version: u32,
+
+    /// The first block index this invocation ingests.
+    #[prost(uint64, tag = 4)]
+    pub start_block: u64,
+
+    /// Whether this invocation was decommissioned.
+    #[prost(bool, tag = 5)]
+    pub decommissioned: bool,
+
+    /// Seconds since the unix epoch at which this invocation was last seen
+    /// alive.
+    #[prost(uint64, tag = 6)]
+    pub last_active_at: u64,
+
+    /// The last block ingested by this invocation, if any.
+    #[prost(uint64, optional, tag = 7)]
+    pub last_ingested_block: Option<u64>,
+}
+
+/// The data produced by scanning a block with an ingress key.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct IngestedBlockRecord {
+    /// The invocation that scanned this block.
+    #[prost(int64, tag = 1)]
+    pub ingest_invocation_id: i64,
+
+    /// The cumulative txo count of the block.
+    #[prost(uint64, tag = 2)]
+    pub cumulative_txo_count: u64,
+
+    /// Seconds since the unix epoch when the block was signed.
+    #[prost(uint64, tag = 3)]
+    pub block_signature_timestamp: u64,
+
+    /// Any ETxOutRecord's that fog ingest emitted in connection to this block.
+    #[prost(repeated, message, tag = 4)]
+    pub e_tx_out_records: Vec<ETxOutRecord>,
+}
+
+/// Where the ETxOutRecord with a given search key was found, along with its
+/// payload.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct TxOutSearchKeyRecord {
+    /// The ingress public key of the block that contains the record.
+    #[prost(bytes, tag = 1)]
+    pub ingress_public_key: Vec<u8>,
+
+    /// The index of the block that contains the record.
+    #[prost(uint64, tag = 2)]
+    pub block_index: u64,
+
+    /// The payload of the ETxOutRecord.
+    #[prost(bytes, tag = 3)]
+    pub payload: Vec<u8>,
+}
+
+/// The type of a user event.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Enumeration)]
+#[repr(i32)]
+pub enum UserEventType {
+    /// A new ingest invocation (and with it a new rng record) was created.
+    NewIngestInvocation = 0,
+
+    /// An ingest invocation was decommissioned.
+    DecommissionIngestInvocation = 1,
+
+    /// A range of blocks was missed.
+    MissingBlocks = 2,
+}
+
+/// A user event.
+///
+/// The rng record and the last ingested block of a decommissioned invocation
+/// are looked up from the ingest invocation when the event is read.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct UserEventRecord {
+    /// The type of this event.
+    #[prost(enumeration = "UserEventType", tag = 1)]
+    pub event_type: i32,
+
+    /// The ingest invocation this event refers to, for
+    /// NewIngestInvocation and DecommissionIngestInvocation events.
+    #[prost(int64, optional, tag = 2)]
+    pub ingest_invocation_id: Option<i64>,
+
+    /// The first missed block, for MissingBlocks events.
+    #[prost(uint64, optional, tag = 3)]
+    pub missing_blocks_start: Option<u64>,
+
+    /// One past the last missed block, for MissingBlocks events.
+    #[prost(uint64, optional, tag = 4)]
+    pub missing_blocks_end: Option<u64>,
+}
+
+/// A fog report.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct ReportRecord {
+    /// Insertion order of this report id, used to list reports consistently.
+    #[prost(uint64, tag = 1)]
+    pub sequence: u64,
+
+    /// The ingress public key signed by this report.
+    #[prost(bytes, tag = 2)]
+    pub ingress_public_key: Vec<u8>,
+
+    /// The ingest invocation that wrote this report, if any.
+    #[prost(int64, optional, tag = 3)]
+    pub ingest_invocation_id: Option<i64>,
+
+    /// The encoded VerificationReport.
+    #[prost(bytes, tag = 4)]
+    pub report: Vec<u8>,
+
+    /// The pubkey expiry (a block height).
+    #[prost(uint64, tag = 5)]
+    pub pubkey_expiry: u64,
+}
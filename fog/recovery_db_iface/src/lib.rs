@@ -19,7 +19,7 @@ pub use mc_fog_types::{common::BlockRange, ETxOutRecord};
 pub use mc_transaction_core::Block;
 pub use types::{
     AddBlockDataStatus, FogUserEvent, IngestInvocationId, IngestableRange, IngressPublicKeyRecord,
-    IngressPublicKeyStatus, ReportData,
+    IngressPublicKeyStatus, PrunableBlock, ReportData, RetentionPolicy,
 };
 
 /// Contains fields that are used as filters in  queries for ingress keys.
@@ -309,6 +309,39 @@ pub trait RecoveryDb {
 
     /// Get the highest block index for which we have any data at all.
     fn get_highest_known_block_index(&self) -> Result<Option<u64>, Self::Error>;
+
+    /// Get ingested blocks whose ETxOutRecords may be pruned under the given
+    /// retention policy, ordered by block index and then by ingress key bytes.
+    ///
+    /// Blocks that were already pruned, or that have no ETxOutRecords, are not
+    /// returned. The ETxOutRecords themselves are not returned, they can be
+    /// read with get_tx_outs_by_block_and_key.
+    ///
+    /// Arguments:
+    /// * retention_policy: The policy selecting which blocks may be pruned
+    /// * start_after: If set, only blocks after this (block index, ingress key)
+    ///   pair are returned, so that a caller can resume after the last block of
+    ///   a previous call
+    /// * max_blocks: The maximum number of blocks to return
+    fn get_prunable_blocks(
+        &self,
+        retention_policy: &RetentionPolicy,
+        start_after: Option<(u64, CompressedRistrettoPublic)>,
+        max_blocks: usize,
+    ) -> Result<Vec<PrunableBlock>, Self::Error>;
+
+    /// Delete the ETxOutRecords of some ingested blocks.
+    ///
+    /// The rest of the block data is kept, and afterwards
+    /// get_tx_outs_by_block_and_key returns an empty list for these blocks.
+    ///
+    /// Arguments:
+    /// * blocks: (ingress key, block index) pairs identifying the blocks
+    ///
+    /// Returns:
+    /// * The number of ETxOutRecords that were deleted
+    fn prune_blocks(&self, blocks: &[(CompressedRistrettoPublic, u64)])
+        -> Result<u64, Self::Error>;
 }
 
 /// The report database interface.
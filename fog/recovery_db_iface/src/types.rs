@@ -225,6 +225,89 @@ pub struct ReportData {
     pub pubkey_expiry: u64,
 }
 
+/// Policy deciding which ETxOutRecords may be pruned from the recovery
+/// database.
+///
+/// Pruning only deletes the ETxOutRecords of a block. The rest of the block
+/// data (cumulative txo count, timestamp, ingest invocation) is kept, so that
+/// fog view can still make progress past pruned blocks.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct RetentionPolicy {
+    /// If set, blocks whose index is more than this many blocks below the
+    /// highest known block index may be pruned.
+    pub max_block_age: Option<u64>,
+
+    /// If set, blocks scanned with an ingress key that will not scan any more
+    /// blocks (it is lost, or retired and scanned up to its pubkey expiry) may
+    /// be pruned once the highest known block index is this many blocks past
+    /// the last block scanned with the key.
+    ///
+    /// The grace period is only counted in blocks: the database does not know
+    /// whether users caught up on the data of the key, so a user who does not
+    /// sync within the grace period misses the pruned TxOuts.
+    pub retired_key_grace_blocks: Option<u64>,
+}
+
+impl RetentionPolicy {
+    /// Whether this policy allows pruning anything at all.
+    pub fn is_enabled(&self) -> bool {
+        self.max_block_age.is_some() || self.retired_key_grace_blocks.is_some()
+    }
+
+    /// Whether the ETxOutRecords of a block scanned with the given ingress
+    /// key may be pruned.
+    ///
+    /// Arguments:
+    /// * block_index: The block that was scanned
+    /// * key_record: The ingress key the block was scanned with
+    /// * highest_known_block_index: The highest block index in the database
+    pub fn allows_pruning(
+        &self,
+        block_index: u64,
+        key_record: &IngressPublicKeyRecord,
+        highest_known_block_index: u64,
+    ) -> bool {
+        let too_old = self
+            .max_block_age
+            .map(|max_block_age| {
+                block_index.saturating_add(max_block_age) < highest_known_block_index
+            })
+            .unwrap_or(false);
+
+        let key_done_scanning = key_record.status.lost
+            || (key_record.status.retired
+                && key_record
+                    .last_scanned_block
+                    .map(|last_scanned| last_scanned + 1 >= key_record.status.pubkey_expiry)
+                    .unwrap_or(false));
+        let key_past_grace_period = key_done_scanning
+            && match (self.retired_key_grace_blocks, key_record.last_scanned_block) {
+                (Some(grace_blocks), Some(last_scanned)) => {
+                    last_scanned.saturating_add(grace_blocks) <= highest_known_block_index
+                }
+                _ => false,
+            };
+
+        too_old || key_past_grace_period
+    }
+}
+
+/// An ingested block whose ETxOutRecords were selected for pruning.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrunableBlock {
+    /// The ingress key the block was scanned with.
+    pub ingress_key: CompressedRistrettoPublic,
+
+    /// The block index.
+    pub block_index: u64,
+
+    /// The ingest invocation that scanned the block.
+    pub ingest_invocation_id: IngestInvocationId,
+
+    /// The number of ETxOutRecords that pruning the block would delete.
+    pub num_e_tx_out_records: u64,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -406,4 +489,67 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn retention_policy_max_block_age() {
+        let rec = IngressPublicKeyRecord {
+            key: Default::default(),
+            status: IngressPublicKeyStatus {
+                start_block: 0,
+                pubkey_expiry: 100,
+                retired: false,
+                lost: false,
+            },
+            last_scanned_block: Some(50),
+        };
+
+        let policy = RetentionPolicy::default();
+        assert!(!policy.is_enabled());
+        assert!(!policy.allows_pruning(0, &rec, 50));
+
+        let policy = RetentionPolicy {
+            max_block_age: Some(10),
+            retired_key_grace_blocks: None,
+        };
+        assert!(policy.is_enabled());
+        assert!(policy.allows_pruning(0, &rec, 50));
+        assert!(policy.allows_pruning(39, &rec, 50));
+        assert!(!policy.allows_pruning(40, &rec, 50));
+        assert!(!policy.allows_pruning(50, &rec, 50));
+    }
+
+    #[test]
+    fn retention_policy_retired_key_grace_blocks() {
+        let mut rec = IngressPublicKeyRecord {
+            key: Default::default(),
+            status: IngressPublicKeyStatus {
+                start_block: 0,
+                pubkey_expiry: 20,
+                retired: false,
+                lost: false,
+            },
+            last_scanned_block: Some(19),
+        };
+        let policy = RetentionPolicy {
+            max_block_age: None,
+            retired_key_grace_blocks: Some(5),
+        };
+
+        // Active keys are kept.
+        assert!(!policy.allows_pruning(10, &rec, 100));
+
+        // Retired keys are kept until they are scanned up to pubkey expiry, and the
+        // grace period is over.
+        rec.status.retired = true;
+        assert!(!policy.allows_pruning(10, &rec, 23));
+        assert!(policy.allows_pruning(10, &rec, 24));
+
+        rec.last_scanned_block = Some(15);
+        assert!(!policy.allows_pruning(10, &rec, 100));
+
+        // Lost keys will not scan anything anymore.
+        rec.status.lost = true;
+        assert!(!policy.allows_pruning(10, &rec, 19));
+        assert!(policy.allows_pruning(10, &rec, 20));
+    }
 }
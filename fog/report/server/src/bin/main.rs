@@ -3,7 +3,12 @@
 //! Main Method for the Fog Report Server
 
 use grpcio::{RpcStatus, RpcStatusCode};
-use mc_common::{logger, sentry};
+use mc_common::{
+    logger::{self, Logger},
+    sentry,
+};
+use mc_fog_lmdb_recovery_db::{is_lmdb_url, LmdbRecoveryDb};
+use mc_fog_recovery_db_iface::ReportDb;
 use mc_fog_report_server::{Config, Materials, Server};
 use mc_fog_sql_recovery_db::SqlRecoveryDb;
 use mc_util_cli::ParserWithBuildInfo;
@@ -20,13 +25,28 @@ fn main() {
 
     let materials = Materials::try_from(&config).expect("Could not read cryptographic materials");
 
-    let db = SqlRecoveryDb::new_from_url(
-        &env::var("DATABASE_URL").expect("DATABASE_URL environment variable missing"),
-        config.postgres_config.clone(),
-        logger.clone(),
-    )
-    .expect("Failed connecting to database");
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL environment variable missing");
+    if is_lmdb_url(&database_url) {
+        let db = LmdbRecoveryDb::new_from_url(&database_url, logger.clone())
+            .expect("Failed opening database");
+        run_server(config, materials, db, logger)
+    } else {
+        let db = SqlRecoveryDb::new_from_url(
+            &database_url,
+            config.postgres_config.clone(),
+            logger.clone(),
+        )
+        .expect("Failed connecting to database");
+        run_server(config, materials, db, logger)
+    }
+}
 
+fn run_server(
+    config: Config,
+    materials: Materials,
+    db: impl ReportDb + Clone + Send + Sync + 'static,
+    logger: Logger,
+) -> ! {
     let mut server = Server::new(db, &config.client_listen_uri, materials, logger.clone());
     server.start();
 
@@ -41,6 +41,16 @@ struct Config {
     /// View server URI
     #[clap(long, env = "MC_VIEW_URI")]
     pub view_uri: FogViewUri,
+
+    /// File in which the wallet state is persisted, so that the balance check
+    /// resumes where a previous run left off.
+    #[clap(long, env = "MC_WALLET_STATE")]
+    pub wallet_state: Option<PathBuf>,
+
+    /// Number of subaddresses, counting up from the default subaddress, that
+    /// fog view should find TxOuts for.
+    #[clap(long, default_value = "1", env = "MC_FOG_SUBADDRESS_COUNT")]
+    pub fog_subaddress_count: u64,
 }
 
 fn main() {
@@ -64,7 +74,10 @@ fn main() {
         account_key,
         logger.clone(),
     )
-    .build();
+    .fog_subaddress_range(Some(0..config.fog_subaddress_count))
+    .wallet_state_path(config.wallet_state)
+    .build()
+    .expect("Could not build client");
 
     loop {
         // Do a balance check and print result on one line in stdout
@@ -81,8 +94,14 @@ fn main() {
         let mut buffer = [0u8; 1];
         if let Err(err) = std::io::stdin().read_exact(&mut buffer) {
             match err.kind() {
-                ErrorKind::BrokenPipe => return,
-                ErrorKind::UnexpectedEof => return,
+                ErrorKind::BrokenPipe | ErrorKind::UnexpectedEof => {
+                    // Save the latest state before exiting, regardless of the
+                    // save period
+                    if let Err(err) = sample_paykit.save_wallet_state() {
+                        log::error!(logger, "Failed to save wallet state: {}", err);
+                    }
+                    return;
+                }
                 _ => {}
             }
         } else if buffer[0] == b'd' {
@@ -26,6 +26,7 @@ use mc_util_keyfile::UncheckedMnemonicAccount;
 use mc_util_uri::{ConsensusClientUri, Uri, UriScheme};
 use std::{
     convert::TryFrom,
+    path::PathBuf,
     str::FromStr,
     sync::{Arc, Mutex},
     thread::sleep,
@@ -65,17 +66,31 @@ struct Config {
         env = "MC_LISTEN_URI"
     )]
     pub listen_uri: RemoteWalletUri,
+
+    /// Directory in which the wallet state of each account is persisted, so
+    /// that balance checks resume where a previous run left off.
+    #[clap(long, env = "MC_WALLET_STATE_DIR")]
+    pub wallet_state_dir: Option<PathBuf>,
 }
 
 #[derive(Clone)]
 struct RemoteWalletService {
     state: Arc<Mutex<State>>,
+    wallet_state_dir: Option<PathBuf>,
     logger: Logger,
 }
 
 impl RemoteWalletService {
-    pub fn new(state: Arc<Mutex<State>>, logger: Logger) -> Self {
-        Self { state, logger }
+    pub fn new(
+        state: Arc<Mutex<State>>,
+        wallet_state_dir: Option<PathBuf>,
+        logger: Logger,
+    ) -> Self {
+        Self {
+            state,
+            wallet_state_dir,
+            logger,
+        }
     }
 
     fn fresh_balance_check_impl(
@@ -123,6 +138,16 @@ impl RemoteWalletService {
             ));
         };
 
+        // Each account gets its own wallet state file, named after its view
+        // public key.
+        let wallet_state_path = self.wallet_state_dir.as_ref().map(|dir| {
+            let view_public_key = account_key
+                .default_subaddress()
+                .view_public_key()
+                .to_bytes();
+            dir.join(format!("{}.wallet-state", hex::encode(view_public_key)))
+        });
+
         // Create client and perform balance check.
         let mut client = ClientBuilder::new(
             consensus_client_uri,
@@ -131,7 +156,9 @@ impl RemoteWalletService {
             account_key,
             self.logger.clone(),
         )
-        .build();
+        .wallet_state_path(wallet_state_path)
+        .build()
+        .map_err(|err| rpc_internal_error("build", err, &self.logger))?;
 
         let (balances, block_count) = client
             .check_balance()
@@ -282,6 +309,7 @@ fn main() {
 
     let remote_wallet_service = create_remote_wallet_api(RemoteWalletService::new(
         Arc::new(Mutex::new(State::default())),
+        config.wallet_state_dir.clone(),
         logger.clone(),
     ));
 
@@ -1,6 +1,9 @@
 // Copyright (c) 2018-2022 The MobileCoin Foundation
 
-use crate::error::{Error, Result, TxOutMatchingError};
+use crate::{
+    error::{Error, Result, TxOutMatchingError},
+    wallet_state::{StoredOwnedTxOut, WalletState},
+};
 use core::{
     cmp::{max, min},
     convert::TryFrom,
@@ -25,7 +28,7 @@ use mc_fog_types::{
     BlockCount,
 };
 use mc_fog_view_connection::FogViewGrpcClient;
-use mc_fog_view_protocol::{FogViewConnection, UserPrivate, UserRngSet};
+use mc_fog_view_protocol::{FogViewConnection, StoredUserRngSet, UserPrivate, UserRngSet};
 use mc_transaction_core::{
     get_tx_out_shared_secret,
     onetime_keys::{recover_onetime_private_key, recover_public_subaddress_spend_key},
@@ -35,7 +38,7 @@ use mc_transaction_core::{
 };
 use mc_transaction_std::MemoType;
 use mc_util_telemetry::{telemetry_static_key, tracer, Key, TraceContextExt, Tracer};
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 mod memo_handler;
 pub use memo_handler::{MemoHandler, MemoHandlerError};
@@ -66,6 +69,9 @@ const TELEMETRY_NUM_TXOS_KEY: Key = telemetry_static_key!("num-txos");
 /// returned, and the client will not spend this TxOut, and the balance of this
 /// account will not reflect such TxOut's. This has to cover at least the
 /// default and change subaddress indexes.
+///
+/// Subaddresses in the fog subaddress range (see
+/// `CachedTxData::set_fog_subaddress_range`) are supported as well.
 const SUBADDRESS_LOW_RANGE: RangeInclusive<u64> = 0..=DEFAULT_SUBADDRESS_INDEX;
 const SUBADDRESS_HIGH_RANGE: Range<u64> = CHANGE_SUBADDRESS_INDEX..INVALID_SUBADDRESS_INDEX;
 
@@ -84,6 +90,9 @@ pub struct CachedTxData {
     /// `num_blocks()` value which tells us to what block we are guaranteed to
     /// have all of the user's TxOuts
     rng_set: UserRngSet,
+    /// The fog credential of the account, covering every subaddress that we
+    /// ask fog view to find TxOuts for.
+    user_private: UserPrivate,
     /// The collection of OwnedTxOuts, keyed by the global index.
     ///
     /// These are TxOuts that we have found, that we know are ours, by view-key
@@ -117,6 +126,8 @@ pub struct CachedTxData {
     latest_block_version: u32,
     /// A memo handler which attempts to decrypt memos and validate them
     memo_handler: MemoHandler,
+    /// The global index of the owned TxOut whose memo was handled last
+    last_memo_global_index: Option<u64>,
     /// A pre-calculated map of subaddress public spend key to subaddress index.
     spsk_to_index: HashMap<RistrettoPublic, u64>,
     /// BlockRanges that Fog View has reported as missed, that we have not yet
@@ -135,6 +146,7 @@ impl CachedTxData {
             .collect();
 
         Self {
+            user_private: UserPrivate::from(&account_key),
             account_key,
             rng_set: UserRngSet::default(),
             owned_tx_outs: Default::default(),
@@ -142,12 +154,127 @@ impl CachedTxData {
             latest_global_txo_count: 0,
             latest_block_version: 0,
             memo_handler: MemoHandler::new(address_book, logger.clone()),
+            last_memo_global_index: None,
             spsk_to_index,
             missed_block_ranges: Vec::new(),
             logger,
         }
     }
 
+    /// Set the range of subaddresses to find TxOuts for with fog view, in
+    /// addition to the default subaddress.
+    ///
+    /// Fog view can only find TxOuts whose fog hint is for a scanned
+    /// subaddress, so this should cover every subaddress the account hands
+    /// out as a fog-enabled public address.
+    pub fn set_fog_subaddress_range(&mut self, subaddress_range: Range<u64>) -> Result<()> {
+        self.user_private =
+            UserPrivate::with_subaddress_range(&self.account_key, subaddress_range.clone())?;
+        for index in subaddress_range {
+            self.spsk_to_index.insert(
+                *self.account_key.subaddress(index).spend_public_key(),
+                index,
+            );
+        }
+        self.check_rng_set_subaddresses();
+        Ok(())
+    }
+
+    // The rngs for a subaddress are created when the rng records of the ingest
+    // invocations are ingested. If the rng set was made for other subaddresses
+    // than the ones we scan, start the fog view protocol over, so that rngs
+    // are created for all of them.
+    fn check_rng_set_subaddresses(&mut self) {
+        let rng_set_subaddresses: BTreeSet<u64> = self
+            .rng_set
+            .get_rngs()
+            .keys()
+            .map(|key| key.subaddress_index)
+            .collect();
+        let scanned_subaddresses: BTreeSet<u64> = self
+            .user_private
+            .subaddress_view_keys()
+            .map(|(index, _)| index)
+            .collect();
+        if !rng_set_subaddresses.is_empty() && rng_set_subaddresses != scanned_subaddresses {
+            log::warn!(
+                self.logger,
+                "Scanned subaddresses changed, restarting fog view protocol from scratch"
+            );
+            self.rng_set = UserRngSet::default();
+        }
+    }
+
+    /// Take a snapshot of the state learned from fog, so that it can be
+    /// restored later with `restore_wallet_state`.
+    pub fn to_wallet_state(&self) -> WalletState {
+        WalletState {
+            rng_set: StoredUserRngSet::from(&self.rng_set),
+            owned_tx_outs: self
+                .owned_tx_outs
+                .values()
+                .map(StoredOwnedTxOut::from)
+                .collect(),
+            key_image_data_completeness: u64::from(self.key_image_data_completeness),
+            latest_global_txo_count: self.latest_global_txo_count,
+            latest_block_version: self.latest_block_version,
+            missed_block_ranges: self.missed_block_ranges.clone(),
+            last_memo_global_index: self.last_memo_global_index,
+        }
+    }
+
+    /// Replace the state learned from fog with a snapshot taken by
+    /// `to_wallet_state`, so that polling fog resumes where the snapshot
+    /// left off.
+    pub fn restore_wallet_state(&mut self, state: WalletState) -> Result<()> {
+        let rng_set = UserRngSet::try_from(state.rng_set)?;
+        let owned_tx_outs = state
+            .owned_tx_outs
+            .into_iter()
+            .map(|stored| {
+                let owned_tx_out = OwnedTxOut::try_from(stored)?;
+                Ok((owned_tx_out.global_index, owned_tx_out))
+            })
+            .collect::<Result<BTreeMap<_, _>>>()?;
+        let last_memo_tx_out = state
+            .last_memo_global_index
+            .map(|global_index| {
+                owned_tx_outs
+                    .get(&global_index)
+                    .map(|owned_tx_out| owned_tx_out.tx_out.clone())
+                    .ok_or_else(|| {
+                        Error::InvalidWalletState(format!(
+                            "Last memo TxOut {} is not an owned TxOut",
+                            global_index
+                        ))
+                    })
+            })
+            .transpose()?;
+
+        self.rng_set = rng_set;
+        self.owned_tx_outs = owned_tx_outs;
+        self.key_image_data_completeness = BlockCount::from(state.key_image_data_completeness);
+        self.latest_global_txo_count = state.latest_global_txo_count;
+        self.latest_block_version = state.latest_block_version;
+        self.missed_block_ranges = state.missed_block_ranges;
+        self.check_rng_set_subaddresses();
+
+        // The memo state is derived from the last memo handled, so handle it again.
+        if let Some(tx_out) = last_memo_tx_out {
+            self.memo_handler.handle_memo(&tx_out, &self.account_key);
+        }
+        self.last_memo_global_index = state.last_memo_global_index;
+
+        log::info!(
+            self.logger,
+            "Restored wallet state: {} owned tx_outs, {} rngs, num blocks = {}",
+            self.owned_tx_outs.len(),
+            self.rng_set.get_rngs().len(),
+            self.get_num_blocks()
+        );
+        Ok(())
+    }
+
     /// Get the last processed memo
     pub fn get_last_memo(&self) -> &StdResult<Option<MemoType>, MemoHandlerError> {
         self.memo_handler.get_last_memo()
@@ -401,6 +528,7 @@ impl CachedTxData {
                     // Handle memo
                     self.memo_handler
                         .handle_memo(&otxo.tx_out, &self.account_key);
+                    self.last_memo_global_index = Some(otxo.global_index);
                 }
                 Err(err) => {
                     errors.push(err);
@@ -424,7 +552,7 @@ impl CachedTxData {
         // Do the fog view protocol, log any errors, and consume any new transactions
 
         let (mut txo_records, new_missed_block_ranges, errors) =
-            fog_view_client.poll(&mut self.rng_set, &UserPrivate::from(&self.account_key));
+            fog_view_client.poll(&mut self.rng_set, &self.user_private);
 
         log::trace!(
             self.logger,
@@ -1125,6 +1253,57 @@ impl From<InputSelectionError> for Error {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mc_common::logger::test_with_logger;
+    use mc_crypto_keys::RistrettoPrivate;
+    use mc_transaction_std::BurnRedemptionMemo;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test_with_logger]
+    fn restore_wallet_state_handles_last_memo(logger: Logger) {
+        let mut rng = StdRng::seed_from_u64(1);
+        let account_key = AccountKey::random(&mut rng);
+
+        let memo = BurnRedemptionMemo::new([3u8; BurnRedemptionMemo::MEMO_DATA_LEN]);
+        let tx_out = TxOut::new_with_memo(
+            Amount::new(10, TokenId::from(0)),
+            &account_key.default_subaddress(),
+            &RistrettoPrivate::try_from(&[7u8; 32]).unwrap(),
+            Default::default(),
+            |_| Ok(Some(memo.clone().into())),
+        )
+        .unwrap();
+
+        let tx_data = CachedTxData::new(account_key.clone(), vec![], logger.clone());
+        let mut state = tx_data.to_wallet_state();
+        assert_eq!(state.last_memo_global_index, None);
+        state.owned_tx_outs.push(StoredOwnedTxOut {
+            global_index: 5,
+            block_index: 1,
+            tx_out,
+            value: 10,
+            token_id: 0,
+            subaddress_index: DEFAULT_SUBADDRESS_INDEX,
+            key_image: KeyImage::from(1),
+            spent_at: None,
+            not_spent_as_of: Some(2),
+        });
+        state.last_memo_global_index = Some(5);
+
+        let mut restored = CachedTxData::new(account_key.clone(), vec![], logger.clone());
+        restored.restore_wallet_state(state.clone()).unwrap();
+        match restored.get_last_memo() {
+            Ok(Some(MemoType::BurnRedemption(restored_memo))) => {
+                assert_eq!(restored_memo, &memo)
+            }
+            other => panic!("Unexpected last memo: {:?}", other),
+        }
+        assert_eq!(restored.to_wallet_state(), state);
+
+        // The last memo must belong to an owned TxOut.
+        state.last_memo_global_index = Some(6);
+        let mut restored = CachedTxData::new(account_key, vec![], logger);
+        assert!(restored.restore_wallet_state(state).is_err());
+    }
 
     #[test]
     fn input_selection_heuristic_3_inputs() {
@@ -5,9 +5,10 @@
 use crate::{
     cached_tx_data::{CachedTxData, OwnedTxOut},
     error::{Error, Result},
+    wallet_state::WalletStateFile,
     BlockInfo, MemoHandlerError, TransactionStatus,
 };
-use core::{convert::TryFrom, result::Result as StdResult, str::FromStr};
+use core::{convert::TryFrom, ops::Range, result::Result as StdResult, str::FromStr};
 use mc_account_keys::{AccountKey, PublicAddress};
 use mc_attest_verifier::Verifier;
 use mc_common::logger::{log, Logger};
@@ -37,7 +38,10 @@ use mc_transaction_std::{
 use mc_util_telemetry::{block_span_builder, telemetry_static_key, tracer, Key, Span};
 use mc_util_uri::{ConnectionUri, FogUri};
 use rand::Rng;
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
 
 /// Default number of blocks used for calculating transaction tombstone block
 /// number. See `new_tx_block_attempts` below.
@@ -66,6 +70,15 @@ pub struct Client {
     /// tombstone block when generating a new transaction.
     new_tx_block_attempts: u16,
 
+    /// The file the wallet state is persisted to, if any.
+    wallet_state_file: Option<WalletStateFile>,
+
+    /// The minimum amount of time between two saves of the wallet state.
+    wallet_state_save_period: Duration,
+
+    /// The last time the wallet state was saved.
+    last_wallet_state_save: Option<Instant>,
+
     logger: Logger,
 }
 
@@ -100,10 +113,67 @@ impl Client {
             account_key,
             tx_data,
             new_tx_block_attempts: DEFAULT_NEW_TX_BLOCK_ATTEMPTS,
+            wallet_state_file: None,
+            wallet_state_save_period: Duration::default(),
+            last_wallet_state_save: None,
             logger,
         }
     }
 
+    /// Find TxOuts sent to any subaddress in `subaddress_range` with fog view,
+    /// in addition to the default subaddress.
+    pub fn set_fog_subaddress_range(&mut self, subaddress_range: Range<u64>) -> Result<()> {
+        self.tx_data.set_fog_subaddress_range(subaddress_range)
+    }
+
+    /// Persist the wallet state of this client to a file.
+    ///
+    /// If the file already holds a wallet state for this account, it is
+    /// restored, so that the next balance check only polls fog for new data.
+    /// From then on, the state is saved after a balance check if at least
+    /// `save_period` passed since it was last saved.
+    pub fn set_wallet_state_file(
+        &mut self,
+        wallet_state_file: WalletStateFile,
+        save_period: Duration,
+    ) -> Result<()> {
+        if let Some(state) = wallet_state_file.load(&self.account_key)? {
+            log::info!(
+                self.logger,
+                "Loading wallet state from {:?}",
+                wallet_state_file.path()
+            );
+            self.tx_data.restore_wallet_state(state)?;
+        }
+        self.wallet_state_file = Some(wallet_state_file);
+        self.wallet_state_save_period = save_period;
+        self.last_wallet_state_save = None;
+        Ok(())
+    }
+
+    /// Save the wallet state now, if a wallet state file was set.
+    pub fn save_wallet_state(&mut self) -> Result<()> {
+        if let Some(wallet_state_file) = self.wallet_state_file.as_ref() {
+            wallet_state_file.save(&self.account_key, &self.tx_data.to_wallet_state())?;
+            self.last_wallet_state_save = Some(Instant::now());
+        }
+        Ok(())
+    }
+
+    // Save the wallet state if the save period elapsed. A failure to save is
+    // logged rather than returned, since the in-memory state is still good.
+    fn maybe_save_wallet_state(&mut self) {
+        let due = self
+            .last_wallet_state_save
+            .map(|last| last.elapsed() >= self.wallet_state_save_period)
+            .unwrap_or(true);
+        if due {
+            if let Err(err) = self.save_wallet_state() {
+                log::error!(self.logger, "Failed to save wallet state: {}", err);
+            }
+        }
+    }
+
     /// Get the account key associated to this paykit
     pub fn get_account_key(&self) -> &AccountKey {
         &self.account_key
@@ -140,6 +210,7 @@ impl Client {
             &mut self.fog_key_image,
             &mut self.fog_block,
         )?;
+        self.maybe_save_wallet_state();
         Ok(self.compute_balance())
     }
 
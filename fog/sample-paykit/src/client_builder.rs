@@ -2,7 +2,7 @@
 
 //! Client Builder
 
-use crate::client::Client;
+use crate::{client::Client, error::Result, wallet_state::WalletStateFile};
 use grpcio::EnvBuilder;
 use mc_account_keys::{AccountKey, PublicAddress};
 use mc_attest_verifier::{MrSignerVerifier, Verifier, DEBUG_ENCLAVE};
@@ -19,7 +19,10 @@ use mc_sgx_css::Signature;
 use mc_transaction_core::constants::RING_SIZE;
 use mc_util_grpc::GrpcRetryConfig;
 use mc_util_uri::{ConnectionUri, ConsensusClientUri};
-use std::sync::Arc;
+use std::{ops::Range, path::PathBuf, sync::Arc, time::Duration};
+
+/// Default minimum amount of time between two saves of the wallet state.
+const DEFAULT_WALLET_STATE_SAVE_PERIOD: Duration = Duration::from_secs(60);
 
 /// Builder object which helps to initialize the sample paykit
 pub struct ClientBuilder {
@@ -46,6 +49,13 @@ pub struct ClientBuilder {
     fog_ingest_sigstruct: Option<Signature>,
     fog_ledger_sigstruct: Option<Signature>,
     fog_view_sigstruct: Option<Signature>,
+
+    // Optional range of subaddresses to find TxOuts for with fog view
+    fog_subaddress_range: Option<Range<u64>>,
+
+    // Optional file the wallet state is persisted to
+    wallet_state_path: Option<PathBuf>,
+    wallet_state_save_period: Duration,
 }
 
 impl ClientBuilder {
@@ -70,6 +80,9 @@ impl ClientBuilder {
             fog_ingest_sigstruct: None,
             fog_ledger_sigstruct: None,
             fog_view_sigstruct: None,
+            fog_subaddress_range: None,
+            wallet_state_path: None,
+            wallet_state_save_period: DEFAULT_WALLET_STATE_SAVE_PERIOD,
         }
     }
 
@@ -122,8 +135,33 @@ impl ClientBuilder {
         self
     }
 
-    /// Create the client
-    pub fn build(self) -> Client {
+    /// Sets the file the wallet state is persisted to. If it holds the state
+    /// of a previous run, the client resumes from it.
+    #[must_use]
+    pub fn wallet_state_path(mut self, path: Option<PathBuf>) -> Self {
+        self.wallet_state_path = path;
+        self
+    }
+
+    /// Sets the range of subaddresses to find TxOuts for with fog view, in
+    /// addition to the default subaddress
+    #[must_use]
+    pub fn fog_subaddress_range(mut self, subaddress_range: Option<Range<u64>>) -> Self {
+        self.fog_subaddress_range = subaddress_range;
+        self
+    }
+
+    /// Sets the minimum amount of time between two saves of the wallet state
+    #[must_use]
+    pub fn wallet_state_save_period(mut self, period: Duration) -> Self {
+        self.wallet_state_save_period = period;
+        self
+    }
+
+    /// Create the client. Fails if the connection to consensus cannot be set
+    /// up, if the fog subaddress range is invalid, or if the wallet state file
+    /// exists but cannot be loaded.
+    pub fn build(self) -> Result<Client> {
         let grpc_env = Arc::new(
             EnvBuilder::new()
                 .name_prefix(format!("sdk-{}", self.uri.addr()))
@@ -154,8 +192,7 @@ impl ClientBuilder {
             grpc_env.clone(),
             HardcodedCredentialsProvider::from(&self.uri),
             self.logger.new(o!("mc.cxn" => self.uri.addr())),
-        )
-        .expect("ThickClient::new returned an error");
+        )?;
 
         let fog_ingest_verifier = self.get_fog_ingest_verifier();
 
@@ -167,7 +204,7 @@ impl ClientBuilder {
 
         let fog_report_conn = GrpcFogReportConnection::new(grpc_env, self.logger.clone());
 
-        Client::new(
+        let mut client = Client::new(
             consensus_service_conn,
             fog_view_client,
             fog_merkle_proof,
@@ -180,7 +217,18 @@ impl ClientBuilder {
             self.key,
             self.address_book,
             self.logger,
-        )
+        );
+
+        if let Some(subaddress_range) = self.fog_subaddress_range {
+            client.set_fog_subaddress_range(subaddress_range)?;
+        }
+
+        if let Some(path) = self.wallet_state_path {
+            client
+                .set_wallet_state_file(WalletStateFile::new(path), self.wallet_state_save_period)?;
+        }
+
+        Ok(client)
     }
 
     // Build a Fog View connection, taking into account acct_host_override
@@ -10,7 +10,7 @@ use mc_fog_enclave_connection::Error as EnclaveConnectionError;
 use mc_fog_ledger_connection::{Error as LedgerConnectionError, KeyImageQueryError};
 use mc_fog_report_connection::Error as FogResolutionError;
 use mc_fog_types::view::FogTxOutError;
-use mc_fog_view_protocol::TxOutPollingError;
+use mc_fog_view_protocol::{RngSetError, SubaddressRangeError, TxOutPollingError};
 use mc_transaction_core::{
     validation::TransactionValidationError, AmountError, BlockVersionError,
     SignedContingentInputError,
@@ -135,6 +135,18 @@ pub enum Error {
 
     /// Fog merkle proof: {0}
     FogMerkleProof(String),
+
+    /// Wallet state file IO: {0}
+    WalletStateIo(std::io::Error),
+
+    /// Wallet state file encryption: {0}
+    WalletStateCrypto(String),
+
+    /// Invalid wallet state: {0}
+    InvalidWalletState(String),
+
+    /// Invalid fog subaddress range: {0}
+    SubaddressRange(SubaddressRangeError),
 }
 
 impl From<ConnectionError> for Error {
@@ -212,6 +224,18 @@ impl From<UriParseError> for Error {
     }
 }
 
+impl From<RngSetError> for Error {
+    fn from(src: RngSetError) -> Self {
+        Self::InvalidWalletState(src.to_string())
+    }
+}
+
+impl From<SubaddressRangeError> for Error {
+    fn from(src: SubaddressRangeError) -> Self {
+        Self::SubaddressRange(src)
+    }
+}
+
 impl From<BlockVersionError> for Error {
     fn from(src: BlockVersionError) -> Self {
         Self::BlockVersion(src)
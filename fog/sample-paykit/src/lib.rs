@@ -22,11 +22,13 @@ mod cached_tx_data;
 mod client;
 mod client_builder;
 mod error;
+mod wallet_state;
 
 pub use crate::{
     client::Client,
     client_builder::ClientBuilder,
     error::{Error, Result, TxOutMatchingError},
+    wallet_state::{StoredOwnedTxOut, WalletState, WalletStateFile},
 };
 pub use cached_tx_data::MemoHandlerError;
 pub use mc_account_keys::{AccountKey, PublicAddress};
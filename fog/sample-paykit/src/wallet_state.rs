@@ -0,0 +1,268 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! On-disk persistence of the wallet state of a sample paykit client.
+//!
+//! The state is everything `CachedTxData` learned from fog: the owned TxOuts
+//! and their key image status, the fog view rngs, how far the client has
+//! progressed, and which memo was processed last. Restoring it lets a client
+//! resume polling fog where it left off, instead of starting again from block
+//! zero.
+//!
+//! The state file is encrypted with a `VersionedCryptoBox` to the spend public
+//! key of the account, so only the holder of the account key can read it.
+
+use crate::{
+    cached_tx_data::{KeyImageStatus, OwnedTxOut},
+    error::{Error, Result},
+};
+use core::convert::TryFrom;
+use mc_account_keys::AccountKey;
+use mc_crypto_box::{CryptoBox, VersionedCryptoBox};
+use mc_crypto_keys::RistrettoPublic;
+use mc_crypto_rand::McRng;
+use mc_fog_types::{common::BlockRange, BlockCount};
+use mc_fog_view_protocol::StoredUserRngSet;
+use mc_transaction_core::{ring_signature::KeyImage, tx::TxOut, Amount, TokenId};
+use prost::Message;
+use std::{
+    fs::{self, File},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+/// A snapshot of the state of a `CachedTxData`.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct WalletState {
+    /// The fog view rngs and the position in the fog view protocol
+    #[prost(message, required, tag = 1)]
+    pub rng_set: StoredUserRngSet,
+    /// The TxOuts owned by the account
+    #[prost(message, repeated, tag = 2)]
+    pub owned_tx_outs: Vec<StoredOwnedTxOut>,
+    /// The block count up to which key image data is complete
+    #[prost(uint64, tag = 3)]
+    pub key_image_data_completeness: u64,
+    /// The latest global txo count heard about
+    #[prost(uint64, tag = 4)]
+    pub latest_global_txo_count: u64,
+    /// The latest block version heard about
+    #[prost(uint32, tag = 5)]
+    pub latest_block_version: u32,
+    /// Missed block ranges that were not completely downloaded yet
+    #[prost(message, repeated, tag = 6)]
+    pub missed_block_ranges: Vec<BlockRange>,
+    /// The global index of the owned TxOut whose memo was processed last, if
+    /// any. Its memo is processed again when the state is restored.
+    #[prost(uint64, optional, tag = 7)]
+    pub last_memo_global_index: Option<u64>,
+}
+
+/// A stored representation of an `OwnedTxOut`.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct StoredOwnedTxOut {
+    /// The global index of the tx_out
+    #[prost(uint64, tag = 1)]
+    pub global_index: u64,
+    /// The block in which the tx_out appeared
+    #[prost(uint64, tag = 2)]
+    pub block_index: u64,
+    /// The tx_out
+    #[prost(message, required, tag = 3)]
+    pub tx_out: TxOut,
+    /// The value of the tx_out
+    #[prost(uint64, tag = 4)]
+    pub value: u64,
+    /// The token id of the tx_out
+    #[prost(uint64, tag = 5)]
+    pub token_id: u64,
+    /// The subaddress index the tx_out was sent to
+    #[prost(uint64, tag = 6)]
+    pub subaddress_index: u64,
+    /// The key image of the tx_out
+    #[prost(message, required, tag = 7)]
+    pub key_image: KeyImage,
+    /// The block index the key image was spent at, if it is known to be spent
+    #[prost(uint64, optional, tag = 8)]
+    pub spent_at: Option<u64>,
+    /// The block count as of which the key image is known not to be spent
+    #[prost(uint64, optional, tag = 9)]
+    pub not_spent_as_of: Option<u64>,
+}
+
+impl From<&OwnedTxOut> for StoredOwnedTxOut {
+    fn from(src: &OwnedTxOut) -> Self {
+        let (spent_at, not_spent_as_of) = match src.status {
+            KeyImageStatus::SpentAt(block_index) => (Some(block_index), None),
+            KeyImageStatus::NotSpent(block_count) => (None, Some(u64::from(block_count))),
+        };
+        Self {
+            global_index: src.global_index,
+            block_index: src.block_index,
+            tx_out: src.tx_out.clone(),
+            value: src.amount.value,
+            token_id: *src.amount.token_id,
+            subaddress_index: src.subaddress_index,
+            key_image: src.key_image,
+            spent_at,
+            not_spent_as_of,
+        }
+    }
+}
+
+impl TryFrom<StoredOwnedTxOut> for OwnedTxOut {
+    type Error = Error;
+
+    fn try_from(src: StoredOwnedTxOut) -> Result<Self> {
+        let status = match (src.spent_at, src.not_spent_as_of) {
+            (Some(block_index), None) => KeyImageStatus::SpentAt(block_index),
+            (None, Some(block_count)) => KeyImageStatus::NotSpent(BlockCount::from(block_count)),
+            _ => {
+                return Err(Error::InvalidWalletState(format!(
+                    "TxOut {} has no valid key image status",
+                    src.global_index
+                )))
+            }
+        };
+        Ok(Self {
+            global_index: src.global_index,
+            block_index: src.block_index,
+            tx_out: src.tx_out,
+            amount: Amount::new(src.value, TokenId::from(src.token_id)),
+            subaddress_index: src.subaddress_index,
+            key_image: src.key_image,
+            status,
+        })
+    }
+}
+
+/// A file holding the encrypted wallet state of an account.
+#[derive(Clone, Debug)]
+pub struct WalletStateFile {
+    path: PathBuf,
+}
+
+impl WalletStateFile {
+    /// Create a new wallet state file object. The file is not created until
+    /// the state is first saved.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load the wallet state from the file, if it exists.
+    pub fn load(&self, account_key: &AccountKey) -> Result<Option<WalletState>> {
+        let ciphertext = match fs::read(&self.path) {
+            Ok(ciphertext) => ciphertext,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::WalletStateIo(err)),
+        };
+
+        let (success, plaintext) = VersionedCryptoBox::default()
+            .decrypt(account_key.spend_private_key(), &ciphertext)
+            .map_err(|err| Error::WalletStateCrypto(err.to_string()))?;
+        if !bool::from(success) {
+            return Err(Error::WalletStateCrypto(
+                "Mac check failed, the file may belong to a different account".to_string(),
+            ));
+        }
+
+        let state = mc_util_serial::decode(&plaintext)
+            .map_err(|err| Error::InvalidWalletState(err.to_string()))?;
+        Ok(Some(state))
+    }
+
+    /// Save the wallet state to the file.
+    ///
+    /// The state is written to a temporary file first, which then replaces the
+    /// previous state, so that an interrupted save never corrupts the file.
+    pub fn save(&self, account_key: &AccountKey, state: &WalletState) -> Result<()> {
+        let plaintext = mc_util_serial::encode(state);
+        let spend_public_key = RistrettoPublic::from(account_key.spend_private_key());
+        let ciphertext = VersionedCryptoBox::default()
+            .encrypt(&mut McRng::default(), &spend_public_key, &plaintext)
+            .map_err(|err| Error::WalletStateCrypto(err.to_string()))?;
+
+        let tmp_path = self.path.with_extension("tmp");
+        let mut file = File::create(&tmp_path).map_err(Error::WalletStateIo)?;
+        file.write_all(&ciphertext).map_err(Error::WalletStateIo)?;
+        file.sync_all().map_err(Error::WalletStateIo)?;
+        fs::rename(&tmp_path, &self.path).map_err(Error::WalletStateIo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_fog_view_protocol::StoredUserRng;
+    use mc_transaction_core_test_utils::create_test_tx_out;
+    use rand::{rngs::StdRng, SeedableRng};
+    use tempdir::TempDir;
+
+    fn test_state(rng: &mut StdRng) -> WalletState {
+        let owned_tx_outs = (0..3)
+            .map(|i| StoredOwnedTxOut {
+                global_index: 10 + i,
+                block_index: i,
+                tx_out: create_test_tx_out(rng),
+                value: 1000 * i,
+                token_id: i,
+                subaddress_index: 0,
+                key_image: KeyImage::from(i),
+                spent_at: if i == 0 { Some(2) } else { None },
+                not_spent_as_of: if i == 0 { None } else { Some(5) },
+            })
+            .collect();
+
+        WalletState {
+            rng_set: StoredUserRngSet {
+                rngs: vec![StoredUserRng {
+                    nonce: vec![1u8; 32],
+                    rng: Default::default(),
+                    subaddress_index: 0,
+                }],
+                highest_processed_block_count: 5,
+                next_start_from_user_event_id: 7,
+            },
+            owned_tx_outs,
+            key_image_data_completeness: 5,
+            latest_global_txo_count: 100,
+            latest_block_version: 3,
+            missed_block_ranges: vec![BlockRange::new(1, 2)],
+            last_memo_global_index: Some(11),
+        }
+    }
+
+    #[test]
+    fn wallet_state_file_round_trip() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let account_key = AccountKey::random(&mut rng);
+        let dir = TempDir::new("wallet_state").unwrap();
+        let file = WalletStateFile::new(dir.path().join("wallet.state"));
+
+        assert_eq!(file.load(&account_key).unwrap(), None);
+
+        let state = test_state(&mut rng);
+        file.save(&account_key, &state).unwrap();
+        assert_eq!(file.load(&account_key).unwrap(), Some(state.clone()));
+
+        // Saving again replaces the previous state.
+        let mut state2 = state.clone();
+        state2.latest_global_txo_count = 200;
+        file.save(&account_key, &state2).unwrap();
+        assert_eq!(file.load(&account_key).unwrap(), Some(state2));
+
+        // Another account can't read the state.
+        let other_account_key = AccountKey::random(&mut rng);
+        assert!(file.load(&other_account_key).is_err());
+
+        // The stored TxOuts survive the conversion to and from OwnedTxOut.
+        for stored in state.owned_tx_outs {
+            let owned = OwnedTxOut::try_from(stored.clone()).unwrap();
+            assert_eq!(StoredOwnedTxOut::from(&owned), stored);
+        }
+    }
+}
@@ -33,8 +33,8 @@ use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_fog_kex_rng::KexRngPubkey;
 use mc_fog_recovery_db_iface::{
     AddBlockDataStatus, FogUserEvent, IngestInvocationId, IngressPublicKeyRecord,
-    IngressPublicKeyRecordFilters, IngressPublicKeyStatus, RecoveryDb, RecoveryDbError, ReportData,
-    ReportDb,
+    IngressPublicKeyRecordFilters, IngressPublicKeyStatus, PrunableBlock, RecoveryDb,
+    RecoveryDbError, ReportData, ReportDb, RetentionPolicy,
 };
 use mc_fog_types::{
     common::BlockRange,
@@ -1026,6 +1026,133 @@ impl SqlRecoveryDb {
         SqlRecoveryDb::get_highest_known_block_index_impl(&conn)
     }
 
+    /// Get ingested blocks whose ETxOutRecords may be pruned.
+    fn get_prunable_blocks_retriable(
+        &self,
+        retention_policy: &RetentionPolicy,
+        start_after: Option<(u64, CompressedRistrettoPublic)>,
+        max_blocks: usize,
+    ) -> Result<Vec<PrunableBlock>, Error> {
+        let highest_known_block_index = match self.get_highest_known_block_index_retriable()? {
+            Some(index) if retention_policy.is_enabled() => index,
+            _ => return Ok(Vec::new()),
+        };
+
+        // Blocks below this index are too old to be kept.
+        let max_block_age_cutoff = retention_policy
+            .max_block_age
+            .map(|max_block_age| highest_known_block_index.saturating_sub(max_block_age))
+            .unwrap_or(0);
+
+        // Keys whose blocks may all be pruned, regardless of their age.
+        let key_policy = RetentionPolicy {
+            max_block_age: None,
+            ..retention_policy.clone()
+        };
+        let prunable_keys: Vec<SqlCompressedRistrettoPublic> = self
+            .get_ingress_key_records_retriable(
+                0,
+                &IngressPublicKeyRecordFilters {
+                    should_include_lost_keys: true,
+                    should_include_retired_keys: true,
+                    should_only_include_unexpired_keys: false,
+                },
+            )?
+            .into_iter()
+            .filter(|record| key_policy.allows_pruning(0, record, highest_known_block_index))
+            .map(|record| SqlCompressedRistrettoPublic::from(record.key))
+            .collect();
+
+        let conn = self.pool.get()?;
+
+        // Blocks that were already pruned, or that never had any
+        // ETxOutRecords, have empty block data.
+        use schema::ingested_blocks::dsl;
+        let mut query = dsl::ingested_blocks
+            .filter(dsl::proto_ingested_block_data.ne(Vec::<u8>::new()))
+            .filter(
+                dsl::block_number
+                    .lt(max_block_age_cutoff as i64)
+                    .or(dsl::ingress_public_key.eq_any(prunable_keys)),
+            )
+            .select((
+                dsl::ingress_public_key,
+                dsl::block_number,
+                dsl::ingest_invocation_id,
+                dsl::proto_ingested_block_data,
+            ))
+            .order_by((dsl::block_number, dsl::ingress_public_key))
+            .limit(max_blocks as i64)
+            .into_boxed();
+
+        if let Some((block_index, ingress_key)) = start_after {
+            let block_number = block_index as i64;
+            let ingress_key = SqlCompressedRistrettoPublic::from(ingress_key);
+            query = query.filter(
+                dsl::block_number.gt(block_number).or(dsl::block_number
+                    .eq(block_number)
+                    .and(dsl::ingress_public_key.gt(ingress_key))),
+            );
+        }
+
+        // The block data is only decoded to count its ETxOutRecords, which are
+        // not kept.
+        query
+            .load::<(SqlCompressedRistrettoPublic, i64, i64, Vec<u8>)>(&conn)?
+            .into_iter()
+            .map(
+                |(ingress_key, block_number, ingest_invocation_id, proto_bytes)| {
+                    let proto = ProtoIngestedBlockData::decode(&*proto_bytes)?;
+                    Ok(PrunableBlock {
+                        ingress_key: *ingress_key,
+                        block_index: block_number as u64,
+                        ingest_invocation_id: IngestInvocationId::from(ingest_invocation_id),
+                        num_e_tx_out_records: proto.e_tx_out_records.len() as u64,
+                    })
+                },
+            )
+            .collect()
+    }
+
+    /// Delete the ETxOutRecords of some ingested blocks.
+    fn prune_blocks_retriable(
+        &self,
+        blocks: &[(CompressedRistrettoPublic, u64)],
+    ) -> Result<u64, Error> {
+        let conn = self.pool.get()?;
+
+        conn.build_transaction()
+            .read_write()
+            .run(|| -> Result<u64, Error> {
+                use schema::ingested_blocks::dsl;
+
+                let mut num_pruned_records = 0;
+                for (ingress_key, block_index) in blocks {
+                    let key_bytes: &[u8] = ingress_key.as_ref();
+                    let protos: Vec<Vec<u8>> = dsl::ingested_blocks
+                        .filter(dsl::ingress_public_key.eq(key_bytes))
+                        .filter(dsl::block_number.eq(*block_index as i64))
+                        .select(dsl::proto_ingested_block_data)
+                        .load(&conn)?;
+
+                    for proto_bytes in protos {
+                        let proto = ProtoIngestedBlockData::decode(&*proto_bytes)?;
+                        num_pruned_records += proto.e_tx_out_records.len() as u64;
+                    }
+
+                    diesel::update(
+                        dsl::ingested_blocks
+                            .filter(dsl::ingress_public_key.eq(key_bytes))
+                            .filter(dsl::block_number.eq(*block_index as i64)),
+                    )
+                    .set(dsl::proto_ingested_block_data.eq(Vec::<u8>::new()))
+                    .execute(&conn)?;
+                }
+
+                Ok(num_pruned_records)
+            })
+    }
+
     ////
     // ReportDb functions that are meant to be retriable (don't take a conn as
     // argument)
@@ -1402,6 +1529,24 @@ impl RecoveryDb for SqlRecoveryDb {
             self.get_highest_known_block_index_retriable()
         })
     }
+
+    fn get_prunable_blocks(
+        &self,
+        retention_policy: &RetentionPolicy,
+        start_after: Option<(u64, CompressedRistrettoPublic)>,
+        max_blocks: usize,
+    ) -> Result<Vec<PrunableBlock>, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.get_prunable_blocks_retriable(retention_policy, start_after, max_blocks)
+        })
+    }
+
+    fn prune_blocks(
+        &self,
+        blocks: &[(CompressedRistrettoPublic, u64)],
+    ) -> Result<u64, Self::Error> {
+        our_retry(self.get_retries(), || self.prune_blocks_retriable(blocks))
+    }
 }
 
 /// See trait `fog_recovery_db_iface::ReportDb` for documentation.
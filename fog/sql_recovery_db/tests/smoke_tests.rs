@@ -43,3 +43,13 @@ fn sql_recovery_db_ingress_keys(logger: Logger) {
         test_recovery_db_ingress_keys(&mut rng, &db);
     })
 }
+
+#[test_with_logger]
+fn sql_recovery_db_pruning(logger: Logger) {
+    mc_util_test_helper::run_with_several_seeds(|mut rng| {
+        let db_test_context = SqlRecoveryDbTestContext::new(logger.clone());
+        let db = db_test_context.get_db_instance();
+
+        test_recovery_db_pruning(&mut rng, &db);
+    })
+}
@@ -226,7 +226,8 @@ impl TestClient {
             .fog_ingest_sig(self.fog_ingest_sig.clone())
             .fog_ledger_sig(self.fog_ledger_sig.clone())
             .fog_view_sig(self.fog_view_sig.clone())
-            .build();
+            .build()
+            .expect("Could not build client");
             clients.push(Arc::new(Mutex::new(client)));
         }
         clients
@@ -6,7 +6,7 @@ use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
 use mc_fog_kex_rng::KexRngPubkey;
 use mc_fog_recovery_db_iface::{
     ETxOutRecord, FogUserEvent, IngestInvocationId, IngressPublicKeyStatus, RecoveryDb, ReportData,
-    ReportDb,
+    ReportDb, RetentionPolicy,
 };
 use mc_fog_types::view::{RngRecord, TxOutSearchResultCode};
 use mc_transaction_core::{Block, BlockID, BlockVersion};
@@ -602,6 +602,156 @@ pub fn test_recovery_db_ingress_keys<DB: RecoveryDb>(
     );
 }
 
+/// Tests that ETxOutRecords are pruned according to a retention policy
+pub fn test_recovery_db_pruning<DB: RecoveryDb>(rng: &mut (impl RngCore + CryptoRng), db: &DB) {
+    let ingress_key1 = CompressedRistrettoPublic::from(RistrettoPublic::from_random(rng));
+    db.new_ingress_key(&ingress_key1, 0).unwrap();
+    let invoc_id1 = db
+        .new_ingest_invocation(None, &ingress_key1, &random_kex_rng_pubkey(rng), 0)
+        .unwrap();
+
+    let mut key1_records = Vec::new();
+    for block_index in 0..10 {
+        let (block, records) = random_block(rng, block_index, 5);
+        db.add_block_data(&invoc_id1, &block, 0, &records).unwrap();
+        key1_records.push(records);
+    }
+
+    let ingress_key2 = CompressedRistrettoPublic::from(RistrettoPublic::from_random(rng));
+    db.new_ingress_key(&ingress_key2, 10).unwrap();
+    let invoc_id2 = db
+        .new_ingest_invocation(
+            Some(invoc_id1),
+            &ingress_key2,
+            &random_kex_rng_pubkey(rng),
+            10,
+        )
+        .unwrap();
+
+    for block_index in 10..20 {
+        let (block, records) = random_block(rng, block_index, 5);
+        db.add_block_data(&invoc_id2, &block, 0, &records).unwrap();
+    }
+
+    // Nothing is prunable without a retention policy.
+    assert!(db
+        .get_prunable_blocks(&RetentionPolicy::default(), None, 100)
+        .unwrap()
+        .is_empty());
+
+    // Blocks more than 15 blocks below the highest known block are prunable.
+    let age_policy = RetentionPolicy {
+        max_block_age: Some(15),
+        retired_key_grace_blocks: None,
+    };
+    let prunable = db.get_prunable_blocks(&age_policy, None, 100).unwrap();
+    assert_eq!(
+        prunable
+            .iter()
+            .map(|block| block.block_index)
+            .collect::<Vec<_>>(),
+        vec![0, 1, 2, 3]
+    );
+    for block in prunable.iter() {
+        assert_eq!(block.ingress_key, ingress_key1);
+        assert_eq!(block.ingest_invocation_id, invoc_id1);
+        assert_eq!(block.num_e_tx_out_records, 5);
+        assert_eq!(
+            db.get_tx_outs_by_block_and_key(ingress_key1, block.block_index)
+                .unwrap(),
+            Some(key1_records[block.block_index as usize].clone())
+        );
+    }
+    assert_eq!(
+        db.get_prunable_blocks(&age_policy, None, 2).unwrap().len(),
+        2
+    );
+
+    // Blocks can be selected starting after a given block.
+    assert_eq!(
+        db.get_prunable_blocks(&age_policy, Some((1, ingress_key1)), 100)
+            .unwrap()
+            .iter()
+            .map(|block| block.block_index)
+            .collect::<Vec<_>>(),
+        vec![2, 3]
+    );
+
+    let to_prune: Vec<_> = prunable
+        .iter()
+        .map(|block| (block.ingress_key, block.block_index))
+        .collect();
+    assert_eq!(db.prune_blocks(&to_prune).unwrap(), 20);
+
+    // Pruned blocks lose their ETxOutRecords but keep their metadata.
+    assert_eq!(
+        db.get_tx_outs_by_block_and_key(ingress_key1, 0).unwrap(),
+        Some(vec![])
+    );
+    assert_eq!(db.get_cumulative_txo_count_for_block(0).unwrap(), Some(0));
+    assert_eq!(
+        db.get_invocation_id_by_block_and_key(ingress_key1, 0)
+            .unwrap(),
+        Some(invoc_id1)
+    );
+    assert!(db
+        .get_prunable_blocks(&age_policy, None, 100)
+        .unwrap()
+        .is_empty());
+
+    // Pruning a block again deletes nothing.
+    assert_eq!(db.prune_blocks(&to_prune).unwrap(), 0);
+
+    // The blocks of a key are prunable once it is retired and the grace period
+    // has passed.
+    let grace_policy = RetentionPolicy {
+        max_block_age: None,
+        retired_key_grace_blocks: Some(5),
+    };
+    assert!(db
+        .get_prunable_blocks(&grace_policy, None, 100)
+        .unwrap()
+        .is_empty());
+
+    db.retire_ingress_key(&ingress_key1, true).unwrap();
+    let prunable = db.get_prunable_blocks(&grace_policy, None, 100).unwrap();
+    assert_eq!(
+        prunable
+            .iter()
+            .map(|block| block.block_index)
+            .collect::<Vec<_>>(),
+        vec![4, 5, 6, 7, 8, 9]
+    );
+    assert!(prunable
+        .iter()
+        .all(|block| block.ingress_key == ingress_key1));
+
+    let long_grace_policy = RetentionPolicy {
+        max_block_age: None,
+        retired_key_grace_blocks: Some(20),
+    };
+    assert!(db
+        .get_prunable_blocks(&long_grace_policy, None, 100)
+        .unwrap()
+        .is_empty());
+
+    let to_prune: Vec<_> = prunable
+        .iter()
+        .map(|block| (block.ingress_key, block.block_index))
+        .collect();
+    assert_eq!(db.prune_blocks(&to_prune).unwrap(), 30);
+    assert_eq!(
+        db.get_tx_outs_by_block_and_key(ingress_key1, 9).unwrap(),
+        Some(vec![])
+    );
+    assert_eq!(
+        db.get_tx_outs_by_block_and_key(ingress_key2, 10)
+            .unwrap()
+            .map(|records| records.len()),
+        Some(5)
+    );
+}
+
 // Lower level test routines
 
 // Exercise recovery db apis for writing and reading tx_rows
@@ -14,7 +14,7 @@ use mc_transaction_core::{
 use prost::Message;
 use serde::{Deserialize, Serialize};
 
-pub use mc_fog_kex_rng::KexRngPubkey;
+pub use mc_fog_kex_rng::{KexRngPubkey, StoredRng};
 
 // User <-> enclave proto schema types
 // These are synced with types in fog_api view.proto, and tests enforce that
@@ -46,6 +46,22 @@ pub struct QueryRequest {
     pub get_txos: Vec<Vec<u8>>,
 }
 
+/// The number of outputs of each registered rng that the enclave looks up for
+/// every response to a subscription.
+pub const SUBSCRIPTION_SEARCH_KEYS_PER_RNG: usize = 16;
+
+/// The maximum number of rngs in a subscription.
+pub const MAX_SUBSCRIPTION_RNGS: usize = 256;
+
+/// The SubscribeRequest structure, which should be passed as the encrypted data
+/// when subscribing to pushed fog view responses
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct SubscribeRequest {
+    /// The current state of the user's kex rngs
+    #[prost(message, repeated, tag = "1")]
+    pub rngs: Vec<StoredRng>,
+}
+
 /// The QueryResponse structure, returned by the enclave in response to an
 /// attested request
 #[derive(Clone, Eq, PartialEq, Message)]
@@ -183,11 +199,22 @@ pub struct TxOutSearchResult {
     pub ciphertext: Vec<u8>,
 }
 
+/// The maximum number of subaddresses a fog view client may scan for.
+///
+/// A fog hint holds the view public key of a single subaddress, so a client
+/// derives one rng per ingest invocation for every subaddress it scans, and the
+/// cost of polling grows linearly with this number.
+pub const MAX_SCANNED_SUBADDRESSES: u64 = 1000;
+
 /// TxOutRecord is what information the fog service preserves for a user about
 /// their TxOut. These are created by the ingest server and then encrypted. The
 /// encrypted blobs are eventually returned to the user, who must deserialize
 /// them.
 ///
+/// The blobs are encrypted to the subaddress view public key found in the fog
+/// hint of the TxOut, and must be decrypted with the view private key of that
+/// subaddress.
+///
 /// Note: There are conformance tests in fog-api that check that this matches
 /// the proto
 #[derive(Clone, Eq, Hash, PartialEq, Message)]
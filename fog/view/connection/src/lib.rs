@@ -5,15 +5,20 @@
 
 #![deny(missing_docs)]
 
-use grpcio::{ChannelBuilder, Environment};
+use futures::{executor::block_on, StreamExt};
+use grpcio::{ChannelBuilder, ClientSStreamReceiver, Environment};
+use mc_attest_api::attest;
 use mc_attest_verifier::Verifier;
 use mc_common::{
     logger::{log, o, Logger},
     trace_time,
 };
+use mc_connection::AttestedConnection;
 use mc_fog_api::view_grpc;
 use mc_fog_enclave_connection::{EnclaveConnection, Error as EnclaveConnectionError};
-use mc_fog_types::view::{QueryRequest, QueryRequestAAD, QueryResponse};
+use mc_fog_types::view::{
+    QueryRequest, QueryRequestAAD, QueryResponse, StoredRng, SubscribeRequest,
+};
 use mc_fog_uri::FogViewUri;
 use mc_fog_view_protocol::FogViewConnection;
 use mc_util_grpc::{ConnectionUriGrpcioChannel, GrpcRetryConfig};
@@ -25,6 +30,9 @@ use std::{fmt::Display, sync::Arc};
 pub struct FogViewGrpcClient {
     /// The attested connection
     conn: EnclaveConnection<FogViewUri, view_grpc::FogViewApiClient>,
+    /// The grpc client, for calls which don't go through the attested
+    /// connection's request / response interface
+    grpc_client: view_grpc::FogViewApiClient,
     /// The grpc retry config
     grpc_retry_config: GrpcRetryConfig,
     /// The uri we connected to
@@ -56,7 +64,13 @@ impl FogViewGrpcClient {
         let grpc_client = view_grpc::FogViewApiClient::new(ch);
 
         Self {
-            conn: EnclaveConnection::new(uri.clone(), grpc_client, verifier, logger.clone()),
+            conn: EnclaveConnection::new(
+                uri.clone(),
+                grpc_client.clone(),
+                verifier,
+                logger.clone(),
+            ),
+            grpc_client,
             grpc_retry_config,
             uri,
             logger,
@@ -64,6 +78,90 @@ impl FogViewGrpcClient {
     }
 }
 
+impl FogViewGrpcClient {
+    /// Subscribe to pushed query responses for a set of rngs.
+    ///
+    /// The server pushes a response whenever the highest processed block count
+    /// advances, containing the results of a fixed number of lookups per rng
+    /// (see `mc_fog_types::view::SUBSCRIPTION_SEARCH_KEYS_PER_RNG`).
+    ///
+    /// The responses are encrypted for the attested session of this client, so
+    /// the client must not be used for other requests while the subscription
+    /// is alive. Dropping the subscription cancels it. Subscribing again
+    /// replaces the previous subscription of the session.
+    ///
+    /// Arguments:
+    /// * start_from_user_event_id: The first user event to report
+    /// * start_from_block_index: The first block to report search results for
+    /// * rngs: The current state of the rngs to search with
+    pub fn subscribe(
+        &mut self,
+        start_from_user_event_id: i64,
+        start_from_block_index: u64,
+        rngs: Vec<StoredRng>,
+    ) -> Result<FogViewSubscription<'_>, EnclaveConnectionError> {
+        trace_time!(self.logger, "FogViewGrpcClient::subscribe");
+
+        log::trace!(
+            self.logger,
+            "subscribe: start_from_user_event_id={} start_from_block_index={} num_rngs={}",
+            start_from_user_event_id,
+            start_from_block_index,
+            rngs.len()
+        );
+
+        let req = SubscribeRequest { rngs };
+
+        let req_aad = QueryRequestAAD {
+            start_from_user_event_id,
+            start_from_block_index,
+        };
+
+        let aad_bytes = mc_util_serial::encode(&req_aad);
+
+        let msg = self.conn.encrypt_request(&req, &aad_bytes)?;
+        let call_option = self.conn.call_option();
+        let receiver = self
+            .grpc_client
+            .subscribe_opt(&msg, call_option)
+            .map_err(|err| {
+                // The session may not be in a usable state anymore.
+                self.conn.deattest();
+                EnclaveConnectionError::from(err)
+            })?;
+
+        Ok(FogViewSubscription {
+            conn: &mut self.conn,
+            receiver,
+        })
+    }
+}
+
+/// A stream of query responses pushed by the fog view service
+pub struct FogViewSubscription<'a> {
+    /// The attested connection the responses are encrypted for
+    conn: &'a mut EnclaveConnection<FogViewUri, view_grpc::FogViewApiClient>,
+    /// The grpc stream of encrypted responses
+    receiver: ClientSStreamReceiver<attest::Message>,
+}
+
+impl<'a> Iterator for FogViewSubscription<'a> {
+    type Item = Result<QueryResponse, EnclaveConnectionError>;
+
+    /// Block until the next response is pushed. Returns None once the server
+    /// ends the stream.
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = block_on(self.receiver.next())?
+            .map_err(EnclaveConnectionError::from)
+            .and_then(|msg| self.conn.decrypt_response(&msg));
+        if result.is_err() {
+            // Responses can't be decrypted anymore once one is lost.
+            self.conn.deattest();
+        }
+        Some(result)
+    }
+}
+
 impl FogViewConnection for FogViewGrpcClient {
     type Error = Error;
 
@@ -81,6 +81,15 @@ pub enum ViewEnclaveRequest {
     /// An encrypted fog_types::view::QueryRequest
     /// Respond with fog_types::view::QueryResponse
     Query(EnclaveMessage<ClientSession>, UntrustedQueryResponse),
+    /// An encrypted fog_types::view::SubscribeRequest, registering the rngs
+    /// of a client session
+    Subscribe(EnclaveMessage<ClientSession>),
+    /// Search for the next outputs of the rngs registered for each of the
+    /// client sessions
+    /// Respond with a fog_types::view::QueryResponse per session
+    SubscriptionQuery(Vec<ClientSession>, UntrustedQueryResponse),
+    /// Forget the rngs registered for a client session
+    Unsubscribe(ClientSession),
     /// Request from untrusted to add encrypted tx out records to ORAM
     AddRecords(Vec<ETxOutRecord>),
 }
@@ -129,6 +138,23 @@ pub trait ViewEnclaveApi: ReportableEnclave {
         untrusted_query_response: UntrustedQueryResponse,
     ) -> Result<Vec<u8>>;
 
+    /// Register the rngs in a user's encrypted SubscribeRequest with the
+    /// client session, replacing any previously registered rngs.
+    fn subscribe(&self, payload: EnclaveMessage<ClientSession>) -> Result<()>;
+
+    /// Look up the next outputs of the rngs registered with each of the
+    /// client sessions, and produce an encrypted QueryResponse for each of
+    /// them, in the same order. All the sessions share the untrusted part of
+    /// the response.
+    fn subscription_query(
+        &self,
+        channel_ids: Vec<ClientSession>,
+        untrusted_query_response: UntrustedQueryResponse,
+    ) -> Result<Vec<Result<Vec<u8>>>>;
+
+    /// Forget the rngs registered with the client session.
+    fn unsubscribe(&self, channel_id: ClientSession) -> Result<()>;
+
     /// SERVER-FACING
 
     /// Add encrypted tx out records from the fog recovery db to the view
@@ -187,6 +213,12 @@ pub enum Error {
     Poison,
     /// Enclave not initialized
     EnclaveNotInitialized,
+    /// No subscription for the client session
+    NoSubscription,
+    /// Too many rngs in subscription: {0}
+    TooManySubscriptionRngs(usize),
+    /// Invalid rng in subscription
+    InvalidSubscriptionRng,
 }
 
 impl From<SgxError> for Error {
@@ -9,15 +9,24 @@ extern crate alloc;
 mod e_tx_out_store;
 use e_tx_out_store::{ETxOutStore, StorageDataSize, StorageMetaSize};
 
+use aligned_cmov::subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
 use alloc::vec::Vec;
+use core::convert::TryFrom;
 use mc_attest_core::{IasNonce, Quote, QuoteNonce, Report, TargetInfo, VerificationReport};
 use mc_attest_enclave_api::{ClientAuthRequest, ClientAuthResponse, ClientSession, EnclaveMessage};
-use mc_common::logger::{log, Logger};
-use mc_crypto_ake_enclave::{AkeEnclaveState, NullIdentity};
+use mc_common::{
+    logger::{log, Logger},
+    LruCache,
+};
+use mc_crypto_ake_enclave::{AkeEnclaveState, NullIdentity, MAX_CLIENT_SESSIONS};
 use mc_crypto_keys::X25519Public;
+use mc_fog_kex_rng::{BufferedRng, StoredRng, VersionedKexRng};
 use mc_fog_recovery_db_iface::FogUserEvent;
 use mc_fog_types::{
-    view::{QueryRequest, QueryResponse},
+    view::{
+        QueryRequest, QueryResponse, SubscribeRequest, TxOutSearchResult, TxOutSearchResultCode,
+        MAX_SUBSCRIPTION_RNGS, SUBSCRIPTION_SEARCH_KEYS_PER_RNG,
+    },
     ETxOutRecord,
 };
 use mc_fog_view_enclave_api::{
@@ -37,6 +46,11 @@ where
     /// The state associated to attestation and key exchange
     ake: AkeEnclaveState<NullIdentity>,
 
+    /// The rngs registered by subscribed client sessions. Bounded like the
+    /// client sessions of the AKE, since clients may go away without closing
+    /// their session.
+    subscriptions: Mutex<LruCache<ClientSession, Vec<VersionedKexRng>>>,
+
     /// Logger object
     logger: Logger,
 }
@@ -49,9 +63,78 @@ where
         Self {
             e_tx_out_store: Mutex::new(None),
             ake: Default::default(),
+            subscriptions: Mutex::new(LruCache::new(MAX_CLIENT_SESSIONS)),
             logger,
         }
     }
+
+    /// Build the QueryResponse fields which come from untrusted data
+    fn untrusted_response(untrusted_query_response: UntrustedQueryResponse) -> QueryResponse {
+        let mut missed_block_ranges = Vec::new();
+        let mut rng_records = Vec::new();
+        let mut decommissioned_ingest_invocations = Vec::new();
+
+        for event in untrusted_query_response.user_events.into_iter() {
+            match event {
+                FogUserEvent::NewRngRecord(rng_record) => rng_records.push(rng_record),
+
+                FogUserEvent::DecommissionIngestInvocation(decommissioned_ingest_invocation) => {
+                    decommissioned_ingest_invocations.push(decommissioned_ingest_invocation)
+                }
+
+                FogUserEvent::MissingBlocks(range) => missed_block_ranges.push(range),
+            }
+        }
+
+        QueryResponse {
+            highest_processed_block_count: untrusted_query_response.highest_processed_block_count,
+            highest_processed_block_signature_timestamp: untrusted_query_response
+                .highest_processed_block_signature_timestamp,
+            next_start_from_user_event_id: untrusted_query_response.next_start_from_user_event_id,
+            missed_block_ranges,
+            rng_records,
+            decommissioned_ingest_invocations,
+            tx_out_search_results: Default::default(),
+            last_known_block_count: untrusted_query_response.last_known_block_count,
+            last_known_block_cumulative_txo_count: untrusted_query_response
+                .last_known_block_cumulative_txo_count,
+        }
+    }
+}
+
+/// Look up the next SUBSCRIPTION_SEARCH_KEYS_PER_RNG outputs of an rng, and
+/// advance the rng past the leading outputs that were found.
+///
+/// The same lookups and the same work on the rng state are done whatever the
+/// results are: the state after each lookup is copied into the new state of
+/// the rng with a conditional assignment, while all lookups so far found a
+/// TxOut.
+fn search_rng(
+    rng: &mut VersionedKexRng,
+    mut find_record: impl FnMut(&[u8]) -> TxOutSearchResult,
+) -> Result<Vec<TxOutSearchResult>> {
+    let mut cursor = rng.clone();
+    let mut selected = StoredRng::from(rng.clone());
+    let mut results = Vec::with_capacity(SUBSCRIPTION_SEARCH_KEYS_PER_RNG);
+    let mut found_prefix = Choice::from(1u8);
+    for _ in 0..SUBSCRIPTION_SEARCH_KEYS_PER_RNG {
+        let result = find_record(cursor.peek());
+        cursor.advance();
+
+        found_prefix &= result
+            .result_code
+            .ct_eq(&(TxOutSearchResultCode::Found as u32));
+        let advanced = StoredRng::from(cursor.clone());
+        selected
+            .counter
+            .conditional_assign(&advanced.counter, found_prefix);
+        for (byte, advanced_byte) in selected.buffer.iter_mut().zip(advanced.buffer.iter()) {
+            byte.conditional_assign(advanced_byte, found_prefix);
+        }
+        results.push(result);
+    }
+    *rng = VersionedKexRng::try_from(selected).map_err(|_| Error::InvalidSubscriptionRng)?;
+    Ok(results)
 }
 
 impl<OSC> ReportableEnclave for ViewEnclave<OSC>
@@ -107,6 +190,7 @@ where
     }
 
     fn client_close(&self, channel_id: ClientSession) -> Result<()> {
+        self.subscriptions.lock()?.pop(&channel_id);
         self.ake.client_close(channel_id)?;
         Ok(())
     }
@@ -125,35 +209,7 @@ where
         })?;
 
         // Prepare the untrusted part of the response.
-        let mut missed_block_ranges = Vec::new();
-        let mut rng_records = Vec::new();
-        let mut decommissioned_ingest_invocations = Vec::new();
-
-        for event in untrusted_query_response.user_events.into_iter() {
-            match event {
-                FogUserEvent::NewRngRecord(rng_record) => rng_records.push(rng_record),
-
-                FogUserEvent::DecommissionIngestInvocation(decommissioned_ingest_invocation) => {
-                    decommissioned_ingest_invocations.push(decommissioned_ingest_invocation)
-                }
-
-                FogUserEvent::MissingBlocks(range) => missed_block_ranges.push(range),
-            }
-        }
-
-        let mut resp = QueryResponse {
-            highest_processed_block_count: untrusted_query_response.highest_processed_block_count,
-            highest_processed_block_signature_timestamp: untrusted_query_response
-                .highest_processed_block_signature_timestamp,
-            next_start_from_user_event_id: untrusted_query_response.next_start_from_user_event_id,
-            missed_block_ranges,
-            rng_records,
-            decommissioned_ingest_invocations,
-            tx_out_search_results: Default::default(),
-            last_known_block_count: untrusted_query_response.last_known_block_count,
-            last_known_block_cumulative_txo_count: untrusted_query_response
-                .last_known_block_cumulative_txo_count,
-        };
+        let mut resp = Self::untrusted_response(untrusted_query_response);
 
         // Do the txos part, scope lock of e_tx_out_store
         {
@@ -176,6 +232,72 @@ where
         Ok(response.data)
     }
 
+    fn subscribe(&self, msg: EnclaveMessage<ClientSession>) -> Result<()> {
+        let channel_id = msg.channel_id.clone();
+        let user_plaintext = self.ake.client_decrypt(msg)?;
+
+        let req: SubscribeRequest = mc_util_serial::decode(&user_plaintext).map_err(|e| {
+            log::error!(self.logger, "Could not decode subscribe request: {}", e);
+            Error::ProstDecode
+        })?;
+
+        if req.rngs.len() > MAX_SUBSCRIPTION_RNGS {
+            return Err(Error::TooManySubscriptionRngs(req.rngs.len()));
+        }
+
+        let rngs = req
+            .rngs
+            .into_iter()
+            .map(VersionedKexRng::try_from)
+            .collect::<core::result::Result<Vec<_>, _>>()
+            .map_err(|_| Error::InvalidSubscriptionRng)?;
+
+        self.subscriptions.lock()?.put(channel_id, rngs);
+        Ok(())
+    }
+
+    fn subscription_query(
+        &self,
+        channel_ids: Vec<ClientSession>,
+        untrusted_query_response: UntrustedQueryResponse,
+    ) -> Result<Vec<Result<Vec<u8>>>> {
+        let untrusted_resp = Self::untrusted_response(untrusted_query_response);
+
+        // Scope locks of subscriptions and e_tx_out_store
+        let mut subscriptions = self.subscriptions.lock()?;
+        let mut lk = self.e_tx_out_store.lock()?;
+        let store = lk.as_mut().ok_or(Error::EnclaveNotInitialized)?;
+
+        Ok(channel_ids
+            .into_iter()
+            .map(|channel_id| -> Result<Vec<u8>> {
+                let mut resp = untrusted_resp.clone();
+
+                // Do the txos part
+                let rngs = subscriptions
+                    .get_mut(&channel_id)
+                    .ok_or(Error::NoSubscription)?;
+                for rng in rngs.iter_mut() {
+                    resp.tx_out_search_results
+                        .extend(search_rng(rng, |key| store.find_record(key))?);
+                }
+
+                let response_plaintext_bytes = mc_util_serial::encode(&resp);
+
+                let response =
+                    self.ake
+                        .client_encrypt(&channel_id, &[], &response_plaintext_bytes)?;
+
+                Ok(response.data)
+            })
+            .collect())
+    }
+
+    fn unsubscribe(&self, channel_id: ClientSession) -> Result<()> {
+        self.subscriptions.lock()?.pop(&channel_id);
+        Ok(())
+    }
+
     fn add_records(&self, records: Vec<ETxOutRecord>) -> Result<()> {
         let mut lk = self.e_tx_out_store.lock()?;
         let store = lk.as_mut().ok_or(Error::EnclaveNotInitialized)?;
@@ -185,3 +307,82 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use mc_crypto_keys::{RistrettoPrivate, RistrettoPublic};
+    use mc_fog_kex_rng::NewFromKex;
+
+    fn test_rng() -> VersionedKexRng {
+        let private_key = RistrettoPrivate::try_from(&[7u8; 32]).unwrap();
+        let public_key = RistrettoPublic::from(&private_key);
+        let (_, rng) = VersionedKexRng::new_from_static_static(&private_key, &public_key);
+        rng
+    }
+
+    /// Search the outputs of a fresh rng, finding those at the given indices.
+    /// Returns the rng after the search.
+    fn search_finding(found_indices: &[u64]) -> VersionedKexRng {
+        let found_keys: Vec<Vec<u8>> = test_rng()
+            .enumerate()
+            .take(SUBSCRIPTION_SEARCH_KEYS_PER_RNG)
+            .filter(|(index, _)| found_indices.contains(&(*index as u64)))
+            .map(|(_, key)| key)
+            .collect();
+
+        let mut rng = test_rng();
+        let results = search_rng(&mut rng, |key| {
+            let result_code = if found_keys.iter().any(|found_key| found_key == key) {
+                TxOutSearchResultCode::Found
+            } else {
+                TxOutSearchResultCode::NotFound
+            };
+            TxOutSearchResult {
+                search_key: key.to_vec(),
+                result_code: result_code as u32,
+                ciphertext: vec![],
+            }
+        })
+        .unwrap();
+        assert_eq!(results.len(), SUBSCRIPTION_SEARCH_KEYS_PER_RNG);
+        rng
+    }
+
+    /// The rng after advancing a fresh one `n` times.
+    fn advanced_rng(n: u64) -> VersionedKexRng {
+        let mut rng = test_rng();
+        for _ in 0..n {
+            rng.advance();
+        }
+        rng
+    }
+
+    #[test]
+    fn search_rng_advances_past_leading_hits() {
+        // No hits
+        let rng = search_finding(&[]);
+        assert_eq!(rng.index(), 0);
+        assert_eq!(rng.peek(), advanced_rng(0).peek());
+
+        // A hit after a miss doesn't count
+        let rng = search_finding(&[1, 2]);
+        assert_eq!(rng.index(), 0);
+        assert_eq!(rng.peek(), advanced_rng(0).peek());
+
+        // Partial hits
+        let rng = search_finding(&[0, 1, 2, 4]);
+        assert_eq!(rng.index(), 3);
+        assert_eq!(rng.peek(), advanced_rng(3).peek());
+
+        // All hits
+        let all: Vec<u64> = (0..SUBSCRIPTION_SEARCH_KEYS_PER_RNG as u64).collect();
+        let rng = search_finding(&all);
+        assert_eq!(rng.index(), SUBSCRIPTION_SEARCH_KEYS_PER_RNG as u64);
+        assert_eq!(
+            rng.peek(),
+            advanced_rng(SUBSCRIPTION_SEARCH_KEYS_PER_RNG as u64).peek()
+        );
+    }
+}
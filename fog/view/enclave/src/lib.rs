@@ -174,6 +174,31 @@ impl ViewEnclaveApi for SgxViewEnclave {
         mc_util_serial::deserialize(&outbuf[..])?
     }
 
+    fn subscribe(&self, payload: EnclaveMessage<ClientSession>) -> Result<()> {
+        let inbuf = mc_util_serial::serialize(&ViewEnclaveRequest::Subscribe(payload))?;
+        let outbuf = self.enclave_call(&inbuf)?;
+        mc_util_serial::deserialize(&outbuf[..])?
+    }
+
+    fn subscription_query(
+        &self,
+        channel_ids: Vec<ClientSession>,
+        untrusted_query_response: UntrustedQueryResponse,
+    ) -> Result<Vec<Result<Vec<u8>>>> {
+        let inbuf = mc_util_serial::serialize(&ViewEnclaveRequest::SubscriptionQuery(
+            channel_ids,
+            untrusted_query_response,
+        ))?;
+        let outbuf = self.enclave_call(&inbuf)?;
+        mc_util_serial::deserialize(&outbuf[..])?
+    }
+
+    fn unsubscribe(&self, channel_id: ClientSession) -> Result<()> {
+        let inbuf = mc_util_serial::serialize(&ViewEnclaveRequest::Unsubscribe(channel_id))?;
+        let outbuf = self.enclave_call(&inbuf)?;
+        mc_util_serial::deserialize(&outbuf[..])?
+    }
+
     fn add_records(&self, records: Vec<ETxOutRecord>) -> Result<()> {
         let inbuf = mc_util_serial::serialize(&ViewEnclaveRequest::AddRecords(records))?;
         let outbuf = self.enclave_call(&inbuf)?;
@@ -119,6 +119,11 @@ pub fn ecall_dispatcher(inbuf: &[u8]) -> Result<Vec<u8>, sgx_status_t> {
         ViewEnclaveRequest::Query(req, untrusted_query_response) => {
             serialize(&ENCLAVE.query(req, untrusted_query_response))
         }
+        ViewEnclaveRequest::Subscribe(req) => serialize(&ENCLAVE.subscribe(req)),
+        ViewEnclaveRequest::SubscriptionQuery(sessions, untrusted_query_response) => {
+            serialize(&ENCLAVE.subscription_query(sessions, untrusted_query_response))
+        }
+        ViewEnclaveRequest::Unsubscribe(session) => serialize(&ENCLAVE.unsubscribe(session)),
         ViewEnclaveRequest::AddRecords(records) => serialize(&ENCLAVE.add_records(records)),
     }
     .or(Err(sgx_status_t::SGX_ERROR_UNEXPECTED))
@@ -8,7 +8,12 @@ mod polling;
 pub use polling::{FogViewConnection, TxOutPollingError};
 
 mod user_private;
-pub use user_private::UserPrivate;
+pub use user_private::{SubaddressRangeError, UserPrivate};
 
 mod user_rng_set;
-pub use user_rng_set::{TxOutRecoveryError, UserRngSet};
+pub use user_rng_set::{
+    RngKey, RngSetError, StoredUserRng, StoredUserRngSet, TxOutRecoveryError, UserRngSet,
+};
+
+mod subscription;
+pub use subscription::{ingest_subscription_response, subscription_rngs, SubscriptionUpdate};
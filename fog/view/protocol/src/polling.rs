@@ -16,7 +16,7 @@
 
 use crate::{
     user_private::UserPrivate,
-    user_rng_set::{TxOutRecoveryError, UserRngSet},
+    user_rng_set::{RngKey, TxOutRecoveryError, UserRngSet},
 };
 use alloc::vec::Vec;
 use core::fmt::{Debug, Display};
@@ -124,7 +124,7 @@ pub trait FogViewConnection {
         let mut request_multiplier = 2u64; // This value doubles each round
                                            // A dead rng is one where, we got back fewer Tx's
                                            // than we requested for it in the previous round.
-        let mut dead_rng_set: HashSet<Vec<u8>> = Default::default();
+        let mut dead_rng_set: HashSet<RngKey> = Default::default();
         loop {
             // Escape if there are no more live rngs
             if dead_rng_set.len() >= user_rng_set.get_rngs().len() {
@@ -140,7 +140,7 @@ pub trait FogViewConnection {
             let search_keys: Vec<Vec<u8>> = user_rng_set
                 .get_rngs()
                 .iter()
-                .filter(|(nonce, _)| !dead_rng_set.contains(&nonce[..]))
+                .filter(|(key, _)| !dead_rng_set.contains(*key))
                 .flat_map(|(_, rng)| {
                     rng.clone()
                         .take(request_multiplier as usize)
@@ -188,7 +188,7 @@ pub trait FogViewConnection {
                 .iter()
                 .filter(|(key, rng)| {
                     old_rngs
-                        .get(&key[..])
+                        .get(*key)
                         .map(|old_rng| old_rng.index() + request_multiplier > rng.index())
                         .unwrap_or(true)
                 })
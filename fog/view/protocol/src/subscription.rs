@@ -0,0 +1,98 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Client side of the streaming fog view Subscribe API.
+//!
+//! A subscription searches a fixed number of outputs of each rng per pushed
+//! response (`SUBSCRIPTION_SEARCH_KEYS_PER_RNG`). When an rng found all of
+//! them, or when new rngs showed up, the pushed responses can no longer vouch
+//! for the highest processed block count, and the client has to catch up with
+//! `FogViewConnection::poll` and subscribe again with its new rng states.
+
+use crate::{
+    user_private::UserPrivate,
+    user_rng_set::{RngKey, TxOutRecoveryError, UserRngSet},
+};
+use alloc::vec::Vec;
+use mc_common::HashMap;
+use mc_fog_kex_rng::{BufferedRng, StoredRng};
+use mc_fog_types::{
+    common::BlockRange,
+    view::{QueryResponse, TxOutRecord, SUBSCRIPTION_SEARCH_KEYS_PER_RNG},
+};
+
+/// The outcome of ingesting one pushed subscription response
+#[derive(Debug, Default)]
+pub struct SubscriptionUpdate {
+    /// New TxOuts found for the user
+    pub tx_outs: Vec<TxOutRecord>,
+    /// Block ranges for which Fog Ingest did not process a user's TxOuts
+    pub missed_block_ranges: Vec<BlockRange>,
+    /// Errors encountered while ingesting the response
+    pub errors: Vec<TxOutRecoveryError>,
+    /// Whether the subscription no longer covers all of the user's TxOuts,
+    /// in which case the client should poll and subscribe again
+    pub needs_resubscribe: bool,
+}
+
+/// The rng states to send in a SubscribeRequest, ordered by subaddress index
+/// and nonce.
+pub fn subscription_rngs(user_rng_set: &UserRngSet) -> Vec<StoredRng> {
+    let mut rngs: Vec<_> = user_rng_set.get_rngs().iter().collect();
+    rngs.sort_by(|a, b| a.0.cmp(b.0));
+    rngs.into_iter()
+        .map(|(_, rng)| rng.clone().into())
+        .collect()
+}
+
+/// Ingest a pushed subscription response into a user rng set.
+///
+/// The highest processed block count of the set only advances if the
+/// response is complete, i.e. if no rng was saturated and no rng was added.
+pub fn ingest_subscription_response(
+    user_rng_set: &mut UserRngSet,
+    upriv: &UserPrivate,
+    resp: QueryResponse,
+) -> SubscriptionUpdate {
+    let mut update = SubscriptionUpdate::default();
+
+    let old_indices: HashMap<RngKey, u64> = user_rng_set
+        .get_rngs()
+        .iter()
+        .map(|(key, rng)| (key.clone(), rng.index()))
+        .collect();
+
+    for rng_record in resp.rng_records.iter() {
+        if let Err(err) = user_rng_set.ingest_rng_record(upriv, rng_record) {
+            update.errors.push(err);
+        }
+    }
+    let has_new_rngs = user_rng_set.get_rngs().len() != old_indices.len();
+
+    let (tx_outs, errors) =
+        user_rng_set.ingest_tx_out_search_results(upriv, &resp.tx_out_search_results);
+    update.tx_outs = tx_outs;
+    update.errors.extend(errors);
+
+    // An rng that advanced by the full number of searched outputs may have
+    // more TxOuts than the subscription looked for.
+    let is_saturated = old_indices.iter().any(|(key, old_index)| {
+        user_rng_set
+            .get_rngs()
+            .get(key)
+            .map(|rng| rng.index() >= old_index + SUBSCRIPTION_SEARCH_KEYS_PER_RNG as u64)
+            .unwrap_or(false)
+    });
+
+    update.missed_block_ranges = resp.missed_block_ranges;
+    user_rng_set.set_next_start_from_user_event_id(resp.next_start_from_user_event_id);
+
+    update.needs_resubscribe = has_new_rngs || is_saturated;
+    if !update.needs_resubscribe
+        && resp.highest_processed_block_count
+            > user_rng_set.get_highest_processed_block_count().into()
+    {
+        user_rng_set.set_highest_processed_block_count(resp.highest_processed_block_count);
+    }
+
+    update
+}
@@ -1,12 +1,16 @@
 // Copyright (c) 2018-2022 The MobileCoin Foundation
 
 use crate::user_rng_set::TxOutRecoveryError;
-use alloc::vec::Vec;
-use core::hash::{Hash, Hasher};
-use mc_account_keys::AccountKey;
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{
+    hash::{Hash, Hasher},
+    ops::Range,
+};
+use displaydoc::Display;
+use mc_account_keys::{AccountKey, DEFAULT_SUBADDRESS_INDEX};
 use mc_crypto_box::{CryptoBox, VersionedCryptoBox};
 use mc_crypto_keys::{RistrettoPrivate, RistrettoPublic};
-use mc_fog_types::view::TxOutRecord;
+use mc_fog_types::view::{TxOutRecord, MAX_SCANNED_SUBADDRESSES};
 use mc_transaction_core::fog_hint::FogHint;
 use mc_util_from_random::FromRandom;
 use rand_core::{CryptoRng, RngCore};
@@ -14,39 +18,76 @@ use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
 /// User's private keys (the only ones relevant to fog server)
+///
+/// This is the view private key of the subaddress the user gets paid at, and
+/// optionally the view private keys of further subaddresses to scan for.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct UserPrivate {
     view_key: RistrettoPrivate,
+    /// subaddress index -> view private key, for subaddresses other than the
+    /// default subaddress which fog should find TxOuts for
+    #[serde(default)]
+    subaddress_view_keys: BTreeMap<u64, RistrettoPrivate>,
 }
 impl Eq for UserPrivate {}
 
+// Two credentials are equal when they scan the same subaddresses with the
+// same view keys, compared by their public keys.
 impl PartialEq for UserPrivate {
     fn eq(&self, other: &Self) -> bool {
-        let self_pubkey = RistrettoPublic::from(&self.view_key);
-        let other_pubkey = RistrettoPublic::from(&other.view_key);
-        self_pubkey.eq(&other_pubkey)
+        self.subaddress_view_keys()
+            .map(|(index, view_key)| (index, RistrettoPublic::from(view_key)))
+            .eq(other
+                .subaddress_view_keys()
+                .map(|(index, view_key)| (index, RistrettoPublic::from(view_key))))
     }
 }
 
 impl Hash for UserPrivate {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        let pubkey = RistrettoPublic::from(&self.view_key);
-        pubkey.hash(state);
+        for (index, view_key) in self.subaddress_view_keys() {
+            index.hash(state);
+            RistrettoPublic::from(view_key).hash(state);
+        }
     }
 }
 
 impl UserPrivate {
     #[inline]
     pub fn new(view_key: RistrettoPrivate) -> Self {
-        Self { view_key }
+        Self {
+            view_key,
+            subaddress_view_keys: Default::default(),
+        }
+    }
+
+    /// Make the fog credential of an account which scans for TxOuts sent to
+    /// any subaddress in `subaddress_range`, in addition to the default
+    /// subaddress.
+    pub fn with_subaddress_range(
+        account_key: &AccountKey,
+        subaddress_range: Range<u64>,
+    ) -> Result<Self, SubaddressRangeError> {
+        let len = subaddress_range.end.saturating_sub(subaddress_range.start);
+        if len > MAX_SCANNED_SUBADDRESSES {
+            return Err(SubaddressRangeError::TooLarge(len));
+        }
+
+        let subaddress_view_keys = subaddress_range
+            .filter(|index| *index != DEFAULT_SUBADDRESS_INDEX)
+            .map(|index| (index, account_key.subaddress_view_private(index)))
+            .collect();
+
+        Ok(Self {
+            view_key: account_key.default_subaddress_view_private(),
+            subaddress_view_keys,
+        })
     }
 
     /// Make a random user (random private key)
     #[inline]
     pub fn random<T: RngCore + CryptoRng>(rng: &mut T) -> Self {
-        Self {
-            view_key: RistrettoPrivate::from_random(rng),
-        }
+        Self::new(RistrettoPrivate::from_random(rng))
     }
 
     /// Get private key b
@@ -67,15 +108,49 @@ impl UserPrivate {
         FogHint::new(self.get_view_pubkey())
     }
 
+    /// Get the view private key of a scanned subaddress, if any.
+    /// The key of the default subaddress is `get_view_key`.
+    pub fn get_subaddress_view_key(&self, subaddress_index: u64) -> Option<&RistrettoPrivate> {
+        if subaddress_index == DEFAULT_SUBADDRESS_INDEX {
+            Some(&self.view_key)
+        } else {
+            self.subaddress_view_keys.get(&subaddress_index)
+        }
+    }
+
+    /// Iterate over the scanned subaddresses and their view private keys,
+    /// starting with the default subaddress.
+    pub fn subaddress_view_keys(&self) -> impl Iterator<Item = (u64, &RistrettoPrivate)> {
+        core::iter::once((DEFAULT_SUBADDRESS_INDEX, &self.view_key)).chain(
+            self.subaddress_view_keys
+                .iter()
+                .map(|(index, view_key)| (*index, view_key)),
+        )
+    }
+
     /// Extract a TxOutRecord from an encrypted TxOutRecord from the view
     /// server, or return an error
     #[inline]
     pub fn decrypt_tx_out_result(
         &self,
+        payload: Vec<u8>,
+    ) -> Result<TxOutRecord, TxOutRecoveryError> {
+        self.decrypt_subaddress_tx_out_result(DEFAULT_SUBADDRESS_INDEX, payload)
+    }
+
+    /// Extract a TxOutRecord from an encrypted TxOutRecord that the view
+    /// server found with an rng of the given scanned subaddress, or return
+    /// an error
+    pub fn decrypt_subaddress_tx_out_result(
+        &self,
+        subaddress_index: u64,
         mut payload: Vec<u8>,
     ) -> Result<TxOutRecord, TxOutRecoveryError> {
+        let view_key = self
+            .get_subaddress_view_key(subaddress_index)
+            .ok_or(TxOutRecoveryError::UnknownSubaddress(subaddress_index))?;
         let success = VersionedCryptoBox::default()
-            .decrypt_in_place(self.get_view_key(), &mut payload)
+            .decrypt_in_place(view_key, &mut payload)
             .map_err(TxOutRecoveryError::DecryptionFailed)?;
         if !bool::from(success) {
             payload.zeroize();
@@ -88,6 +163,13 @@ impl UserPrivate {
     }
 }
 
+/// An error in the subaddress range scanned by a UserPrivate
+#[derive(Debug, Display, Eq, PartialEq)]
+pub enum SubaddressRangeError {
+    /// Too many subaddresses to scan: {0}
+    TooLarge(u64),
+}
+
 /// For fog users, we expect that the default subaddress is the public address
 /// that they will distribute to be paid at. Then, when talking to fog, they
 /// need to get the private view key corresponding to that public address that
@@ -98,9 +180,7 @@ impl UserPrivate {
 /// account key.  Now it is `c = a(b + m)` where `m = Hs(a || subaddress_index)`
 impl From<&AccountKey> for UserPrivate {
     fn from(src: &AccountKey) -> UserPrivate {
-        UserPrivate {
-            view_key: src.default_subaddress_view_private(),
-        }
+        UserPrivate::new(src.default_subaddress_view_private())
     }
 }
 
@@ -189,4 +269,59 @@ mod testing {
             .expect("Could not decrypt cryptogram");
         assert_eq!(plaintext, protobuf);
     }
+
+    /// Test that a fog credential scanning a subaddress range can decrypt
+    /// TxOutRecords encrypted for any subaddress of the range
+    #[test]
+    fn test_subaddress_range_decryption() {
+        let mut rng = Hc128Rng::from_seed([2u8; 32]);
+        let recipient = AccountKey::random_with_fog(&mut rng);
+        let upriv = UserPrivate::with_subaddress_range(&recipient, 0..10).unwrap();
+
+        assert_eq!(upriv.subaddress_view_keys().count(), 10);
+        assert_eq!(
+            upriv.get_subaddress_view_key(5).map(RistrettoPublic::from),
+            Some(*recipient.subaddress(5).view_public_key())
+        );
+        assert!(upriv.get_subaddress_view_key(10).is_none());
+        assert_ne!(upriv, UserPrivate::from(&recipient));
+        assert_eq!(
+            UserPrivate::with_subaddress_range(&recipient, 0..1).unwrap(),
+            UserPrivate::from(&recipient)
+        );
+
+        let txo_record = TxOutRecord {
+            tx_out_global_index: 7,
+            ..Default::default()
+        };
+        let protobuf = mc_util_serial::encode(&txo_record);
+        let payload = VersionedCryptoBox::default()
+            .encrypt(
+                &mut rng,
+                recipient.subaddress(5).view_public_key(),
+                &protobuf,
+            )
+            .expect("CryptoBox encryption should not fail");
+
+        assert_eq!(
+            upriv
+                .decrypt_subaddress_tx_out_result(5, payload.clone())
+                .unwrap(),
+            txo_record
+        );
+        assert!(matches!(
+            upriv.decrypt_subaddress_tx_out_result(4, payload.clone()),
+            Err(TxOutRecoveryError::MacCheckFailed)
+        ));
+        assert!(matches!(
+            upriv.decrypt_subaddress_tx_out_result(11, payload),
+            Err(TxOutRecoveryError::UnknownSubaddress(11))
+        ));
+
+        assert_eq!(
+            UserPrivate::with_subaddress_range(&recipient, 0..MAX_SCANNED_SUBADDRESSES + 1)
+                .unwrap_err(),
+            SubaddressRangeError::TooLarge(MAX_SCANNED_SUBADDRESSES + 1)
+        );
+    }
 }
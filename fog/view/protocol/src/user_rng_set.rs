@@ -7,19 +7,33 @@ use displaydoc::Display;
 use mc_common::HashMap;
 use mc_crypto_box::Error as CryptoBoxError;
 use mc_crypto_keys::KeyError;
-use mc_fog_kex_rng::{BufferedRng, Error as KexRngError, NewFromKex, VersionedKexRng};
+use mc_fog_kex_rng::{BufferedRng, Error as KexRngError, NewFromKex, StoredRng, VersionedKexRng};
 use mc_fog_types::{
     view::{RngRecord, TxOutRecord, TxOutSearchResult, TxOutSearchResultCode},
     BlockCount,
 };
 use mc_util_serial::DecodeError;
+use prost::Message;
+
+/// Identifies an rng of a UserRngSet
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct RngKey {
+    /// The subaddress whose view private key the rng was derived from
+    pub subaddress_index: u64,
+    /// The nonce (kex rng pubkey of the ingest invocation) the rng was
+    /// derived from
+    pub nonce: Vec<u8>,
+}
 
 /// A set of kex_rngs. Together with a view node endpoint, this can be used to
 /// find the user's transactions.
+///
+/// There is one rng for every pair of an ingest invocation and a subaddress
+/// scanned by the user.
 #[derive(Clone, Default)]
 pub struct UserRngSet {
-    /// nonce -> rngs
-    rngs: HashMap<Vec<u8>, VersionedKexRng>,
+    /// (subaddress index, nonce) -> rngs
+    rngs: HashMap<RngKey, VersionedKexRng>,
 
     /// Last highest_processed_block_count reported by the server
     highest_processed_block_count: u64,
@@ -37,7 +51,7 @@ impl UserRngSet {
         }
     }
 
-    pub fn get_rngs(&self) -> &HashMap<Vec<u8>, VersionedKexRng> {
+    pub fn get_rngs(&self) -> &HashMap<RngKey, VersionedKexRng> {
         &self.rngs
     }
 
@@ -57,18 +71,24 @@ impl UserRngSet {
         self.next_start_from_user_event_id = val;
     }
 
-    // Take a nonce and initialize a new rng from it if there isn't one
-    // already
+    // Take a nonce and initialize a new rng from it for each scanned subaddress
+    // if there isn't one already
     // TODO: Also update the start_block and end_block values
     pub fn ingest_rng_record(
         &mut self,
         upriv: &UserPrivate,
         rec: &RngRecord,
     ) -> Result<(), TxOutRecoveryError> {
-        let rng = VersionedKexRng::try_from_kex_pubkey(&rec.pubkey, upriv.get_view_key())?;
-        self.rngs
-            .entry(rec.pubkey.public_key.clone())
-            .or_insert(rng);
+        for (subaddress_index, view_key) in upriv.subaddress_view_keys() {
+            let key = RngKey {
+                subaddress_index,
+                nonce: rec.pubkey.public_key.clone(),
+            };
+            if !self.rngs.contains_key(&key) {
+                let rng = VersionedKexRng::try_from_kex_pubkey(&rec.pubkey, view_key)?;
+                self.rngs.insert(key, rng);
+            }
+        }
         Ok(())
     }
 
@@ -107,9 +127,9 @@ impl UserRngSet {
         }
 
         // Iterate over rngs, searching for its current state and advancing it
-        for (_, ref mut rng) in self.rngs.iter_mut() {
+        for (key, ref mut rng) in self.rngs.iter_mut() {
             while let Some(ciphertext) = tx_result_map.remove(rng.peek()) {
-                match upriv.decrypt_tx_out_result(ciphertext) {
+                match upriv.decrypt_subaddress_tx_out_result(key.subaddress_index, ciphertext) {
                     Ok(txo) => successes.push(txo),
                     Err(err) => failures.push(err),
                 };
@@ -126,6 +146,78 @@ impl UserRngSet {
     }
 }
 
+/// A stored, wire-stable representation of a UserRngSet, which lets a client
+/// resume the fog-view protocol where it left off.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct StoredUserRngSet {
+    /// The rngs in the set
+    #[prost(message, repeated, tag = 1)]
+    pub rngs: Vec<StoredUserRng>,
+    /// Last highest_processed_block_count reported by the server
+    #[prost(uint64, tag = 2)]
+    pub highest_processed_block_count: u64,
+    /// Last next_start_from_user_event_id reported by the server
+    #[prost(int64, tag = 3)]
+    pub next_start_from_user_event_id: i64,
+}
+
+/// A stored rng of a UserRngSet, together with its nonce
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct StoredUserRng {
+    /// The nonce (kex rng pubkey) the rng was created from
+    #[prost(bytes, tag = 1)]
+    pub nonce: Vec<u8>,
+    /// The state of the rng
+    #[prost(message, required, tag = 2)]
+    pub rng: StoredRng,
+    /// The subaddress whose view private key the rng was derived from
+    #[prost(uint64, tag = 3)]
+    pub subaddress_index: u64,
+}
+
+impl From<&UserRngSet> for StoredUserRngSet {
+    fn from(src: &UserRngSet) -> Self {
+        let mut rngs: Vec<StoredUserRng> = src
+            .rngs
+            .iter()
+            .map(|(key, rng)| StoredUserRng {
+                nonce: key.nonce.clone(),
+                rng: rng.clone().into(),
+                subaddress_index: key.subaddress_index,
+            })
+            .collect();
+        // Make the encoding deterministic
+        rngs.sort_by(|a, b| (a.subaddress_index, &a.nonce).cmp(&(b.subaddress_index, &b.nonce)));
+        Self {
+            rngs,
+            highest_processed_block_count: src.highest_processed_block_count,
+            next_start_from_user_event_id: src.next_start_from_user_event_id,
+        }
+    }
+}
+
+impl TryFrom<StoredUserRngSet> for UserRngSet {
+    type Error = RngSetError;
+    fn try_from(src: StoredUserRngSet) -> Result<Self, RngSetError> {
+        let rngs = src
+            .rngs
+            .into_iter()
+            .map(|stored| {
+                let key = RngKey {
+                    subaddress_index: stored.subaddress_index,
+                    nonce: stored.nonce,
+                };
+                Ok((key, VersionedKexRng::try_from(stored.rng)?))
+            })
+            .collect::<Result<_, RngSetError>>()?;
+        Ok(Self {
+            rngs,
+            highest_processed_block_count: src.highest_processed_block_count,
+            next_start_from_user_event_id: src.next_start_from_user_event_id,
+        })
+    }
+}
+
 // Error type for a user rng set
 
 #[derive(Debug, Display)]
@@ -151,6 +243,8 @@ pub enum TxOutRecoveryError {
     UnexpectedTxOutSearchResultCode(u32, Vec<u8>),
     /// Search key was not found amongst our rngs: {0:?}
     SearchKeyNotFound(Vec<u8>),
+    /// No view key for subaddress {0}
+    UnknownSubaddress(u64),
     /// Error initializing KexRng: {0}
     KexRng(KexRngError),
 }
@@ -173,9 +267,11 @@ impl From<DecodeError> for TxOutRecoveryError {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Display)]
 pub enum RngSetError {
+    /// Could not decode stored rng set: {0}
     Decode(DecodeError),
+    /// Could not restore KexRng: {0}
     KexRng(KexRngError),
 }
 
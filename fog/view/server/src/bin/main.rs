@@ -4,7 +4,12 @@
 //! MobileCoin Fog View target
 use grpcio::{RpcStatus, RpcStatusCode};
 use mc_attest_net::{Client, RaClient};
-use mc_common::{logger::log, time::SystemTimeProvider};
+use mc_common::{
+    logger::{log, Logger},
+    time::SystemTimeProvider,
+};
+use mc_fog_lmdb_recovery_db::{is_lmdb_url, LmdbRecoveryDb};
+use mc_fog_recovery_db_iface::RecoveryDb;
 use mc_fog_sql_recovery_db::SqlRecoveryDb;
 use mc_fog_view_enclave::{SgxViewEnclave, ENCLAVE_FILE};
 use mc_fog_view_server::{config::MobileAcctViewConfig, server::ViewServer};
@@ -19,13 +24,6 @@ fn main() {
         mc_common::logger::create_app_logger(mc_common::logger::o!());
     let config = MobileAcctViewConfig::parse();
 
-    let recovery_db = SqlRecoveryDb::new_from_url(
-        &std::env::var("DATABASE_URL").expect("DATABASE_URL environment variable missing"),
-        config.postgres_config.clone(),
-        logger.clone(),
-    )
-    .expect("Failed connecting to database");
-
     let _tracer = mc_util_telemetry::setup_default_tracer_with_tags(
         env!("CARGO_PKG_NAME"),
         &[(
@@ -35,6 +33,27 @@ fn main() {
     )
     .expect("Failed setting telemetry tracer");
 
+    let database_url =
+        std::env::var("DATABASE_URL").expect("DATABASE_URL environment variable missing");
+    if is_lmdb_url(&database_url) {
+        let recovery_db = LmdbRecoveryDb::new_from_url(&database_url, logger.clone())
+            .expect("Failed opening database");
+        run_server(config, recovery_db, logger)
+    } else {
+        let recovery_db = SqlRecoveryDb::new_from_url(
+            &database_url,
+            config.postgres_config.clone(),
+            logger.clone(),
+        )
+        .expect("Failed connecting to database");
+        run_server(config, recovery_db, logger)
+    }
+}
+
+fn run_server<DB>(config: MobileAcctViewConfig, recovery_db: DB, logger: Logger) -> !
+where
+    DB: RecoveryDb + Clone + Send + Sync + 'static,
+{
     let enclave_path = env::current_exe()
         .expect("Could not get the path of our executable")
         .with_file_name(ENCLAVE_FILE);
@@ -1,8 +1,14 @@
 // Copyright (c) 2018-2022 The MobileCoin Foundation
 
-use crate::server::DbPollSharedState;
-use grpcio::{RpcContext, RpcStatus, RpcStatusCode, UnarySink};
+use crate::{
+    server::DbPollSharedState,
+    subscription::{
+        BlockProcessedNotifier, SubscriptionBackend, SubscriptionPoller, SubscriptionQueryResult,
+    },
+};
+use grpcio::{RpcContext, RpcStatus, RpcStatusCode, ServerStreamingSink, UnarySink};
 use mc_attest_api::attest;
+use mc_attest_enclave_api::ClientSession;
 use mc_common::logger::{log, Logger};
 use mc_fog_api::view_grpc::FogViewApi;
 use mc_fog_recovery_db_iface::RecoveryDb;
@@ -28,6 +34,9 @@ pub struct FogViewService<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync> {
     /// Shared state from db polling thread.
     db_poll_shared_state: Arc<Mutex<DbPollSharedState>>,
 
+    /// Pushes new results to subscribed client sessions.
+    subscription_poller: SubscriptionPoller<ViewSubscriptionBackend<E, DB>>,
+
     /// GRPC request authenticator.
     authenticator: Arc<dyn Authenticator + Send + Sync>,
 
@@ -35,20 +44,33 @@ pub struct FogViewService<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync> {
     logger: Logger,
 }
 
-impl<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync> FogViewService<E, DB> {
+impl<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync + 'static> FogViewService<E, DB> {
     /// Creates a new fog-view-service node (but does not create sockets and
     /// start it etc.)
     pub fn new(
         enclave: E,
         db: Arc<DB>,
         db_poll_shared_state: Arc<Mutex<DbPollSharedState>>,
+        block_processed_notifier: BlockProcessedNotifier,
         authenticator: Arc<dyn Authenticator + Send + Sync>,
         logger: Logger,
     ) -> Self {
+        let subscription_poller = SubscriptionPoller::new(
+            ViewSubscriptionBackend {
+                enclave: enclave.clone(),
+                db: db.clone(),
+                db_poll_shared_state: db_poll_shared_state.clone(),
+                logger: logger.clone(),
+            },
+            block_processed_notifier,
+            logger.clone(),
+        );
+
         Self {
             enclave,
             db,
             db_poll_shared_state,
+            subscription_poller,
             authenticator,
             logger,
         }
@@ -61,49 +83,19 @@ impl<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync> FogViewService<E, DB> {
 
         tracer.in_span("query_impl", |_cx| {
             // Attempt and deserialize the untrusted portion of this request.
-            let query_request_aad: QueryRequestAAD = mc_util_serial::decode(request.get_aad())
-                .map_err(|err| {
-                    RpcStatus::with_message(
-                        RpcStatusCode::INVALID_ARGUMENT,
-                        format!("AAD deserialization error: {}", err),
-                    )
-                })?;
-
-            let (user_events, next_start_from_user_event_id) =
-                tracer.in_span("search_user_events", |_cx| {
-                    self.db
-                        .search_user_events(query_request_aad.start_from_user_event_id)
-                        .map_err(|e| rpc_internal_error("search_user_events", e, &self.logger))
-                })?;
-
-            let (
-                highest_processed_block_count,
-                highest_processed_block_signature_timestamp,
-                last_known_block_count,
-                last_known_block_cumulative_txo_count,
-            ) = tracer.in_span("get_shared_state", |_cx_| {
-                let shared_state = self.db_poll_shared_state.lock().expect("mutex poisoned");
-                (
-                    shared_state.highest_processed_block_count,
-                    shared_state.highest_processed_block_signature_timestamp,
-                    shared_state.last_known_block_count,
-                    shared_state.last_known_block_cumulative_txo_count,
-                )
-            });
-
-            let untrusted_query_response = UntrustedQueryResponse {
-                user_events,
-                next_start_from_user_event_id,
-                highest_processed_block_count,
-                highest_processed_block_signature_timestamp,
-                last_known_block_count,
-                last_known_block_cumulative_txo_count,
-            };
+            let query_request_aad = decode_aad(&request)?;
+
+            let untrusted_query_response = untrusted_query_response(
+                self.db.as_ref(),
+                &self.db_poll_shared_state,
+                query_request_aad.start_from_user_event_id,
+                &self.logger,
+            )?;
 
             let result_blob = tracer.in_span("enclave_query", |_cx| {
                 self.enclave
                     .query(request.into(), untrusted_query_response)
-                    .map_err(|e| self.enclave_err_to_rpc_status("enclave request", e))
+                    .map_err(|e| enclave_err_to_rpc_status("enclave request", e, &self.logger))
             })?;
 
             let mut resp = attest::Message::new();
@@ -111,26 +103,159 @@ impl<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync> FogViewService<E, DB> {
             Ok(resp)
         })
     }
+}
 
-    // Helper function that is common
-    fn enclave_err_to_rpc_status(&self, context: &str, src: ViewEnclaveError) -> RpcStatus {
-        // Treat prost-decode error as an invalid arg,
-        // treat attest error as permission denied,
-        // everything else is an internal error
-        match src {
-            ViewEnclaveError::ProstDecode => {
-                rpc_invalid_arg_error(context, "Prost decode failed", &self.logger)
-            }
-            ViewEnclaveError::AttestEnclave(err) => {
-                rpc_permissions_error(context, err, &self.logger)
-            }
-            other => rpc_internal_error(context, format!("{}", &other), &self.logger),
+/// Serves the subscriptions of the fog view service from the enclave and the
+/// recovery db.
+pub struct ViewSubscriptionBackend<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync> {
+    enclave: E,
+    db: Arc<DB>,
+    db_poll_shared_state: Arc<Mutex<DbPollSharedState>>,
+    logger: Logger,
+}
+
+impl<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync + 'static> SubscriptionBackend
+    for ViewSubscriptionBackend<E, DB>
+{
+    fn highest_processed_block_count(&self) -> u64 {
+        self.db_poll_shared_state
+            .lock()
+            .expect("mutex poisoned")
+            .highest_processed_block_count
+    }
+
+    fn subscribe(&self, request: attest::Message) -> Result<(ClientSession, i64), RpcStatus> {
+        log::trace!(self.logger, "Getting encrypted subscribe request");
+        let query_request_aad = decode_aad(&request)?;
+        let channel_id = ClientSession::from(request.get_channel_id());
+
+        self.enclave
+            .subscribe(request.into())
+            .map_err(|e| enclave_err_to_rpc_status("enclave subscribe", e, &self.logger))?;
+
+        Ok((channel_id, query_request_aad.start_from_user_event_id))
+    }
+
+    fn subscription_query(
+        &self,
+        channel_ids: &[ClientSession],
+        start_from_user_event_id: i64,
+    ) -> SubscriptionQueryResult {
+        let untrusted_query_response = untrusted_query_response(
+            self.db.as_ref(),
+            &self.db_poll_shared_state,
+            start_from_user_event_id,
+            &self.logger,
+        )?;
+        let highest_processed_block_count = untrusted_query_response.highest_processed_block_count;
+        let next_start_from_user_event_id = untrusted_query_response.next_start_from_user_event_id;
+
+        let result_blobs = self
+            .enclave
+            .subscription_query(channel_ids.to_vec(), untrusted_query_response)
+            .map_err(|e| {
+                enclave_err_to_rpc_status("enclave subscription query", e, &self.logger)
+            })?;
+
+        let responses = channel_ids
+            .iter()
+            .zip(result_blobs)
+            .map(
+                |(channel_id, result_blob)| -> Result<attest::Message, RpcStatus> {
+                    let result_blob = result_blob.map_err(|e| {
+                        enclave_err_to_rpc_status("enclave subscription query", e, &self.logger)
+                    })?;
+                    let mut resp = attest::Message::new();
+                    resp.set_channel_id(channel_id.clone().into());
+                    resp.set_data(result_blob);
+                    Ok(resp)
+                },
+            )
+            .collect();
+
+        Ok((
+            highest_processed_block_count,
+            responses,
+            next_start_from_user_event_id,
+        ))
+    }
+
+    fn unsubscribe(&self, channel_id: &ClientSession) {
+        if let Err(err) = self.enclave.unsubscribe(channel_id.clone()) {
+            log::debug!(self.logger, "Failed to unsubscribe: {}", err);
         }
     }
 }
 
+// Attempt and deserialize the untrusted portion of a request.
+fn decode_aad(request: &attest::Message) -> Result<QueryRequestAAD, RpcStatus> {
+    mc_util_serial::decode(request.get_aad()).map_err(|err| {
+        RpcStatus::with_message(
+            RpcStatusCode::INVALID_ARGUMENT,
+            format!("AAD deserialization error: {}", err),
+        )
+    })
+}
+
+// Collect the untrusted data for a response: user events since
+// start_from_user_event_id, and the state of the db polling thread.
+fn untrusted_query_response<DB: RecoveryDb>(
+    db: &DB,
+    db_poll_shared_state: &Mutex<DbPollSharedState>,
+    start_from_user_event_id: i64,
+    logger: &Logger,
+) -> Result<UntrustedQueryResponse, RpcStatus> {
+    let tracer = tracer!();
+
+    let (user_events, next_start_from_user_event_id) =
+        tracer.in_span("search_user_events", |_cx| {
+            db.search_user_events(start_from_user_event_id)
+                .map_err(|e| rpc_internal_error("search_user_events", e, logger))
+        })?;
+
+    let (
+        highest_processed_block_count,
+        highest_processed_block_signature_timestamp,
+        last_known_block_count,
+        last_known_block_cumulative_txo_count,
+    ) = tracer.in_span("get_shared_state", |_cx_| {
+        let shared_state = db_poll_shared_state.lock().expect("mutex poisoned");
+        (
+            shared_state.highest_processed_block_count,
+            shared_state.highest_processed_block_signature_timestamp,
+            shared_state.last_known_block_count,
+            shared_state.last_known_block_cumulative_txo_count,
+        )
+    });
+
+    Ok(UntrustedQueryResponse {
+        user_events,
+        next_start_from_user_event_id,
+        highest_processed_block_count,
+        highest_processed_block_signature_timestamp,
+        last_known_block_count,
+        last_known_block_cumulative_txo_count,
+    })
+}
+
+// Helper function that is common
+fn enclave_err_to_rpc_status(context: &str, src: ViewEnclaveError, logger: &Logger) -> RpcStatus {
+    // Treat prost-decode error as an invalid arg,
+    // treat attest error as permission denied,
+    // everything else is an internal error
+    match src {
+        ViewEnclaveError::ProstDecode => {
+            rpc_invalid_arg_error(context, "Prost decode failed", logger)
+        }
+        ViewEnclaveError::AttestEnclave(err) => rpc_permissions_error(context, err, logger),
+        other => rpc_internal_error(context, format!("{}", &other), logger),
+    }
+}
+
 // Implement grpc trait
-impl<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync> FogViewApi for FogViewService<E, DB> {
+impl<E: ViewEnclaveProxy, DB: RecoveryDb + Clone + Send + Sync + 'static> FogViewApi
+    for FogViewService<E, DB>
+{
     fn auth(
         &mut self,
         ctx: RpcContext,
@@ -188,4 +313,23 @@ impl<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync> FogViewApi for FogViewSe
             send_result(ctx, sink, self.query_impl(request), logger)
         })
     }
+
+    fn subscribe(
+        &mut self,
+        ctx: RpcContext,
+        request: attest::Message,
+        sink: ServerStreamingSink<attest::Message>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            let request = self
+                .authenticator
+                .authenticate_rpc(&ctx)
+                .map(|_| request)
+                .or_else(|err| err.into());
+
+            self.subscription_poller
+                .subscribe(&ctx, sink, request, logger.clone());
+        })
+    }
 }
@@ -6,6 +6,7 @@ pub mod config;
 pub mod error;
 pub mod fog_view_service;
 pub mod server;
+pub mod subscription;
 
 mod block_tracker;
 mod counters;
@@ -6,7 +6,7 @@
 
 use crate::{
     block_tracker::BlockTracker, config::MobileAcctViewConfig, counters, db_fetcher::DbFetcher,
-    fog_view_service::FogViewService,
+    fog_view_service::FogViewService, subscription::BlockProcessedNotifier,
 };
 use futures::executor::block_on;
 use mc_attest_net::RaClient;
@@ -98,6 +98,7 @@ where
             enclave.clone(),
             Arc::new(recovery_db),
             db_poll_thread.get_shared_state(),
+            db_poll_thread.get_notifier(),
             client_authenticator,
             logger.clone(),
         ));
@@ -224,6 +225,9 @@ where
     /// Shared state.
     shared_state: Arc<Mutex<DbPollSharedState>>,
 
+    /// Signals changes of the highest processed block count.
+    notifier: BlockProcessedNotifier,
+
     /// Readiness indicator.
     readiness_indicator: ReadinessIndicator,
 
@@ -244,6 +248,12 @@ where
         self.shared_state.clone()
     }
 
+    /// Get the notifier signaling changes of the highest processed block
+    /// count.
+    pub fn get_notifier(&self) -> BlockProcessedNotifier {
+        self.notifier.clone()
+    }
+
     /// Initialize a new DbPollThread object.
     pub fn new(
         enclave: E,
@@ -260,6 +270,7 @@ where
             join_handle: None,
             stop_requested,
             shared_state,
+            notifier: BlockProcessedNotifier::default(),
             readiness_indicator,
             logger,
         }
@@ -278,6 +289,7 @@ where
         let thread_db = self.db.clone();
         let thread_stop_requested = self.stop_requested.clone();
         let thread_shared_state = self.shared_state.clone();
+        let thread_notifier = self.notifier.clone();
         let thread_readiness_indicator = self.readiness_indicator.clone();
         let thread_logger = self.logger.clone();
 
@@ -290,6 +302,7 @@ where
                         thread_db,
                         thread_stop_requested,
                         thread_shared_state,
+                        thread_notifier,
                         thread_readiness_indicator,
                         thread_logger,
                     )
@@ -313,6 +326,7 @@ where
         db: DB,
         stop_requested: Arc<AtomicBool>,
         shared_state: Arc<Mutex<DbPollSharedState>>,
+        notifier: BlockProcessedNotifier,
         readiness_indicator: ReadinessIndicator,
         logger: Logger,
     ) {
@@ -323,6 +337,7 @@ where
             enclave,
             db,
             shared_state,
+            notifier,
             readiness_indicator,
             logger.clone(),
         );
@@ -370,6 +385,9 @@ where
     /// Shared state.
     shared_state: Arc<Mutex<DbPollSharedState>>,
 
+    /// Signals changes of the highest processed block count to subscriptions.
+    notifier: BlockProcessedNotifier,
+
     /// Database fetcher - a background thread that attempts to fetch as much
     /// data from the database as possible.
     db_fetcher: DbFetcher,
@@ -405,6 +423,7 @@ where
         enclave: E,
         db: DB,
         shared_state: Arc<Mutex<DbPollSharedState>>,
+        notifier: BlockProcessedNotifier,
         readiness_indicator: ReadinessIndicator,
         logger: Logger,
     ) -> Self {
@@ -413,6 +432,7 @@ where
             enclave,
             db: db.clone(),
             shared_state,
+            notifier,
             db_fetcher: DbFetcher::new(db, readiness_indicator, logger.clone()),
             enclave_block_tracker: BlockTracker::new(logger.clone()),
             last_unblocked_at: Instant::now(),
@@ -480,6 +500,7 @@ where
         if shared_state.highest_processed_block_count != highest_processed_block_count {
             shared_state.highest_processed_block_count = highest_processed_block_count;
             self.last_unblocked_at = Instant::now();
+            self.notifier.notify();
         } else if self.last_unblocked_at.elapsed() >= Duration::from_secs(60) {
             if let Some(reason_we_stopped) = reason_we_stopped {
                 log::warn!(self.logger, "We seem to be stuck at highest_processed_block_count = {} for at least a minute... we are blocked on an ingress key making progress: {:?}", highest_processed_block_count, reason_we_stopped);
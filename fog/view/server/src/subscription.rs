@@ -0,0 +1,481 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Support for the streaming Subscribe API.
+//! * `BlockProcessedNotifier` wakes up the poller whenever the db poll thread
+//!   advances the highest processed block count.
+//! * `SubscriptionPoller` pushes one response to every subscriber each time the
+//!   highest processed block count changes, serving all subscriptions from a
+//!   single thread and querying the enclave for many sessions at once.
+
+use futures::{FutureExt, TryFutureExt};
+use grpcio::{RpcContext, RpcStatus, RpcStatusCode, ServerStreamingSink};
+use mc_attest_api::attest;
+use mc_attest_enclave_api::ClientSession;
+use mc_common::{
+    logger::{log, Logger},
+    HashMap,
+};
+use mc_util_grpc::{streaming_sender, StreamingSendError, StreamingSender};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::Duration,
+};
+
+/// How long the poller waits for a notification before checking whether
+/// subscribers went away or caught up with their queued responses.
+pub const SUBSCRIPTION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Maximum number of concurrent subscriptions. Further requests are rejected
+/// as over capacity.
+pub const MAX_SUBSCRIPTIONS: usize = 10_000;
+
+/// Maximum number of client sessions served by a single enclave call.
+pub const MAX_SESSIONS_PER_ENCLAVE_CALL: usize = 100;
+
+/// Number of responses that may be queued for a subscriber.
+const SUBSCRIPTION_CHANNEL_SIZE: usize = 4;
+
+/// A generation counter that is bumped every time the highest processed block
+/// count changes, and that subscriptions can wait on.
+#[derive(Clone, Default)]
+pub struct BlockProcessedNotifier {
+    inner: Arc<(Mutex<u64>, Condvar)>,
+}
+
+impl BlockProcessedNotifier {
+    /// Signal that the highest processed block count changed.
+    pub fn notify(&self) {
+        let (lock, condvar) = &*self.inner;
+        let mut generation = lock.lock().expect("mutex poisoned");
+        *generation = generation.wrapping_add(1);
+        condvar.notify_all();
+    }
+
+    /// Get the current generation.
+    pub fn generation(&self) -> u64 {
+        *self.inner.0.lock().expect("mutex poisoned")
+    }
+
+    /// Wait until the generation differs from `generation`, or until `timeout`
+    /// elapses. Returns the current generation.
+    pub fn wait(&self, generation: u64, timeout: Duration) -> u64 {
+        let (lock, condvar) = &*self.inner;
+        let guard = lock.lock().expect("mutex poisoned");
+        let (guard, _timeout_result) = condvar
+            .wait_timeout_while(guard, timeout, |current| *current == generation)
+            .expect("mutex poisoned");
+        *guard
+    }
+}
+
+/// The highest processed block count a batch of responses was made for, a
+/// response (or an error) per client session, and the next value of
+/// start_from_user_event_id.
+pub type SubscriptionQueryResult =
+    Result<(u64, Vec<Result<attest::Message, RpcStatus>>, i64), RpcStatus>;
+
+/// What the poller needs from the view server.
+pub trait SubscriptionBackend: Send + Sync + 'static {
+    /// Get the highest processed block count.
+    fn highest_processed_block_count(&self) -> u64;
+
+    /// Register the rngs of an encrypted SubscribeRequest with the enclave,
+    /// returning the client session and the start_from_user_event_id of its
+    /// first response.
+    fn subscribe(&self, request: attest::Message) -> Result<(ClientSession, i64), RpcStatus>;
+
+    /// Produce the next encrypted response of each of the client sessions,
+    /// all starting from `start_from_user_event_id`.
+    fn subscription_query(
+        &self,
+        channel_ids: &[ClientSession],
+        start_from_user_event_id: i64,
+    ) -> SubscriptionQueryResult;
+
+    /// Forget the rngs registered with a client session.
+    fn unsubscribe(&self, channel_id: &ClientSession);
+}
+
+/// A client session subscribed to new results.
+struct Subscription {
+    sender: StreamingSender<attest::Message>,
+
+    /// The start_from_user_event_id of the next response.
+    start_from_user_event_id: i64,
+
+    /// The highest processed block count of the last response produced.
+    last_block_count: Option<u64>,
+
+    /// A response that did not fit in the queue yet. The enclave advanced the
+    /// rngs of the session when producing it, so it has to be sent before the
+    /// next one is produced.
+    unsent: Option<attest::Message>,
+}
+
+impl Subscription {
+    /// Queue a response, keeping it for later if the queue is full. Returns
+    /// false once the subscriber went away.
+    fn send(&mut self, response: attest::Message) -> bool {
+        match self.sender.try_send(response.clone()) {
+            Ok(()) => true,
+            Err(StreamingSendError::Full) => {
+                self.unsent = Some(response);
+                true
+            }
+            Err(StreamingSendError::Disconnected) => false,
+        }
+    }
+}
+
+/// Pushes new results to subscribed client sessions.
+///
+/// A single thread waits for the highest processed block count to change, and
+/// then produces the next response of every subscription, querying the enclave
+/// for up to `MAX_SESSIONS_PER_ENCLAVE_CALL` sessions at once. A session has at
+/// most one subscription: subscribing again replaces the previous one. The
+/// thread exits once all clones of the poller are dropped.
+pub struct SubscriptionPoller<B> {
+    backend: Arc<B>,
+
+    /// The subscription of each client session. The lock is held while
+    /// querying the enclave, so that a session cannot subscribe again between
+    /// a query advancing its rngs and the response being queued.
+    subscriptions: Arc<Mutex<HashMap<ClientSession, Subscription>>>,
+}
+
+impl<B> Clone for SubscriptionPoller<B> {
+    fn clone(&self) -> Self {
+        Self {
+            backend: self.backend.clone(),
+            subscriptions: self.subscriptions.clone(),
+        }
+    }
+}
+
+impl<B: SubscriptionBackend> SubscriptionPoller<B> {
+    /// Start polling.
+    pub fn new(backend: B, notifier: BlockProcessedNotifier, logger: Logger) -> Self {
+        let backend = Arc::new(backend);
+        let subscriptions = Arc::new(Mutex::new(HashMap::default()));
+
+        let thread_backend = backend.clone();
+        let weak_subscriptions = Arc::downgrade(&subscriptions);
+        thread::Builder::new()
+            .name("FogViewSubscriptionPoller".to_owned())
+            .spawn(move || {
+                while let Some(subscriptions) = weak_subscriptions.upgrade() {
+                    let generation = notifier.generation();
+                    poll(thread_backend.as_ref(), &subscriptions);
+                    drop(subscriptions);
+                    notifier.wait(generation, SUBSCRIPTION_POLL_INTERVAL);
+                }
+                log::debug!(logger, "Subscription poller stopped");
+            })
+            .expect("failed starting subscription poller thread");
+
+        Self {
+            backend,
+            subscriptions,
+        }
+    }
+
+    /// Subscribe a client session to new results, replacing its previous
+    /// subscription if any. The first response is pushed on the next poll.
+    /// Requests beyond `MAX_SUBSCRIPTIONS` concurrent subscriptions are
+    /// rejected.
+    pub fn subscribe(
+        &self,
+        ctx: &RpcContext,
+        sink: ServerStreamingSink<attest::Message>,
+        request: Result<attest::Message, RpcStatus>,
+        logger: Logger,
+    ) {
+        let mut subscriptions = self.subscriptions.lock().expect("mutex poisoned");
+        let result = request.and_then(|request| {
+            let channel_id = ClientSession::from(request.get_channel_id());
+            if subscriptions.len() >= MAX_SUBSCRIPTIONS && !subscriptions.contains_key(&channel_id)
+            {
+                return Err(RpcStatus::with_message(
+                    RpcStatusCode::RESOURCE_EXHAUSTED,
+                    "Too many subscriptions".into(),
+                ));
+            }
+            self.backend.subscribe(request)
+        });
+
+        match result {
+            Ok((channel_id, start_from_user_event_id)) => {
+                let sender = streaming_sender(ctx, sink, SUBSCRIPTION_CHANNEL_SIZE, logger);
+                // Dropping the previous subscription of the session ends its stream.
+                subscriptions.insert(
+                    channel_id,
+                    Subscription {
+                        sender,
+                        start_from_user_event_id,
+                        last_block_count: None,
+                        unsent: None,
+                    },
+                );
+            }
+            Err(status) => {
+                let future = sink
+                    .fail(status)
+                    .map_err(move |err| log::error!(logger, "failed to reply: {:?}", err))
+                    .map(|_| ());
+                ctx.spawn(future);
+            }
+        }
+    }
+}
+
+/// Produce the next response of every subscription that is not up to date
+/// with the highest processed block count, and drop the subscriptions whose
+/// subscriber went away.
+fn poll<B: SubscriptionBackend>(
+    backend: &B,
+    subscriptions: &Mutex<HashMap<ClientSession, Subscription>>,
+) {
+    let mut subscriptions = subscriptions.lock().expect("mutex poisoned");
+    let block_count = backend.highest_processed_block_count();
+
+    // Retry the responses that did not fit in the queues, and group the
+    // sessions that are due a new response by the user event they start from.
+    let mut ended = Vec::new();
+    let mut due = BTreeMap::<i64, Vec<ClientSession>>::new();
+    for (channel_id, subscription) in subscriptions.iter_mut() {
+        if let Some(response) = subscription.unsent.take() {
+            if !subscription.send(response) {
+                ended.push(channel_id.clone());
+                continue;
+            }
+        }
+        if subscription.sender.is_closed() {
+            ended.push(channel_id.clone());
+        } else if subscription.unsent.is_none()
+            && subscription.last_block_count != Some(block_count)
+        {
+            due.entry(subscription.start_from_user_event_id)
+                .or_default()
+                .push(channel_id.clone());
+        }
+    }
+
+    for (start_from_user_event_id, channel_ids) in due {
+        for channel_ids in channel_ids.chunks(MAX_SESSIONS_PER_ENCLAVE_CALL) {
+            match backend.subscription_query(channel_ids, start_from_user_event_id) {
+                Ok((block_count, responses, next_start_from_user_event_id)) => {
+                    for (channel_id, response) in channel_ids.iter().zip(responses) {
+                        let subscription = subscriptions
+                            .get_mut(channel_id)
+                            .expect("subscriptions are only removed by the poller");
+                        subscription.start_from_user_event_id = next_start_from_user_event_id;
+                        subscription.last_block_count = Some(block_count);
+                        let sent = match response {
+                            Ok(response) => subscription.send(response),
+                            Err(status) => {
+                                let _ = subscription.sender.try_fail(status);
+                                false
+                            }
+                        };
+                        if !sent {
+                            ended.push(channel_id.clone());
+                        }
+                    }
+                }
+                Err(status) => {
+                    for channel_id in channel_ids {
+                        if let Some(subscription) = subscriptions.get_mut(channel_id) {
+                            let _ = subscription.sender.try_fail(status.clone());
+                        }
+                        ended.push(channel_id.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    // Forget the rngs of the sessions once nothing pushes to them anymore.
+    for channel_id in ended {
+        if subscriptions.remove(&channel_id).is_some() {
+            backend.unsubscribe(&channel_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_util_grpc::{streaming_channel, StreamingReceiver};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// A backend whose responses carry the client session, and which starts
+    /// every response one user event after the previous one.
+    #[derive(Default)]
+    struct TestBackend {
+        block_count: AtomicU64,
+        queries: Mutex<Vec<(Vec<ClientSession>, i64)>>,
+        unsubscribed: Mutex<Vec<ClientSession>>,
+    }
+
+    impl SubscriptionBackend for TestBackend {
+        fn highest_processed_block_count(&self) -> u64 {
+            self.block_count.load(Ordering::SeqCst)
+        }
+
+        fn subscribe(&self, request: attest::Message) -> Result<(ClientSession, i64), RpcStatus> {
+            Ok((ClientSession::from(request.get_channel_id()), 0))
+        }
+
+        fn subscription_query(
+            &self,
+            channel_ids: &[ClientSession],
+            start_from_user_event_id: i64,
+        ) -> SubscriptionQueryResult {
+            let mut channel_ids = channel_ids.to_vec();
+            let responses = channel_ids
+                .iter()
+                .map(|channel_id| {
+                    let mut response = attest::Message::new();
+                    response.set_channel_id(channel_id.clone().into());
+                    Ok(response)
+                })
+                .collect();
+            channel_ids.sort();
+            self.queries
+                .lock()
+                .unwrap()
+                .push((channel_ids, start_from_user_event_id));
+            Ok((
+                self.highest_processed_block_count(),
+                responses,
+                start_from_user_event_id + 1,
+            ))
+        }
+
+        fn unsubscribe(&self, channel_id: &ClientSession) {
+            self.unsubscribed.lock().unwrap().push(channel_id.clone());
+        }
+    }
+
+    /// Subscribe a session, starting from a given user event.
+    fn subscribe(
+        subscriptions: &Mutex<HashMap<ClientSession, Subscription>>,
+        channel_id: &ClientSession,
+        start_from_user_event_id: i64,
+        buffer: usize,
+    ) -> StreamingReceiver<attest::Message> {
+        let (sender, receiver) = streaming_channel(buffer);
+        subscriptions.lock().unwrap().insert(
+            channel_id.clone(),
+            Subscription {
+                sender,
+                start_from_user_event_id,
+                last_block_count: None,
+                unsent: None,
+            },
+        );
+        receiver
+    }
+
+    /// The channel ids of the responses queued for a subscriber.
+    fn received(receiver: &mut StreamingReceiver<attest::Message>) -> Vec<Vec<u8>> {
+        let mut received = Vec::new();
+        while let Ok(Some(response)) = receiver.try_next() {
+            received.push(response.unwrap().get_channel_id().to_vec());
+        }
+        received
+    }
+
+    #[test]
+    fn poll_batches_sessions_once_per_block_count() {
+        let backend = TestBackend::default();
+        let subscriptions = Mutex::new(HashMap::default());
+        let session_a = ClientSession(vec![1]);
+        let session_b = ClientSession(vec![2]);
+        let session_c = ClientSession(vec![3]);
+        let mut receiver_a = subscribe(&subscriptions, &session_a, 0, 4);
+        let mut receiver_b = subscribe(&subscriptions, &session_b, 0, 4);
+        let mut receiver_c = subscribe(&subscriptions, &session_c, 5, 4);
+
+        // Sessions starting from the same user event share an enclave call.
+        poll(&backend, &subscriptions);
+        assert_eq!(
+            *backend.queries.lock().unwrap(),
+            vec![
+                (vec![session_a.clone(), session_b.clone()], 0),
+                (vec![session_c.clone()], 5)
+            ]
+        );
+        assert_eq!(received(&mut receiver_a), vec![vec![1]]);
+        assert_eq!(received(&mut receiver_b), vec![vec![2]]);
+        assert_eq!(received(&mut receiver_c), vec![vec![3]]);
+
+        // Nothing is pushed until the highest processed block count changes.
+        poll(&backend, &subscriptions);
+        assert_eq!(backend.queries.lock().unwrap().len(), 2);
+
+        backend.block_count.store(1, Ordering::SeqCst);
+        poll(&backend, &subscriptions);
+        assert_eq!(
+            backend.queries.lock().unwrap()[2..],
+            [(vec![session_a, session_b], 1), (vec![session_c], 6)]
+        );
+        assert_eq!(received(&mut receiver_a), vec![vec![1]]);
+    }
+
+    #[test]
+    fn poll_keeps_responses_for_slow_subscribers() {
+        let backend = TestBackend::default();
+        let subscriptions = Mutex::new(HashMap::default());
+        let session = ClientSession(vec![1]);
+        let other_session = ClientSession(vec![2]);
+        // A channel has room for one message per sender beyond its buffer.
+        let mut receiver = subscribe(&subscriptions, &session, 0, 0);
+        let other_receiver = subscribe(&subscriptions, &other_session, 0, 0);
+
+        poll(&backend, &subscriptions);
+        backend.block_count.store(1, Ordering::SeqCst);
+        poll(&backend, &subscriptions);
+        assert_eq!(backend.queries.lock().unwrap().len(), 2);
+
+        // The second response did not fit in the queue, so no further response
+        // is produced until it is sent.
+        backend.block_count.store(2, Ordering::SeqCst);
+        poll(&backend, &subscriptions);
+        assert_eq!(backend.queries.lock().unwrap().len(), 2);
+        assert_eq!(received(&mut receiver), vec![vec![1]]);
+        poll(&backend, &subscriptions);
+        assert_eq!(received(&mut receiver), vec![vec![1]]);
+        poll(&backend, &subscriptions);
+        assert_eq!(backend.queries.lock().unwrap().len(), 3);
+
+        // Subscriptions whose subscriber went away are dropped.
+        drop(other_receiver);
+        drop(receiver);
+        poll(&backend, &subscriptions);
+        assert!(subscriptions.lock().unwrap().is_empty());
+        let mut unsubscribed = backend.unsubscribed.lock().unwrap().clone();
+        unsubscribed.sort();
+        assert_eq!(unsubscribed, vec![session, other_session]);
+    }
+
+    #[test]
+    fn notifier_wakes_waiters() {
+        let notifier = BlockProcessedNotifier::default();
+        let generation = notifier.generation();
+
+        // Times out when nothing happens.
+        assert_eq!(
+            notifier.wait(generation, Duration::from_millis(10)),
+            generation
+        );
+
+        let thread_notifier = notifier.clone();
+        let handle =
+            thread::spawn(move || thread_notifier.wait(generation, Duration::from_secs(60)));
+        notifier.notify();
+        assert_ne!(handle.join().unwrap(), generation);
+    }
+}
@@ -1,17 +1,21 @@
 // Copyright (c) 2018-2022 The MobileCoin Foundation
 
 use crate::{
+    verifier::{
+        BlockInconsistency, LedgerInconsistency, VerificationError, VerificationState,
+        VerificationSummary,
+    },
     ActiveMintConfig, ActiveMintConfigs, Error, Ledger, LedgerMetrics, MetadataStore,
     MetadataStoreSettings, MintConfigStore, MintTxStore, TxOutStore,
 };
 use lmdb::{
-    Database, DatabaseFlags, Environment, EnvironmentFlags, RoTransaction, RwTransaction,
+    Cursor, Database, DatabaseFlags, Environment, EnvironmentFlags, RoTransaction, RwTransaction,
     Transaction, WriteFlags,
 };
 use mc_common::{logger::global_log, HashMap};
-use mc_crypto_keys::CompressedRistrettoPublic;
+use mc_crypto_keys::{CompressedRistrettoPublic, Ed25519Public};
 use mc_transaction_core::{
-    membership_proofs::Range,
+    membership_proofs::{hash_leaf, Range},
     mint::MintTx,
     ring_signature::KeyImage,
     tx::{TxOut, TxOutMembershipElement, TxOutMembershipProof},
@@ -505,6 +509,96 @@ impl LedgerDB {
         Ok(())
     }
 
+    /// Walk every block in the ledger and re-check it against the data stored
+    /// for it, without trusting any of the derived databases. Stops at the
+    /// first inconsistency found.
+    ///
+    /// # Arguments
+    /// * `signer_keys` - The keys allowed to sign blocks. If empty, block
+    ///   signatures are only checked against the signer they name.
+    pub fn verify(
+        &self,
+        signer_keys: &[Ed25519Public],
+    ) -> Result<VerificationSummary, VerificationError> {
+        let db_transaction = self
+            .env
+            .begin_ro_txn()
+            .map_err(|err| VerificationError::Read(err.into()))?;
+
+        let num_blocks = db_transaction
+            .get(self.counts, &NUM_BLOCKS_KEY)
+            .map(key_bytes_to_u64)
+            .map_err(|err| VerificationError::Read(err.into()))?;
+
+        let mut state = VerificationState::default();
+        for block_index in 0..num_blocks {
+            self.verify_block(block_index, signer_keys, &mut state, &db_transaction)?;
+        }
+
+        // Nothing should be stored past the last block.
+        match self.get_block_impl(&db_transaction, num_blocks) {
+            Ok(_) => {
+                return Err(VerificationError::InvalidLedger(
+                    LedgerInconsistency::ExtraBlock(num_blocks),
+                ))
+            }
+            Err(Error::NotFound) => {}
+            Err(err) => return Err(VerificationError::Read(err)),
+        }
+
+        let num_tx_outs = self
+            .tx_out_store
+            .num_tx_outs(&db_transaction)
+            .map_err(VerificationError::Read)?;
+        if num_tx_outs != state.summary.num_tx_outs {
+            return Err(VerificationError::InvalidLedger(
+                LedgerInconsistency::NumTxOutsMismatch(num_tx_outs, state.summary.num_tx_outs),
+            ));
+        }
+
+        let root_merkle_hash = self
+            .tx_out_store
+            .get_root_merkle_hash(&db_transaction)
+            .map_err(VerificationError::Read)?;
+        if root_merkle_hash != state.merkle_root.root() {
+            return Err(VerificationError::InvalidLedger(
+                LedgerInconsistency::MerkleRootMismatch,
+            ));
+        }
+
+        let num_key_images = db_transaction
+            .open_ro_cursor(self.key_images)
+            .map_err(|err| VerificationError::Read(err.into()))?
+            .iter_start()
+            .count() as u64;
+        if num_key_images != state.summary.num_key_images {
+            return Err(VerificationError::InvalidLedger(
+                LedgerInconsistency::NumKeyImagesMismatch(
+                    num_key_images,
+                    state.summary.num_key_images,
+                ),
+            ));
+        }
+
+        let active_mint_configs = self
+            .mint_config_store
+            .get_active_mint_configs_map(&db_transaction)
+            .map_err(VerificationError::Read)?;
+        for token_id in active_mint_configs
+            .keys()
+            .chain(state.active_mint_configs.keys())
+        {
+            if active_mint_configs.get(token_id) != state.active_mint_configs.get(token_id) {
+                return Err(VerificationError::InvalidLedger(
+                    LedgerInconsistency::ActiveMintConfigsMismatch(*token_id),
+                ));
+            }
+        }
+
+        state.summary.num_blocks = num_blocks;
+        Ok(state.summary)
+    }
+
     /// Write a `Block`.
     fn write_block(
         &self,
@@ -852,6 +946,220 @@ impl LedgerDB {
             Err(e) => Err(Error::Lmdb(e)),
         }
     }
+
+    /// Re-check a single block, given what was derived from the blocks
+    /// before it. Used by `verify`.
+    fn verify_block(
+        &self,
+        block_index: BlockIndex,
+        signer_keys: &[Ed25519Public],
+        state: &mut VerificationState,
+        db_transaction: &impl Transaction,
+    ) -> Result<(), VerificationError> {
+        let read_error = |err: Error| VerificationError::ReadBlock(block_index, err);
+        let invalid = |inconsistency| VerificationError::InvalidBlock(block_index, inconsistency);
+        let not_found_as_none = |result: Result<u64, Error>| match result {
+            Ok(value) => Ok(Some(value)),
+            Err(Error::NotFound) => Ok(None),
+            Err(err) => Err(read_error(err)),
+        };
+
+        // Block header.
+        let block = self
+            .get_block_impl(db_transaction, block_index)
+            .map_err(read_error)?;
+        if block.index != block_index {
+            return Err(invalid(BlockInconsistency::WrongIndex(block.index)));
+        }
+        if !block.is_block_id_valid() {
+            return Err(invalid(BlockInconsistency::InvalidBlockId));
+        }
+        let (parent_id, min_version) = match &state.parent {
+            Some(parent) => (parent.id.clone(), parent.version),
+            None => (BlockID::default(), 0),
+        };
+        if block.parent_id != parent_id {
+            return Err(invalid(BlockInconsistency::InvalidParentId(
+                block.parent_id.clone(),
+            )));
+        }
+        if block.version < min_version
+            || block.version > *MAX_BLOCK_VERSION
+            || (block_index == 0 && block.version != 0)
+        {
+            return Err(invalid(BlockInconsistency::InvalidVersion(block.version)));
+        }
+
+        // Block contents.
+        let block_contents = self
+            .get_block_contents_impl(db_transaction, block_index)
+            .map_err(read_error)?;
+        if block.contents_hash != block_contents.hash() {
+            return Err(invalid(BlockInconsistency::ContentsHashMismatch));
+        }
+
+        // TxOuts.
+        let tx_outs_by_block: TxOutsByBlockValue = db_transaction
+            .get(self.tx_outs_by_block, &u64_to_key_bytes(block_index))
+            .map_err(|err| read_error(err.into()))
+            .and_then(|bytes| decode(bytes).map_err(|err| read_error(err.into())))?;
+        let first_tx_out_index = state.summary.num_tx_outs;
+        if tx_outs_by_block.first_tx_out_index != first_tx_out_index {
+            return Err(invalid(BlockInconsistency::FirstTxOutIndexMismatch(
+                tx_outs_by_block.first_tx_out_index,
+                first_tx_out_index,
+            )));
+        }
+        let cumulative_txo_count = first_tx_out_index + block_contents.outputs.len() as u64;
+        if block.cumulative_txo_count != cumulative_txo_count {
+            return Err(invalid(BlockInconsistency::CumulativeTxoCountMismatch(
+                block.cumulative_txo_count,
+                cumulative_txo_count,
+            )));
+        }
+
+        for (tx_out_index, tx_out) in (first_tx_out_index..).zip(block_contents.outputs.iter()) {
+            let tx_out_block_index = not_found_as_none(
+                db_transaction
+                    .get(
+                        self.block_number_by_tx_out_index,
+                        &u64_to_key_bytes(tx_out_index),
+                    )
+                    .map(key_bytes_to_u64)
+                    .map_err(Error::from),
+            )?;
+            if tx_out_block_index != Some(block_index) {
+                return Err(invalid(BlockInconsistency::TxOutBlockMismatch(
+                    tx_out_index,
+                    tx_out_block_index,
+                )));
+            }
+
+            let index_by_public_key = not_found_as_none(
+                self.tx_out_store
+                    .get_tx_out_index_by_public_key(&tx_out.public_key, db_transaction),
+            )?;
+            if index_by_public_key != Some(tx_out_index) {
+                return Err(invalid(BlockInconsistency::TxOutPublicKeyMismatch(
+                    tx_out_index,
+                    index_by_public_key,
+                )));
+            }
+
+            let index_by_hash = not_found_as_none(
+                self.tx_out_store
+                    .get_tx_out_index_by_hash(&tx_out.hash(), db_transaction),
+            )?;
+            if index_by_hash != Some(tx_out_index) {
+                return Err(invalid(BlockInconsistency::TxOutHashMismatch(
+                    tx_out_index,
+                    index_by_hash,
+                )));
+            }
+
+            let leaf_hash = hash_leaf(tx_out);
+            let range =
+                Range::new(tx_out_index, tx_out_index).map_err(|err| read_error(err.into()))?;
+            let stored_leaf_hash = self
+                .tx_out_store
+                .get_merkle_hash(&range, db_transaction)
+                .map_err(read_error)?;
+            if stored_leaf_hash != leaf_hash {
+                return Err(invalid(BlockInconsistency::MerkleLeafMismatch(
+                    tx_out_index,
+                )));
+            }
+            state.merkle_root.push(leaf_hash);
+        }
+        state.summary.num_tx_outs = cumulative_txo_count;
+
+        // Key images.
+        for key_image in &block_contents.key_images {
+            if let Some(spent_at) = state.key_images.insert(*key_image, block_index) {
+                return Err(invalid(BlockInconsistency::DuplicateKeyImage(
+                    *key_image, spent_at,
+                )));
+            }
+            let spent_at = self
+                .check_key_image_impl(key_image, db_transaction)
+                .map_err(read_error)?;
+            if spent_at != Some(block_index) {
+                return Err(invalid(BlockInconsistency::KeyImageBlockMismatch(
+                    *key_image, spent_at,
+                )));
+            }
+        }
+        state.summary.num_key_images += block_contents.key_images.len() as u64;
+
+        // Block signature.
+        match self.get_block_signature_impl(db_transaction, block_index) {
+            Ok(signature) => {
+                if signature.verify(&block).is_err() {
+                    return Err(invalid(BlockInconsistency::InvalidSignature));
+                }
+                if !signer_keys.is_empty() && !signer_keys.contains(signature.signer()) {
+                    return Err(invalid(BlockInconsistency::UnknownSigner(
+                        *signature.signer(),
+                    )));
+                }
+                state.summary.num_signed_blocks += 1;
+            }
+            Err(Error::NotFound) => {}
+            Err(err) => return Err(read_error(err)),
+        }
+
+        // MintTxs are checked against the configurations that were active before
+        // this block, the same way `append_block` writes them.
+        for mint_tx in &block_contents.mint_txs {
+            let nonce_block_index = self
+                .mint_tx_store
+                .check_mint_tx_nonce(&mint_tx.prefix.nonce, db_transaction)
+                .map_err(read_error)?;
+            if nonce_block_index != Some(block_index) {
+                return Err(invalid(BlockInconsistency::MintTxNonceMismatch(
+                    nonce_block_index,
+                )));
+            }
+
+            let active_mint_configs = state
+                .active_mint_configs
+                .get_mut(&TokenId::from(mint_tx.prefix.token_id))
+                .ok_or_else(|| invalid(BlockInconsistency::UnauthorizedMintTx(Error::NotFound)))?;
+            let active_mint_config = active_mint_configs
+                .get_active_mint_config_for_mint_tx(mint_tx)
+                .map_err(|err| invalid(BlockInconsistency::UnauthorizedMintTx(err)))?;
+            if let Some(config) = active_mint_configs
+                .configs
+                .iter_mut()
+                .find(|config| config.mint_config == active_mint_config.mint_config)
+            {
+                config.total_minted += mint_tx.prefix.amount;
+            }
+        }
+        state.summary.num_mint_txs += block_contents.mint_txs.len() as u64;
+
+        for validated_mint_config_tx in &block_contents.validated_mint_config_txs {
+            let mint_config_tx = &validated_mint_config_tx.mint_config_tx;
+            let nonce_block_index = self
+                .mint_config_store
+                .check_mint_config_tx_nonce(&mint_config_tx.prefix.nonce, db_transaction)
+                .map_err(read_error)?;
+            if nonce_block_index != Some(block_index) {
+                return Err(invalid(BlockInconsistency::MintConfigTxNonceMismatch(
+                    nonce_block_index,
+                )));
+            }
+
+            state.active_mint_configs.insert(
+                TokenId::from(mint_config_tx.prefix.token_id),
+                ActiveMintConfigs::from(mint_config_tx),
+            );
+        }
+        state.summary.num_mint_config_txs += block_contents.validated_mint_config_txs.len() as u64;
+
+        state.parent = Some(block);
+        Ok(())
+    }
 }
 
 // Specifies how we encode the u32/u64 chunk number in lmdb
@@ -1721,6 +2029,11 @@ mod ledger_db_test {
                 },
             ]
         );
+
+        // Replaying the mint transactions should reproduce the stored totals.
+        let summary = ledger_db.verify(&[]).unwrap();
+        assert_eq!(summary.num_blocks, 7);
+        assert_eq!(summary.num_tx_outs, 8);
     }
 
     #[test]
@@ -3109,6 +3422,170 @@ mod ledger_db_test {
         }
     }
 
+    #[test]
+    // A ledger written by `append_block` should pass verification.
+    fn test_verify_valid_ledger() {
+        let mut ledger_db = create_db();
+        assert_eq!(
+            ledger_db.verify(&[]).unwrap(),
+            VerificationSummary::default()
+        );
+
+        populate_db(&mut ledger_db, 5, 3);
+        assert_eq!(
+            ledger_db.verify(&[]).unwrap(),
+            VerificationSummary {
+                num_blocks: 5,
+                num_tx_outs: 15,
+                num_key_images: 4,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    // Verification should report the first block that does not match the data
+    // stored for it.
+    fn test_verify_reports_first_bad_block() {
+        let mut ledger_db = create_db();
+        let (blocks, _) = populate_db(&mut ledger_db, 5, 2);
+
+        // Rewrite block 3 with a wrong TxOut count, and an ID matching it.
+        let mut block = blocks[3].clone();
+        block.cumulative_txo_count += 1;
+        block.id = compute_block_id(
+            block.version,
+            &block.parent_id,
+            block.index,
+            block.cumulative_txo_count,
+            &block.root_element,
+            &block.contents_hash,
+        );
+        let mut db_transaction = ledger_db.env.begin_rw_txn().unwrap();
+        db_transaction
+            .put(
+                ledger_db.blocks,
+                &u64_to_key_bytes(3),
+                &encode(&block),
+                WriteFlags::empty(),
+            )
+            .unwrap();
+        db_transaction.commit().unwrap();
+
+        let err = ledger_db.verify(&[]).unwrap_err();
+        assert_eq!(
+            err,
+            VerificationError::InvalidBlock(
+                3,
+                BlockInconsistency::CumulativeTxoCountMismatch(9, 8)
+            )
+        );
+        assert_eq!(err.block_index(), Some(3));
+
+        // Record the key image spent in block 2 as spent in block 4.
+        let key_image = KeyImage::from(2);
+        let mut db_transaction = ledger_db.env.begin_rw_txn().unwrap();
+        db_transaction
+            .put(
+                ledger_db.key_images,
+                &key_image,
+                &4u64.to_le_bytes(),
+                WriteFlags::empty(),
+            )
+            .unwrap();
+        db_transaction.commit().unwrap();
+
+        assert_eq!(
+            ledger_db.verify(&[]),
+            Err(VerificationError::InvalidBlock(
+                2,
+                BlockInconsistency::KeyImageBlockMismatch(key_image, Some(4))
+            ))
+        );
+    }
+
+    #[test]
+    // Verification should detect TxOuts missing from the derived databases.
+    fn test_verify_detects_missing_tx_out_index() {
+        let mut ledger_db = create_db();
+        populate_db(&mut ledger_db, 4, 2);
+
+        let mut db_transaction = ledger_db.env.begin_rw_txn().unwrap();
+        db_transaction
+            .del(
+                ledger_db.block_number_by_tx_out_index,
+                &u64_to_key_bytes(5),
+                None,
+            )
+            .unwrap();
+        db_transaction.commit().unwrap();
+
+        assert_eq!(
+            ledger_db.verify(&[]),
+            Err(VerificationError::InvalidBlock(
+                2,
+                BlockInconsistency::TxOutBlockMismatch(5, None)
+            ))
+        );
+    }
+
+    #[test]
+    // Block signatures should verify, and be made by one of the given signers.
+    fn test_verify_block_signatures() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let mut ledger_db = create_db();
+        let (blocks, _) = populate_db(&mut ledger_db, 3, 1);
+
+        let signer = Ed25519Pair::from_random(&mut rng);
+        let other_signer = Ed25519Pair::from_random(&mut rng);
+
+        let mut db_transaction = ledger_db.env.begin_rw_txn().unwrap();
+        for block in &blocks {
+            let signature = BlockSignature::from_block_and_keypair(block, &signer).unwrap();
+            db_transaction
+                .put(
+                    ledger_db.block_signatures,
+                    &u64_to_key_bytes(block.index),
+                    &encode(&signature),
+                    WriteFlags::empty(),
+                )
+                .unwrap();
+        }
+        db_transaction.commit().unwrap();
+
+        let summary = ledger_db.verify(&[signer.public_key()]).unwrap();
+        assert_eq!(summary.num_signed_blocks, 3);
+
+        assert_eq!(
+            ledger_db.verify(&[other_signer.public_key()]),
+            Err(VerificationError::InvalidBlock(
+                0,
+                BlockInconsistency::UnknownSigner(signer.public_key())
+            ))
+        );
+
+        // Store the signature of block 1 for block 2.
+        let signature = BlockSignature::from_block_and_keypair(&blocks[1], &signer).unwrap();
+        let mut db_transaction = ledger_db.env.begin_rw_txn().unwrap();
+        db_transaction
+            .put(
+                ledger_db.block_signatures,
+                &u64_to_key_bytes(2),
+                &encode(&signature),
+                WriteFlags::empty(),
+            )
+            .unwrap();
+        db_transaction.commit().unwrap();
+
+        assert_eq!(
+            ledger_db.verify(&[signer.public_key()]),
+            Err(VerificationError::InvalidBlock(
+                2,
+                BlockInconsistency::InvalidSignature
+            ))
+        );
+    }
+
     // FIXME(MC-526): If these benches are not marked ignore, they get run during
     // cargo test and they are not compiled with optimizations which makes them
     // take several minutes I think they should probably be moved to
@@ -17,6 +17,7 @@ pub mod ledger_db;
 #[cfg(any(test, feature = "test_utils"))]
 pub mod test_utils;
 pub mod tx_out_store;
+pub mod verifier;
 
 pub use crate::{
     error::Error,
@@ -26,5 +27,6 @@ pub use crate::{
     mint_config_store::{ActiveMintConfig, ActiveMintConfigs, MintConfigStore},
     mint_tx_store::MintTxStore,
     tx_out_store::TxOutStore,
+    verifier::{BlockInconsistency, LedgerInconsistency, VerificationError, VerificationSummary},
 };
 pub use mc_util_lmdb::{MetadataStore, MetadataStoreError, MetadataStoreSettings};
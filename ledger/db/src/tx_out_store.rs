@@ -222,7 +222,7 @@ impl TxOutStore {
     }
 
     /// Gets the Merkle hash value for a node spanning the given range.
-    fn get_merkle_hash<T: Transaction>(
+    pub(crate) fn get_merkle_hash<T: Transaction>(
         &self,
         range: &Range,
         db_transaction: &T,
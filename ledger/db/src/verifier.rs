@@ -0,0 +1,259 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Types used by `LedgerDB::verify`, which re-checks the data stored in a
+//! ledger without trusting any of the derived databases.
+
+use crate::{ActiveMintConfigs, Error};
+use displaydoc::Display;
+use mc_common::HashMap;
+use mc_crypto_keys::Ed25519Public;
+use mc_transaction_core::{
+    membership_proofs::{hash_nodes, NIL_HASH},
+    ring_signature::KeyImage,
+    Block, BlockID, BlockIndex, TokenId,
+};
+use std::fmt;
+
+/// The reason verification of a ledger failed.
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+pub enum VerificationError {
+    /// Block {0} is invalid: {1}
+    InvalidBlock(BlockIndex, BlockInconsistency),
+
+    /// Failed reading block {0}: {1}
+    ReadBlock(BlockIndex, Error),
+
+    /// The ledger is invalid: {0}
+    InvalidLedger(LedgerInconsistency),
+
+    /// Failed reading the ledger: {0}
+    Read(Error),
+}
+
+impl VerificationError {
+    /// The index of the first bad block, if the error can be attributed to
+    /// a block.
+    pub fn block_index(&self) -> Option<BlockIndex> {
+        match self {
+            Self::InvalidBlock(block_index, _) | Self::ReadBlock(block_index, _) => {
+                Some(*block_index)
+            }
+            Self::InvalidLedger(_) | Self::Read(_) => None,
+        }
+    }
+}
+
+/// An inconsistency between a block and the data stored for it.
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+pub enum BlockInconsistency {
+    /// The block is stored with index {0}
+    WrongIndex(BlockIndex),
+
+    /// The block ID does not match the block header
+    InvalidBlockId,
+
+    /// The parent ID {0} is not the ID of the previous block
+    InvalidParentId(BlockID),
+
+    /// Block version {0} is lower than its parent's or higher than supported
+    InvalidVersion(u32),
+
+    /// The contents hash does not match the stored block contents
+    ContentsHashMismatch,
+
+    /// The first TxOut of the block has index {0}, expected {1}
+    FirstTxOutIndexMismatch(u64, u64),
+
+    /// The cumulative TxOut count is {0}, expected {1}
+    CumulativeTxoCountMismatch(u64, u64),
+
+    /// TxOut {0} is recorded in block {1:?}
+    TxOutBlockMismatch(u64, Option<BlockIndex>),
+
+    /// The public key of TxOut {0} is recorded for TxOut {1:?}
+    TxOutPublicKeyMismatch(u64, Option<u64>),
+
+    /// The hash of TxOut {0} is recorded for TxOut {1:?}
+    TxOutHashMismatch(u64, Option<u64>),
+
+    /// The Merkle leaf hash of TxOut {0} does not match the TxOut
+    MerkleLeafMismatch(u64),
+
+    /// Key image {0:?} was already spent in block {1}
+    DuplicateKeyImage(KeyImage, BlockIndex),
+
+    /// Key image {0:?} is recorded as spent in block {1:?}
+    KeyImageBlockMismatch(KeyImage, Option<BlockIndex>),
+
+    /// The block signature does not verify
+    InvalidSignature,
+
+    /// The block is signed by unknown signer {0:?}
+    UnknownSigner(Ed25519Public),
+
+    /// A mint config tx nonce is recorded in block {0:?}
+    MintConfigTxNonceMismatch(Option<BlockIndex>),
+
+    /// A mint tx nonce is recorded in block {0:?}
+    MintTxNonceMismatch(Option<BlockIndex>),
+
+    /// A mint tx is not allowed by the active mint configurations: {0}
+    UnauthorizedMintTx(Error),
+}
+
+/// An inconsistency that cannot be attributed to a single block.
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+pub enum LedgerInconsistency {
+    /// The ledger has {0} TxOuts, but its blocks contain {1}
+    NumTxOutsMismatch(u64, u64),
+
+    /// The ledger has {0} spent key images, but its blocks contain {1}
+    NumKeyImagesMismatch(u64, u64),
+
+    /// Block {0} is stored past the end of the ledger
+    ExtraBlock(BlockIndex),
+
+    /// The stored Merkle root does not match the TxOuts
+    MerkleRootMismatch,
+
+    /// The active mint configurations of token {0} do not match the blocks
+    ActiveMintConfigsMismatch(TokenId),
+}
+
+/// Statistics about a ledger that passed verification.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VerificationSummary {
+    /// Number of blocks.
+    pub num_blocks: u64,
+
+    /// Number of TxOuts.
+    pub num_tx_outs: u64,
+
+    /// Number of spent key images.
+    pub num_key_images: u64,
+
+    /// Number of blocks with a signature.
+    pub num_signed_blocks: u64,
+
+    /// Number of MintConfigTxs.
+    pub num_mint_config_txs: u64,
+
+    /// Number of MintTxs.
+    pub num_mint_txs: u64,
+}
+
+impl fmt::Display for VerificationSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} blocks ({} signed), {} TxOuts, {} key images, {} mint config txs, {} mint txs",
+            self.num_blocks,
+            self.num_signed_blocks,
+            self.num_tx_outs,
+            self.num_key_images,
+            self.num_mint_config_txs,
+            self.num_mint_txs,
+        )
+    }
+}
+
+/// What verification has derived from the blocks checked so far.
+#[derive(Default)]
+pub(crate) struct VerificationState {
+    /// The last block checked.
+    pub parent: Option<Block>,
+
+    /// The block in which each key image was spent.
+    pub key_images: HashMap<KeyImage, BlockIndex>,
+
+    /// Mint configurations, replayed from the MintConfigTxs and MintTxs.
+    pub active_mint_configs: HashMap<TokenId, ActiveMintConfigs>,
+
+    /// Merkle root of the TxOuts checked so far.
+    pub merkle_root: MerkleRootBuilder,
+
+    /// Counts of what was checked so far.
+    pub summary: VerificationSummary,
+}
+
+/// Computes the root hash of the TxOut Merkle tree maintained by
+/// `TxOutStore` from its leaf hashes, using O(log n) memory.
+#[derive(Default)]
+pub(crate) struct MerkleRootBuilder {
+    /// Roots of the complete subtrees of the leaves pushed so far, with their
+    /// heights, in decreasing order of height.
+    peaks: Vec<(u32, [u8; 32])>,
+}
+
+impl MerkleRootBuilder {
+    /// Add the next leaf.
+    pub fn push(&mut self, leaf_hash: [u8; 32]) {
+        let (mut height, mut hash) = (0, leaf_hash);
+        while let Some(&(peak_height, peak_hash)) = self.peaks.last() {
+            if peak_height != height {
+                break;
+            }
+            self.peaks.pop();
+            height += 1;
+            hash = hash_nodes(&peak_hash, &hash);
+        }
+        self.peaks.push((height, hash));
+    }
+
+    /// The root hash of the tree holding the leaves pushed so far. Subtrees
+    /// without leaves hash to `NIL_HASH`.
+    pub fn root(&self) -> [u8; 32] {
+        let mut peaks = self.peaks.iter().rev();
+        let (mut height, mut hash) = match peaks.next() {
+            Some(peak) => *peak,
+            None => return *NIL_HASH,
+        };
+        for (peak_height, peak_hash) in peaks {
+            while height < *peak_height {
+                height += 1;
+                hash = hash_nodes(&hash, &NIL_HASH);
+            }
+            height += 1;
+            hash = hash_nodes(peak_hash, &hash);
+        }
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tx_out_store::tx_out_store_tests::{get_tx_outs, init_tx_out_store};
+    use lmdb::Transaction;
+    use mc_transaction_core::membership_proofs::hash_leaf;
+
+    #[test]
+    // The Merkle root computed from the leaves should match the one maintained by
+    // TxOutStore, for complete and incomplete trees.
+    fn merkle_root_builder_matches_tx_out_store() {
+        let (tx_out_store, env) = init_tx_out_store();
+        let mut builder = MerkleRootBuilder::default();
+
+        {
+            let db_transaction = env.begin_ro_txn().unwrap();
+            assert_eq!(
+                builder.root(),
+                tx_out_store.get_root_merkle_hash(&db_transaction).unwrap()
+            );
+        }
+
+        for tx_out in get_tx_outs(33) {
+            let mut db_transaction = env.begin_rw_txn().unwrap();
+            tx_out_store.push(&tx_out, &mut db_transaction).unwrap();
+            db_transaction.commit().unwrap();
+
+            builder.push(hash_leaf(&tx_out));
+
+            let db_transaction = env.begin_ro_txn().unwrap();
+            assert_eq!(
+                builder.root(),
+                tx_out_store.get_root_merkle_hash(&db_transaction).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+#![deny(missing_docs)]
+
+//! Ledger verifier: Walk every block of a LedgerDB and re-check it against the
+//! data stored for it, reporting the first bad block.
+
+use clap::Parser;
+use mc_common::logger::{create_app_logger, o};
+use mc_crypto_keys::Ed25519Public;
+use mc_ledger_db::LedgerDB;
+use std::{fs, path::PathBuf, process::exit};
+
+/// Command line configuration
+#[derive(Clone, Debug, Parser)]
+pub struct Config {
+    /// Ledger DB path.
+    #[clap(long, parse(from_os_str), env = "MC_LEDGER_DB")]
+    pub ledger_db: PathBuf,
+
+    /// PEM files holding the public keys allowed to sign blocks. If omitted,
+    /// block signatures are only checked against the signer they name.
+    #[clap(
+        long = "signer-key",
+        parse(try_from_str = load_signer_key),
+        use_value_delimiter = true,
+        env = "MC_SIGNER_KEYS"
+    )]
+    pub signer_keys: Vec<Ed25519Public>,
+}
+
+fn load_signer_key(path: &str) -> Result<Ed25519Public, String> {
+    let bytes = fs::read(path).map_err(|err| format!("Failed reading {}: {}", path, err))?;
+    let parsed_pem =
+        pem::parse(&bytes).map_err(|err| format!("Failed parsing PEM file {}: {}", path, err))?;
+    Ed25519Public::try_from_der(&parsed_pem.contents[..])
+        .map_err(|err| format!("Failed parsing DER from PEM file {}: {}", path, err))
+}
+
+fn main() {
+    let config = Config::parse();
+
+    mc_common::setup_panic_handler();
+    let (_logger, _global_logger_guard) = create_app_logger(o!());
+
+    let ledger_db = LedgerDB::open(&config.ledger_db).unwrap_or_else(|err| {
+        eprintln!("Failed opening {:?}: {}", config.ledger_db, err);
+        exit(2);
+    });
+
+    match ledger_db.verify(&config.signer_keys) {
+        Ok(summary) => println!("Ledger is consistent: {}", summary),
+        Err(err) => {
+            eprintln!("Ledger is inconsistent: {}", err);
+            exit(1);
+        }
+    }
+}
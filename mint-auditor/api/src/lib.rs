@@ -6,6 +6,7 @@ use mc_util_uri::{Uri, UriScheme};
 
 mod autogenerated_code {
     // Expose proto data types from included third-party/external proto files.
+    pub use mc_api::external;
     pub use protobuf::well_known_types::Empty;
 
     // Needed due to how to the auto-generated code references the Empty message.
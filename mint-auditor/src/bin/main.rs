@@ -6,9 +6,14 @@ use clap::{Parser, Subcommand};
 use grpcio::{EnvBuilder, ServerBuilder};
 use mc_common::logger::{log, o, Logger};
 use mc_ledger_db::{Ledger, LedgerDB};
-use mc_mint_auditor::{counters, Error, MintAuditorDb, MintAuditorService};
+use mc_mint_auditor::{
+    counters,
+    reserve_source::{reserve_source_from_uri, ReserveSource},
+    Error, MintAuditorDb, MintAuditorService,
+};
 use mc_mint_auditor_api::MintAuditorUri;
 use mc_util_grpc::{AdminServer, BuildInfoService, ConnectionUriGrpcioServer, HealthService};
+use mc_util_lmdb::MetadataStoreError;
 use mc_util_parse::parse_duration_in_seconds;
 use mc_util_uri::AdminUri;
 use std::{cmp::Ordering, path::PathBuf, sync::Arc, thread::sleep, time::Duration};
@@ -39,6 +44,13 @@ pub enum Command {
         /// Optional admin service listening URI.
         #[clap(long, env = "MC_ADMIN_LISTEN_URI")]
         admin_listen_uri: Option<AdminUri>,
+
+        /// Optional source of the reserves backing minted tokens, checked
+        /// once the ledger tip is synced. Either a path or `file://` URL of a
+        /// JSON file, or an `http(s)://` URL serving JSON, mapping token ids
+        /// to reserve amounts.
+        #[clap(long, env = "MC_RESERVE_SOURCE")]
+        reserve_source: Option<String>,
     },
 
     /// Get the audit data for a specific block, optionally in JSON format
@@ -82,6 +94,7 @@ fn main() {
             poll_interval,
             listen_uri,
             admin_listen_uri,
+            reserve_source,
         } => {
             cmd_scan_ledger(
                 ledger_db,
@@ -89,6 +102,7 @@ fn main() {
                 poll_interval,
                 listen_uri,
                 admin_listen_uri,
+                reserve_source,
                 logger,
             );
         }
@@ -110,11 +124,22 @@ fn cmd_scan_ledger(
     poll_interval: Duration,
     listen_uri: Option<MintAuditorUri>,
     admin_listen_uri: Option<AdminUri>,
+    reserve_source: Option<String>,
     logger: Logger,
 ) {
     let ledger_db = LedgerDB::open(&ledger_db_path).expect("Could not open ledger DB");
-    let mint_auditor_db = MintAuditorDb::create_or_open(&mint_auditor_db_path, logger.clone())
-        .expect("Could not open mint auditor DB");
+    let reserve_source = reserve_source.map(|uri| {
+        log::info!(logger, "Checking reserves using {}", uri);
+        reserve_source_from_uri(&uri).expect("Could not create reserve source")
+    });
+    let mint_auditor_db = match MintAuditorDb::create_or_open(&mint_auditor_db_path, logger.clone())
+    {
+        Ok(mint_auditor_db) => mint_auditor_db,
+        Err(Error::MetadataStore(MetadataStoreError::VersionIncompatible(old, new))) => {
+            panic!("Mint auditor DB {:?} is at version {}, which is incompatible with {}. Delete it and run scan-ledger again to resync it from the ledger.", mint_auditor_db_path, old, new);
+        }
+        Err(err) => panic!("Could not open mint auditor DB: {}", err),
+    };
 
     let _api_server = listen_uri.map(|listen_uri| {
         // Create RPC services.
@@ -158,7 +183,13 @@ fn cmd_scan_ledger(
     });
 
     loop {
-        sync_loop(&mint_auditor_db, &ledger_db, &logger).expect("sync_loop failed");
+        sync_loop(
+            &mint_auditor_db,
+            &ledger_db,
+            reserve_source.as_deref(),
+            &logger,
+        )
+        .expect("sync_loop failed");
         sleep(poll_interval);
     }
 }
@@ -200,9 +231,14 @@ fn cmd_get_block_audit_data(
 
 /// Synchronizes the mint auditor database with the ledger database.
 /// Will run until all blocks in the ledger database have been synced.
+/// If a reserve source is provided, the reserves are checked once the ledger
+/// tip is reached, unless they were already checked at that block. The reserve
+/// source only reports current reserves, so comparing them against the
+/// balances of older blocks while catching up would raise false alerts.
 fn sync_loop(
     mint_auditor_db: &MintAuditorDb,
     ledger_db: &LedgerDB,
+    reserve_source: Option<&dyn ReserveSource>,
     logger: &Logger,
 ) -> Result<(), Error> {
     loop {
@@ -216,6 +252,17 @@ fn sync_loop(
         match num_blocks_synced.cmp(&num_blocks_in_ledger) {
             Ordering::Equal => {
                 // Nothing more to sync.
+                if let (Some(reserve_source), Some(tip_block_index)) =
+                    (reserve_source, last_synced_block_index)
+                {
+                    let last_checked_block_index = mint_auditor_db
+                        .get_last_reserve_check()?
+                        .map(|reserve_check| reserve_check.block_index);
+                    if last_checked_block_index != Some(tip_block_index) {
+                        check_reserves(mint_auditor_db, reserve_source, tip_block_index, logger)?;
+                        update_counters(mint_auditor_db)?;
+                    }
+                }
                 break;
             }
             Ordering::Greater => {
@@ -235,6 +282,33 @@ fn sync_loop(
     Ok(())
 }
 
+// Compare the balances at a given block against the reserves.
+fn check_reserves(
+    mint_auditor_db: &MintAuditorDb,
+    reserve_source: &dyn ReserveSource,
+    block_index: u64,
+    logger: &Logger,
+) -> Result<(), Error> {
+    match reserve_source.get_reserves() {
+        Ok(reserve_map) => {
+            let reserve_check = mint_auditor_db.check_reserves(block_index, &reserve_map)?;
+            counters::NUM_TOKENS_WITH_RESERVE_DISCREPANCY
+                .set(reserve_check.discrepancies.len() as i64);
+        }
+        Err(err) => {
+            // Failing to reach the reserve source should not stop the audit.
+            log::error!(
+                logger,
+                "Block {}: Failed getting reserves: {}",
+                block_index,
+                err
+            );
+            counters::NUM_RESERVE_SOURCE_ERRORS.inc();
+        }
+    }
+    Ok(())
+}
+
 // Update prometheus counters.
 fn update_counters(mint_auditor_db: &MintAuditorDb) -> Result<(), Error> {
     let counters = mint_auditor_db.get_counters()?;
@@ -243,6 +317,7 @@ fn update_counters(mint_auditor_db: &MintAuditorDb) -> Result<(), Error> {
     counters::NUM_BURNS_EXCEEDING_BALANCE.set(counters.num_burns_exceeding_balance as i64);
     counters::NUM_MINT_TXS_WITHOUT_MATCHING_MINT_CONFIG
         .set(counters.num_mint_txs_without_matching_mint_config as i64);
+    counters::NUM_RESERVE_DISCREPANCIES.set(counters.num_reserve_discrepancies as i64);
 
     Ok(())
 }
@@ -0,0 +1,90 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Convert to/from mc_mint_auditor_api::BurnEvent.
+
+use crate::BurnEvent;
+use mc_api::ConversionError;
+use mc_crypto_keys::CompressedRistrettoPublic;
+
+/// Convert BurnEvent --> mc_mint_auditor_api::BurnEvent
+impl From<&BurnEvent> for mc_mint_auditor_api::BurnEvent {
+    fn from(src: &BurnEvent) -> Self {
+        let mut dst = mc_mint_auditor_api::BurnEvent::new();
+        dst.set_block_index(src.block_index);
+        dst.set_token_id(src.token_id);
+        dst.set_amount(src.amount);
+        dst.set_public_key((&src.public_key).into());
+        dst.set_burn_redemption_memo_data(src.burn_redemption_memo_data.clone());
+        dst
+    }
+}
+
+/// Convert mc_mint_auditor_api::BurnEvent --> BurnEvent
+impl TryFrom<&mc_mint_auditor_api::BurnEvent> for BurnEvent {
+    type Error = ConversionError;
+
+    fn try_from(src: &mc_mint_auditor_api::BurnEvent) -> Result<Self, Self::Error> {
+        Ok(Self {
+            block_index: src.get_block_index(),
+            token_id: src.get_token_id(),
+            amount: src.get_amount(),
+            public_key: CompressedRistrettoPublic::try_from(src.get_public_key())?,
+            burn_redemption_memo_data: src.get_burn_redemption_memo_data().to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_crypto_keys::RistrettoPublic;
+    use mc_util_from_random::FromRandom;
+    use mc_util_serial::{decode, encode};
+    use protobuf::Message;
+    use rand_core::SeedableRng;
+    use rand_hc::Hc128Rng;
+
+    #[test]
+    // BurnEvent --> mc_mint_auditor_api::BurnEvent --> BurnEvent
+    // should be the identity function.
+    fn test_convert_burn_event() {
+        let mut rng = Hc128Rng::from_seed([1u8; 32]);
+        let source = BurnEvent {
+            block_index: 10,
+            token_id: 5,
+            amount: 1000,
+            public_key: CompressedRistrettoPublic::from(&RistrettoPublic::from_random(&mut rng)),
+            burn_redemption_memo_data: vec![7; 64],
+        };
+
+        // decode(encode(source)) should be the identity function.
+        {
+            let bytes = encode(&source);
+            let recovered = decode(&bytes).unwrap();
+            assert_eq!(source, recovered);
+        }
+
+        // Converting should be the identity function.
+        {
+            let external = mc_mint_auditor_api::BurnEvent::from(&source);
+            let recovered = BurnEvent::try_from(&external).unwrap();
+            assert_eq!(source, recovered);
+        }
+
+        // Encoding with prost, decoding with protobuf should be the identity
+        // function.
+        {
+            let bytes = encode(&source);
+            let recovered = mc_mint_auditor_api::BurnEvent::parse_from_bytes(&bytes).unwrap();
+            assert_eq!(recovered, mc_mint_auditor_api::BurnEvent::from(&source));
+        }
+
+        // Encoding with protobuf, decoding with prost should be the identity function.
+        {
+            let external = mc_mint_auditor_api::BurnEvent::from(&source);
+            let bytes = external.write_to_bytes().unwrap();
+            let recovered: BurnEvent = decode(&bytes).unwrap();
+            assert_eq!(source, recovered);
+        }
+    }
+}
@@ -13,6 +13,7 @@ impl From<&Counters> for mc_mint_auditor_api::Counters {
         dst.set_num_mint_txs_without_matching_mint_config(
             src.num_mint_txs_without_matching_mint_config,
         );
+        dst.set_num_reserve_discrepancies(src.num_reserve_discrepancies);
         dst
     }
 }
@@ -25,6 +26,7 @@ impl From<&mc_mint_auditor_api::Counters> for Counters {
             num_burns_exceeding_balance: src.get_num_burns_exceeding_balance(),
             num_mint_txs_without_matching_mint_config: src
                 .get_num_mint_txs_without_matching_mint_config(),
+            num_reserve_discrepancies: src.get_num_reserve_discrepancies(),
         }
     }
 }
@@ -43,6 +45,7 @@ mod tests {
             num_blocks_synced: 10,
             num_burns_exceeding_balance: 20,
             num_mint_txs_without_matching_mint_config: 30,
+            num_reserve_discrepancies: 40,
         };
 
         // decode(encode(source)) should be the identity function.
@@ -0,0 +1,120 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Convert to/from mc_mint_auditor_api::MintEvent.
+
+use crate::MintEvent;
+use mc_api::ConversionError;
+use mc_crypto_keys::Ed25519Public;
+use mc_transaction_core::mint::MintConfig;
+
+/// Convert MintEvent --> mc_mint_auditor_api::MintEvent
+impl From<&MintEvent> for mc_mint_auditor_api::MintEvent {
+    fn from(src: &MintEvent) -> Self {
+        let mut dst = mc_mint_auditor_api::MintEvent::new();
+        dst.set_block_index(src.block_index);
+        dst.set_token_id(src.token_id);
+        dst.set_amount(src.amount);
+        dst.set_nonce(src.nonce.clone());
+        if let Some(mint_config) = src.mint_config.as_ref() {
+            dst.set_mint_config(mint_config.into());
+        }
+        dst.set_signers(src.signers.iter().map(Into::into).collect());
+        dst
+    }
+}
+
+/// Convert mc_mint_auditor_api::MintEvent --> MintEvent
+impl TryFrom<&mc_mint_auditor_api::MintEvent> for MintEvent {
+    type Error = ConversionError;
+
+    fn try_from(src: &mc_mint_auditor_api::MintEvent) -> Result<Self, Self::Error> {
+        let mint_config = if src.has_mint_config() {
+            Some(MintConfig::try_from(src.get_mint_config())?)
+        } else {
+            None
+        };
+        let signers = src
+            .get_signers()
+            .iter()
+            .map(Ed25519Public::try_from)
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            block_index: src.get_block_index(),
+            token_id: src.get_token_id(),
+            amount: src.get_amount(),
+            nonce: src.get_nonce().to_vec(),
+            mint_config,
+            signers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_transaction_core::TokenId;
+    use mc_transaction_core_test_utils::create_mint_config_tx_and_signers;
+    use mc_util_serial::{decode, encode};
+    use protobuf::Message;
+    use rand_core::SeedableRng;
+    use rand_hc::Hc128Rng;
+
+    #[test]
+    // MintEvent --> mc_mint_auditor_api::MintEvent --> MintEvent
+    // should be the identity function.
+    fn test_convert_mint_event() {
+        let mut rng = Hc128Rng::from_seed([1u8; 32]);
+        let (mint_config_tx, _signers) =
+            create_mint_config_tx_and_signers(TokenId::from(5), &mut rng);
+        let mint_config = mint_config_tx.prefix.configs[0].clone();
+
+        for source in [
+            MintEvent {
+                block_index: 10,
+                token_id: 5,
+                amount: 1000,
+                nonce: vec![1, 2, 3, 4],
+                signers: mint_config.signer_set.signers().to_vec(),
+                mint_config: Some(mint_config),
+            },
+            MintEvent {
+                block_index: 11,
+                token_id: 6,
+                amount: 2000,
+                nonce: vec![5, 6, 7, 8],
+                mint_config: None,
+                signers: vec![],
+            },
+        ] {
+            // decode(encode(source)) should be the identity function.
+            {
+                let bytes = encode(&source);
+                let recovered = decode(&bytes).unwrap();
+                assert_eq!(source, recovered);
+            }
+
+            // Converting should be the identity function.
+            {
+                let external = mc_mint_auditor_api::MintEvent::from(&source);
+                let recovered = MintEvent::try_from(&external).unwrap();
+                assert_eq!(source, recovered);
+            }
+
+            // Encoding with prost, decoding with protobuf should be the identity
+            // function.
+            {
+                let bytes = encode(&source);
+                let recovered = mc_mint_auditor_api::MintEvent::parse_from_bytes(&bytes).unwrap();
+                assert_eq!(recovered, mc_mint_auditor_api::MintEvent::from(&source));
+            }
+
+            // Encoding with protobuf, decoding with prost should be the identity function.
+            {
+                let external = mc_mint_auditor_api::MintEvent::from(&source);
+                let bytes = external.write_to_bytes().unwrap();
+                let recovered: MintEvent = decode(&bytes).unwrap();
+                assert_eq!(source, recovered);
+            }
+        }
+    }
+}
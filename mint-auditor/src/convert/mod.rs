@@ -3,4 +3,7 @@
 //! Convertion trait implementations betweens Prost and Protobuf.
 
 mod block_audit_data;
+mod burn_event;
 mod counters;
+mod mint_event;
+mod reserve_check;
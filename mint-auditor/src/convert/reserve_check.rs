@@ -0,0 +1,106 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Convert to/from mc_mint_auditor_api::ReserveCheck and
+//! mc_mint_auditor_api::ReserveDiscrepancy.
+
+use crate::{ReserveCheck, ReserveDiscrepancy};
+
+/// Convert ReserveDiscrepancy --> mc_mint_auditor_api::ReserveDiscrepancy
+impl From<&ReserveDiscrepancy> for mc_mint_auditor_api::ReserveDiscrepancy {
+    fn from(src: &ReserveDiscrepancy) -> Self {
+        let mut dst = mc_mint_auditor_api::ReserveDiscrepancy::new();
+        dst.set_block_index(src.block_index);
+        dst.set_token_id(src.token_id);
+        dst.set_balance(src.balance);
+        dst.set_reserve(src.reserve);
+        dst
+    }
+}
+
+/// Convert mc_mint_auditor_api::ReserveDiscrepancy --> ReserveDiscrepancy
+impl From<&mc_mint_auditor_api::ReserveDiscrepancy> for ReserveDiscrepancy {
+    fn from(src: &mc_mint_auditor_api::ReserveDiscrepancy) -> Self {
+        Self {
+            block_index: src.get_block_index(),
+            token_id: src.get_token_id(),
+            balance: src.get_balance(),
+            reserve: src.get_reserve(),
+        }
+    }
+}
+
+/// Convert ReserveCheck --> mc_mint_auditor_api::ReserveCheck
+impl From<&ReserveCheck> for mc_mint_auditor_api::ReserveCheck {
+    fn from(src: &ReserveCheck) -> Self {
+        let mut dst = mc_mint_auditor_api::ReserveCheck::new();
+        dst.set_block_index(src.block_index);
+        dst.set_reserve_map(src.reserve_map.clone().into_iter().collect());
+        dst.set_discrepancies(src.discrepancies.iter().map(Into::into).collect());
+        dst
+    }
+}
+
+/// Convert mc_mint_auditor_api::ReserveCheck --> ReserveCheck
+impl From<&mc_mint_auditor_api::ReserveCheck> for ReserveCheck {
+    fn from(src: &mc_mint_auditor_api::ReserveCheck) -> Self {
+        Self {
+            block_index: src.get_block_index(),
+            reserve_map: src.get_reserve_map().clone().into_iter().collect(),
+            discrepancies: src.get_discrepancies().iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_util_serial::{decode, encode};
+    use protobuf::Message;
+    use std::{collections::BTreeMap, iter::FromIterator};
+
+    #[test]
+    // ReserveCheck --> mc_mint_auditor_api::ReserveCheck --> ReserveCheck
+    // should be the identity function.
+    fn test_convert_reserve_check() {
+        let source = ReserveCheck {
+            block_index: 10,
+            reserve_map: BTreeMap::from_iter([(1, 2), (3, 4)]),
+            discrepancies: vec![ReserveDiscrepancy {
+                block_index: 10,
+                token_id: 3,
+                balance: 5,
+                reserve: 4,
+            }],
+        };
+
+        // decode(encode(source)) should be the identity function.
+        {
+            let bytes = encode(&source);
+            let recovered = decode(&bytes).unwrap();
+            assert_eq!(source, recovered);
+        }
+
+        // Converting should be the identity function.
+        {
+            let external = mc_mint_auditor_api::ReserveCheck::from(&source);
+            let recovered = ReserveCheck::from(&external);
+            assert_eq!(source, recovered);
+        }
+
+        // Encoding with prost, decoding with protobuf should be the identity
+        // function.
+        {
+            let bytes = encode(&source);
+            let recovered = mc_mint_auditor_api::ReserveCheck::parse_from_bytes(&bytes).unwrap();
+            assert_eq!(recovered, mc_mint_auditor_api::ReserveCheck::from(&source));
+        }
+
+        // Encoding with protobuf, decoding with prost should be the identity function.
+        {
+            let external = mc_mint_auditor_api::ReserveCheck::from(&source);
+            let bytes = external.write_to_bytes().unwrap();
+            let recovered: ReserveCheck = decode(&bytes).unwrap();
+            assert_eq!(source, recovered);
+        }
+    }
+}
@@ -16,4 +16,13 @@ lazy_static::lazy_static! {
 
     /// Number of MintTxs without a matching MintConfig.
     pub static ref NUM_MINT_TXS_WITHOUT_MATCHING_MINT_CONFIG: IntGauge = OP_COUNTERS.gauge("num_mint_txs_without_matching_mint_config");
+
+    /// Number of mismatches between a calculated balance and its reserve.
+    pub static ref NUM_RESERVE_DISCREPANCIES: IntGauge = OP_COUNTERS.gauge("num_reserve_discrepancies");
+
+    /// Number of tokens whose balance did not match their reserve in the last reserve check.
+    pub static ref NUM_TOKENS_WITH_RESERVE_DISCREPANCY: IntGauge = OP_COUNTERS.gauge("num_tokens_with_reserve_discrepancy");
+
+    /// Number of failures to get reserves from the reserve source.
+    pub static ref NUM_RESERVE_SOURCE_ERRORS: IntGauge = OP_COUNTERS.gauge("num_reserve_source_errors");
 }
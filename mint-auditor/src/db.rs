@@ -4,22 +4,29 @@
 
 use crate::Error;
 use lmdb::{
-    Database, DatabaseFlags, Environment, EnvironmentFlags, RwTransaction, Transaction, WriteFlags,
+    Cursor, Database, DatabaseFlags, Environment, EnvironmentFlags, RwTransaction, Transaction,
+    WriteFlags,
 };
 use mc_account_keys::burn_address_view_private;
 use mc_common::logger::{log, Logger};
-use mc_ledger_db::{u64_to_key_bytes, Error as LedgerDbError, MintConfigStore};
-use mc_transaction_core::{Block, BlockContents, BlockIndex};
-use mc_util_lmdb::{MetadataStore, MetadataStoreSettings};
+use mc_crypto_keys::{CompressedRistrettoPublic, Ed25519Public};
+use mc_ledger_db::{key_bytes_to_u64, u64_to_key_bytes, Error as LedgerDbError, MintConfigStore};
+use mc_transaction_core::{mint::MintConfig, Block, BlockContents, BlockIndex};
+use mc_transaction_std::MemoType;
+use mc_util_lmdb::{MetadataStore, MetadataStoreError, MetadataStoreSettings};
 use mc_util_serial::{decode, encode, Message};
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, path::Path, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+    sync::Arc,
+};
 
 /// Max LMDB file size.
 const MAX_LMDB_FILE_SIZE: usize = 1_099_511_627_776; // 1 TB
 
 /// Number of LMDB databases.
-const NUM_LMDB_DATABASES: u32 = 6;
+const NUM_LMDB_DATABASES: u32 = 9;
 
 /// Metadata store settings that are used for version control.
 #[derive(Clone, Default, Debug)]
@@ -30,7 +37,7 @@ impl MetadataStoreSettings for WatcherDbMetadataStoreSettings {
     // db opening for any incompatibilities, and either refuse to open or
     // perform a migration.
     #[allow(clippy::inconsistent_digit_grouping)]
-    const LATEST_VERSION: u64 = 2022_03_28;
+    const LATEST_VERSION: u64 = 2022_07_04;
 
     /// The current crate version that manages the database.
     const CRATE_VERSION: &'static str = env!("CARGO_PKG_VERSION");
@@ -43,9 +50,14 @@ impl MetadataStoreSettings for WatcherDbMetadataStoreSettings {
 pub const KEY_VAL_DB_NAME: &str = "mint_auditor_db:key_val";
 pub const MINT_AUDIT_DATA_BY_BLOCK_INDEX_DB_NAME: &str =
     "mint_auditor_db:mint_audit_data_by_block_index";
+pub const RESERVE_DISCREPANCIES_BY_BLOCK_INDEX_DB_NAME: &str =
+    "mint_auditor_db:reserve_discrepancies_by_block_index";
+pub const MINT_EVENTS_BY_BLOCK_INDEX_DB_NAME: &str = "mint_auditor_db:mint_events_by_block_index";
+pub const BURN_EVENTS_BY_BLOCK_INDEX_DB_NAME: &str = "mint_auditor_db:burn_events_by_block_index";
 
 /// Keys used by the `key_val` database.
 pub const COUNTERS_KEY: &str = "counters";
+pub const LAST_RESERVE_CHECK_KEY: &str = "last_reserve_check";
 
 /// Mint audit data that we store per block.
 #[derive(Deserialize, Eq, Message, PartialEq, Serialize)]
@@ -70,6 +82,101 @@ pub struct Counters {
     /// Number of `MintTx`s that did not match an active mint config.
     #[prost(uint64, tag = 3)]
     pub num_mint_txs_without_matching_mint_config: u64,
+
+    /// Number of times the calculated balance of a token did not match its
+    /// reserve.
+    #[prost(uint64, tag = 4)]
+    pub num_reserve_discrepancies: u64,
+}
+
+/// A mismatch between the calculated balance of a token and the reserve
+/// backing it.
+#[derive(Deserialize, Eq, Message, PartialEq, Serialize)]
+pub struct ReserveDiscrepancy {
+    /// The block index the balance was calculated at.
+    #[prost(uint64, tag = 1)]
+    pub block_index: u64,
+
+    /// The token id.
+    #[prost(uint64, tag = 2)]
+    pub token_id: u64,
+
+    /// The calculated balance in circulation.
+    #[prost(uint64, tag = 3)]
+    pub balance: u64,
+
+    /// The amount held in reserve.
+    #[prost(uint64, tag = 4)]
+    pub reserve: u64,
+}
+
+/// The outcome of comparing the calculated balances against the reserves.
+#[derive(Deserialize, Eq, Message, PartialEq, Serialize)]
+pub struct ReserveCheck {
+    /// The block index the balances were calculated at.
+    #[prost(uint64, tag = 1)]
+    pub block_index: u64,
+
+    /// A map of token id -> amount held in reserve.
+    #[prost(btree_map = "uint64, uint64", tag = 2)]
+    pub reserve_map: BTreeMap<u64, u64>,
+
+    /// The tokens whose balance did not match their reserve.
+    #[prost(message, repeated, tag = 3)]
+    pub discrepancies: Vec<ReserveDiscrepancy>,
+}
+
+/// A single `MintTx` that was included in the ledger.
+#[derive(Deserialize, Eq, Message, PartialEq, Serialize)]
+pub struct MintEvent {
+    /// The block index the mint was included in.
+    #[prost(uint64, tag = 1)]
+    pub block_index: u64,
+
+    /// The token id that was minted.
+    #[prost(uint64, tag = 2)]
+    pub token_id: u64,
+
+    /// The amount that was minted.
+    #[prost(uint64, tag = 3)]
+    pub amount: u64,
+
+    /// The nonce of the `MintTx`.
+    #[prost(bytes, tag = 4)]
+    pub nonce: Vec<u8>,
+
+    /// The active mint configuration the `MintTx` matched, if any.
+    #[prost(message, optional, tag = 5)]
+    pub mint_config: Option<MintConfig>,
+
+    /// The signers of the matched mint configuration that signed the `MintTx`.
+    #[prost(message, repeated, tag = 6)]
+    pub signers: Vec<Ed25519Public>,
+}
+
+/// A single TxOut sent to the burn address that was included in the ledger.
+#[derive(Deserialize, Eq, Message, PartialEq, Serialize)]
+pub struct BurnEvent {
+    /// The block index the burn was included in.
+    #[prost(uint64, tag = 1)]
+    pub block_index: u64,
+
+    /// The token id that was burned.
+    #[prost(uint64, tag = 2)]
+    pub token_id: u64,
+
+    /// The amount that was burned.
+    #[prost(uint64, tag = 3)]
+    pub amount: u64,
+
+    /// The public key of the burn TxOut.
+    #[prost(message, required, tag = 4)]
+    pub public_key: CompressedRistrettoPublic,
+
+    /// The data of the burn redemption memo, or empty if the TxOut did not
+    /// have one.
+    #[prost(bytes, tag = 5)]
+    pub burn_redemption_memo_data: Vec<u8>,
 }
 
 /// Mint Auditor Database.
@@ -80,11 +187,21 @@ pub struct MintAuditorDb {
 
     /// General-purpose key-value store.
     /// * `COUNTERS`: A serialized `Counters` object.
+    /// * `LAST_RESERVE_CHECK`: A serialized `ReserveCheck` object.
     key_val: Database,
 
     /// block index -> BlockAuditData database.
     mint_audit_data_by_block_index: Database,
 
+    /// block index -> ReserveDiscrepancy database (duplicate keys allowed).
+    reserve_discrepancies_by_block_index: Database,
+
+    /// (block index, event index) -> MintEvent database.
+    mint_events_by_block_index: Database,
+
+    /// (block index, event index) -> BurnEvent database.
+    burn_events_by_block_index: Database,
+
     /// Mint config store.
     mint_config_store: MintConfigStore,
 
@@ -115,15 +232,26 @@ impl MintAuditorDb {
         );
         db_txn.commit()?;
 
+        // The per-block records of a database created by an older version cover only
+        // the blocks synced since it was upgraded, so it must be resynced from scratch.
+        version.is_compatible_with_latest()?;
+
         let key_val = env.open_db(Some(KEY_VAL_DB_NAME))?;
         let mint_audit_data_by_block_index =
             env.open_db(Some(MINT_AUDIT_DATA_BY_BLOCK_INDEX_DB_NAME))?;
+        let reserve_discrepancies_by_block_index =
+            env.open_db(Some(RESERVE_DISCREPANCIES_BY_BLOCK_INDEX_DB_NAME))?;
+        let mint_events_by_block_index = env.open_db(Some(MINT_EVENTS_BY_BLOCK_INDEX_DB_NAME))?;
+        let burn_events_by_block_index = env.open_db(Some(BURN_EVENTS_BY_BLOCK_INDEX_DB_NAME))?;
         let mint_config_store = MintConfigStore::new(&env)?;
 
         Ok(Self {
             env,
             key_val,
             mint_audit_data_by_block_index,
+            reserve_discrepancies_by_block_index,
+            mint_events_by_block_index,
+            burn_events_by_block_index,
             mint_config_store,
             logger,
         })
@@ -147,6 +275,20 @@ impl MintAuditorDb {
             Some(MINT_AUDIT_DATA_BY_BLOCK_INDEX_DB_NAME),
             DatabaseFlags::DUP_SORT,
         )?;
+        env.create_db(
+            Some(RESERVE_DISCREPANCIES_BY_BLOCK_INDEX_DB_NAME),
+            DatabaseFlags::DUP_SORT,
+        )?;
+        // Events can exceed the maximum size of a DUP_SORT value, so each of them gets
+        // its own key.
+        env.create_db(
+            Some(MINT_EVENTS_BY_BLOCK_INDEX_DB_NAME),
+            DatabaseFlags::empty(),
+        )?;
+        env.create_db(
+            Some(BURN_EVENTS_BY_BLOCK_INDEX_DB_NAME),
+            DatabaseFlags::empty(),
+        )?;
         MintConfigStore::create(&env)?;
         Ok(())
     }
@@ -158,11 +300,18 @@ impl MintAuditorDb {
         }
 
         // Attempt to open the database and see if it has anything in it.
-        if let Ok(db) = Self::open(path, logger.clone()) {
-            if db.last_synced_block_index()?.is_some() {
-                // Successfully opened a database that has something in it.
-                return Ok(db);
+        match Self::open(path, logger.clone()) {
+            Ok(db) => {
+                if db.last_synced_block_index()?.is_some() {
+                    // Successfully opened a database that has something in it.
+                    return Ok(db);
+                }
             }
+            // Never overwrite the version of an existing database.
+            Err(err @ Error::MetadataStore(MetadataStoreError::VersionIncompatible(_, _))) => {
+                return Err(err);
+            }
+            Err(_) => {}
         }
 
         // DB doesn't exist or is empty.
@@ -217,6 +366,9 @@ impl MintAuditorDb {
             self.get_block_audit_data_impl(block_index - 1, &db_txn)?
         };
 
+        let mut mint_events = Vec::new();
+        let mut burn_events = Vec::new();
+
         // Count mints.
         for mint_tx in &block_contents.mint_txs {
             let balance = block_audit_data
@@ -234,12 +386,29 @@ impl MintAuditorDb {
                 balance,
             );
 
+            let mut mint_event = MintEvent {
+                block_index,
+                token_id: mint_tx.prefix.token_id,
+                amount: mint_tx.prefix.amount,
+                nonce: mint_tx.prefix.nonce.clone(),
+                mint_config: None,
+                signers: Vec::new(),
+            };
+
             // See if this mint matches an active mint configuration.
             match self
                 .mint_config_store
                 .get_active_mint_config_for_mint_tx(mint_tx, &db_txn)
             {
                 Ok(active_mint_config) => {
+                    // Record which signers of the matched configuration signed the mint.
+                    mint_event.signers = active_mint_config
+                        .mint_config
+                        .signer_set
+                        .verify(&mint_tx.prefix.hash(), &mint_tx.signature)
+                        .unwrap_or_default();
+                    mint_event.mint_config = Some(active_mint_config.mint_config.clone());
+
                     // Got a match, which is what we were hoping would happen.
                     // Update the total amount this configuration has minted.
                     let total_minted = active_mint_config.total_minted + mint_tx.prefix.amount;
@@ -265,11 +434,14 @@ impl MintAuditorDb {
                     return Err(err.into());
                 }
             }
+
+            mint_events.push(mint_event);
         }
 
         // Count burns.
         for tx_out in &block_contents.outputs {
-            if let Ok((amount, _)) = tx_out.view_key_match(&burn_address_view_private()) {
+            if let Ok((amount, shared_secret)) = tx_out.view_key_match(&burn_address_view_private())
+            {
                 let balance = block_audit_data
                     .balance_map
                     .entry(*amount.token_id)
@@ -297,6 +469,19 @@ impl MintAuditorDb {
                         balance,
                     );
                 }
+
+                let burn_redemption_memo_data =
+                    match MemoType::try_from(&tx_out.decrypt_memo(&shared_secret)) {
+                        Ok(MemoType::BurnRedemption(memo)) => memo.memo_data().to_vec(),
+                        _ => Vec::new(),
+                    };
+                burn_events.push(BurnEvent {
+                    block_index,
+                    token_id: *amount.token_id,
+                    amount: amount.value,
+                    public_key: tx_out.public_key,
+                    burn_redemption_memo_data,
+                });
             }
         }
 
@@ -308,6 +493,24 @@ impl MintAuditorDb {
             WriteFlags::NO_OVERWRITE,
         )?;
 
+        for (event_index, mint_event) in mint_events.iter().enumerate() {
+            db_txn.put(
+                self.mint_events_by_block_index,
+                &event_key_bytes(block_index, event_index as u64),
+                &encode(mint_event),
+                WriteFlags::NO_OVERWRITE,
+            )?;
+        }
+
+        for (event_index, burn_event) in burn_events.iter().enumerate() {
+            db_txn.put(
+                self.burn_events_by_block_index,
+                &event_key_bytes(block_index, event_index as u64),
+                &encode(burn_event),
+                WriteFlags::NO_OVERWRITE,
+            )?;
+        }
+
         self.mint_config_store.write_validated_mint_config_txs(
             block_index,
             &block_contents.validated_mint_config_txs,
@@ -323,6 +526,180 @@ impl MintAuditorDb {
         Ok(block_audit_data)
     }
 
+    /// Compare the calculated balances at a given block against the reserves
+    /// backing each token, and record any discrepancies.
+    ///
+    /// Tokens missing from either side are treated as having a zero balance or
+    /// reserve.
+    pub fn check_reserves(
+        &self,
+        block_index: BlockIndex,
+        reserve_map: &BTreeMap<u64, u64>,
+    ) -> Result<ReserveCheck, Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        let block_audit_data = self.get_block_audit_data_impl(block_index, &db_txn)?;
+
+        let token_ids: BTreeSet<u64> = block_audit_data
+            .balance_map
+            .keys()
+            .chain(reserve_map.keys())
+            .cloned()
+            .collect();
+
+        let mut discrepancies = Vec::new();
+        for token_id in token_ids {
+            let balance = block_audit_data
+                .balance_map
+                .get(&token_id)
+                .cloned()
+                .unwrap_or(0);
+            let reserve = reserve_map.get(&token_id).cloned().unwrap_or(0);
+            if balance != reserve {
+                log::crit!(
+                    self.logger,
+                    "Block {}: Balance of token id {} is {} but the reserve is {}",
+                    block_index,
+                    token_id,
+                    balance,
+                    reserve,
+                );
+                discrepancies.push(ReserveDiscrepancy {
+                    block_index,
+                    token_id,
+                    balance,
+                    reserve,
+                });
+            }
+        }
+
+        for discrepancy in discrepancies.iter() {
+            db_txn.put(
+                self.reserve_discrepancies_by_block_index,
+                &u64_to_key_bytes(block_index),
+                &encode(discrepancy),
+                WriteFlags::empty(),
+            )?;
+        }
+
+        let reserve_check = ReserveCheck {
+            block_index,
+            reserve_map: reserve_map.clone(),
+            discrepancies,
+        };
+        db_txn.put(
+            self.key_val,
+            &LAST_RESERVE_CHECK_KEY,
+            &encode(&reserve_check),
+            WriteFlags::empty(),
+        )?;
+
+        let mut counters = self.get_counters_impl(&db_txn)?;
+        counters.num_reserve_discrepancies += reserve_check.discrepancies.len() as u64;
+        self.set_counters_impl(&counters, &mut db_txn)?;
+
+        db_txn.commit()?;
+        Ok(reserve_check)
+    }
+
+    /// Get the outcome of the most recent reserve check, if any.
+    pub fn get_last_reserve_check(&self) -> Result<Option<ReserveCheck>, Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        match db_txn.get(self.key_val, &LAST_RESERVE_CHECK_KEY) {
+            Ok(bytes) => Ok(Some(decode(bytes)?)),
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Get the reserve discrepancies recorded for blocks in the range
+    /// `[start_block_index, end_block_index)`, or from `start_block_index`
+    /// onwards if `end_block_index` is `None`.
+    pub fn get_reserve_discrepancies(
+        &self,
+        start_block_index: BlockIndex,
+        end_block_index: Option<BlockIndex>,
+    ) -> Result<Vec<ReserveDiscrepancy>, Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        self.get_values_in_block_range_impl(
+            self.reserve_discrepancies_by_block_index,
+            start_block_index,
+            end_block_index,
+            &db_txn,
+        )
+    }
+
+    /// Get the mint events for blocks in the range
+    /// `[start_block_index, end_block_index)`, or from `start_block_index`
+    /// onwards if `end_block_index` is `None`.
+    /// If `token_ids` is not empty, only events for those tokens are returned.
+    pub fn get_mint_events(
+        &self,
+        start_block_index: BlockIndex,
+        end_block_index: Option<BlockIndex>,
+        token_ids: &[u64],
+    ) -> Result<Vec<MintEvent>, Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        let mint_events: Vec<MintEvent> = self.get_values_in_block_range_impl(
+            self.mint_events_by_block_index,
+            start_block_index,
+            end_block_index,
+            &db_txn,
+        )?;
+        Ok(mint_events
+            .into_iter()
+            .filter(|mint_event| token_ids.is_empty() || token_ids.contains(&mint_event.token_id))
+            .collect())
+    }
+
+    /// Get the burn events for blocks in the range
+    /// `[start_block_index, end_block_index)`, or from `start_block_index`
+    /// onwards if `end_block_index` is `None`.
+    /// If `token_ids` is not empty, only events for those tokens are returned.
+    pub fn get_burn_events(
+        &self,
+        start_block_index: BlockIndex,
+        end_block_index: Option<BlockIndex>,
+        token_ids: &[u64],
+    ) -> Result<Vec<BurnEvent>, Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        let burn_events: Vec<BurnEvent> = self.get_values_in_block_range_impl(
+            self.burn_events_by_block_index,
+            start_block_index,
+            end_block_index,
+            &db_txn,
+        )?;
+        Ok(burn_events
+            .into_iter()
+            .filter(|burn_event| token_ids.is_empty() || token_ids.contains(&burn_event.token_id))
+            .collect())
+    }
+
+    // Get all values stored in a database whose keys start with a block index, for
+    // the blocks in the range `[start_block_index, end_block_index)`.
+    fn get_values_in_block_range_impl<T: Message + Default>(
+        &self,
+        db: Database,
+        start_block_index: BlockIndex,
+        end_block_index: Option<BlockIndex>,
+        db_txn: &impl Transaction,
+    ) -> Result<Vec<T>, Error> {
+        let mut cursor = db_txn.open_ro_cursor(db)?;
+
+        let mut values = Vec::new();
+        for (key_bytes, value_bytes) in cursor
+            .iter_from(&u64_to_key_bytes(start_block_index))
+            .filter_map(Result::ok)
+        {
+            let block_index = key_bytes_to_u64(&key_bytes[..8]);
+            if end_block_index.map_or(false, |end| block_index >= end) {
+                break;
+            }
+            values.push(decode(value_bytes)?);
+        }
+        Ok(values)
+    }
+
     fn get_block_audit_data_impl(
         &self,
         block_index: BlockIndex,
@@ -358,18 +735,31 @@ impl MintAuditorDb {
     }
 }
 
+/// The key of an event in the mint and burn event databases: the block index
+/// followed by the index of the event within the block, both big-endian so
+/// that events are sorted by block and iterating from a block index prefix
+/// finds the events of that block in order.
+fn event_key_bytes(block_index: BlockIndex, event_index: u64) -> [u8; 16] {
+    let mut key = [0u8; 16];
+    key[..8].copy_from_slice(&u64_to_key_bytes(block_index));
+    key[8..].copy_from_slice(&u64_to_key_bytes(event_index));
+    key
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use mc_account_keys::{burn_address, AccountKey};
     use mc_common::logger::{test_with_logger, Logger};
-    use mc_crypto_keys::RistrettoPrivate;
+    use mc_crypto_keys::{Ed25519Pair, RistrettoPrivate};
+    use mc_crypto_multisig::SignerSet;
     use mc_ledger_db::Ledger;
     use mc_transaction_core::{tx::TxOut, Amount, BlockVersion, TokenId};
     use mc_transaction_core_test_utils::{
         create_ledger, create_mint_config_tx_and_signers, create_mint_tx, create_test_tx_out,
         initialize_ledger, mint_config_tx_to_validated as to_validated,
     };
+    use mc_transaction_std::BurnRedemptionMemo;
     use mc_util_from_random::FromRandom;
     use rand_core::SeedableRng;
     use rand_hc::Hc128Rng;
@@ -575,10 +965,71 @@ mod tests {
                 num_blocks_synced: block.index + 1,
                 num_burns_exceeding_balance: 0,
                 num_mint_txs_without_matching_mint_config: 0,
+                num_reserve_discrepancies: 0,
             }
         );
     }
 
+    // A database created by an older version should not be opened, or silently
+    // recreated on top of its synced blocks.
+    #[test_with_logger]
+    fn test_create_or_open_refuses_older_version(logger: Logger) {
+        let mut rng = Hc128Rng::from_seed([1u8; 32]);
+
+        let mint_audit_db_path = tempdir().unwrap();
+        let mint_audit_db =
+            MintAuditorDb::create_or_open(&mint_audit_db_path, logger.clone()).unwrap();
+
+        let mut ledger_db = create_ledger();
+        let account_key = AccountKey::random(&mut rng);
+        initialize_ledger(BlockVersion::MAX, &mut ledger_db, 1, &account_key, &mut rng);
+
+        let block_data = ledger_db.get_block_data(0).unwrap();
+        mint_audit_db
+            .sync_block(block_data.block(), block_data.contents())
+            .unwrap();
+
+        // Pretend the database was created before the mint and burn event logs existed.
+        #[allow(clippy::inconsistent_digit_grouping)]
+        let old_version = 2022_06_27;
+        let metadata_store =
+            MetadataStore::<WatcherDbMetadataStoreSettings>::new(&mint_audit_db.env).unwrap();
+        let mut db_txn = mint_audit_db.env.begin_rw_txn().unwrap();
+        metadata_store
+            .set_version(&mut db_txn, old_version)
+            .unwrap();
+        db_txn.commit().unwrap();
+        drop(mint_audit_db);
+
+        let expected = MetadataStoreError::VersionIncompatible(
+            old_version,
+            WatcherDbMetadataStoreSettings::LATEST_VERSION,
+        );
+        match MintAuditorDb::open(&mint_audit_db_path, logger.clone()) {
+            Err(Error::MetadataStore(err)) if err == expected => {
+                // Expected
+            }
+            Ok(_) => panic!("Unexpected success"),
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        }
+        match MintAuditorDb::create_or_open(&mint_audit_db_path, logger.clone()) {
+            Err(Error::MetadataStore(err)) if err == expected => {
+                // Expected
+            }
+            Ok(_) => panic!("Unexpected success"),
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        }
+
+        // The version was left untouched.
+        match MintAuditorDb::open(&mint_audit_db_path, logger) {
+            Err(Error::MetadataStore(err)) if err == expected => {
+                // Expected
+            }
+            Ok(_) => panic!("Unexpected success"),
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
     // Attempting to skip a block when syncing should fail.
     #[test_with_logger]
     fn test_sync_block_refuses_skipping_a_block(logger: Logger) {
@@ -929,6 +1380,7 @@ mod tests {
                 num_blocks_synced: block.index + 1,
                 num_burns_exceeding_balance: 0,
                 num_mint_txs_without_matching_mint_config: 1,
+                num_reserve_discrepancies: 0,
             }
         );
 
@@ -974,6 +1426,7 @@ mod tests {
                 num_blocks_synced: block.index + 1,
                 num_burns_exceeding_balance: 0,
                 num_mint_txs_without_matching_mint_config: 2,
+                num_reserve_discrepancies: 0,
             }
         );
 
@@ -1001,6 +1454,7 @@ mod tests {
                 num_blocks_synced: block.index + 1,
                 num_burns_exceeding_balance: 0,
                 num_mint_txs_without_matching_mint_config: 2,
+                num_reserve_discrepancies: 0,
             }
         );
     }
@@ -1098,6 +1552,7 @@ mod tests {
                 num_blocks_synced: block.index + 1,
                 num_burns_exceeding_balance: 0,
                 num_mint_txs_without_matching_mint_config: 0,
+                num_reserve_discrepancies: 0,
             }
         );
 
@@ -1125,7 +1580,237 @@ mod tests {
                 num_blocks_synced: block.index + 1,
                 num_burns_exceeding_balance: 0,
                 num_mint_txs_without_matching_mint_config: 1,
+                num_reserve_discrepancies: 0,
             }
         );
     }
+
+    // Mints and burns should be recorded as individual events.
+    #[test_with_logger]
+    fn test_sync_block_records_mint_and_burn_events(logger: Logger) {
+        let mut rng = Hc128Rng::from_seed([1u8; 32]);
+        let token_id1 = TokenId::from(1);
+        let token_id2 = TokenId::from(22);
+
+        let mint_audit_db_path = tempdir().unwrap();
+        let mint_audit_db = MintAuditorDb::create_or_open(&mint_audit_db_path, logger).unwrap();
+
+        let mut ledger_db = create_ledger();
+        let account_key = AccountKey::random(&mut rng);
+        initialize_ledger(BlockVersion::MAX, &mut ledger_db, 1, &account_key, &mut rng);
+
+        let block_data = ledger_db.get_block_data(0).unwrap();
+        mint_audit_db
+            .sync_block(block_data.block(), block_data.contents())
+            .unwrap();
+
+        // Sync a block that contains a MintConfigTx for token 1 only.
+        let (mint_config_tx1, signers1) = create_mint_config_tx_and_signers(token_id1, &mut rng);
+
+        let block_contents = BlockContents {
+            validated_mint_config_txs: vec![to_validated(&mint_config_tx1)],
+            ..Default::default()
+        };
+
+        let block = Block::new_with_parent(
+            BlockVersion::MAX,
+            block_data.block(),
+            &Default::default(),
+            &block_contents,
+        );
+        mint_audit_db.sync_block(&block, &block_contents).unwrap();
+
+        // Sync a block with a matching mint, a mint without a config and two burns,
+        // one of which has a burn redemption memo.
+        let (_mint_config_tx2, signers2) = create_mint_config_tx_and_signers(token_id2, &mut rng);
+        let mint_tx1 = create_mint_tx(token_id1, &signers1[0..1], 10, &mut rng);
+        let mint_tx2 = create_mint_tx(token_id2, &signers2, 20, &mut rng);
+
+        let burn_recipient = burn_address();
+        let tx_out1 = TxOut::new_with_memo(
+            Amount {
+                value: 5,
+                token_id: token_id1,
+            },
+            &burn_recipient,
+            &RistrettoPrivate::from_random(&mut rng),
+            Default::default(),
+            |_| Ok(Some(BurnRedemptionMemo::new([7; 64]).into())),
+        )
+        .unwrap();
+        let tx_out2 = TxOut::new(
+            Amount {
+                value: 3,
+                token_id: token_id1,
+            },
+            &burn_recipient,
+            &RistrettoPrivate::from_random(&mut rng),
+            Default::default(),
+        )
+        .unwrap();
+
+        let block_contents = BlockContents {
+            mint_txs: vec![mint_tx1.clone(), mint_tx2.clone()],
+            outputs: vec![
+                tx_out1.clone(),
+                tx_out2.clone(),
+                create_test_tx_out(&mut rng),
+            ],
+            ..Default::default()
+        };
+
+        let block = Block::new_with_parent(
+            BlockVersion::MAX,
+            &block,
+            &Default::default(),
+            &block_contents,
+        );
+        mint_audit_db.sync_block(&block, &block_contents).unwrap();
+
+        // Check the mint events.
+        assert_eq!(
+            mint_audit_db.get_mint_events(0, None, &[]).unwrap().len(),
+            2
+        );
+        assert_eq!(
+            mint_audit_db.get_mint_events(0, None, &[1]).unwrap(),
+            vec![MintEvent {
+                block_index: 2,
+                token_id: 1,
+                amount: 10,
+                nonce: mint_tx1.prefix.nonce.clone(),
+                mint_config: Some(mint_config_tx1.prefix.configs[0].clone()),
+                signers: vec![signers1[0].public_key()],
+            }]
+        );
+        assert_eq!(
+            mint_audit_db.get_mint_events(0, None, &[22]).unwrap(),
+            vec![MintEvent {
+                block_index: 2,
+                token_id: 22,
+                amount: 20,
+                nonce: mint_tx2.prefix.nonce.clone(),
+                mint_config: None,
+                signers: vec![],
+            }]
+        );
+        assert!(mint_audit_db
+            .get_mint_events(0, Some(2), &[])
+            .unwrap()
+            .is_empty());
+        assert!(mint_audit_db
+            .get_mint_events(3, None, &[])
+            .unwrap()
+            .is_empty());
+
+        // Check the burn events.
+        let mut burn_events = mint_audit_db.get_burn_events(2, Some(3), &[1]).unwrap();
+        burn_events.sort_by_key(|burn_event| burn_event.amount);
+        assert_eq!(
+            burn_events,
+            vec![
+                BurnEvent {
+                    block_index: 2,
+                    token_id: 1,
+                    amount: 3,
+                    public_key: tx_out2.public_key,
+                    burn_redemption_memo_data: vec![],
+                },
+                BurnEvent {
+                    block_index: 2,
+                    token_id: 1,
+                    amount: 5,
+                    public_key: tx_out1.public_key,
+                    burn_redemption_memo_data: vec![7; 64],
+                },
+            ]
+        );
+        assert!(mint_audit_db
+            .get_burn_events(0, None, &[22])
+            .unwrap()
+            .is_empty());
+    }
+
+    // Events larger than the maximum size of a DUP_SORT value should be recorded,
+    // in the order they appear in the block.
+    #[test_with_logger]
+    fn test_sync_block_records_large_mint_events(logger: Logger) {
+        let mut rng = Hc128Rng::from_seed([1u8; 32]);
+        let token_id1 = TokenId::from(1);
+
+        let mint_audit_db_path = tempdir().unwrap();
+        let mint_audit_db = MintAuditorDb::create_or_open(&mint_audit_db_path, logger).unwrap();
+
+        let mut ledger_db = create_ledger();
+        let account_key = AccountKey::random(&mut rng);
+        initialize_ledger(BlockVersion::MAX, &mut ledger_db, 1, &account_key, &mut rng);
+
+        let block_data = ledger_db.get_block_data(0).unwrap();
+        mint_audit_db
+            .sync_block(block_data.block(), block_data.contents())
+            .unwrap();
+
+        // Sync a block with a MintConfigTx whose configuration has many signers.
+        let signers: Vec<Ed25519Pair> = (0..20)
+            .map(|_| Ed25519Pair::from_random(&mut rng))
+            .collect();
+        let (mut mint_config_tx1, _) = create_mint_config_tx_and_signers(token_id1, &mut rng);
+        mint_config_tx1.prefix.configs = vec![MintConfig {
+            token_id: *token_id1,
+            signer_set: SignerSet::new(
+                signers.iter().map(|signer| signer.public_key()).collect(),
+                signers.len() as u32,
+            ),
+            mint_limit: 1000,
+        }];
+        mint_config_tx1.prefix.total_mint_limit = 1000;
+
+        let block_contents = BlockContents {
+            validated_mint_config_txs: vec![to_validated(&mint_config_tx1)],
+            ..Default::default()
+        };
+
+        let block = Block::new_with_parent(
+            BlockVersion::MAX,
+            block_data.block(),
+            &Default::default(),
+            &block_contents,
+        );
+        mint_audit_db.sync_block(&block, &block_contents).unwrap();
+
+        // Sync a block with mints signed by all of them.
+        let mint_txs: Vec<_> = (1..=3)
+            .map(|amount| create_mint_tx(token_id1, &signers, amount, &mut rng))
+            .collect();
+
+        let block_contents = BlockContents {
+            mint_txs: mint_txs.clone(),
+            ..Default::default()
+        };
+
+        let block = Block::new_with_parent(
+            BlockVersion::MAX,
+            &block,
+            &Default::default(),
+            &block_contents,
+        );
+        mint_audit_db.sync_block(&block, &block_contents).unwrap();
+
+        let mint_events = mint_audit_db.get_mint_events(2, Some(3), &[]).unwrap();
+        assert!(encode(&mint_events[0]).len() > 511);
+        assert_eq!(
+            mint_events,
+            mint_txs
+                .iter()
+                .map(|mint_tx| MintEvent {
+                    block_index: 2,
+                    token_id: 1,
+                    amount: mint_tx.prefix.amount,
+                    nonce: mint_tx.prefix.nonce.clone(),
+                    mint_config: Some(mint_config_tx1.prefix.configs[0].clone()),
+                    signers: signers.iter().map(|signer| signer.public_key()).collect(),
+                })
+                .collect::<Vec<_>>()
+        );
+    }
 }
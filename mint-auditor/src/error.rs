@@ -32,6 +32,15 @@ pub enum Error {
 
     /// Unexpected block index {0} (was expecting {1})
     UnexpectedBlockIndex(BlockIndex, BlockIndex),
+
+    /// JSON: {0}
+    Json(serde_json::Error),
+
+    /// HTTP: {0}
+    Reqwest(reqwest::Error),
+
+    /// Invalid reserve source: {0}
+    InvalidReserveSource(String),
 }
 
 impl From<lmdb::Error> for Error {
@@ -66,3 +75,15 @@ impl From<DecodeError> for Error {
         Self::Decode(err)
     }
 }
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Reqwest(err)
+    }
+}
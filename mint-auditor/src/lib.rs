@@ -5,6 +5,7 @@
 #![deny(missing_docs)]
 
 pub mod counters;
+pub mod reserve_source;
 
 mod convert;
 mod db;
@@ -12,7 +13,10 @@ mod error;
 mod service;
 
 pub use crate::{
-    db::{BlockAuditData, Counters, MintAuditorDb},
+    db::{
+        BlockAuditData, BurnEvent, Counters, MintAuditorDb, MintEvent, ReserveCheck,
+        ReserveDiscrepancy,
+    },
     error::Error,
     service::MintAuditorService,
 };
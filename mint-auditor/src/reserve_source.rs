@@ -0,0 +1,157 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Sources of the reserves backing minted tokens.
+//!
+//! A reserve source reports, for each token id, the amount held in reserve
+//! (in the token's smallest unit). The auditor compares it against the
+//! calculated balance in circulation after every synced block.
+//!
+//! Reserves are reported as a JSON object mapping token ids to amounts, e.g.
+//! `{"1": 1000000, "22": 5000}`.
+
+use crate::Error;
+use std::{collections::BTreeMap, fs, path::PathBuf, time::Duration};
+
+/// Timeout for fetching reserves over HTTP.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A source of the reserves backing minted tokens.
+pub trait ReserveSource: Send + Sync {
+    /// Get a map of token id -> amount held in reserve.
+    fn get_reserves(&self) -> Result<BTreeMap<u64, u64>, Error>;
+}
+
+/// Create a reserve source from a URI:
+/// * `http://` and `https://` URLs are fetched using [HttpReserveSource],
+/// * `file://` URLs and plain paths are read using [FileReserveSource].
+pub fn reserve_source_from_uri(uri: &str) -> Result<Box<dyn ReserveSource>, Error> {
+    if uri.starts_with("http://") || uri.starts_with("https://") {
+        Ok(Box::new(HttpReserveSource::new(uri)?))
+    } else {
+        let path = uri.strip_prefix("file://").unwrap_or(uri);
+        if path.is_empty() {
+            return Err(Error::InvalidReserveSource(uri.to_owned()));
+        }
+        Ok(Box::new(FileReserveSource::new(path)))
+    }
+}
+
+/// Parse the JSON representation of reserves.
+fn parse_reserves(json: &str) -> Result<BTreeMap<u64, u64>, Error> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Reads reserves from a JSON file, which is re-read on every call so that it
+/// can be updated by an external process.
+#[derive(Clone, Debug)]
+pub struct FileReserveSource {
+    path: PathBuf,
+}
+
+impl FileReserveSource {
+    /// Create a new file reserve source.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl ReserveSource for FileReserveSource {
+    fn get_reserves(&self) -> Result<BTreeMap<u64, u64>, Error> {
+        parse_reserves(&fs::read_to_string(&self.path)?)
+    }
+}
+
+/// Fetches reserves as JSON from an HTTP endpoint, e.g. a local service
+/// standing in for the custodian's reserve attestation API.
+#[derive(Clone, Debug)]
+pub struct HttpReserveSource {
+    url: String,
+    client: reqwest::blocking::Client,
+}
+
+impl HttpReserveSource {
+    /// Create a new HTTP reserve source.
+    pub fn new(url: impl Into<String>) -> Result<Self, Error> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()?;
+        Ok(Self {
+            url: url.into(),
+            client,
+        })
+    }
+}
+
+impl ReserveSource for HttpReserveSource {
+    fn get_reserves(&self) -> Result<BTreeMap<u64, u64>, Error> {
+        let body = self
+            .client
+            .get(&self.url)
+            .send()?
+            .error_for_status()?
+            .text()?;
+        parse_reserves(&body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        iter::FromIterator,
+        net::TcpListener,
+        thread,
+    };
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_file_reserve_source() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("reserves.json");
+        fs::write(&path, r#"{"1": 1000, "22": 5}"#).unwrap();
+
+        let source = reserve_source_from_uri(&format!("file://{}", path.display())).unwrap();
+        assert_eq!(
+            source.get_reserves().unwrap(),
+            BTreeMap::from_iter([(1, 1000), (22, 5)])
+        );
+
+        // The file is re-read on every call.
+        fs::write(&path, r#"{"1": 2000}"#).unwrap();
+        assert_eq!(
+            source.get_reserves().unwrap(),
+            BTreeMap::from_iter([(1, 2000)])
+        );
+
+        // Malformed files are reported.
+        fs::write(&path, "not json").unwrap();
+        assert!(matches!(source.get_reserves(), Err(Error::Json(_))));
+    }
+
+    #[test]
+    fn test_http_reserve_source() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).unwrap();
+            let body = r#"{"3": 42}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+
+        let source = reserve_source_from_uri(&format!("http://{}/reserves", addr)).unwrap();
+        assert_eq!(
+            source.get_reserves().unwrap(),
+            BTreeMap::from_iter([(3, 42)])
+        );
+        server.join().unwrap();
+    }
+}
@@ -8,8 +8,10 @@ use mc_common::logger::Logger;
 use mc_mint_auditor_api::{
     empty::Empty,
     mint_auditor::{
-        Counters, GetBlockAuditDataRequest, GetBlockAuditDataResponse,
-        GetLastBlockAuditDataResponse,
+        Counters, GetBlockAuditDataRequest, GetBlockAuditDataResponse, GetBurnEventsRequest,
+        GetBurnEventsResponse, GetLastBlockAuditDataResponse, GetLastReserveCheckResponse,
+        GetMintEventsRequest, GetMintEventsResponse, GetReserveDiscrepanciesRequest,
+        GetReserveDiscrepanciesResponse,
     },
     mint_auditor_grpc::{create_mint_auditor_api, MintAuditorApi},
 };
@@ -138,6 +140,115 @@ impl MintAuditorApi for MintAuditorService {
 
         send_result(ctx, sink, result, &logger);
     }
+
+    fn get_reserve_discrepancies(
+        &mut self,
+        ctx: RpcContext,
+        req: GetReserveDiscrepanciesRequest,
+        sink: UnarySink<GetReserveDiscrepanciesResponse>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+
+        let end_block_index = match req.get_end_block_index() {
+            0 => None,
+            end_block_index => Some(end_block_index),
+        };
+
+        let result = self
+            .mint_auditor_db
+            .get_reserve_discrepancies(req.get_start_block_index(), end_block_index)
+            .map(|discrepancies| {
+                let mut resp = GetReserveDiscrepanciesResponse::new();
+                resp.set_discrepancies(discrepancies.iter().map(Into::into).collect());
+                resp
+            })
+            .map_err(|err| RpcStatus::with_message(RpcStatusCode::INTERNAL, err.to_string()));
+
+        send_result(ctx, sink, result, &logger);
+    }
+
+    fn get_last_reserve_check(
+        &mut self,
+        ctx: RpcContext,
+        _req: Empty,
+        sink: UnarySink<GetLastReserveCheckResponse>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+
+        let result = self
+            .mint_auditor_db
+            .get_last_reserve_check()
+            .map(|reserve_check| {
+                let mut resp = GetLastReserveCheckResponse::new();
+                if let Some(reserve_check) = reserve_check.as_ref() {
+                    resp.set_reserve_check(reserve_check.into());
+                }
+                resp
+            })
+            .map_err(|err| RpcStatus::with_message(RpcStatusCode::INTERNAL, err.to_string()));
+
+        send_result(ctx, sink, result, &logger);
+    }
+
+    fn get_mint_events(
+        &mut self,
+        ctx: RpcContext,
+        req: GetMintEventsRequest,
+        sink: UnarySink<GetMintEventsResponse>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+
+        let end_block_index = match req.get_end_block_index() {
+            0 => None,
+            end_block_index => Some(end_block_index),
+        };
+
+        let result = self
+            .mint_auditor_db
+            .get_mint_events(
+                req.get_start_block_index(),
+                end_block_index,
+                req.get_token_ids(),
+            )
+            .map(|mint_events| {
+                let mut resp = GetMintEventsResponse::new();
+                resp.set_mint_events(mint_events.iter().map(Into::into).collect());
+                resp
+            })
+            .map_err(|err| RpcStatus::with_message(RpcStatusCode::INTERNAL, err.to_string()));
+
+        send_result(ctx, sink, result, &logger);
+    }
+
+    fn get_burn_events(
+        &mut self,
+        ctx: RpcContext,
+        req: GetBurnEventsRequest,
+        sink: UnarySink<GetBurnEventsResponse>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+
+        let end_block_index = match req.get_end_block_index() {
+            0 => None,
+            end_block_index => Some(end_block_index),
+        };
+
+        let result = self
+            .mint_auditor_db
+            .get_burn_events(
+                req.get_start_block_index(),
+                end_block_index,
+                req.get_token_ids(),
+            )
+            .map(|burn_events| {
+                let mut resp = GetBurnEventsResponse::new();
+                resp.set_burn_events(burn_events.iter().map(Into::into).collect());
+                resp
+            })
+            .map_err(|err| RpcStatus::with_message(RpcStatusCode::INTERNAL, err.to_string()));
+
+        send_result(ctx, sink, result, &logger);
+    }
 }
 
 #[cfg(test)]
@@ -155,7 +266,11 @@ mod tests {
     };
     use rand_core::SeedableRng;
     use rand_hc::Hc128Rng;
-    use std::{collections::HashMap, iter::FromIterator, sync::Arc};
+    use std::{
+        collections::{BTreeMap, HashMap},
+        iter::FromIterator,
+        sync::Arc,
+    };
     use tempfile::tempdir;
 
     /// Starts the service on localhost and connects a client to it.
@@ -281,6 +396,125 @@ mod tests {
         );
     }
 
+    #[test_with_logger]
+    fn test_get_reserve_discrepancies(logger: Logger) {
+        let mint_audit_db = get_test_db(&logger);
+        let (client, _server) = get_client_server(&mint_audit_db, &logger);
+
+        // No reserves have been checked yet.
+        let response = client.get_last_reserve_check(&Empty::default()).unwrap();
+        assert!(!response.has_reserve_check());
+
+        // Token 1 is fully backed, token 22 is not, and token 3 has not been minted.
+        let reserve_map = BTreeMap::from_iter([(1, 101), (22, 1), (3, 5)]);
+        mint_audit_db.check_reserves(0, &reserve_map).unwrap();
+        mint_audit_db.check_reserves(1, &reserve_map).unwrap();
+
+        let response = client
+            .get_reserve_discrepancies(&GetReserveDiscrepanciesRequest {
+                start_block_index: 1,
+                ..Default::default()
+            })
+            .unwrap();
+        let discrepancies: Vec<_> = response
+            .get_discrepancies()
+            .iter()
+            .map(|discrepancy| {
+                (
+                    discrepancy.get_block_index(),
+                    discrepancy.get_token_id(),
+                    discrepancy.get_balance(),
+                    discrepancy.get_reserve(),
+                )
+            })
+            .collect();
+        assert_eq!(discrepancies, vec![(1, 3, 0, 5), (1, 22, 2, 1)]);
+
+        // Block 0 had no balances, so every reserve was a discrepancy.
+        let response = client
+            .get_reserve_discrepancies(&GetReserveDiscrepanciesRequest {
+                start_block_index: 0,
+                end_block_index: 1,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(response.get_discrepancies().len(), 3);
+
+        let response = client.get_last_reserve_check(&Empty::default()).unwrap();
+        let reserve_check = response.get_reserve_check();
+        assert_eq!(reserve_check.get_block_index(), 1);
+        assert_eq!(
+            reserve_check.get_reserve_map(),
+            &HashMap::from_iter([(1, 101), (22, 1), (3, 5)])
+        );
+        assert_eq!(reserve_check.get_discrepancies().len(), 2);
+
+        assert_eq!(
+            client
+                .get_counters(&Empty::default())
+                .unwrap()
+                .get_num_reserve_discrepancies(),
+            5
+        );
+    }
+
+    #[test_with_logger]
+    fn test_get_mint_events(logger: Logger) {
+        let mint_audit_db = get_test_db(&logger);
+        let (client, _server) = get_client_server(&mint_audit_db, &logger);
+
+        let response = client
+            .get_mint_events(&GetMintEventsRequest::default())
+            .unwrap();
+        let mut mint_events: Vec<_> = response
+            .get_mint_events()
+            .iter()
+            .map(|mint_event| {
+                // None of the mints in the test db match an active mint config.
+                assert!(!mint_event.has_mint_config());
+                assert!(mint_event.get_signers().is_empty());
+                (
+                    mint_event.get_block_index(),
+                    mint_event.get_token_id(),
+                    mint_event.get_amount(),
+                )
+            })
+            .collect();
+        mint_events.sort();
+        assert_eq!(mint_events, vec![(1, 1, 1), (1, 1, 100), (1, 22, 2)]);
+
+        let response = client
+            .get_mint_events(&GetMintEventsRequest {
+                token_ids: vec![22],
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(response.get_mint_events().len(), 1);
+        assert_eq!(response.get_mint_events()[0].get_amount(), 2);
+
+        // Block 0 contains no mints.
+        let response = client
+            .get_mint_events(&GetMintEventsRequest {
+                start_block_index: 0,
+                end_block_index: 1,
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(response.get_mint_events().is_empty());
+    }
+
+    #[test_with_logger]
+    fn test_get_burn_events(logger: Logger) {
+        let mint_audit_db = get_test_db(&logger);
+        let (client, _server) = get_client_server(&mint_audit_db, &logger);
+
+        // The test db contains no burns.
+        let response = client
+            .get_burn_events(&GetBurnEventsRequest::default())
+            .unwrap();
+        assert!(response.get_burn_events().is_empty());
+    }
+
     #[test_with_logger]
     fn test_get_counters(logger: Logger) {
         let mint_audit_db = get_test_db(&logger);
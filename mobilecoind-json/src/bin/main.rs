@@ -6,14 +6,22 @@
 #![feature(proc_macro_hygiene, decl_macro)]
 
 use clap::Parser;
+use futures::StreamExt;
 use grpcio::ChannelBuilder;
-use mc_api::external::{CompressedRistretto, PublicAddress, RistrettoPrivate};
+use mc_api::external::{AccountKey, CompressedRistretto, PublicAddress};
 use mc_common::logger::{create_app_logger, log, o};
 use mc_mobilecoind_api::{mobilecoind_api_grpc::MobilecoindApiClient, MobilecoindUri};
-use mc_mobilecoind_json::data_types::*;
+use mc_mobilecoind_json::{data_types::*, OPENAPI_SPEC};
 use mc_util_grpc::ConnectionUriGrpcioChannel;
 use protobuf::RepeatedField;
-use rocket::{delete, get, post, routes, serde::json::Json};
+use rocket::{
+    delete, get,
+    http::ContentType,
+    post, put,
+    response::stream::{Event, EventStream},
+    routes,
+    serde::json::Json,
+};
 use std::{convert::TryFrom, sync::Arc};
 
 /// Command line config, set with defaults that will work with
@@ -141,25 +149,16 @@ fn add_monitor(
     state: &rocket::State<State>,
     monitor: Json<JsonMonitorRequest>,
 ) -> Result<Json<JsonMonitorResponse>, String> {
-    let mut account_key = mc_mobilecoind_api::external::AccountKey::new();
-    let mut view_private_key = RistrettoPrivate::new();
-    view_private_key.set_data(
-        hex::decode(&monitor.account_key.view_private_key)
-            .map_err(|err| format!("Failed to decode hex key: {}", err))?,
-    );
-    let mut spend_private_key = RistrettoPrivate::new();
-    spend_private_key.set_data(
-        hex::decode(&monitor.account_key.spend_private_key)
-            .map_err(|err| format!("Failed to decode hex key: {}", err))?,
-    );
-    account_key.set_view_private_key(view_private_key);
-    account_key.set_spend_private_key(spend_private_key);
+    let account_key = AccountKey::try_from(&monitor.account_key)?;
 
     let mut req = mc_mobilecoind_api::AddMonitorRequest::new();
     req.set_account_key(account_key);
     req.set_first_subaddress(monitor.first_subaddress);
     req.set_num_subaddresses(monitor.num_subaddresses);
-    req.set_first_block(0);
+    req.set_first_block(monitor.first_block.map(u64::from).unwrap_or(0));
+    if let Some(name) = monitor.name.clone() {
+        req.set_name(name);
+    }
 
     let monitor_response = state
         .mobilecoind_api_client
@@ -216,12 +215,111 @@ fn monitor_status(
     Ok(Json(JsonMonitorStatusResponse::from(&resp)))
 }
 
-/// Balance check using a created monitor and subaddress index
-#[get("/monitors/<monitor_hex>/subaddresses/<subaddress_index>/balance")]
+/// Set or remove the webhook notified when a monitor receives or spends TxOuts
+#[put("/monitors/<monitor_hex>/webhook", format = "json", data = "<request>")]
+fn set_monitor_webhook(
+    state: &rocket::State<State>,
+    monitor_hex: String,
+    request: Json<JsonSetMonitorWebhookRequest>,
+) -> Result<Json<JsonSetMonitorWebhookResponse>, String> {
+    let monitor_id =
+        hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
+    let secret = hex::decode(&request.secret)
+        .map_err(|err| format!("Failed to decode secret hex: {}", err))?;
+
+    let mut req = mc_mobilecoind_api::SetMonitorWebhookRequest::new();
+    req.set_monitor_id(monitor_id);
+    req.set_url(request.url.clone());
+    req.set_secret(secret);
+
+    state
+        .mobilecoind_api_client
+        .set_monitor_webhook(&req)
+        .map_err(|err| format!("Failed setting monitor webhook: {}", err))?;
+
+    Ok(Json(JsonSetMonitorWebhookResponse { success: true }))
+}
+
+/// Get the webhook of a monitor
+#[get("/monitors/<monitor_hex>/webhook")]
+fn monitor_webhook(
+    state: &rocket::State<State>,
+    monitor_hex: String,
+) -> Result<Json<JsonMonitorWebhookResponse>, String> {
+    let monitor_id =
+        hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
+
+    let mut req = mc_mobilecoind_api::GetMonitorWebhookRequest::new();
+    req.set_monitor_id(monitor_id);
+
+    let resp = state
+        .mobilecoind_api_client
+        .get_monitor_webhook(&req)
+        .map_err(|err| format!("Failed getting monitor webhook: {}", err))?;
+
+    Ok(Json(JsonMonitorWebhookResponse::from(&resp)))
+}
+
+/// Get the TxOuts received by a view-only monitor whose key images have not
+/// been imported yet
+#[get("/monitors/<monitor_hex>/unverified-txos")]
+fn unverified_txos(
+    state: &rocket::State<State>,
+    monitor_hex: String,
+) -> Result<Json<JsonUnverifiedTxOutsResponse>, String> {
+    let monitor_id =
+        hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
+
+    let mut req = mc_mobilecoind_api::GetUnverifiedTxOutListRequest::new();
+    req.set_monitor_id(monitor_id);
+
+    let resp = state
+        .mobilecoind_api_client
+        .get_unverified_tx_out_list(&req)
+        .map_err(|err| format!("Failed getting unverified txos: {}", err))?;
+
+    Ok(Json(JsonUnverifiedTxOutsResponse::from(&resp)))
+}
+
+/// Import the key images of TxOuts received by a view-only monitor
+#[post(
+    "/monitors/<monitor_hex>/key-images",
+    format = "json",
+    data = "<request>"
+)]
+fn import_key_images(
+    state: &rocket::State<State>,
+    monitor_hex: String,
+    request: Json<JsonImportKeyImagesRequest>,
+) -> Result<Json<JsonImportKeyImagesResponse>, String> {
+    let monitor_id =
+        hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
+    let key_images = request
+        .key_images
+        .iter()
+        .map(mc_mobilecoind_api::TxOutKeyImage::try_from)
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut req = mc_mobilecoind_api::ImportKeyImagesRequest::new();
+    req.set_monitor_id(monitor_id);
+    req.set_key_images(RepeatedField::from_vec(key_images));
+
+    let resp = state
+        .mobilecoind_api_client
+        .import_key_images(&req)
+        .map_err(|err| format!("Failed importing key images: {}", err))?;
+
+    Ok(Json(JsonImportKeyImagesResponse::from(&resp)))
+}
+
+/// Balance check using a created monitor and subaddress index, in a given
+/// token id (MOB by default)
+#[get("/monitors/<monitor_hex>/subaddresses/<subaddress_index>/balance?<token_id>")]
 fn balance(
     state: &rocket::State<State>,
     monitor_hex: String,
     subaddress_index: u64,
+    token_id: Option<u64>,
 ) -> Result<Json<JsonBalanceResponse>, String> {
     let monitor_id =
         hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
@@ -229,6 +327,7 @@ fn balance(
     let mut req = mc_mobilecoind_api::GetBalanceRequest::new();
     req.set_monitor_id(monitor_id);
     req.set_subaddress_index(subaddress_index);
+    req.set_token_id(token_id.unwrap_or(0));
 
     let resp = state
         .mobilecoind_api_client
@@ -238,11 +337,14 @@ fn balance(
     Ok(Json(JsonBalanceResponse::from(&resp)))
 }
 
-#[get("/monitors/<monitor_hex>/subaddresses/<subaddress_index>/utxos")]
+/// Get the unspent TxOuts of a monitor and subaddress index, in a given token
+/// id (MOB by default)
+#[get("/monitors/<monitor_hex>/subaddresses/<subaddress_index>/utxos?<token_id>")]
 fn utxos(
     state: &rocket::State<State>,
     monitor_hex: String,
     subaddress_index: u64,
+    token_id: Option<u64>,
 ) -> Result<Json<JsonUtxosResponse>, String> {
     let monitor_id =
         hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
@@ -250,6 +352,7 @@ fn utxos(
     let mut req = mc_mobilecoind_api::GetUnspentTxOutListRequest::new();
     req.set_monitor_id(monitor_id);
     req.set_subaddress_index(subaddress_index);
+    req.set_token_id(token_id.unwrap_or(0));
 
     let resp = state
         .mobilecoind_api_client
@@ -300,6 +403,9 @@ fn create_request_code(
     if let Some(memo) = request.memo.clone() {
         req.set_memo(memo);
     }
+    if let Some(token_id) = request.token_id {
+        req.set_token_id(u64::from(token_id));
+    }
 
     let resp = state
         .mobilecoind_api_client
@@ -366,6 +472,38 @@ fn parse_address_code(
     Ok(Json(JsonParseAddressCodeResponse::from(&resp)))
 }
 
+/// Generates a transfer code
+#[post("/codes/transfer", format = "json", data = "<request>")]
+fn create_transfer_code(
+    state: &rocket::State<State>,
+    request: Json<JsonCreateTransferCodeRequest>,
+) -> Result<Json<JsonCreateTransferCodeResponse>, String> {
+    let req = mc_mobilecoind_api::CreateTransferCodeRequest::try_from(&request.0)?;
+
+    let resp = state
+        .mobilecoind_api_client
+        .create_transfer_code(&req)
+        .map_err(|err| format!("Failed creating transfer code: {}", err))?;
+
+    Ok(Json(JsonCreateTransferCodeResponse::from(&resp)))
+}
+
+/// Retrieves the data in a transfer b58_code
+#[get("/codes/transfer/<b58_code>")]
+fn parse_transfer_code(
+    state: &rocket::State<State>,
+    b58_code: String,
+) -> Result<Json<JsonParseTransferCodeResponse>, String> {
+    let mut req = mc_mobilecoind_api::ParseTransferCodeRequest::new();
+    req.set_b58_code(b58_code);
+    let resp = state
+        .mobilecoind_api_client
+        .parse_transfer_code(&req)
+        .map_err(|err| format!("Failed parsing transfer code: {}", err))?;
+
+    Ok(Json(JsonParseTransferCodeResponse::from(&resp)))
+}
+
 /// Performs a transfer from a monitor and subaddress. The public keys and
 /// amount are in the POST data.
 #[post(
@@ -389,6 +527,13 @@ fn build_and_submit(
     outlay.set_receiver(public_address);
     outlay.set_value(transfer.request_data.value.into());
 
+    let utxo_selection_strategy = transfer
+        .utxo_selection_strategy
+        .as_deref()
+        .map(parse_utxo_selection_strategy)
+        .transpose()?
+        .unwrap_or_default();
+
     // Get max_input_utxo_value.
     let max_input_utxo_value = transfer
         .max_input_utxo_value
@@ -406,6 +551,10 @@ fn build_and_submit(
         req.set_override_change_subaddress(true);
         req.set_change_subaddress(u64::from(subaddress))
     }
+    req.set_fee(transfer.fee.map(u64::from).unwrap_or(0));
+    req.set_tombstone(transfer.tombstone.map(u64::from).unwrap_or(0));
+    req.set_token_id(transfer.request_data.token_id.into());
+    req.set_utxo_selection_strategy(utxo_selection_strategy);
 
     let resp = state
         .mobilecoind_api_client
@@ -435,6 +584,13 @@ fn pay_address_code(
     // Get amount.
     let amount = u64::from(transfer.value);
 
+    let utxo_selection_strategy = transfer
+        .utxo_selection_strategy
+        .as_deref()
+        .map(parse_utxo_selection_strategy)
+        .transpose()?
+        .unwrap_or_default();
+
     // Get max_input_utxo_value.
     let max_input_utxo_value = transfer
         .max_input_utxo_value
@@ -453,6 +609,10 @@ fn pay_address_code(
         req.set_override_change_subaddress(true);
         req.set_change_subaddress(u64::from(subaddress))
     }
+    req.set_fee(transfer.fee.map(u64::from).unwrap_or(0));
+    req.set_tombstone(transfer.tombstone.map(u64::from).unwrap_or(0));
+    req.set_token_id(transfer.token_id.map(u64::from).unwrap_or(0));
+    req.set_utxo_selection_strategy(utxo_selection_strategy);
 
     let resp = state
         .mobilecoind_api_client
@@ -463,6 +623,63 @@ fn pay_address_code(
     Ok(Json(JsonSendPaymentResponse::from(&resp)))
 }
 
+/// Performs a transfer from a monitor and subaddress to a list of outlays,
+/// possibly in several token ids.
+#[post(
+    "/monitors/<monitor_hex>/subaddresses/<subaddress_index>/send-payment",
+    format = "json",
+    data = "<transfer>"
+)]
+fn send_payment(
+    state: &rocket::State<State>,
+    monitor_hex: String,
+    subaddress_index: u64,
+    transfer: Json<JsonSendPaymentV2Request>,
+) -> Result<Json<JsonSendPaymentResponse>, String> {
+    let monitor_id =
+        hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
+
+    let outlays: Vec<mc_mobilecoind_api::Outlay> = transfer
+        .outlay_list
+        .iter()
+        .map(mc_mobilecoind_api::Outlay::try_from)
+        .collect::<Result<_, String>>()?;
+    let outlays_v2: Vec<mc_mobilecoind_api::OutlayV2> = transfer
+        .outlay_list_v2
+        .iter()
+        .map(mc_mobilecoind_api::OutlayV2::try_from)
+        .collect::<Result<_, String>>()?;
+
+    let utxo_selection_strategy = transfer
+        .utxo_selection_strategy
+        .as_deref()
+        .map(parse_utxo_selection_strategy)
+        .transpose()?
+        .unwrap_or_default();
+
+    let mut req = mc_mobilecoind_api::SendPaymentRequest::new();
+    req.set_sender_monitor_id(monitor_id);
+    req.set_sender_subaddress(subaddress_index);
+    req.set_outlay_list(RepeatedField::from_vec(outlays));
+    req.set_outlay_list_v2(RepeatedField::from_vec(outlays_v2));
+    req.set_max_input_utxo_value(transfer.max_input_utxo_value.map(u64::from).unwrap_or(0));
+    if let Some(subaddress) = transfer.change_subaddress.as_ref() {
+        req.set_override_change_subaddress(true);
+        req.set_change_subaddress(u64::from(subaddress))
+    }
+    req.set_fee(transfer.fee.map(u64::from).unwrap_or(0));
+    req.set_tombstone(transfer.tombstone.map(u64::from).unwrap_or(0));
+    req.set_token_id(transfer.token_id.map(u64::from).unwrap_or(0));
+    req.set_utxo_selection_strategy(utxo_selection_strategy);
+
+    let resp = state
+        .mobilecoind_api_client
+        .send_payment(&req)
+        .map_err(|err| format!("Failed to send payment: {}", err))?;
+
+    Ok(Json(JsonSendPaymentResponse::from(&resp)))
+}
+
 /// Creates a transaction proposal. This can be used in an offline transaction
 /// construction flow, where the proposal is created on the offline machine, and
 /// copied to the connected machine for submission, via submit-tx.
@@ -502,6 +719,74 @@ fn generate_request_code_transaction(
     req.set_change_subaddress(subaddress_index);
     req.set_outlay_list(RepeatedField::from_vec(vec![outlay]));
     req.set_input_list(RepeatedField::from_vec(inputs));
+    req.set_token_id(request.transfer.token_id.into());
+
+    let resp = state
+        .mobilecoind_api_client
+        .generate_tx(&req)
+        .map_err(|err| format!("Failed to generate tx: {}", err))?;
+
+    Ok(Json(JsonCreateTxProposalResponse::from(&resp)))
+}
+
+fn convert_inputs(
+    input_list: &[JsonUnspentTxOut],
+) -> Result<Vec<mc_mobilecoind_api::UnspentTxOut>, String> {
+    input_list
+        .iter()
+        .map(|input| {
+            mc_mobilecoind_api::UnspentTxOut::try_from(input)
+                .map_err(|err| format!("Failed to convert input: {}", err))
+        })
+        .collect()
+}
+
+/// Creates a transaction proposal from a list of inputs and outlays, possibly
+/// in several token ids.
+#[post(
+    "/monitors/<monitor_hex>/subaddresses/<subaddress_index>/generate-tx",
+    format = "json",
+    data = "<request>"
+)]
+fn generate_tx(
+    state: &rocket::State<State>,
+    monitor_hex: String,
+    subaddress_index: u64,
+    request: Json<JsonGenerateTxRequest>,
+) -> Result<Json<JsonCreateTxProposalResponse>, String> {
+    let monitor_id =
+        hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
+
+    let outlays: Vec<mc_mobilecoind_api::Outlay> = request
+        .outlay_list
+        .iter()
+        .map(mc_mobilecoind_api::Outlay::try_from)
+        .collect::<Result<_, String>>()?;
+    let outlays_v2: Vec<mc_mobilecoind_api::OutlayV2> = request
+        .outlay_list_v2
+        .iter()
+        .map(mc_mobilecoind_api::OutlayV2::try_from)
+        .collect::<Result<_, String>>()?;
+
+    let utxo_selection_strategy = request
+        .utxo_selection_strategy
+        .as_deref()
+        .map(parse_utxo_selection_strategy)
+        .transpose()?
+        .unwrap_or_default();
+
+    let mut req = mc_mobilecoind_api::GenerateTxRequest::new();
+    req.set_sender_monitor_id(monitor_id);
+    req.set_change_subaddress(subaddress_index);
+    req.set_input_list(RepeatedField::from_vec(convert_inputs(
+        &request.input_list,
+    )?));
+    req.set_outlay_list(RepeatedField::from_vec(outlays));
+    req.set_outlay_list_v2(RepeatedField::from_vec(outlays_v2));
+    req.set_fee(request.fee.map(u64::from).unwrap_or(0));
+    req.set_tombstone(request.tombstone.map(u64::from).unwrap_or(0));
+    req.set_token_id(request.token_id.map(u64::from).unwrap_or(0));
+    req.set_utxo_selection_strategy(utxo_selection_strategy);
 
     let resp = state
         .mobilecoind_api_client
@@ -511,6 +796,142 @@ fn generate_request_code_transaction(
     Ok(Json(JsonCreateTxProposalResponse::from(&resp)))
 }
 
+/// Creates a transaction proposal that merges some of the UnspentTxOuts of a
+/// monitor and subaddress.
+#[post(
+    "/monitors/<monitor_hex>/subaddresses/<subaddress_index>/generate-optimization-tx",
+    format = "json",
+    data = "<request>"
+)]
+fn generate_optimization_tx(
+    state: &rocket::State<State>,
+    monitor_hex: String,
+    subaddress_index: u64,
+    request: Json<JsonGenerateOptimizationTxRequest>,
+) -> Result<Json<JsonCreateTxProposalResponse>, String> {
+    let monitor_id =
+        hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
+
+    let mut req = mc_mobilecoind_api::GenerateOptimizationTxRequest::new();
+    req.set_monitor_id(monitor_id);
+    req.set_subaddress(subaddress_index);
+    req.set_fee(request.fee.map(u64::from).unwrap_or(0));
+    req.set_token_id(request.token_id.map(u64::from).unwrap_or(0));
+
+    let resp = state
+        .mobilecoind_api_client
+        .generate_optimization_tx(&req)
+        .map_err(|err| format!("Failed to generate optimization tx: {}", err))?;
+
+    Ok(Json(JsonCreateTxProposalResponse::from(&resp)))
+}
+
+/// Creates a transaction proposal that funds a new transfer code.
+#[post(
+    "/monitors/<monitor_hex>/subaddresses/<subaddress_index>/generate-transfer-code-tx",
+    format = "json",
+    data = "<request>"
+)]
+fn generate_transfer_code_tx(
+    state: &rocket::State<State>,
+    monitor_hex: String,
+    subaddress_index: u64,
+    request: Json<JsonGenerateTransferCodeTxRequest>,
+) -> Result<Json<JsonGenerateTransferCodeTxResponse>, String> {
+    let monitor_id =
+        hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
+
+    let mut req = mc_mobilecoind_api::GenerateTransferCodeTxRequest::new();
+    req.set_sender_monitor_id(monitor_id);
+    req.set_change_subaddress(subaddress_index);
+    req.set_input_list(RepeatedField::from_vec(convert_inputs(
+        &request.input_list,
+    )?));
+    req.set_value(request.value.into());
+    req.set_fee(request.fee.map(u64::from).unwrap_or(0));
+    req.set_tombstone(request.tombstone.map(u64::from).unwrap_or(0));
+    if let Some(memo) = request.memo.clone() {
+        req.set_memo(memo);
+    }
+    req.set_token_id(request.token_id.map(u64::from).unwrap_or(0));
+
+    let resp = state
+        .mobilecoind_api_client
+        .generate_transfer_code_tx(&req)
+        .map_err(|err| format!("Failed to generate transfer code tx: {}", err))?;
+
+    Ok(Json(JsonGenerateTransferCodeTxResponse::from(&resp)))
+}
+
+/// Creates a transaction proposal that burns funds, with a burn redemption
+/// memo.
+#[post(
+    "/monitors/<monitor_hex>/subaddresses/<subaddress_index>/generate-burn-redemption-tx",
+    format = "json",
+    data = "<request>"
+)]
+fn generate_burn_redemption_tx(
+    state: &rocket::State<State>,
+    monitor_hex: String,
+    subaddress_index: u64,
+    request: Json<JsonGenerateBurnRedemptionTxRequest>,
+) -> Result<Json<JsonCreateTxProposalResponse>, String> {
+    let monitor_id =
+        hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
+
+    let mut req = mc_mobilecoind_api::GenerateBurnRedemptionTxRequest::new();
+    req.set_sender_monitor_id(monitor_id);
+    req.set_change_subaddress(subaddress_index);
+    req.set_input_list(RepeatedField::from_vec(convert_inputs(
+        &request.input_list,
+    )?));
+    req.set_burn_amount(request.burn_amount.into());
+    req.set_fee(request.fee.map(u64::from).unwrap_or(0));
+    req.set_tombstone(request.tombstone.map(u64::from).unwrap_or(0));
+    req.set_token_id(request.token_id.map(u64::from).unwrap_or(0));
+    if let Some(redemption_memo) = request.redemption_memo.as_ref() {
+        req.set_redemption_memo(
+            hex::decode(redemption_memo)
+                .map_err(|err| format!("Failed to decode redemption memo hex: {}", err))?,
+        );
+    }
+    req.set_enable_destination_memo(request.enable_destination_memo);
+
+    let resp = state
+        .mobilecoind_api_client
+        .generate_burn_redemption_tx(&req)
+        .map_err(|err| format!("Failed to generate burn redemption tx: {}", err))?;
+
+    Ok(Json(JsonCreateTxProposalResponse::from(&resp)))
+}
+
+/// Creates a transaction proposal that sends a list of TxOuts owned by an
+/// account key to a single receiver, without a monitor.
+#[post("/generate-tx-from-tx-out-list", format = "json", data = "<request>")]
+fn generate_tx_from_tx_out_list(
+    state: &rocket::State<State>,
+    request: Json<JsonGenerateTxFromTxOutListRequest>,
+) -> Result<Json<JsonCreateTxProposalResponse>, String> {
+    let mut req = mc_mobilecoind_api::GenerateTxFromTxOutListRequest::new();
+    req.set_account_key(AccountKey::try_from(&request.account_key)?);
+    req.set_input_list(RepeatedField::from_vec(convert_inputs(
+        &request.input_list,
+    )?));
+    req.set_receiver(
+        PublicAddress::try_from(&request.receiver)
+            .map_err(|err| format!("Failed to parse receiver's public address: {}", err))?,
+    );
+    req.set_fee(request.fee.map(u64::from).unwrap_or(0));
+    req.set_token_id(request.token_id.map(u64::from).unwrap_or(0));
+
+    let resp = state
+        .mobilecoind_api_client
+        .generate_tx_from_tx_out_list(&req)
+        .map_err(|err| format!("Failed to generate tx: {}", err))?;
+
+    Ok(Json(JsonCreateTxProposalResponse::from(&resp)))
+}
+
 /// Submit a prepared TxProposal
 #[post("/submit-tx", format = "json", data = "<proposal>")]
 fn submit_tx(
@@ -637,6 +1058,46 @@ fn block_details(
 
     Ok(Json(JsonBlockDetailsResponse::from(&resp)))
 }
+
+/// Streams the details of every block, starting at start_block and continuing
+/// with new blocks as they are appended to the ledger, as server-sent events.
+#[get("/ledger/blocks/subscribe?<start_block>")]
+fn subscribe_blocks(
+    state: &rocket::State<State>,
+    start_block: Option<u64>,
+) -> Result<EventStream![], String> {
+    let mut req = mc_mobilecoind_api::SubscribeBlocksRequest::new();
+    req.set_start_block(start_block.unwrap_or(0));
+
+    let mut stream = state
+        .mobilecoind_api_client
+        .subscribe_blocks(&req)
+        .map_err(|err| format!("Failed subscribing to blocks: {}", err))?;
+
+    Ok(EventStream! {
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok(resp) => yield Event::json(&JsonBlockDetailsResponse::from(&resp)),
+                Err(err) => {
+                    yield Event::data(format!("Failed getting block: {}", err)).event("error");
+                    break;
+                }
+            }
+        }
+    })
+}
+
+/// Gets the status of the network, as seen by mobilecoind
+#[get("/ledger/network-status")]
+fn network_status(state: &rocket::State<State>) -> Result<Json<JsonNetworkStatusResponse>, String> {
+    let resp = state
+        .mobilecoind_api_client
+        .get_network_status(&mc_mobilecoind_api::Empty::new())
+        .map_err(|err| format!("Failed getting network status: {}", err))?;
+
+    Ok(Json(JsonNetworkStatusResponse::from(&resp)))
+}
+
 /// Retreives processed block information.
 #[get("/monitors/<monitor_hex>/processed-block/<block_num>")]
 fn processed_block(
@@ -659,6 +1120,62 @@ fn processed_block(
     Ok(Json(JsonProcessedBlockResponse::from(&resp)))
 }
 
+/// Streams the processed block data of a monitor, one event per block,
+/// starting at start_block, as server-sent events.
+#[get("/monitors/<monitor_hex>/events?<start_block>")]
+fn subscribe_monitor_events(
+    state: &rocket::State<State>,
+    monitor_hex: String,
+    start_block: Option<u64>,
+) -> Result<EventStream![], String> {
+    let monitor_id =
+        hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
+
+    let mut req = mc_mobilecoind_api::SubscribeMonitorEventsRequest::new();
+    req.set_monitor_id(monitor_id);
+    req.set_start_block(start_block.unwrap_or(0));
+
+    let mut stream = state
+        .mobilecoind_api_client
+        .subscribe_monitor_events(&req)
+        .map_err(|err| format!("Failed subscribing to monitor events: {}", err))?;
+
+    Ok(EventStream! {
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok(resp) => yield Event::json(&JsonMonitorEventsResponse::from(&resp)),
+                Err(err) => {
+                    yield Event::data(format!("Failed getting monitor events: {}", err))
+                        .event("error");
+                    break;
+                }
+            }
+        }
+    })
+}
+
+/// Gets the transaction history of a monitor, oldest first, with decoded memos.
+#[post(
+    "/monitors/<monitor_hex>/transaction-history",
+    format = "json",
+    data = "<request>"
+)]
+fn transaction_history(
+    state: &rocket::State<State>,
+    monitor_hex: String,
+    request: Json<JsonTransactionHistoryRequest>,
+) -> Result<Json<JsonTransactionHistoryResponse>, String> {
+    let monitor_id =
+        hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
+
+    let resp = state
+        .mobilecoind_api_client
+        .get_transaction_history(&request.to_proto(monitor_id)?)
+        .map_err(|err| format!("Failed getting transaction history: {}", err))?;
+
+    Ok(Json(JsonTransactionHistoryResponse::from(&resp)))
+}
+
 /// Get the block index of a given tx out, identified by its public key.
 #[get("/tx-out/<public_key_hex>/block-index")]
 fn tx_out_get_block_index_by_public_key(
@@ -766,6 +1283,12 @@ fn get_mixins(
     Ok(Json(response))
 }
 
+/// Serves the OpenAPI description of this API.
+#[get("/openapi.json")]
+fn openapi() -> (ContentType, &'static str) {
+    (ContentType::JSON, OPENAPI_SPEC)
+}
+
 #[rocket::main]
 async fn main() -> Result<(), rocket::Error> {
     mc_common::setup_panic_handler();
@@ -809,6 +1332,10 @@ async fn main() -> Result<(), rocket::Error> {
                 remove_monitor,
                 monitors,
                 monitor_status,
+                set_monitor_webhook,
+                monitor_webhook,
+                unverified_txos,
+                import_key_images,
                 balance,
                 utxos,
                 public_address,
@@ -816,19 +1343,32 @@ async fn main() -> Result<(), rocket::Error> {
                 parse_request_code,
                 create_address_code,
                 parse_address_code,
+                create_transfer_code,
+                parse_transfer_code,
                 build_and_submit,
                 pay_address_code,
+                send_payment,
                 generate_request_code_transaction,
+                generate_tx,
+                generate_optimization_tx,
+                generate_transfer_code_tx,
+                generate_burn_redemption_tx,
+                generate_tx_from_tx_out_list,
                 submit_tx,
                 check_transfer_status,
                 check_receiver_transfer_status,
                 ledger_info,
                 block_info,
                 block_details,
+                subscribe_blocks,
+                network_status,
                 processed_block,
+                subscribe_monitor_events,
+                transaction_history,
                 tx_out_get_block_index_by_public_key,
                 get_mixins,
                 get_proof_of_membership,
+                openapi,
             ],
         )
         .manage(State {
@@ -3,13 +3,13 @@
 //! Serializeable data types that wrap the mobilecoind API.
 
 use mc_api::external::{
-    CompressedRistretto, EncryptedFogHint, EncryptedMemo, InputRules, KeyImage, MaskedAmount,
-    PublicAddress, RingMLSAG, SignatureRctBulletproofs, Tx, TxIn, TxOutMembershipElement,
-    TxOutMembershipHash, TxOutMembershipProof, TxPrefix,
+    AccountKey, CompressedRistretto, EncryptedFogHint, EncryptedMemo, InputRules, KeyImage,
+    MaskedAmount, PublicAddress, RingMLSAG, RistrettoPrivate, SignatureRctBulletproofs, Tx, TxIn,
+    TxOutMembershipElement, TxOutMembershipHash, TxOutMembershipProof, TxPrefix,
 };
 use protobuf::RepeatedField;
 use serde_derive::{Deserialize, Serialize};
-use std::convert::TryFrom;
+use std::{collections::BTreeMap, convert::TryFrom};
 
 // Represents u64 using string, when serializing to Json
 // Javascript integers are not 64 bit, and so it is not really proper json.
@@ -103,11 +103,39 @@ impl From<&mc_mobilecoind_api::GetAccountKeyResponse> for JsonAccountKeyResponse
     }
 }
 
+// Helper conversion between json and protobuf
+impl TryFrom<&JsonAccountKeyResponse> for AccountKey {
+    type Error = String;
+
+    fn try_from(src: &JsonAccountKeyResponse) -> Result<AccountKey, String> {
+        let mut view_private_key = RistrettoPrivate::new();
+        view_private_key.set_data(
+            hex::decode(&src.view_private_key)
+                .map_err(|err| format!("Failed to decode view key hex: {}", err))?,
+        );
+        let mut spend_private_key = RistrettoPrivate::new();
+        spend_private_key.set_data(
+            hex::decode(&src.spend_private_key)
+                .map_err(|err| format!("Failed to decode spend key hex: {}", err))?,
+        );
+
+        let mut account_key = AccountKey::new();
+        account_key.set_view_private_key(view_private_key);
+        account_key.set_spend_private_key(spend_private_key);
+
+        Ok(account_key)
+    }
+}
+
 #[derive(Deserialize, Default, Debug)]
 pub struct JsonMonitorRequest {
     pub account_key: JsonAccountKeyResponse,
     pub first_subaddress: u64,
     pub num_subaddresses: u64,
+    /// Block index to start monitoring from (defaults to 0)
+    pub first_block: Option<JsonU64>,
+    /// Optional monitor name
+    pub name: Option<String>,
 }
 
 #[derive(Serialize, Default, Debug)]
@@ -144,6 +172,7 @@ pub struct JsonMonitorStatusResponse {
     pub num_subaddresses: u64,
     pub first_block: u64,
     pub next_block: u64,
+    pub name: String,
 }
 
 impl From<&mc_mobilecoind_api::GetMonitorStatusResponse> for JsonMonitorStatusResponse {
@@ -155,6 +184,36 @@ impl From<&mc_mobilecoind_api::GetMonitorStatusResponse> for JsonMonitorStatusRe
             num_subaddresses: status.get_num_subaddresses(),
             first_block: status.get_first_block(),
             next_block: status.get_next_block(),
+            name: status.get_name().to_owned(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct JsonSetMonitorWebhookRequest {
+    /// The url notifications are POSTed to. Empty to remove the webhook.
+    pub url: String,
+    /// Hex encoded secret used to sign notifications.
+    #[serde(default)]
+    pub secret: String,
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct JsonSetMonitorWebhookResponse {
+    pub success: bool,
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct JsonMonitorWebhookResponse {
+    pub url: String,
+    pub num_pending_notifications: u64,
+}
+
+impl From<&mc_mobilecoind_api::GetMonitorWebhookResponse> for JsonMonitorWebhookResponse {
+    fn from(src: &mc_mobilecoind_api::GetMonitorWebhookResponse) -> Self {
+        Self {
+            url: src.get_url().to_owned(),
+            num_pending_notifications: src.get_num_pending_notifications(),
         }
     }
 }
@@ -181,6 +240,8 @@ pub struct JsonUnspentTxOut {
     pub attempted_spend_height: u64,
     pub attempted_spend_tombstone: u64,
     pub monitor_id: String,
+    #[serde(default)]
+    pub token_id: JsonU64,
 }
 
 impl From<&mc_mobilecoind_api::UnspentTxOut> for JsonUnspentTxOut {
@@ -193,6 +254,7 @@ impl From<&mc_mobilecoind_api::UnspentTxOut> for JsonUnspentTxOut {
             attempted_spend_height: src.get_attempted_spend_height(),
             attempted_spend_tombstone: src.get_attempted_spend_tombstone(),
             monitor_id: hex::encode(&src.get_monitor_id()),
+            token_id: JsonU64(src.get_token_id()),
         }
     }
 }
@@ -223,6 +285,7 @@ impl TryFrom<&JsonUnspentTxOut> for mc_mobilecoind_api::UnspentTxOut {
             hex::decode(&src.monitor_id)
                 .map_err(|err| format!("Failed to decode monitor id hex: {}", err))?,
         );
+        utxo.set_token_id(src.token_id.into());
 
         Ok(utxo)
     }
@@ -245,11 +308,103 @@ impl From<&mc_mobilecoind_api::GetUnspentTxOutListResponse> for JsonUtxosRespons
     }
 }
 
+#[derive(Serialize, Default, Debug)]
+pub struct JsonUnverifiedTxOut {
+    pub tx_out: JsonTxOut,
+    pub subaddress_index: u64,
+    pub value: JsonU64,
+    pub token_id: JsonU64,
+    pub block_index: u64,
+}
+
+impl From<&mc_mobilecoind_api::UnverifiedTxOut> for JsonUnverifiedTxOut {
+    fn from(src: &mc_mobilecoind_api::UnverifiedTxOut) -> Self {
+        Self {
+            tx_out: src.get_tx_out().into(),
+            subaddress_index: src.get_subaddress_index(),
+            value: JsonU64(src.get_value()),
+            token_id: JsonU64(src.get_token_id()),
+            block_index: src.get_block_index(),
+        }
+    }
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct JsonUnverifiedTxOutsResponse {
+    pub output_list: Vec<JsonUnverifiedTxOut>,
+}
+
+impl From<&mc_mobilecoind_api::GetUnverifiedTxOutListResponse> for JsonUnverifiedTxOutsResponse {
+    fn from(src: &mc_mobilecoind_api::GetUnverifiedTxOutListResponse) -> Self {
+        Self {
+            output_list: src
+                .get_output_list()
+                .iter()
+                .map(JsonUnverifiedTxOut::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct JsonTxOutKeyImage {
+    /// Hex encoded public key of the TxOut.
+    pub tx_public_key: String,
+    /// Hex encoded key image of the TxOut.
+    pub key_image: String,
+    /// Proof that the key image belongs to the TxOut.
+    pub proof: JsonRingMLSAG,
+}
+
+impl TryFrom<&JsonTxOutKeyImage> for mc_mobilecoind_api::TxOutKeyImage {
+    type Error = String;
+
+    fn try_from(src: &JsonTxOutKeyImage) -> Result<mc_mobilecoind_api::TxOutKeyImage, String> {
+        let mut tx_public_key = CompressedRistretto::new();
+        tx_public_key.set_data(
+            hex::decode(&src.tx_public_key)
+                .map_err(|err| format!("Failed to decode tx public key hex: {}", err))?,
+        );
+        let mut key_image = KeyImage::new();
+        key_image.set_data(
+            hex::decode(&src.key_image)
+                .map_err(|err| format!("Failed to decode key image hex: {}", err))?,
+        );
+
+        let mut tx_out_key_image = mc_mobilecoind_api::TxOutKeyImage::new();
+        tx_out_key_image.set_tx_public_key(tx_public_key);
+        tx_out_key_image.set_key_image(key_image);
+        tx_out_key_image.set_proof(RingMLSAG::try_from(&src.proof)?);
+        Ok(tx_out_key_image)
+    }
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct JsonImportKeyImagesRequest {
+    pub key_images: Vec<JsonTxOutKeyImage>,
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct JsonImportKeyImagesResponse {
+    pub num_unspent: u64,
+    pub num_spent: u64,
+}
+
+impl From<&mc_mobilecoind_api::ImportKeyImagesResponse> for JsonImportKeyImagesResponse {
+    fn from(src: &mc_mobilecoind_api::ImportKeyImagesResponse) -> Self {
+        Self {
+            num_unspent: src.get_num_unspent(),
+            num_spent: src.get_num_spent(),
+        }
+    }
+}
+
 #[derive(Deserialize, Default, Debug)]
 pub struct JsonCreateRequestCodeRequest {
     pub receiver: JsonPublicAddress,
     pub value: Option<JsonU64>,
     pub memo: Option<String>,
+    pub token_id: Option<JsonU64>,
 }
 
 #[derive(Serialize, Default, Debug)]
@@ -367,6 +522,8 @@ pub struct JsonParseRequestCodeResponse {
     pub receiver: JsonPublicAddress,
     pub value: JsonU64,
     pub memo: String,
+    #[serde(default)]
+    pub token_id: JsonU64,
 }
 
 impl From<&mc_mobilecoind_api::ParseRequestCodeResponse> for JsonParseRequestCodeResponse {
@@ -375,6 +532,7 @@ impl From<&mc_mobilecoind_api::ParseRequestCodeResponse> for JsonParseRequestCod
             receiver: JsonPublicAddress::from(src.get_receiver()),
             value: JsonU64(src.get_value()),
             memo: src.get_memo().to_string(),
+            token_id: JsonU64(src.get_token_id()),
         }
     }
 }
@@ -410,6 +568,75 @@ impl From<&mc_mobilecoind_api::ParseAddressCodeResponse> for JsonParseAddressCod
     }
 }
 
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct JsonCreateTransferCodeRequest {
+    /// Hex encoded compressed ristretto bytes of the TxOut holding the funds
+    pub tx_public_key: String,
+
+    /// Hex encoded entropy of the account key that can spend the funds
+    pub bip39_entropy: String,
+
+    pub memo: Option<String>,
+}
+
+impl TryFrom<&JsonCreateTransferCodeRequest> for mc_mobilecoind_api::CreateTransferCodeRequest {
+    type Error = String;
+
+    fn try_from(src: &JsonCreateTransferCodeRequest) -> Result<Self, String> {
+        let mut tx_public_key = CompressedRistretto::new();
+        tx_public_key.set_data(
+            hex::decode(&src.tx_public_key)
+                .map_err(|err| format!("Failed to decode tx public key hex: {}", err))?,
+        );
+
+        let mut req = mc_mobilecoind_api::CreateTransferCodeRequest::new();
+        req.set_tx_public_key(tx_public_key);
+        req.set_bip39_entropy(
+            hex::decode(&src.bip39_entropy)
+                .map_err(|err| format!("Failed to decode bip39 entropy hex: {}", err))?,
+        );
+        if let Some(memo) = src.memo.clone() {
+            req.set_memo(memo);
+        }
+
+        Ok(req)
+    }
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct JsonCreateTransferCodeResponse {
+    pub b58_code: String,
+}
+
+impl From<&mc_mobilecoind_api::CreateTransferCodeResponse> for JsonCreateTransferCodeResponse {
+    fn from(src: &mc_mobilecoind_api::CreateTransferCodeResponse) -> Self {
+        Self {
+            b58_code: String::from(src.get_b58_code()),
+        }
+    }
+}
+
+/// The contents of a transfer code. The deprecated root entropy is not
+/// exposed, transfer codes created by current clients use bip39 entropy.
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct JsonParseTransferCodeResponse {
+    pub tx_public_key: String,
+    pub memo: String,
+    pub utxo: JsonUnspentTxOut,
+    pub bip39_entropy: String,
+}
+
+impl From<&mc_mobilecoind_api::ParseTransferCodeResponse> for JsonParseTransferCodeResponse {
+    fn from(src: &mc_mobilecoind_api::ParseTransferCodeResponse) -> Self {
+        Self {
+            tx_public_key: hex::encode(&src.get_tx_public_key().get_data()),
+            memo: src.get_memo().to_string(),
+            utxo: src.get_utxo().into(),
+            bip39_entropy: hex::encode(&src.get_bip39_entropy()),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Default, Debug)]
 pub struct JsonSenderTxReceipt {
     pub key_images: Vec<String>,
@@ -455,12 +682,32 @@ pub struct JsonSendPaymentRequest {
     pub request_data: JsonParseRequestCodeResponse,
     pub max_input_utxo_value: Option<JsonU64>,
     pub change_subaddress: Option<JsonU64>,
+    pub fee: Option<JsonU64>,
+    pub tombstone: Option<JsonU64>,
+    pub utxo_selection_strategy: Option<String>,
+}
+
+/// A payment to any number of outlays, possibly in several token ids.
+/// Mirrors SendPaymentRequest.
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct JsonSendPaymentV2Request {
+    #[serde(default)]
+    pub outlay_list: Vec<JsonOutlay>,
+    #[serde(default)]
+    pub outlay_list_v2: Vec<JsonOutlayV2>,
+    pub max_input_utxo_value: Option<JsonU64>,
+    pub change_subaddress: Option<JsonU64>,
+    pub fee: Option<JsonU64>,
+    pub tombstone: Option<JsonU64>,
+    pub token_id: Option<JsonU64>,
+    pub utxo_selection_strategy: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Default, Debug)]
 pub struct JsonSendPaymentResponse {
     pub sender_tx_receipt: JsonSenderTxReceipt,
     pub receiver_tx_receipt_list: Vec<JsonReceiverTxReceipt>,
+    pub tx_proposal: Option<JsonTxProposal>,
 }
 
 impl From<&mc_mobilecoind_api::SendPaymentResponse> for JsonSendPaymentResponse {
@@ -472,6 +719,7 @@ impl From<&mc_mobilecoind_api::SendPaymentResponse> for JsonSendPaymentResponse
                 .iter()
                 .map(JsonReceiverTxReceipt::from)
                 .collect(),
+            tx_proposal: src.tx_proposal.as_ref().map(JsonTxProposal::from),
         }
     }
 }
@@ -482,6 +730,25 @@ pub struct JsonPayAddressCodeRequest {
     pub value: JsonU64,
     pub max_input_utxo_value: Option<JsonU64>,
     pub change_subaddress: Option<JsonU64>,
+    pub fee: Option<JsonU64>,
+    pub tombstone: Option<JsonU64>,
+    pub token_id: Option<JsonU64>,
+    pub utxo_selection_strategy: Option<String>,
+}
+
+/// Parse the name of a UtxoSelectionStrategy, as used in the json API.
+pub fn parse_utxo_selection_strategy(
+    src: &str,
+) -> Result<mc_mobilecoind_api::UtxoSelectionStrategy, String> {
+    use mc_mobilecoind_api::UtxoSelectionStrategy;
+    match src {
+        "daemon_default" => Ok(UtxoSelectionStrategy::DaemonDefault),
+        "smallest_first" => Ok(UtxoSelectionStrategy::SmallestFirst),
+        "largest_first" => Ok(UtxoSelectionStrategy::LargestFirst),
+        "branch_and_bound" => Ok(UtxoSelectionStrategy::BranchAndBound),
+        "random" => Ok(UtxoSelectionStrategy::Random),
+        _ => Err(format!("Unknown utxo selection strategy: {}", src)),
+    }
 }
 
 #[derive(Deserialize, Serialize, Default, Debug, Clone)]
@@ -514,6 +781,39 @@ impl TryFrom<&JsonOutlay> for mc_mobilecoind_api::Outlay {
     }
 }
 
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct JsonOutlayV2 {
+    pub value: JsonU64,
+    pub receiver: JsonPublicAddress,
+    pub token_id: JsonU64,
+}
+
+impl From<&mc_mobilecoind_api::OutlayV2> for JsonOutlayV2 {
+    fn from(src: &mc_mobilecoind_api::OutlayV2) -> Self {
+        Self {
+            value: JsonU64(src.get_value()),
+            receiver: src.get_receiver().into(),
+            token_id: JsonU64(src.get_token_id()),
+        }
+    }
+}
+
+impl TryFrom<&JsonOutlayV2> for mc_mobilecoind_api::OutlayV2 {
+    type Error = String;
+
+    fn try_from(src: &JsonOutlayV2) -> Result<mc_mobilecoind_api::OutlayV2, String> {
+        let mut outlay = mc_mobilecoind_api::OutlayV2::new();
+        outlay.set_value(src.value.into());
+        outlay.set_receiver(
+            PublicAddress::try_from(&src.receiver)
+                .map_err(|err| format!("Could not convert receiver: {}", err))?,
+        );
+        outlay.set_token_id(src.token_id.into());
+
+        Ok(outlay)
+    }
+}
+
 #[derive(Deserialize, Serialize, Default, Debug, Clone)]
 pub struct JsonMaskedAmount {
     pub commitment: String,
@@ -874,6 +1174,40 @@ impl From<&RingMLSAG> for JsonRingMLSAG {
     }
 }
 
+impl TryFrom<&JsonRingMLSAG> for RingMLSAG {
+    type Error = String;
+
+    fn try_from(src: &JsonRingMLSAG) -> Result<RingMLSAG, String> {
+        let mut c_zero = mc_api::external::CurveScalar::new();
+        c_zero.set_data(
+            hex::decode(&src.c_zero)
+                .map_err(|err| format!("Could not decode from hex: {}", err))?,
+        );
+
+        let mut responses: Vec<mc_api::external::CurveScalar> = Vec::new();
+        for resp in &src.responses {
+            let mut response = mc_api::external::CurveScalar::new();
+            response.set_data(
+                hex::decode(resp).map_err(|err| format!("Could not decode from hex: {}", err))?,
+            );
+            responses.push(response);
+        }
+
+        let mut key_image = KeyImage::new();
+        key_image.set_data(
+            hex::decode(&src.key_image)
+                .map_err(|err| format!("Could not decode from hex: {}", err))?,
+        );
+
+        let mut ring_sig = RingMLSAG::new();
+        ring_sig.set_c_zero(c_zero);
+        ring_sig.set_responses(RepeatedField::from_vec(responses));
+        ring_sig.set_key_image(key_image);
+
+        Ok(ring_sig)
+    }
+}
+
 #[derive(Deserialize, Serialize, Default, Debug, Clone)]
 pub struct JsonSignatureRctBulletproofs {
     pub ring_signatures: Vec<JsonRingMLSAG>,
@@ -911,34 +1245,7 @@ impl TryFrom<&JsonSignatureRctBulletproofs> for SignatureRctBulletproofs {
     fn try_from(src: &JsonSignatureRctBulletproofs) -> Result<SignatureRctBulletproofs, String> {
         let mut ring_sigs: Vec<RingMLSAG> = Vec::new();
         for sig in &src.ring_signatures {
-            let mut c_zero = mc_api::external::CurveScalar::new();
-            c_zero.set_data(
-                hex::decode(&sig.c_zero)
-                    .map_err(|err| format!("Could not decode from hex: {}", err))?,
-            );
-
-            let mut responses: Vec<mc_api::external::CurveScalar> = Vec::new();
-            for resp in &sig.responses {
-                let mut response = mc_api::external::CurveScalar::new();
-                response.set_data(
-                    hex::decode(resp)
-                        .map_err(|err| format!("Could not decode from hex: {}", err))?,
-                );
-                responses.push(response);
-            }
-
-            let mut key_image = KeyImage::new();
-            key_image.set_data(
-                hex::decode(&sig.key_image)
-                    .map_err(|err| format!("Could not decode from hex: {}", err))?,
-            );
-
-            let mut ring_sig = RingMLSAG::new();
-            ring_sig.set_c_zero(c_zero);
-            ring_sig.set_responses(RepeatedField::from_vec(responses));
-            ring_sig.set_key_image(key_image);
-
-            ring_sigs.push(ring_sig);
+            ring_sigs.push(RingMLSAG::try_from(sig)?);
         }
 
         let mut commitments: Vec<CompressedRistretto> = Vec::new();
@@ -1025,6 +1332,8 @@ pub struct JsonTxProposal {
     pub fee: u64,
     pub outlay_index_to_tx_out_index: Vec<(usize, usize)>,
     pub outlay_confirmation_numbers: Vec<Vec<u8>>,
+    #[serde(default)]
+    pub outlay_list_v2: Vec<JsonOutlayV2>,
 }
 
 impl From<&mc_mobilecoind_api::TxProposal> for JsonTxProposal {
@@ -1045,6 +1354,11 @@ impl From<&mc_mobilecoind_api::TxProposal> for JsonTxProposal {
             fee: src.get_fee(),
             outlay_index_to_tx_out_index: outlay_map,
             outlay_confirmation_numbers: src.get_outlay_confirmation_numbers().to_vec(),
+            outlay_list_v2: src
+                .get_outlay_list_v2()
+                .iter()
+                .map(JsonOutlayV2::from)
+                .collect(),
         }
     }
 }
@@ -1068,6 +1382,13 @@ impl TryFrom<&JsonTxProposal> for mc_mobilecoind_api::TxProposal {
             outlays.push(out);
         }
 
+        let mut outlays_v2: Vec<mc_mobilecoind_api::OutlayV2> = Vec::new();
+        for outlay in src.outlay_list_v2.iter() {
+            let out = mc_mobilecoind_api::OutlayV2::try_from(outlay)
+                .map_err(|err| format!("Failed to convert outlay: {}", err))?;
+            outlays_v2.push(out);
+        }
+
         // Reconstruct the public address as a protobuf
         let mut proposal = mc_mobilecoind_api::TxProposal::new();
         proposal.set_input_list(RepeatedField::from_vec(inputs));
@@ -1084,6 +1405,7 @@ impl TryFrom<&JsonTxProposal> for mc_mobilecoind_api::TxProposal {
         proposal.set_outlay_confirmation_numbers(RepeatedField::from_vec(
             src.outlay_confirmation_numbers.clone(),
         ));
+        proposal.set_outlay_list_v2(RepeatedField::from_vec(outlays_v2));
 
         Ok(proposal)
     }
@@ -1108,6 +1430,108 @@ impl From<&mc_mobilecoind_api::GenerateTxResponse> for JsonCreateTxProposalRespo
     }
 }
 
+impl From<&mc_mobilecoind_api::GenerateOptimizationTxResponse> for JsonCreateTxProposalResponse {
+    fn from(src: &mc_mobilecoind_api::GenerateOptimizationTxResponse) -> Self {
+        Self {
+            tx_proposal: src.get_tx_proposal().into(),
+        }
+    }
+}
+
+impl From<&mc_mobilecoind_api::GenerateTxFromTxOutListResponse> for JsonCreateTxProposalResponse {
+    fn from(src: &mc_mobilecoind_api::GenerateTxFromTxOutListResponse) -> Self {
+        Self {
+            tx_proposal: src.get_tx_proposal().into(),
+        }
+    }
+}
+
+impl From<&mc_mobilecoind_api::GenerateBurnRedemptionTxResponse> for JsonCreateTxProposalResponse {
+    fn from(src: &mc_mobilecoind_api::GenerateBurnRedemptionTxResponse) -> Self {
+        Self {
+            tx_proposal: src.get_tx_proposal().into(),
+        }
+    }
+}
+
+/// Mirrors GenerateTxRequest. Either outlay_list or outlay_list_v2 should be
+/// set.
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct JsonGenerateTxRequest {
+    pub input_list: Vec<JsonUnspentTxOut>,
+    #[serde(default)]
+    pub outlay_list: Vec<JsonOutlay>,
+    #[serde(default)]
+    pub outlay_list_v2: Vec<JsonOutlayV2>,
+    pub fee: Option<JsonU64>,
+    pub tombstone: Option<JsonU64>,
+    pub token_id: Option<JsonU64>,
+    pub utxo_selection_strategy: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct JsonGenerateOptimizationTxRequest {
+    pub fee: Option<JsonU64>,
+    pub token_id: Option<JsonU64>,
+}
+
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct JsonGenerateTransferCodeTxRequest {
+    pub input_list: Vec<JsonUnspentTxOut>,
+    pub value: JsonU64,
+    pub fee: Option<JsonU64>,
+    pub tombstone: Option<JsonU64>,
+    pub memo: Option<String>,
+    pub token_id: Option<JsonU64>,
+}
+
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct JsonGenerateTransferCodeTxResponse {
+    pub tx_proposal: JsonTxProposal,
+    pub tx_public_key: String,
+    pub memo: String,
+    pub b58_code: String,
+    pub bip39_entropy: String,
+}
+
+impl From<&mc_mobilecoind_api::GenerateTransferCodeTxResponse>
+    for JsonGenerateTransferCodeTxResponse
+{
+    fn from(src: &mc_mobilecoind_api::GenerateTransferCodeTxResponse) -> Self {
+        Self {
+            tx_proposal: src.get_tx_proposal().into(),
+            tx_public_key: hex::encode(&src.get_tx_public_key().get_data()),
+            memo: src.get_memo().to_string(),
+            b58_code: src.get_b58_code().to_string(),
+            bip39_entropy: hex::encode(&src.get_bip39_entropy()),
+        }
+    }
+}
+
+/// Spends a list of TxOuts owned by the given account key, without a monitor.
+/// All of the inputs (minus the fee) go to the receiver.
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct JsonGenerateTxFromTxOutListRequest {
+    pub account_key: JsonAccountKeyResponse,
+    pub input_list: Vec<JsonUnspentTxOut>,
+    pub receiver: JsonPublicAddress,
+    pub fee: Option<JsonU64>,
+    pub token_id: Option<JsonU64>,
+}
+
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct JsonGenerateBurnRedemptionTxRequest {
+    pub input_list: Vec<JsonUnspentTxOut>,
+    pub burn_amount: JsonU64,
+    pub fee: Option<JsonU64>,
+    pub tombstone: Option<JsonU64>,
+    pub token_id: Option<JsonU64>,
+    /// Hex encoded 64 bytes to include in the burn redemption memo
+    pub redemption_memo: Option<String>,
+    #[serde(default)]
+    pub enable_destination_memo: bool,
+}
+
 #[derive(Deserialize, Serialize, Default, Debug)]
 pub struct JsonTxProposalRequest {
     pub tx_proposal: JsonTxProposal,
@@ -1313,23 +1737,31 @@ pub struct JsonProcessedTxOut {
     pub key_image: String,
     pub value: JsonU64,
     pub direction: String,
+    pub address_code: String,
+    pub token_id: JsonU64,
+    pub memo_payload: String,
+}
+
+fn direction_str(direction: mc_mobilecoind_api::ProcessedTxOutDirection) -> &'static str {
+    match direction {
+        mc_mobilecoind_api::ProcessedTxOutDirection::Invalid => "invalid",
+        mc_mobilecoind_api::ProcessedTxOutDirection::Received => "received",
+        mc_mobilecoind_api::ProcessedTxOutDirection::Spent => "spent",
+    }
 }
 
 impl From<&mc_mobilecoind_api::ProcessedTxOut> for JsonProcessedTxOut {
     fn from(src: &mc_mobilecoind_api::ProcessedTxOut) -> Self {
-        let direction_str = match src.direction {
-            mc_mobilecoind_api::ProcessedTxOutDirection::Invalid => "invalid",
-            mc_mobilecoind_api::ProcessedTxOutDirection::Received => "received",
-            mc_mobilecoind_api::ProcessedTxOutDirection::Spent => "spent",
-        };
-
         Self {
             monitor_id: hex::encode(&src.get_monitor_id()),
             subaddress_index: src.subaddress_index,
             public_key: hex::encode(&src.get_public_key().get_data()),
             key_image: hex::encode(&src.get_key_image().get_data()),
             value: JsonU64(src.value),
-            direction: direction_str.to_owned(),
+            direction: direction_str(src.direction).to_owned(),
+            address_code: src.get_address_code().to_owned(),
+            token_id: JsonU64(src.token_id),
+            memo_payload: hex::encode(&src.get_memo_payload()),
         }
     }
 }
@@ -1366,6 +1798,209 @@ impl From<&mc_mobilecoind_api::GetBlockIndexByTxPubKeyResponse>
     }
 }
 
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct JsonTransactionHistoryRequest {
+    pub start_block: Option<JsonU64>,
+    /// Exclusive upper bound, no upper bound if not set
+    pub end_block: Option<JsonU64>,
+    #[serde(default)]
+    pub subaddress_indices: Vec<JsonU64>,
+    #[serde(default)]
+    pub token_ids: Vec<JsonU64>,
+    /// Hex encoded short address hashes
+    #[serde(default)]
+    pub counterparty_address_hashes: Vec<String>,
+    /// Addresses used to authenticate sender memos
+    #[serde(default)]
+    pub known_addresses: Vec<JsonPublicAddress>,
+    pub page_size: Option<u32>,
+    /// The next_page_token of a previous response
+    pub page_token: Option<String>,
+}
+
+impl JsonTransactionHistoryRequest {
+    /// Build the protobuf request for a given monitor.
+    pub fn to_proto(
+        &self,
+        monitor_id: Vec<u8>,
+    ) -> Result<mc_mobilecoind_api::GetTransactionHistoryRequest, String> {
+        let counterparty_address_hashes = self
+            .counterparty_address_hashes
+            .iter()
+            .map(|hash| {
+                hex::decode(hash)
+                    .map_err(|err| format!("Failed to decode address hash hex: {}", err))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let known_addresses = self
+            .known_addresses
+            .iter()
+            .map(PublicAddress::try_from)
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mut req = mc_mobilecoind_api::GetTransactionHistoryRequest::new();
+        req.set_monitor_id(monitor_id);
+        req.set_start_block(self.start_block.map(u64::from).unwrap_or(0));
+        req.set_end_block(self.end_block.map(u64::from).unwrap_or(0));
+        req.set_subaddress_indices(self.subaddress_indices.iter().map(u64::from).collect());
+        req.set_token_ids(self.token_ids.iter().map(u64::from).collect());
+        req.set_counterparty_address_hashes(RepeatedField::from_vec(counterparty_address_hashes));
+        req.set_known_addresses(RepeatedField::from_vec(known_addresses));
+        req.set_page_size(self.page_size.unwrap_or(0));
+        if let Some(page_token) = self.page_token.as_ref() {
+            req.set_page_token(
+                hex::decode(page_token)
+                    .map_err(|err| format!("Failed to decode page token hex: {}", err))?,
+            );
+        }
+
+        Ok(req)
+    }
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct JsonAuthenticatedSenderMemo {
+    pub sender_address_hash: String,
+    pub payment_request_id: JsonU64,
+}
+
+impl From<&mc_mobilecoind_api::AuthenticatedSenderMemo> for JsonAuthenticatedSenderMemo {
+    fn from(src: &mc_mobilecoind_api::AuthenticatedSenderMemo) -> Self {
+        Self {
+            sender_address_hash: hex::encode(src.get_sender_address_hash()),
+            payment_request_id: JsonU64(src.payment_request_id),
+        }
+    }
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct JsonDestinationMemo {
+    pub recipient_address_hash: String,
+    pub num_recipients: u32,
+    pub fee: JsonU64,
+    pub total_outlay: JsonU64,
+}
+
+impl From<&mc_mobilecoind_api::DestinationMemo> for JsonDestinationMemo {
+    fn from(src: &mc_mobilecoind_api::DestinationMemo) -> Self {
+        Self {
+            recipient_address_hash: hex::encode(src.get_recipient_address_hash()),
+            num_recipients: src.num_recipients,
+            fee: JsonU64(src.fee),
+            total_outlay: JsonU64(src.total_outlay),
+        }
+    }
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct JsonTransactionHistoryEntry {
+    pub block: JsonU64,
+    pub subaddress_index: u64,
+    pub public_key: String,
+    pub key_image: String,
+    pub value: JsonU64,
+    pub token_id: JsonU64,
+    pub direction: String,
+    pub memo_payload: String,
+    pub counterparty_address_hash: String,
+    pub authenticated_sender_memo: Option<JsonAuthenticatedSenderMemo>,
+    pub destination_memo: Option<JsonDestinationMemo>,
+    pub memo_validation_status: String,
+}
+
+impl From<&mc_mobilecoind_api::TransactionHistoryEntry> for JsonTransactionHistoryEntry {
+    fn from(src: &mc_mobilecoind_api::TransactionHistoryEntry) -> Self {
+        let memo_validation_status = match src.memo_validation_status {
+            mc_mobilecoind_api::MemoValidationStatus::NotAuthenticated => "not_authenticated",
+            mc_mobilecoind_api::MemoValidationStatus::Authenticated => "authenticated",
+            mc_mobilecoind_api::MemoValidationStatus::AuthenticationFailed => {
+                "authentication_failed"
+            }
+            mc_mobilecoind_api::MemoValidationStatus::UnknownSender => "unknown_sender",
+        };
+
+        Self {
+            block: JsonU64(src.block),
+            subaddress_index: src.subaddress_index,
+            public_key: hex::encode(&src.get_public_key().get_data()),
+            key_image: hex::encode(&src.get_key_image().get_data()),
+            value: JsonU64(src.value),
+            token_id: JsonU64(src.token_id),
+            direction: direction_str(src.direction).to_owned(),
+            memo_payload: hex::encode(&src.get_memo_payload()),
+            counterparty_address_hash: hex::encode(&src.get_counterparty_address_hash()),
+            authenticated_sender_memo: src
+                .authenticated_sender_memo
+                .as_ref()
+                .map(JsonAuthenticatedSenderMemo::from),
+            destination_memo: src.destination_memo.as_ref().map(JsonDestinationMemo::from),
+            memo_validation_status: memo_validation_status.to_owned(),
+        }
+    }
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct JsonTransactionHistoryResponse {
+    pub entries: Vec<JsonTransactionHistoryEntry>,
+    /// Empty when there are no more entries
+    pub next_page_token: String,
+}
+
+impl From<&mc_mobilecoind_api::GetTransactionHistoryResponse> for JsonTransactionHistoryResponse {
+    fn from(src: &mc_mobilecoind_api::GetTransactionHistoryResponse) -> Self {
+        Self {
+            entries: src
+                .get_entries()
+                .iter()
+                .map(JsonTransactionHistoryEntry::from)
+                .collect(),
+            next_page_token: hex::encode(src.get_next_page_token()),
+        }
+    }
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct JsonNetworkStatusResponse {
+    pub network_highest_block_index: JsonU64,
+    pub peer_block_index_map: BTreeMap<String, JsonU64>,
+    pub local_block_index: JsonU64,
+    pub is_behind: bool,
+}
+
+impl From<&mc_mobilecoind_api::GetNetworkStatusResponse> for JsonNetworkStatusResponse {
+    fn from(src: &mc_mobilecoind_api::GetNetworkStatusResponse) -> Self {
+        Self {
+            network_highest_block_index: JsonU64(src.network_highest_block_index),
+            peer_block_index_map: src
+                .get_peer_block_index_map()
+                .iter()
+                .map(|(responder_id, block_index)| (responder_id.clone(), JsonU64(*block_index)))
+                .collect(),
+            local_block_index: JsonU64(src.local_block_index),
+            is_behind: src.is_behind,
+        }
+    }
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct JsonMonitorEventsResponse {
+    pub block: JsonU64,
+    pub tx_outs: Vec<JsonProcessedTxOut>,
+}
+
+impl From<&mc_mobilecoind_api::SubscribeMonitorEventsResponse> for JsonMonitorEventsResponse {
+    fn from(src: &mc_mobilecoind_api::SubscribeMonitorEventsResponse) -> Self {
+        Self {
+            block: JsonU64(src.block),
+            tx_outs: src
+                .get_tx_outs()
+                .iter()
+                .map(JsonProcessedTxOut::from)
+                .collect(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1380,6 +2015,7 @@ mod test {
     };
     use mc_util_from_random::FromRandom;
     use rand::{rngs::StdRng, SeedableRng};
+    use rocket::serde::json::serde_json;
     use std::{collections::HashMap, iter::FromIterator};
 
     /// Test conversion of TxProposal
@@ -1546,4 +2182,65 @@ mod test {
         );
         assert_eq!(proto_proposal, proto2);
     }
+
+    /// Test that token ids survive the conversion of UnspentTxOut and OutlayV2
+    #[test]
+    fn test_token_id_conversion() {
+        let mut rng: StdRng = SeedableRng::from_seed([2u8; 32]);
+
+        let amount = Amount {
+            value: 1000,
+            token_id: 3.into(),
+        };
+        let tx_out = mc_transaction_core::tx::TxOut {
+            masked_amount: MaskedAmount::new(amount, &RistrettoPublic::from_random(&mut rng))
+                .unwrap(),
+            target_key: RistrettoPublic::from_random(&mut rng).into(),
+            public_key: RistrettoPublic::from_random(&mut rng).into(),
+            e_fog_hint: (&[0u8; ENCRYPTED_FOG_HINT_LEN]).into(),
+            e_memo: Some(Default::default()),
+        };
+
+        let mut unspent = mc_mobilecoind_api::UnspentTxOut::new();
+        unspent.set_tx_out(mc_api::external::TxOut::from(&tx_out));
+        unspent.set_key_image(mc_api::external::KeyImage::from(
+            &mc_transaction_core::ring_signature::KeyImage::from(7),
+        ));
+        unspent.set_value(amount.value);
+        unspent.set_token_id(*amount.token_id);
+
+        let json_unspent = JsonUnspentTxOut::from(&unspent);
+        assert_eq!(json_unspent.token_id.0, 3);
+        assert_eq!(
+            mc_mobilecoind_api::UnspentTxOut::try_from(&json_unspent).unwrap(),
+            unspent
+        );
+
+        let mut outlay = mc_mobilecoind_api::OutlayV2::new();
+        let public_addr = AccountKey::random(&mut rng).default_subaddress();
+        outlay.set_receiver(mc_api::external::PublicAddress::from(&public_addr));
+        outlay.set_value(1234);
+        outlay.set_token_id(3);
+
+        let json_outlay = JsonOutlayV2::from(&outlay);
+        assert_eq!(
+            mc_mobilecoind_api::OutlayV2::try_from(&json_outlay).unwrap(),
+            outlay
+        );
+
+        // UnspentTxOuts serialized before token ids were supported are MOB.
+        let mut json = serde_json::to_value(&json_unspent).unwrap();
+        json.as_object_mut().unwrap().remove("token_id");
+        let old_unspent: JsonUnspentTxOut = serde_json::from_value(json).unwrap();
+        assert_eq!(old_unspent.token_id.0, 0);
+    }
+
+    #[test]
+    fn test_parse_utxo_selection_strategy() {
+        assert_eq!(
+            parse_utxo_selection_strategy("branch_and_bound").unwrap(),
+            mc_mobilecoind_api::UtxoSelectionStrategy::BranchAndBound
+        );
+        assert!(parse_utxo_selection_strategy("biggest").is_err());
+    }
 }
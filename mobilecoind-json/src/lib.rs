@@ -3,3 +3,38 @@
 //! JSON wrapper for the mobilecoind API.
 
 pub mod data_types;
+
+/// The OpenAPI description of the JSON API, served at `/openapi.json`.
+pub const OPENAPI_SPEC: &str = include_str!("../openapi.json");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rocket::serde::json::{serde_json, Value};
+
+    /// Every mobilecoind RPC should be wrapped by at least one operation of the
+    /// OpenAPI description.
+    #[test]
+    fn openapi_spec_covers_every_rpc() {
+        let spec: Value = serde_json::from_str(OPENAPI_SPEC).unwrap();
+        let wrapped_rpcs: Vec<&str> = spec["paths"]
+            .as_object()
+            .unwrap()
+            .values()
+            .flat_map(|path| path.as_object().unwrap().values())
+            .filter_map(|operation| operation["x-mobilecoind-rpc"].as_str())
+            .collect();
+
+        let proto = include_str!("../../mobilecoind/api/proto/mobilecoind_api.proto");
+        for line in proto.lines() {
+            if let Some(rpc) = line.trim().strip_prefix("rpc ") {
+                let name = rpc.split(|c: char| c == ' ' || c == '(').next().unwrap();
+                assert!(
+                    wrapped_rpcs.contains(&name),
+                    "{} is missing from openapi.json",
+                    name
+                );
+            }
+        }
+    }
+}
@@ -10,6 +10,7 @@ use mc_ledger_db::{Ledger, LedgerDB};
 use mc_ledger_sync::{LedgerSyncServiceThread, PollingNetworkState, ReqwestTransactionsFetcher};
 use mc_mobilecoind::{
     config::Config, database::Database, payments::TransactionsManager, service::Service,
+    webhooks::WebhookDeliveryThread,
 };
 use mc_util_telemetry::setup_default_tracer;
 use mc_watcher::{watcher::WatcherSyncThread, watcher_db::create_or_open_rw_watcher_db};
@@ -125,11 +126,19 @@ fn main() {
             let mobilecoind_db = Database::new(mobilecoind_db, logger.clone())
                 .expect("Could not open mobilecoind_db");
 
+            let _webhook_delivery_thread = WebhookDeliveryThread::start(
+                mobilecoind_db.clone(),
+                config.webhook_timeout,
+                config.webhook_max_attempts,
+                logger.clone(),
+            );
+
             let transactions_manager = TransactionsManager::new(
                 ledger_db.clone(),
                 mobilecoind_db.clone(),
                 peer_manager,
                 config.get_fog_resolver_factory(logger.clone()),
+                config.utxo_selection_strategy,
                 logger.clone(),
             );
 
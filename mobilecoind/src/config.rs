@@ -3,6 +3,7 @@
 
 //! Configuration parameters for mobilecoind
 
+use crate::utxo_selection::UtxoSelectionStrategyKind;
 use clap::Parser;
 use displaydoc::Display;
 use mc_attest_verifier::{MrSignerVerifier, Verifier, DEBUG_ENCLAVE};
@@ -103,6 +104,25 @@ pub struct Config {
     /// An authorization token for the ipinfo.io service, if available
     #[clap(long, env = "MC_IP_INFO_TOKEN", default_value = "")]
     pub ip_info_token: String,
+
+    /// Strategy for selecting the UTXOs spent by a transaction, used when a
+    /// request does not specify one.
+    #[clap(
+        arg_enum,
+        long,
+        default_value = "smallest-first",
+        env = "MC_UTXO_SELECTION_STRATEGY"
+    )]
+    pub utxo_selection_strategy: UtxoSelectionStrategyKind,
+
+    /// How many seconds to wait for a webhook receiver to respond.
+    #[clap(long, default_value = "10", parse(try_from_str = parse_duration_in_seconds), env = "MC_WEBHOOK_TIMEOUT")]
+    pub webhook_timeout: Duration,
+
+    /// Number of attempts after which the delivery of a webhook notification
+    /// is given up.
+    #[clap(long, default_value = "20", env = "MC_WEBHOOK_MAX_ATTEMPTS")]
+    pub webhook_max_attempts: u32,
 }
 
 fn parse_quorum_set_from_json(src: &str) -> Result<QuorumSet<ResponderId>, String> {
@@ -4,16 +4,21 @@
 //! types.
 
 use crate::{
-    payments::{Outlay, TxProposal},
+    history_store::MemoValidation,
+    payments::{Outlay, OutlayV2, TxProposal},
+    unverified_tx_out_store::UnverifiedTxOut,
+    utxo_selection::UtxoSelectionStrategyKind,
     utxo_store::UnspentTxOut,
 };
 use mc_account_keys::PublicAddress;
 use mc_api::ConversionError;
 use mc_common::HashMap;
+use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_mobilecoind_api::{self};
 use mc_transaction_core::{
-    ring_signature::KeyImage,
+    ring_signature::{KeyImage, RingMLSAG},
     tx::{Tx, TxOut, TxOutConfirmationNumber},
+    TokenId, TxOutKeyImage,
 };
 use protobuf::RepeatedField;
 use std::convert::TryFrom;
@@ -58,6 +63,48 @@ impl TryFrom<&mc_mobilecoind_api::UnspentTxOut> for UnspentTxOut {
     }
 }
 
+impl From<&UnverifiedTxOut> for mc_mobilecoind_api::UnverifiedTxOut {
+    fn from(src: &UnverifiedTxOut) -> Self {
+        let mut dst = Self::new();
+
+        dst.set_tx_out((&src.tx_out).into());
+        dst.set_subaddress_index(src.subaddress_index);
+        dst.set_value(src.value);
+        dst.set_token_id(src.token_id);
+        dst.set_block_index(src.block_index);
+
+        dst
+    }
+}
+
+impl From<&TxOutKeyImage> for mc_mobilecoind_api::TxOutKeyImage {
+    fn from(src: &TxOutKeyImage) -> Self {
+        let mut dst = Self::new();
+
+        dst.set_tx_public_key((&src.tx_public_key).into());
+        dst.set_key_image((&src.key_image).into());
+        dst.set_proof((&src.proof).into());
+
+        dst
+    }
+}
+
+impl TryFrom<&mc_mobilecoind_api::TxOutKeyImage> for TxOutKeyImage {
+    type Error = ConversionError;
+
+    fn try_from(src: &mc_mobilecoind_api::TxOutKeyImage) -> Result<Self, Self::Error> {
+        let tx_public_key = CompressedRistrettoPublic::try_from(src.get_tx_public_key())?;
+        let key_image = KeyImage::try_from(src.get_key_image())?;
+        let proof = RingMLSAG::try_from(src.get_proof())?;
+
+        Ok(Self {
+            tx_public_key,
+            key_image,
+            proof,
+        })
+    }
+}
+
 impl From<&Outlay> for mc_mobilecoind_api::Outlay {
     fn from(src: &Outlay) -> Self {
         let mut dst = Self::new();
@@ -80,6 +127,78 @@ impl TryFrom<&mc_mobilecoind_api::Outlay> for Outlay {
     }
 }
 
+impl From<&OutlayV2> for mc_mobilecoind_api::OutlayV2 {
+    fn from(src: &OutlayV2) -> Self {
+        let mut dst = Self::new();
+
+        dst.set_value(src.value);
+        dst.set_receiver((&src.receiver).into());
+        dst.set_token_id(*src.token_id);
+
+        dst
+    }
+}
+
+impl TryFrom<&mc_mobilecoind_api::OutlayV2> for OutlayV2 {
+    type Error = ConversionError;
+
+    fn try_from(src: &mc_mobilecoind_api::OutlayV2) -> Result<Self, Self::Error> {
+        let value = src.value;
+        let receiver = PublicAddress::try_from(src.get_receiver())?;
+        let token_id = TokenId::from(src.token_id);
+
+        Ok(Self {
+            value,
+            receiver,
+            token_id,
+        })
+    }
+}
+
+impl From<UtxoSelectionStrategyKind> for mc_mobilecoind_api::UtxoSelectionStrategy {
+    fn from(src: UtxoSelectionStrategyKind) -> Self {
+        match src {
+            UtxoSelectionStrategyKind::SmallestFirst => Self::SmallestFirst,
+            UtxoSelectionStrategyKind::LargestFirst => Self::LargestFirst,
+            UtxoSelectionStrategyKind::BranchAndBound => Self::BranchAndBound,
+            UtxoSelectionStrategyKind::Random => Self::Random,
+        }
+    }
+}
+
+/// Convert the API representation of a UTXO selection strategy. Returns None
+/// when the daemon-wide default should be used.
+pub fn utxo_selection_strategy_from_proto(
+    src: mc_mobilecoind_api::UtxoSelectionStrategy,
+) -> Option<UtxoSelectionStrategyKind> {
+    match src {
+        mc_mobilecoind_api::UtxoSelectionStrategy::DaemonDefault => None,
+        mc_mobilecoind_api::UtxoSelectionStrategy::SmallestFirst => {
+            Some(UtxoSelectionStrategyKind::SmallestFirst)
+        }
+        mc_mobilecoind_api::UtxoSelectionStrategy::LargestFirst => {
+            Some(UtxoSelectionStrategyKind::LargestFirst)
+        }
+        mc_mobilecoind_api::UtxoSelectionStrategy::BranchAndBound => {
+            Some(UtxoSelectionStrategyKind::BranchAndBound)
+        }
+        mc_mobilecoind_api::UtxoSelectionStrategy::Random => {
+            Some(UtxoSelectionStrategyKind::Random)
+        }
+    }
+}
+
+impl From<MemoValidation> for mc_mobilecoind_api::MemoValidationStatus {
+    fn from(src: MemoValidation) -> Self {
+        match src {
+            MemoValidation::NotAuthenticated => Self::NotAuthenticated,
+            MemoValidation::Authenticated => Self::Authenticated,
+            MemoValidation::AuthenticationFailed => Self::AuthenticationFailed,
+            MemoValidation::UnknownSender => Self::UnknownSender,
+        }
+    }
+}
+
 impl From<&TxProposal> for mc_mobilecoind_api::TxProposal {
     fn from(src: &TxProposal) -> mc_mobilecoind_api::TxProposal {
         let mut dst = mc_mobilecoind_api::TxProposal::new();
@@ -88,6 +207,17 @@ impl From<&TxProposal> for mc_mobilecoind_api::TxProposal {
             src.utxos.iter().map(|utxo| utxo.into()).collect(),
         ));
         dst.set_outlay_list(RepeatedField::from_vec(
+            src.outlays
+                .iter()
+                .map(|outlay| {
+                    let mut proto_outlay = mc_mobilecoind_api::Outlay::new();
+                    proto_outlay.set_value(outlay.value);
+                    proto_outlay.set_receiver((&outlay.receiver).into());
+                    proto_outlay
+                })
+                .collect(),
+        ));
+        dst.set_outlay_list_v2(RepeatedField::from_vec(
             src.outlays.iter().map(|outlay| outlay.into()).collect(),
         ));
         dst.set_tx((&src.tx).into());
@@ -123,14 +253,22 @@ impl TryFrom<&mc_mobilecoind_api::TxProposal> for TxProposal {
             .map(UnspentTxOut::try_from)
             .collect::<Result<Vec<UnspentTxOut>, ConversionError>>()?;
 
-        let outlays = src
-            .get_outlay_list()
-            .iter()
-            .map(Outlay::try_from)
-            .collect::<Result<Vec<Outlay>, ConversionError>>()?;
-
         let tx = Tx::try_from(src.get_tx())?;
 
+        // Proposals that predate outlay_list_v2 only involve the fee token id.
+        let outlays = if src.get_outlay_list_v2().is_empty() {
+            let fee_token_id = TokenId::from(tx.prefix.fee_token_id);
+            src.get_outlay_list()
+                .iter()
+                .map(|outlay| Ok(OutlayV2::new(&Outlay::try_from(outlay)?, fee_token_id)))
+                .collect::<Result<Vec<OutlayV2>, ConversionError>>()?
+        } else {
+            src.get_outlay_list_v2()
+                .iter()
+                .map(OutlayV2::try_from)
+                .collect::<Result<Vec<OutlayV2>, ConversionError>>()?
+        };
+
         let outlay_index_to_tx_out_index = src
             .get_outlay_index_to_tx_out_index()
             .iter()
@@ -258,6 +396,50 @@ mod test {
         assert_eq!(rust, Outlay::try_from(&proto).unwrap());
     }
 
+    #[test]
+    fn test_outlay_v2_conversion() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let public_addr = AccountKey::random(&mut rng).default_subaddress();
+
+        // Rust -> Proto
+        let rust = OutlayV2 {
+            receiver: public_addr.clone(),
+            value: 1234,
+            token_id: TokenId::from(2),
+        };
+        let proto = mc_mobilecoind_api::OutlayV2::from(&rust);
+
+        assert_eq!(proto.value, rust.value);
+        assert_eq!(proto.token_id, 2);
+        assert_eq!(
+            PublicAddress::try_from(proto.get_receiver()).unwrap(),
+            public_addr
+        );
+
+        // Proto -> Rust
+        assert_eq!(rust, OutlayV2::try_from(&proto).unwrap());
+    }
+
+    #[test]
+    fn test_utxo_selection_strategy_conversion() {
+        assert_eq!(
+            utxo_selection_strategy_from_proto(
+                mc_mobilecoind_api::UtxoSelectionStrategy::DaemonDefault
+            ),
+            None
+        );
+
+        for kind in [
+            UtxoSelectionStrategyKind::SmallestFirst,
+            UtxoSelectionStrategyKind::LargestFirst,
+            UtxoSelectionStrategyKind::BranchAndBound,
+            UtxoSelectionStrategyKind::Random,
+        ] {
+            let proto = mc_mobilecoind_api::UtxoSelectionStrategy::from(kind);
+            assert_eq!(utxo_selection_strategy_from_proto(proto), Some(kind));
+        }
+    }
+
     #[test]
     fn test_tx_proposal_conversion() {
         let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
@@ -315,9 +497,10 @@ mod test {
 
         let outlay = {
             let public_addr = AccountKey::random(&mut rng).default_subaddress();
-            Outlay {
+            OutlayV2 {
                 receiver: public_addr,
                 value: 1234,
+                token_id: Mob::ID,
             }
         };
 
@@ -342,9 +525,12 @@ mod test {
 
         assert_eq!(
             rust.outlays,
-            vec![Outlay::try_from(&proto.get_outlay_list()[0]).unwrap()],
+            vec![OutlayV2::try_from(&proto.get_outlay_list_v2()[0]).unwrap()],
         );
 
+        assert_eq!(proto.get_outlay_list().len(), 1);
+        assert_eq!(proto.get_outlay_list()[0].value, rust.outlays[0].value);
+
         assert_eq!(proto.get_outlay_index_to_tx_out_index().len(), 1);
         assert_eq!(proto.get_outlay_index_to_tx_out_index().get(&0), Some(&0));
 
@@ -352,5 +538,10 @@ mod test {
 
         // Proto -> Rust
         assert_eq!(rust, TxProposal::try_from(&proto).unwrap());
+
+        // Proposals without outlay_list_v2 take the outlay token id from the fee.
+        let mut legacy_proto = proto;
+        legacy_proto.clear_outlay_list_v2();
+        assert_eq!(rust, TxProposal::try_from(&legacy_proto).unwrap());
     }
 }
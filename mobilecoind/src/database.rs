@@ -5,10 +5,15 @@
 use crate::{
     db_crypto::DbCryptoProvider,
     error::Error,
+    history_store::{HistoryFilter, HistoryKey, HistoryPage, HistoryStore},
     monitor_store::{MonitorData, MonitorId, MonitorStore},
     processed_block_store::{ProcessedBlockStore, ProcessedTxOut},
     subaddress_store::{SubaddressId, SubaddressSPKId, SubaddressStore},
+    subscription::BlockProcessedNotifier,
+    unverified_tx_out_store::{UnverifiedTxOut, UnverifiedTxOutStore},
     utxo_store::{UtxoId, UtxoStore},
+    webhook_store::{PendingWebhook, WebhookConfig, WebhookDeliveryKey, WebhookStore},
+    webhooks::unix_timestamp,
 };
 
 use crate::utxo_store::UnspentTxOut;
@@ -17,7 +22,8 @@ use mc_common::{
     logger::{log, Logger},
     HashMap,
 };
-use mc_transaction_core::ring_signature::KeyImage;
+use mc_ledger_db::Ledger;
+use mc_transaction_core::{ring_signature::KeyImage, TxOutKeyImage};
 use mc_util_lmdb::{MetadataStore, MetadataStoreSettings};
 use std::{path::Path, sync::Arc};
 
@@ -63,6 +69,18 @@ pub struct Database {
     /// Processed block store.
     processed_block_store: ProcessedBlockStore,
 
+    /// Transaction history store.
+    history_store: HistoryStore,
+
+    /// Webhook store.
+    webhook_store: WebhookStore,
+
+    /// Store for TxOuts received by view-only monitors.
+    unverified_tx_out_store: UnverifiedTxOutStore,
+
+    /// Notified every time a monitor finishes processing a block.
+    block_processed_notifier: BlockProcessedNotifier,
+
     /// Logger.
     logger: Logger,
 }
@@ -71,7 +89,7 @@ impl Database {
     pub fn new<P: AsRef<Path>>(path: P, logger: Logger) -> Result<Self, Error> {
         let env = Arc::new(
             Environment::new()
-                .set_max_dbs(10)
+                .set_max_dbs(16)
                 .set_map_size(MAX_LMDB_FILE_SIZE)
                 .open(path.as_ref())?,
         );
@@ -97,6 +115,10 @@ impl Database {
         let subaddress_store = SubaddressStore::new(env.clone(), logger.clone())?;
         let utxo_store = UtxoStore::new(env.clone(), logger.clone())?;
         let processed_block_store = ProcessedBlockStore::new(env.clone(), logger.clone())?;
+        let history_store = HistoryStore::new(env.clone(), logger.clone())?;
+        let webhook_store =
+            WebhookStore::new(env.clone(), crypto_provider.clone(), logger.clone())?;
+        let unverified_tx_out_store = UnverifiedTxOutStore::new(env.clone(), logger.clone())?;
 
         Ok(Self {
             env,
@@ -105,10 +127,20 @@ impl Database {
             subaddress_store,
             utxo_store,
             processed_block_store,
+            history_store,
+            webhook_store,
+            unverified_tx_out_store,
+            block_processed_notifier: BlockProcessedNotifier::default(),
             logger,
         })
     }
 
+    /// Get the notifier that is signalled every time a monitor finishes
+    /// processing a block.
+    pub fn block_processed_notifier(&self) -> &BlockProcessedNotifier {
+        &self.block_processed_notifier
+    }
+
     /// Check if data is currently being encrypted.
     pub fn is_db_encrypted(&self) -> bool {
         self.crypto_provider.is_db_encrypted()
@@ -134,8 +166,9 @@ impl Database {
     pub fn re_encrypt(&self, new_password: &[u8]) -> Result<(), Error> {
         let mut db_txn = self.env.begin_rw_txn()?;
 
-        // Currently only the monitor store stores encrypted data.
+        // The monitor and webhook stores hold encrypted data.
         self.monitor_store.re_encrypt(&mut db_txn, new_password)?;
+        self.webhook_store.re_encrypt(&mut db_txn, new_password)?;
 
         // set_password consumes the transaction to ensure atomicity.
         self.crypto_provider.change_password(db_txn, new_password)?;
@@ -172,6 +205,10 @@ impl Database {
         }
 
         self.processed_block_store.remove(&mut db_txn, id)?;
+        self.history_store.remove(&mut db_txn, id)?;
+        self.webhook_store.remove(&mut db_txn, id)?;
+        self.unverified_tx_out_store
+            .remove_monitor(&mut db_txn, id)?;
 
         self.monitor_store.remove(&mut db_txn, id)?;
 
@@ -240,11 +277,15 @@ impl Database {
     }
 
     /// Feed data processed from a given block into the various stores.
+    ///
+    /// `unverified_tx_outs` are the TxOuts received by a view-only monitor,
+    /// whose key images are not known.
     pub fn block_processed(
         &self,
         monitor_id: &MonitorId,
         block_num: u64,
         discovered_utxos: &[UnspentTxOut],
+        unverified_tx_outs: &[UnverifiedTxOut],
         spent_key_images: &[KeyImage],
     ) -> Result<(), Error> {
         let mut db_txn = self.env.begin_rw_txn()?;
@@ -268,6 +309,10 @@ impl Database {
             self.utxo_store
                 .append_utxo(&mut db_txn, monitor_id, utxo.subaddress_index, utxo)?;
         }
+        for unverified_tx_out in unverified_tx_outs {
+            self.unverified_tx_out_store
+                .add(&mut db_txn, monitor_id, unverified_tx_out)?;
+        }
 
         // Remove spent utxos
         let removed_utxos = self.utxo_store.remove_utxos_by_key_images(
@@ -290,15 +335,42 @@ impl Database {
             &removed_utxos,
         )?;
 
+        // Update transaction history store. Building history records requires
+        // the subaddress view private keys, so view-only monitors have no history.
+        if let Some(account_key) = &monitor_data.account_key {
+            self.history_store.block_processed(
+                &mut db_txn,
+                monitor_id,
+                account_key,
+                block_num,
+                discovered_utxos,
+                &removed_utxos,
+            )?;
+        }
+
+        // Queue a webhook notification, if the monitor has a webhook.
+        self.webhook_store.block_processed(
+            &mut db_txn,
+            monitor_id,
+            block_num,
+            discovered_utxos,
+            &removed_utxos,
+            unix_timestamp(),
+        )?;
+
         // Commit.
         db_txn.commit()?;
 
+        // Wake up any subscribers waiting for this block.
+        self.block_processed_notifier.notify();
+
         // Success.
-        if discovered_utxos.is_empty() && removed_utxos.is_empty() {
+        let num_discovered = discovered_utxos.len() + unverified_tx_outs.len();
+        if num_discovered == 0 && removed_utxos.is_empty() {
             log::debug!(
                 self.logger,
                 "Processed {} utxos and {} key images in block {} for monitor id {}",
-                discovered_utxos.len(),
+                num_discovered,
                 removed_utxos.len(),
                 block_num,
                 monitor_id
@@ -307,7 +379,7 @@ impl Database {
             log::info!(
                 self.logger,
                 "Processed {} utxos and {} key images in block {} for monitor id {}",
-                discovered_utxos.len(),
+                num_discovered,
                 removed_utxos.len(),
                 block_num,
                 monitor_id
@@ -342,6 +414,183 @@ impl Database {
         self.processed_block_store
             .get_processed_block(&db_txn, monitor_id, block_num)
     }
+
+    /// Get the TxOuts received by a view-only monitor whose key images have not
+    /// been imported yet.
+    pub fn get_unverified_tx_outs(
+        &self,
+        monitor_id: &MonitorId,
+    ) -> Result<Vec<UnverifiedTxOut>, Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+
+        let monitor_data = self.monitor_store.get_data(&db_txn, monitor_id)?;
+        if !monitor_data.is_view_only() {
+            return Err(Error::NotViewOnlyMonitor);
+        }
+
+        self.unverified_tx_out_store
+            .get_for_monitor(&db_txn, monitor_id)
+    }
+
+    /// Import the key images of TxOuts received by a view-only monitor, e.g.
+    /// computed by an offline machine holding the spend key. Each key image
+    /// must come with a valid proof that it belongs to its TxOut.
+    ///
+    /// TxOuts whose key image appears in a block the monitor has already
+    /// processed are spent, and are dropped. The others become UnspentTxOuts,
+    /// and are removed once the monitor processes the block spending them.
+    /// Either all key images are imported, or none is.
+    ///
+    /// Returns the number of TxOuts found to be unspent, and spent.
+    pub fn import_key_images(
+        &self,
+        monitor_id: &MonitorId,
+        key_images: &[TxOutKeyImage],
+        ledger_db: &impl Ledger,
+    ) -> Result<(usize, usize), Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        let monitor_data = self.monitor_store.get_data(&db_txn, monitor_id)?;
+        if !monitor_data.is_view_only() {
+            return Err(Error::NotViewOnlyMonitor);
+        }
+
+        let (mut num_unspent, mut num_spent) = (0, 0);
+        for tx_out_key_image in key_images {
+            let tx_public_key = &tx_out_key_image.tx_public_key;
+            let key_image = &tx_out_key_image.key_image;
+            let unverified_tx_out =
+                self.unverified_tx_out_store
+                    .get(&db_txn, monitor_id, tx_public_key)?;
+            tx_out_key_image
+                .verify(&unverified_tx_out.tx_out)
+                .map_err(Error::InvalidKeyImageProof)?;
+            self.unverified_tx_out_store
+                .remove(&mut db_txn, monitor_id, tx_public_key)?;
+
+            match ledger_db.check_key_image(key_image)? {
+                Some(block_index) if block_index < monitor_data.next_block => {
+                    num_spent += 1;
+                }
+                _ => {
+                    let utxo = unverified_tx_out.into_utxo(*key_image);
+                    self.utxo_store.append_utxo(
+                        &mut db_txn,
+                        monitor_id,
+                        utxo.subaddress_index,
+                        &utxo,
+                    )?;
+                    num_unspent += 1;
+                }
+            }
+        }
+
+        db_txn.commit()?;
+
+        log::info!(
+            self.logger,
+            "Imported {} key images ({} unspent, {} spent) for monitor id {}",
+            key_images.len(),
+            num_unspent,
+            num_spent,
+            monitor_id
+        );
+        Ok((num_unspent, num_spent))
+    }
+
+    /// Get up to `limit` transaction history records of a monitor, starting at
+    /// `start_key` and matching `filter`.
+    pub fn get_history(
+        &self,
+        start_key: &HistoryKey,
+        filter: &HistoryFilter,
+        limit: usize,
+    ) -> Result<HistoryPage, Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+
+        // Ensure the monitor exists.
+        self.monitor_store
+            .get_data(&db_txn, &start_key.monitor_id)?;
+
+        self.history_store
+            .get_history(&db_txn, start_key, filter, limit)
+    }
+
+    /// Set the webhook of a monitor, or remove it when `config` is None.
+    /// Removing a webhook also discards its pending deliveries.
+    pub fn set_monitor_webhook(
+        &self,
+        monitor_id: &MonitorId,
+        config: Option<&WebhookConfig>,
+    ) -> Result<(), Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        // Ensure the monitor exists.
+        self.monitor_store.get_data(&db_txn, monitor_id)?;
+
+        match config {
+            Some(config) => self
+                .webhook_store
+                .set_config(&mut db_txn, monitor_id, config)?,
+            None => self.webhook_store.remove(&mut db_txn, monitor_id)?,
+        }
+
+        db_txn.commit()?;
+        Ok(())
+    }
+
+    /// Get the webhook of a monitor, if it has one, and the number of its
+    /// pending deliveries.
+    pub fn get_monitor_webhook(
+        &self,
+        monitor_id: &MonitorId,
+    ) -> Result<(Option<WebhookConfig>, u64), Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+
+        // Ensure the monitor exists.
+        self.monitor_store.get_data(&db_txn, monitor_id)?;
+
+        let config = self.webhook_store.get_config(&db_txn, monitor_id)?;
+        let num_pending = self.webhook_store.get_num_pending(&db_txn, monitor_id)?;
+        Ok((config, num_pending))
+    }
+
+    /// Get up to `limit` webhook deliveries that are due at `now`.
+    pub fn get_due_webhooks(
+        &self,
+        now: u64,
+        limit: usize,
+    ) -> Result<Vec<(WebhookDeliveryKey, PendingWebhook)>, Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        self.webhook_store.get_due(&db_txn, now, limit)
+    }
+
+    /// Remove a webhook delivery, once it succeeded or was given up on.
+    pub fn remove_webhook_delivery(&self, key: &WebhookDeliveryKey) -> Result<(), Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+        self.webhook_store.delete(&mut db_txn, key)?;
+        db_txn.commit()?;
+        Ok(())
+    }
+
+    /// Reschedule a failed webhook delivery to `next_attempt_at`. Nothing
+    /// happens if the delivery was removed in the meantime, e.g. because its
+    /// monitor was removed.
+    pub fn reschedule_webhook_delivery(
+        &self,
+        key: &WebhookDeliveryKey,
+        next_attempt_at: u64,
+        pending: &PendingWebhook,
+    ) -> Result<(), Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+        if self.webhook_store.delete(&mut db_txn, key)? {
+            let new_key =
+                WebhookDeliveryKey::new(next_attempt_at, &key.monitor_id, key.block_index);
+            self.webhook_store.put(&mut db_txn, &new_key, pending)?;
+        }
+        db_txn.commit()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
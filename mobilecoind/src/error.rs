@@ -9,6 +9,7 @@ use mc_connection::Error as ConnectionError;
 use mc_consensus_api::ConversionError;
 use mc_crypto_keys::KeyError;
 use mc_ledger_db::Error as LedgerDbError;
+use mc_transaction_core::KeyImageProofError;
 use mc_util_lmdb::MetadataStoreError;
 use mc_util_serial::{decode::Error as DecodeError, encode::Error as EncodeError};
 use prost::DecodeError as ProstDecodeError;
@@ -111,6 +112,18 @@ pub enum Error {
 
     /// Db encryption: {0}
     DbCrypto(DbCryptoError),
+
+    /// The monitor is view-only and cannot spend
+    ViewOnlyMonitor,
+
+    /// The monitor is not view-only
+    NotViewOnlyMonitor,
+
+    /// No TxOut awaiting a key image was found for this public key
+    UnverifiedTxOutNotFound,
+
+    /// Invalid key image proof: {0}
+    InvalidKeyImageProof(KeyImageProofError),
 }
 
 impl From<RetryError<ConnectionError>> for Error {
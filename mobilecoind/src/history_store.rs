@@ -0,0 +1,692 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Database storage for the transaction history of monitors.
+//! * Stores a map of (monitor id, block number, index in block) -> a record of
+//!   a TxOut that was received or spent by the monitor in the given block,
+//!   including its decrypted memo.
+//! * Memos are decoded and validated when the history is queried, since
+//!   validating an authenticated sender memo requires knowing the sender's
+//!   public address.
+
+use crate::{
+    error::Error, monitor_store::MonitorId, processed_block_store::ProcessedTxOutDirection,
+    utxo_store::UnspentTxOut,
+};
+use lmdb::{Cursor, Database, DatabaseFlags, Environment, RwTransaction, Transaction, WriteFlags};
+use mc_account_keys::{AccountKey, PublicAddress, ShortAddressHash, CHANGE_SUBADDRESS_INDEX};
+use mc_common::logger::Logger;
+use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
+use mc_transaction_core::{
+    get_tx_out_shared_secret, ring_signature::KeyImage, tx::TxOut, MemoPayload,
+};
+use mc_transaction_std::MemoType;
+use prost::Message;
+use std::{
+    convert::{TryFrom, TryInto},
+    sync::Arc,
+};
+
+// LMDB Database Names
+pub const HISTORY_KEY_TO_HISTORY_RECORD_DB_NAME: &str =
+    "mobilecoind_db:history_store:history_key_to_history_record";
+
+/// Type used as the key in the databases managed by the history store.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct HistoryKey {
+    /// The monitor_id for which the data belongs to.
+    pub monitor_id: MonitorId,
+
+    /// The block index.
+    pub block_index: u64,
+
+    /// The position of the record within the block.
+    pub index_in_block: u32,
+}
+impl HistoryKey {
+    pub fn new(monitor_id: &MonitorId, block_index: u64, index_in_block: u32) -> Self {
+        Self {
+            monitor_id: *monitor_id,
+            block_index,
+            index_in_block,
+        }
+    }
+
+    // 44 bytes: 32 for MonitorId, 8 for block index, 4 for index in block.
+    pub fn to_bytes(&self) -> [u8; 44] {
+        let mut buf = [0u8; 44];
+        buf[0..32].copy_from_slice(self.monitor_id.as_bytes());
+        buf[32..40].copy_from_slice(&self.block_index.to_be_bytes());
+        buf[40..44].copy_from_slice(&self.index_in_block.to_be_bytes());
+        buf
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for HistoryKey {
+    type Error = Error;
+
+    fn try_from(src: &[u8]) -> Result<Self, Self::Error> {
+        if src.len() != 44 {
+            return Err(Error::InvalidArgument(
+                "src".to_string(),
+                "src length must be exactly 44".to_string(),
+            ));
+        }
+
+        let monitor_id = MonitorId::try_from(&src[0..32])?;
+        let block_index = u64::from_be_bytes(src[32..40].try_into().expect("arithmetic error"));
+        let index_in_block = u32::from_be_bytes(src[40..44].try_into().expect("arithmetic error"));
+
+        Ok(Self {
+            monitor_id,
+            block_index,
+            index_in_block,
+        })
+    }
+}
+
+/// Type used as the stored data in the history_key_to_history_record database.
+#[derive(Clone, Eq, Hash, PartialEq, Message)]
+pub struct HistoryRecord {
+    /// The subaddress index the tx out belongs to.
+    #[prost(uint64, tag = "1")]
+    pub subaddress_index: u64,
+
+    /// The public key of the TxOut.
+    #[prost(message, required, tag = "2")]
+    pub public_key: CompressedRistrettoPublic,
+
+    /// Key image of the TxOut.
+    #[prost(message, required, tag = "3")]
+    pub key_image: KeyImage,
+
+    /// Value of this TxOut.
+    #[prost(uint64, tag = "4")]
+    pub value: u64,
+
+    /// Token id.
+    #[prost(uint64, tag = "5")]
+    pub token_id: u64,
+
+    /// Direction.
+    #[prost(enumeration = "ProcessedTxOutDirection", tag = "6")]
+    pub direction: i32,
+
+    /// The decrypted memo payload of the TxOut.
+    #[prost(bytes, tag = "7")]
+    pub memo_payload: Vec<u8>,
+
+    /// The address hash of the counterparty named by the memo, if the memo
+    /// names one. Empty otherwise.
+    #[prost(bytes, tag = "8")]
+    pub counterparty_address_hash: Vec<u8>,
+}
+
+/// The outcome of validating the memo of a history record.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MemoValidation {
+    /// The memo type does not carry any authentication.
+    NotAuthenticated,
+
+    /// The memo was authenticated.
+    Authenticated,
+
+    /// The memo failed authentication.
+    AuthenticationFailed,
+
+    /// The memo names a sender that is not one of the known addresses, so it
+    /// could not be authenticated.
+    UnknownSender,
+}
+
+impl HistoryRecord {
+    /// Create a record for a TxOut belonging to `account_key`.
+    pub fn new(
+        account_key: &AccountKey,
+        utxo: &UnspentTxOut,
+        direction: ProcessedTxOutDirection,
+    ) -> Result<Self, Error> {
+        let memo_payload = decrypt_memo(account_key, &utxo.tx_out)?;
+        let counterparty_address_hash = MemoType::try_from(&memo_payload)
+            .ok()
+            .and_then(|memo| counterparty_address_hash(&memo))
+            .map(|hash| <[u8; 16]>::from(hash).to_vec())
+            .unwrap_or_default();
+
+        Ok(Self {
+            subaddress_index: utxo.subaddress_index,
+            public_key: utxo.tx_out.public_key,
+            key_image: utxo.key_image,
+            value: utxo.value,
+            token_id: utxo.token_id,
+            direction: direction as i32,
+            memo_payload: memo_payload.as_ref().to_vec(),
+            counterparty_address_hash,
+        })
+    }
+
+    /// Decode the memo of this record. Returns None if the memo type is not
+    /// recognized.
+    pub fn memo(&self) -> Option<MemoType> {
+        let memo_payload = MemoPayload::try_from(&self.memo_payload[..]).ok()?;
+        MemoType::try_from(&memo_payload).ok()
+    }
+
+    /// Validate the memo of this record.
+    /// * Authenticated sender memos are validated against whichever of
+    ///   `known_addresses` matches the sender address hash in the memo.
+    /// * Destination memos are valid only when attached to a TxOut received at
+    ///   the change subaddress.
+    pub fn validate_memo(
+        &self,
+        account_key: &AccountKey,
+        known_addresses: &[PublicAddress],
+    ) -> MemoValidation {
+        let find_sender = |hash: ShortAddressHash| {
+            known_addresses
+                .iter()
+                .find(|address| ShortAddressHash::from(*address) == hash)
+        };
+        let hmac_validation = |valid: bool| {
+            if valid {
+                MemoValidation::Authenticated
+            } else {
+                MemoValidation::AuthenticationFailed
+            }
+        };
+        let view_private_key = account_key.subaddress_view_private(self.subaddress_index);
+
+        match self.memo() {
+            Some(MemoType::AuthenticatedSender(memo)) => {
+                match find_sender(memo.sender_address_hash()) {
+                    Some(sender) => hmac_validation(bool::from(memo.validate(
+                        sender,
+                        &view_private_key,
+                        &self.public_key,
+                    ))),
+                    None => MemoValidation::UnknownSender,
+                }
+            }
+            Some(MemoType::AuthenticatedSenderWithPaymentRequestId(memo)) => {
+                match find_sender(memo.sender_address_hash()) {
+                    Some(sender) => hmac_validation(bool::from(memo.validate(
+                        sender,
+                        &view_private_key,
+                        &self.public_key,
+                    ))),
+                    None => MemoValidation::UnknownSender,
+                }
+            }
+            Some(MemoType::Destination(_)) => {
+                if self.subaddress_index == CHANGE_SUBADDRESS_INDEX {
+                    MemoValidation::Authenticated
+                } else {
+                    MemoValidation::AuthenticationFailed
+                }
+            }
+            _ => MemoValidation::NotAuthenticated,
+        }
+    }
+}
+
+/// Criteria for selecting history records. Empty lists match everything.
+#[derive(Clone, Debug, Default)]
+pub struct HistoryFilter {
+    /// Only include records belonging to one of these subaddresses.
+    pub subaddress_indices: Vec<u64>,
+
+    /// Only include records of one of these token ids.
+    pub token_ids: Vec<u64>,
+
+    /// Only include records whose memo names one of these counterparties.
+    pub counterparty_address_hashes: Vec<ShortAddressHash>,
+
+    /// Only include records from blocks lower than this one.
+    pub end_block: Option<u64>,
+}
+
+impl HistoryFilter {
+    /// Check whether a record matches this filter.
+    pub fn matches(&self, record: &HistoryRecord) -> bool {
+        (self.subaddress_indices.is_empty()
+            || self.subaddress_indices.contains(&record.subaddress_index))
+            && (self.token_ids.is_empty() || self.token_ids.contains(&record.token_id))
+            && (self.counterparty_address_hashes.is_empty()
+                || self
+                    .counterparty_address_hashes
+                    .iter()
+                    .any(|hash| hash.as_ref()[..] == record.counterparty_address_hash[..]))
+    }
+}
+
+/// A page of history records.
+#[derive(Clone, Debug, Default)]
+pub struct HistoryPage {
+    /// The records, ordered by block index and position within the block.
+    pub records: Vec<(HistoryKey, HistoryRecord)>,
+
+    /// The key of the first record of the next page, if there is one.
+    pub next_key: Option<HistoryKey>,
+}
+
+/// The transaction history database.
+#[derive(Clone)]
+pub struct HistoryStore {
+    /// Retain a reference to the Environment so the Database handles are valid.
+    _env: Arc<Environment>,
+
+    /// Mapping of HistoryKey -> HistoryRecord.
+    history_key_to_history_record: Database,
+}
+
+impl HistoryStore {
+    pub fn new(env: Arc<Environment>, _logger: Logger) -> Result<Self, Error> {
+        let history_key_to_history_record = env.create_db(
+            Some(HISTORY_KEY_TO_HISTORY_RECORD_DB_NAME),
+            DatabaseFlags::empty(),
+        )?;
+
+        Ok(Self {
+            _env: env,
+            history_key_to_history_record,
+        })
+    }
+
+    /// Get up to `limit` records for a given monitor id, starting at
+    /// `start_key` and matching `filter`.
+    pub fn get_history(
+        &self,
+        db_txn: &impl Transaction,
+        start_key: &HistoryKey,
+        filter: &HistoryFilter,
+        limit: usize,
+    ) -> Result<HistoryPage, Error> {
+        let mut cursor = db_txn.open_ro_cursor(self.history_key_to_history_record)?;
+
+        let mut page = HistoryPage::default();
+        for result in cursor.iter_from(&start_key.to_vec()) {
+            let (db_key, db_value) = result?;
+            let key = HistoryKey::try_from(db_key)?;
+            if key.monitor_id != start_key.monitor_id
+                || filter
+                    .end_block
+                    .map_or(false, |end_block| key.block_index >= end_block)
+            {
+                break;
+            }
+
+            let record: HistoryRecord = mc_util_serial::decode(db_value)?;
+            if !filter.matches(&record) {
+                continue;
+            }
+
+            if page.records.len() == limit {
+                page.next_key = Some(key);
+                break;
+            }
+            page.records.push((key, record));
+        }
+
+        Ok(page)
+    }
+
+    /// Remove the data associated with a given monitor id.
+    pub fn remove<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        monitor_id: &MonitorId,
+    ) -> Result<(), Error> {
+        let start_key = HistoryKey::new(monitor_id, 0, 0);
+        let start_key_bytes = start_key.to_vec();
+
+        let mut cursor = db_txn.open_rw_cursor(self.history_key_to_history_record)?;
+
+        for (db_key, _db_value) in cursor.iter_from(&start_key_bytes).filter_map(|r| r.ok()) {
+            let key = HistoryKey::try_from(db_key)?;
+            if key.monitor_id == *monitor_id {
+                cursor.del(WriteFlags::empty())?;
+            } else {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Feed data processed from a given block.
+    pub fn block_processed<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        monitor_id: &MonitorId,
+        account_key: &AccountKey,
+        block_index: u64,
+        discovered_utxos: &[UnspentTxOut],
+        spent_utxos: &[UnspentTxOut],
+    ) -> Result<(), Error> {
+        let records = discovered_utxos
+            .iter()
+            .map(|utxo| (utxo, ProcessedTxOutDirection::Received))
+            .chain(
+                spent_utxos
+                    .iter()
+                    .map(|utxo| (utxo, ProcessedTxOutDirection::Spent)),
+            );
+
+        for (index_in_block, (utxo, direction)) in records.enumerate() {
+            let key = HistoryKey::new(monitor_id, block_index, index_in_block as u32);
+            let record = HistoryRecord::new(account_key, utxo, direction)?;
+            db_txn.put(
+                self.history_key_to_history_record,
+                &key.to_vec(),
+                &mc_util_serial::encode(&record),
+                WriteFlags::empty(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Decrypt the memo of a TxOut belonging to `account_key`.
+fn decrypt_memo(account_key: &AccountKey, tx_out: &TxOut) -> Result<MemoPayload, Error> {
+    let tx_public_key = RistrettoPublic::try_from(&tx_out.public_key)?;
+    let shared_secret = get_tx_out_shared_secret(account_key.view_private_key(), &tx_public_key);
+    Ok(tx_out.decrypt_memo(&shared_secret))
+}
+
+/// The address hash of the counterparty named by a memo, if there is one.
+fn counterparty_address_hash(memo: &MemoType) -> Option<ShortAddressHash> {
+    match memo {
+        MemoType::AuthenticatedSender(memo) => Some(memo.sender_address_hash()),
+        MemoType::AuthenticatedSenderWithPaymentRequestId(memo) => Some(memo.sender_address_hash()),
+        MemoType::Destination(memo) => Some(memo.get_address_hash().clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::monitor_store::MonitorData;
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_crypto_keys::RistrettoPrivate;
+    use mc_crypto_rand::{CryptoRng, RngCore};
+    use mc_transaction_core::{
+        encrypted_fog_hint::EncryptedFogHint, tokens::Mob, Amount, Token, TokenId,
+    };
+    use mc_transaction_std::{AuthenticatedSenderMemo, DestinationMemo, SenderMemoCredential};
+    use mc_util_from_random::FromRandom;
+    use rand::{rngs::StdRng, SeedableRng};
+    use tempdir::TempDir;
+
+    // Create an UnspentTxOut sent to `recipient`, optionally with a memo.
+    fn create_utxo(
+        recipient_account: &AccountKey,
+        subaddress_index: u64,
+        amount: Amount,
+        memo_fn: impl FnOnce(&PublicAddress, &CompressedRistrettoPublic) -> Option<MemoPayload>,
+        rng: &mut (impl CryptoRng + RngCore),
+    ) -> UnspentTxOut {
+        let recipient = recipient_account.subaddress(subaddress_index);
+        let tx_out = TxOut::new_with_memo(
+            amount,
+            &recipient,
+            &RistrettoPrivate::from_random(rng),
+            EncryptedFogHint::fake_onetime_hint(rng),
+            |memo_ctxt| {
+                Ok(memo_fn(
+                    &recipient,
+                    &CompressedRistrettoPublic::from(memo_ctxt.tx_public_key),
+                ))
+            },
+        )
+        .unwrap();
+
+        UnspentTxOut {
+            tx_out,
+            subaddress_index,
+            key_image: KeyImage::from(rng.next_u64()),
+            value: amount.value,
+            attempted_spend_height: 0,
+            attempted_spend_tombstone: 0,
+            token_id: *amount.token_id,
+        }
+    }
+
+    // HistoryStore basic functionality tests
+    #[test_with_logger]
+    fn test_history_store(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+
+        let db_tmp =
+            TempDir::new("history_store_db").expect("Could not make tempdir for history store db");
+        let db_path = db_tmp
+            .path()
+            .to_str()
+            .expect("Could not get path as string");
+        let env = Arc::new(
+            Environment::new()
+                .set_max_dbs(10)
+                .set_map_size(10000000)
+                .open(db_path.as_ref())
+                .unwrap(),
+        );
+        let store = HistoryStore::new(env.clone(), logger).unwrap();
+
+        let alice = AccountKey::random(&mut rng);
+        let bob = AccountKey::random(&mut rng);
+        let carol = AccountKey::random(&mut rng);
+        let alice_hash = ShortAddressHash::from(&alice.default_subaddress());
+        let carol_hash = ShortAddressHash::from(&carol.default_subaddress());
+
+        let monitor_data = MonitorData::new(
+            bob.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+        let monitor_id = MonitorId::from(&monitor_data);
+
+        let mob = |value| Amount {
+            value,
+            token_id: Mob::ID,
+        };
+
+        // A payment from alice, with an authenticated sender memo.
+        let from_alice = create_utxo(
+            &bob,
+            3,
+            mob(100),
+            |recipient, tx_public_key| {
+                Some(
+                    AuthenticatedSenderMemo::new(
+                        &SenderMemoCredential::from(&alice),
+                        recipient.view_public_key(),
+                        tx_public_key,
+                    )
+                    .into(),
+                )
+            },
+            &mut rng,
+        );
+
+        // Change from a payment to carol, with a destination memo.
+        let change = create_utxo(
+            &bob,
+            CHANGE_SUBADDRESS_INDEX,
+            mob(200),
+            |_, _| {
+                Some(
+                    DestinationMemo::new(carol_hash.clone(), 300, 10)
+                        .unwrap()
+                        .into(),
+                )
+            },
+            &mut rng,
+        );
+
+        // A payment of another token, without a memo.
+        let other_token = create_utxo(
+            &bob,
+            4,
+            Amount {
+                value: 400,
+                token_id: TokenId::from(2),
+            },
+            |_, _| None,
+            &mut rng,
+        );
+
+        {
+            let mut db_txn = env.begin_rw_txn().unwrap();
+            store
+                .block_processed(
+                    &mut db_txn,
+                    &monitor_id,
+                    &bob,
+                    5,
+                    &[from_alice.clone(), change.clone()],
+                    &[],
+                )
+                .unwrap();
+            store
+                .block_processed(
+                    &mut db_txn,
+                    &monitor_id,
+                    &bob,
+                    6,
+                    &[other_token.clone()],
+                    &[from_alice.clone()],
+                )
+                .unwrap();
+            db_txn.commit().unwrap();
+        }
+
+        let get_history = |start_key: &HistoryKey, filter: &HistoryFilter, limit: usize| {
+            let db_txn = env.begin_ro_txn().unwrap();
+            store
+                .get_history(&db_txn, start_key, filter, limit)
+                .unwrap()
+        };
+        let start_key = HistoryKey::new(&monitor_id, 0, 0);
+
+        // All records, in order.
+        let page = get_history(&start_key, &HistoryFilter::default(), 100);
+        assert_eq!(page.next_key, None);
+        let keys: Vec<_> = page
+            .records
+            .iter()
+            .map(|(key, _)| (key.block_index, key.index_in_block))
+            .collect();
+        assert_eq!(keys, vec![(5, 0), (5, 1), (6, 0), (6, 1)]);
+
+        let records: Vec<_> = page.records.iter().map(|(_, record)| record).collect();
+        assert_eq!(records[0].public_key, from_alice.tx_out.public_key);
+        assert_eq!(
+            records[0].direction,
+            ProcessedTxOutDirection::Received as i32
+        );
+        assert_eq!(records[0].counterparty_address_hash, alice_hash.as_ref());
+        assert_eq!(records[1].counterparty_address_hash, carol_hash.as_ref());
+        assert_eq!(records[2].token_id, 2);
+        assert!(records[2].counterparty_address_hash.is_empty());
+        assert_eq!(records[3].public_key, from_alice.tx_out.public_key);
+        assert_eq!(records[3].direction, ProcessedTxOutDirection::Spent as i32);
+
+        // Memo validation.
+        assert_eq!(
+            records[0].validate_memo(&bob, &[alice.default_subaddress()]),
+            MemoValidation::Authenticated
+        );
+        assert_eq!(
+            records[0].validate_memo(&bob, &[carol.default_subaddress()]),
+            MemoValidation::UnknownSender
+        );
+        assert_eq!(
+            records[0].validate_memo(&carol, &[alice.default_subaddress()]),
+            MemoValidation::AuthenticationFailed
+        );
+        assert_eq!(
+            records[1].validate_memo(&bob, &[]),
+            MemoValidation::Authenticated
+        );
+        assert_eq!(
+            records[2].validate_memo(&bob, &[]),
+            MemoValidation::NotAuthenticated
+        );
+        match records[1].memo() {
+            Some(MemoType::Destination(memo)) => {
+                assert_eq!(memo.get_address_hash(), &carol_hash);
+                assert_eq!(memo.get_total_outlay(), 300);
+                assert_eq!(memo.get_fee(), 10);
+            }
+            other => panic!("unexpected memo {:?}", other),
+        }
+
+        // Filters.
+        let filter = HistoryFilter {
+            subaddress_indices: vec![3],
+            ..Default::default()
+        };
+        assert_eq!(get_history(&start_key, &filter, 100).records.len(), 2);
+
+        let filter = HistoryFilter {
+            token_ids: vec![2],
+            ..Default::default()
+        };
+        assert_eq!(get_history(&start_key, &filter, 100).records.len(), 1);
+
+        let filter = HistoryFilter {
+            counterparty_address_hashes: vec![carol_hash],
+            ..Default::default()
+        };
+        let page = get_history(&start_key, &filter, 100);
+        assert_eq!(page.records.len(), 1);
+        assert_eq!(page.records[0].1.public_key, change.tx_out.public_key);
+
+        let filter = HistoryFilter {
+            end_block: Some(6),
+            ..Default::default()
+        };
+        assert_eq!(get_history(&start_key, &filter, 100).records.len(), 2);
+
+        // Starting at a later block.
+        let page = get_history(
+            &HistoryKey::new(&monitor_id, 6, 0),
+            &HistoryFilter::default(),
+            100,
+        );
+        assert_eq!(page.records.len(), 2);
+
+        // Pagination.
+        let page = get_history(&start_key, &HistoryFilter::default(), 3);
+        assert_eq!(page.records.len(), 3);
+        assert_eq!(page.next_key, Some(HistoryKey::new(&monitor_id, 6, 1)));
+        let page = get_history(&page.next_key.unwrap(), &HistoryFilter::default(), 3);
+        assert_eq!(page.records.len(), 1);
+        assert_eq!(page.next_key, None);
+
+        // Other monitors have no history.
+        let other_monitor_id = MonitorId::from(&MonitorData::new(alice, 0, 20, 0, "").unwrap());
+        let page = get_history(
+            &HistoryKey::new(&other_monitor_id, 0, 0),
+            &HistoryFilter::default(),
+            100,
+        );
+        assert!(page.records.is_empty());
+
+        // Removing the monitor removes its history.
+        {
+            let mut db_txn = env.begin_rw_txn().unwrap();
+            store.remove(&mut db_txn, &monitor_id).unwrap();
+            db_txn.commit().unwrap();
+        }
+        let page = get_history(&start_key, &HistoryFilter::default(), 100);
+        assert!(page.records.is_empty());
+    }
+}
@@ -8,16 +8,22 @@ pub mod config;
 pub mod database;
 pub mod payments;
 pub mod service;
+pub mod utxo_selection;
+pub mod webhooks;
 
 mod conversions;
 mod database_key;
 mod db_crypto;
 mod error;
+mod history_store;
 mod monitor_store;
 mod processed_block_store;
 mod subaddress_store;
+mod subscription;
 mod sync;
+mod unverified_tx_out_store;
 mod utxo_store;
+mod webhook_store;
 pub use utxo_store::UnspentTxOut;
 
 #[cfg(any(test, feature = "test_utils"))]
@@ -7,13 +7,13 @@
 use crate::{database_key::DatabaseByteArrayKey, db_crypto::DbCryptoProvider, error::Error};
 
 use lmdb::{Cursor, Database, DatabaseFlags, Environment, RwTransaction, Transaction, WriteFlags};
-use mc_account_keys::AccountKey;
+use mc_account_keys::{AccountKey, PublicAddress, ViewAccountKey, DEFAULT_SUBADDRESS_INDEX};
 use mc_common::{
     logger::{log, Logger},
     HashMap,
 };
 use mc_crypto_digestible::{Digestible, MerlinTranscript};
-use mc_crypto_keys::RistrettoPublic;
+use mc_crypto_keys::{RistrettoPrivate, RistrettoPublic};
 use mc_util_serial::Message;
 use std::{convert::TryFrom, ops::Range, sync::Arc};
 
@@ -22,11 +22,16 @@ pub const MONITOR_ID_TO_MONITOR_DATA_DB_NAME: &str =
     "mobilecoind_db:monitor_store:monitor_id_to_monitor_data";
 
 /// Type used as the stored data in the monitor_id_to_monitor_data database.
+///
+/// A monitor holds either the full account key, or only a view-only account
+/// key, in which case it can detect received TxOuts but cannot compute their
+/// key images or spend them.
 #[derive(Clone, Eq, Hash, PartialEq, Message)]
 pub struct MonitorData {
-    /// The private key pair for the account this monitor watches.
-    #[prost(message, required, tag = "1")]
-    pub account_key: AccountKey,
+    /// The private key pair for the account this monitor watches, if this is
+    /// not a view-only monitor.
+    #[prost(message, optional, tag = "1")]
+    pub account_key: Option<AccountKey>,
 
     /// The smallest subaddress index in the range this monitor watches.
     #[prost(uint64, tag = "2")]
@@ -47,6 +52,11 @@ pub struct MonitorData {
     /// Optional monitor name.
     #[prost(string, tag = "6")]
     pub name: String,
+
+    /// The view private key and spend public key for the account this monitor
+    /// watches, if this is a view-only monitor.
+    #[prost(message, optional, tag = "7")]
+    pub view_account_key: Option<ViewAccountKey>,
 }
 
 impl MonitorData {
@@ -56,6 +66,42 @@ impl MonitorData {
         num_subaddresses: u64,
         first_block: u64,
         name: &str,
+    ) -> Result<Self, Error> {
+        Self::new_internal(
+            Some(account_key),
+            None,
+            first_subaddress,
+            num_subaddresses,
+            first_block,
+            name,
+        )
+    }
+
+    /// Create the data of a view-only monitor.
+    pub fn new_view_only(
+        view_account_key: ViewAccountKey,
+        first_subaddress: u64,
+        num_subaddresses: u64,
+        first_block: u64,
+        name: &str,
+    ) -> Result<Self, Error> {
+        Self::new_internal(
+            None,
+            Some(view_account_key),
+            first_subaddress,
+            num_subaddresses,
+            first_block,
+            name,
+        )
+    }
+
+    fn new_internal(
+        account_key: Option<AccountKey>,
+        view_account_key: Option<ViewAccountKey>,
+        first_subaddress: u64,
+        num_subaddresses: u64,
+        first_block: u64,
+        name: &str,
     ) -> Result<Self, Error> {
         if num_subaddresses == 0 {
             return Err(Error::InvalidArgument(
@@ -72,12 +118,43 @@ impl MonitorData {
             // The next block we need to sync is our first block.
             next_block: first_block,
             name: name.to_owned(),
+            view_account_key,
         })
     }
 
     pub fn subaddress_indexes(&self) -> Range<u64> {
         self.first_subaddress..self.first_subaddress + self.num_subaddresses
     }
+
+    /// Whether this monitor only holds a view-only account key.
+    pub fn is_view_only(&self) -> bool {
+        self.account_key.is_none()
+    }
+
+    /// The account key, needed for computing key images and spending. Fails
+    /// for view-only monitors.
+    pub fn account_key(&self) -> Result<&AccountKey, Error> {
+        self.account_key.as_ref().ok_or(Error::ViewOnlyMonitor)
+    }
+
+    /// The view private key of the account.
+    pub fn view_private_key(&self) -> &RistrettoPrivate {
+        match (&self.account_key, &self.view_account_key) {
+            (Some(account_key), _) => account_key.view_private_key(),
+            (None, Some(view_account_key)) => view_account_key.view_private_key(),
+            (None, None) => panic!("monitor data holds no account key"),
+        }
+    }
+
+    /// The i^th subaddress of the account. Subaddresses of view-only monitors
+    /// have no fog info.
+    pub fn subaddress(&self, index: u64) -> PublicAddress {
+        match (&self.account_key, &self.view_account_key) {
+            (Some(account_key), _) => account_key.subaddress(index),
+            (None, Some(view_account_key)) => view_account_key.subaddress(index),
+            (None, None) => panic!("monitor data holds no account key"),
+        }
+    }
 }
 
 /// Type used as the key in the monitor_id_to_monitor_data database
@@ -119,7 +196,7 @@ impl From<&MonitorData> for MonitorId {
             pub first_block: u64,
         }
 
-        let real_subaddress = src.account_key.default_subaddress();
+        let real_subaddress = src.subaddress(DEFAULT_SUBADDRESS_INDEX);
 
         let const_data = ConstMonitorData {
             address: PublicAddress {
@@ -378,6 +455,18 @@ pKZkdp8MQU5TLFOE9qjNeVsCAwEAAQ==
         let expected = hex::decode(HEXPECTED).expect("Could not decode expected data to bytes");
         assert_eq!(expected, id.as_bytes().to_vec(), "{}", hex_fmt::HexFmt(id));
 
+        // A view-only monitor of the same account has the same ID, since the
+        // account has no fog info.
+        let view_data = MonitorData::new_view_only(
+            ViewAccountKey::from(data.account_key().unwrap()),
+            1,
+            10,
+            1,
+            "test",
+        )
+        .expect("Could not create view-only monitor data");
+        assert_eq!(id, MonitorId::from(&view_data));
+
         let fog_authority_spki = pem::parse(AUTHORITY_PUBKEY)
             .expect("Could not parse pubkey")
             .contents;
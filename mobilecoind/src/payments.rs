@@ -2,7 +2,13 @@
 
 //! Construct and submit transactions to the validator network.
 
-use crate::{database::Database, error::Error, monitor_store::MonitorId, utxo_store::UnspentTxOut};
+use crate::{
+    database::Database,
+    error::Error,
+    monitor_store::MonitorId,
+    utxo_selection::{UtxoSelectionStrategy, UtxoSelectionStrategyKind},
+    utxo_store::UnspentTxOut,
+};
 use mc_account_keys::{AccountKey, PublicAddress};
 use mc_common::{
     logger::{log, o, Logger},
@@ -31,9 +37,10 @@ use mc_util_uri::FogUri;
 use rand::Rng;
 use rayon::prelude::*;
 use std::{
-    cmp::{max, Reverse},
+    cmp::max,
+    collections::BTreeMap,
     convert::TryFrom,
-    iter::empty,
+    iter::{empty, once},
     str::FromStr,
     sync::{
         atomic::{AtomicUsize, Ordering},
@@ -63,6 +70,36 @@ pub struct Outlay {
     pub receiver: PublicAddress,
 }
 
+/// An outlay in a specific token id - the API representation of a desired
+/// transaction output in a transaction that may involve several token ids.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OutlayV2 {
+    /// Value being sent.
+    pub value: u64,
+
+    /// Destination.
+    pub receiver: PublicAddress,
+
+    /// Token id of the value being sent.
+    pub token_id: TokenId,
+}
+
+impl OutlayV2 {
+    /// Create an OutlayV2 from a single-token Outlay.
+    pub fn new(outlay: &Outlay, token_id: TokenId) -> Self {
+        Self {
+            value: outlay.value,
+            receiver: outlay.receiver.clone(),
+            token_id,
+        }
+    }
+
+    /// The amount being sent.
+    pub fn amount(&self) -> Amount {
+        Amount::new(self.value, self.token_id)
+    }
+}
+
 /// A single pending transaction.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct TxProposal {
@@ -70,7 +107,7 @@ pub struct TxProposal {
     pub utxos: Vec<UnspentTxOut>,
 
     /// Destinations the transaction is being sent to.
-    pub outlays: Vec<Outlay>,
+    pub outlays: Vec<OutlayV2>,
 
     /// The actual transaction.
     pub tx: Tx,
@@ -88,6 +125,10 @@ impl TxProposal {
     pub fn fee(&self) -> u64 {
         self.tx.prefix.fee
     }
+
+    pub fn fee_token_id(&self) -> TokenId {
+        TokenId::from(self.tx.prefix.fee_token_id)
+    }
 }
 
 pub struct TransactionsManager<
@@ -112,6 +153,9 @@ pub struct TransactionsManager<
     /// connections to fog
     fog_resolver_factory: Arc<dyn Fn(&[FogUri]) -> Result<FPR, String> + Send + Sync>,
 
+    /// UTXO selection strategy used when a request does not specify one.
+    default_utxo_selection_strategy: UtxoSelectionStrategyKind,
+
     /// Logger.
     logger: Logger,
 }
@@ -126,6 +170,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             peer_manager: self.peer_manager.clone(),
             submit_node_offset: self.submit_node_offset.clone(),
             fog_resolver_factory: self.fog_resolver_factory.clone(),
+            default_utxo_selection_strategy: self.default_utxo_selection_strategy,
             logger: self.logger.clone(),
         }
     }
@@ -174,6 +219,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         mobilecoind_db: Database,
         peer_manager: ConnectionManager<T>,
         fog_resolver_factory: Arc<dyn Fn(&[FogUri]) -> Result<FPR, String> + Send + Sync>,
+        default_utxo_selection_strategy: UtxoSelectionStrategyKind,
         logger: Logger,
     ) -> Self {
         let mut rng = rand::thread_rng();
@@ -183,6 +229,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             peer_manager,
             submit_node_offset: Arc::new(AtomicUsize::new(rng.next_u64() as usize)),
             fog_resolver_factory,
+            default_utxo_selection_strategy,
             logger,
         }
     }
@@ -224,6 +271,8 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
     /// * `opt_tombstone` - Tombstone block. If zero, sets to default.
     /// * `opt_memo_builder` - Optional memo builder to use instead of the
     ///   default one (EmptyMemoBuilder).
+    /// * `opt_utxo_selection_strategy` - Optional UTXO selection strategy to
+    ///   use instead of the daemon-wide default.
     pub fn build_transaction(
         &self,
         sender_monitor_id: &MonitorId,
@@ -234,10 +283,8 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         opt_fee: u64,
         opt_tombstone: u64,
         opt_memo_builder: Option<Box<dyn MemoBuilder + 'static + Send + Sync>>,
+        opt_utxo_selection_strategy: Option<UtxoSelectionStrategyKind>,
     ) -> Result<TxProposal, Error> {
-        let logger = self.logger.new(o!("sender_monitor_id" => sender_monitor_id.to_string(), "outlays" => format!("{:?}", outlays)));
-        log::trace!(logger, "Building pending transaction...");
-
         // All inputs must be of the correct token id.
         if inputs.iter().any(|utxo| utxo.token_id != *token_id) {
             return Err(Error::InvalidArgument(
@@ -246,6 +293,61 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             ));
         }
 
+        let outlays: Vec<OutlayV2> = outlays
+            .iter()
+            .map(|outlay| OutlayV2::new(outlay, token_id))
+            .collect();
+
+        self.build_mixed_transaction(
+            sender_monitor_id,
+            token_id,
+            change_subaddress,
+            inputs,
+            &outlays,
+            opt_fee,
+            opt_tombstone,
+            opt_memo_builder,
+            opt_utxo_selection_strategy,
+        )
+    }
+
+    /// Create a TxProposal whose outlays may be in several token ids.
+    ///
+    /// Inputs are selected separately for each token id involved, and any
+    /// change is returned to `change_subaddress` in each of them. Involving
+    /// more than one token id requires a block version that supports mixed
+    /// transactions.
+    ///
+    /// # Arguments
+    /// * `sender_monitor_id` - Indicates the the account key needed to spend
+    ///   the txo's.
+    /// * `fee_token_id` - The token id the fee is paid in.
+    /// * `change_subaddress` - Recipient of any change.
+    /// * `inputs` - UTXOs that may be spent by the transaction. UTXOs in token
+    ///   ids not involved in the transaction are ignored.
+    /// * `outlays` - Output amounts, token ids and recipients.
+    /// * `opt_fee` - Transaction fee, in the smallest unit of `fee_token_id`.
+    ///   If zero, defaults to the network minimum fee.
+    /// * `opt_tombstone` - Tombstone block. If zero, sets to default.
+    /// * `opt_memo_builder` - Optional memo builder to use instead of the
+    ///   default one (EmptyMemoBuilder).
+    /// * `opt_utxo_selection_strategy` - Optional UTXO selection strategy to
+    ///   use instead of the daemon-wide default.
+    pub fn build_mixed_transaction(
+        &self,
+        sender_monitor_id: &MonitorId,
+        fee_token_id: TokenId,
+        change_subaddress: u64,
+        inputs: &[UnspentTxOut],
+        outlays: &[OutlayV2],
+        opt_fee: u64,
+        opt_tombstone: u64,
+        opt_memo_builder: Option<Box<dyn MemoBuilder + 'static + Send + Sync>>,
+        opt_utxo_selection_strategy: Option<UtxoSelectionStrategyKind>,
+    ) -> Result<TxProposal, Error> {
+        let logger = self.logger.new(o!("sender_monitor_id" => sender_monitor_id.to_string(), "outlays" => format!("{:?}", outlays)));
+        log::trace!(logger, "Building pending transaction...");
+
         // Must have at least one output
         if outlays.is_empty() {
             return Err(Error::TxBuild("Must have at least one destination".into()));
@@ -253,26 +355,71 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
 
         // Get sender monitor data.
         let sender_monitor_data = self.mobilecoind_db.get_monitor_data(sender_monitor_id)?;
-
-        // Figure out total amount of transaction (excluding fee).
-        let total_value: u64 = outlays.iter().map(|outlay| outlay.value).sum();
+        let sender_account_key = sender_monitor_data.account_key()?;
+
+        // Figure out total amount of transaction (excluding fee), per token id.
+        let mut values_by_token_id: BTreeMap<TokenId, u64> = BTreeMap::new();
+        for outlay in outlays {
+            let total = values_by_token_id.entry(outlay.token_id).or_default();
+            *total = total.checked_add(outlay.value).ok_or_else(|| {
+                Error::InvalidArgument("outlays".to_string(), "total value overflow".to_string())
+            })?;
+        }
         log::trace!(
             logger,
-            "Total transaction value excluding fees: {}",
-            total_value
+            "Total transaction values excluding fees: {:?}",
+            values_by_token_id
         );
 
         // Figure out the block_version and fee (involves network round-trips to
         // consensus, unless opt_fee is non-zero)
-        let (fee, block_version) = self.get_network_fee_and_block_version(token_id, opt_fee)?;
+        let (fee, block_version) = self.get_network_fee_and_block_version(fee_token_id, opt_fee)?;
 
         // Confirm that we understand this block version
         let block_version =
             BlockVersion::try_from(block_version).map_err(|err| Error::TxBuild(err.to_string()))?;
 
-        // Select the UTXOs to be used for this transaction.
-        let selected_utxos =
-            Self::select_utxos_for_value(token_id, inputs, total_value + fee, MAX_INPUTS as usize)?;
+        // The fee needs to be covered by inputs of the fee token id.
+        let fee_total = values_by_token_id.entry(fee_token_id).or_default();
+        *fee_total = fee_total.checked_add(fee).ok_or_else(|| {
+            Error::InvalidArgument("fee".to_string(), "total value overflow".to_string())
+        })?;
+
+        // Every token id needs at least one input, which cannot be selected for a
+        // total value of zero.
+        if let Some((token_id, _)) = values_by_token_id.iter().find(|(_, value)| **value == 0) {
+            return Err(Error::InvalidArgument(
+                "outlays".to_string(),
+                format!("total value of token id {} is zero", token_id),
+            ));
+        }
+
+        if values_by_token_id.len() > 1 && !block_version.mixed_transactions_are_supported() {
+            return Err(Error::TxBuild(format!(
+                "Block version {} does not support transactions with more than one token id",
+                block_version
+            )));
+        }
+
+        // Select the UTXOs to be used for this transaction, separately for each token
+        // id. Every token id needs at least one input, so leave room for the
+        // ones that have not been selected yet.
+        let utxo_selection_strategy =
+            opt_utxo_selection_strategy.unwrap_or(self.default_utxo_selection_strategy);
+        let mut selected_utxos: Vec<UnspentTxOut> = Vec::new();
+        let num_token_ids = values_by_token_id.len();
+        for (i, (token_id, value)) in values_by_token_id.iter().enumerate() {
+            let max_inputs = (MAX_INPUTS as usize)
+                .saturating_sub(selected_utxos.len())
+                .saturating_sub(num_token_ids - i - 1);
+            selected_utxos.extend(Self::select_utxos_for_value(
+                *token_id,
+                inputs,
+                *value,
+                max_inputs,
+                &utxo_selection_strategy,
+            )?);
+        }
         log::trace!(
             logger,
             "Selected {} utxos ({:?})",
@@ -322,9 +469,9 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             &selected_utxos_with_proofs,
             rings,
             block_version,
-            token_id,
+            fee_token_id,
             fee,
-            &sender_monitor_data.account_key,
+            sender_account_key,
             change_subaddress,
             outlays,
             tombstone_block,
@@ -361,6 +508,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
 
         // Get monitor data.
         let monitor_data = self.mobilecoind_db.get_monitor_data(monitor_id)?;
+        let account_key = monitor_data.account_key()?;
 
         let num_blocks_in_ledger = self.ledger_db.num_blocks()?;
 
@@ -433,9 +581,10 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         log::trace!(logger, "Tombstone block set to {}", tombstone_block);
 
         // We are paying ourselves the entire amount.
-        let outlays = vec![Outlay {
-            receiver: monitor_data.account_key.subaddress(subaddress_index),
+        let outlays = vec![OutlayV2 {
+            receiver: account_key.subaddress(subaddress_index),
             value: total_value - fee,
+            token_id,
         }];
 
         // Build and return the TxProposal object
@@ -446,7 +595,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             block_version,
             token_id,
             fee,
-            &monitor_data.account_key,
+            account_key,
             subaddress_index,
             &outlays,
             tombstone_block,
@@ -536,9 +685,10 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         log::trace!(logger, "Tombstone block set to {}", tombstone_block);
 
         // The entire value goes to receiver
-        let outlays = vec![Outlay {
+        let outlays = vec![OutlayV2 {
             receiver: receiver.clone(),
             value: total_value - fee,
+            token_id,
         }];
 
         // Build and return the TxProposal object
@@ -597,63 +747,23 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         Ok(block_height)
     }
 
-    /// Returns a subset of UTXOs totalling at least the given amount.
+    /// Returns a subset of UTXOs of the given token id totalling at least the
+    /// given amount, chosen using the given selection strategy.
     // TODO: This method should take attempted_spend_height into account.
     fn select_utxos_for_value(
         token_id: TokenId,
         utxos: &[UnspentTxOut],
         value: u64,
         max_inputs: usize,
+        strategy: &dyn UtxoSelectionStrategy,
     ) -> Result<Vec<UnspentTxOut>, Error> {
-        // Sort the utxos in descending order by value.
-        let mut sorted_utxos: Vec<UnspentTxOut> = utxos
+        let token_utxos: Vec<UnspentTxOut> = utxos
             .iter()
             .filter(|utxo| utxo.token_id == token_id)
             .cloned()
             .collect();
-        sorted_utxos.sort_by_key(|utxo| Reverse(utxo.value));
-
-        // The maximum spendable is limited by the maximal number of inputs we can use.
-        let max_spendable_amount = sorted_utxos
-            .iter()
-            .take(max_inputs)
-            .map(|utxo| utxo.value)
-            .sum();
-        if value > max_spendable_amount {
-            // See if we merged the UTXOs we would be able to spend this amount.
-            let total_utxos_value: u64 = sorted_utxos.iter().map(|utxo| utxo.value).sum();
-            if total_utxos_value >= value {
-                return Err(Error::InsufficientFundsFragmentedUtxos);
-            } else {
-                return Err(Error::InsufficientFunds);
-            }
-        }
-
-        // Choose utxos to spend.
-        let mut selected_utxos: Vec<UnspentTxOut> = Vec::new();
-        loop {
-            let total: u64 = selected_utxos.iter().map(|utxo| utxo.value).sum();
-            if total >= value {
-                break;
-            }
-
-            // Grab the next (smallest utxo)
-            let next_utxo = sorted_utxos.pop().ok_or(Error::InsufficientFunds)?;
-            selected_utxos.push(next_utxo.clone());
-
-            // Cap at maximum allowed inputs.
-            if selected_utxos.len() > max_inputs {
-                // Remove the lowest utxo.
-                selected_utxos.remove(0);
-            }
-        }
 
-        // Sanity.
-        assert!(!selected_utxos.is_empty());
-        assert!(selected_utxos.len() <= max_inputs);
-
-        // Return selected utxos.
-        Ok(selected_utxos)
+        strategy.select_utxos(&token_utxos, value, max_inputs)
     }
 
     /// Select UTXOs for optimization. The current strategy is to to attempt to
@@ -822,8 +932,8 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
     /// * `inputs` - UTXOs to spend, with membership proofs.
     /// * `rings` - A set of mixins for each input, with membership proofs.
     /// * `block_version` - The block version to target for this transaction
-    /// * `token_id` - The token id to transact in
-    /// * `fee` - Transaction fee, in picoMOB.
+    /// * `fee_token_id` - The token id the fee is paid in
+    /// * `fee` - Transaction fee, in the smallest unit of `fee_token_id`.
     /// * `from_account_key` - Owns the inputs. Also the recipient of any
     ///   change.
     /// * `change_subaddress` - Subaddress for change recipient.
@@ -838,11 +948,11 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         inputs: &[(UnspentTxOut, TxOutMembershipProof)],
         rings: Vec<Vec<(TxOut, TxOutMembershipProof)>>,
         block_version: BlockVersion,
-        token_id: TokenId,
+        fee_token_id: TokenId,
         fee: u64,
         from_account_key: &AccountKey,
         change_subaddress: u64,
-        destinations: &[Outlay],
+        destinations: &[OutlayV2],
         tombstone_block: BlockIndex,
         fog_resolver_factory: &Arc<dyn Fn(&[FogUri]) -> Result<FPR, String> + Send + Sync>,
         opt_memo_builder: Option<Box<dyn MemoBuilder + 'static + Send + Sync>>,
@@ -882,7 +992,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         let memo_builder: Box<dyn MemoBuilder + Send + Sync> =
             opt_memo_builder.unwrap_or_else(|| Box::new(EmptyMemoBuilder::default()));
 
-        let fee_amount = Amount::new(fee, token_id);
+        let fee_amount = Amount::new(fee, fee_token_id);
         let mut tx_builder =
             TransactionBuilder::new_with_box(block_version, fee_amount, fog_resolver, memo_builder)
                 .map_err(|err| {
@@ -967,55 +1077,54 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         }
 
         // Add outputs to our destinations.
-        let mut total_value = 0;
         let mut tx_out_to_outlay_index = HashMap::default();
         let mut outlay_confirmation_numbers = Vec::default();
         for (i, outlay) in destinations.iter().enumerate() {
-            // TODO (GH #1867): If you want to support mixed transactions, use
-            // outlay-specific token id here
-            let amount = Amount {
-                value: outlay.value,
-                token_id,
-            };
             let (tx_out, confirmation_number) = tx_builder
-                .add_output(amount, &outlay.receiver, rng)
+                .add_output(outlay.amount(), &outlay.receiver, rng)
                 .map_err(|err| Error::TxBuild(format!("failed adding output: {}", err)))?;
 
             tx_out_to_outlay_index.insert(tx_out, i);
             outlay_confirmation_numbers.push(confirmation_number);
-
-            total_value += outlay.value;
         }
 
-        // Figure out if we have change.
-        let input_value = inputs
+        // Figure out if we have change, in each token id.
+        let mut change_by_token_id: BTreeMap<TokenId, u64> = BTreeMap::new();
+        for (utxo, _proof) in inputs {
+            *change_by_token_id
+                .entry(TokenId::from(utxo.token_id))
+                .or_default() += utxo.value;
+        }
+        for amount in destinations
             .iter()
-            .fold(0, |acc, (utxo, _proof)| acc + utxo.value);
-        if total_value > input_value {
-            return Err(Error::InsufficientFunds);
+            .map(OutlayV2::amount)
+            .chain(once(Amount::new(
+                tx_builder.get_fee(),
+                tx_builder.get_fee_token_id(),
+            )))
+        {
+            let change = change_by_token_id.entry(amount.token_id).or_default();
+            *change = change
+                .checked_sub(amount.value)
+                .ok_or(Error::InsufficientFunds)?;
         }
-        let change = input_value - total_value - tx_builder.get_fee();
 
         // If we do have nonzero change, add an output for that as well.
         // TODO (GH #1522): Should the exchange write destination memos?
         // If so then we must always write a change output, even if the change is zero
-        if change > 0 {
-            // TODO: If you want to support mixed transactions, use outlay-specific token id
-            // here
-            let change_amount = Amount {
-                value: change,
-                token_id,
-            };
-
-            let change_dest = ReservedSubaddresses::from_subaddress_index(
-                from_account_key,
-                Some(change_subaddress),
-                None,
-            );
-
-            tx_builder
-                .add_change_output(change_amount, &change_dest, rng)
-                .map_err(|err| Error::TxBuild(format!("failed adding output (change): {}", err)))?;
+        let change_dest = ReservedSubaddresses::from_subaddress_index(
+            from_account_key,
+            Some(change_subaddress),
+            None,
+        );
+        for (token_id, change) in change_by_token_id {
+            if change > 0 {
+                tx_builder
+                    .add_change_output(Amount::new(change, token_id), &change_dest, rng)
+                    .map_err(|err| {
+                        Error::TxBuild(format!("failed adding output (change): {}", err))
+                    })?;
+            }
         }
 
         // Set tombstone block.
@@ -1082,6 +1191,7 @@ fn extract_fog_uri(addr: &PublicAddress) -> Result<Option<FogUri>, Error> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::utxo_selection::{LargestFirst, SmallestFirst};
     use mc_connection::{HardcodedCredentialsProvider, ThickClient};
     use mc_crypto_keys::RistrettoPrivate;
     use mc_fog_report_validation::MockFogPubkeyResolver;
@@ -1132,7 +1242,9 @@ mod test {
         let selected_utxos = TransactionsManager::<
             ThickClient<HardcodedCredentialsProvider>,
             MockFogPubkeyResolver,
-        >::select_utxos_for_value(Mob::ID, &utxos, 300, utxos.len())
+        >::select_utxos_for_value(
+            Mob::ID, &utxos, 300, utxos.len(), &SmallestFirst
+        )
         .unwrap();
 
         assert_eq!(selected_utxos, vec![utxos[0].clone(), utxos[1].clone()]);
@@ -1141,7 +1253,9 @@ mod test {
         let selected_utxos = TransactionsManager::<
             ThickClient<HardcodedCredentialsProvider>,
             MockFogPubkeyResolver,
-        >::select_utxos_for_value(Mob::ID, &utxos, 301, utxos.len())
+        >::select_utxos_for_value(
+            Mob::ID, &utxos, 301, utxos.len(), &SmallestFirst
+        )
         .unwrap();
 
         assert_eq!(
@@ -1153,7 +1267,9 @@ mod test {
         let selected_utxos = TransactionsManager::<
             ThickClient<HardcodedCredentialsProvider>,
             MockFogPubkeyResolver,
-        >::select_utxos_for_value(Mob::ID, &utxos, 301, 2)
+        >::select_utxos_for_value(
+            Mob::ID, &utxos, 301, 2, &SmallestFirst
+        )
         .unwrap();
 
         assert_eq!(selected_utxos, vec![utxos[1].clone(), utxos[2].clone()]);
@@ -1165,7 +1281,7 @@ mod test {
         // While we have enough utxos to sum to 5, if the input limit is 4 we should
         // fail.
         match TransactionsManager::<ThickClient<HardcodedCredentialsProvider>, MockFogPubkeyResolver>::select_utxos_for_value(
-            Mob::ID, &utxos, 5, 4,
+            Mob::ID, &utxos, 5, 4, &SmallestFirst,
         ) {
             Err(Error::InsufficientFundsFragmentedUtxos) => {
                 // Expected.
@@ -1180,7 +1296,7 @@ mod test {
         // While we have enough utxos to sum to 5, if the input limit is 4 we should
         // fail.
         match TransactionsManager::<ThickClient<HardcodedCredentialsProvider>, MockFogPubkeyResolver>::select_utxos_for_value(
-            Mob::ID, &utxos, 50, 100,
+            Mob::ID, &utxos, 50, 100, &SmallestFirst,
         ) {
             Err(Error::InsufficientFunds) => {
                 // Expected.
@@ -1189,6 +1305,40 @@ mod test {
         };
     }
 
+    #[test]
+    fn test_select_utxos_for_value_uses_strategy_and_filters_token_id() {
+        let mut utxos = generate_utxos(4);
+
+        utxos[0].value = 100;
+        utxos[1].value = 200;
+        utxos[2].value = 300;
+        utxos[3].value = 5000;
+        utxos[3].token_id = 1;
+
+        // Largest-first picks the 300 input, and ignores the larger input of a
+        // different token id.
+        let selected_utxos = TransactionsManager::<
+            ThickClient<HardcodedCredentialsProvider>,
+            MockFogPubkeyResolver,
+        >::select_utxos_for_value(
+            Mob::ID, &utxos, 250, utxos.len(), &LargestFirst
+        )
+        .unwrap();
+
+        assert_eq!(selected_utxos, vec![utxos[2].clone()]);
+
+        // Smallest-first picks 100 + 200 for the same value.
+        let selected_utxos = TransactionsManager::<
+            ThickClient<HardcodedCredentialsProvider>,
+            MockFogPubkeyResolver,
+        >::select_utxos_for_value(
+            Mob::ID, &utxos, 250, utxos.len(), &SmallestFirst
+        )
+        .unwrap();
+
+        assert_eq!(selected_utxos, vec![utxos[0].clone(), utxos[1].clone()]);
+    }
+
     #[test]
     fn test_select_utxos_for_optimization_selects_smallest_inputs() {
         // Optimizing with max_inputs=2 should select 100, 2000
@@ -7,22 +7,31 @@
 //! * writes matching transactions to a local DB, organized by subaddress_id
 
 use crate::{
+    conversions::utxo_selection_strategy_from_proto,
     database::Database,
     error::Error,
+    history_store::{HistoryFilter, HistoryKey},
     monitor_store::{MonitorData, MonitorId},
-    payments::{Outlay, TransactionsManager, TxProposal},
+    payments::{Outlay, OutlayV2, TransactionsManager, TxProposal},
+    processed_block_store::ProcessedTxOut,
+    subscription::spawn_subscription,
     sync::SyncThread,
+    utxo_selection::UtxoSelectionStrategyKind,
     utxo_store::{UnspentTxOut, UtxoId},
+    webhook_store::WebhookConfig,
 };
 use bip39::{Language, Mnemonic, MnemonicType};
-use grpcio::{EnvBuilder, RpcContext, RpcStatus, RpcStatusCode, ServerBuilder, UnarySink};
+use grpcio::{
+    EnvBuilder, RpcContext, RpcStatus, RpcStatusCode, ServerBuilder, ServerStreamingSink, UnarySink,
+};
 use mc_account_keys::{
-    burn_address, AccountKey, PublicAddress, RootIdentity, DEFAULT_SUBADDRESS_INDEX,
+    burn_address, AccountKey, PublicAddress, RootIdentity, ShortAddressHash, ViewAccountKey,
+    DEFAULT_SUBADDRESS_INDEX,
 };
 use mc_account_keys_slip10::Slip10KeyGenerator;
 use mc_common::{
     logger::{log, Logger},
-    HashMap,
+    HashMap, HashSet,
 };
 use mc_connection::{BlockchainConnection, UserTxConnection};
 use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
@@ -38,21 +47,29 @@ use mc_transaction_core::{
     onetime_keys::recover_onetime_private_key,
     ring_signature::KeyImage,
     tx::{TxOut, TxOutConfirmationNumber, TxOutMembershipProof},
-    TokenId,
+    TokenId, TxOutKeyImage,
 };
-use mc_transaction_std::{BurnRedemptionMemo, BurnRedemptionMemoBuilder};
+use mc_transaction_std::{BurnRedemptionMemo, BurnRedemptionMemoBuilder, MemoType};
 use mc_util_from_random::FromRandom;
 use mc_util_grpc::{
-    rpc_internal_error, rpc_invalid_arg_error, rpc_logger, send_result, AdminService,
-    BuildInfoService, ConnectionUriGrpcioServer,
+    rpc_internal_error, rpc_invalid_arg_error, rpc_logger, rpc_precondition_error, send_result,
+    AdminService, BuildInfoService, ConnectionUriGrpcioServer,
 };
 use mc_watcher::watcher_db::WatcherDB;
 use protobuf::{ProtobufEnum, RepeatedField};
 use std::{
     convert::{TryFrom, TryInto},
+    iter::once,
     sync::{Arc, Mutex, RwLock},
 };
 
+/// Number of entries returned by GetTransactionHistory when the request does
+/// not specify a page size.
+const DEFAULT_TRANSACTION_HISTORY_PAGE_SIZE: usize = 100;
+
+/// Maximum number of entries returned by a single GetTransactionHistory call.
+const MAX_TRANSACTION_HISTORY_PAGE_SIZE: usize = 1000;
+
 pub struct Service {
     /// Sync thread.
     _sync_thread: Arc<Mutex<Option<SyncThread>>>,
@@ -212,25 +229,150 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         }
     }
 
+    /// Convert the outlays of a GenerateTx/SendPayment request. Exactly one of
+    /// `outlay_list` (whose outlays are all in `token_id`) and
+    /// `outlay_list_v2` may be non-empty.
+    fn get_outlays_v2(
+        &self,
+        outlay_list: &[mc_mobilecoind_api::Outlay],
+        outlay_list_v2: &[mc_mobilecoind_api::OutlayV2],
+        token_id: TokenId,
+    ) -> Result<Vec<OutlayV2>, RpcStatus> {
+        if !outlay_list.is_empty() && !outlay_list_v2.is_empty() {
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::INVALID_ARGUMENT,
+                "outlay_list and outlay_list_v2 cannot both be set".into(),
+            ));
+        }
+
+        let outlays = outlay_list
+            .iter()
+            .map(|outlay_proto| {
+                Outlay::try_from(outlay_proto)
+                    .map(|outlay| OutlayV2::new(&outlay, token_id))
+                    .map_err(|err| rpc_internal_error("outlay.try_from", err, &self.logger))
+            })
+            .chain(outlay_list_v2.iter().map(|outlay_proto| {
+                OutlayV2::try_from(outlay_proto)
+                    .map_err(|err| rpc_internal_error("outlay_v2.try_from", err, &self.logger))
+            }))
+            .collect::<Result<Vec<OutlayV2>, RpcStatus>>()?;
+
+        Ok(outlays)
+    }
+
+    /// Report an error returned by the transactions manager. Trying to spend
+    /// from a view-only monitor, or passing invalid arguments, is the client's
+    /// mistake, not an internal error.
+    fn transactions_manager_error(&self, context: &str, err: Error) -> RpcStatus {
+        match err {
+            Error::ViewOnlyMonitor => rpc_precondition_error(context, err, &self.logger),
+            Error::InvalidArgument(..) => rpc_invalid_arg_error(context, err, &self.logger),
+            err => rpc_internal_error(context, err, &self.logger),
+        }
+    }
+
+    /// Build a transaction for a GenerateTx/SendPayment request, using the
+    /// mixed-token transaction builder if the request used outlay_list_v2.
+    fn build_transaction(
+        &self,
+        is_mixed: bool,
+        sender_monitor_id: &MonitorId,
+        token_id: TokenId,
+        change_subaddress: u64,
+        inputs: &[UnspentTxOut],
+        outlays: &[OutlayV2],
+        fee: u64,
+        tombstone: u64,
+        opt_utxo_selection_strategy: Option<UtxoSelectionStrategyKind>,
+    ) -> Result<TxProposal, RpcStatus> {
+        if is_mixed {
+            self.transactions_manager
+                .build_mixed_transaction(
+                    sender_monitor_id,
+                    token_id,
+                    change_subaddress,
+                    inputs,
+                    outlays,
+                    fee,
+                    tombstone,
+                    None,
+                    opt_utxo_selection_strategy,
+                )
+                .map_err(|err| {
+                    self.transactions_manager_error(
+                        "transactions_manager.build_mixed_transaction",
+                        err,
+                    )
+                })
+        } else {
+            let outlays: Vec<Outlay> = outlays
+                .iter()
+                .map(|outlay| Outlay {
+                    value: outlay.value,
+                    receiver: outlay.receiver.clone(),
+                })
+                .collect();
+
+            self.transactions_manager
+                .build_transaction(
+                    sender_monitor_id,
+                    token_id,
+                    change_subaddress,
+                    inputs,
+                    &outlays,
+                    fee,
+                    tombstone,
+                    None,
+                    opt_utxo_selection_strategy,
+                )
+                .map_err(|err| {
+                    self.transactions_manager_error("transactions_manager.build_transaction", err)
+                })
+        }
+    }
+
     fn add_monitor_impl(
         &mut self,
         request: mc_mobilecoind_api::AddMonitorRequest,
     ) -> Result<mc_mobilecoind_api::AddMonitorResponse, RpcStatus> {
-        // Get the AccountKey from the GRPC request.
-        let proto_account_key = request.account_key.as_ref().ok_or_else(|| {
-            RpcStatus::with_message(RpcStatusCode::INVALID_ARGUMENT, "account_key".into())
-        })?;
-        let account_key = AccountKey::try_from(proto_account_key)
-            .map_err(|err| rpc_internal_error("account_key.try_from", err, &self.logger))?;
-
-        // Populate a new `MonitorData` instance.
-        let data = MonitorData::new(
-            account_key,
-            request.first_subaddress,
-            request.num_subaddresses,
-            request.first_block,
-            &request.name,
-        )
+        // Populate a new `MonitorData` instance from either the AccountKey or the
+        // ViewAccountKey in the GRPC request.
+        let data = match (
+            request.account_key.as_ref(),
+            request.view_account_key.as_ref(),
+        ) {
+            (Some(proto_account_key), None) => {
+                let account_key = AccountKey::try_from(proto_account_key)
+                    .map_err(|err| rpc_internal_error("account_key.try_from", err, &self.logger))?;
+                MonitorData::new(
+                    account_key,
+                    request.first_subaddress,
+                    request.num_subaddresses,
+                    request.first_block,
+                    &request.name,
+                )
+            }
+            (None, Some(proto_view_account_key)) => {
+                let view_account_key =
+                    ViewAccountKey::try_from(proto_view_account_key).map_err(|err| {
+                        rpc_invalid_arg_error("view_account_key.try_from", err, &self.logger)
+                    })?;
+                MonitorData::new_view_only(
+                    view_account_key,
+                    request.first_subaddress,
+                    request.num_subaddresses,
+                    request.first_block,
+                    &request.name,
+                )
+            }
+            _ => {
+                return Err(RpcStatus::with_message(
+                    RpcStatusCode::INVALID_ARGUMENT,
+                    "exactly one of account_key and view_account_key must be set".into(),
+                ))
+            }
+        }
         .map_err(|err| rpc_internal_error("monitor_data.new", err, &self.logger))?;
 
         // Insert into database. Return the id and flag if the monitor already existed.
@@ -299,7 +441,12 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             })?;
 
         let mut status = mc_mobilecoind_api::MonitorStatus::new();
-        status.set_account_key(mc_api::external::AccountKey::from(&data.account_key));
+        if let Some(account_key) = &data.account_key {
+            status.set_account_key(account_key.into());
+        }
+        if let Some(view_account_key) = &data.view_account_key {
+            status.set_view_account_key(view_account_key.into());
+        }
         status.set_first_subaddress(data.first_subaddress);
         status.set_num_subaddresses(data.num_subaddresses);
         status.set_first_block(data.first_block);
@@ -342,6 +489,138 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         Ok(response)
     }
 
+    fn set_monitor_webhook_impl(
+        &mut self,
+        request: mc_mobilecoind_api::SetMonitorWebhookRequest,
+    ) -> Result<mc_mobilecoind_api::Empty, RpcStatus> {
+        // Get MonitorId from from the GRPC request.
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        // An empty url removes the webhook.
+        let config = if request.url.is_empty() {
+            None
+        } else {
+            let url = reqwest::Url::parse(&request.url)
+                .map_err(|err| rpc_invalid_arg_error("url", err, &self.logger))?;
+            if url.scheme() != "http" && url.scheme() != "https" {
+                return Err(rpc_invalid_arg_error(
+                    "url",
+                    "scheme must be http or https",
+                    &self.logger,
+                ));
+            }
+            if request.secret.is_empty() {
+                return Err(rpc_invalid_arg_error(
+                    "secret",
+                    "must not be empty",
+                    &self.logger,
+                ));
+            }
+            Some(WebhookConfig {
+                url: request.url,
+                secret: request.secret,
+            })
+        };
+
+        self.mobilecoind_db
+            .set_monitor_webhook(&monitor_id, config.as_ref())
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.set_monitor_webhook", err, &self.logger)
+            })?;
+
+        Ok(mc_mobilecoind_api::Empty::new())
+    }
+
+    fn get_monitor_webhook_impl(
+        &mut self,
+        request: mc_mobilecoind_api::GetMonitorWebhookRequest,
+    ) -> Result<mc_mobilecoind_api::GetMonitorWebhookResponse, RpcStatus> {
+        // Get MonitorId from from the GRPC request.
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        let (config, num_pending_notifications) = self
+            .mobilecoind_db
+            .get_monitor_webhook(&monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_monitor_webhook", err, &self.logger)
+            })?;
+
+        let mut response = mc_mobilecoind_api::GetMonitorWebhookResponse::new();
+        if let Some(config) = config {
+            response.set_url(config.url);
+        }
+        response.set_num_pending_notifications(num_pending_notifications);
+        Ok(response)
+    }
+
+    fn get_unverified_tx_out_list_impl(
+        &mut self,
+        request: mc_mobilecoind_api::GetUnverifiedTxOutListRequest,
+    ) -> Result<mc_mobilecoind_api::GetUnverifiedTxOutListResponse, RpcStatus> {
+        // Get MonitorId from from the GRPC request.
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        let unverified_tx_outs = self
+            .mobilecoind_db
+            .get_unverified_tx_outs(&monitor_id)
+            .map_err(|err| match err {
+                Error::NotViewOnlyMonitor => rpc_precondition_error(
+                    "mobilecoind_db.get_unverified_tx_outs",
+                    err,
+                    &self.logger,
+                ),
+                err => {
+                    rpc_internal_error("mobilecoind_db.get_unverified_tx_outs", err, &self.logger)
+                }
+            })?;
+
+        let mut response = mc_mobilecoind_api::GetUnverifiedTxOutListResponse::new();
+        response.set_output_list(RepeatedField::from_vec(
+            unverified_tx_outs
+                .iter()
+                .map(mc_mobilecoind_api::UnverifiedTxOut::from)
+                .collect(),
+        ));
+        Ok(response)
+    }
+
+    fn import_key_images_impl(
+        &mut self,
+        request: mc_mobilecoind_api::ImportKeyImagesRequest,
+    ) -> Result<mc_mobilecoind_api::ImportKeyImagesResponse, RpcStatus> {
+        // Get MonitorId from from the GRPC request.
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        let key_images = request
+            .get_key_images()
+            .iter()
+            .map(TxOutKeyImage::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| rpc_invalid_arg_error("tx_out_key_image.try_from", err, &self.logger))?;
+
+        let (num_unspent, num_spent) = self
+            .mobilecoind_db
+            .import_key_images(&monitor_id, &key_images, &self.ledger_db)
+            .map_err(|err| match err {
+                Error::NotViewOnlyMonitor | Error::UnverifiedTxOutNotFound => {
+                    rpc_precondition_error("mobilecoind_db.import_key_images", err, &self.logger)
+                }
+                Error::InvalidKeyImageProof(_) => {
+                    rpc_invalid_arg_error("mobilecoind_db.import_key_images", err, &self.logger)
+                }
+                err => rpc_internal_error("mobilecoind_db.import_key_images", err, &self.logger),
+            })?;
+
+        let mut response = mc_mobilecoind_api::ImportKeyImagesResponse::new();
+        response.set_num_unspent(num_unspent as u64);
+        response.set_num_spent(num_spent as u64);
+        Ok(response)
+    }
+
     fn generate_root_entropy_impl(
         &mut self,
         _request: mc_mobilecoind_api::Empty,
@@ -432,7 +711,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         }
 
         // Get the subaddress.
-        let subaddress = data.account_key.subaddress(request.subaddress_index);
+        let subaddress = data.subaddress(request.subaddress_index);
 
         // Also build the b58 wrapper
         let mut wrapper = mc_mobilecoind_api::printable::PrintableWrapper::new();
@@ -819,6 +1098,22 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             ));
         }
 
+        // Get the list of outlays. Single-token requests use outlay_list and
+        // request.token_id, mixed requests use outlay_list_v2.
+        let outlays = self.get_outlays_v2(
+            request.get_outlay_list(),
+            request.get_outlay_list_v2(),
+            TokenId::from(request.token_id),
+        )?;
+        let is_mixed = !request.get_outlay_list_v2().is_empty();
+
+        // The token ids that inputs may be in.
+        let allowed_token_ids: HashSet<u64> = outlays
+            .iter()
+            .map(|outlay| *outlay.token_id)
+            .chain(once(request.token_id))
+            .collect();
+
         // Get the list of potential inputs passed to.
         let input_list: Vec<UnspentTxOut> = request
             .get_input_list()
@@ -831,7 +1126,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                 })?;
 
                 // Verify token id matches.
-                if utxo.token_id != request.token_id {
+                if !allowed_token_ids.contains(&utxo.token_id) {
                     return Err(RpcStatus::with_message(
                         RpcStatusCode::INVALID_ARGUMENT,
                         format!("input_list[{}].token_id", i),
@@ -862,32 +1157,18 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             })
             .collect::<Result<Vec<UnspentTxOut>, RpcStatus>>()?;
 
-        // Get the list of outlays.
-        let outlays: Vec<Outlay> = request
-            .get_outlay_list()
-            .iter()
-            .map(|outlay_proto| {
-                Outlay::try_from(outlay_proto)
-                    .map_err(|err| rpc_internal_error("outlay.try_from", err, &self.logger))
-            })
-            .collect::<Result<Vec<Outlay>, RpcStatus>>()?;
-
         // Attempt to construct a transaction.
-        let tx_proposal = self
-            .transactions_manager
-            .build_transaction(
-                &sender_monitor_id,
-                TokenId::from(request.token_id),
-                request.change_subaddress,
-                &input_list,
-                &outlays,
-                request.fee,
-                request.tombstone,
-                None,
-            )
-            .map_err(|err| {
-                rpc_internal_error("transactions_manager.build_transaction", err, &self.logger)
-            })?;
+        let tx_proposal = self.build_transaction(
+            is_mixed,
+            &sender_monitor_id,
+            TokenId::from(request.token_id),
+            request.change_subaddress,
+            &input_list,
+            &outlays,
+            request.fee,
+            request.tombstone,
+            utxo_selection_strategy_from_proto(request.get_utxo_selection_strategy()),
+        )?;
 
         // Success.
         let mut response = mc_mobilecoind_api::GenerateTxResponse::new();
@@ -913,10 +1194,9 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                 request.fee,
             )
             .map_err(|err| {
-                rpc_internal_error(
+                self.transactions_manager_error(
                     "transactions_manager.generate_optimization_tx",
                     err,
-                    &self.logger,
                 )
             })?;
 
@@ -1082,9 +1362,10 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                 request.fee,
                 request.tombstone,
                 Some(Box::new(memo_builder)),
+                None,
             )
             .map_err(|err| {
-                rpc_internal_error("transactions_manager.build_transaction", err, &self.logger)
+                self.transactions_manager_error("transactions_manager.build_transaction", err)
             })?;
 
         // Success.
@@ -1595,7 +1876,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                                         &self.logger,
                                     )
                                 })?;
-                        let view_private_key = monitor_data.account_key.view_private_key();
+                        let view_private_key = monitor_data.view_private_key();
 
                         if request.get_receipt().get_confirmation_number().len() != 32 {
                             return Err(RpcStatus::with_message(
@@ -1668,30 +1949,16 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         Ok(response)
     }
 
-    fn get_processed_block_impl(
-        &mut self,
-        request: mc_mobilecoind_api::GetProcessedBlockRequest,
-    ) -> Result<mc_mobilecoind_api::GetProcessedBlockResponse, RpcStatus> {
-        // Get MonitorId from from the GRPC request.
-        let monitor_id = MonitorId::try_from(&request.monitor_id)
-            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
-
-        // We will use the AccountKey to compute the Address Code
-        let account_key = self
-            .mobilecoind_db
-            .get_monitor_data(&monitor_id)
-            .map_err(|err| {
-                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
-            })?
-            .account_key;
-
-        // Get all processed block data for the requested block.
-        let processed_tx_outs = self
-            .mobilecoind_db
-            .get_processed_block(&monitor_id, request.block)
-            .map_err(|err| {
-                rpc_internal_error("mobilecoind_db.get_processed_block", err, &self.logger)
-            })?
+    /// Convert processed TxOuts belonging to a monitor into their API
+    /// representation, which includes the address code of the subaddress and
+    /// the decrypted memo payload.
+    fn processed_tx_outs_to_proto(
+        &self,
+        monitor_id: &MonitorId,
+        monitor_data: &MonitorData,
+        processed_tx_outs: &[ProcessedTxOut],
+    ) -> Result<Vec<mc_mobilecoind_api::ProcessedTxOut>, RpcStatus> {
+        processed_tx_outs
             .iter()
             .map(|src| {
                 let mut dst = mc_mobilecoind_api::ProcessedTxOut::new();
@@ -1705,7 +1972,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                         .unwrap_or(mc_mobilecoind_api::ProcessedTxOutDirection::Invalid),
                 );
 
-                let subaddress = account_key.subaddress(src.subaddress_index);
+                let subaddress = monitor_data.subaddress(src.subaddress_index);
                 let mut wrapper = mc_mobilecoind_api::printable::PrintableWrapper::new();
                 wrapper.set_public_address((&subaddress).into());
                 let encoded = wrapper
@@ -1713,9 +1980,62 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                     .map_err(|err| rpc_internal_error("wrapper.b58_encode", err, &self.logger))?;
                 dst.set_address_code(encoded);
                 dst.set_token_id(src.token_id);
+
+                // Look up the TxOut in the ledger in order to decrypt its memo.
+                let tx_out_index = self
+                    .ledger_db
+                    .get_tx_out_index_by_public_key(&src.public_key)
+                    .map_err(|err| {
+                        rpc_internal_error(
+                            "ledger_db.get_tx_out_index_by_public_key",
+                            err,
+                            &self.logger,
+                        )
+                    })?;
+                let tx_out = self
+                    .ledger_db
+                    .get_tx_out_by_index(tx_out_index)
+                    .map_err(|err| {
+                        rpc_internal_error("ledger_db.get_tx_out_by_index", err, &self.logger)
+                    })?;
+                let tx_public_key =
+                    RistrettoPublic::try_from(&tx_out.public_key).map_err(|err| {
+                        rpc_internal_error("RistrettoPublic.try_from", err, &self.logger)
+                    })?;
+                let shared_secret =
+                    get_tx_out_shared_secret(monitor_data.view_private_key(), &tx_public_key);
+                dst.set_memo_payload(tx_out.decrypt_memo(&shared_secret).as_ref().to_vec());
+
                 Ok(dst)
             })
-            .collect::<Result<Vec<_>, _>>()?;
+            .collect()
+    }
+
+    fn get_processed_block_impl(
+        &mut self,
+        request: mc_mobilecoind_api::GetProcessedBlockRequest,
+    ) -> Result<mc_mobilecoind_api::GetProcessedBlockResponse, RpcStatus> {
+        // Get MonitorId from from the GRPC request.
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        // We will use the monitor's keys to compute the Address Code
+        let monitor_data = self
+            .mobilecoind_db
+            .get_monitor_data(&monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+            })?;
+
+        // Get all processed block data for the requested block.
+        let processed_tx_outs = self
+            .mobilecoind_db
+            .get_processed_block(&monitor_id, request.block)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_processed_block", err, &self.logger)
+            })?;
+        let processed_tx_outs =
+            self.processed_tx_outs_to_proto(&monitor_id, &monitor_data, &processed_tx_outs)?;
 
         // Return response
         let mut response = mc_mobilecoind_api::GetProcessedBlockResponse::new();
@@ -1759,48 +2079,180 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         Ok(response)
     }
 
-    fn get_balance_impl(
+    fn get_transaction_history_impl(
         &mut self,
-        request: mc_mobilecoind_api::GetBalanceRequest,
-    ) -> Result<mc_mobilecoind_api::GetBalanceResponse, RpcStatus> {
+        request: mc_mobilecoind_api::GetTransactionHistoryRequest,
+    ) -> Result<mc_mobilecoind_api::GetTransactionHistoryResponse, RpcStatus> {
         // Get MonitorId from from the GRPC request.
         let monitor_id = MonitorId::try_from(&request.monitor_id)
             .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
 
-        // Get all utxos for this monitor id.
-        let utxos = self
+        // We need the AccountKey to validate memos. View-only monitors have no
+        // transaction history.
+        let account_key = self
             .mobilecoind_db
-            .get_utxos_for_subaddress(&monitor_id, request.subaddress_index)
+            .get_monitor_data(&monitor_id)
             .map_err(|err| {
-                rpc_internal_error("mobilecoind_db.get_utxos_for_subaddress", err, &self.logger)
+                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+            })?
+            .account_key
+            .ok_or_else(|| {
+                rpc_precondition_error("monitor_id", Error::ViewOnlyMonitor, &self.logger)
             })?;
 
-        // Sum them up.
-        let balance = utxos
+        // Figure out where to start: either the page token, or the start block.
+        let start_key = if request.get_page_token().is_empty() {
+            HistoryKey::new(&monitor_id, request.start_block, 0)
+        } else {
+            let page_token: [u8; 12] = request.get_page_token().try_into().map_err(|_| {
+                RpcStatus::with_message(RpcStatusCode::INVALID_ARGUMENT, "page_token".to_string())
+            })?;
+            HistoryKey::new(
+                &monitor_id,
+                u64::from_be_bytes(page_token[0..8].try_into().expect("arithmetic error")),
+                u32::from_be_bytes(page_token[8..12].try_into().expect("arithmetic error")),
+            )
+        };
+
+        let counterparty_address_hashes = request
+            .get_counterparty_address_hashes()
             .iter()
-            // Filter only to the requested token id.
-            .filter(|utxo| utxo.token_id == request.token_id)
-            .map(|utxo| utxo.value as u128)
-            .sum::<u128>();
+            .map(|hash| {
+                <[u8; 16]>::try_from(&hash[..])
+                    .map(ShortAddressHash::from)
+                    .map_err(|_| {
+                        RpcStatus::with_message(
+                            RpcStatusCode::INVALID_ARGUMENT,
+                            "counterparty_address_hashes".to_string(),
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
-        // It's possible the balance does not fit into a u64.
-        if balance > u64::max_value().into() {
-            return Err(RpcStatus::with_message(
-                RpcStatusCode::INTERNAL,
-                format!(
-                    "balance of {} won't fit in u64, fetch utxo list instead",
-                    balance
-                ),
-            ));
-        }
+        let known_addresses = request
+            .get_known_addresses()
+            .iter()
+            .map(PublicAddress::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| rpc_internal_error("PublicAddress.try_from", err, &self.logger))?;
 
-        // Return response.
-        let mut response = mc_mobilecoind_api::GetBalanceResponse::new();
-        response.set_balance(balance as u64);
-        Ok(response)
-    }
+        let filter = HistoryFilter {
+            subaddress_indices: request.get_subaddress_indices().to_vec(),
+            token_ids: request.get_token_ids().to_vec(),
+            counterparty_address_hashes,
+            end_block: (request.end_block != 0).then(|| request.end_block),
+        };
 
-    fn send_payment_impl(
+        let page_size = match request.page_size as usize {
+            0 => DEFAULT_TRANSACTION_HISTORY_PAGE_SIZE,
+            page_size => page_size.min(MAX_TRANSACTION_HISTORY_PAGE_SIZE),
+        };
+
+        let page = self
+            .mobilecoind_db
+            .get_history(&start_key, &filter, page_size)
+            .map_err(|err| rpc_internal_error("mobilecoind_db.get_history", err, &self.logger))?;
+
+        let entries = page
+            .records
+            .iter()
+            .map(|(key, record)| {
+                let mut entry = mc_mobilecoind_api::TransactionHistoryEntry::new();
+                entry.set_block(key.block_index);
+                entry.set_subaddress_index(record.subaddress_index);
+                entry.set_public_key((&record.public_key).into());
+                entry.set_key_image((&record.key_image).into());
+                entry.set_value(record.value);
+                entry.set_token_id(record.token_id);
+                entry.set_direction(
+                    mc_mobilecoind_api::ProcessedTxOutDirection::from_i32(record.direction)
+                        .unwrap_or(mc_mobilecoind_api::ProcessedTxOutDirection::Invalid),
+                );
+                entry.set_memo_payload(record.memo_payload.clone());
+                entry.set_counterparty_address_hash(record.counterparty_address_hash.clone());
+
+                match record.memo() {
+                    Some(MemoType::AuthenticatedSender(memo)) => {
+                        let mut dst = mc_mobilecoind_api::AuthenticatedSenderMemo::new();
+                        dst.set_sender_address_hash(memo.sender_address_hash().as_ref().to_vec());
+                        entry.set_authenticated_sender_memo(dst);
+                    }
+                    Some(MemoType::AuthenticatedSenderWithPaymentRequestId(memo)) => {
+                        let mut dst = mc_mobilecoind_api::AuthenticatedSenderMemo::new();
+                        dst.set_sender_address_hash(memo.sender_address_hash().as_ref().to_vec());
+                        dst.set_payment_request_id(memo.payment_request_id());
+                        entry.set_authenticated_sender_memo(dst);
+                    }
+                    Some(MemoType::Destination(memo)) => {
+                        let mut dst = mc_mobilecoind_api::DestinationMemo::new();
+                        dst.set_recipient_address_hash(memo.get_address_hash().as_ref().to_vec());
+                        dst.set_num_recipients(memo.get_num_recipients() as u32);
+                        dst.set_fee(memo.get_fee());
+                        dst.set_total_outlay(memo.get_total_outlay());
+                        entry.set_destination_memo(dst);
+                    }
+                    _ => {}
+                }
+
+                entry.set_memo_validation_status(
+                    record.validate_memo(&account_key, &known_addresses).into(),
+                );
+                entry
+            })
+            .collect();
+
+        let mut response = mc_mobilecoind_api::GetTransactionHistoryResponse::new();
+        response.set_entries(RepeatedField::from_vec(entries));
+        if let Some(next_key) = page.next_key {
+            let mut next_page_token = next_key.block_index.to_be_bytes().to_vec();
+            next_page_token.extend_from_slice(&next_key.index_in_block.to_be_bytes());
+            response.set_next_page_token(next_page_token);
+        }
+        Ok(response)
+    }
+
+    fn get_balance_impl(
+        &mut self,
+        request: mc_mobilecoind_api::GetBalanceRequest,
+    ) -> Result<mc_mobilecoind_api::GetBalanceResponse, RpcStatus> {
+        // Get MonitorId from from the GRPC request.
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        // Get all utxos for this monitor id.
+        let utxos = self
+            .mobilecoind_db
+            .get_utxos_for_subaddress(&monitor_id, request.subaddress_index)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_utxos_for_subaddress", err, &self.logger)
+            })?;
+
+        // Sum them up.
+        let balance = utxos
+            .iter()
+            // Filter only to the requested token id.
+            .filter(|utxo| utxo.token_id == request.token_id)
+            .map(|utxo| utxo.value as u128)
+            .sum::<u128>();
+
+        // It's possible the balance does not fit into a u64.
+        if balance > u64::max_value().into() {
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::INTERNAL,
+                format!(
+                    "balance of {} won't fit in u64, fetch utxo list instead",
+                    balance
+                ),
+            ));
+        }
+
+        // Return response.
+        let mut response = mc_mobilecoind_api::GetBalanceResponse::new();
+        response.set_balance(balance as u64);
+        Ok(response)
+    }
+
+    fn send_payment_impl(
         &mut self,
         request: mc_mobilecoind_api::SendPaymentRequest,
     ) -> Result<mc_mobilecoind_api::SendPaymentResponse, RpcStatus> {
@@ -1821,18 +2273,22 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             utxos.retain(|utxo| utxo.value <= request.max_input_utxo_value);
         }
 
-        // Filter for requested token id.
-        utxos.retain(|utxo| utxo.token_id == request.token_id);
-
-        // Get the list of outlays.
-        let outlays: Vec<Outlay> = request
-            .get_outlay_list()
+        // Get the list of outlays. Single-token requests use outlay_list and
+        // request.token_id, mixed requests use outlay_list_v2.
+        let outlays = self.get_outlays_v2(
+            request.get_outlay_list(),
+            request.get_outlay_list_v2(),
+            TokenId::from(request.token_id),
+        )?;
+        let is_mixed = !request.get_outlay_list_v2().is_empty();
+
+        // Filter for the token ids involved in the transaction.
+        let token_ids: HashSet<u64> = outlays
             .iter()
-            .map(|outlay_proto| {
-                Outlay::try_from(outlay_proto)
-                    .map_err(|err| rpc_internal_error("outlay.try_from", err, &self.logger))
-            })
-            .collect::<Result<Vec<Outlay>, RpcStatus>>()?;
+            .map(|outlay| *outlay.token_id)
+            .chain(once(request.token_id))
+            .collect();
+        utxos.retain(|utxo| token_ids.contains(&utxo.token_id));
 
         // Set change address to sender address unless it has been overridden
         let change_subaddress = if request.override_change_subaddress {
@@ -1842,21 +2298,17 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         };
 
         // Attempt to construct a transaction.
-        let tx_proposal = self
-            .transactions_manager
-            .build_transaction(
-                &sender_monitor_id,
-                TokenId::from(request.token_id),
-                change_subaddress,
-                &utxos,
-                &outlays,
-                request.fee,
-                request.tombstone,
-                None,
-            )
-            .map_err(|err| {
-                rpc_internal_error("transactions_manager.build_transaction", err, &self.logger)
-            })?;
+        let tx_proposal = self.build_transaction(
+            is_mixed,
+            &sender_monitor_id,
+            TokenId::from(request.token_id),
+            change_subaddress,
+            &utxos,
+            &outlays,
+            request.fee,
+            request.tombstone,
+            utxo_selection_strategy_from_proto(request.get_utxo_selection_strategy()),
+        )?;
 
         let proto_tx_proposal = mc_mobilecoind_api::TxProposal::from(&tx_proposal);
 
@@ -1906,6 +2358,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         send_payment_request.set_override_change_subaddress(request.override_change_subaddress);
         send_payment_request.set_change_subaddress(request.change_subaddress);
         send_payment_request.set_token_id(request.token_id);
+        send_payment_request.set_utxo_selection_strategy(request.get_utxo_selection_strategy());
 
         self.send_payment_impl(send_payment_request)
     }
@@ -1989,10 +2442,104 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
 
         Ok(mc_mobilecoind_api::Empty::default())
     }
+
+    fn subscribe_blocks_impl(
+        &mut self,
+        request: mc_mobilecoind_api::SubscribeBlocksRequest,
+    ) -> Result<
+        (
+            u64,
+            impl FnMut(u64) -> Result<Option<mc_mobilecoind_api::GetBlockResponse>, RpcStatus>
+                + Send
+                + 'static,
+        ),
+        RpcStatus,
+    > {
+        let mut api = self.clone();
+        let fetch = move |block| {
+            let num_blocks = api
+                .ledger_db
+                .num_blocks()
+                .map_err(|err| rpc_internal_error("ledger_db.num_blocks", err, &api.logger))?;
+            if block >= num_blocks {
+                return Ok(None);
+            }
+
+            let mut get_block_request = mc_mobilecoind_api::GetBlockRequest::new();
+            get_block_request.set_block(block);
+            api.get_block_impl(get_block_request).map(Some)
+        };
+
+        Ok((request.start_block, fetch))
+    }
+
+    fn subscribe_monitor_events_impl(
+        &mut self,
+        request: mc_mobilecoind_api::SubscribeMonitorEventsRequest,
+    ) -> Result<
+        (
+            u64,
+            impl FnMut(
+                    u64,
+                ) -> Result<
+                    Option<mc_mobilecoind_api::SubscribeMonitorEventsResponse>,
+                    RpcStatus,
+                > + Send
+                + 'static,
+        ),
+        RpcStatus,
+    > {
+        // Get MonitorId from from the GRPC request.
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        // Get monitor data, this also ensures the monitor exists.
+        let monitor_data = self
+            .mobilecoind_db
+            .get_monitor_data(&monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+            })?;
+        if request.start_block < monitor_data.first_block {
+            return Err(rpc_invalid_arg_error(
+                "start_block",
+                Error::BlockIndexTooSmall(request.start_block, monitor_data.first_block),
+                &self.logger,
+            ));
+        }
+
+        let api = self.clone();
+        let fetch = move |block| {
+            let processed_tx_outs = match api.mobilecoind_db.get_processed_block(&monitor_id, block)
+            {
+                Ok(processed_tx_outs) => processed_tx_outs,
+                Err(Error::BlockNotYetProcessed(_, _)) => return Ok(None),
+                Err(err) => {
+                    return Err(rpc_internal_error(
+                        "mobilecoind_db.get_processed_block",
+                        err,
+                        &api.logger,
+                    ))
+                }
+            };
+
+            let mut response = mc_mobilecoind_api::SubscribeMonitorEventsResponse::new();
+            response.set_block(block);
+            response.set_tx_outs(RepeatedField::from_vec(api.processed_tx_outs_to_proto(
+                &monitor_id,
+                &monitor_data,
+                &processed_tx_outs,
+            )?));
+            Ok(Some(response))
+        };
+
+        Ok((request.start_block, fetch))
+    }
 }
 
 macro_rules! build_api {
-    ($( $service_function_name:ident $service_request_type:ident $service_response_type:ident $service_function_impl:ident ),+)
+    ($( $service_function_name:ident $service_request_type:ident $service_response_type:ident $service_function_impl:ident ),+ ;
+     $( stream $stream_function_name:ident $stream_request_type:ident $stream_response_type:ident $stream_function_impl:ident ),*)
     =>
     (
         impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolver> MobilecoindApi for ServiceApi<T, FPR> {
@@ -2012,6 +2559,24 @@ macro_rules! build_api {
                     )
                 }
             )+
+            $(
+                fn $stream_function_name(
+                    &mut self,
+                    ctx: RpcContext,
+                    request: mc_mobilecoind_api::$stream_request_type,
+                    sink: ServerStreamingSink<mc_mobilecoind_api::$stream_response_type>,
+                ) {
+                    let logger = rpc_logger(&ctx, &self.logger);
+                    spawn_subscription(
+                        &ctx,
+                        sink,
+                        stringify!($stream_function_name),
+                        self.mobilecoind_db.block_processed_notifier().clone(),
+                        self.$stream_function_impl(request),
+                        logger,
+                    )
+                }
+            )*
         }
     );
 }
@@ -2023,6 +2588,10 @@ build_api! {
     get_monitor_list Empty GetMonitorListResponse get_monitor_list_impl,
     get_monitor_status GetMonitorStatusRequest GetMonitorStatusResponse get_monitor_status_impl,
     get_unspent_tx_out_list GetUnspentTxOutListRequest GetUnspentTxOutListResponse get_unspent_tx_out_list_impl,
+    set_monitor_webhook SetMonitorWebhookRequest Empty set_monitor_webhook_impl,
+    get_monitor_webhook GetMonitorWebhookRequest GetMonitorWebhookResponse get_monitor_webhook_impl,
+    get_unverified_tx_out_list GetUnverifiedTxOutListRequest GetUnverifiedTxOutListResponse get_unverified_tx_out_list_impl,
+    import_key_images ImportKeyImagesRequest ImportKeyImagesResponse import_key_images_impl,
 
     // Utilities
     generate_root_entropy Empty GenerateRootEntropyResponse generate_root_entropy_impl,
@@ -2057,6 +2626,7 @@ build_api! {
     get_tx_status_as_receiver GetTxStatusAsReceiverRequest GetTxStatusAsReceiverResponse get_tx_status_as_receiver_impl,
     get_processed_block GetProcessedBlockRequest GetProcessedBlockResponse get_processed_block_impl,
     get_block_index_by_tx_pub_key GetBlockIndexByTxPubKeyRequest GetBlockIndexByTxPubKeyResponse get_block_index_by_tx_pub_key_impl,
+    get_transaction_history GetTransactionHistoryRequest GetTransactionHistoryResponse get_transaction_history_impl,
 
     // Convenience calls
     get_balance GetBalanceRequest GetBalanceResponse get_balance_impl,
@@ -2068,7 +2638,11 @@ build_api! {
 
     // Database encryption
     set_db_password SetDbPasswordRequest Empty set_db_password_impl,
-    unlock_db UnlockDbRequest Empty unlock_db_impl
+    unlock_db UnlockDbRequest Empty unlock_db_impl;
+
+    // Subscriptions
+    stream subscribe_blocks SubscribeBlocksRequest GetBlockResponse subscribe_blocks_impl,
+    stream subscribe_monitor_events SubscribeMonitorEventsRequest SubscribeMonitorEventsResponse subscribe_monitor_events_impl
 }
 
 #[cfg(test)]
@@ -2084,6 +2658,7 @@ mod test {
         },
         utxo_store::UnspentTxOut,
     };
+    use futures::executor::block_on_stream;
     use grpcio::Error as GrpcError;
     use mc_account_keys::{
         burn_address_view_private, AccountKey, PublicAddress, ShortAddressHash,
@@ -2101,7 +2676,7 @@ mod test {
         onetime_keys::{recover_onetime_private_key, recover_public_subaddress_spend_key},
         tokens::Mob,
         tx::{Tx, TxOut},
-        Amount, Block, BlockContents, BlockVersion, Token,
+        Amount, Block, BlockContents, BlockVersion, MemoPayload, Token,
     };
     use mc_transaction_std::{EmptyMemoBuilder, MemoType, TransactionBuilder};
     use mc_util_repr_bytes::{typenum::U32, GenericArray, ReprBytes};
@@ -2136,7 +2711,9 @@ mod test {
         .expect("failed to create data");
 
         let mut request = mc_mobilecoind_api::AddMonitorRequest::new();
-        request.set_account_key(mc_api::external::AccountKey::from(&data.account_key));
+        request.set_account_key(mc_api::external::AccountKey::from(
+            data.account_key().unwrap(),
+        ));
         request.set_first_subaddress(data.first_subaddress);
         request.set_num_subaddresses(data.num_subaddresses);
         request.set_first_block(data.first_block);
@@ -2301,8 +2878,9 @@ mod test {
         // Verify the data we got matches what we expected
         assert_eq!(
             data.account_key,
-            AccountKey::try_from(status.account_key.as_ref().unwrap()).unwrap(),
+            Some(AccountKey::try_from(status.account_key.as_ref().unwrap()).unwrap()),
         );
+        assert!(status.view_account_key.is_none());
         assert_eq!(status.first_subaddress, data.first_subaddress);
         assert_eq!(status.num_subaddresses, data.num_subaddresses);
         assert_eq!(status.first_block, data.first_block);
@@ -2324,6 +2902,236 @@ mod test {
         assert!(client.get_monitor_status(&request).is_err());
     }
 
+    #[test_with_logger]
+    fn test_view_only_monitor(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([24u8; 32]);
+
+        let account_key = AccountKey::random(&mut rng);
+        let data = MonitorData::new_view_only(
+            ViewAccountKey::from(&account_key),
+            DEFAULT_SUBADDRESS_INDEX, // first_subaddress
+            1,                        // num_subaddresses
+            0,                        // first_block
+            "",                       // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[account_key.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Add the monitor through the API, which must be given exactly one key.
+        let mut request = mc_mobilecoind_api::AddMonitorRequest::new();
+        request.set_view_account_key(data.view_account_key.as_ref().unwrap().into());
+        request.set_account_key((&account_key).into());
+        assert!(client.add_monitor(&request).is_err());
+
+        request.clear_account_key();
+        let response = client.add_monitor(&request).unwrap();
+        let monitor_id = MonitorId::try_from(&response.monitor_id).unwrap();
+        assert_eq!(monitor_id, MonitorId::from(&data));
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        let mut request = mc_mobilecoind_api::GetMonitorStatusRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        let status = client.get_monitor_status(&request).unwrap().take_status();
+        assert!(status.account_key.is_none());
+        assert_eq!(
+            ViewAccountKey::try_from(status.get_view_account_key()).unwrap(),
+            ViewAccountKey::from(&account_key),
+        );
+
+        // The monitor found our TxOuts, but cannot know their key images.
+        let mut request = mc_mobilecoind_api::GetUnverifiedTxOutListRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        let unverified_tx_outs = client
+            .get_unverified_tx_out_list(&request)
+            .unwrap()
+            .take_output_list()
+            .into_vec();
+        assert_eq!(
+            unverified_tx_outs.len(),
+            test_utils::GET_TESTING_ENVIRONMENT_NUM_BLOCKS
+        );
+        assert!(mobilecoind_db
+            .get_utxos_for_subaddress(&monitor_id, DEFAULT_SUBADDRESS_INDEX)
+            .unwrap()
+            .is_empty());
+
+        // Spending is refused.
+        let mut request = mc_mobilecoind_api::GenerateOptimizationTxRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_subaddress(DEFAULT_SUBADDRESS_INDEX);
+        match client.generate_optimization_tx(&request) {
+            Ok(_) => panic!("Should've returned an error"),
+            Err(GrpcError::RpcFailure(rpc_status)) => {
+                assert_eq!(rpc_status.code(), RpcStatusCode::FAILED_PRECONDITION);
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        };
+
+        // Key images must come with a valid proof: a proof for another TxOut is
+        // rejected, and nothing is imported.
+        let tx_outs: Vec<_> = unverified_tx_outs
+            .iter()
+            .map(|unverified_tx_out| TxOut::try_from(unverified_tx_out.get_tx_out()).unwrap())
+            .collect();
+        let key_images: Vec<_> = unverified_tx_outs
+            .iter()
+            .zip(tx_outs.iter())
+            .map(|(unverified_tx_out, tx_out)| {
+                TxOutKeyImage::new(
+                    tx_out,
+                    &account_key,
+                    unverified_tx_out.subaddress_index,
+                    &mut rng,
+                )
+                .unwrap()
+            })
+            .collect();
+        let mut forged_key_image = key_images[1].clone();
+        forged_key_image.tx_public_key = key_images[0].tx_public_key;
+
+        let mut request = mc_mobilecoind_api::ImportKeyImagesRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_key_images(RepeatedField::from_vec(vec![(&forged_key_image).into()]));
+        match client.import_key_images(&request) {
+            Ok(_) => panic!("Should've returned an error"),
+            Err(GrpcError::RpcFailure(rpc_status)) => {
+                assert_eq!(rpc_status.code(), RpcStatusCode::INVALID_ARGUMENT);
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        };
+        assert_eq!(
+            mobilecoind_db
+                .get_unverified_tx_outs(&monitor_id)
+                .unwrap()
+                .len(),
+            test_utils::GET_TESTING_ENVIRONMENT_NUM_BLOCKS
+        );
+
+        // Import the key images computed by the holder of the spend key.
+        let mut request = mc_mobilecoind_api::ImportKeyImagesRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_key_images(RepeatedField::from_vec(
+            key_images.iter().map(Into::into).collect(),
+        ));
+        let response = client.import_key_images(&request).unwrap();
+        assert_eq!(
+            response.num_unspent,
+            test_utils::GET_TESTING_ENVIRONMENT_NUM_BLOCKS as u64
+        );
+        assert_eq!(response.num_spent, 0);
+
+        // The TxOuts are now regular UnspentTxOuts.
+        assert_eq!(
+            mobilecoind_db
+                .get_utxos_for_subaddress(&monitor_id, DEFAULT_SUBADDRESS_INDEX)
+                .unwrap()
+                .len(),
+            test_utils::GET_TESTING_ENVIRONMENT_NUM_BLOCKS
+        );
+        assert!(mobilecoind_db
+            .get_unverified_tx_outs(&monitor_id)
+            .unwrap()
+            .is_empty());
+
+        // Importing the same key images again fails.
+        assert!(client.import_key_images(&request).is_err());
+    }
+
+    #[test_with_logger]
+    fn test_monitor_webhook_impl(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
+
+        let account_key = AccountKey::random(&mut rng);
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (mut ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[account_key.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Monitor only the blocks added below.
+        let data = MonitorData::new(
+            account_key.clone(),
+            0,                               // first_subaddress
+            20,                              // num_subaddresses
+            ledger_db.num_blocks().unwrap(), // first_block
+            "",                              // name
+        )
+        .unwrap();
+        let id = mobilecoind_db.add_monitor(&data).unwrap();
+
+        // The monitor has no webhook yet.
+        let mut request = mc_mobilecoind_api::GetMonitorWebhookRequest::new();
+        request.set_monitor_id(id.to_vec());
+        let response = client.get_monitor_webhook(&request).unwrap();
+        assert_eq!(response.url, "");
+        assert_eq!(response.num_pending_notifications, 0);
+
+        // Invalid urls and missing secrets are rejected.
+        let mut request = mc_mobilecoind_api::SetMonitorWebhookRequest::new();
+        request.set_monitor_id(id.to_vec());
+        request.set_url("ftp://example.com".to_owned());
+        request.set_secret(b"secret".to_vec());
+        assert!(client.set_monitor_webhook(&request).is_err());
+
+        request.set_url("https://example.com/webhook".to_owned());
+        request.set_secret(vec![]);
+        assert!(client.set_monitor_webhook(&request).is_err());
+
+        request.set_secret(b"secret".to_vec());
+        client.set_monitor_webhook(&request).unwrap();
+
+        // Add a block paying the monitored account, which queues a notification.
+        add_block_to_ledger_db(
+            BLOCK_VERSION,
+            &mut ledger_db,
+            &[account_key.default_subaddress()],
+            Amount {
+                value: DEFAULT_PER_RECIPIENT_AMOUNT,
+                token_id: Mob::ID,
+            },
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        let mut request = mc_mobilecoind_api::GetMonitorWebhookRequest::new();
+        request.set_monitor_id(id.to_vec());
+        let response = client.get_monitor_webhook(&request).unwrap();
+        assert_eq!(response.url, "https://example.com/webhook");
+        assert_eq!(response.num_pending_notifications, 1);
+
+        // Removing the webhook discards the pending notification.
+        let mut request = mc_mobilecoind_api::SetMonitorWebhookRequest::new();
+        request.set_monitor_id(id.to_vec());
+        client.set_monitor_webhook(&request).unwrap();
+
+        let mut request = mc_mobilecoind_api::GetMonitorWebhookRequest::new();
+        request.set_monitor_id(id.to_vec());
+        let response = client.get_monitor_webhook(&request).unwrap();
+        assert_eq!(response.url, "");
+        assert_eq!(response.num_pending_notifications, 0);
+
+        // Unknown monitors are rejected.
+        mobilecoind_db.remove_monitor(&id).unwrap();
+        assert!(client.get_monitor_webhook(&request).is_err());
+    }
+
     #[test_with_logger]
     fn test_get_unspent_tx_out_list_impl(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
@@ -3287,65 +4095,354 @@ mod test {
             }
         }
 
-        // Add a block with a non-MOB token id and see that it gets picked up
-        // correctly.
-        {
-            add_block_to_ledger_db(
+        // Add a block with a non-MOB token id and see that it gets picked up
+        // correctly.
+        {
+            add_block_to_ledger_db(
+                BLOCK_VERSION,
+                &mut ledger_db,
+                &vec![account_key.subaddress(5)],
+                Amount {
+                    value: 102030,
+                    token_id: TokenId::from(2),
+                },
+                &[KeyImage::from(101)],
+                &mut rng,
+            );
+
+            wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+            let mut request = mc_mobilecoind_api::GetProcessedBlockRequest::new();
+            request.set_monitor_id(monitor_id.to_vec());
+            request.set_block(num_blocks + 1);
+
+            let response = client
+                .get_processed_block(&request)
+                .expect("failed to get processed block");
+
+            let tx_outs = response.get_tx_outs();
+            assert_eq!(tx_outs.len(), 1);
+
+            let tx_out = &tx_outs[0];
+            assert_eq!(tx_out.get_monitor_id().to_vec(), monitor_id.to_vec());
+            assert_eq!(tx_out.get_value(), 102030);
+            assert_eq!(
+                tx_out.get_direction(),
+                mc_mobilecoind_api::ProcessedTxOutDirection::Received
+            );
+            assert_eq!(tx_out.get_token_id(), 2);
+        }
+
+        // Query a block that will never get processed since its before the monitor's
+        // first block.
+        let mut request = mc_mobilecoind_api::GetProcessedBlockRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_block(0);
+
+        assert!(client.get_processed_block(&request).is_err());
+
+        // Query a block that hasn't been processed yet.
+        let mut request = mc_mobilecoind_api::GetProcessedBlockRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_block(num_blocks + 2);
+
+        assert!(client.get_processed_block(&request).is_err());
+
+        // Query with an unknown monitor id.
+        let mut request = mc_mobilecoind_api::GetProcessedBlockRequest::new();
+        request.set_monitor_id(vec![1; 32]);
+        request.set_block(1);
+
+        assert!(client.get_processed_block(&request).is_err());
+    }
+
+    #[test_with_logger]
+    fn test_subscribe_blocks(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
+
+        // 3 random recipients and no monitors.
+        let (mut ledger_db, _mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(BLOCK_VERSION, 3, &[], &[], logger.clone(), &mut rng);
+        let num_blocks = ledger_db.num_blocks().unwrap();
+
+        let mut request = mc_mobilecoind_api::SubscribeBlocksRequest::new();
+        request.set_start_block(2);
+        let mut stream = block_on_stream(client.subscribe_blocks(&request).unwrap());
+
+        // Existing blocks are streamed in order, and match what GetBlock returns.
+        for block_index in 2..num_blocks {
+            let response = stream.next().unwrap().unwrap();
+            assert_eq!(response.get_block().get_index(), block_index);
+
+            let mut request = mc_mobilecoind_api::GetBlockRequest::new();
+            request.set_block(block_index);
+            assert_eq!(response, client.get_block(&request).unwrap());
+        }
+
+        // New blocks are streamed as they get appended to the ledger.
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        add_block_to_ledger_db(
+            BLOCK_VERSION,
+            &mut ledger_db,
+            &[recipient],
+            Amount {
+                value: DEFAULT_PER_RECIPIENT_AMOUNT,
+                token_id: Mob::ID,
+            },
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        let response = stream.next().unwrap().unwrap();
+        assert_eq!(response.get_block().get_index(), num_blocks);
+        assert_eq!(response.get_txos().len(), 1);
+    }
+
+    #[test_with_logger]
+    fn test_subscribe_monitor_events(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
+
+        let account_key = AccountKey::random(&mut rng);
+        let monitor_data = MonitorData::new(
+            account_key.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            1,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (mut ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[account_key.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+        let num_blocks = ledger_db.num_blocks().unwrap();
+
+        // Subscribing to an unknown monitor is an error.
+        let mut request = mc_mobilecoind_api::SubscribeMonitorEventsRequest::new();
+        request.set_monitor_id(vec![1; 32]);
+        request.set_start_block(1);
+        let mut stream = block_on_stream(client.subscribe_monitor_events(&request).unwrap());
+        assert!(stream.next().unwrap().is_err());
+
+        let monitor_id = mobilecoind_db.add_monitor(&monitor_data).unwrap();
+
+        // Starting before the monitor's first block is an error.
+        let mut request = mc_mobilecoind_api::SubscribeMonitorEventsRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_start_block(0);
+        let mut stream = block_on_stream(client.subscribe_monitor_events(&request).unwrap());
+        assert!(stream.next().unwrap().is_err());
+
+        let mut request = mc_mobilecoind_api::SubscribeMonitorEventsRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_start_block(monitor_data.first_block);
+        let mut stream = block_on_stream(client.subscribe_monitor_events(&request).unwrap());
+
+        // We expect one received TxOut per block, which matches what GetProcessedBlock
+        // returns.
+        for block_index in monitor_data.first_block..num_blocks {
+            let response = stream.next().unwrap().unwrap();
+            assert_eq!(response.get_block(), block_index);
+
+            let tx_outs = response.get_tx_outs();
+            assert_eq!(tx_outs.len(), 1);
+            assert_eq!(
+                tx_outs[0].get_direction(),
+                mc_mobilecoind_api::ProcessedTxOutDirection::Received
+            );
+            assert_eq!(tx_outs[0].get_value(), DEFAULT_PER_RECIPIENT_AMOUNT);
+            // Block version zero TxOuts have no memo.
+            assert_eq!(
+                tx_outs[0].get_memo_payload(),
+                MemoPayload::default().as_ref()
+            );
+
+            let mut request = mc_mobilecoind_api::GetProcessedBlockRequest::new();
+            request.set_monitor_id(monitor_id.to_vec());
+            request.set_block(block_index);
+            let expected = client.get_processed_block(&request).unwrap();
+            assert_eq!(tx_outs, expected.get_tx_outs());
+        }
+
+        // A new block that does not touch our monitor still produces an (empty)
+        // event.
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        add_block_to_ledger_db(
+            BLOCK_VERSION,
+            &mut ledger_db,
+            &[recipient],
+            Amount {
+                value: DEFAULT_PER_RECIPIENT_AMOUNT,
+                token_id: Mob::ID,
+            },
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        let response = stream.next().unwrap().unwrap();
+        assert_eq!(response.get_block(), num_blocks);
+        assert!(response.get_tx_outs().is_empty());
+
+        // A new block paying one of our subaddresses produces an event for it.
+        add_block_to_ledger_db(
+            BLOCK_VERSION,
+            &mut ledger_db,
+            &[account_key.subaddress(5)],
+            Amount {
+                value: 102030,
+                token_id: TokenId::from(2),
+            },
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        let response = stream.next().unwrap().unwrap();
+        assert_eq!(response.get_block(), num_blocks + 1);
+        let tx_outs = response.get_tx_outs();
+        assert_eq!(tx_outs.len(), 1);
+        assert_eq!(tx_outs[0].get_subaddress_index(), 5);
+        assert_eq!(tx_outs[0].get_value(), 102030);
+        assert_eq!(tx_outs[0].get_token_id(), 2);
+
+        // Resuming from a later block skips everything before it.
+        let mut request = mc_mobilecoind_api::SubscribeMonitorEventsRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_start_block(num_blocks + 1);
+        let mut stream = block_on_stream(client.subscribe_monitor_events(&request).unwrap());
+        let response = stream.next().unwrap().unwrap();
+        assert_eq!(response.get_block(), num_blocks + 1);
+        assert_eq!(response.get_tx_outs().len(), 1);
+    }
+
+    #[test_with_logger]
+    fn test_get_transaction_history(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
+
+        let account_key = AccountKey::random(&mut rng);
+        let monitor_data = MonitorData::new(
+            account_key.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            1,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (mut ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
                 BLOCK_VERSION,
-                &mut ledger_db,
-                &vec![account_key.subaddress(5)],
-                Amount {
-                    value: 102030,
-                    token_id: TokenId::from(2),
-                },
-                &[KeyImage::from(101)],
+                3,
+                &[account_key.default_subaddress()],
+                &[],
+                logger.clone(),
                 &mut rng,
             );
 
-            wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
-
-            let mut request = mc_mobilecoind_api::GetProcessedBlockRequest::new();
-            request.set_monitor_id(monitor_id.to_vec());
-            request.set_block(num_blocks + 1);
+        let monitor_id = mobilecoind_db.add_monitor(&monitor_data).unwrap();
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+        let num_blocks = ledger_db.num_blocks().unwrap();
 
-            let response = client
-                .get_processed_block(&request)
-                .expect("failed to get processed block");
+        // Spend the first utxo and receive a payment of another token id.
+        let utxos = mobilecoind_db
+            .get_utxos_for_subaddress(&monitor_id, 0)
+            .unwrap();
+        add_block_to_ledger_db(
+            BLOCK_VERSION,
+            &mut ledger_db,
+            &[account_key.subaddress(5)],
+            Amount {
+                value: 102030,
+                token_id: TokenId::from(2),
+            },
+            &[utxos[0].key_image],
+            &mut rng,
+        );
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
 
-            let tx_outs = response.get_tx_outs();
-            assert_eq!(tx_outs.len(), 1);
+        // Page through the entire history.
+        let mut entries = Vec::new();
+        let mut page_token = Vec::new();
+        loop {
+            let mut request = mc_mobilecoind_api::GetTransactionHistoryRequest::new();
+            request.set_monitor_id(monitor_id.to_vec());
+            request.set_page_size(2);
+            request.set_page_token(page_token);
+            let response = client.get_transaction_history(&request).unwrap();
+            assert!(response.get_entries().len() <= 2);
+            entries.extend_from_slice(response.get_entries());
+
+            page_token = response.get_next_page_token().to_vec();
+            if page_token.is_empty() {
+                break;
+            }
+        }
 
-            let tx_out = &tx_outs[0];
-            assert_eq!(tx_out.get_monitor_id().to_vec(), monitor_id.to_vec());
-            assert_eq!(tx_out.get_value(), 102030);
+        // One received TxOut per block, followed by the last block which received
+        // one TxOut and spent another.
+        assert_eq!(
+            entries.len() as u64,
+            num_blocks - monitor_data.first_block + 2
+        );
+        for (entry, block) in entries.iter().zip(monitor_data.first_block..num_blocks) {
+            assert_eq!(entry.get_block(), block);
             assert_eq!(
-                tx_out.get_direction(),
+                entry.get_direction(),
                 mc_mobilecoind_api::ProcessedTxOutDirection::Received
             );
-            assert_eq!(tx_out.get_token_id(), 2);
+            assert_eq!(entry.get_value(), DEFAULT_PER_RECIPIENT_AMOUNT);
+            assert_eq!(
+                entry.get_memo_validation_status(),
+                mc_mobilecoind_api::MemoValidationStatus::NotAuthenticated
+            );
         }
 
-        // Query a block that will never get processed since its before the monitor's
-        // first block.
-        let mut request = mc_mobilecoind_api::GetProcessedBlockRequest::new();
+        // Filter by token id.
+        let mut request = mc_mobilecoind_api::GetTransactionHistoryRequest::new();
         request.set_monitor_id(monitor_id.to_vec());
-        request.set_block(0);
-
-        assert!(client.get_processed_block(&request).is_err());
-
-        // Query a block that hasn't been processed yet.
-        let mut request = mc_mobilecoind_api::GetProcessedBlockRequest::new();
+        request.set_token_ids(vec![2]);
+        let response = client.get_transaction_history(&request).unwrap();
+        assert_eq!(response.get_entries().len(), 1);
+        assert_eq!(response.get_entries()[0].get_block(), num_blocks);
+        assert_eq!(response.get_entries()[0].get_subaddress_index(), 5);
+        assert_eq!(response.get_entries()[0].get_value(), 102030);
+
+        // Filter by block range.
+        let mut request = mc_mobilecoind_api::GetTransactionHistoryRequest::new();
         request.set_monitor_id(monitor_id.to_vec());
-        request.set_block(num_blocks + 2);
+        request.set_start_block(num_blocks);
+        request.set_end_block(num_blocks + 1);
+        let response = client.get_transaction_history(&request).unwrap();
+        let directions: Vec<_> = response
+            .get_entries()
+            .iter()
+            .map(|entry| entry.get_direction())
+            .collect();
+        assert_eq!(
+            directions,
+            vec![
+                mc_mobilecoind_api::ProcessedTxOutDirection::Received,
+                mc_mobilecoind_api::ProcessedTxOutDirection::Spent,
+            ]
+        );
 
-        assert!(client.get_processed_block(&request).is_err());
+        // An invalid page token is rejected.
+        let mut request = mc_mobilecoind_api::GetTransactionHistoryRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_page_token(vec![1, 2, 3]);
+        assert!(client.get_transaction_history(&request).is_err());
 
-        // Query with an unknown monitor id.
-        let mut request = mc_mobilecoind_api::GetProcessedBlockRequest::new();
+        // An unknown monitor id is rejected.
+        let mut request = mc_mobilecoind_api::GetTransactionHistoryRequest::new();
         request.set_monitor_id(vec![1; 32]);
-        request.set_block(1);
-
-        assert!(client.get_processed_block(&request).is_err());
+        assert!(client.get_transaction_history(&request).is_err());
     }
 
     #[test_with_logger]
@@ -4291,10 +5388,7 @@ mod test {
         assert_eq!(tx_proposal.tx.prefix.inputs.len(), expected_num_inputs);
 
         assert_eq!(tx_proposal.outlays.len(), 1);
-        assert_eq!(
-            tx_proposal.outlays[0].receiver,
-            data.account_key.subaddress(0)
-        );
+        assert_eq!(tx_proposal.outlays[0].receiver, data.subaddress(0));
         assert_eq!(
             tx_proposal.outlays[0].value,
             // Each UTXO we have has PER_RECIPIENT_AMOUNT coins. We will be merging MAX_INPUTS of
@@ -4308,8 +5402,7 @@ mod test {
         assert_eq!(tx_proposal.tx.prefix.outputs.len(), 1);
         let tx_out = &tx_proposal.tx.prefix.outputs[0];
         let tx_public_key = RistrettoPublic::try_from(&tx_out.public_key).unwrap();
-        let shared_secret =
-            get_tx_out_shared_secret(data.account_key.view_private_key(), &tx_public_key);
+        let shared_secret = get_tx_out_shared_secret(data.view_private_key(), &tx_public_key);
         let (amount, _blinding) = tx_out.masked_amount.get_value(&shared_secret).unwrap();
         assert_eq!(amount.value, tx_proposal.outlays[0].value);
         assert_eq!(amount.token_id, Mob::ID);
@@ -4829,6 +5922,234 @@ mod test {
         assert_eq!(matched_utxos, tx_proposal.utxos.len());
     }
 
+    #[test_with_logger]
+    fn test_send_payment_mixed_token_ids(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
+
+        let sender = AccountKey::random(&mut rng);
+        let data = MonitorData::new(
+            sender.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (mut ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BlockVersion::THREE,
+                3,
+                &[sender.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Give the sender some of a second token id.
+        let token_id2 = TokenId::from(2);
+        add_block_to_ledger_db(
+            BlockVersion::THREE,
+            &mut ledger_db,
+            &[sender.default_subaddress()],
+            Amount::new(1000, token_id2),
+            &[KeyImage::from(101)],
+            &mut rng,
+        );
+
+        // Insert into database.
+        let monitor_id = mobilecoind_db.add_monitor(&data).unwrap();
+
+        // Allow the new monitor to process the ledger.
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        // Pay one recipient in MOB and another in the second token id, with the fee
+        // paid in MOB.
+        let receiver1 = AccountKey::random(&mut rng);
+        let receiver2 = AccountKey::random(&mut rng);
+
+        let outlays = vec![
+            OutlayV2 {
+                value: 123,
+                receiver: receiver1.default_subaddress(),
+                token_id: Mob::ID,
+            },
+            OutlayV2 {
+                value: 400,
+                receiver: receiver2.default_subaddress(),
+                token_id: token_id2,
+            },
+        ];
+
+        let mut request = mc_mobilecoind_api::SendPaymentRequest::new();
+        request.set_sender_monitor_id(monitor_id.to_vec());
+        request.set_sender_subaddress(0);
+        request.set_outlay_list_v2(RepeatedField::from_vec(
+            outlays
+                .iter()
+                .map(mc_mobilecoind_api::OutlayV2::from)
+                .collect(),
+        ));
+        request.set_fee(Mob::MINIMUM_FEE);
+        request.set_token_id(*Mob::ID);
+
+        let response = client.send_payment(&request).unwrap();
+        let tx_proposal = TxProposal::try_from(response.get_tx_proposal()).unwrap();
+
+        // Both outlays should be in the proposal, along with change in both token ids.
+        assert_eq!(tx_proposal.outlays, outlays);
+        assert_eq!(tx_proposal.fee_token_id(), Mob::ID);
+        assert_eq!(tx_proposal.tx.prefix.outputs.len(), outlays.len() + 2);
+
+        let mut input_token_ids: Vec<u64> =
+            tx_proposal.utxos.iter().map(|utxo| utxo.token_id).collect();
+        input_token_ids.sort_unstable();
+        input_token_ids.dedup();
+        assert_eq!(input_token_ids, vec![*Mob::ID, *token_id2]);
+
+        // Outlays in both lists is an error.
+        request.set_outlay_list(RepeatedField::from_vec(vec![(&Outlay {
+            value: 123,
+            receiver: receiver1.default_subaddress(),
+        })
+            .into()]));
+        assert!(client.send_payment(&request).is_err());
+    }
+
+    #[test_with_logger]
+    fn test_send_payment_mixed_token_ids_rejects_zero_value(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
+
+        let sender = AccountKey::random(&mut rng);
+        let data = MonitorData::new(
+            sender.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        let (mut ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BlockVersion::THREE,
+                3,
+                &[sender.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        let token_id2 = TokenId::from(2);
+        add_block_to_ledger_db(
+            BlockVersion::THREE,
+            &mut ledger_db,
+            &[sender.default_subaddress()],
+            Amount::new(1000, token_id2),
+            &[KeyImage::from(101)],
+            &mut rng,
+        );
+
+        let monitor_id = mobilecoind_db.add_monitor(&data).unwrap();
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        // A zero-value outlay in a token id other than the fee token id.
+        let outlays = vec![
+            OutlayV2 {
+                value: 123,
+                receiver: AccountKey::random(&mut rng).default_subaddress(),
+                token_id: Mob::ID,
+            },
+            OutlayV2 {
+                value: 0,
+                receiver: AccountKey::random(&mut rng).default_subaddress(),
+                token_id: token_id2,
+            },
+        ];
+
+        let mut request = mc_mobilecoind_api::SendPaymentRequest::new();
+        request.set_sender_monitor_id(monitor_id.to_vec());
+        request.set_sender_subaddress(0);
+        request.set_outlay_list_v2(RepeatedField::from_vec(
+            outlays
+                .iter()
+                .map(mc_mobilecoind_api::OutlayV2::from)
+                .collect(),
+        ));
+        request.set_fee(Mob::MINIMUM_FEE);
+        request.set_token_id(*Mob::ID);
+
+        match client.send_payment(&request) {
+            Ok(_) => panic!("Should've returned an error"),
+            Err(GrpcError::RpcFailure(rpc_status)) => {
+                assert_eq!(rpc_status.code(), RpcStatusCode::INVALID_ARGUMENT);
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        };
+    }
+
+    #[test_with_logger]
+    fn test_send_payment_mixed_token_ids_requires_block_version(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
+
+        let sender = AccountKey::random(&mut rng);
+        let data = MonitorData::new(
+            sender.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        let (mut ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BlockVersion::TWO,
+                3,
+                &[sender.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        let token_id2 = TokenId::from(2);
+        add_block_to_ledger_db(
+            BlockVersion::TWO,
+            &mut ledger_db,
+            &[sender.default_subaddress()],
+            Amount::new(1000, token_id2),
+            &[KeyImage::from(101)],
+            &mut rng,
+        );
+
+        let monitor_id = mobilecoind_db.add_monitor(&data).unwrap();
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        let outlay = OutlayV2 {
+            value: 400,
+            receiver: AccountKey::random(&mut rng).default_subaddress(),
+            token_id: token_id2,
+        };
+
+        let mut request = mc_mobilecoind_api::SendPaymentRequest::new();
+        request.set_sender_monitor_id(monitor_id.to_vec());
+        request.set_sender_subaddress(0);
+        request.set_outlay_list_v2(RepeatedField::from_vec(vec![(&outlay).into()]));
+        request.set_fee(Mob::MINIMUM_FEE);
+        request.set_token_id(*Mob::ID);
+
+        match client.send_payment(&request) {
+            Ok(_) => panic!("Should've returned an error"),
+            Err(GrpcError::RpcFailure(rpc_status)) => {
+                assert!(rpc_status
+                    .message()
+                    .starts_with("transactions_manager.build_mixed_transaction: Tx build error"));
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        };
+    }
+
     #[test_with_logger]
     fn test_send_payment_with_max_input_utxo_value(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
@@ -5831,7 +7152,9 @@ mod test {
             );
 
         let mut request = mc_mobilecoind_api::AddMonitorRequest::new();
-        request.set_account_key(mc_api::external::AccountKey::from(&data.account_key));
+        request.set_account_key(mc_api::external::AccountKey::from(
+            data.account_key().unwrap(),
+        ));
         request.set_first_subaddress(data.first_subaddress);
         request.set_num_subaddresses(data.num_subaddresses);
         request.set_first_block(data.first_block);
@@ -5922,7 +7245,9 @@ mod test {
 
         // Re-add the monitor.
         let mut request = mc_mobilecoind_api::AddMonitorRequest::new();
-        request.set_account_key(mc_api::external::AccountKey::from(&data.account_key));
+        request.set_account_key(mc_api::external::AccountKey::from(
+            data.account_key().unwrap(),
+        ));
         request.set_first_subaddress(data.first_subaddress);
         request.set_num_subaddresses(data.num_subaddresses);
         request.set_first_block(data.first_block);
@@ -0,0 +1,124 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Support for the streaming subscription APIs.
+//! * `BlockProcessedNotifier` wakes up subscribers whenever a monitor finishes
+//!   processing a block.
+//! * `spawn_subscription` streams one response per block to a subscriber.
+
+use futures::{FutureExt, TryFutureExt};
+use grpcio::{RpcContext, RpcStatus, ServerStreamingSink};
+use mc_common::logger::{log, Logger};
+use mc_util_grpc::spawn_streaming_thread;
+use std::{
+    sync::{Arc, Condvar, Mutex},
+    time::Duration,
+};
+
+/// How long a subscription waits for a notification before checking for new
+/// data on its own. Blocks appended to the ledger do not trigger a
+/// notification unless a monitor processes them, so this also bounds the
+/// latency of `SubscribeBlocks`.
+pub const SUBSCRIPTION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Number of responses that may be queued for a subscriber.
+const SUBSCRIPTION_CHANNEL_SIZE: usize = 16;
+
+/// A generation counter that is bumped every time a block is processed, and
+/// that subscribers can wait on.
+#[derive(Clone, Default)]
+pub struct BlockProcessedNotifier {
+    inner: Arc<(Mutex<u64>, Condvar)>,
+}
+
+impl BlockProcessedNotifier {
+    /// Signal that a block has been processed.
+    pub fn notify(&self) {
+        let (lock, condvar) = &*self.inner;
+        let mut generation = lock.lock().expect("mutex poisoned");
+        *generation = generation.wrapping_add(1);
+        condvar.notify_all();
+    }
+
+    /// Get the current generation.
+    pub fn generation(&self) -> u64 {
+        *self.inner.0.lock().expect("mutex poisoned")
+    }
+
+    /// Wait until the generation differs from `generation`, or until `timeout`
+    /// elapses. Returns the current generation.
+    pub fn wait(&self, generation: u64, timeout: Duration) -> u64 {
+        let (lock, condvar) = &*self.inner;
+        let guard = lock.lock().expect("mutex poisoned");
+        let (guard, _timeout_result) = condvar
+            .wait_timeout_while(guard, timeout, |current| *current == generation)
+            .expect("mutex poisoned");
+        *guard
+    }
+}
+
+/// Stream responses to a subscriber.
+///
+/// `subscription` is either an error to report to the subscriber, or the first
+/// block index to stream along with a `fetch` function, which a thread named
+/// `name` calls with consecutive block indices. `fetch` returns `Ok(None)`
+/// while the data for a block is not available, and the thread then waits on
+/// `notifier` before trying again. The thread exits on an error, or once a
+/// response fails to reach the subscriber, or when it finds the subscriber gone
+/// while waiting.
+pub fn spawn_subscription<R, F>(
+    ctx: &RpcContext,
+    sink: ServerStreamingSink<R>,
+    name: &str,
+    notifier: BlockProcessedNotifier,
+    subscription: Result<(u64, F), RpcStatus>,
+    logger: Logger,
+) where
+    R: Send + 'static,
+    F: FnMut(u64) -> Result<Option<R>, RpcStatus> + Send + 'static,
+{
+    let (start_block, mut fetch) = match subscription {
+        Ok(subscription) => subscription,
+        Err(status) => {
+            let future = sink
+                .fail(status)
+                .map_err(move |err| log::error!(logger, "failed to reply: {:?}", err))
+                .map(|_| ());
+            ctx.spawn(future);
+            return;
+        }
+    };
+
+    let thread_logger = logger.clone();
+    spawn_streaming_thread(
+        ctx,
+        sink,
+        name,
+        SUBSCRIPTION_CHANNEL_SIZE,
+        move |mut sender| {
+            let mut block_index = start_block;
+            loop {
+                let generation = notifier.generation();
+                match fetch(block_index) {
+                    Ok(Some(response)) => {
+                        if sender.send(response).is_err() {
+                            break;
+                        }
+                        block_index += 1;
+                    }
+                    Ok(None) => {
+                        if sender.is_closed() {
+                            break;
+                        }
+                        notifier.wait(generation, SUBSCRIPTION_POLL_INTERVAL);
+                    }
+                    Err(status) => {
+                        sender.fail(status);
+                        break;
+                    }
+                }
+            }
+            log::debug!(thread_logger, "Subscription ended at block {}", block_index);
+        },
+        logger,
+    );
+}
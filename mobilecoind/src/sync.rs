@@ -25,6 +25,7 @@ use crate::{
     error::Error,
     monitor_store::{MonitorData, MonitorId},
     subaddress_store::SubaddressSPKId,
+    unverified_tx_out_store::UnverifiedTxOut,
     utxo_store::UnspentTxOut,
 };
 use mc_common::{
@@ -317,7 +318,7 @@ fn sync_monitor(
         );
 
         // Match tx outs into UTXOs.
-        let utxos = match_tx_outs_into_utxos(
+        let (utxos, unverified_tx_outs) = match_tx_outs_into_utxos(
             mobilecoind_db,
             &block_contents.outputs,
             monitor_id,
@@ -330,6 +331,7 @@ fn sync_monitor(
             monitor_id,
             monitor_data.next_block,
             &utxos,
+            &unverified_tx_outs,
             &block_contents.key_images,
         )?;
     }
@@ -338,15 +340,19 @@ fn sync_monitor(
 }
 
 /// Helper function for matching a list of TxOuts to a given monitor.
+///
+/// View-only monitors cannot compute key images, so the TxOuts they match are
+/// returned as UnverifiedTxOuts instead of UnspentTxOuts.
 fn match_tx_outs_into_utxos(
     mobilecoind_db: &Database,
     outputs: &[TxOut],
     monitor_id: &MonitorId,
     monitor_data: &MonitorData,
     logger: &Logger,
-) -> Result<Vec<UnspentTxOut>, Error> {
-    let account_key = &monitor_data.account_key;
+) -> Result<(Vec<UnspentTxOut>, Vec<UnverifiedTxOut>), Error> {
+    let view_private_key = monitor_data.view_private_key();
     let mut results = Vec::new();
+    let mut unverified_results = Vec::new();
 
     for tx_out in outputs {
         // Calculate the subaddress spend public key for tx_out.
@@ -354,7 +360,7 @@ fn match_tx_outs_into_utxos(
         let tx_public_key = RistrettoPublic::try_from(&tx_out.public_key)?;
 
         let subaddress_spk = SubaddressSPKId::from(&recover_public_subaddress_spend_key(
-            account_key.view_private_key(),
+            view_private_key,
             &tx_out_target_key,
             &tx_public_key,
         ));
@@ -380,14 +386,27 @@ fn match_tx_outs_into_utxos(
         // Sanity - we should only get a match for our own monitor id.
         assert_eq!(monitor_id, &subaddress_id.monitor_id);
 
-        let shared_secret =
-            get_tx_out_shared_secret(account_key.view_private_key(), &tx_public_key);
+        let shared_secret = get_tx_out_shared_secret(view_private_key, &tx_public_key);
 
         let (amount, _blinding) = tx_out
             .masked_amount
             .get_value(&shared_secret)
             .expect("Malformed amount"); // TODO
 
+        let account_key = match &monitor_data.account_key {
+            Some(account_key) => account_key,
+            None => {
+                unverified_results.push(UnverifiedTxOut {
+                    tx_out: tx_out.clone(),
+                    subaddress_index: subaddress_id.index,
+                    value: amount.value,
+                    token_id: *amount.token_id,
+                    block_index: monitor_data.next_block,
+                });
+                continue;
+            }
+        };
+
         let onetime_private_key = recover_onetime_private_key(
             &tx_public_key,
             account_key.view_private_key(),
@@ -407,7 +426,7 @@ fn match_tx_outs_into_utxos(
         });
     }
 
-    Ok(results)
+    Ok((results, unverified_results))
 }
 
 #[cfg(test)]
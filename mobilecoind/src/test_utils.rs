@@ -7,6 +7,7 @@ use crate::{
     monitor_store::{MonitorData, MonitorId},
     payments::TransactionsManager,
     service::Service,
+    utxo_selection::UtxoSelectionStrategyKind,
 };
 use grpcio::{ChannelBuilder, EnvBuilder};
 use mc_account_keys::{AccountKey, PublicAddress, DEFAULT_SUBADDRESS_INDEX};
@@ -280,6 +281,7 @@ pub fn setup_server<FPR: FogPubkeyResolver + Default + Send + Sync + 'static>(
         mobilecoind_db.clone(),
         conn_manager.clone(),
         fog_resolver_factory.unwrap_or_else(|| Arc::new(|_| Ok(FPR::default()))),
+        UtxoSelectionStrategyKind::default(),
         logger.clone(),
     );
 
@@ -0,0 +1,370 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Database storage for TxOuts received by view-only monitors.
+//! * View-only monitors cannot compute the key images of the TxOuts they
+//!   receive, so these TxOuts are kept here (UnverifiedTxOutKey ->
+//!   UnverifiedTxOut) until their key images are imported. Importing a key
+//!   image moves the TxOut to the UtxoStore, where its spent status is tracked
+//!   like for any other monitor.
+
+use crate::{error::Error, monitor_store::MonitorId, utxo_store::UnspentTxOut};
+use lmdb::{Cursor, Database, DatabaseFlags, Environment, RwTransaction, Transaction, WriteFlags};
+use mc_common::logger::{log, Logger};
+use mc_crypto_keys::CompressedRistrettoPublic;
+use mc_transaction_core::{ring_signature::KeyImage, tx::TxOut};
+use mc_util_serial::Message;
+use std::{convert::TryFrom, sync::Arc};
+
+// LMDB Database Names
+pub const UNVERIFIED_TX_OUT_KEY_TO_UNVERIFIED_TX_OUT_DB_NAME: &str =
+    "mobilecoind_db:unverified_tx_out_store:unverified_tx_out_key_to_unverified_tx_out";
+
+/// A TxOut received by a view-only monitor, whose key image is not known yet.
+#[derive(Clone, Eq, Hash, PartialEq, Message)]
+pub struct UnverifiedTxOut {
+    /// The actual transaction output.
+    #[prost(message, required, tag = "1")]
+    pub tx_out: TxOut,
+
+    /// Index of the subaddress to which this output was sent.
+    #[prost(uint64, tag = "2")]
+    pub subaddress_index: u64,
+
+    /// Value of this TxOut.
+    #[prost(uint64, tag = "3")]
+    pub value: u64,
+
+    /// The token id of this TxOut
+    #[prost(uint64, tag = "4")]
+    pub token_id: u64,
+
+    /// The block this TxOut was received in.
+    #[prost(uint64, tag = "5")]
+    pub block_index: u64,
+}
+
+impl UnverifiedTxOut {
+    /// The UnspentTxOut for this TxOut, once its key image is known.
+    pub fn into_utxo(self, key_image: KeyImage) -> UnspentTxOut {
+        UnspentTxOut {
+            tx_out: self.tx_out,
+            subaddress_index: self.subaddress_index,
+            key_image,
+            value: self.value,
+            attempted_spend_height: 0,
+            attempted_spend_tombstone: 0,
+            token_id: self.token_id,
+        }
+    }
+}
+
+/// Type used as the key in the unverified_tx_out_key_to_unverified_tx_out
+/// database.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct UnverifiedTxOutKey {
+    /// The monitor_id that received the TxOut.
+    pub monitor_id: MonitorId,
+
+    /// The public key of the TxOut.
+    pub tx_public_key: CompressedRistrettoPublic,
+}
+
+impl UnverifiedTxOutKey {
+    pub fn new(monitor_id: &MonitorId, tx_public_key: &CompressedRistrettoPublic) -> Self {
+        Self {
+            monitor_id: *monitor_id,
+            tx_public_key: *tx_public_key,
+        }
+    }
+
+    // 64 bytes: 32 for MonitorId, 32 for the TxOut public key.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut buf = [0u8; 64];
+        buf[0..32].copy_from_slice(self.monitor_id.as_bytes());
+        buf[32..64].copy_from_slice(self.tx_public_key.as_bytes());
+        buf
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for UnverifiedTxOutKey {
+    type Error = Error;
+
+    fn try_from(src: &[u8]) -> Result<Self, Self::Error> {
+        if src.len() != 64 {
+            return Err(Error::InvalidArgument(
+                "src".to_string(),
+                "src length must be exactly 64".to_string(),
+            ));
+        }
+
+        let monitor_id = MonitorId::try_from(&src[0..32])?;
+        let tx_public_key = CompressedRistrettoPublic::try_from(&src[32..64])?;
+
+        Ok(Self {
+            monitor_id,
+            tx_public_key,
+        })
+    }
+}
+
+/// The unverified TxOuts database.
+#[derive(Clone)]
+pub struct UnverifiedTxOutStore {
+    /// Retain a reference to the Environment so the Database handles are valid.
+    _env: Arc<Environment>,
+
+    /// Mapping of UnverifiedTxOutKey -> UnverifiedTxOut.
+    unverified_tx_out_key_to_unverified_tx_out: Database,
+
+    /// Logger.
+    logger: Logger,
+}
+
+impl UnverifiedTxOutStore {
+    pub fn new(env: Arc<Environment>, logger: Logger) -> Result<Self, Error> {
+        let unverified_tx_out_key_to_unverified_tx_out = env.create_db(
+            Some(UNVERIFIED_TX_OUT_KEY_TO_UNVERIFIED_TX_OUT_DB_NAME),
+            DatabaseFlags::empty(),
+        )?;
+
+        Ok(Self {
+            _env: env,
+            unverified_tx_out_key_to_unverified_tx_out,
+            logger,
+        })
+    }
+
+    /// Add a TxOut received by a view-only monitor.
+    pub fn add<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        monitor_id: &MonitorId,
+        unverified_tx_out: &UnverifiedTxOut,
+    ) -> Result<(), Error> {
+        let key = UnverifiedTxOutKey::new(monitor_id, &unverified_tx_out.tx_out.public_key);
+        log::trace!(
+            self.logger,
+            "adding unverified tx out {} for monitor {}",
+            key.tx_public_key,
+            monitor_id
+        );
+
+        db_txn.put(
+            self.unverified_tx_out_key_to_unverified_tx_out,
+            &key.to_vec(),
+            &mc_util_serial::encode(unverified_tx_out),
+            WriteFlags::empty(),
+        )?;
+        Ok(())
+    }
+
+    /// Get the TxOut with public key `tx_public_key` received by a monitor.
+    pub fn get(
+        &self,
+        db_txn: &impl Transaction,
+        monitor_id: &MonitorId,
+        tx_public_key: &CompressedRistrettoPublic,
+    ) -> Result<UnverifiedTxOut, Error> {
+        let key = UnverifiedTxOutKey::new(monitor_id, tx_public_key);
+        match db_txn.get(
+            self.unverified_tx_out_key_to_unverified_tx_out,
+            &key.to_vec(),
+        ) {
+            Ok(value_bytes) => Ok(mc_util_serial::decode(value_bytes)?),
+            Err(lmdb::Error::NotFound) => Err(Error::UnverifiedTxOutNotFound),
+            Err(err) => Err(Error::Lmdb(err)),
+        }
+    }
+
+    /// Get all the TxOuts received by a monitor whose key images are not known
+    /// yet.
+    pub fn get_for_monitor(
+        &self,
+        db_txn: &impl Transaction,
+        monitor_id: &MonitorId,
+    ) -> Result<Vec<UnverifiedTxOut>, Error> {
+        let start_key = UnverifiedTxOutKey::new(monitor_id, &CompressedRistrettoPublic::default());
+        let mut cursor = db_txn.open_ro_cursor(self.unverified_tx_out_key_to_unverified_tx_out)?;
+
+        let mut results = Vec::new();
+        for result in cursor.iter_from(&start_key.to_vec()) {
+            let (db_key, db_value) = result?;
+            if UnverifiedTxOutKey::try_from(db_key)?.monitor_id != *monitor_id {
+                break;
+            }
+            results.push(mc_util_serial::decode(db_value)?);
+        }
+        Ok(results)
+    }
+
+    /// Remove the TxOut with public key `tx_public_key` received by a monitor.
+    pub fn remove<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        monitor_id: &MonitorId,
+        tx_public_key: &CompressedRistrettoPublic,
+    ) -> Result<(), Error> {
+        let key = UnverifiedTxOutKey::new(monitor_id, tx_public_key);
+        match db_txn.del(
+            self.unverified_tx_out_key_to_unverified_tx_out,
+            &key.to_vec(),
+            None,
+        ) {
+            Ok(()) => Ok(()),
+            Err(lmdb::Error::NotFound) => Err(Error::UnverifiedTxOutNotFound),
+            Err(err) => Err(Error::Lmdb(err)),
+        }
+    }
+
+    /// Remove all the TxOuts received by a monitor.
+    pub fn remove_monitor<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        monitor_id: &MonitorId,
+    ) -> Result<(), Error> {
+        let start_key = UnverifiedTxOutKey::new(monitor_id, &CompressedRistrettoPublic::default());
+        let mut cursor = db_txn.open_rw_cursor(self.unverified_tx_out_key_to_unverified_tx_out)?;
+
+        for (db_key, _db_value) in cursor.iter_from(&start_key.to_vec()).filter_map(|r| r.ok()) {
+            let key = UnverifiedTxOutKey::try_from(db_key)?;
+            if key.monitor_id == *monitor_id {
+                cursor.del(WriteFlags::empty())?;
+            } else {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::monitor_store::MonitorData;
+    use mc_account_keys::{AccountKey, ViewAccountKey};
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_crypto_keys::RistrettoPrivate;
+    use mc_crypto_rand::RngCore;
+    use mc_transaction_core::{encrypted_fog_hint::EncryptedFogHint, tokens::Mob, Amount, Token};
+    use mc_util_from_random::FromRandom;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::assert_matches::assert_matches;
+    use tempdir::TempDir;
+
+    fn create_unverified_tx_out(
+        account_key: &AccountKey,
+        value: u64,
+        rng: &mut StdRng,
+    ) -> UnverifiedTxOut {
+        let tx_out = TxOut::new(
+            Amount {
+                value,
+                token_id: Mob::ID,
+            },
+            &account_key.default_subaddress(),
+            &RistrettoPrivate::from_random(rng),
+            EncryptedFogHint::fake_onetime_hint(rng),
+        )
+        .unwrap();
+
+        UnverifiedTxOut {
+            tx_out,
+            subaddress_index: 0,
+            value,
+            token_id: *Mob::ID,
+            block_index: rng.next_u64(),
+        }
+    }
+
+    // UnverifiedTxOutStore basic functionality tests
+    #[test_with_logger]
+    fn test_unverified_tx_out_store(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+
+        let db_tmp = TempDir::new("unverified_tx_out_store_db")
+            .expect("Could not make tempdir for unverified tx out store db");
+        let db_path = db_tmp
+            .path()
+            .to_str()
+            .expect("Could not get path as string");
+        let env = Arc::new(
+            Environment::new()
+                .set_max_dbs(10)
+                .set_map_size(10000000)
+                .open(db_path.as_ref())
+                .unwrap(),
+        );
+        let store = UnverifiedTxOutStore::new(env.clone(), logger).unwrap();
+
+        let account_key = AccountKey::random(&mut rng);
+        let view_account_key = ViewAccountKey::from(&account_key);
+        let monitor_id = MonitorId::from(
+            &MonitorData::new_view_only(view_account_key.clone(), 0, 10, 0, "").unwrap(),
+        );
+        let other_monitor_id =
+            MonitorId::from(&MonitorData::new_view_only(view_account_key, 10, 10, 0, "").unwrap());
+
+        let tx_outs: Vec<_> = (0..3)
+            .map(|i| create_unverified_tx_out(&account_key, 100 + i, &mut rng))
+            .collect();
+        let other_tx_out = create_unverified_tx_out(&account_key, 200, &mut rng);
+
+        let mut db_txn = env.begin_rw_txn().unwrap();
+        assert_eq!(store.get_for_monitor(&db_txn, &monitor_id).unwrap(), vec![]);
+
+        for tx_out in tx_outs.iter() {
+            store.add(&mut db_txn, &monitor_id, tx_out).unwrap();
+        }
+        store
+            .add(&mut db_txn, &other_monitor_id, &other_tx_out)
+            .unwrap();
+
+        // Each monitor only sees its own TxOuts.
+        let mut expected = tx_outs.clone();
+        expected.sort_by_key(|tx_out| tx_out.tx_out.public_key);
+        assert_eq!(
+            store.get_for_monitor(&db_txn, &monitor_id).unwrap(),
+            expected
+        );
+        assert_eq!(
+            store.get_for_monitor(&db_txn, &other_monitor_id).unwrap(),
+            vec![other_tx_out.clone()]
+        );
+        assert_eq!(
+            store
+                .get(&db_txn, &monitor_id, &tx_outs[1].tx_out.public_key)
+                .unwrap(),
+            tx_outs[1]
+        );
+        assert_matches!(
+            store.get(&db_txn, &monitor_id, &other_tx_out.tx_out.public_key),
+            Err(Error::UnverifiedTxOutNotFound)
+        );
+
+        // Removing a TxOut.
+        store
+            .remove(&mut db_txn, &monitor_id, &tx_outs[1].tx_out.public_key)
+            .unwrap();
+        assert_matches!(
+            store.remove(&mut db_txn, &monitor_id, &tx_outs[1].tx_out.public_key),
+            Err(Error::UnverifiedTxOutNotFound)
+        );
+        assert_eq!(
+            store.get_for_monitor(&db_txn, &monitor_id).unwrap().len(),
+            2
+        );
+
+        // Removing a monitor leaves the other monitors untouched.
+        store.remove_monitor(&mut db_txn, &monitor_id).unwrap();
+        assert_eq!(store.get_for_monitor(&db_txn, &monitor_id).unwrap(), vec![]);
+        assert_eq!(
+            store.get_for_monitor(&db_txn, &other_monitor_id).unwrap(),
+            vec![other_tx_out]
+        );
+    }
+}
@@ -0,0 +1,405 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Strategies for selecting which UTXOs to spend when building a transaction.
+
+use crate::{error::Error, utxo_store::UnspentTxOut};
+use clap::ArgEnum;
+use rand::seq::SliceRandom;
+use std::cmp::Reverse;
+
+/// Maximum number of search steps the branch-and-bound strategy takes before
+/// giving up on finding an exact match.
+pub const BRANCH_AND_BOUND_MAX_TRIES: usize = 100_000;
+
+/// Number of random shuffles the random strategy attempts before falling back
+/// to largest-first selection.
+pub const RANDOM_SELECTION_MAX_TRIES: usize = 100;
+
+/// A method of choosing a subset of UTXOs that covers a given value.
+pub trait UtxoSelectionStrategy {
+    /// Returns a subset of `utxos` totalling at least `value`, using no more
+    /// than `max_inputs` inputs.
+    ///
+    /// All of `utxos` are expected to be of the same token id.
+    fn select_utxos(
+        &self,
+        utxos: &[UnspentTxOut],
+        value: u64,
+        max_inputs: usize,
+    ) -> Result<Vec<UnspentTxOut>, Error>;
+}
+
+/// The UTXO selection strategies built into mobilecoind.
+#[derive(ArgEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UtxoSelectionStrategyKind {
+    /// Spend the smallest UTXOs first, consolidating dust.
+    SmallestFirst,
+
+    /// Spend the largest UTXOs first, minimizing the number of inputs.
+    LargestFirst,
+
+    /// Search for a set of UTXOs that exactly matches the value being spent,
+    /// avoiding a change output.
+    BranchAndBound,
+
+    /// Spend randomly chosen UTXOs, avoiding a predictable selection pattern.
+    Random,
+}
+
+impl Default for UtxoSelectionStrategyKind {
+    fn default() -> Self {
+        Self::SmallestFirst
+    }
+}
+
+impl UtxoSelectionStrategy for UtxoSelectionStrategyKind {
+    fn select_utxos(
+        &self,
+        utxos: &[UnspentTxOut],
+        value: u64,
+        max_inputs: usize,
+    ) -> Result<Vec<UnspentTxOut>, Error> {
+        match self {
+            Self::SmallestFirst => SmallestFirst.select_utxos(utxos, value, max_inputs),
+            Self::LargestFirst => LargestFirst.select_utxos(utxos, value, max_inputs),
+            Self::BranchAndBound => BranchAndBound.select_utxos(utxos, value, max_inputs),
+            Self::Random => RandomSelection.select_utxos(utxos, value, max_inputs),
+        }
+    }
+}
+
+/// Spends the smallest UTXOs first. If more than `max_inputs` UTXOs are needed
+/// the smallest ones are dropped in favor of larger ones.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SmallestFirst;
+
+impl UtxoSelectionStrategy for SmallestFirst {
+    fn select_utxos(
+        &self,
+        utxos: &[UnspentTxOut],
+        value: u64,
+        max_inputs: usize,
+    ) -> Result<Vec<UnspentTxOut>, Error> {
+        let mut sorted_utxos = sorted_descending(utxos);
+        check_spendable(&sorted_utxos, value, max_inputs)?;
+
+        // Choose utxos to spend.
+        let mut selected_utxos: Vec<UnspentTxOut> = Vec::new();
+        loop {
+            let total: u64 = selected_utxos.iter().map(|utxo| utxo.value).sum();
+            if total >= value {
+                break;
+            }
+
+            // Grab the next (smallest utxo)
+            let next_utxo = sorted_utxos.pop().ok_or(Error::InsufficientFunds)?;
+            selected_utxos.push(next_utxo);
+
+            // Cap at maximum allowed inputs.
+            if selected_utxos.len() > max_inputs {
+                // Remove the lowest utxo.
+                selected_utxos.remove(0);
+            }
+        }
+
+        // Sanity.
+        assert!(!selected_utxos.is_empty());
+        assert!(selected_utxos.len() <= max_inputs);
+
+        Ok(selected_utxos)
+    }
+}
+
+/// Spends the largest UTXOs first, using as few inputs as possible.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LargestFirst;
+
+impl UtxoSelectionStrategy for LargestFirst {
+    fn select_utxos(
+        &self,
+        utxos: &[UnspentTxOut],
+        value: u64,
+        max_inputs: usize,
+    ) -> Result<Vec<UnspentTxOut>, Error> {
+        let sorted_utxos = sorted_descending(utxos);
+        check_spendable(&sorted_utxos, value, max_inputs)?;
+
+        let mut total = 0;
+        let mut selected_utxos = Vec::new();
+        for utxo in sorted_utxos {
+            if total >= value {
+                break;
+            }
+            total += utxo.value;
+            selected_utxos.push(utxo);
+        }
+
+        // Sanity - check_spendable guarantees the largest `max_inputs` UTXOs cover
+        // the value.
+        assert!(!selected_utxos.is_empty());
+        assert!(selected_utxos.len() <= max_inputs);
+
+        Ok(selected_utxos)
+    }
+}
+
+/// Searches for a set of UTXOs whose total is exactly the value being spent,
+/// so that the resulting transaction has no change output. If no exact match
+/// is found within [BRANCH_AND_BOUND_MAX_TRIES] steps, falls back to
+/// [SmallestFirst].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BranchAndBound;
+
+impl BranchAndBound {
+    /// Depth-first search over `utxos` (sorted in descending order) for a
+    /// subset summing to exactly `target`. Returns the indices of the selected
+    /// UTXOs.
+    fn search(utxos: &[UnspentTxOut], target: u64, max_inputs: usize) -> Option<Vec<usize>> {
+        // remaining[i] is the sum of the values of utxos[i..].
+        let mut remaining = vec![0u64; utxos.len() + 1];
+        for i in (0..utxos.len()).rev() {
+            remaining[i] = remaining[i + 1].saturating_add(utxos[i].value);
+        }
+
+        let mut selected: Vec<usize> = Vec::new();
+        let mut total = 0u64;
+        let mut next = 0usize;
+        let mut tries = 0usize;
+
+        loop {
+            tries += 1;
+            if tries > BRANCH_AND_BOUND_MAX_TRIES {
+                return None;
+            }
+
+            if total == target {
+                return Some(selected);
+            }
+
+            // Decide whether we can keep going down this branch.
+            let can_include = next < utxos.len()
+                && selected.len() < max_inputs
+                && total.saturating_add(remaining[next]) >= target;
+
+            if can_include {
+                let candidate = utxos[next].value;
+                if total.saturating_add(candidate) <= target {
+                    // Include utxos[next] and move on.
+                    selected.push(next);
+                    total += candidate;
+                }
+                next += 1;
+                continue;
+            }
+
+            // Backtrack: drop the last included UTXO and try the branch that
+            // excludes it.
+            match selected.pop() {
+                Some(last) => {
+                    total -= utxos[last].value;
+                    next = last + 1;
+                }
+                None => return None,
+            }
+        }
+    }
+}
+
+impl UtxoSelectionStrategy for BranchAndBound {
+    fn select_utxos(
+        &self,
+        utxos: &[UnspentTxOut],
+        value: u64,
+        max_inputs: usize,
+    ) -> Result<Vec<UnspentTxOut>, Error> {
+        let sorted_utxos = sorted_descending(utxos);
+        check_spendable(&sorted_utxos, value, max_inputs)?;
+
+        match Self::search(&sorted_utxos, value, max_inputs) {
+            Some(indices) if !indices.is_empty() => Ok(indices
+                .into_iter()
+                .map(|i| sorted_utxos[i].clone())
+                .collect()),
+            _ => SmallestFirst.select_utxos(utxos, value, max_inputs),
+        }
+    }
+}
+
+/// Spends a random subset of UTXOs, so that the choice of inputs does not leak
+/// information about the wallet's UTXO set. If no random subset fits within
+/// `max_inputs` after [RANDOM_SELECTION_MAX_TRIES] attempts, falls back to
+/// [LargestFirst].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RandomSelection;
+
+impl UtxoSelectionStrategy for RandomSelection {
+    fn select_utxos(
+        &self,
+        utxos: &[UnspentTxOut],
+        value: u64,
+        max_inputs: usize,
+    ) -> Result<Vec<UnspentTxOut>, Error> {
+        let sorted_utxos = sorted_descending(utxos);
+        check_spendable(&sorted_utxos, value, max_inputs)?;
+
+        let mut rng = rand::thread_rng();
+        let mut candidates = sorted_utxos;
+        for _ in 0..RANDOM_SELECTION_MAX_TRIES {
+            candidates.shuffle(&mut rng);
+
+            let mut total = 0;
+            let mut selected_utxos = Vec::new();
+            for utxo in candidates.iter().take(max_inputs) {
+                if total >= value {
+                    break;
+                }
+                total += utxo.value;
+                selected_utxos.push(utxo.clone());
+            }
+
+            if total >= value {
+                return Ok(selected_utxos);
+            }
+        }
+
+        LargestFirst.select_utxos(utxos, value, max_inputs)
+    }
+}
+
+/// Returns a copy of `utxos`, sorted in descending order by value.
+fn sorted_descending(utxos: &[UnspentTxOut]) -> Vec<UnspentTxOut> {
+    let mut sorted_utxos = utxos.to_vec();
+    sorted_utxos.sort_by_key(|utxo| Reverse(utxo.value));
+    sorted_utxos
+}
+
+/// Checks that `value` can be covered by at most `max_inputs` of the given
+/// UTXOs, which must be sorted in descending order by value.
+fn check_spendable(
+    sorted_utxos: &[UnspentTxOut],
+    value: u64,
+    max_inputs: usize,
+) -> Result<(), Error> {
+    // The maximum spendable is limited by the maximal number of inputs we can use.
+    let max_spendable_amount: u64 = sorted_utxos
+        .iter()
+        .take(max_inputs)
+        .map(|utxo| utxo.value)
+        .sum();
+    if value > max_spendable_amount {
+        // See if we merged the UTXOs we would be able to spend this amount.
+        let total_utxos_value: u64 = sorted_utxos.iter().map(|utxo| utxo.value).sum();
+        if total_utxos_value >= value {
+            return Err(Error::InsufficientFundsFragmentedUtxos);
+        } else {
+            return Err(Error::InsufficientFunds);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use mc_account_keys::AccountKey;
+    use mc_crypto_keys::RistrettoPrivate;
+    use mc_transaction_core::{tokens::Mob, tx::TxOut, Amount, Token};
+    use mc_util_from_random::FromRandom;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn generate_utxos(values: &[u64]) -> Vec<UnspentTxOut> {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let alice = AccountKey::random(&mut rng);
+
+        values
+            .iter()
+            .map(|value| {
+                let tx_out = TxOut::new(
+                    Amount::new(*value, Mob::ID),
+                    &alice.default_subaddress(),
+                    &RistrettoPrivate::from_random(&mut rng),
+                    Default::default(),
+                )
+                .unwrap();
+
+                UnspentTxOut {
+                    tx_out,
+                    subaddress_index: 0,
+                    key_image: Default::default(),
+                    value: *value,
+                    attempted_spend_height: 0,
+                    attempted_spend_tombstone: 0,
+                    token_id: *Mob::ID,
+                }
+            })
+            .collect()
+    }
+
+    fn total(utxos: &[UnspentTxOut]) -> u64 {
+        utxos.iter().map(|utxo| utxo.value).sum()
+    }
+
+    #[test]
+    fn test_largest_first() {
+        let utxos = generate_utxos(&[100, 200, 300, 2000, 1000]);
+
+        let selected = LargestFirst.select_utxos(&utxos, 300, 5).unwrap();
+        assert_eq!(selected, vec![utxos[3].clone()]);
+
+        let selected = LargestFirst.select_utxos(&utxos, 2500, 5).unwrap();
+        assert_eq!(selected, vec![utxos[3].clone(), utxos[4].clone()]);
+
+        assert!(matches!(
+            LargestFirst.select_utxos(&utxos, 3500, 2),
+            Err(Error::InsufficientFundsFragmentedUtxos)
+        ));
+        assert!(matches!(
+            LargestFirst.select_utxos(&utxos, 5000, 5),
+            Err(Error::InsufficientFunds)
+        ));
+    }
+
+    #[test]
+    fn test_branch_and_bound_finds_exact_match() {
+        let utxos = generate_utxos(&[100, 200, 300, 2000, 1000, 7]);
+
+        // 1307 can only be made exactly out of 1000 + 300 + 7.
+        let selected = BranchAndBound.select_utxos(&utxos, 1307, 5).unwrap();
+        assert_eq!(total(&selected), 1307);
+        assert_eq!(selected.len(), 3);
+
+        // 2100 is 2000 + 100.
+        let selected = BranchAndBound.select_utxos(&utxos, 2100, 5).unwrap();
+        assert_eq!(selected, vec![utxos[3].clone(), utxos[0].clone()]);
+    }
+
+    #[test]
+    fn test_branch_and_bound_respects_max_inputs() {
+        let utxos = generate_utxos(&[1, 2, 4, 8, 16]);
+
+        // 15 = 8 + 4 + 2 + 1 needs four inputs. With only three allowed, there is
+        // no exact match so we fall back to smallest-first.
+        let selected = BranchAndBound.select_utxos(&utxos, 15, 3).unwrap();
+        assert!(selected.len() <= 3);
+        assert!(total(&selected) >= 15);
+
+        let selected = BranchAndBound.select_utxos(&utxos, 15, 4).unwrap();
+        assert_eq!(total(&selected), 15);
+    }
+
+    #[test]
+    fn test_random_selection() {
+        let utxos = generate_utxos(&[100, 200, 300, 2000, 1000]);
+
+        for _ in 0..50 {
+            let selected = RandomSelection.select_utxos(&utxos, 1100, 2).unwrap();
+            assert!(selected.len() <= 2);
+            assert!(total(&selected) >= 1100);
+        }
+
+        assert!(matches!(
+            RandomSelection.select_utxos(&utxos, 3500, 2),
+            Err(Error::InsufficientFundsFragmentedUtxos)
+        ));
+    }
+}
@@ -0,0 +1,567 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Database storage for webhook notifications.
+//! * Stores the webhook configuration of monitors (MonitorId -> WebhookConfig).
+//!   Configurations contain the HMAC secret, so they are encrypted the same way
+//!   MonitorData is.
+//! * Stores the queue of pending deliveries (WebhookDeliveryKey ->
+//!   PendingWebhook). Deliveries are enqueued in the same transaction that
+//!   processes a block, so that a notification is not lost if mobilecoind stops
+//!   before delivering it. Keys start with the time of the next delivery
+//!   attempt, so due deliveries are found at the start of the database.
+
+use crate::{
+    db_crypto::DbCryptoProvider, error::Error, monitor_store::MonitorId, utxo_store::UnspentTxOut,
+    webhooks::webhook_payload,
+};
+use lmdb::{Cursor, Database, DatabaseFlags, Environment, RwTransaction, Transaction, WriteFlags};
+use mc_common::logger::{log, Logger};
+use prost::Message;
+use std::{
+    convert::{TryFrom, TryInto},
+    sync::Arc,
+};
+
+// LMDB Database Names
+pub const MONITOR_ID_TO_WEBHOOK_CONFIG_DB_NAME: &str =
+    "mobilecoind_db:webhook_store:monitor_id_to_webhook_config";
+pub const WEBHOOK_DELIVERY_KEY_TO_PENDING_WEBHOOK_DB_NAME: &str =
+    "mobilecoind_db:webhook_store:webhook_delivery_key_to_pending_webhook";
+
+/// The webhook configuration of a monitor.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct WebhookConfig {
+    /// The URL notifications are POSTed to.
+    #[prost(string, tag = "1")]
+    pub url: String,
+
+    /// The secret used to sign notifications.
+    #[prost(bytes, tag = "2")]
+    pub secret: Vec<u8>,
+}
+
+/// Type used as the key in the webhook_delivery_key_to_pending_webhook
+/// database.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct WebhookDeliveryKey {
+    /// The time of the next delivery attempt, in seconds since the UNIX epoch.
+    pub next_attempt_at: u64,
+
+    /// The monitor_id the notification belongs to.
+    pub monitor_id: MonitorId,
+
+    /// The block the notification was created for.
+    pub block_index: u64,
+}
+
+impl WebhookDeliveryKey {
+    pub fn new(next_attempt_at: u64, monitor_id: &MonitorId, block_index: u64) -> Self {
+        Self {
+            next_attempt_at,
+            monitor_id: *monitor_id,
+            block_index,
+        }
+    }
+
+    // 48 bytes: 8 for the next attempt time, 32 for MonitorId, 8 for block index.
+    pub fn to_bytes(&self) -> [u8; 48] {
+        let mut buf = [0u8; 48];
+        buf[0..8].copy_from_slice(&self.next_attempt_at.to_be_bytes());
+        buf[8..40].copy_from_slice(self.monitor_id.as_bytes());
+        buf[40..48].copy_from_slice(&self.block_index.to_be_bytes());
+        buf
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for WebhookDeliveryKey {
+    type Error = Error;
+
+    fn try_from(src: &[u8]) -> Result<Self, Self::Error> {
+        if src.len() != 48 {
+            return Err(Error::InvalidArgument(
+                "src".to_string(),
+                "src length must be exactly 48".to_string(),
+            ));
+        }
+
+        let next_attempt_at = u64::from_be_bytes(src[0..8].try_into().expect("arithmetic error"));
+        let monitor_id = MonitorId::try_from(&src[8..40])?;
+        let block_index = u64::from_be_bytes(src[40..48].try_into().expect("arithmetic error"));
+
+        Ok(Self {
+            next_attempt_at,
+            monitor_id,
+            block_index,
+        })
+    }
+}
+
+/// Type used as the stored data in the
+/// webhook_delivery_key_to_pending_webhook database.
+///
+/// The notification is signed when it is delivered, so that the timestamp of
+/// the signature is that of the delivery attempt rather than that of the
+/// notification. It therefore carries the secret of the webhook it was created
+/// for.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct PendingWebhook {
+    /// The URL to deliver the notification to.
+    #[prost(string, tag = "1")]
+    pub url: String,
+
+    /// The unique id of the notification.
+    #[prost(string, tag = "2")]
+    pub event_id: String,
+
+    /// The JSON body of the notification.
+    #[prost(bytes, tag = "3")]
+    pub body: Vec<u8>,
+
+    // Tag 4 held a signature computed when the notification was created.
+    /// The number of failed delivery attempts so far.
+    #[prost(uint32, tag = "5")]
+    pub attempts: u32,
+
+    /// The secret used to sign the notification.
+    #[prost(bytes, tag = "6")]
+    pub secret: Vec<u8>,
+}
+
+/// The webhook database.
+#[derive(Clone)]
+pub struct WebhookStore {
+    /// Retain a reference to the Environment so the Database handles are valid.
+    _env: Arc<Environment>,
+
+    /// Crypto provider, used for managing database encryption.
+    crypto_provider: DbCryptoProvider,
+
+    /// Mapping of MonitorId -> WebhookConfig.
+    monitor_id_to_webhook_config: Database,
+
+    /// Mapping of WebhookDeliveryKey -> PendingWebhook.
+    webhook_delivery_key_to_pending_webhook: Database,
+
+    /// Logger.
+    logger: Logger,
+}
+
+impl WebhookStore {
+    pub fn new(
+        env: Arc<Environment>,
+        crypto_provider: DbCryptoProvider,
+        logger: Logger,
+    ) -> Result<Self, Error> {
+        let monitor_id_to_webhook_config = env.create_db(
+            Some(MONITOR_ID_TO_WEBHOOK_CONFIG_DB_NAME),
+            DatabaseFlags::empty(),
+        )?;
+        let webhook_delivery_key_to_pending_webhook = env.create_db(
+            Some(WEBHOOK_DELIVERY_KEY_TO_PENDING_WEBHOOK_DB_NAME),
+            DatabaseFlags::empty(),
+        )?;
+
+        Ok(Self {
+            _env: env,
+            crypto_provider,
+            monitor_id_to_webhook_config,
+            webhook_delivery_key_to_pending_webhook,
+            logger,
+        })
+    }
+
+    /// Get the webhook configuration of a monitor, if it has one.
+    pub fn get_config(
+        &self,
+        db_txn: &impl Transaction,
+        monitor_id: &MonitorId,
+    ) -> Result<Option<WebhookConfig>, Error> {
+        match db_txn.get(self.monitor_id_to_webhook_config, monitor_id) {
+            Ok(value_bytes) => {
+                let value_bytes = self.crypto_provider.decrypt(value_bytes)?;
+                Ok(Some(mc_util_serial::decode(&value_bytes)?))
+            }
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(err) => Err(Error::Lmdb(err)),
+        }
+    }
+
+    /// Set the webhook configuration of a monitor, replacing any previous one.
+    pub fn set_config<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        monitor_id: &MonitorId,
+        config: &WebhookConfig,
+    ) -> Result<(), Error> {
+        let value_bytes = self
+            .crypto_provider
+            .encrypt(&mc_util_serial::encode(config))?;
+        db_txn.put(
+            self.monitor_id_to_webhook_config,
+            monitor_id,
+            &value_bytes,
+            WriteFlags::empty(),
+        )?;
+        Ok(())
+    }
+
+    /// Remove the webhook configuration and all pending deliveries of a
+    /// monitor.
+    pub fn remove<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        monitor_id: &MonitorId,
+    ) -> Result<(), Error> {
+        match db_txn.del(self.monitor_id_to_webhook_config, monitor_id, None) {
+            Ok(()) | Err(lmdb::Error::NotFound) => {}
+            Err(err) => return Err(Error::Lmdb(err)),
+        }
+
+        let mut cursor = db_txn.open_rw_cursor(self.webhook_delivery_key_to_pending_webhook)?;
+        for (db_key, _db_value) in cursor.iter().filter_map(|r| r.ok()) {
+            let key = WebhookDeliveryKey::try_from(db_key)?;
+            if key.monitor_id == *monitor_id {
+                cursor.del(WriteFlags::empty())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the number of pending deliveries of a monitor.
+    pub fn get_num_pending(
+        &self,
+        db_txn: &impl Transaction,
+        monitor_id: &MonitorId,
+    ) -> Result<u64, Error> {
+        let mut cursor = db_txn.open_ro_cursor(self.webhook_delivery_key_to_pending_webhook)?;
+        let mut num_pending = 0;
+        for result in cursor.iter() {
+            let (db_key, _db_value) = result?;
+            if WebhookDeliveryKey::try_from(db_key)?.monitor_id == *monitor_id {
+                num_pending += 1;
+            }
+        }
+        Ok(num_pending)
+    }
+
+    /// Get up to `limit` pending deliveries whose next attempt is due at `now`.
+    pub fn get_due(
+        &self,
+        db_txn: &impl Transaction,
+        now: u64,
+        limit: usize,
+    ) -> Result<Vec<(WebhookDeliveryKey, PendingWebhook)>, Error> {
+        let mut cursor = db_txn.open_ro_cursor(self.webhook_delivery_key_to_pending_webhook)?;
+
+        let mut due = Vec::new();
+        for result in cursor.iter() {
+            let (db_key, db_value) = result?;
+            let key = WebhookDeliveryKey::try_from(db_key)?;
+            if key.next_attempt_at > now || due.len() == limit {
+                break;
+            }
+
+            let value_bytes = self.crypto_provider.decrypt(db_value)?;
+            due.push((key, mc_util_serial::decode(&value_bytes)?));
+        }
+
+        Ok(due)
+    }
+
+    /// Add a pending delivery.
+    pub fn put<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        key: &WebhookDeliveryKey,
+        pending: &PendingWebhook,
+    ) -> Result<(), Error> {
+        let value_bytes = self
+            .crypto_provider
+            .encrypt(&mc_util_serial::encode(pending))?;
+        db_txn.put(
+            self.webhook_delivery_key_to_pending_webhook,
+            &key.to_vec(),
+            &value_bytes,
+            WriteFlags::empty(),
+        )?;
+        Ok(())
+    }
+
+    /// Delete a pending delivery. Returns false if it did not exist, e.g.
+    /// because its monitor was removed in the meantime.
+    pub fn delete<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        key: &WebhookDeliveryKey,
+    ) -> Result<bool, Error> {
+        match db_txn.del(
+            self.webhook_delivery_key_to_pending_webhook,
+            &key.to_vec(),
+            None,
+        ) {
+            Ok(()) => Ok(true),
+            Err(lmdb::Error::NotFound) => Ok(false),
+            Err(err) => Err(Error::Lmdb(err)),
+        }
+    }
+
+    /// Re-encrypt the encrypted parts of the database with a new password.
+    /// This will fail if the current password is not set in the crypto_provider
+    /// since part of the re-encryption process relies on being able to
+    /// decrypt the existing data.
+    pub fn re_encrypt<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        new_password: &[u8],
+    ) -> Result<(), Error> {
+        for db in [
+            self.monitor_id_to_webhook_config,
+            self.webhook_delivery_key_to_pending_webhook,
+        ] {
+            let mut cursor = db_txn.open_rw_cursor(db)?;
+
+            for (key_bytes, value_bytes) in cursor.iter().filter_map(|r| r.ok()) {
+                let decrypted_bytes = self.crypto_provider.decrypt(value_bytes)?;
+                let encrypted_bytes = self
+                    .crypto_provider
+                    .encrypt_with_password(new_password, &decrypted_bytes)?;
+                cursor.put(&key_bytes, &encrypted_bytes, WriteFlags::CURRENT)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Feed data processed from a given block. If the monitor has a webhook
+    /// and the block contains TxOuts it received or spent, a notification is
+    /// queued for delivery at `now`.
+    pub fn block_processed<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        monitor_id: &MonitorId,
+        block_index: u64,
+        discovered_utxos: &[UnspentTxOut],
+        spent_utxos: &[UnspentTxOut],
+        now: u64,
+    ) -> Result<(), Error> {
+        if discovered_utxos.is_empty() && spent_utxos.is_empty() {
+            return Ok(());
+        }
+        let config = match self.get_config(&*db_txn, monitor_id)? {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+
+        let (event_id, body) =
+            webhook_payload(monitor_id, block_index, now, discovered_utxos, spent_utxos);
+        let pending = PendingWebhook {
+            url: config.url,
+            event_id,
+            body,
+            attempts: 0,
+            secret: config.secret,
+        };
+
+        log::trace!(
+            self.logger,
+            "queueing webhook {} for monitor {}",
+            pending.event_id,
+            monitor_id
+        );
+        self.put(
+            db_txn,
+            &WebhookDeliveryKey::new(now, monitor_id, block_index),
+            &pending,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::monitor_store::MonitorData;
+    use mc_account_keys::AccountKey;
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_crypto_keys::RistrettoPrivate;
+    use mc_crypto_rand::RngCore;
+    use mc_transaction_core::{
+        encrypted_fog_hint::EncryptedFogHint, ring_signature::KeyImage, tokens::Mob, tx::TxOut,
+        Amount, Token,
+    };
+    use mc_util_from_random::FromRandom;
+    use rand::{rngs::StdRng, SeedableRng};
+    use tempdir::TempDir;
+
+    fn create_utxo(account_key: &AccountKey, value: u64, rng: &mut StdRng) -> UnspentTxOut {
+        let tx_out = TxOut::new(
+            Amount {
+                value,
+                token_id: Mob::ID,
+            },
+            &account_key.default_subaddress(),
+            &RistrettoPrivate::from_random(rng),
+            EncryptedFogHint::fake_onetime_hint(rng),
+        )
+        .unwrap();
+
+        UnspentTxOut {
+            tx_out,
+            subaddress_index: 0,
+            key_image: KeyImage::from(rng.next_u64()),
+            value,
+            attempted_spend_height: 0,
+            attempted_spend_tombstone: 0,
+            token_id: *Mob::ID,
+        }
+    }
+
+    // WebhookStore basic functionality tests
+    #[test_with_logger]
+    fn test_webhook_store(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+
+        let db_tmp =
+            TempDir::new("webhook_store_db").expect("Could not make tempdir for webhook store db");
+        let db_path = db_tmp
+            .path()
+            .to_str()
+            .expect("Could not get path as string");
+        let env = Arc::new(
+            Environment::new()
+                .set_max_dbs(10)
+                .set_map_size(10000000)
+                .open(db_path.as_ref())
+                .unwrap(),
+        );
+        let crypto_provider = DbCryptoProvider::new(env.clone()).unwrap();
+        let store = WebhookStore::new(env.clone(), crypto_provider, logger).unwrap();
+
+        let account_key = AccountKey::random(&mut rng);
+        let monitor_id =
+            MonitorId::from(&MonitorData::new(account_key.clone(), 0, 10, 0, "").unwrap());
+        let other_monitor_id =
+            MonitorId::from(&MonitorData::new(account_key.clone(), 10, 10, 0, "").unwrap());
+
+        let config = WebhookConfig {
+            url: "https://example.com/webhook".to_string(),
+            secret: b"secret".to_vec(),
+        };
+
+        let received = create_utxo(&account_key, 100, &mut rng);
+        let spent = create_utxo(&account_key, 200, &mut rng);
+
+        {
+            let mut db_txn = env.begin_rw_txn().unwrap();
+            assert_eq!(store.get_config(&db_txn, &monitor_id).unwrap(), None);
+
+            store.set_config(&mut db_txn, &monitor_id, &config).unwrap();
+            assert_eq!(
+                store.get_config(&db_txn, &monitor_id).unwrap(),
+                Some(config.clone())
+            );
+
+            // Blocks without TxOuts, and monitors without a webhook, do not queue
+            // anything.
+            store
+                .block_processed(&mut db_txn, &monitor_id, 4, &[], &[], 1000)
+                .unwrap();
+            store
+                .block_processed(
+                    &mut db_txn,
+                    &other_monitor_id,
+                    5,
+                    &[received.clone()],
+                    &[],
+                    1000,
+                )
+                .unwrap();
+            assert_eq!(store.get_due(&db_txn, u64::MAX, 10).unwrap(), vec![]);
+
+            store
+                .block_processed(
+                    &mut db_txn,
+                    &monitor_id,
+                    5,
+                    &[received.clone()],
+                    &[spent.clone()],
+                    1000,
+                )
+                .unwrap();
+            store
+                .block_processed(&mut db_txn, &monitor_id, 6, &[received.clone()], &[], 1010)
+                .unwrap();
+            db_txn.commit().unwrap();
+        }
+
+        {
+            let db_txn = env.begin_ro_txn().unwrap();
+            assert_eq!(store.get_num_pending(&db_txn, &monitor_id).unwrap(), 2);
+            assert_eq!(
+                store.get_num_pending(&db_txn, &other_monitor_id).unwrap(),
+                0
+            );
+
+            // Only deliveries that are due are returned, oldest first.
+            assert_eq!(store.get_due(&db_txn, 999, 10).unwrap(), vec![]);
+            let due = store.get_due(&db_txn, 1005, 10).unwrap();
+            assert_eq!(due.len(), 1);
+            let (key, pending) = &due[0];
+            assert_eq!(*key, WebhookDeliveryKey::new(1000, &monitor_id, 5));
+            assert_eq!(pending.url, config.url);
+            assert_eq!(pending.attempts, 0);
+            assert_eq!(pending.secret, config.secret);
+
+            let body: serde_json::Value = serde_json::from_slice(&pending.body).unwrap();
+            assert_eq!(body["event_id"], pending.event_id.as_str());
+            assert_eq!(body["block_index"], "5");
+            assert_eq!(body["tx_outs"][0]["direction"], "received");
+            assert_eq!(body["tx_outs"][0]["value"], "100");
+            assert_eq!(body["tx_outs"][1]["direction"], "spent");
+            assert_eq!(body["tx_outs"][1]["value"], "200");
+
+            assert_eq!(store.get_due(&db_txn, 1010, 10).unwrap().len(), 2);
+            assert_eq!(store.get_due(&db_txn, 1010, 1).unwrap().len(), 1);
+        }
+
+        {
+            // Rescheduling a delivery moves it behind the other one.
+            let mut db_txn = env.begin_rw_txn().unwrap();
+            let (key, mut pending) = store.get_due(&db_txn, 1000, 1).unwrap().remove(0);
+            assert!(store.delete(&mut db_txn, &key).unwrap());
+            assert!(!store.delete(&mut db_txn, &key).unwrap());
+            pending.attempts += 1;
+            let new_key = WebhookDeliveryKey::new(1020, &monitor_id, key.block_index);
+            store.put(&mut db_txn, &new_key, &pending).unwrap();
+
+            let due = store.get_due(&db_txn, 1020, 10).unwrap();
+            assert_eq!(
+                due.iter()
+                    .map(|(key, _)| key.block_index)
+                    .collect::<Vec<_>>(),
+                vec![6, 5]
+            );
+            assert_eq!(due[1].1.attempts, 1);
+
+            // Removing the monitor removes its configuration and pending deliveries.
+            store.remove(&mut db_txn, &monitor_id).unwrap();
+            assert_eq!(store.get_config(&db_txn, &monitor_id).unwrap(), None);
+            assert_eq!(store.get_num_pending(&db_txn, &monitor_id).unwrap(), 0);
+            db_txn.commit().unwrap();
+        }
+    }
+
+    #[test]
+    fn webhook_delivery_key_round_trip() {
+        let key = WebhookDeliveryKey::new(123, &MonitorId::from([7u8; 32]), 456);
+        assert_eq!(
+            WebhookDeliveryKey::try_from(&key.to_bytes()[..]).unwrap(),
+            key
+        );
+        assert!(WebhookDeliveryKey::try_from(&key.to_bytes()[1..]).is_err());
+    }
+}
@@ -0,0 +1,505 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Webhook notifications of monitor activity.
+//!
+//! When a monitor with a webhook processes a block in which it received or
+//! spent TxOuts, a JSON notification is POSTed to the webhook URL:
+//!
+//! ```json
+//! {
+//!   "event_id": "<monitor id>-<block index>",
+//!   "monitor_id": "<hex>",
+//!   "block_index": "1234",
+//!   "created_at": 1650000000,
+//!   "tx_outs": [{
+//!     "direction": "received",
+//!     "subaddress_index": "0",
+//!     "public_key": "<hex>",
+//!     "key_image": "<hex>",
+//!     "value": "1000000000000",
+//!     "token_id": "0"
+//!   }]
+//! }
+//! ```
+//!
+//! 64 bit numbers are encoded as strings, like in mobilecoind-json.
+//!
+//! Every notification carries an `X-Mobilecoind-Event-Id` header with its
+//! event id, and an `X-Mobilecoind-Signature` header of the form
+//! `t=<timestamp>,v1=<signature>`, where the timestamp is the time of the
+//! delivery attempt and the signature is the hex encoded HMAC-SHA256 of
+//! `<timestamp>.<body>` keyed with the webhook secret. Receivers should verify
+//! the signature and may reject stale timestamps, and may use the event id to
+//! ignore notifications delivered more than once.
+//!
+//! Notifications are delivered at least once. A delivery succeeds when the
+//! receiver responds with a 2xx status code. Otherwise it is retried with an
+//! exponential backoff, until it succeeds or the maximum number of attempts is
+//! reached. Pending deliveries are persisted in the mobilecoind database, so
+//! they survive restarts. Each endpoint is delivered to by its own thread, so
+//! an unresponsive endpoint does not delay the notifications of others.
+
+use crate::{
+    database::Database,
+    monitor_store::MonitorId,
+    utxo_store::UnspentTxOut,
+    webhook_store::{PendingWebhook, WebhookDeliveryKey},
+};
+use crossbeam_channel::Sender;
+use hmac::{Hmac, Mac};
+use mc_common::logger::{log, Logger};
+use reqwest::{blocking::Client, header::CONTENT_TYPE};
+use serde_json::json;
+use sha2::Sha256;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The header carrying the signature of a notification.
+pub const SIGNATURE_HEADER: &str = "X-Mobilecoind-Signature";
+
+/// The header carrying the event id of a notification.
+pub const EVENT_ID_HEADER: &str = "X-Mobilecoind-Event-Id";
+
+/// The delay before the first retry of a failed delivery. It doubles with
+/// every further failure.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The maximum delay between two delivery attempts.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// The maximum number of new deliveries dispatched between two database
+/// queries.
+const MAX_DELIVERIES_PER_ITERATION: usize = 100;
+
+/// The maximum number of endpoints notifications are delivered to
+/// concurrently.
+const MAX_CONCURRENT_ENDPOINTS: usize = 32;
+
+/// The maximum number of deliveries queued for a single endpoint.
+const MAX_IN_FLIGHT_PER_ENDPOINT: usize = 10;
+
+/// The current time, in seconds since the UNIX epoch.
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is before the UNIX epoch")
+        .as_secs()
+}
+
+/// The delay before retrying a delivery that failed `attempts` times.
+pub fn retry_delay(attempts: u32) -> Duration {
+    INITIAL_RETRY_DELAY
+        .checked_mul(1 << attempts.saturating_sub(1).min(31))
+        .map_or(MAX_RETRY_DELAY, |delay| delay.min(MAX_RETRY_DELAY))
+}
+
+/// Compute the signature header of a notification delivered at `timestamp`.
+pub fn signature_header(secret: &[u8], timestamp: u64, body: &[u8]) -> String {
+    format!(
+        "t={},v1={}",
+        timestamp,
+        hex::encode(
+            signature_mac(secret, timestamp, body)
+                .finalize()
+                .into_bytes()
+        )
+    )
+}
+
+/// Verify the signature header of a notification. Returns the time the
+/// notification was signed at if the signature is valid.
+pub fn verify_signature_header(secret: &[u8], header: &str, body: &[u8]) -> Option<u64> {
+    let mut timestamp = None;
+    let mut signature = None;
+    for part in header.split(',') {
+        match part.split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<u64>().ok(),
+            Some(("v1", value)) => signature = hex::decode(value).ok(),
+            _ => {}
+        }
+    }
+
+    let timestamp = timestamp?;
+    signature_mac(secret, timestamp, body)
+        .verify_slice(&signature?)
+        .ok()
+        .map(|_| timestamp)
+}
+
+fn signature_mac(secret: &[u8], timestamp: u64, body: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+/// Build the notification for the TxOuts a monitor received and spent in a
+/// block. Returns the event id and the JSON body.
+pub(crate) fn webhook_payload(
+    monitor_id: &MonitorId,
+    block_index: u64,
+    created_at: u64,
+    received_utxos: &[UnspentTxOut],
+    spent_utxos: &[UnspentTxOut],
+) -> (String, Vec<u8>) {
+    let event_id = format!("{}-{}", monitor_id, block_index);
+
+    let tx_outs = received_utxos
+        .iter()
+        .map(|utxo| (utxo, "received"))
+        .chain(spent_utxos.iter().map(|utxo| (utxo, "spent")))
+        .map(|(utxo, direction)| {
+            json!({
+                "direction": direction,
+                "subaddress_index": utxo.subaddress_index.to_string(),
+                "public_key": hex::encode(utxo.tx_out.public_key.as_bytes()),
+                "key_image": hex::encode(utxo.key_image.as_bytes()),
+                "value": utxo.value.to_string(),
+                "token_id": utxo.token_id.to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let body = json!({
+        "event_id": event_id,
+        "monitor_id": monitor_id.to_string(),
+        "block_index": block_index.to_string(),
+        "created_at": created_at,
+        "tx_outs": tx_outs,
+    });
+
+    (
+        event_id,
+        serde_json::to_vec(&body).expect("failed serializing webhook payload"),
+    )
+}
+
+/// A thread that delivers pending webhook notifications.
+///
+/// Deliveries are handed to one worker thread per endpoint, so that an
+/// endpoint that is down or slow to respond only delays its own
+/// notifications.
+pub struct WebhookDeliveryThread {
+    /// The thread handle.
+    join_handle: Option<thread::JoinHandle<()>>,
+
+    /// Stop trigger, used to signal the thread to terminate.
+    stop_requested: Arc<AtomicBool>,
+}
+
+impl WebhookDeliveryThread {
+    pub fn start(
+        mobilecoind_db: Database,
+        timeout: Duration,
+        max_attempts: u32,
+        logger: Logger,
+    ) -> Self {
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("failed creating webhook http client");
+
+        let stop_requested = Arc::new(AtomicBool::new(false));
+        let thread_stop_requested = stop_requested.clone();
+
+        let join_handle = Some(
+            thread::Builder::new()
+                .name("webhook_delivery".to_string())
+                .spawn(move || {
+                    log::debug!(logger, "WebhookDeliveryThread started.");
+
+                    // The deliveries handed to an endpoint worker and not handled yet, and
+                    // the URL of their endpoint.
+                    let in_flight = Arc::new(Mutex::new(HashMap::new()));
+                    let mut workers = HashMap::<String, EndpointWorker>::new();
+
+                    loop {
+                        if thread_stop_requested.load(Ordering::SeqCst) {
+                            log::debug!(logger, "WebhookDeliveryThread stop requested.");
+                            break;
+                        }
+
+                        // Stop the workers of endpoints that have nothing left to deliver.
+                        let (num_in_flight, busy_urls) = {
+                            let in_flight = in_flight.lock().expect("mutex poisoned");
+                            (
+                                in_flight.len(),
+                                in_flight.values().cloned().collect::<HashSet<String>>(),
+                            )
+                        };
+                        workers.retain(|url, _| busy_urls.contains(url));
+
+                        // Pending deliveries are encrypted, so nothing can be delivered until
+                        // the database is unlocked. Deliveries that are already in flight are
+                        // still in the database, so they are fetched in addition to the limit.
+                        let due = if mobilecoind_db.is_unlocked() {
+                            mobilecoind_db
+                                .get_due_webhooks(
+                                    unix_timestamp(),
+                                    MAX_DELIVERIES_PER_ITERATION + num_in_flight,
+                                )
+                                .unwrap_or_else(|err| {
+                                    log::error!(logger, "Failed getting due webhooks: {}", err);
+                                    Vec::new()
+                                })
+                        } else {
+                            Vec::new()
+                        };
+
+                        let mut num_dispatched = 0;
+                        for (key, pending) in due {
+                            let mut in_flight_guard = in_flight.lock().expect("mutex poisoned");
+                            if in_flight_guard.contains_key(&key) {
+                                continue;
+                            }
+                            let endpoint_in_flight = in_flight_guard
+                                .values()
+                                .filter(|url| **url == pending.url)
+                                .count();
+                            if endpoint_in_flight >= MAX_IN_FLIGHT_PER_ENDPOINT
+                                || (!workers.contains_key(&pending.url)
+                                    && workers.len() >= MAX_CONCURRENT_ENDPOINTS)
+                            {
+                                continue;
+                            }
+                            in_flight_guard.insert(key, pending.url.clone());
+                            drop(in_flight_guard);
+
+                            let worker = workers.entry(pending.url.clone()).or_insert_with(|| {
+                                EndpointWorker::start(
+                                    mobilecoind_db.clone(),
+                                    client.clone(),
+                                    max_attempts,
+                                    in_flight.clone(),
+                                    thread_stop_requested.clone(),
+                                    logger.clone(),
+                                )
+                            });
+                            worker.send(key, pending);
+                            num_dispatched += 1;
+                        }
+
+                        // If there is nothing new to deliver, sleep for a bit.
+                        if num_dispatched == 0 {
+                            thread::sleep(Duration::from_secs(1));
+                        }
+                    }
+
+                    // Dropping the workers waits for their current delivery to finish.
+                    drop(workers);
+
+                    log::debug!(logger, "WebhookDeliveryThread stopped.");
+                })
+                .expect("failed starting webhook delivery thread"),
+        );
+
+        Self {
+            join_handle,
+            stop_requested,
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.stop_requested.store(true, Ordering::SeqCst);
+        if let Some(join_handle) = self.join_handle.take() {
+            join_handle
+                .join()
+                .expect("WebhookDeliveryThread join failed");
+        }
+    }
+}
+
+/// A thread that delivers the notifications of one endpoint, in the order
+/// they are handed to it. It stops once it is dropped and its queue is empty.
+struct EndpointWorker {
+    /// Queue of the deliveries to attempt.
+    sender: Option<Sender<(WebhookDeliveryKey, PendingWebhook)>>,
+
+    /// The thread handle.
+    join_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl EndpointWorker {
+    fn start(
+        mobilecoind_db: Database,
+        client: Client,
+        max_attempts: u32,
+        in_flight: Arc<Mutex<HashMap<WebhookDeliveryKey, String>>>,
+        stop_requested: Arc<AtomicBool>,
+        logger: Logger,
+    ) -> Self {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+
+        let join_handle = Some(
+            thread::Builder::new()
+                .name("webhook_endpoint".to_string())
+                .spawn(move || {
+                    for (key, pending) in receiver.iter() {
+                        // Once a stop is requested, the remaining deliveries are left in the
+                        // database and attempted after a restart.
+                        if !stop_requested.load(Ordering::SeqCst) {
+                            attempt_delivery(
+                                &mobilecoind_db,
+                                &client,
+                                max_attempts,
+                                &key,
+                                pending,
+                                &logger,
+                            );
+                        }
+                        in_flight.lock().expect("mutex poisoned").remove(&key);
+                    }
+                })
+                .expect("failed starting webhook endpoint thread"),
+        );
+
+        Self {
+            sender: Some(sender),
+            join_handle,
+        }
+    }
+
+    /// Queue a delivery.
+    fn send(&self, key: WebhookDeliveryKey, pending: PendingWebhook) {
+        self.sender
+            .as_ref()
+            .expect("sender is only taken on drop")
+            .send((key, pending))
+            .expect("endpoint worker stopped");
+    }
+}
+
+impl Drop for EndpointWorker {
+    fn drop(&mut self) {
+        // Closing the channel lets the thread exit once its queue is empty.
+        self.sender.take();
+        if let Some(join_handle) = self.join_handle.take() {
+            join_handle.join().expect("EndpointWorker join failed");
+        }
+    }
+}
+
+/// Attempt a delivery, and remove it from the database once it succeeded or
+/// failed too many times. Otherwise, reschedule it.
+fn attempt_delivery(
+    mobilecoind_db: &Database,
+    client: &Client,
+    max_attempts: u32,
+    key: &WebhookDeliveryKey,
+    mut pending: PendingWebhook,
+    logger: &Logger,
+) {
+    let db_result = match deliver(client, &pending) {
+        Ok(()) => {
+            log::debug!(logger, "Delivered webhook {}", pending.event_id);
+            mobilecoind_db.remove_webhook_delivery(key)
+        }
+        Err(err) => {
+            pending.attempts += 1;
+            if pending.attempts >= max_attempts {
+                log::error!(
+                    logger,
+                    "Giving up on webhook {} to {} after {} attempts: {}",
+                    pending.event_id,
+                    pending.url,
+                    pending.attempts,
+                    err
+                );
+                mobilecoind_db.remove_webhook_delivery(key)
+            } else {
+                let delay = retry_delay(pending.attempts);
+                log::warn!(
+                    logger,
+                    "Failed delivering webhook {} to {} (attempt {}), retrying in {:?}: {}",
+                    pending.event_id,
+                    pending.url,
+                    pending.attempts,
+                    delay,
+                    err
+                );
+                mobilecoind_db.reschedule_webhook_delivery(
+                    key,
+                    unix_timestamp() + delay.as_secs(),
+                    &pending,
+                )
+            }
+        }
+    };
+    if let Err(err) = db_result {
+        log::error!(
+            logger,
+            "Failed updating webhook {}: {}",
+            pending.event_id,
+            err
+        );
+    }
+}
+
+/// POST a notification to its URL, signed with the current time.
+fn deliver(client: &Client, pending: &PendingWebhook) -> Result<(), String> {
+    let signature = signature_header(&pending.secret, unix_timestamp(), &pending.body);
+    let response = client
+        .post(&pending.url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, signature)
+        .header(EVENT_ID_HEADER, &pending.event_id)
+        .body(pending.body.clone())
+        .send()
+        .map_err(|err| err.to_string())?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("status {}", response.status()))
+    }
+}
+
+impl Drop for WebhookDeliveryThread {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn signature_header_round_trip() {
+        let body = br#"{"event_id":"abc-1"}"#;
+        let header = signature_header(b"secret", 1234, body);
+        assert!(header.starts_with("t=1234,v1="));
+
+        assert_eq!(
+            verify_signature_header(b"secret", &header, body),
+            Some(1234)
+        );
+        assert_eq!(verify_signature_header(b"other", &header, body), None);
+        assert_eq!(
+            verify_signature_header(b"secret", &header, br#"{"event_id":"abc-2"}"#),
+            None
+        );
+
+        // Changing the timestamp invalidates the signature.
+        let tampered = header.replace("t=1234", "t=1235");
+        assert_eq!(verify_signature_header(b"secret", &tampered, body), None);
+        assert_eq!(verify_signature_header(b"secret", "t=1234", body), None);
+    }
+
+    #[test]
+    fn retry_delay_backs_off_exponentially() {
+        assert_eq!(retry_delay(1), Duration::from_secs(1));
+        assert_eq!(retry_delay(2), Duration::from_secs(2));
+        assert_eq!(retry_delay(5), Duration::from_secs(16));
+        assert_eq!(retry_delay(13), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(u32::MAX), MAX_RETRY_DELAY);
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Order book error data type.
+
+use displaydoc::Display;
+use mc_ledger_db::Error as LedgerDbError;
+use mc_transaction_core::SignedContingentInputError;
+use mc_transaction_std::TxBuilderError;
+
+/// Order book error data type.
+#[derive(Debug, Display)]
+pub enum Error {
+    /// Invalid signed contingent input: {0}
+    SignedContingentInput(SignedContingentInputError),
+
+    /// The signed contingent input has no input rules
+    MissingRules,
+
+    /// Partial fill orders are not supported
+    PartialFillNotSupported,
+
+    /// The required outputs must ask for exactly one token other than the input
+    UnsupportedRequiredOutputs,
+
+    /// The required outputs leave nothing of the input on offer
+    NothingOffered,
+
+    /// The order is already in the order book
+    DuplicateOrder,
+
+    /// Order not found
+    NotFound,
+
+    /// The key image of the order has already appeared in the ledger
+    OrderSpent,
+
+    /// The orders do not cross
+    OrdersDoNotCross,
+
+    /// The surplus of the match cannot pay the fee
+    InsufficientSurplus,
+
+    /// Ledger DB: {0}
+    LedgerDb(LedgerDbError),
+
+    /// Tx builder: {0}
+    TxBuilder(TxBuilderError),
+}
+
+impl From<SignedContingentInputError> for Error {
+    fn from(err: SignedContingentInputError) -> Self {
+        Self::SignedContingentInput(err)
+    }
+}
+
+impl From<LedgerDbError> for Error {
+    fn from(err: LedgerDbError) -> Self {
+        Self::LedgerDb(err)
+    }
+}
+
+impl From<TxBuilderError> for Error {
+    fn from(err: TxBuilderError) -> Self {
+        Self::TxBuilder(err)
+    }
+}
@@ -0,0 +1,19 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A matching engine for signed contingent inputs (MCIP #31).
+//!
+//! Every signed contingent input which spends one token and requires outputs
+//! in another token is treated as an order. Orders are indexed by the pair of
+//! tokens they swap, and sorted by the price implied by their required outputs.
+
+#![deny(missing_docs)]
+
+mod error;
+mod order;
+mod order_book;
+
+pub use crate::{
+    error::Error,
+    order::Order,
+    order_book::{Match, OrderBook},
+};
@@ -0,0 +1,131 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! An order, i.e. a validated signed contingent input offering to swap one
+//! token for another.
+
+use crate::Error;
+use core::cmp::Ordering;
+use mc_transaction_core::{ring_signature::KeyImage, Amount, SignedContingentInput, TokenId};
+
+/// A validated signed contingent input, together with the amounts it swaps.
+#[derive(Clone, Debug)]
+pub struct Order {
+    /// The signed contingent input
+    sci: SignedContingentInput,
+    /// The amount offered, i.e. the value of the input, less any required
+    /// outputs in the same token (which return change to the signer)
+    offered: Amount,
+    /// The amount asked for, i.e. the total value of the required outputs in
+    /// the other token
+    asked: Amount,
+}
+
+impl Order {
+    /// Validate a signed contingent input and determine the amounts it swaps.
+    pub fn new(sci: SignedContingentInput) -> Result<Self, Error> {
+        sci.validate()?;
+
+        let rules = sci.tx_in.input_rules.as_ref().ok_or(Error::MissingRules)?;
+        if !rules.partial_fill_outputs.is_empty() || rules.partial_fill_change.is_some() {
+            return Err(Error::PartialFillNotSupported);
+        }
+
+        let input = Amount::from(&sci.pseudo_output_amount);
+        let mut change = 0u64;
+        let mut asked: Option<Amount> = None;
+        for required_output_amount in sci.required_output_amounts.iter() {
+            let amount = Amount::from(required_output_amount);
+            if amount.token_id == input.token_id {
+                change = change
+                    .checked_add(amount.value)
+                    .ok_or(Error::NothingOffered)?;
+                continue;
+            }
+            match asked.as_mut() {
+                None => asked = Some(amount),
+                Some(asked) if asked.token_id == amount.token_id => {
+                    asked.value = asked
+                        .value
+                        .checked_add(amount.value)
+                        .ok_or(Error::UnsupportedRequiredOutputs)?;
+                }
+                Some(_) => return Err(Error::UnsupportedRequiredOutputs),
+            }
+        }
+        let asked = asked.ok_or(Error::UnsupportedRequiredOutputs)?;
+
+        let offered_value = input.value.checked_sub(change).unwrap_or(0);
+        if offered_value == 0 {
+            return Err(Error::NothingOffered);
+        }
+        let offered = Amount::new(offered_value, input.token_id);
+
+        Ok(Self {
+            sci,
+            offered,
+            asked,
+        })
+    }
+
+    /// The signed contingent input of this order
+    pub fn sci(&self) -> &SignedContingentInput {
+        &self.sci
+    }
+
+    /// The key image of the input, which identifies the order
+    pub fn key_image(&self) -> KeyImage {
+        self.sci.key_image()
+    }
+
+    /// The amount offered by the signer
+    pub fn offered(&self) -> Amount {
+        self.offered
+    }
+
+    /// The amount the signer asks for in exchange
+    pub fn asked(&self) -> Amount {
+        self.asked
+    }
+
+    /// The pair of tokens swapped by this order, (offered, asked)
+    pub fn pair(&self) -> (TokenId, TokenId) {
+        (self.offered.token_id, self.asked.token_id)
+    }
+
+    /// The maximum tombstone block imposed by the input rules, or zero if
+    /// there is no limit
+    pub fn max_tombstone_block(&self) -> u64 {
+        self.sci
+            .tx_in
+            .input_rules
+            .as_ref()
+            .map(|rules| rules.max_tombstone_block)
+            .unwrap_or(0)
+    }
+
+    /// Compare the prices of two orders for the same pair, where the price is
+    /// asked / offered. A lower price is a better deal for the counterparty.
+    pub fn cmp_price(&self, other: &Order) -> Ordering {
+        // Cross multiply to avoid division
+        let lhs = self.asked.value as u128 * other.offered.value as u128;
+        let rhs = other.asked.value as u128 * self.offered.value as u128;
+        lhs.cmp(&rhs)
+    }
+
+    /// Whether this order and an order for the opposite pair can be filled by
+    /// each other, i.e. each one offers at least what the other one asks.
+    pub fn crosses(&self, other: &Order) -> bool {
+        self.offered.token_id == other.asked.token_id
+            && self.asked.token_id == other.offered.token_id
+            && self.offered.value >= other.asked.value
+            && other.offered.value >= self.asked.value
+    }
+
+    /// Whether the prices of this order and an order for the opposite pair
+    /// cross, i.e. the product of their prices is at most one.
+    pub fn prices_cross(&self, other: &Order) -> bool {
+        let lhs = self.asked.value as u128 * other.asked.value as u128;
+        let rhs = self.offered.value as u128 * other.offered.value as u128;
+        lhs <= rhs
+    }
+}
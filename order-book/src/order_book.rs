@@ -0,0 +1,466 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! The order book, which stores orders and matches them.
+
+use crate::{Error, Order};
+use mc_crypto_ring_signature_signer::NoKeysRingSigner;
+use mc_fog_report_validation::FogPubkeyResolver;
+use mc_ledger_db::Ledger;
+use mc_transaction_core::{
+    ring_signature::KeyImage, tx::Tx, Amount, BlockVersion, SignedContingentInput, TokenId,
+};
+use mc_transaction_std::{EmptyMemoBuilder, ReservedSubaddresses, TransactionBuilder};
+use rand_core::{CryptoRng, RngCore};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// A pair of orders which cross, and can be combined into a swap.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Match {
+    /// The order offering the lower token id
+    pub bid: KeyImage,
+    /// The order offering the higher token id
+    pub ask: KeyImage,
+}
+
+/// A collection of orders, indexed by token pair and price.
+#[derive(Default)]
+pub struct OrderBook {
+    /// All orders, by key image
+    orders: HashMap<KeyImage, Order>,
+    /// Key images of the orders for each pair of tokens (offered, asked),
+    /// sorted by price, best first. Orders with the same price are kept in
+    /// insertion order.
+    orders_by_pair: BTreeMap<(TokenId, TokenId), Vec<KeyImage>>,
+}
+
+impl OrderBook {
+    /// Create an empty order book.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validate a signed contingent input and add it to the order book.
+    ///
+    /// Returns the key image which identifies the order.
+    pub fn add_sci(&mut self, sci: SignedContingentInput) -> Result<KeyImage, Error> {
+        let order = Order::new(sci)?;
+        let key_image = order.key_image();
+        if self.orders.contains_key(&key_image) {
+            return Err(Error::DuplicateOrder);
+        }
+
+        let orders = &self.orders;
+        let pair_orders = self.orders_by_pair.entry(order.pair()).or_default();
+        let position = pair_orders
+            .partition_point(|other| order.cmp_price(&orders[other]) != core::cmp::Ordering::Less);
+        pair_orders.insert(position, key_image);
+
+        self.orders.insert(key_image, order);
+        Ok(key_image)
+    }
+
+    /// Remove an order from the order book.
+    pub fn remove(&mut self, key_image: &KeyImage) -> Option<Order> {
+        let order = self.orders.remove(key_image)?;
+        if let Some(pair_orders) = self.orders_by_pair.get_mut(&order.pair()) {
+            pair_orders.retain(|other| other != key_image);
+            if pair_orders.is_empty() {
+                self.orders_by_pair.remove(&order.pair());
+            }
+        }
+        Some(order)
+    }
+
+    /// Get an order by key image.
+    pub fn get(&self, key_image: &KeyImage) -> Option<&Order> {
+        self.orders.get(key_image)
+    }
+
+    /// The number of orders in the order book.
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// Whether the order book is empty.
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// The orders offering `offered` in exchange for `asked`, best price first.
+    pub fn orders_for_pair(
+        &self,
+        offered: TokenId,
+        asked: TokenId,
+    ) -> impl Iterator<Item = &Order> + '_ {
+        self.orders_by_pair
+            .get(&(offered, asked))
+            .into_iter()
+            .flatten()
+            .map(|key_image| &self.orders[key_image])
+    }
+
+    /// Remove the orders whose key image has appeared in the ledger, since they
+    /// can no longer be filled.
+    ///
+    /// Returns the removed orders.
+    pub fn remove_spent(&mut self, ledger: &impl Ledger) -> Result<Vec<Order>, Error> {
+        let mut spent = Vec::new();
+        for key_image in self.orders.keys() {
+            if ledger.contains_key_image(key_image)? {
+                spent.push(*key_image);
+            }
+        }
+        Ok(spent
+            .iter()
+            .filter_map(|key_image| self.remove(key_image))
+            .collect())
+    }
+
+    /// Remove the orders whose max tombstone block has been reached, since
+    /// they can no longer be filled.
+    ///
+    /// Returns the removed orders.
+    pub fn remove_expired(&mut self, num_blocks: u64) -> Vec<Order> {
+        let expired: Vec<KeyImage> = self
+            .orders
+            .values()
+            .filter(|order| {
+                order.max_tombstone_block() != 0 && order.max_tombstone_block() <= num_blocks
+            })
+            .map(Order::key_image)
+            .collect();
+        expired
+            .iter()
+            .filter_map(|key_image| self.remove(key_image))
+            .collect()
+    }
+
+    /// Find pairs of orders which cross.
+    ///
+    /// Each order appears in at most one match. For each pair of tokens,
+    /// bids are matched best price first, against the best priced ask which
+    /// they cross.
+    pub fn find_matches(&self) -> Vec<Match> {
+        let mut matches = Vec::new();
+        let mut matched = HashSet::new();
+
+        for (&(offered, asked), bids) in self.orders_by_pair.iter() {
+            // Visit each pair of tokens once, from the side offering the lower
+            // token id
+            if offered >= asked {
+                continue;
+            }
+            let asks = match self.orders_by_pair.get(&(asked, offered)) {
+                Some(asks) => asks,
+                None => continue,
+            };
+
+            for bid in bids.iter() {
+                let bid_order = &self.orders[bid];
+                for ask in asks.iter() {
+                    if matched.contains(ask) {
+                        continue;
+                    }
+                    let ask_order = &self.orders[ask];
+                    // Asks are sorted by price, so if the prices do not cross,
+                    // none of the remaining asks do either.
+                    if !bid_order.prices_cross(ask_order) {
+                        break;
+                    }
+                    if bid_order.crosses(ask_order) {
+                        matched.insert(*ask);
+                        matches.push(Match {
+                            bid: *bid,
+                            ask: *ask,
+                        });
+                        break;
+                    }
+                }
+            }
+        }
+
+        matches
+    }
+
+    /// Assemble a swap transaction out of a pair of crossing orders.
+    ///
+    /// The surplus of each token, i.e. what one order offers beyond what the
+    /// other one asks, pays the fee, and the rest is sent to
+    /// `surplus_destination`.
+    ///
+    /// # Arguments
+    /// * `matched` - The pair of orders to combine
+    /// * `ledger` - The ledger, used to detect spent orders and to get the
+    ///   membership proofs of the inputs
+    /// * `block_version` - The block version to build the transaction for
+    /// * `fee` - The fee, whose token must be one of the two tokens swapped
+    /// * `tombstone_block` - The tombstone block, which will be lowered to
+    ///   respect the input rules of the orders
+    /// * `fog_resolver` - Source of validated fog keys for the surplus outputs
+    /// * `surplus_destination` - The addresses receiving the surplus
+    /// * `rng` - Randomness
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_swap_tx<FPR: FogPubkeyResolver, RNG: CryptoRng + RngCore>(
+        &self,
+        matched: &Match,
+        ledger: &impl Ledger,
+        block_version: BlockVersion,
+        fee: Amount,
+        tombstone_block: u64,
+        fog_resolver: FPR,
+        surplus_destination: &ReservedSubaddresses,
+        rng: &mut RNG,
+    ) -> Result<Tx, Error> {
+        let bid = self.get(&matched.bid).ok_or(Error::NotFound)?;
+        let ask = self.get(&matched.ask).ok_or(Error::NotFound)?;
+        if !bid.crosses(ask) {
+            return Err(Error::OrdersDoNotCross);
+        }
+
+        let mut surpluses = [
+            Amount::new(
+                bid.offered().value - ask.asked().value,
+                bid.offered().token_id,
+            ),
+            Amount::new(
+                ask.offered().value - bid.asked().value,
+                ask.offered().token_id,
+            ),
+        ];
+        let fee_surplus = surpluses
+            .iter_mut()
+            .find(|surplus| surplus.token_id == fee.token_id)
+            .ok_or(Error::InsufficientSurplus)?;
+        fee_surplus.value = fee_surplus
+            .value
+            .checked_sub(fee.value)
+            .ok_or(Error::InsufficientSurplus)?;
+
+        let mut tx_builder = TransactionBuilder::new(
+            block_version,
+            fee,
+            fog_resolver,
+            EmptyMemoBuilder::default(),
+        )?;
+
+        for order in [bid, ask] {
+            if ledger.contains_key_image(&order.key_image())? {
+                return Err(Error::OrderSpent);
+            }
+            let mut sci = order.sci().clone();
+            sci.tx_in.proofs =
+                ledger.get_tx_out_proof_of_memberships(&sci.tx_out_global_indices)?;
+            tx_builder.add_presigned_input(sci)?;
+        }
+
+        for surplus in surpluses.iter().filter(|surplus| surplus.value != 0) {
+            tx_builder.add_change_output(*surplus, surplus_destination, rng)?;
+        }
+
+        tx_builder.set_tombstone_block(tombstone_block);
+
+        Ok(tx_builder.build(&NoKeysRingSigner {}, rng)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_account_keys::AccountKey;
+    use mc_fog_report_validation_test_utils::MockFogResolver;
+    use mc_ledger_db::test_utils::MockLedger;
+    use mc_transaction_core::{
+        tx::TxOutMembershipProof,
+        validation::{validate_all_input_rules, validate_signature},
+    };
+    use mc_transaction_std::{test_utils::get_input_credentials, SignedContingentInputBuilder};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // Create an order offering `offered` for `asked`
+    fn create_sci(
+        offered: Amount,
+        asked: Amount,
+        account: &AccountKey,
+        rng: &mut StdRng,
+    ) -> SignedContingentInput {
+        let block_version = BlockVersion::MAX;
+        let fog_resolver = MockFogResolver(Default::default());
+        let input_credentials =
+            get_input_credentials(block_version, offered, account, &fog_resolver, rng);
+
+        let mut builder = SignedContingentInputBuilder::new(
+            block_version,
+            input_credentials,
+            fog_resolver,
+            EmptyMemoBuilder::default(),
+        )
+        .unwrap();
+        builder
+            .add_required_output(asked, &account.default_subaddress(), rng)
+            .unwrap();
+        builder.build(&NoKeysRingSigner {}, rng).unwrap()
+    }
+
+    // A ledger which has membership proofs for the test input credentials
+    fn create_ledger() -> MockLedger {
+        let ledger = MockLedger::default();
+        ledger
+            .lock()
+            .membership_proofs
+            .insert(0, TxOutMembershipProof::default());
+        ledger
+    }
+
+    #[test]
+    fn test_orders_are_sorted_by_price() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let alice = AccountKey::random(&mut rng);
+        let token1 = TokenId::from(1);
+        let token2 = TokenId::from(2);
+
+        let mut order_book = OrderBook::new();
+        let expensive = order_book
+            .add_sci(create_sci(
+                Amount::new(100, token1),
+                Amount::new(300, token2),
+                &alice,
+                &mut rng,
+            ))
+            .unwrap();
+        let cheap = order_book
+            .add_sci(create_sci(
+                Amount::new(100, token1),
+                Amount::new(150, token2),
+                &alice,
+                &mut rng,
+            ))
+            .unwrap();
+        let reverse = order_book
+            .add_sci(create_sci(
+                Amount::new(100, token2),
+                Amount::new(10, token1),
+                &alice,
+                &mut rng,
+            ))
+            .unwrap();
+
+        assert_eq!(order_book.len(), 3);
+        let key_images: Vec<KeyImage> = order_book
+            .orders_for_pair(token1, token2)
+            .map(Order::key_image)
+            .collect();
+        assert_eq!(key_images, vec![cheap, expensive]);
+        let key_images: Vec<KeyImage> = order_book
+            .orders_for_pair(token2, token1)
+            .map(Order::key_image)
+            .collect();
+        assert_eq!(key_images, vec![reverse]);
+
+        // Adding the same order twice fails
+        let sci = order_book.get(&cheap).unwrap().sci().clone();
+        assert!(matches!(
+            order_book.add_sci(sci),
+            Err(Error::DuplicateOrder)
+        ));
+
+        // Removing an order removes it from the index
+        order_book.remove(&cheap).unwrap();
+        let key_images: Vec<KeyImage> = order_book
+            .orders_for_pair(token1, token2)
+            .map(Order::key_image)
+            .collect();
+        assert_eq!(key_images, vec![expensive]);
+    }
+
+    #[test]
+    fn test_match_and_build_swap_tx() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let alice = AccountKey::random(&mut rng);
+        let bob = AccountKey::random(&mut rng);
+        let charlie = AccountKey::random(&mut rng);
+        let block_version = BlockVersion::MAX;
+        let token1 = TokenId::from(1);
+        let token2 = TokenId::from(2);
+        let fee = Amount::new(1_000, token2);
+
+        let mut order_book = OrderBook::new();
+        // Alice offers 1000 of token 1 for 20_000 of token 2
+        let alice_order = order_book
+            .add_sci(create_sci(
+                Amount::new(1_000, token1),
+                Amount::new(20_000, token2),
+                &alice,
+                &mut rng,
+            ))
+            .unwrap();
+        // Bob offers 10_000 of token 2 for 1000 of token 1, which does not cross
+        order_book
+            .add_sci(create_sci(
+                Amount::new(10_000, token2),
+                Amount::new(1_000, token1),
+                &bob,
+                &mut rng,
+            ))
+            .unwrap();
+        assert!(order_book.find_matches().is_empty());
+
+        // Bob offers 25_000 of token 2 for 900 of token 1, which crosses
+        let bob_order = order_book
+            .add_sci(create_sci(
+                Amount::new(25_000, token2),
+                Amount::new(900, token1),
+                &bob,
+                &mut rng,
+            ))
+            .unwrap();
+        let matches = order_book.find_matches();
+        assert_eq!(
+            matches,
+            vec![Match {
+                bid: alice_order,
+                ask: bob_order
+            }]
+        );
+
+        // Charlie assembles the swap, and keeps the surplus after the fee
+        let ledger = create_ledger();
+        let tx = order_book
+            .build_swap_tx(
+                &matches[0],
+                &ledger,
+                block_version,
+                fee,
+                100,
+                MockFogResolver(Default::default()),
+                &ReservedSubaddresses::from(&charlie),
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(tx.prefix.inputs.len(), 2);
+        // Two required outputs, and the surplus of each token
+        assert_eq!(tx.prefix.outputs.len(), 4);
+        validate_all_input_rules(block_version, &tx).unwrap();
+        validate_signature(block_version, &tx, &mut rng).unwrap();
+
+        // Once the key image of an order appears in the ledger, the swap cannot
+        // be built, and the order is removed.
+        ledger.lock().key_images.insert(bob_order, 1);
+        assert!(matches!(
+            order_book.build_swap_tx(
+                &matches[0],
+                &ledger,
+                block_version,
+                fee,
+                100,
+                MockFogResolver(Default::default()),
+                &ReservedSubaddresses::from(&charlie),
+                &mut rng,
+            ),
+            Err(Error::OrderSpent)
+        ));
+        let removed = order_book.remove_spent(&ledger).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].key_image(), bob_order);
+        assert!(order_book.find_matches().is_empty());
+    }
+}
@@ -57,7 +57,7 @@ impl FromStr for BlockVersion {
 impl BlockVersion {
     /// The maximum value of block_version that this build of
     /// mc-transaction-core has support for
-    pub const MAX: Self = Self(3);
+    pub const MAX: Self = Self(4);
 
     /// Refers to the block version number at network launch.
     pub const ZERO: Self = Self(0);
@@ -71,6 +71,9 @@ impl BlockVersion {
     /// Constant for block version three
     pub const THREE: Self = Self(3);
 
+    /// Constant for block version four
+    pub const FOUR: Self = Self(4);
+
     /// Iterator over block versions from one up to max, inclusive. For use in
     /// tests.
     pub fn iterator() -> BlockVersionIterator {
@@ -117,6 +120,12 @@ impl BlockVersion {
     pub fn signed_input_rules_are_supported(&self) -> bool {
         self.0 >= 3
     }
+
+    /// Partial fill, minimum fill and block version input rules, which extend
+    /// the signed input rules of MCIP #31, are introduced in block version 4
+    pub fn extended_input_rules_are_supported(&self) -> bool {
+        self.0 >= 4
+    }
 }
 
 impl Deref for BlockVersion {
@@ -7,14 +7,26 @@
 //! coming from some parties, and some inputs come from others. They give
 //! participants a way to make their signature contingent on certain rules being
 //! followed, to facilitate trustless interactions.
+//!
+//! Besides required outputs and a tombstone limit, the rules may describe a
+//! partial fill: the signer reveals the amounts of some "partial fill outputs"
+//! and of a "partial fill change" output, and the counterparty may fill any
+//! fraction of the offer. The Tx then has to contain "fractional outputs",
+//! which are copies of the revealed outputs with smaller values. The fraction
+//! of the offer that was filled is determined from the fractional change
+//! output, and every other partial fill output must be paid out at least in
+//! proportion to it.
 
 use crate::{
     tx::{Tx, TxOut},
-    BlockVersion,
+    Amount, BlockVersion,
 };
 use alloc::vec::Vec;
+use core::convert::TryFrom;
+use curve25519_dalek::scalar::Scalar;
 use displaydoc::Display;
 use mc_crypto_digestible::Digestible;
+use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
 use prost::Message;
 use serde::{Deserialize, Serialize};
 
@@ -34,11 +46,92 @@ pub struct InputRules {
     /// transaction to be valid
     #[prost(fixed64, tag = "2")]
     pub max_tombstone_block: u64,
+
+    /// Outputs whose amounts are revealed, and which may be paid out
+    /// fractionally, in proportion to how much of the offer is filled
+    #[prost(message, repeated, tag = "3")]
+    pub partial_fill_outputs: Vec<RevealedTxOut>,
+
+    /// The change output returned to the signer, whose amount is revealed.
+    /// The value of the fractional change output in the Tx determines how much
+    /// of the offer was filled. Required if any other partial fill rule is
+    /// present.
+    #[prost(message, optional, tag = "4")]
+    pub partial_fill_change: Option<RevealedTxOut>,
+
+    /// A lower bound on the value which must be filled, i.e. the value of the
+    /// partial fill change output minus the value of the fractional change
+    /// output
+    #[prost(fixed64, tag = "5")]
+    #[digestible(omit_when = 0)]
+    pub min_partial_fill_value: u64,
+
+    /// If nonzero, the block version which the Tx must be evaluated at
+    #[prost(fixed32, tag = "6")]
+    #[digestible(omit_when = 0)]
+    pub block_version: u32,
+}
+
+/// A TxOut together with the shared secret that reveals its amount.
+#[derive(Clone, Digestible, PartialEq, Eq, Message, Serialize, Deserialize)]
+pub struct RevealedTxOut {
+    /// The output
+    #[prost(message, required, tag = "1")]
+    pub tx_out: TxOut,
+
+    /// The shared secret of the output, which unmasks its amount
+    #[prost(message, required, tag = "2")]
+    pub shared_secret: CompressedRistrettoPublic,
+}
+
+impl RevealedTxOut {
+    /// Create a revealed output from a TxOut and its shared secret
+    pub fn new(tx_out: TxOut, shared_secret: &RistrettoPublic) -> Self {
+        Self {
+            tx_out,
+            shared_secret: shared_secret.into(),
+        }
+    }
+
+    /// Reveal the amount of the output
+    pub fn reveal_amount(&self) -> Result<(Amount, Scalar), InputRuleError> {
+        let shared_secret = RistrettoPublic::try_from(&self.shared_secret)
+            .map_err(|_| InputRuleError::InvalidRevealedAmount)?;
+        self.tx_out
+            .masked_amount
+            .get_value(&shared_secret)
+            .map_err(|_| InputRuleError::InvalidRevealedAmount)
+    }
+
+    /// Find the fractional output corresponding to this output in a Tx, and
+    /// reveal its amount. The fractional output has the same public key and
+    /// target key, so the same shared secret unmasks it.
+    fn reveal_fractional_amount(&self, tx: &Tx) -> Result<Amount, InputRuleError> {
+        let (amount, _) = self.reveal_amount()?;
+        let fractional_output = tx
+            .prefix
+            .outputs
+            .iter()
+            .find(|output| {
+                output.public_key == self.tx_out.public_key
+                    && output.target_key == self.tx_out.target_key
+            })
+            .ok_or(InputRuleError::MissingFractionalOutput)?;
+        let fractional = RevealedTxOut {
+            tx_out: fractional_output.clone(),
+            shared_secret: self.shared_secret,
+        };
+        let (fractional_amount, _) = fractional.reveal_amount()?;
+        if fractional_amount.token_id != amount.token_id {
+            return Err(InputRuleError::FractionalOutputTokenIdMismatch);
+        }
+        Ok(fractional_amount)
+    }
 }
 
 impl InputRules {
     /// Verify that a Tx conforms to the rules.
-    pub fn verify(&self, _block_version: BlockVersion, tx: &Tx) -> Result<(), InputRuleError> {
+    pub fn verify(&self, block_version: BlockVersion, tx: &Tx) -> Result<(), InputRuleError> {
         // NOTE: If this function gets too busy, we should split it into several smaller
         // functions NOTE: The tests for this function are in
         // transaction/core/tests/input_rules.rs
@@ -53,6 +146,64 @@ impl InputRules {
                 return Err(InputRuleError::MissingRequiredOutput);
             }
         }
+        if self.has_extended_rules() {
+            if !block_version.extended_input_rules_are_supported() {
+                return Err(InputRuleError::ExtendedRulesNotSupported);
+            }
+            // Verify block_version
+            if self.block_version != 0 && *block_version != self.block_version {
+                return Err(InputRuleError::BlockVersionMismatch);
+            }
+            self.verify_partial_fill(tx)?;
+        }
+        Ok(())
+    }
+
+    /// Whether any of the rules introduced alongside partial fills are present.
+    pub fn has_extended_rules(&self) -> bool {
+        !self.partial_fill_outputs.is_empty()
+            || self.partial_fill_change.is_some()
+            || self.min_partial_fill_value != 0
+            || self.block_version != 0
+    }
+
+    // Verify partial_fill_outputs, partial_fill_change and min_partial_fill_value
+    fn verify_partial_fill(&self, tx: &Tx) -> Result<(), InputRuleError> {
+        let partial_fill_change = match self.partial_fill_change.as_ref() {
+            Some(partial_fill_change) => partial_fill_change,
+            None if self.partial_fill_outputs.is_empty() && self.min_partial_fill_value == 0 => {
+                return Ok(())
+            }
+            None => return Err(InputRuleError::MissingPartialFillChange),
+        };
+
+        // Determine how much of the offer was filled from the fractional change
+        let (change_amount, _) = partial_fill_change.reveal_amount()?;
+        let fractional_change = partial_fill_change.reveal_fractional_amount(tx)?;
+        let fill_value = change_amount
+            .value
+            .checked_sub(fractional_change.value)
+            .ok_or(InputRuleError::FractionalChangeTooLarge)?;
+        if fill_value < self.min_partial_fill_value {
+            return Err(InputRuleError::MinPartialFillValueNotMet);
+        }
+
+        // Each fractional output must be paid at least in proportion to the fill,
+        // rounding in favor of the signer
+        for partial_fill_output in self.partial_fill_outputs.iter() {
+            let (amount, _) = partial_fill_output.reveal_amount()?;
+            let fractional_amount = partial_fill_output.reveal_fractional_amount(tx)?;
+            let min_value = if change_amount.value == 0 {
+                amount.value
+            } else {
+                let numerator = amount.value as u128 * fill_value as u128;
+                let denominator = change_amount.value as u128;
+                ((numerator + denominator - 1) / denominator) as u64
+            };
+            if fractional_amount.value < min_value {
+                return Err(InputRuleError::FractionalOutputTooSmall);
+            }
+        }
         Ok(())
     }
 }
@@ -64,4 +215,23 @@ pub enum InputRuleError {
     MissingRequiredOutput,
     /// The tombstone block exceeds the limit
     MaxTombstoneBlockExceeded,
+    /// The block version does not support partial fill or block version rules
+    ExtendedRulesNotSupported,
+    /// The block version does not match the one required by the rules
+    BlockVersionMismatch,
+    /// Partial fill rules are present without a partial fill change output
+    MissingPartialFillChange,
+    /// The transaction is missing a fractional output
+    MissingFractionalOutput,
+    /// The amount of a revealed output could not be unmasked
+    InvalidRevealedAmount,
+    /// A fractional output has a different token id than its partial fill
+    /// output
+    FractionalOutputTokenIdMismatch,
+    /// The fractional change exceeds the partial fill change
+    FractionalChangeTooLarge,
+    /// The value filled is less than the minimum partial fill value
+    MinPartialFillValueNotMet,
+    /// A fractional output is smaller than required by the fill
+    FractionalOutputTooSmall,
 }
@@ -0,0 +1,199 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Key images of TxOuts, along with a proof that they are correct, for parties
+//! which can see a TxOut but cannot spend it, such as view-only monitors.
+//!
+//! The proof is a RingMLSAG over a ring made of the TxOut alone, whose output
+//! commitment is the TxOut's own commitment. Only the holder of the TxOut's
+//! one-time private key can produce it, and its key image is the one that a
+//! transaction spending the TxOut reveals.
+
+use crate::{
+    domain_separators::KEY_IMAGE_PROOF_DOMAIN_TAG,
+    get_tx_out_shared_secret,
+    onetime_keys::recover_onetime_private_key,
+    ring_signature::{generators, CryptoRngCore, Error as RingSignatureError, KeyImage, RingMLSAG},
+    tx::TxOut,
+    AmountError,
+};
+use alloc::vec::Vec;
+use displaydoc::Display;
+use mc_account_keys::AccountKey;
+use mc_crypto_keys::{CompressedRistrettoPublic, KeyError, RistrettoPublic};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+/// The key image of a TxOut, with a proof that it is correct.
+#[derive(Clone, Deserialize, Eq, Message, PartialEq, Serialize)]
+pub struct TxOutKeyImage {
+    /// The public key of the TxOut.
+    #[prost(message, required, tag = "1")]
+    pub tx_public_key: CompressedRistrettoPublic,
+
+    /// The key image of the TxOut.
+    #[prost(message, required, tag = "2")]
+    pub key_image: KeyImage,
+
+    /// A size-one RingMLSAG over the TxOut, signed with its one-time private
+    /// key.
+    #[prost(message, required, tag = "3")]
+    pub proof: RingMLSAG,
+}
+
+impl TxOutKeyImage {
+    /// Compute the key image of a TxOut sent to the given subaddress of
+    /// `account_key`, and prove it.
+    pub fn new(
+        tx_out: &TxOut,
+        account_key: &AccountKey,
+        subaddress_index: u64,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<Self, KeyImageProofError> {
+        let public_key = RistrettoPublic::try_from(&tx_out.public_key)?;
+        let shared_secret = get_tx_out_shared_secret(account_key.view_private_key(), &public_key);
+        let (amount, blinding) = tx_out.masked_amount.get_value(&shared_secret)?;
+        let onetime_private_key = recover_onetime_private_key(
+            &public_key,
+            account_key.view_private_key(),
+            &account_key.subaddress_spend_private(subaddress_index),
+        );
+
+        let proof = RingMLSAG::sign(
+            &message(&tx_out.public_key),
+            &[tx_out.into()],
+            0,
+            &onetime_private_key,
+            amount.value,
+            &blinding,
+            &blinding,
+            &generators(*amount.token_id),
+            rng,
+        )?;
+
+        Ok(Self {
+            tx_public_key: tx_out.public_key,
+            key_image: proof.key_image,
+            proof,
+        })
+    }
+
+    /// Check that this is the key image of `tx_out`.
+    pub fn verify(&self, tx_out: &TxOut) -> Result<(), KeyImageProofError> {
+        if self.tx_public_key != tx_out.public_key {
+            return Err(KeyImageProofError::TxOutMismatch);
+        }
+        if self.key_image != self.proof.key_image {
+            return Err(KeyImageProofError::KeyImageMismatch);
+        }
+        self.proof.verify(
+            &message(&tx_out.public_key),
+            &[tx_out.into()],
+            &tx_out.masked_amount.commitment,
+        )?;
+        Ok(())
+    }
+}
+
+/// The message signed by the proof of the key image of the TxOut with the
+/// given public key.
+fn message(tx_public_key: &CompressedRistrettoPublic) -> Vec<u8> {
+    let mut message = KEY_IMAGE_PROOF_DOMAIN_TAG.as_bytes().to_vec();
+    message.extend_from_slice(tx_public_key.as_bytes());
+    message
+}
+
+/// An error that occurs when proving or verifying the key image of a TxOut
+#[derive(Debug, Display, Eq, PartialEq)]
+pub enum KeyImageProofError {
+    /// Key: {0}
+    Key(KeyError),
+    /// Amount: {0}
+    Amount(AmountError),
+    /// Ring signature: {0}
+    RingSignature(RingSignatureError),
+    /// The proof is for another TxOut
+    TxOutMismatch,
+    /// The key image does not match the proof
+    KeyImageMismatch,
+}
+
+impl From<KeyError> for KeyImageProofError {
+    fn from(src: KeyError) -> Self {
+        Self::Key(src)
+    }
+}
+
+impl From<AmountError> for KeyImageProofError {
+    fn from(src: AmountError) -> Self {
+        Self::Amount(src)
+    }
+}
+
+impl From<RingSignatureError> for KeyImageProofError {
+    fn from(src: RingSignatureError) -> Self {
+        Self::RingSignature(src)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tokens::Mob, Amount, Token};
+    use mc_util_from_random::FromRandom;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn tx_out(account_key: &AccountKey, subaddress_index: u64, rng: &mut StdRng) -> TxOut {
+        TxOut::new(
+            Amount::new(1000, Mob::ID),
+            &account_key.subaddress(subaddress_index),
+            &mc_crypto_keys::RistrettoPrivate::from_random(rng),
+            Default::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn key_image_proof_roundtrip() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let account_key = AccountKey::random(&mut rng);
+        let tx_out = tx_out(&account_key, 3, &mut rng);
+
+        let key_image = TxOutKeyImage::new(&tx_out, &account_key, 3, &mut rng).unwrap();
+        let onetime_private_key = recover_onetime_private_key(
+            &RistrettoPublic::try_from(&tx_out.public_key).unwrap(),
+            account_key.view_private_key(),
+            &account_key.subaddress_spend_private(3),
+        );
+        assert_eq!(key_image.key_image, KeyImage::from(&onetime_private_key));
+        assert_eq!(key_image.verify(&tx_out), Ok(()));
+
+        let decoded: TxOutKeyImage =
+            mc_util_serial::decode(&mc_util_serial::encode(&key_image)).unwrap();
+        assert_eq!(decoded, key_image);
+    }
+
+    #[test]
+    fn key_image_proof_rejects_wrong_key_image() {
+        let mut rng: StdRng = SeedableRng::from_seed([2u8; 32]);
+        let account_key = AccountKey::random(&mut rng);
+        let tx_out = tx_out(&account_key, 0, &mut rng);
+        let other_tx_out = tx_out(&account_key, 0, &mut rng);
+
+        let mut key_image = TxOutKeyImage::new(&tx_out, &account_key, 0, &mut rng).unwrap();
+        assert_eq!(
+            key_image.verify(&other_tx_out),
+            Err(KeyImageProofError::TxOutMismatch)
+        );
+
+        // A key image that does not match its proof.
+        key_image.key_image = KeyImage::from(7);
+        assert_eq!(
+            key_image.verify(&tx_out),
+            Err(KeyImageProofError::KeyImageMismatch)
+        );
+
+        // A proof made with the wrong subaddress does not verify.
+        let key_image = TxOutKeyImage::new(&tx_out, &account_key, 1, &mut rng);
+        assert!(key_image.map_or(true, |key_image| key_image.verify(&tx_out).is_err()));
+    }
+}
@@ -19,6 +19,7 @@ mod amount;
 mod blockchain;
 mod domain_separators;
 mod input_rules;
+mod key_image_proof;
 mod memo;
 mod signed_contingent_input;
 mod token;
@@ -40,7 +41,8 @@ pub mod proptest_fixtures;
 
 pub use amount::{AmountError, MaskedAmount};
 pub use blockchain::*;
-pub use input_rules::{InputRuleError, InputRules};
+pub use input_rules::{InputRuleError, InputRules, RevealedTxOut};
+pub use key_image_proof::{KeyImageProofError, TxOutKeyImage};
 pub use memo::{EncryptedMemo, MemoError, MemoPayload};
 pub use signed_contingent_input::{
     SignedContingentInput, SignedContingentInputError, UnmaskedAmount,
@@ -125,6 +125,16 @@ impl SignedContingentInput {
                     return Err(SignedContingentInputError::RequiredOutputMismatch);
                 }
             }
+
+            for revealed in rules
+                .partial_fill_outputs
+                .iter()
+                .chain(rules.partial_fill_change.iter())
+            {
+                revealed
+                    .reveal_amount()
+                    .map_err(|_| SignedContingentInputError::InvalidRevealedAmount)?;
+            }
         }
 
         Ok(())
@@ -177,6 +187,8 @@ pub enum SignedContingentInputError {
     WrongNumberOfGlobalIndices,
     /// The amount of a required output was incorrect
     RequiredOutputMismatch,
+    /// The amount of a partial fill output could not be revealed
+    InvalidRevealedAmount,
     /// Input rules are missing
     MissingRules,
     /// Proofs of membership are missing
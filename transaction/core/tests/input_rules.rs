@@ -1,6 +1,14 @@
 mod util;
 
-use mc_transaction_core::{tx::Tx, BlockVersion, InputRules};
+use mc_crypto_keys::{RistrettoPrivate, RistrettoPublic};
+use mc_transaction_core::{
+    encrypted_fog_hint::EncryptedFogHint,
+    onetime_keys::create_shared_secret,
+    tx::{Tx, TxOut},
+    Amount, BlockVersion, InputRuleError, InputRules, MaskedAmount, RevealedTxOut, TokenId,
+};
+use mc_transaction_core_test_utils::AccountKey;
+use mc_util_test_helper::{RngType, SeedableRng};
 
 use util::create_test_tx;
 
@@ -26,6 +34,7 @@ fn test_input_rules_verify_required_outputs() {
     tx.prefix.inputs[0].input_rules = Some(InputRules {
         required_outputs: vec![],
         max_tombstone_block: 0,
+        ..Default::default()
     });
 
     // Check that the Tx is following input rules (vacuously)
@@ -63,6 +72,7 @@ fn test_input_rules_verify_max_tombstone() {
     tx.prefix.inputs[0].input_rules = Some(InputRules {
         required_outputs: vec![],
         max_tombstone_block: 0,
+        ..Default::default()
     });
 
     // Check that the Tx is following input rules (vacuously)
@@ -72,6 +82,7 @@ fn test_input_rules_verify_max_tombstone() {
     tx.prefix.inputs[0].input_rules = Some(InputRules {
         required_outputs: vec![],
         max_tombstone_block: tx.prefix.tombstone_block - 1,
+        ..Default::default()
     });
 
     assert!(get_first_rules(&tx).verify(block_version, &tx).is_err());
@@ -82,3 +93,130 @@ fn test_input_rules_verify_max_tombstone() {
 
     get_first_rules(&tx).verify(block_version, &tx).unwrap();
 }
+
+// Creates an output with a revealed amount, for use in partial fill rules
+fn create_revealed_tx_out(amount: Amount, rng: &mut RngType) -> RevealedTxOut {
+    let recipient = AccountKey::random(rng).default_subaddress();
+    let tx_private_key = RistrettoPrivate::from_random(rng);
+    let tx_out = TxOut::new(
+        amount,
+        &recipient,
+        &tx_private_key,
+        EncryptedFogHint::fake_onetime_hint(rng),
+    )
+    .unwrap();
+    let shared_secret = create_shared_secret(recipient.view_public_key(), &tx_private_key);
+    RevealedTxOut::new(tx_out, &shared_secret)
+}
+
+// Creates the fractional output corresponding to a revealed output
+fn create_fractional_tx_out(revealed: &RevealedTxOut, value: u64) -> TxOut {
+    let (amount, _) = revealed.reveal_amount().unwrap();
+    let shared_secret = RistrettoPublic::try_from(&revealed.shared_secret).unwrap();
+    let mut tx_out = revealed.tx_out.clone();
+    tx_out.masked_amount =
+        MaskedAmount::new(Amount::new(value, amount.token_id), &shared_secret).unwrap();
+    tx_out
+}
+
+// Test that input rules verification is working for partial fill rules
+#[test]
+fn test_input_rules_verify_partial_fill() {
+    let block_version = BlockVersion::FOUR;
+    let mut rng: RngType = SeedableRng::from_seed([2u8; 32]);
+
+    let (mut tx, _ledger) = create_test_tx(block_version);
+
+    // Offer 1000 of token 0 in exchange for 500 of token 1
+    let change = create_revealed_tx_out(Amount::new(1000, TokenId::from(0)), &mut rng);
+    let output = create_revealed_tx_out(Amount::new(500, TokenId::from(1)), &mut rng);
+
+    // Modify the Tx to have partial fill rules.
+    // (This invalidates the signature, but we aren't checking that here)
+    tx.prefix.inputs[0].input_rules = Some(InputRules {
+        partial_fill_outputs: vec![output.clone()],
+        min_partial_fill_value: 100,
+        ..Default::default()
+    });
+
+    // The partial fill change is required
+    assert_eq!(
+        get_first_rules(&tx).verify(block_version, &tx),
+        Err(InputRuleError::MissingPartialFillChange)
+    );
+    get_first_rules_mut(&mut tx).partial_fill_change = Some(change.clone());
+
+    // The fractional outputs are required
+    assert_eq!(
+        get_first_rules(&tx).verify(block_version, &tx),
+        Err(InputRuleError::MissingFractionalOutput)
+    );
+
+    // Fill 40% of the offer: 400 is filled, 600 goes back as change, and at least
+    // 200 must be paid out
+    tx.prefix
+        .outputs
+        .push(create_fractional_tx_out(&change, 600));
+    tx.prefix
+        .outputs
+        .push(create_fractional_tx_out(&output, 199));
+    assert_eq!(
+        get_first_rules(&tx).verify(block_version, &tx),
+        Err(InputRuleError::FractionalOutputTooSmall)
+    );
+
+    *tx.prefix.outputs.last_mut().unwrap() = create_fractional_tx_out(&output, 200);
+    get_first_rules(&tx).verify(block_version, &tx).unwrap();
+
+    // The fill must respect the minimum fill value
+    get_first_rules_mut(&mut tx).min_partial_fill_value = 401;
+    assert_eq!(
+        get_first_rules(&tx).verify(block_version, &tx),
+        Err(InputRuleError::MinPartialFillValueNotMet)
+    );
+    get_first_rules_mut(&mut tx).min_partial_fill_value = 400;
+    get_first_rules(&tx).verify(block_version, &tx).unwrap();
+
+    // The fractional change cannot exceed the offer
+    let change_index = tx.prefix.outputs.len() - 2;
+    tx.prefix.outputs[change_index] = create_fractional_tx_out(&change, 1001);
+    assert_eq!(
+        get_first_rules(&tx).verify(block_version, &tx),
+        Err(InputRuleError::FractionalChangeTooLarge)
+    );
+
+    // Partial fill rules are not supported before block version 4
+    assert_eq!(
+        get_first_rules(&tx).verify(BlockVersion::THREE, &tx),
+        Err(InputRuleError::ExtendedRulesNotSupported)
+    );
+}
+
+// Test that input rules verification is working for block version rules
+#[test]
+fn test_input_rules_verify_block_version() {
+    let block_version = BlockVersion::FOUR;
+
+    let (mut tx, _ledger) = create_test_tx(block_version);
+
+    // Pin the block version.
+    // (This invalidates the signature, but we aren't checking that here)
+    tx.prefix.inputs[0].input_rules = Some(InputRules {
+        block_version: *block_version,
+        ..Default::default()
+    });
+
+    get_first_rules(&tx).verify(block_version, &tx).unwrap();
+
+    get_first_rules_mut(&mut tx).block_version = *block_version + 1;
+    assert_eq!(
+        get_first_rules(&tx).verify(block_version, &tx),
+        Err(InputRuleError::BlockVersionMismatch)
+    );
+
+    // Block version rules are not supported before block version 4
+    assert_eq!(
+        get_first_rules(&tx).verify(BlockVersion::THREE, &tx),
+        Err(InputRuleError::ExtendedRulesNotSupported)
+    );
+}
@@ -16,7 +16,7 @@ use mc_transaction_core::{
     tokens::Mob,
     tx::{TxOutMembershipHash, TxOutMembershipProof},
     validation::*,
-    BlockVersion, InputRules, Token,
+    BlockVersion, InputRuleError, InputRules, Token,
 };
 use mc_transaction_core_test_utils::{InverseTxOutputsOrdering, INITIALIZE_LEDGER_AMOUNT};
 use mc_util_test_helper::get_seeded_rng;
@@ -805,6 +805,7 @@ fn test_input_rules_validation() {
     tx.prefix.inputs[0].input_rules = Some(InputRules {
         required_outputs: vec![first_tx_out],
         max_tombstone_block: 0,
+        ..Default::default()
     });
 
     // Check that the Tx is following input rules (the required output is there)
@@ -830,3 +831,46 @@ fn test_input_rules_validation() {
 
     validate_all_input_rules(block_version, &tx).unwrap();
 }
+
+// Test that partial fill, minimum fill and block version input rules are
+// rejected before block version 4
+#[test]
+fn test_extended_input_rules_validation_requires_block_version() {
+    let (mut tx, _ledger) = create_test_tx(BlockVersion::THREE);
+
+    // Pin the block version.
+    // (This invalidates the signature, but we aren't checking that here)
+    tx.prefix.inputs[0].input_rules = Some(InputRules {
+        block_version: *BlockVersion::THREE,
+        ..Default::default()
+    });
+
+    assert_eq!(
+        validate_all_input_rules(BlockVersion::THREE, &tx),
+        Err(TransactionValidationError::InputRule(
+            InputRuleError::ExtendedRulesNotSupported
+        ))
+    );
+
+    // A minimum fill value alone is also an extended rule
+    tx.prefix.inputs[0].input_rules = Some(InputRules {
+        min_partial_fill_value: 1,
+        ..Default::default()
+    });
+
+    assert_eq!(
+        validate_all_input_rules(BlockVersion::THREE, &tx),
+        Err(TransactionValidationError::InputRule(
+            InputRuleError::ExtendedRulesNotSupported
+        ))
+    );
+
+    // The same rules pass the block version check in block version 4
+    let (mut tx, _ledger) = create_test_tx(BlockVersion::FOUR);
+    tx.prefix.inputs[0].input_rules = Some(InputRules {
+        block_version: *BlockVersion::FOUR,
+        ..Default::default()
+    });
+
+    validate_all_input_rules(BlockVersion::FOUR, &tx).unwrap();
+}
@@ -0,0 +1,322 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+#![deny(missing_docs)]
+
+//! Transaction signer: Sign transactions built with
+//! `TransactionBuilder::build_unsigned` on an offline machine, and assemble the
+//! signed transactions on an online machine.
+//!
+//! All files hold protobuf-encoded `UnsignedTx`, `TxSignature` and `Tx`
+//! messages.
+//!
+//! The signer also computes the key images of TxOuts received by a view-only
+//! mobilecoind monitor, along with their proofs. It reads a
+//! `GetUnverifiedTxOutListResponse` and writes a message whose key images can
+//! be sent as an `ImportKeyImagesRequest`.
+
+use clap::{Parser, Subcommand};
+use mc_account_keys::{AccountKey, CHANGE_SUBADDRESS_INDEX};
+use mc_crypto_ring_signature_signer::LocalRingSigner;
+use mc_transaction_core::{subaddress_matches_tx_out, tx::TxOut, Amount, TxOutKeyImage};
+use mc_transaction_std::{TxSignature, UnsignedTx};
+use mc_util_keyfile::read_keyfile;
+use prost::Message;
+use rand::thread_rng;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+/// Command line configuration
+#[derive(Debug, Parser)]
+pub struct Config {
+    /// The command to run.
+    #[clap(subcommand)]
+    pub command: Commands,
+}
+
+/// The commands of the transaction signer.
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Review and sign an unsigned transaction, after confirming on the
+    /// terminal. Run this on the offline machine.
+    #[clap(arg_required_else_help = true)]
+    Sign {
+        /// The account key file (root entropy or mnemonic JSON) of the account
+        /// which owns the inputs.
+        #[clap(long, parse(from_os_str), env = "MC_KEYFILE")]
+        keyfile: PathBuf,
+
+        /// The unsigned transaction.
+        #[clap(long, parse(from_os_str), env = "MC_UNSIGNED_TX")]
+        unsigned_tx: PathBuf,
+
+        /// Where to write the signature.
+        #[clap(long, parse(from_os_str), env = "MC_SIGNATURE")]
+        out: PathBuf,
+
+        /// Sign without asking for confirmation after the review.
+        #[clap(long)]
+        yes: bool,
+    },
+
+    /// Print the contents of an unsigned transaction.
+    #[clap(arg_required_else_help = true)]
+    Inspect {
+        /// The unsigned transaction.
+        #[clap(long, parse(from_os_str), env = "MC_UNSIGNED_TX")]
+        unsigned_tx: PathBuf,
+    },
+
+    /// Combine an unsigned transaction with its signature into a transaction
+    /// that can be submitted. Run this on the online machine.
+    #[clap(arg_required_else_help = true)]
+    Finalize {
+        /// The unsigned transaction.
+        #[clap(long, parse(from_os_str), env = "MC_UNSIGNED_TX")]
+        unsigned_tx: PathBuf,
+
+        /// The signature produced by the `sign` command.
+        #[clap(long, parse(from_os_str), env = "MC_SIGNATURE")]
+        signature: PathBuf,
+
+        /// Where to write the signed transaction.
+        #[clap(long, parse(from_os_str), env = "MC_TX")]
+        out: PathBuf,
+    },
+
+    /// Compute the key images of TxOuts received by a view-only monitor, with
+    /// proofs that they are correct. Run this on the offline machine.
+    #[clap(arg_required_else_help = true)]
+    KeyImages {
+        /// The account key file (root entropy or mnemonic JSON) of the account
+        /// which owns the TxOuts.
+        #[clap(long, parse(from_os_str), env = "MC_KEYFILE")]
+        keyfile: PathBuf,
+
+        /// The TxOuts, as returned by mobilecoind's GetUnverifiedTxOutList.
+        #[clap(long, parse(from_os_str), env = "MC_UNVERIFIED_TXOS")]
+        unverified_txos: PathBuf,
+
+        /// Where to write the key images, in the format of mobilecoind's
+        /// ImportKeyImagesRequest.
+        #[clap(long, parse(from_os_str), env = "MC_KEY_IMAGES")]
+        out: PathBuf,
+    },
+}
+
+/// A TxOut received by a view-only monitor. Wire compatible with mobilecoind's
+/// `UnverifiedTxOut`, of which only the fields needed here are decoded.
+#[derive(Clone, Message)]
+struct UnverifiedTxOut {
+    #[prost(message, required, tag = "1")]
+    tx_out: TxOut,
+    #[prost(uint64, tag = "2")]
+    subaddress_index: u64,
+}
+
+/// Wire compatible with mobilecoind's `GetUnverifiedTxOutListResponse`.
+#[derive(Clone, Message)]
+struct UnverifiedTxOutList {
+    #[prost(message, repeated, tag = "1")]
+    output_list: Vec<UnverifiedTxOut>,
+}
+
+/// Wire compatible with mobilecoind's `ImportKeyImagesRequest`, without the
+/// monitor id.
+#[derive(Clone, Message)]
+struct KeyImageList {
+    #[prost(message, repeated, tag = "2")]
+    key_images: Vec<TxOutKeyImage>,
+}
+
+fn read_unsigned_tx(path: &Path) -> Result<UnsignedTx, String> {
+    let bytes = fs::read(path).map_err(|err| format!("Failed reading {:?}: {}", path, err))?;
+    mc_util_serial::decode(&bytes).map_err(|err| format!("Failed decoding {:?}: {}", path, err))
+}
+
+fn read_signature(path: &Path) -> Result<TxSignature, String> {
+    let bytes = fs::read(path).map_err(|err| format!("Failed reading {:?}: {}", path, err))?;
+    mc_util_serial::decode(&bytes).map_err(|err| format!("Failed decoding {:?}: {}", path, err))
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    fs::write(path, bytes).map_err(|err| format!("Failed writing {:?}: {}", path, err))
+}
+
+fn format_amount(amount: &Amount) -> String {
+    format!("{} (token id {})", amount.value, amount.token_id)
+}
+
+/// Print what the transaction spends and where it sends it. If
+/// `account_key` is given, outputs to its change subaddress are marked as
+/// change, the others as external, and the total leaving the account is
+/// printed.
+fn print_review(unsigned_tx: &UnsignedTx, account_key: Option<&AccountKey>) {
+    println!("Block version: {}", unsigned_tx.block_version);
+    println!("Tombstone block: {}", unsigned_tx.tx_prefix.tombstone_block);
+    println!("Fee: {}", format_amount(&unsigned_tx.fee()));
+
+    println!("Inputs:");
+    for (ring, amount) in unsigned_tx
+        .rings
+        .iter()
+        .zip(unsigned_tx.input_amounts().iter())
+    {
+        if ring.presigned_mlsag.is_some() {
+            println!("  {} (presigned)", format_amount(amount));
+        } else {
+            println!(
+                "  {} from subaddress {}",
+                format_amount(amount),
+                ring.subaddress_index
+            );
+        }
+    }
+
+    // The fee and the external outputs leave the account, by token id.
+    let fee = unsigned_tx.fee();
+    let mut leaving = BTreeMap::from([(fee.token_id, fee.value as u128)]);
+
+    println!("Outputs:");
+    for (tx_out, amount) in unsigned_tx
+        .tx_prefix
+        .outputs
+        .iter()
+        .zip(unsigned_tx.output_amounts().iter())
+    {
+        let label = match account_key {
+            Some(key) => {
+                if subaddress_matches_tx_out(key, CHANGE_SUBADDRESS_INDEX, tx_out).unwrap_or(false)
+                {
+                    " (change)"
+                } else {
+                    *leaving.entry(amount.token_id).or_default() += amount.value as u128;
+                    " (external)"
+                }
+            }
+            None => "",
+        };
+        println!(
+            "  {} to {}{}",
+            format_amount(amount),
+            tx_out.public_key,
+            label
+        );
+    }
+
+    if account_key.is_some() {
+        println!("Leaving the account, including the fee:");
+        for (token_id, value) in leaving {
+            println!("  {} (token id {})", value, token_id);
+        }
+    }
+}
+
+/// Ask the user to confirm on the terminal. Anything but "y" or "yes" is a
+/// refusal.
+fn confirm(question: &str) -> Result<bool, String> {
+    print!("{} [y/N] ", question);
+    io::stdout()
+        .flush()
+        .map_err(|err| format!("Failed writing to stdout: {}", err))?;
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .map_err(|err| format!("Failed reading from stdin: {}", err))?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn sign(keyfile: &Path, unsigned_tx: &Path, out: &Path, yes: bool) -> Result<(), String> {
+    let account_key =
+        read_keyfile(keyfile).map_err(|err| format!("Failed reading {:?}: {}", keyfile, err))?;
+    let unsigned_tx = read_unsigned_tx(unsigned_tx)?;
+
+    print_review(&unsigned_tx, Some(&account_key));
+    if !yes && !confirm("Sign this transaction?")? {
+        return Err("Not signed".to_string());
+    }
+
+    let signature = unsigned_tx
+        .sign(&LocalRingSigner::from(&account_key), &mut thread_rng())
+        .map_err(|err| format!("Failed signing: {}", err))?;
+    write_file(out, &mc_util_serial::encode(&signature))?;
+    println!("Wrote signature to {:?}", out);
+    Ok(())
+}
+
+fn key_images(keyfile: &Path, unverified_txos: &Path, out: &Path) -> Result<(), String> {
+    let account_key =
+        read_keyfile(keyfile).map_err(|err| format!("Failed reading {:?}: {}", keyfile, err))?;
+    let bytes = fs::read(unverified_txos)
+        .map_err(|err| format!("Failed reading {:?}: {}", unverified_txos, err))?;
+    let list: UnverifiedTxOutList = mc_util_serial::decode(&bytes)
+        .map_err(|err| format!("Failed decoding {:?}: {}", unverified_txos, err))?;
+
+    let key_images = list
+        .output_list
+        .iter()
+        .map(|utxo| {
+            TxOutKeyImage::new(
+                &utxo.tx_out,
+                &account_key,
+                utxo.subaddress_index,
+                &mut thread_rng(),
+            )
+            .map_err(|err| {
+                format!(
+                    "Failed computing the key image of {}: {}",
+                    utxo.tx_out.public_key, err
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    write_file(out, &mc_util_serial::encode(&KeyImageList { key_images }))?;
+    println!("Wrote {} key images to {:?}", list.output_list.len(), out);
+    Ok(())
+}
+
+fn finalize(unsigned_tx: &Path, signature: &Path, out: &Path) -> Result<(), String> {
+    let unsigned_tx = read_unsigned_tx(unsigned_tx)?;
+    let signature = read_signature(signature)?;
+
+    let tx = unsigned_tx
+        .finalize(signature, &mut thread_rng())
+        .map_err(|err| format!("Failed finalizing: {}", err))?;
+    write_file(out, &mc_util_serial::encode(&tx))?;
+    println!("Wrote transaction to {:?}", out);
+    Ok(())
+}
+
+fn main() {
+    let config = Config::parse();
+
+    let result = match config.command {
+        Commands::Sign {
+            keyfile,
+            unsigned_tx,
+            out,
+            yes,
+        } => sign(&keyfile, &unsigned_tx, &out, yes),
+        Commands::Inspect { unsigned_tx } => {
+            read_unsigned_tx(&unsigned_tx).map(|unsigned_tx| print_review(&unsigned_tx, None))
+        }
+        Commands::Finalize {
+            unsigned_tx,
+            signature,
+            out,
+        } => finalize(&unsigned_tx, &signature, &out),
+        Commands::KeyImages {
+            keyfile,
+            unverified_txos,
+            out,
+        } => key_images(&keyfile, &unverified_txos, &out),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        exit(1);
+    }
+}
@@ -4,7 +4,8 @@ use displaydoc::Display;
 use mc_crypto_ring_signature_signer::Error as SignerError;
 use mc_fog_report_validation::FogPubkeyError;
 use mc_transaction_core::{
-    ring_ct::Error as RingCtError, AmountError, NewMemoError, NewTxError, TokenId,
+    ring_ct::Error as RingCtError, validation::TransactionValidationError, AmountError,
+    BlockVersionError, NewMemoError, NewTxError, TokenId,
 };
 
 /// An error that can occur when using the TransactionBuilder
@@ -66,6 +67,9 @@ pub enum TxBuilderError {
 
     /// Signer: {0}
     Signer(SignerError),
+
+    /// Input cannot be signed offline, since it is not owned by a subaddress
+    OfflineSigningNotSupported,
 }
 
 impl From<mc_util_serial::encode::Error> for TxBuilderError {
@@ -136,3 +140,52 @@ impl From<NewMemoError> for SignedContingentInputBuilderError {
         SignedContingentInputBuilderError::Memo(src)
     }
 }
+
+/// An error that can occur when signing or finalizing an UnsignedTx
+#[derive(Debug, Display)]
+pub enum UnsignedTxError {
+    /// Unsupported format version: {0}
+    UnsupportedFormatVersion(u32),
+
+    /// Block version: {0}
+    BlockVersion(BlockVersionError),
+
+    /// Wrong number of rings: {0} rings, {1} inputs
+    WrongNumberOfRings(usize, usize),
+
+    /// Wrong number of output secrets: {0} secrets, {1} outputs
+    WrongNumberOfOutputSecrets(usize, usize),
+
+    /// Ring {0} does not match the corresponding input
+    RingMismatch(usize),
+
+    /// Real input index out of bounds
+    RealInputIndexOutOfBounds,
+
+    /// Ring Signature construction failed: {0}
+    RingSignatureFailed(RingCtError),
+
+    /// The signature is for a different transaction
+    TxPrefixMismatch,
+
+    /// Invalid signature: {0}
+    InvalidSignature(TransactionValidationError),
+}
+
+impl From<BlockVersionError> for UnsignedTxError {
+    fn from(src: BlockVersionError) -> Self {
+        UnsignedTxError::BlockVersion(src)
+    }
+}
+
+impl From<RingCtError> for UnsignedTxError {
+    fn from(src: RingCtError) -> Self {
+        UnsignedTxError::RingSignatureFailed(src)
+    }
+}
+
+impl From<TransactionValidationError> for UnsignedTxError {
+    fn from(src: TransactionValidationError) -> Self {
+        UnsignedTxError::InvalidSignature(src)
+    }
+}
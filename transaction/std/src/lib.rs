@@ -15,11 +15,12 @@ mod memo_builder;
 mod reserved_subaddresses;
 mod signed_contingent_input_builder;
 mod transaction_builder;
+mod unsigned_tx;
 
 #[cfg(any(test, feature = "test-only"))]
 pub mod test_utils;
 
-pub use error::{SignedContingentInputBuilderError, TxBuilderError};
+pub use error::{SignedContingentInputBuilderError, TxBuilderError, UnsignedTxError};
 pub use input_credentials::InputCredentials;
 pub use memo::{
     AuthenticatedSenderMemo, AuthenticatedSenderWithPaymentRequestIdMemo, BurnRedemptionMemo,
@@ -34,6 +35,7 @@ pub use memo_builder::{
 pub use reserved_subaddresses::ReservedSubaddresses;
 pub use signed_contingent_input_builder::SignedContingentInputBuilder;
 pub use transaction_builder::{DefaultTxOutputsOrdering, TransactionBuilder, TxOutputsOrdering};
+pub use unsigned_tx::{TxSignature, UnsignedInputRing, UnsignedTx, UNSIGNED_TX_FORMAT_VERSION};
 
 // Re-export this to help the exported macros work
 pub use mc_transaction_core::MemoPayload;
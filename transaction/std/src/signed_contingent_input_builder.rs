@@ -9,13 +9,14 @@ use crate::{
 };
 use core::cmp::min;
 use mc_account_keys::PublicAddress;
+use mc_crypto_keys::RistrettoPublic;
 use mc_crypto_ring_signature_signer::{RingSigner, SignableInputRing};
 use mc_fog_report_validation::FogPubkeyResolver;
 use mc_transaction_core::{
     ring_ct::OutputSecret,
     ring_signature::Scalar,
     tx::{TxIn, TxOut, TxOutConfirmationNumber},
-    Amount, BlockVersion, InputRules, MemoContext, MemoPayload, NewMemoError,
+    Amount, BlockVersion, InputRules, MemoContext, MemoPayload, NewMemoError, RevealedTxOut,
     SignedContingentInput, TokenId, UnmaskedAmount,
 };
 use rand_core::{CryptoRng, RngCore};
@@ -37,6 +38,14 @@ pub struct SignedContingentInputBuilder<FPR: FogPubkeyResolver> {
     /// The outputs required by the rules for this signed input, and associated
     /// secrets
     required_outputs_and_secrets: Vec<(TxOut, OutputSecret)>,
+    /// The outputs which may be filled partially, with revealed amounts
+    partial_fill_outputs: Vec<RevealedTxOut>,
+    /// The change output for a partial fill, with revealed amount
+    partial_fill_change: Option<RevealedTxOut>,
+    /// The minimum value which must be filled, if partial fills are allowed
+    min_partial_fill_value: u64,
+    /// Whether the input rules require the Tx to use `block_version`
+    pin_block_version: bool,
     /// The tombstone_block value, a block index in which the signed input
     /// expires, and can no longer be used. (This works by implying a limit
     /// on the tombstone block for any transaction which incorporates the signed
@@ -115,6 +124,10 @@ impl<FPR: FogPubkeyResolver> SignedContingentInputBuilder<FPR> {
             block_version,
             input_credentials,
             required_outputs_and_secrets: Vec::new(),
+            partial_fill_outputs: Vec::new(),
+            partial_fill_change: None,
+            min_partial_fill_value: 0,
+            pin_block_version: false,
             tombstone_block: u64::max_value(),
             fog_resolver,
             fog_tombstone_block_limit: u64::max_value(),
@@ -250,14 +263,10 @@ impl<FPR: FogPubkeyResolver> SignedContingentInputBuilder<FPR> {
         memo_fn: impl FnOnce(MemoContext) -> Result<Option<MemoPayload>, NewMemoError>,
         rng: &mut RNG,
     ) -> Result<(TxOut, TxOutConfirmationNumber), TxBuilderError> {
-        let (hint, pubkey_expiry) =
-            crate::transaction_builder::create_fog_hint(fog_hint_address, &self.fog_resolver, rng)?;
-
-        let (tx_out, shared_secret) = crate::transaction_builder::create_output_with_fog_hint(
-            self.block_version,
+        let (tx_out, shared_secret) = self.create_output_with_fog_hint_address(
             amount,
             recipient,
-            hint,
+            fog_hint_address,
             memo_fn,
             rng,
         )?;
@@ -268,8 +277,6 @@ impl<FPR: FogPubkeyResolver> SignedContingentInputBuilder<FPR> {
             .expect("TransactionBuilder created an invalid Amount");
         let output_secret = OutputSecret { amount, blinding };
 
-        self.impose_tombstone_block_limit(pubkey_expiry);
-
         self.required_outputs_and_secrets
             .push((tx_out.clone(), output_secret));
 
@@ -278,6 +285,144 @@ impl<FPR: FogPubkeyResolver> SignedContingentInputBuilder<FPR> {
         Ok((tx_out, confirmation))
     }
 
+    /// Add a non-change partial fill output to the input rules.
+    ///
+    /// The amount of a partial fill output is revealed in the input rules. The
+    /// party using the signed contingent input may fill any fraction of it,
+    /// and must pay out at least the same fraction of `amount` to the
+    /// recipient.
+    ///
+    /// A partial fill change output must also be added, see
+    /// `add_partial_fill_change_output`.
+    ///
+    /// # Arguments
+    /// * `amount` - The amount of this output, when completely filled
+    /// * `recipient` - The recipient's public address
+    /// * `rng` - RNG used to generate blinding for commitment
+    pub fn add_partial_fill_output<RNG: CryptoRng + RngCore>(
+        &mut self,
+        amount: Amount,
+        recipient: &PublicAddress,
+        rng: &mut RNG,
+    ) -> Result<(TxOut, TxOutConfirmationNumber), TxBuilderError> {
+        let mut mb = self
+            .memo_builder
+            .take()
+            .expect("memo builder is missing, this is a logic error");
+        let block_version = self.block_version;
+        let result = self.create_output_with_fog_hint_address(
+            amount,
+            recipient,
+            recipient,
+            |memo_ctxt| {
+                if block_version.e_memo_feature_is_supported() {
+                    Some(mb.make_memo_for_output(amount, recipient, memo_ctxt)).transpose()
+                } else {
+                    Ok(None)
+                }
+            },
+            rng,
+        );
+        // Put the memo builder back
+        self.memo_builder = Some(mb);
+        let (tx_out, shared_secret) = result?;
+
+        self.partial_fill_outputs
+            .push(RevealedTxOut::new(tx_out.clone(), &shared_secret));
+
+        Ok((tx_out, TxOutConfirmationNumber::from(&shared_secret)))
+    }
+
+    /// Set the partial fill change output of the input rules, replacing any
+    /// previous one.
+    ///
+    /// The amount of this output is the value offered by the signed input
+    /// which the signer gets back if nothing is filled. The amount of the
+    /// fractional change output in the Tx determines how much was filled.
+    ///
+    /// # Arguments
+    /// * `amount` - The amount of this change output, when nothing is filled
+    /// * `change_destination` - The primary address and change subaddress of
+    ///   the signer, see `add_required_change_output`.
+    /// * `rng` - RNG used to generate blinding for commitment
+    pub fn add_partial_fill_change_output<RNG: CryptoRng + RngCore>(
+        &mut self,
+        amount: Amount,
+        change_destination: &ReservedSubaddresses,
+        rng: &mut RNG,
+    ) -> Result<(TxOut, TxOutConfirmationNumber), TxBuilderError> {
+        let mut mb = self
+            .memo_builder
+            .take()
+            .expect("memo builder is missing, this is a logic error");
+        let block_version = self.block_version;
+        let result = self.create_output_with_fog_hint_address(
+            amount,
+            &change_destination.change_subaddress,
+            &change_destination.primary_address,
+            |memo_ctxt| {
+                if block_version.e_memo_feature_is_supported() {
+                    Some(mb.make_memo_for_change_output(amount, change_destination, memo_ctxt))
+                        .transpose()
+                } else {
+                    Ok(None)
+                }
+            },
+            rng,
+        );
+        // Put the memo builder back
+        self.memo_builder = Some(mb);
+        let (tx_out, shared_secret) = result?;
+
+        self.partial_fill_change = Some(RevealedTxOut::new(tx_out.clone(), &shared_secret));
+
+        Ok((tx_out, TxOutConfirmationNumber::from(&shared_secret)))
+    }
+
+    /// Sets the minimum value which must be filled, when partial fill outputs
+    /// are present.
+    ///
+    /// # Arguments
+    /// * `min_partial_fill_value` - The minimum value, or zero for no minimum
+    pub fn set_min_partial_fill_value(&mut self, min_partial_fill_value: u64) {
+        self.min_partial_fill_value = min_partial_fill_value;
+    }
+
+    /// Sets whether the input rules require the Tx to be evaluated at the
+    /// block version of this builder.
+    pub fn set_pin_block_version(&mut self, pin_block_version: bool) {
+        self.pin_block_version = pin_block_version;
+    }
+
+    /// Create an output, using `fog_hint_address` to construct the fog hint,
+    /// and impose the fog pubkey expiry on the tombstone block.
+    ///
+    /// Returns the output and its shared secret.
+    fn create_output_with_fog_hint_address<RNG: CryptoRng + RngCore>(
+        &mut self,
+        amount: Amount,
+        recipient: &PublicAddress,
+        fog_hint_address: &PublicAddress,
+        memo_fn: impl FnOnce(MemoContext) -> Result<Option<MemoPayload>, NewMemoError>,
+        rng: &mut RNG,
+    ) -> Result<(TxOut, RistrettoPublic), TxBuilderError> {
+        let (hint, pubkey_expiry) =
+            crate::transaction_builder::create_fog_hint(fog_hint_address, &self.fog_resolver, rng)?;
+
+        let (tx_out, shared_secret) = crate::transaction_builder::create_output_with_fog_hint(
+            self.block_version,
+            amount,
+            recipient,
+            hint,
+            memo_fn,
+            rng,
+        )?;
+
+        self.impose_tombstone_block_limit(pubkey_expiry);
+
+        Ok((tx_out, shared_secret))
+    }
+
     /// Sets the tombstone block, clamping to smallest pubkey expiry value.
     ///
     /// # Arguments
@@ -297,6 +442,9 @@ impl<FPR: FogPubkeyResolver> SignedContingentInputBuilder<FPR> {
     }
 
     /// Consume the builder and return the transaction.
+    ///
+    /// Partial fill, minimum fill and block version rules require block
+    /// version 4 or higher.
     pub fn build<RNG: CryptoRng + RngCore>(
         mut self,
         ring_signer: &impl RingSigner,
@@ -324,6 +472,9 @@ impl<FPR: FogPubkeyResolver> SignedContingentInputBuilder<FPR> {
         let (outputs, output_secrets): (Vec<TxOut>, Vec<_>) =
             self.required_outputs_and_secrets.drain(..).unzip();
 
+        self.partial_fill_outputs
+            .sort_by(|a, b| a.tx_out.public_key.cmp(&b.tx_out.public_key));
+
         let input_rules = InputRules {
             required_outputs: outputs,
             max_tombstone_block: if self.tombstone_block == u64::max_value() {
@@ -331,8 +482,25 @@ impl<FPR: FogPubkeyResolver> SignedContingentInputBuilder<FPR> {
             } else {
                 self.tombstone_block
             },
+            partial_fill_outputs: self.partial_fill_outputs,
+            partial_fill_change: self.partial_fill_change,
+            min_partial_fill_value: self.min_partial_fill_value,
+            block_version: if self.pin_block_version {
+                *self.block_version
+            } else {
+                0
+            },
         };
 
+        if input_rules.has_extended_rules()
+            && !self.block_version.extended_input_rules_are_supported()
+        {
+            return Err(TxBuilderError::BlockVersionTooOld(
+                *self.block_version,
+                *BlockVersion::FOUR,
+            ));
+        }
+
         // Get the tx out indices from the proofs in the input credentials,
         // after sorting has happened
         let tx_out_global_indices: Vec<u64> = self
@@ -519,6 +687,118 @@ pub mod tests {
         }
     }
 
+    #[test]
+    // Test a signed contingent input with partial fill rules
+    fn test_partial_fill_signed_contingent_input() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+
+        for block_version in 4..=*BlockVersion::MAX {
+            let block_version = BlockVersion::try_from(block_version).unwrap();
+
+            let alice = AccountKey::random(&mut rng);
+
+            let fog_resolver = MockFogResolver(Default::default());
+
+            let value = 1475 * MILLIMOB_TO_PICOMOB;
+            let amount = Amount::new(value, Mob::ID);
+            let amount2 = Amount::new(100_000, 2.into());
+
+            let input_credentials =
+                get_input_credentials(block_version, amount, &alice, &fog_resolver, &mut rng);
+
+            let mut builder = SignedContingentInputBuilder::new(
+                block_version,
+                input_credentials,
+                fog_resolver,
+                EmptyMemoBuilder::default(),
+            )
+            .unwrap();
+
+            builder
+                .add_partial_fill_output(amount2, &alice.default_subaddress(), &mut rng)
+                .unwrap();
+            builder
+                .add_partial_fill_change_output(
+                    amount,
+                    &ReservedSubaddresses::from(&alice),
+                    &mut rng,
+                )
+                .unwrap();
+            builder.set_min_partial_fill_value(value / 10);
+            builder.set_pin_block_version(true);
+
+            let sci = builder.build(&NoKeysRingSigner {}, &mut rng).unwrap();
+
+            // The contingent input should have a valid signature.
+            sci.validate().unwrap();
+
+            let rules = sci.tx_in.input_rules.as_ref().unwrap();
+            assert!(rules.required_outputs.is_empty());
+            assert_eq!(rules.min_partial_fill_value, value / 10);
+            assert_eq!(rules.block_version, *block_version);
+
+            // The partial fill amounts should be revealed
+            assert_eq!(rules.partial_fill_outputs.len(), 1);
+            let (revealed, _) = rules.partial_fill_outputs[0].reveal_amount().unwrap();
+            assert_eq!(revealed, amount2);
+
+            let change = rules.partial_fill_change.as_ref().unwrap();
+            let (revealed, _) = change.reveal_amount().unwrap();
+            assert_eq!(revealed, amount);
+
+            // The change should belong to the signer's change subaddress
+            assert!(
+                subaddress_matches_tx_out(&alice, CHANGE_SUBADDRESS_INDEX, &change.tx_out).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    // Test that partial fill and block version rules are refused before block
+    // version 4
+    fn test_partial_fill_signed_contingent_input_requires_block_version() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let block_version = BlockVersion::THREE;
+
+        let alice = AccountKey::random(&mut rng);
+        let fog_resolver = MockFogResolver(Default::default());
+        let amount = Amount::new(1475 * MILLIMOB_TO_PICOMOB, Mob::ID);
+
+        // Partial fill rules
+        let input_credentials =
+            get_input_credentials(block_version, amount, &alice, &fog_resolver, &mut rng);
+        let mut builder = SignedContingentInputBuilder::new(
+            block_version,
+            input_credentials,
+            fog_resolver.clone(),
+            EmptyMemoBuilder::default(),
+        )
+        .unwrap();
+        builder
+            .add_partial_fill_change_output(amount, &ReservedSubaddresses::from(&alice), &mut rng)
+            .unwrap();
+        assert_matches!(
+            builder.build(&NoKeysRingSigner {}, &mut rng),
+            Err(TxBuilderError::BlockVersionTooOld(3, 4))
+        );
+
+        // Block version rules
+        let input_credentials =
+            get_input_credentials(block_version, amount, &alice, &fog_resolver, &mut rng);
+        let mut builder = SignedContingentInputBuilder::new(
+            block_version,
+            input_credentials,
+            fog_resolver,
+            EmptyMemoBuilder::default(),
+        )
+        .unwrap();
+        builder.set_pin_block_version(true);
+        assert_matches!(
+            builder.build(&NoKeysRingSigner {}, &mut rng),
+            Err(TxBuilderError::BlockVersionTooOld(3, 4))
+        );
+    }
+
     #[test]
     // Test a signed contingent input with two fog recipients
     fn test_two_fogs_signed_contingent_input() {
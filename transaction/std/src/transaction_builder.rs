@@ -6,7 +6,7 @@
 
 use crate::{
     input_materials::InputMaterials, InputCredentials, MemoBuilder, ReservedSubaddresses,
-    TxBuilderError,
+    TxBuilderError, UnsignedTx,
 };
 use core::{cmp::min, fmt::Debug};
 use mc_account_keys::PublicAddress;
@@ -446,6 +446,18 @@ impl<FPR: FogPubkeyResolver> TransactionBuilder<FPR> {
         self.build_with_comparer_internal::<RNG, O, S>(ring_signer, rng)
     }
 
+    /// Consume the builder and return the transaction, without signing it.
+    ///
+    /// The resulting `UnsignedTx` can be serialized and signed on another
+    /// machine which holds the spend keys of the inputs, see `UnsignedTx`.
+    /// Inputs must then be given with `OneTimeKeyDeriveData::SubaddressIndex`,
+    /// or be presigned.
+    pub fn build_unsigned(mut self) -> Result<UnsignedTx, TxBuilderError> {
+        let (tx_prefix, input_rings, output_secrets) =
+            self.prepare::<DefaultTxOutputsOrdering>()?;
+        UnsignedTx::new(self.block_version, tx_prefix, input_rings, output_secrets)
+    }
+
     /// Consume the builder and return the transaction with a comparer
     /// (internal usage only).
     fn build_with_comparer_internal<
@@ -457,6 +469,30 @@ impl<FPR: FogPubkeyResolver> TransactionBuilder<FPR> {
         ring_signer: &S,
         rng: &mut RNG,
     ) -> Result<Tx, TxBuilderError> {
+        let (tx_prefix, input_rings, output_secrets) = self.prepare::<O>()?;
+
+        let message = tx_prefix.hash().0;
+        let signature = SignatureRctBulletproofs::sign(
+            self.block_version,
+            &message,
+            &input_rings,
+            &output_secrets,
+            self.fee,
+            ring_signer,
+            rng,
+        )?;
+
+        Ok(Tx {
+            prefix: tx_prefix,
+            signature,
+        })
+    }
+
+    /// Check the inputs and outputs, and sort them into the transaction
+    /// prefix, along with the secrets needed to sign it.
+    fn prepare<O: TxOutputsOrdering>(
+        &mut self,
+    ) -> Result<(TxPrefix, Vec<InputRing>, Vec<OutputSecret>), TxBuilderError> {
         // Note: Origin block has block version zero, so some clients like slam that
         // start with a bootstrapped ledger will target block version 0. However,
         // block version zero has no special rules and so targeting block version 0
@@ -546,25 +582,11 @@ impl<FPR: FogPubkeyResolver> TransactionBuilder<FPR> {
 
         let input_rings = self
             .input_materials
-            .into_iter()
+            .drain(..)
             .map(Into::into)
             .collect::<Vec<InputRing>>();
 
-        let message = tx_prefix.hash().0;
-        let signature = SignatureRctBulletproofs::sign(
-            self.block_version,
-            &message,
-            &input_rings,
-            &output_secrets,
-            self.fee,
-            ring_signer,
-            rng,
-        )?;
-
-        Ok(Tx {
-            prefix: tx_prefix,
-            signature,
-        })
+        Ok((tx_prefix, input_rings, output_secrets))
     }
 }
 
@@ -635,7 +657,7 @@ pub mod transaction_builder_tests {
         test_utils::{create_output, get_input_credentials, get_ring, get_transaction},
         BurnRedemptionMemoBuilder, EmptyMemoBuilder, GiftCodeCancellationMemoBuilder,
         GiftCodeFundingMemoBuilder, GiftCodeSenderMemoBuilder, MemoType, RTHMemoBuilder,
-        SenderMemoCredential,
+        SenderMemoCredential, TxSignature, UnsignedTxError, UNSIGNED_TX_FORMAT_VERSION,
     };
     use assert_matches::assert_matches;
     use maplit::btreemap;
@@ -643,7 +665,9 @@ pub mod transaction_builder_tests {
         burn_address, burn_address_view_private, AccountKey, ShortAddressHash,
         CHANGE_SUBADDRESS_INDEX, DEFAULT_SUBADDRESS_INDEX, GIFT_CODE_SUBADDRESS_INDEX,
     };
-    use mc_crypto_ring_signature_signer::{InputSecret, NoKeysRingSigner, OneTimeKeyDeriveData};
+    use mc_crypto_ring_signature_signer::{
+        InputSecret, LocalRingSigner, NoKeysRingSigner, OneTimeKeyDeriveData,
+    };
     use mc_fog_report_validation_test_utils::{FullyValidatedFogPubkey, MockFogResolver};
     use mc_transaction_core::{
         constants::{MAX_INPUTS, MAX_OUTPUTS, MILLIMOB_TO_PICOMOB},
@@ -3661,4 +3685,218 @@ pub mod transaction_builder_tests {
             );
         }
     }
+
+    // Creates an `InputCredentials` whose one-time private key is derived from
+    // the default subaddress by the signer, as needed for offline signing.
+    fn get_subaddress_input_credentials(
+        block_version: BlockVersion,
+        amount: Amount,
+        account: &AccountKey,
+        rng: &mut StdRng,
+    ) -> InputCredentials {
+        let (ring, real_index) = get_ring(
+            block_version,
+            amount,
+            3,
+            account,
+            &MockFogResolver::default(),
+            rng,
+        );
+        let membership_proofs = ring
+            .iter()
+            .map(|_| TxOutMembershipProof::default())
+            .collect();
+        InputCredentials::new(
+            ring,
+            membership_proofs,
+            real_index,
+            OneTimeKeyDeriveData::SubaddressIndex(DEFAULT_SUBADDRESS_INDEX),
+            *account.view_private_key(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    // Build a transaction without signing it, serialize it, sign it with the
+    // account key, and assemble the signed transaction.
+    fn test_build_unsigned_sign_and_finalize() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+
+        for (block_version, token_id) in get_block_version_token_id_pairs() {
+            let sender = AccountKey::random(&mut rng);
+            let recipient = AccountKey::random(&mut rng);
+            let value = 1475 * MILLIMOB_TO_PICOMOB;
+            let amount = Amount::new(value, token_id);
+
+            let mut transaction_builder = TransactionBuilder::new(
+                block_version,
+                Amount::new(Mob::MINIMUM_FEE, token_id),
+                MockFogResolver::default(),
+                EmptyMemoBuilder::default(),
+            )
+            .unwrap();
+            transaction_builder.set_tombstone_block(2000);
+            transaction_builder.add_input(get_subaddress_input_credentials(
+                block_version,
+                amount,
+                &sender,
+                &mut rng,
+            ));
+            transaction_builder
+                .add_output(
+                    Amount::new(value - Mob::MINIMUM_FEE, token_id),
+                    &recipient.default_subaddress(),
+                    &mut rng,
+                )
+                .unwrap();
+
+            let unsigned_tx = transaction_builder.build_unsigned().unwrap();
+            assert_eq!(unsigned_tx.fee(), Amount::new(Mob::MINIMUM_FEE, token_id));
+            assert_eq!(unsigned_tx.input_amounts(), vec![amount]);
+            assert_eq!(
+                unsigned_tx.output_amounts(),
+                vec![Amount::new(value - Mob::MINIMUM_FEE, token_id)]
+            );
+            assert_eq!(unsigned_tx.tx_prefix.tombstone_block, 2000);
+
+            // The offline machine only sees the serialized bytes.
+            let bytes = mc_util_serial::encode(&unsigned_tx);
+            let offline_unsigned_tx: UnsignedTx = mc_util_serial::decode(&bytes).unwrap();
+            assert_eq!(offline_unsigned_tx, unsigned_tx);
+
+            // Without the account keys, the transaction cannot be signed.
+            assert_matches!(
+                offline_unsigned_tx.sign(&NoKeysRingSigner {}, &mut rng),
+                Err(UnsignedTxError::RingSignatureFailed(_))
+            );
+
+            let signature = offline_unsigned_tx
+                .sign(&LocalRingSigner::from(&sender), &mut rng)
+                .unwrap();
+            let bytes = mc_util_serial::encode(&signature);
+            let signature: TxSignature = mc_util_serial::decode(&bytes).unwrap();
+
+            let tx = unsigned_tx.finalize(signature, &mut rng).unwrap();
+            assert_eq!(tx.prefix.inputs.len(), 1);
+            assert_eq!(tx.prefix.outputs.len(), 1);
+            assert!(subaddress_matches_tx_out(
+                &recipient,
+                DEFAULT_SUBADDRESS_INDEX,
+                &tx.prefix.outputs[0]
+            )
+            .unwrap());
+            assert!(validate_signature(block_version, &tx, &mut rng).is_ok());
+        }
+    }
+
+    #[test]
+    // A signature must not be usable with a different unsigned transaction, and
+    // the rings must match the inputs of the transaction.
+    fn test_unsigned_tx_expected_failures() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let block_version = BlockVersion::MAX;
+        let token_id = Mob::ID;
+        let sender = AccountKey::random(&mut rng);
+        let recipient = AccountKey::random(&mut rng);
+        let value = 1475 * MILLIMOB_TO_PICOMOB;
+
+        let build_unsigned = |rng: &mut StdRng| {
+            let mut transaction_builder = TransactionBuilder::new(
+                block_version,
+                Amount::new(Mob::MINIMUM_FEE, token_id),
+                MockFogResolver::default(),
+                EmptyMemoBuilder::default(),
+            )
+            .unwrap();
+            transaction_builder.add_input(get_subaddress_input_credentials(
+                block_version,
+                Amount::new(value, token_id),
+                &sender,
+                rng,
+            ));
+            transaction_builder
+                .add_output(
+                    Amount::new(value - Mob::MINIMUM_FEE, token_id),
+                    &recipient.default_subaddress(),
+                    rng,
+                )
+                .unwrap();
+            transaction_builder.build_unsigned().unwrap()
+        };
+
+        let unsigned_tx = build_unsigned(&mut rng);
+        let other_unsigned_tx = build_unsigned(&mut rng);
+        let signer = LocalRingSigner::from(&sender);
+
+        // A signature for another transaction is rejected.
+        let other_signature = other_unsigned_tx.sign(&signer, &mut rng).unwrap();
+        assert_matches!(
+            unsigned_tx
+                .clone()
+                .finalize(other_signature.clone(), &mut rng),
+            Err(UnsignedTxError::TxPrefixMismatch)
+        );
+
+        // Rings which do not match the inputs are rejected.
+        let mut bad_unsigned_tx = unsigned_tx.clone();
+        bad_unsigned_tx.rings[0].members = other_unsigned_tx.rings[0].members.clone();
+        assert_matches!(
+            bad_unsigned_tx.sign(&signer, &mut rng),
+            Err(UnsignedTxError::RingMismatch(0))
+        );
+
+        // Unknown format versions are rejected.
+        let mut bad_unsigned_tx = unsigned_tx.clone();
+        bad_unsigned_tx.format_version = UNSIGNED_TX_FORMAT_VERSION + 1;
+        assert_matches!(
+            bad_unsigned_tx.sign(&signer, &mut rng),
+            Err(UnsignedTxError::UnsupportedFormatVersion(_))
+        );
+
+        // A signature that was tampered with is rejected.
+        let mut signature = unsigned_tx.sign(&signer, &mut rng).unwrap();
+        signature.signature = other_signature.signature;
+        assert_matches!(
+            unsigned_tx.finalize(signature, &mut rng),
+            Err(UnsignedTxError::InvalidSignature(_))
+        );
+    }
+
+    #[test]
+    // Inputs which carry a one-time private key cannot be signed offline.
+    fn test_build_unsigned_rejects_onetime_keys() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let block_version = BlockVersion::MAX;
+        let token_id = Mob::ID;
+        let fpr = MockFogResolver::default();
+        let sender = AccountKey::random(&mut rng);
+        let value = 1475 * MILLIMOB_TO_PICOMOB;
+
+        let mut transaction_builder = TransactionBuilder::new(
+            block_version,
+            Amount::new(Mob::MINIMUM_FEE, token_id),
+            fpr.clone(),
+            EmptyMemoBuilder::default(),
+        )
+        .unwrap();
+        transaction_builder.add_input(get_input_credentials(
+            block_version,
+            Amount::new(value, token_id),
+            &sender,
+            &fpr,
+            &mut rng,
+        ));
+        transaction_builder
+            .add_output(
+                Amount::new(value - Mob::MINIMUM_FEE, token_id),
+                &sender.default_subaddress(),
+                &mut rng,
+            )
+            .unwrap();
+
+        assert_matches!(
+            transaction_builder.build_unsigned(),
+            Err(TxBuilderError::OfflineSigningNotSupported)
+        );
+    }
 }
@@ -0,0 +1,317 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A portable format for transactions that were built on one machine and are
+//! signed on another, e.g. an air-gapped machine holding the account keys.
+//!
+//! The online machine builds an `UnsignedTx` with
+//! `TransactionBuilder::build_unsigned`. The offline machine checks it and
+//! signs it with `UnsignedTx::sign`, using e.g. a `LocalRingSigner`, which
+//! derives the one-time private keys of the inputs from their subaddress
+//! indices. The online machine then combines the resulting `TxSignature`
+//! with the `UnsignedTx` using `UnsignedTx::finalize`, which produces a `Tx`
+//! that can be submitted.
+
+use crate::{TxBuilderError, UnsignedTxError};
+use mc_crypto_ring_signature_signer::{
+    InputSecret, OneTimeKeyDeriveData, RingSigner, SignableInputRing,
+};
+use mc_transaction_core::{
+    ring_ct::{InputRing, OutputSecret, PresignedInputRing, SignatureRctBulletproofs},
+    ring_signature::{ReducedTxOut, RingMLSAG},
+    tx::{Tx, TxPrefix},
+    validation::validate_signature,
+    Amount, BlockVersion, TokenId, UnmaskedAmount,
+};
+use prost::Message;
+use rand_core::{CryptoRng, RngCore};
+use std::convert::TryFrom;
+
+/// The version of the `UnsignedTx` and `TxSignature` formats produced by this
+/// crate.
+pub const UNSIGNED_TX_FORMAT_VERSION: u32 = 1;
+
+/// A transaction that has been built but not signed, along with the secrets
+/// needed to sign it, except for the spend keys of the account.
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct UnsignedTx {
+    /// The version of this format, see `UNSIGNED_TX_FORMAT_VERSION`.
+    #[prost(uint32, tag = 1)]
+    pub format_version: u32,
+
+    /// The block version rules the transaction was built for.
+    #[prost(uint32, tag = 2)]
+    pub block_version: u32,
+
+    /// The transaction to sign. This holds the inputs, the outputs and their
+    /// encrypted memos, the fee and the tombstone block.
+    #[prost(message, required, tag = 3)]
+    pub tx_prefix: TxPrefix,
+
+    /// The input rings, in the same order as `tx_prefix.inputs`.
+    #[prost(message, repeated, tag = 4)]
+    pub rings: Vec<UnsignedInputRing>,
+
+    /// The amount and blinding factor of each output, in the same order as
+    /// `tx_prefix.outputs`.
+    #[prost(message, repeated, tag = 5)]
+    pub output_secrets: Vec<UnmaskedAmount>,
+}
+
+/// An input ring of an `UnsignedTx`.
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct UnsignedInputRing {
+    /// The ring members.
+    #[prost(message, repeated, tag = 1)]
+    pub members: Vec<ReducedTxOut>,
+
+    /// The index of the real input among `members`.
+    #[prost(uint64, tag = 2)]
+    pub real_input_index: u64,
+
+    /// The subaddress index of the account which owns the real input.
+    #[prost(uint64, tag = 3)]
+    pub subaddress_index: u64,
+
+    /// The amount and blinding factor of the real input, or of the
+    /// pseudo-output of a presigned input.
+    #[prost(message, required, tag = 4)]
+    pub amount: UnmaskedAmount,
+
+    /// The signature of a presigned input (a signed contingent input), which
+    /// needs no signing. When set, only `amount` is used.
+    #[prost(message, optional, tag = 5)]
+    pub presigned_mlsag: Option<RingMLSAG>,
+}
+
+/// The signature of an `UnsignedTx`, produced by the offline machine.
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct TxSignature {
+    /// The version of this format, see `UNSIGNED_TX_FORMAT_VERSION`.
+    #[prost(uint32, tag = 1)]
+    pub format_version: u32,
+
+    /// The hash of the `TxPrefix` that was signed.
+    #[prost(bytes, tag = 2)]
+    pub tx_prefix_hash: Vec<u8>,
+
+    /// The signature.
+    #[prost(message, required, tag = 3)]
+    pub signature: SignatureRctBulletproofs,
+}
+
+impl UnsignedTx {
+    /// Package a transaction prepared by the `TransactionBuilder`.
+    ///
+    /// Fails if an input can only be signed with a one-time private key,
+    /// since the offline machine derives these from subaddress indices.
+    pub(crate) fn new(
+        block_version: BlockVersion,
+        tx_prefix: TxPrefix,
+        input_rings: Vec<InputRing>,
+        output_secrets: Vec<OutputSecret>,
+    ) -> Result<Self, TxBuilderError> {
+        let rings = input_rings
+            .into_iter()
+            .map(UnsignedInputRing::try_from)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            format_version: UNSIGNED_TX_FORMAT_VERSION,
+            block_version: *block_version,
+            tx_prefix,
+            rings,
+            output_secrets: output_secrets.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    /// The fee paid by the transaction.
+    pub fn fee(&self) -> Amount {
+        Amount::new(
+            self.tx_prefix.fee,
+            TokenId::from(self.tx_prefix.fee_token_id),
+        )
+    }
+
+    /// The amounts of the real inputs.
+    pub fn input_amounts(&self) -> Vec<Amount> {
+        self.rings
+            .iter()
+            .map(|ring| (&ring.amount).into())
+            .collect()
+    }
+
+    /// The amounts of the outputs.
+    pub fn output_amounts(&self) -> Vec<Amount> {
+        self.output_secrets.iter().map(Into::into).collect()
+    }
+
+    /// Sign the transaction.
+    ///
+    /// # Arguments
+    /// * `signer` - Holds the keys of the account which owns the inputs.
+    /// * `rng` - Randomness for the signature.
+    pub fn sign<S: RingSigner + ?Sized, RNG: CryptoRng + RngCore>(
+        &self,
+        signer: &S,
+        rng: &mut RNG,
+    ) -> Result<TxSignature, UnsignedTxError> {
+        let block_version = self.check_version()?;
+
+        if self.rings.len() != self.tx_prefix.inputs.len() {
+            return Err(UnsignedTxError::WrongNumberOfRings(
+                self.rings.len(),
+                self.tx_prefix.inputs.len(),
+            ));
+        }
+        if self.output_secrets.len() != self.tx_prefix.outputs.len() {
+            return Err(UnsignedTxError::WrongNumberOfOutputSecrets(
+                self.output_secrets.len(),
+                self.tx_prefix.outputs.len(),
+            ));
+        }
+
+        // The rings we sign must be the ones the transaction spends from.
+        let input_rings = self
+            .rings
+            .iter()
+            .zip(self.tx_prefix.inputs.iter())
+            .enumerate()
+            .map(|(index, (ring, tx_in))| {
+                if ring.presigned_mlsag.is_none()
+                    && ring.members
+                        != tx_in
+                            .ring
+                            .iter()
+                            .map(ReducedTxOut::from)
+                            .collect::<Vec<_>>()
+                {
+                    return Err(UnsignedTxError::RingMismatch(index));
+                }
+                InputRing::try_from(ring)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let output_secrets = self
+            .output_secrets
+            .iter()
+            .cloned()
+            .map(OutputSecret::from)
+            .collect::<Vec<_>>();
+
+        let message = self.tx_prefix.hash().0;
+        let signature = SignatureRctBulletproofs::sign(
+            block_version,
+            &message,
+            &input_rings,
+            &output_secrets,
+            self.fee(),
+            signer,
+            rng,
+        )?;
+
+        Ok(TxSignature {
+            format_version: UNSIGNED_TX_FORMAT_VERSION,
+            tx_prefix_hash: message.to_vec(),
+            signature,
+        })
+    }
+
+    /// Combine the transaction with its signature, and check that the
+    /// signature is valid.
+    pub fn finalize<RNG: CryptoRng + RngCore>(
+        self,
+        signature: TxSignature,
+        rng: &mut RNG,
+    ) -> Result<Tx, UnsignedTxError> {
+        let block_version = self.check_version()?;
+        if signature.format_version != UNSIGNED_TX_FORMAT_VERSION {
+            return Err(UnsignedTxError::UnsupportedFormatVersion(
+                signature.format_version,
+            ));
+        }
+        if signature.tx_prefix_hash != self.tx_prefix.hash().0 {
+            return Err(UnsignedTxError::TxPrefixMismatch);
+        }
+
+        let tx = Tx {
+            prefix: self.tx_prefix,
+            signature: signature.signature,
+        };
+        validate_signature(block_version, &tx, rng)?;
+        Ok(tx)
+    }
+
+    fn check_version(&self) -> Result<BlockVersion, UnsignedTxError> {
+        if self.format_version != UNSIGNED_TX_FORMAT_VERSION {
+            return Err(UnsignedTxError::UnsupportedFormatVersion(
+                self.format_version,
+            ));
+        }
+        Ok(BlockVersion::try_from(self.block_version)?)
+    }
+}
+
+impl TryFrom<InputRing> for UnsignedInputRing {
+    type Error = TxBuilderError;
+
+    fn try_from(src: InputRing) -> Result<Self, Self::Error> {
+        match src {
+            InputRing::Signable(ring) => {
+                let subaddress_index = match ring.input_secret.onetime_key_derive_data {
+                    OneTimeKeyDeriveData::SubaddressIndex(subaddress_index) => subaddress_index,
+                    OneTimeKeyDeriveData::OneTimeKey(_) => {
+                        return Err(TxBuilderError::OfflineSigningNotSupported)
+                    }
+                };
+                Ok(Self {
+                    members: ring.members,
+                    real_input_index: ring.real_input_index as u64,
+                    subaddress_index,
+                    amount: OutputSecret {
+                        amount: ring.input_secret.amount,
+                        blinding: ring.input_secret.blinding,
+                    }
+                    .into(),
+                    presigned_mlsag: None,
+                })
+            }
+            InputRing::Presigned(ring) => Ok(Self {
+                members: Vec::new(),
+                real_input_index: 0,
+                subaddress_index: 0,
+                amount: ring.pseudo_output_secret.clone().into(),
+                presigned_mlsag: Some(ring.mlsag),
+            }),
+        }
+    }
+}
+
+impl TryFrom<&UnsignedInputRing> for InputRing {
+    type Error = UnsignedTxError;
+
+    fn try_from(src: &UnsignedInputRing) -> Result<Self, Self::Error> {
+        if let Some(mlsag) = &src.presigned_mlsag {
+            return Ok(InputRing::Presigned(PresignedInputRing {
+                mlsag: mlsag.clone(),
+                pseudo_output_secret: src.amount.clone().into(),
+            }));
+        }
+
+        let real_input_index = src.real_input_index as usize;
+        if real_input_index >= src.members.len() {
+            return Err(UnsignedTxError::RealInputIndexOutOfBounds);
+        }
+        let secret = OutputSecret::from(src.amount.clone());
+        Ok(InputRing::Signable(SignableInputRing {
+            members: src.members.clone(),
+            real_input_index,
+            input_secret: InputSecret {
+                onetime_key_derive_data: OneTimeKeyDeriveData::SubaddressIndex(
+                    src.subaddress_index,
+                ),
+                amount: secret.amount,
+                blinding: secret.blinding,
+            },
+        }))
+    }
+}
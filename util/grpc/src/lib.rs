@@ -29,6 +29,7 @@ mod grpcio_extensions;
 mod health_service;
 mod retry_config;
 mod server_cert_reloader;
+mod streaming;
 
 pub use crate::{
     admin_server::AdminServer,
@@ -45,6 +46,10 @@ pub use crate::{
     health_service::{HealthCheckStatus, HealthService, ReadinessIndicator},
     retry_config::GrpcRetryConfig,
     server_cert_reloader::{ServerCertReloader, ServerCertReloaderError},
+    streaming::{
+        spawn_streaming_thread, streaming_channel, streaming_sender, StreamingReceiver,
+        StreamingSendError, StreamingSender,
+    },
 };
 
 use futures::prelude::*;
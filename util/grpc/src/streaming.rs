@@ -0,0 +1,157 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Helpers for serving server streaming calls from outside of the grpcio
+//! event loop.
+//!
+//! Responses are queued into a bounded channel by a `StreamingSender`, and a
+//! future spawned on the grpcio event loop writes them into the sink. An error
+//! status ends the stream, and dropping the sender closes it.
+//!
+//! grpcio only lets us notice that a client went away when writing a response
+//! to it fails. Producers which may stay idle for long should therefore send
+//! something from time to time, or bound how long they run.
+
+use displaydoc::Display;
+use futures::{
+    channel::mpsc::{self, TrySendError},
+    executor::block_on,
+    FutureExt, SinkExt, StreamExt, TryFutureExt,
+};
+use grpcio::{RpcContext, RpcStatus, ServerStreamingSink, WriteFlags};
+use mc_common::logger::{log, Logger};
+use std::thread;
+
+/// An error queueing a response for a client.
+#[derive(Clone, Copy, Debug, Display, Eq, PartialEq)]
+pub enum StreamingSendError {
+    /// The client went away
+    Disconnected,
+
+    /// The client is not keeping up with the responses
+    Full,
+}
+
+impl<T> From<TrySendError<T>> for StreamingSendError {
+    fn from(src: TrySendError<T>) -> Self {
+        if src.is_disconnected() {
+            Self::Disconnected
+        } else {
+            Self::Full
+        }
+    }
+}
+
+/// The receiving end of a `StreamingSender`.
+pub type StreamingReceiver<R> = mpsc::Receiver<Result<R, RpcStatus>>;
+
+/// Queues the responses of a server streaming call.
+pub struct StreamingSender<R> {
+    sender: mpsc::Sender<Result<R, RpcStatus>>,
+}
+
+impl<R> StreamingSender<R> {
+    /// Queue a response, waiting for room in the queue.
+    pub fn send(&mut self, response: R) -> Result<(), StreamingSendError> {
+        block_on(self.sender.send(Ok(response))).map_err(|_| StreamingSendError::Disconnected)
+    }
+
+    /// Queue a response if there is room for it right away.
+    pub fn try_send(&mut self, response: R) -> Result<(), StreamingSendError> {
+        Ok(self.sender.try_send(Ok(response))?)
+    }
+
+    /// Report an error to the client, which ends the stream, waiting for room
+    /// in the queue.
+    pub fn fail(mut self, status: RpcStatus) {
+        let _ = block_on(self.sender.send(Err(status)));
+    }
+
+    /// Report an error to the client, which ends the stream, if there is room
+    /// for it in the queue right away.
+    pub fn try_fail(&mut self, status: RpcStatus) -> Result<(), StreamingSendError> {
+        Ok(self.sender.try_send(Err(status))?)
+    }
+
+    /// Returns true once the responses can no longer reach the client.
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+}
+
+/// Create a queue of at most `buffer` responses, not connected to any sink.
+pub fn streaming_channel<R>(buffer: usize) -> (StreamingSender<R>, StreamingReceiver<R>) {
+    let (sender, receiver) = mpsc::channel(buffer);
+    (StreamingSender { sender }, receiver)
+}
+
+/// Create a sender whose responses are written into `sink`, queueing at most
+/// `buffer` of them.
+pub fn streaming_sender<R: Send + 'static>(
+    ctx: &RpcContext,
+    sink: ServerStreamingSink<R>,
+    buffer: usize,
+    logger: Logger,
+) -> StreamingSender<R> {
+    let (sender, mut receiver) = streaming_channel(buffer);
+
+    let future = async move {
+        let mut sink = sink;
+        while let Some(item) = receiver.next().await {
+            match item {
+                Ok(response) => sink.send((response, WriteFlags::default())).await?,
+                Err(status) => return sink.fail(status).await,
+            }
+        }
+        sink.close().await
+    }
+    .map_err(move |err| log::debug!(logger, "Streaming sink closed: {:?}", err))
+    .map(|_| ());
+
+    ctx.spawn(future);
+    sender
+}
+
+/// Run `producer` on a new thread named `name`, with a sender whose responses
+/// are written into `sink`. The stream is closed once `producer` returns.
+pub fn spawn_streaming_thread<R, P>(
+    ctx: &RpcContext,
+    sink: ServerStreamingSink<R>,
+    name: &str,
+    buffer: usize,
+    producer: P,
+    logger: Logger,
+) where
+    R: Send + 'static,
+    P: FnOnce(StreamingSender<R>) + Send + 'static,
+{
+    let sender = streaming_sender(ctx, sink, buffer, logger);
+    thread::Builder::new()
+        .name(name.to_owned())
+        .spawn(move || producer(sender))
+        .expect("failed starting streaming thread");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grpcio::RpcStatusCode;
+
+    #[test]
+    fn try_send_reports_full_and_disconnected() {
+        let (mut sender, mut receiver) = streaming_channel::<u64>(0);
+
+        // A channel has room for one message per sender beyond its buffer.
+        assert_eq!(sender.try_send(1), Ok(()));
+        assert_eq!(sender.try_send(2), Err(StreamingSendError::Full));
+        assert_eq!(
+            sender.try_fail(RpcStatus::new(RpcStatusCode::INTERNAL)),
+            Err(StreamingSendError::Full)
+        );
+        assert_eq!(block_on(receiver.next()).unwrap().unwrap(), 1);
+
+        drop(receiver);
+        assert!(sender.is_closed());
+        assert_eq!(sender.try_send(3), Err(StreamingSendError::Disconnected));
+        assert_eq!(sender.send(4), Err(StreamingSendError::Disconnected));
+    }
+}
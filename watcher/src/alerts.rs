@@ -0,0 +1,414 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Alerting on suspicious conditions observed by the watcher.
+//!
+//! The following conditions are flagged:
+//! * Blocks signed by unknown signers - a signature that does not match the
+//!   block it was published with, or a signer that could not be tied to an
+//!   attested enclave of the node whose archive it appeared in.
+//! * Nodes whose signatures stop arriving - a node that published too many
+//!   consecutive blocks without a signature, or whose archive fell too far
+//!   behind the other archives.
+//! * Signers whose attestation report changes MRENCLAVE.
+//! * Blocks whose contents differ between archives.
+//!
+//! Alerts are logged, counted in Prometheus metrics and stored in the
+//! [WatcherDB] alert log.
+
+use crate::{error::WatcherDBError, metrics::WatcherMetrics, watcher_db::WatcherDB};
+use mc_common::{
+    logger::{log, Logger},
+    HashMap, HashSet,
+};
+use mc_crypto_keys::Ed25519Public;
+use mc_transaction_core::{Block, BlockData, BlockIndex};
+use mc_util_serial::Message;
+use prost::Enumeration;
+use std::{
+    collections::BTreeMap,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use url::Url;
+
+/// Default number of consecutive blocks without a signature (or blocks behind
+/// the most advanced archive) after which a node is considered to have stopped
+/// signing.
+pub const DEFAULT_MISSING_SIGNATURES_THRESHOLD: u64 = 20;
+
+/// Maximal number of blocks kept in memory for comparing block contents between
+/// archives. Blocks are normally dropped as soon as all archives have synced
+/// them, but a dead archive would otherwise hold them forever.
+const MAX_PENDING_BLOCKS: usize = 10_000;
+
+/// The kind of condition an alert was raised for.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Enumeration)]
+pub enum AlertKind {
+    /// A block was signed by an unknown signer.
+    UnknownSigner = 0,
+
+    /// A node stopped publishing signatures.
+    MissingSignatures = 1,
+
+    /// The MRENCLAVE of a node's attestation report changed.
+    MrEnclaveChanged = 2,
+
+    /// The contents of a block differ between archives.
+    BlockContentsMismatch = 3,
+}
+
+impl AlertKind {
+    /// A short name for this kind of alert, used for metrics.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UnknownSigner => "unknown_signer",
+            Self::MissingSignatures => "missing_signatures",
+            Self::MrEnclaveChanged => "mr_enclave_changed",
+            Self::BlockContentsMismatch => "block_contents_mismatch",
+        }
+    }
+}
+
+/// An alert stored in the alert log.
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct Alert {
+    /// The kind of alert.
+    #[prost(enumeration = "AlertKind", tag = "1")]
+    pub kind: i32,
+
+    /// The block index the alert was raised at.
+    #[prost(uint64, tag = "2")]
+    pub block_index: u64,
+
+    /// The tx source url the alert was raised for.
+    #[prost(string, tag = "3")]
+    pub src_url: String,
+
+    /// The block signer involved, if any.
+    #[prost(message, optional, tag = "4")]
+    pub signer: Option<Ed25519Public>,
+
+    /// Human readable details.
+    #[prost(string, tag = "5")]
+    pub details: String,
+
+    /// The time the alert was raised at, in seconds since the Unix epoch.
+    #[prost(uint64, tag = "6")]
+    pub raised_at: u64,
+}
+
+impl Alert {
+    /// Create a new alert, raised now.
+    pub fn new(
+        kind: AlertKind,
+        block_index: BlockIndex,
+        src_url: &Url,
+        signer: Option<Ed25519Public>,
+        details: impl Into<String>,
+    ) -> Self {
+        let raised_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        Self {
+            kind: kind as i32,
+            block_index,
+            src_url: src_url.to_string(),
+            signer,
+            details: details.into(),
+            raised_at,
+        }
+    }
+
+    /// The kind of alert, if it is a known one.
+    pub fn kind(&self) -> Option<AlertKind> {
+        AlertKind::from_i32(self.kind)
+    }
+}
+
+/// Log an alert, count it and store it in the alert log.
+pub fn raise_alert(
+    watcher_db: &WatcherDB,
+    metrics: &WatcherMetrics,
+    alert: Alert,
+    logger: &Logger,
+) {
+    log::crit!(
+        logger,
+        "Watcher alert {:?} at block {} for {}: {}",
+        alert.kind(),
+        alert.block_index,
+        alert.src_url,
+        alert.details
+    );
+
+    metrics.record_alert(&alert);
+
+    if let Err(err) = watcher_db.add_alert(&alert) {
+        log::error!(logger, "Failed storing alert: {}", err);
+    }
+}
+
+/// Evaluates the blocks synced by the watcher and raises alerts.
+pub struct AlertEvaluator {
+    watcher_db: WatcherDB,
+    metrics: WatcherMetrics,
+    missing_signatures_threshold: u64,
+    state: Mutex<AlertEvaluatorState>,
+    logger: Logger,
+}
+
+#[derive(Default)]
+struct AlertEvaluatorState {
+    /// Number of consecutive blocks synced without a signature, per url.
+    blocks_without_signature: HashMap<Url, u64>,
+
+    /// Urls currently known to be lagging behind the other archives.
+    lagging_urls: HashSet<Url>,
+
+    /// The first block seen at each index, and the url it came from, kept until
+    /// all archives have synced that index.
+    pending_blocks: BTreeMap<BlockIndex, (Url, Block)>,
+}
+
+impl AlertEvaluator {
+    /// Create a new alert evaluator.
+    pub fn new(watcher_db: WatcherDB, logger: Logger) -> Self {
+        Self {
+            watcher_db,
+            metrics: WatcherMetrics::new(),
+            missing_signatures_threshold: DEFAULT_MISSING_SIGNATURES_THRESHOLD,
+            state: Mutex::new(AlertEvaluatorState::default()),
+            logger,
+        }
+    }
+
+    /// Set the number of blocks after which a node is considered to have
+    /// stopped signing.
+    pub fn set_missing_signatures_threshold(&mut self, threshold: u64) {
+        self.missing_signatures_threshold = threshold;
+    }
+
+    /// Evaluate a block fetched from a given url.
+    pub fn check_block_data(&self, src_url: &Url, block_data: &BlockData) {
+        let block = block_data.block();
+        let mut state = self.state.lock().expect("mutex poisoned");
+
+        // Check the signature matches the block.
+        let blocks_without_signature = state
+            .blocks_without_signature
+            .entry(src_url.clone())
+            .or_default();
+        if let Some(signature) = block_data.signature() {
+            *blocks_without_signature = 0;
+
+            if signature.verify(block).is_err() {
+                self.raise(Alert::new(
+                    AlertKind::UnknownSigner,
+                    block.index,
+                    src_url,
+                    Some(*signature.signer()),
+                    "Block signature does not match the block",
+                ));
+            }
+        } else {
+            *blocks_without_signature += 1;
+
+            if *blocks_without_signature == self.missing_signatures_threshold {
+                self.raise(Alert::new(
+                    AlertKind::MissingSignatures,
+                    block.index,
+                    src_url,
+                    None,
+                    format!(
+                        "{} consecutive blocks without a signature",
+                        self.missing_signatures_threshold
+                    ),
+                ));
+            }
+        }
+        self.metrics
+            .set_blocks_without_signature(src_url, *blocks_without_signature);
+
+        // Compare the block against the first one seen at this index.
+        match state.pending_blocks.get(&block.index) {
+            None => {
+                state
+                    .pending_blocks
+                    .insert(block.index, (src_url.clone(), block.clone()));
+                while state.pending_blocks.len() > MAX_PENDING_BLOCKS {
+                    let lowest_block_index = *state.pending_blocks.keys().next().unwrap();
+                    state.pending_blocks.remove(&lowest_block_index);
+                }
+            }
+            Some((first_src_url, first_block)) if first_block != block => {
+                self.raise(Alert::new(
+                    AlertKind::BlockContentsMismatch,
+                    block.index,
+                    src_url,
+                    None,
+                    format!(
+                        "Block {} (contents hash {}) differs from block {} (contents hash {}) at {}",
+                        hex::encode(&block.id),
+                        hex::encode(&block.contents_hash),
+                        hex::encode(&first_block.id),
+                        hex::encode(&first_block.contents_hash),
+                        first_src_url,
+                    ),
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+
+    /// Evaluate the sync progress of all archives, after a round of syncing.
+    pub fn check_sync_progress(&self) -> Result<(), WatcherDBError> {
+        let last_synced = self.watcher_db.last_synced_blocks()?;
+        let highest_synced = last_synced.values().flatten().max().cloned().unwrap_or(0);
+        let lowest_synced = last_synced
+            .values()
+            .map(|block_index| block_index.unwrap_or(0))
+            .min()
+            .unwrap_or(0);
+
+        let mut state = self.state.lock().expect("mutex poisoned");
+
+        // Flag archives that fell too far behind, once.
+        for (src_url, block_index) in last_synced.iter() {
+            let lag = highest_synced - block_index.unwrap_or(0);
+            if lag >= self.missing_signatures_threshold {
+                if state.lagging_urls.insert(src_url.clone()) {
+                    self.raise(Alert::new(
+                        AlertKind::MissingSignatures,
+                        highest_synced,
+                        src_url,
+                        None,
+                        format!("Archive is {} blocks behind", lag),
+                    ));
+                }
+            } else {
+                state.lagging_urls.remove(src_url);
+            }
+        }
+
+        // Blocks all archives have synced no longer need to be compared.
+        state.pending_blocks = state.pending_blocks.split_off(&(lowest_synced + 1));
+
+        Ok(())
+    }
+
+    fn raise(&self, alert: Alert) {
+        raise_alert(&self.watcher_db, &self.metrics, alert, &self.logger);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watcher_db::tests::{setup_blocks, setup_watcher_db};
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_crypto_keys::Ed25519Pair;
+    use mc_transaction_core::BlockSignature;
+    use mc_util_from_random::FromRandom;
+    use rand_core::SeedableRng;
+    use rand_hc::Hc128Rng;
+
+    fn alert_kinds(watcher_db: &WatcherDB) -> Vec<(AlertKind, u64, String)> {
+        watcher_db
+            .get_alerts(0, None)
+            .unwrap()
+            .into_iter()
+            .map(|alert| (alert.kind().unwrap(), alert.block_index, alert.src_url))
+            .collect()
+    }
+
+    #[test_with_logger]
+    fn test_unknown_signer_and_contents_mismatch(logger: Logger) {
+        let mut rng = Hc128Rng::from_seed([8u8; 32]);
+        let url1 = Url::parse("http://www.my_url1.com").unwrap();
+        let url2 = Url::parse("http://www.my_url2.com").unwrap();
+        let watcher_db = setup_watcher_db(&[url1.clone(), url2.clone()], logger.clone());
+        let blocks = setup_blocks();
+        let evaluator = AlertEvaluator::new(watcher_db.clone(), logger);
+
+        let signer = Ed25519Pair::from_random(&mut rng);
+        let (block1, contents1) = blocks[1].clone();
+        let (block2, contents2) = blocks[2].clone();
+        let block2_index = block2.index;
+
+        // A properly signed block from both archives raises nothing.
+        let signature = BlockSignature::from_block_and_keypair(&block1, &signer).unwrap();
+        let block_data = BlockData::new(block1.clone(), contents1.clone(), Some(signature));
+        evaluator.check_block_data(&url1, &block_data);
+        evaluator.check_block_data(&url2, &block_data);
+        assert_eq!(alert_kinds(&watcher_db), vec![]);
+
+        // A signature over a different block is flagged.
+        let signature = BlockSignature::from_block_and_keypair(&block1, &signer).unwrap();
+        let block_data = BlockData::new(block2.clone(), contents2.clone(), Some(signature));
+        evaluator.check_block_data(&url1, &block_data);
+        assert_eq!(
+            alert_kinds(&watcher_db),
+            vec![(AlertKind::UnknownSigner, block2_index, url1.to_string())]
+        );
+
+        // A different block at the same index from another archive is flagged.
+        let mut other_block2 = block2;
+        other_block2.contents_hash = block1.contents_hash.clone();
+        let block_data = BlockData::new(other_block2, contents2, None);
+        evaluator.check_block_data(&url2, &block_data);
+        assert_eq!(
+            alert_kinds(&watcher_db),
+            vec![
+                (AlertKind::UnknownSigner, block2_index, url1.to_string()),
+                (
+                    AlertKind::BlockContentsMismatch,
+                    block2_index,
+                    url2.to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test_with_logger]
+    fn test_missing_signatures(logger: Logger) {
+        let url1 = Url::parse("http://www.my_url1.com").unwrap();
+        let url2 = Url::parse("http://www.my_url2.com").unwrap();
+        let watcher_db = setup_watcher_db(&[url1.clone(), url2.clone()], logger.clone());
+        let blocks = setup_blocks();
+        let mut evaluator = AlertEvaluator::new(watcher_db.clone(), logger);
+        evaluator.set_missing_signatures_threshold(3);
+
+        // Url 1 stops signing, which gets flagged at the third block.
+        let flagged_block_index = blocks[2].0.index;
+        for (block, contents) in blocks.iter().take(5) {
+            let block_data = BlockData::new(block.clone(), contents.clone(), None);
+            evaluator.check_block_data(&url1, &block_data);
+        }
+        assert_eq!(
+            alert_kinds(&watcher_db),
+            vec![(
+                AlertKind::MissingSignatures,
+                flagged_block_index,
+                url1.to_string()
+            )]
+        );
+
+        // Url 2 falls behind url 1.
+        watcher_db.update_last_synced(&url1, 4).unwrap();
+        watcher_db.update_last_synced(&url2, 1).unwrap();
+        evaluator.check_sync_progress().unwrap();
+        evaluator.check_sync_progress().unwrap();
+        assert_eq!(
+            alert_kinds(&watcher_db),
+            vec![
+                (
+                    AlertKind::MissingSignatures,
+                    flagged_block_index,
+                    url1.to_string()
+                ),
+                (AlertKind::MissingSignatures, 4, url2.to_string()),
+            ]
+        );
+    }
+}
@@ -7,6 +7,7 @@
 use displaydoc::Display;
 use mc_watcher::{
     config::WatcherConfig,
+    service::WatcherService,
     verification_reports_collector::VerificationReportsCollector,
     watcher::{SyncResult, Watcher},
     watcher_db::create_or_open_rw_watcher_db,
@@ -39,11 +40,12 @@ fn main() {
         logger.clone(),
     )
     .expect("Could not create or open watcher db");
-    let watcher = Watcher::new(watcher_db.clone(), config.store_block_data, logger.clone())
+    let mut watcher = Watcher::new(watcher_db.clone(), config.store_block_data, logger.clone())
         .expect("Failed creating watcher");
+    watcher.set_missing_signatures_threshold(config.missing_signatures_threshold);
 
     let _verification_reports_collector = <VerificationReportsCollector>::new(
-        watcher_db,
+        watcher_db.clone(),
         sources_config.sources().to_vec(),
         config.poll_interval,
         logger.clone(),
@@ -58,6 +60,7 @@ fn main() {
         Arc::new(move |_| HealthCheckStatus::SERVING);
     let health_service =
         HealthService::new(Some(health_check_callback), logger.clone()).into_service();
+    let watcher_service = WatcherService::new(watcher_db, logger.clone()).into_service();
 
     let env = Arc::new(
         EnvBuilder::new()
@@ -67,6 +70,7 @@ fn main() {
 
     let server_builder = ServerBuilder::new(env)
         .register_service(health_service)
+        .register_service(watcher_service)
         .bind_using_uri(&config.client_listen_uri, logger.clone());
 
     let mut server = server_builder.build().unwrap();
@@ -4,10 +4,12 @@
 //! Configuration parameters for the watcher test utility.
 
 use clap::Parser;
+use mc_crypto_keys::Ed25519Public;
 use mc_util_parse::parse_duration_in_seconds;
+use mc_util_repr_bytes::ReprBytes;
 use mc_util_uri::{ConsensusClientUri, WatcherUri};
 use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf, str::FromStr, time::Duration};
+use std::{convert::TryFrom, fs, path::PathBuf, str::FromStr, time::Duration};
 use url::Url;
 
 #[derive(Clone, Debug, Parser)]
@@ -49,6 +51,11 @@ pub struct WatcherConfig {
         env = "MC_CLIENT_LISTEN_URI"
     )]
     pub client_listen_uri: WatcherUri,
+
+    /// Number of consecutive blocks without a signature (or blocks behind the
+    /// most advanced archive) after which an alert is raised for a node.
+    #[clap(long, default_value = "20", env = "MC_MISSING_SIGNATURES_THRESHOLD")]
+    pub missing_signatures_threshold: u64,
 }
 
 impl WatcherConfig {
@@ -79,6 +86,12 @@ pub struct SourceConfig {
     /// (Optional) Client authentication token secret, for generating
     /// Authorization tokens when connecting to consensus nodes.
     consensus_client_auth_token_secret: Option<String>,
+
+    /// (Optional) Block signers known to belong to this node, for example
+    /// keys it used before being restarted. Blocks they signed are not
+    /// alerted on even if no attestation report was captured for them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    known_block_signers: Vec<KnownBlockSigner>,
 }
 
 impl SourceConfig {
@@ -92,9 +105,16 @@ impl SourceConfig {
             tx_source_url,
             consensus_client_url,
             consensus_client_auth_token_secret,
+            known_block_signers: Vec::new(),
         }
     }
 
+    /// Set the block signers known to belong to this node.
+    pub fn with_known_block_signers(mut self, known_block_signers: Vec<KnownBlockSigner>) -> Self {
+        self.known_block_signers = known_block_signers;
+        self
+    }
+
     /// Get the tx_source_url and ensure it has a trailing slash.
     /// This is compatible with the behavior inside ReqwestTransactionsFetcher
     /// and ensures everywhere we use URLs we always have "slash-terminated"
@@ -120,6 +140,58 @@ impl SourceConfig {
             })
         })
     }
+
+    /// Check whether a block signer is known to belong to this node for all
+    /// the blocks in `first_block..=last_block`.
+    pub fn is_known_block_signer(
+        &self,
+        block_signer: &Ed25519Public,
+        first_block: u64,
+        last_block: u64,
+    ) -> bool {
+        self.known_block_signers.iter().any(|known| {
+            known.public_key() == *block_signer
+                && known.start_block.map_or(true, |start| start <= first_block)
+                && known.end_block.map_or(true, |end| last_block <= end)
+        })
+    }
+}
+
+/// A block signer known to belong to a node, over an (optional) range of
+/// blocks.
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Hash)]
+pub struct KnownBlockSigner {
+    /// Hex encoded public key of the block signer.
+    public_key: String,
+
+    /// (Optional) First block the signer may sign.
+    start_block: Option<u64>,
+
+    /// (Optional) Last block the signer may sign.
+    end_block: Option<u64>,
+}
+
+impl KnownBlockSigner {
+    /// Construct a new KnownBlockSigner object.
+    pub fn new(
+        public_key: &Ed25519Public,
+        start_block: Option<u64>,
+        end_block: Option<u64>,
+    ) -> Self {
+        Self {
+            public_key: hex::encode(public_key.to_bytes()),
+            start_block,
+            end_block,
+        }
+    }
+
+    /// Get the public key of the block signer.
+    pub fn public_key(&self) -> Ed25519Public {
+        let bytes = hex::decode(&self.public_key)
+            .unwrap_or_else(|err| panic!("failed parsing known block signer: {}", err));
+        Ed25519Public::try_from(&bytes[..])
+            .unwrap_or_else(|err| panic!("invalid known block signer: {}", err))
+    }
 }
 
 /// Sources configuration - this configures which sources are being watched.
@@ -147,10 +219,17 @@ impl SourcesConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mc_crypto_keys::Ed25519Pair;
+    use mc_util_from_random::FromRandom;
+    use rand_core::SeedableRng;
+    use rand_hc::Hc128Rng;
     use std::str::FromStr;
 
     #[test]
     fn sources_config_toml() {
+        let mut rng: Hc128Rng = Hc128Rng::from_seed([1u8; 32]);
+        let signer = Ed25519Pair::from_random(&mut rng).public_key();
+
         let expected_config = SourcesConfig {
             sources: vec![
                 SourceConfig::new("https://www.source.com/".to_owned(), None, None),
@@ -161,11 +240,17 @@ mod tests {
                         "1111111111111111111111111111111111111111111111111111111111111111"
                             .to_owned(),
                     ),
-                ),
+                )
+                .with_known_block_signers(vec![KnownBlockSigner::new(
+                    &signer,
+                    None,
+                    Some(100),
+                )]),
             ],
         };
 
-        let input_toml: &str = r#"
+        let input_toml = format!(
+            r#"
             [[sources]]
             tx_source_url = "https://www.source.com/"
 
@@ -173,9 +258,20 @@ mod tests {
             tx_source_url = "https://www.2nd-source.com/"
             consensus_client_url = "mc://www.x.com:443/"
             consensus_client_auth_token_secret = "1111111111111111111111111111111111111111111111111111111111111111"
-        "#;
-        let config: SourcesConfig = toml::from_str(input_toml).expect("failed parsing toml");
+
+            [[sources.known_block_signers]]
+            public_key = "{}"
+            end_block = 100
+        "#,
+            hex::encode(signer.to_bytes())
+        );
+        let config: SourcesConfig = toml::from_str(&input_toml).expect("failed parsing toml");
 
         assert_eq!(config, expected_config);
+
+        let source = &config.sources()[1];
+        assert!(source.is_known_block_signer(&signer, 0, 100));
+        assert!(!source.is_known_block_signer(&signer, 50, 101));
+        assert!(!config.sources()[0].is_known_block_signer(&signer, 0, 0));
     }
 }
@@ -4,10 +4,12 @@
 #![deny(missing_docs)]
 #![forbid(unsafe_code)]
 
+pub mod alerts;
 pub mod block_data_store;
 pub mod config;
 pub mod error;
 pub mod metrics;
+pub mod service;
 pub mod verification_reports_collector;
 pub mod watcher;
 pub mod watcher_db;
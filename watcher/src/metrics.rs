@@ -2,6 +2,7 @@
 
 //! Watcher metrics comparing ledger height and block height
 
+use crate::alerts::Alert;
 use mc_common::HashMap;
 use mc_util_metrics::{IntGauge, OpMetrics};
 use url::Url;
@@ -45,4 +46,20 @@ impl WatcherMetrics {
                 .set(num_blocks.unwrap_or(0) as i64);
         });
     }
+
+    /// Count an alert, per kind and source url
+    pub fn record_alert(&self, alert: &Alert) {
+        let kind = alert.kind().map_or("unknown", |kind| kind.as_str());
+        COLLECTOR
+            .peer_counter(&format!("watcher_alerts_{}", kind), &alert.src_url)
+            .inc();
+    }
+
+    /// Record the number of consecutive blocks synced without a signature for
+    /// a peer
+    pub fn set_blocks_without_signature(&self, url: &Url, num_blocks: u64) {
+        COLLECTOR
+            .peer_gauge("watcher_blocks_without_signature", url.as_str())
+            .set(num_blocks as i64);
+    }
 }
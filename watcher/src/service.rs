@@ -0,0 +1,390 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Watcher gRPC service implementation.
+
+use crate::watcher_db::WatcherDB;
+use grpcio::{RpcContext, RpcStatus, Service, UnarySink};
+use mc_common::logger::Logger;
+use mc_crypto_keys::Ed25519Public;
+use mc_util_grpc::{rpc_database_err, rpc_invalid_arg_error, rpc_logger, send_result};
+use mc_watcher_grpc_api::{
+    empty::Empty,
+    watcher_api::{
+        BlockSignatureData, GetBlockSignaturesRequest, GetBlockSignaturesResponse,
+        GetBlockTimestampRequest, GetBlockTimestampResponse, GetHighestCommonBlockResponse,
+        GetLastSyncedBlocksResponse, GetVerificationReportsRequest, GetVerificationReportsResponse,
+        LastSyncedBlock, SignerVerificationReport,
+    },
+    watcher_api_grpc::{create_watcher_api, WatcherApi},
+};
+use std::convert::TryFrom;
+
+/// Watcher gRPC service implementation, serving queries against the watcher
+/// database.
+#[derive(Clone)]
+pub struct WatcherService {
+    /// Watcher database.
+    watcher_db: WatcherDB,
+
+    /// Logger.
+    logger: Logger,
+}
+
+impl WatcherService {
+    /// Create a new watcher service.
+    pub fn new(watcher_db: WatcherDB, logger: Logger) -> Self {
+        Self { watcher_db, logger }
+    }
+
+    /// Convert into a grpc service
+    pub fn into_service(self) -> Service {
+        create_watcher_api(self)
+    }
+
+    fn get_block_signatures_impl(
+        &self,
+        req: GetBlockSignaturesRequest,
+        logger: &Logger,
+    ) -> Result<GetBlockSignaturesResponse, RpcStatus> {
+        let signatures = self
+            .watcher_db
+            .get_block_signatures(req.get_block_index())
+            .map_err(|err| rpc_database_err(err, logger))?;
+
+        let mut resp = GetBlockSignaturesResponse::new();
+        resp.set_signatures(
+            signatures
+                .iter()
+                .map(|signature_data| {
+                    let mut signature_message = BlockSignatureData::new();
+                    signature_message.set_src_url(signature_data.src_url.clone());
+                    signature_message.set_archive_filename(signature_data.archive_filename.clone());
+                    signature_message.set_block_signature((&signature_data.block_signature).into());
+                    signature_message
+                })
+                .collect(),
+        );
+        Ok(resp)
+    }
+
+    fn get_block_timestamp_impl(
+        &self,
+        req: GetBlockTimestampRequest,
+        logger: &Logger,
+    ) -> Result<GetBlockTimestampResponse, RpcStatus> {
+        let (timestamp, result_code) = self
+            .watcher_db
+            .get_block_timestamp(req.get_block_index())
+            .map_err(|err| rpc_database_err(err, logger))?;
+
+        let mut resp = GetBlockTimestampResponse::new();
+        resp.set_timestamp(timestamp);
+        resp.set_result_code((&result_code).into());
+        Ok(resp)
+    }
+
+    fn get_highest_common_block_impl(
+        &self,
+        logger: &Logger,
+    ) -> Result<GetHighestCommonBlockResponse, RpcStatus> {
+        let block_index = self
+            .watcher_db
+            .highest_common_block()
+            .map_err(|err| rpc_database_err(err, logger))?;
+
+        let mut resp = GetHighestCommonBlockResponse::new();
+        resp.set_block_index(block_index);
+        Ok(resp)
+    }
+
+    fn get_last_synced_blocks_impl(
+        &self,
+        logger: &Logger,
+    ) -> Result<GetLastSyncedBlocksResponse, RpcStatus> {
+        let last_synced = self
+            .watcher_db
+            .last_synced_blocks()
+            .map_err(|err| rpc_database_err(err, logger))?;
+
+        let mut last_synced_blocks = last_synced
+            .into_iter()
+            .map(|(src_url, opt_block_index)| {
+                let mut last_synced_block = LastSyncedBlock::new();
+                last_synced_block.set_src_url(src_url.to_string());
+                if let Some(block_index) = opt_block_index {
+                    last_synced_block.set_has_synced(true);
+                    last_synced_block.set_block_index(block_index);
+                }
+                last_synced_block
+            })
+            .collect::<Vec<_>>();
+        last_synced_blocks.sort_by(|a, b| a.get_src_url().cmp(b.get_src_url()));
+
+        let mut resp = GetLastSyncedBlocksResponse::new();
+        resp.set_last_synced_blocks(last_synced_blocks.into());
+        Ok(resp)
+    }
+
+    fn get_verification_reports_impl(
+        &self,
+        req: GetVerificationReportsRequest,
+        logger: &Logger,
+    ) -> Result<GetVerificationReportsResponse, RpcStatus> {
+        let signer = Ed25519Public::try_from(req.get_signer())
+            .map_err(|err| rpc_invalid_arg_error("signer", err, logger))?;
+
+        let reports_by_url = self
+            .watcher_db
+            .get_verification_reports_for_signer(&signer)
+            .map_err(|err| rpc_database_err(err, logger))?;
+
+        let mut reports = reports_by_url
+            .into_iter()
+            .flat_map(|(src_url, reports)| {
+                reports.into_iter().map(move |opt_report| {
+                    let mut signer_report = SignerVerificationReport::new();
+                    signer_report.set_src_url(src_url.to_string());
+                    if let Some(report) = opt_report {
+                        signer_report.set_report((&report).into());
+                    }
+                    signer_report
+                })
+            })
+            .collect::<Vec<_>>();
+        reports.sort_by(|a, b| a.get_src_url().cmp(b.get_src_url()));
+
+        let mut resp = GetVerificationReportsResponse::new();
+        resp.set_reports(reports.into());
+        Ok(resp)
+    }
+}
+
+impl WatcherApi for WatcherService {
+    fn get_block_signatures(
+        &mut self,
+        ctx: RpcContext,
+        req: GetBlockSignaturesRequest,
+        sink: UnarySink<GetBlockSignaturesResponse>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+        send_result(
+            ctx,
+            sink,
+            self.get_block_signatures_impl(req, &logger),
+            &logger,
+        )
+    }
+
+    fn get_block_timestamp(
+        &mut self,
+        ctx: RpcContext,
+        req: GetBlockTimestampRequest,
+        sink: UnarySink<GetBlockTimestampResponse>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+        send_result(
+            ctx,
+            sink,
+            self.get_block_timestamp_impl(req, &logger),
+            &logger,
+        )
+    }
+
+    fn get_highest_common_block(
+        &mut self,
+        ctx: RpcContext,
+        _req: Empty,
+        sink: UnarySink<GetHighestCommonBlockResponse>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+        send_result(
+            ctx,
+            sink,
+            self.get_highest_common_block_impl(&logger),
+            &logger,
+        )
+    }
+
+    fn get_last_synced_blocks(
+        &mut self,
+        ctx: RpcContext,
+        _req: Empty,
+        sink: UnarySink<GetLastSyncedBlocksResponse>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+        send_result(
+            ctx,
+            sink,
+            self.get_last_synced_blocks_impl(&logger),
+            &logger,
+        )
+    }
+
+    fn get_verification_reports(
+        &mut self,
+        ctx: RpcContext,
+        req: GetVerificationReportsRequest,
+        sink: UnarySink<GetVerificationReportsResponse>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+        send_result(
+            ctx,
+            sink,
+            self.get_verification_reports_impl(req, &logger),
+            &logger,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watcher_db::tests::{setup_blocks, setup_watcher_db};
+    use grpcio::{ChannelBuilder, Environment, Server, ServerBuilder};
+    use mc_attest_core::{VerificationReport, VerificationSignature};
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_crypto_keys::Ed25519Pair;
+    use mc_transaction_core::BlockSignature;
+    use mc_util_from_random::FromRandom;
+    use mc_watcher_grpc_api::{watcher::TimestampResultCode, watcher_api_grpc::WatcherApiClient};
+    use rand_core::SeedableRng;
+    use rand_hc::Hc128Rng;
+    use std::sync::Arc;
+    use url::Url;
+
+    /// Starts the service on localhost and connects a client to it.
+    fn get_client_server(watcher_db: &WatcherDB, logger: &Logger) -> (WatcherApiClient, Server) {
+        let service = WatcherService::new(watcher_db.clone(), logger.clone()).into_service();
+        let env = Arc::new(Environment::new(1));
+        let mut server = ServerBuilder::new(env.clone())
+            .register_service(service)
+            .bind("127.0.0.1", 0)
+            .build()
+            .unwrap();
+        server.start();
+        let (_, port) = server.bind_addrs().next().unwrap();
+        let ch = ChannelBuilder::new(env).connect(&format!("127.0.0.1:{}", port));
+        let client = WatcherApiClient::new(ch);
+        (client, server)
+    }
+
+    #[test_with_logger]
+    fn test_block_signatures_and_sync_progress(logger: Logger) {
+        let mut rng = Hc128Rng::from_seed([8u8; 32]);
+        let url1 = Url::parse("http://www.my_url1.com").unwrap();
+        let url2 = Url::parse("http://www.my_url2.com").unwrap();
+        let watcher_db = setup_watcher_db(&[url1.clone(), url2.clone()], logger.clone());
+        let blocks = setup_blocks();
+        let (client, _server) = get_client_server(&watcher_db, &logger);
+
+        // Nothing has been synced yet.
+        let resp = client.get_last_synced_blocks(&Empty::new()).unwrap();
+        assert_eq!(resp.get_last_synced_blocks().len(), 2);
+        assert!(resp
+            .get_last_synced_blocks()
+            .iter()
+            .all(|last_synced_block| !last_synced_block.get_has_synced()));
+
+        let mut req = GetBlockTimestampRequest::new();
+        req.set_block_index(1);
+        let resp = client.get_block_timestamp(&req).unwrap();
+        assert_eq!(resp.get_result_code(), TimestampResultCode::WatcherBehind);
+
+        // Url 1 signs block 1, url 2 syncs block 1 without a signature.
+        let signer = Ed25519Pair::from_random(&mut rng);
+        let mut signature = BlockSignature::from_block_and_keypair(&blocks[1].0, &signer).unwrap();
+        signature.set_signed_at(1594679718);
+        watcher_db
+            .add_block_signature(&url1, 1, signature.clone(), "00/01".to_owned())
+            .unwrap();
+        watcher_db.update_last_synced(&url2, 1).unwrap();
+
+        let mut req = GetBlockSignaturesRequest::new();
+        req.set_block_index(1);
+        let resp = client.get_block_signatures(&req).unwrap();
+        assert_eq!(resp.get_signatures().len(), 1);
+        let signature_data = &resp.get_signatures()[0];
+        assert_eq!(signature_data.get_src_url(), url1.as_str());
+        assert_eq!(signature_data.get_archive_filename(), "00/01");
+        assert_eq!(
+            BlockSignature::try_from(signature_data.get_block_signature()).unwrap(),
+            signature
+        );
+
+        let mut req = GetBlockTimestampRequest::new();
+        req.set_block_index(1);
+        let resp = client.get_block_timestamp(&req).unwrap();
+        assert_eq!(resp.get_result_code(), TimestampResultCode::TimestampFound);
+        assert_eq!(resp.get_timestamp(), 1594679718);
+
+        let resp = client.get_highest_common_block(&Empty::new()).unwrap();
+        assert_eq!(resp.get_block_index(), 1);
+
+        let resp = client.get_last_synced_blocks(&Empty::new()).unwrap();
+        let last_synced_blocks = resp
+            .get_last_synced_blocks()
+            .iter()
+            .map(|last_synced_block| {
+                (
+                    last_synced_block.get_src_url().to_owned(),
+                    last_synced_block.get_has_synced(),
+                    last_synced_block.get_block_index(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            last_synced_blocks,
+            vec![(url1.to_string(), true, 1), (url2.to_string(), true, 1),]
+        );
+    }
+
+    #[test_with_logger]
+    fn test_get_verification_reports(logger: Logger) {
+        let mut rng = Hc128Rng::from_seed([8u8; 32]);
+        let url1 = Url::parse("http://www.my_url1.com").unwrap();
+        let url2 = Url::parse("http://www.my_url2.com").unwrap();
+        let watcher_db = setup_watcher_db(&[url1.clone(), url2.clone()], logger.clone());
+        let (client, _server) = get_client_server(&watcher_db, &logger);
+
+        let signer1 = Ed25519Pair::from_random(&mut rng);
+        let signer2 = Ed25519Pair::from_random(&mut rng);
+
+        let verification_report = VerificationReport {
+            sig: VerificationSignature::from(vec![1; 32]),
+            chain: vec![vec![2; 16], vec![3; 32]],
+            http_body: "test body".to_owned(),
+        };
+
+        // Url 1 attests to signer 1, while blocks were also signed by signer 2.
+        watcher_db
+            .add_verification_report(
+                &url1,
+                &signer1.public_key(),
+                &verification_report,
+                &[signer2.public_key()],
+            )
+            .unwrap();
+
+        let mut req = GetVerificationReportsRequest::new();
+        req.set_signer((&signer1.public_key()).into());
+        let resp = client.get_verification_reports(&req).unwrap();
+        assert_eq!(resp.get_reports().len(), 1);
+        assert_eq!(resp.get_reports()[0].get_src_url(), url1.as_str());
+        assert_eq!(
+            VerificationReport::from(resp.get_reports()[0].get_report()),
+            verification_report
+        );
+
+        let mut req = GetVerificationReportsRequest::new();
+        req.set_signer((&signer2.public_key()).into());
+        let resp = client.get_verification_reports(&req).unwrap();
+        assert_eq!(resp.get_reports().len(), 1);
+        assert_eq!(resp.get_reports()[0].get_src_url(), url1.as_str());
+        assert!(!resp.get_reports()[0].has_report());
+
+        // Invalid signer keys are rejected.
+        let mut req = GetVerificationReportsRequest::new();
+        req.mut_signer().set_data(vec![1; 3]);
+        assert!(client.get_verification_reports(&req).is_err());
+    }
+}
@@ -2,9 +2,14 @@
 
 //! Worker thread for collecting verification reports from nodes.
 
-use crate::{config::SourceConfig, watcher_db::WatcherDB};
+use crate::{
+    alerts::{raise_alert, Alert, AlertKind},
+    config::SourceConfig,
+    metrics::WatcherMetrics,
+    watcher_db::WatcherDB,
+};
 use grpcio::Environment;
-use mc_attest_core::{VerificationReport, VerificationReportData};
+use mc_attest_core::{MrEnclave, VerificationReport, VerificationReportData};
 use mc_attest_verifier::Verifier;
 use mc_common::{
     logger::{log, Logger},
@@ -33,7 +38,7 @@ use url::Url;
 
 /// A trait that specifies the functionality VerificationReportsCollector needs
 /// in order to go from a ConsensusClientUri into a VerificationReport, and the
-/// associated signer key.
+/// associated signer key and MRENCLAVE.
 pub trait NodeClient {
     /// Get a verification report for a given client.
     fn get_verification_report(
@@ -44,6 +49,9 @@ pub trait NodeClient {
 
     /// Get the block signer key out of a VerificationReport
     fn get_block_signer(verification_report: &VerificationReport) -> Result<Ed25519Public, String>;
+
+    /// Get the MRENCLAVE out of a VerificationReport
+    fn get_mr_enclave(verification_report: &VerificationReport) -> Result<MrEnclave, String>;
 }
 
 /// An implementation of `NodeClient` that talks to a consensus node using
@@ -121,6 +129,19 @@ impl NodeClient for ConsensusNodeClient {
 
         Ok(signer_public_key)
     }
+
+    /// Get the MRENCLAVE out of a VerificationReport
+    fn get_mr_enclave(verification_report: &VerificationReport) -> Result<MrEnclave, String> {
+        let report_data = VerificationReportData::try_from(verification_report)
+            .map_err(|err| format!("Failed constructing VerificationReportData: {}", err))?;
+
+        let report_body = report_data
+            .quote
+            .report_body()
+            .map_err(|err| format!("Failed getting report body: {}", err))?;
+
+        Ok(report_body.mr_enclave())
+    }
 }
 
 /// Periodically checks the verification report poll queue in the database and
@@ -188,6 +209,7 @@ struct VerificationReportsCollectorThread<NC: NodeClient> {
     logger: Logger,
     stop_requested: Arc<AtomicBool>,
     grpcio_env: Arc<Environment>,
+    metrics: WatcherMetrics,
     _nc: PhantomData<NC>,
 }
 
@@ -212,6 +234,7 @@ impl<NC: NodeClient> VerificationReportsCollectorThread<NC> {
             logger,
             stop_requested,
             grpcio_env,
+            metrics: WatcherMetrics::new(),
             _nc: Default::default(),
         }
     }
@@ -296,6 +319,7 @@ impl<NC: NodeClient> VerificationReportsCollectorThread<NC> {
             };
 
             self.process_report(
+                source_config,
                 &node_url,
                 &tx_src_url,
                 &potential_signers,
@@ -306,6 +330,7 @@ impl<NC: NodeClient> VerificationReportsCollectorThread<NC> {
 
     fn process_report(
         &self,
+        source_config: &SourceConfig,
         node_url: &ConsensusClientUri,
         tx_src_url: &Url,
         potential_signers: &[Ed25519Public],
@@ -357,15 +382,144 @@ impl<NC: NodeClient> VerificationReportsCollectorThread<NC> {
                     hex::encode(verification_report_block_signer.to_bytes()),
                     potential_signers.iter().map(|key| hex::encode(key.to_bytes())).collect::<Vec<_>>(),
                 );
+                return;
+            }
+        }
+
+        self.check_report(
+            source_config,
+            node_url,
+            tx_src_url,
+            potential_signers,
+            &verification_report_block_signer,
+            verification_report,
+        );
+    }
+
+    /// Raise alerts for blocks signed by a key other than the one the node
+    /// attested to, and for changes of the node's MRENCLAVE.
+    ///
+    /// A node gets a new signer key every time it restarts, so a signer that
+    /// only signed blocks before the first block signed by the attested signer
+    /// is a key the node rotated away from, and is not alerted on. Neither are
+    /// signers configured as known block signers of the source, over the
+    /// blocks they are configured for.
+    fn check_report(
+        &self,
+        source_config: &SourceConfig,
+        node_url: &ConsensusClientUri,
+        tx_src_url: &Url,
+        potential_signers: &[Ed25519Public],
+        verification_report_block_signer: &Ed25519Public,
+        verification_report: &VerificationReport,
+    ) {
+        let block_index = match self.watcher_db.last_synced_blocks() {
+            Ok(last_synced) => last_synced.get(tx_src_url).cloned().flatten().unwrap_or(0),
+            Err(err) => {
+                log::error!(self.logger, "Failed getting last synced blocks: {}", err);
+                0
+            }
+        };
+
+        let signer_block_range = |signer: &Ed25519Public| {
+            self.watcher_db
+                .get_signer_block_range(signer, tx_src_url)
+                .unwrap_or_else(|err| {
+                    log::error!(self.logger, "Failed getting signer block range: {}", err);
+                    None
+                })
+        };
+        let attested_range = signer_block_range(verification_report_block_signer);
+
+        for signer in potential_signers {
+            if signer == verification_report_block_signer {
+                continue;
+            }
+
+            let (first_block, last_block) =
+                signer_block_range(signer).unwrap_or((block_index, block_index));
+            let rotated = attested_range.map_or(true, |(attested_first_block, _)| {
+                last_block < attested_first_block
+            });
+            if rotated || source_config.is_known_block_signer(signer, first_block, last_block) {
+                log::info!(
+                    self.logger,
+                    "Accepting block signer {} of {} for blocks {}-{}",
+                    hex::encode(signer.to_bytes()),
+                    tx_src_url,
+                    first_block,
+                    last_block
+                );
+                continue;
+            }
+
+            self.raise(Alert::new(
+                AlertKind::UnknownSigner,
+                last_block,
+                tx_src_url,
+                Some(*signer),
+                format!(
+                    "Block signer does not match signer {} attested by {}",
+                    hex::encode(verification_report_block_signer.to_bytes()),
+                    node_url
+                ),
+            ));
+        }
+
+        let mr_enclave = match NC::get_mr_enclave(verification_report) {
+            Ok(mr_enclave) => mr_enclave,
+            Err(err) => {
+                log::error!(
+                    self.logger,
+                    "Failed extracting MRENCLAVE from report by {}: {}",
+                    node_url,
+                    err
+                );
+                return;
+            }
+        };
+        match self
+            .watcher_db
+            .update_mr_enclave(tx_src_url, mr_enclave.as_ref())
+        {
+            Ok(Some(previous_mr_enclave)) if previous_mr_enclave != mr_enclave.as_ref() => {
+                self.raise(Alert::new(
+                    AlertKind::MrEnclaveChanged,
+                    block_index,
+                    tx_src_url,
+                    Some(*verification_report_block_signer),
+                    format!(
+                        "MRENCLAVE of {} changed from {} to {}",
+                        node_url,
+                        hex::encode(&previous_mr_enclave),
+                        hex::encode(mr_enclave.as_ref())
+                    ),
+                ));
+            }
+            Ok(_) => {}
+            Err(err) => {
+                log::error!(
+                    self.logger,
+                    "Failed writing MRENCLAVE to database: {} (src_url:{})",
+                    err,
+                    tx_src_url
+                );
             }
         }
     }
+
+    fn raise(&self, alert: Alert) {
+        raise_alert(&self.watcher_db, &self.metrics, alert, &self.logger);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::watcher_db::tests::{setup_blocks, setup_watcher_db};
+    use crate::{
+        config::KnownBlockSigner,
+        watcher_db::tests::{setup_blocks, setup_watcher_db},
+    };
     use mc_attest_core::VerificationSignature;
     use mc_common::logger::{test_with_logger, Logger};
     use mc_crypto_digestible::{Digestible, MerlinTranscript};
@@ -431,6 +585,12 @@ mod tests {
         ) -> Result<Ed25519Public, String> {
             Ok(Self::report_signer(verification_report).public_key())
         }
+
+        fn get_mr_enclave(verification_report: &VerificationReport) -> Result<MrEnclave, String> {
+            // The report version doubles as the enclave version.
+            let report_version = verification_report.chain[0][0];
+            Ok(MrEnclave::from([report_version; 32]))
+        }
     }
 
     #[test_with_logger]
@@ -634,7 +794,7 @@ mod tests {
 
         let mut tries = 30;
         let expected_reports_signer2 = HashMap::from_iter(vec![
-            (tx_src_url1, vec![None]),
+            (tx_src_url1.clone(), vec![None]),
             (
                 tx_src_url2,
                 vec![Some(TestNodeClient::current_expected_report(&node2_url))],
@@ -665,5 +825,118 @@ mod tests {
             tries -= 1;
             sleep(Duration::from_millis(100));
         }
+
+        // The block signed by signer2 at node 1, and the change of node 1's report
+        // version, should have been flagged.
+        let alerts = watcher_db
+            .get_alerts(0, None)
+            .unwrap()
+            .into_iter()
+            .map(|alert| (alert.kind().unwrap(), alert.src_url, alert.signer))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            alerts,
+            vec![
+                (
+                    AlertKind::UnknownSigner,
+                    tx_src_url1.to_string(),
+                    Some(signer2.public_key())
+                ),
+                (
+                    AlertKind::MrEnclaveChanged,
+                    tx_src_url1.to_string(),
+                    Some(updated_signer1.public_key())
+                ),
+            ]
+        );
+    }
+
+    #[test_with_logger]
+    #[serial]
+    fn test_rotated_and_known_signers_are_accepted(logger: Logger) {
+        TestNodeClient::reset();
+
+        let tx_src_url1 = Url::parse("http://www.my_url1.com").unwrap();
+        let tx_src_url2 = Url::parse("http://www.my_url2.com").unwrap();
+        let watcher_db =
+            setup_watcher_db(&[tx_src_url1.clone(), tx_src_url2.clone()], logger.clone());
+        let blocks = setup_blocks();
+        let filename = String::from("00/00");
+
+        let node1_url = ConsensusClientUri::from_str("mc://node1.test.com:443/").unwrap();
+        let node2_url = ConsensusClientUri::from_str("mc://node2.test.com:443/").unwrap();
+
+        // Node 1 signed blocks 1 and 2 with a key it rotated away from before its
+        // report could be fetched.
+        let rotated_signer = TestNodeClient::current_signer(&node1_url);
+        {
+            let mut report_version_map = REPORT_VERSION.lock().unwrap();
+            report_version_map.insert(node1_url.clone(), 2);
+        }
+        let signer1 = TestNodeClient::current_signer(&node1_url);
+
+        // Node 2 is configured with a known signer that signs after its attested
+        // signer.
+        let signer2 = TestNodeClient::current_signer(&node2_url);
+        let known_signer = Ed25519Pair::from(Ed25519Private::try_from(&[7u8; 32][..]).unwrap());
+
+        let sources = vec![
+            SourceConfig::new(tx_src_url1.to_string(), Some(node1_url), None),
+            SourceConfig::new(tx_src_url2.to_string(), Some(node2_url), None)
+                .with_known_block_signers(vec![KnownBlockSigner::new(
+                    &known_signer.public_key(),
+                    Some(5),
+                    None,
+                )]),
+        ];
+
+        let _verification_reports_collector = VerificationReportsCollector::<TestNodeClient>::new(
+            watcher_db.clone(),
+            sources,
+            Duration::from_millis(100),
+            logger,
+        );
+
+        for (tx_src_url, block_index, signer) in [
+            (&tx_src_url1, 1, &rotated_signer),
+            (&tx_src_url1, 2, &rotated_signer),
+            (&tx_src_url1, 3, &signer1),
+            (&tx_src_url2, 4, &signer2),
+            (&tx_src_url2, 5, &known_signer),
+        ] {
+            let signature =
+                BlockSignature::from_block_and_keypair(&blocks[block_index].0, signer).unwrap();
+            watcher_db
+                .add_block_signature(tx_src_url, block_index as u64, signature, filename.clone())
+                .unwrap();
+        }
+
+        let mut tries = 30;
+        loop {
+            let polled = [
+                (&rotated_signer, &tx_src_url1),
+                (&signer1, &tx_src_url1),
+                (&signer2, &tx_src_url2),
+                (&known_signer, &tx_src_url2),
+            ]
+            .iter()
+            .all(|(signer, tx_src_url)| {
+                !watcher_db
+                    .get_verification_report_for_signer_and_url(&signer.public_key(), tx_src_url)
+                    .unwrap()
+                    .is_empty()
+            });
+            if polled {
+                break;
+            }
+
+            if tries == 0 {
+                panic!("reports not synced");
+            }
+            tries -= 1;
+            sleep(Duration::from_millis(100));
+        }
+
+        assert_eq!(watcher_db.get_alerts(0, None).unwrap(), vec![]);
     }
 }
@@ -3,6 +3,7 @@
 //! Basic Watcher Node
 
 use crate::{
+    alerts::AlertEvaluator,
     error::{WatcherDBError, WatcherError},
     metrics::WatcherMetrics,
     watcher_db::WatcherDB,
@@ -41,6 +42,7 @@ pub struct Watcher {
     store_block_data: bool,
     logger: Logger,
     metrics: WatcherMetrics,
+    alert_evaluator: AlertEvaluator,
 }
 
 /// Result of sync loop
@@ -88,6 +90,7 @@ impl Watcher {
         );
 
         let metrics = WatcherMetrics::new();
+        let alert_evaluator = AlertEvaluator::new(watcher_db.clone(), logger.clone());
 
         Ok(Self {
             transactions_fetcher_by_url,
@@ -95,9 +98,18 @@ impl Watcher {
             store_block_data,
             logger,
             metrics,
+            alert_evaluator,
         })
     }
 
+    /// Set the number of consecutive blocks without a signature (or blocks
+    /// behind the most advanced archive) after which a node is considered to
+    /// have stopped signing.
+    pub fn set_missing_signatures_threshold(&mut self, threshold: u64) {
+        self.alert_evaluator
+            .set_missing_signatures_threshold(threshold);
+    }
+
     /// The lowest next block we need to try and sync.
     pub fn lowest_next_block_to_sync(&self) -> Result<u64, WatcherError> {
         let last_synced = self.watcher_db.last_synced_blocks()?;
@@ -194,6 +206,8 @@ impl Watcher {
                             src_url,
                             block_index
                         );
+                        self.alert_evaluator.check_block_data(src_url, block_data);
+
                         if self.store_block_data {
                             match self.watcher_db.add_block_data(src_url, block_data) {
                                 Ok(()) => {}
@@ -242,6 +256,8 @@ impl Watcher {
                 }
             }
 
+            self.alert_evaluator.check_sync_progress()?;
+
             // If nothing succeeded, maybe we are synced all the way through or something
             // else is wrong.
             if !had_success {
@@ -2,7 +2,7 @@
 
 //! The watcher database
 
-use crate::{block_data_store::BlockDataStore, error::WatcherDBError};
+use crate::{alerts::Alert, block_data_store::BlockDataStore, error::WatcherDBError};
 
 use mc_attest_core::VerificationReport;
 use mc_common::{
@@ -34,6 +34,9 @@ use url::Url;
 /// LMDB Constant.
 const MAX_LMDB_FILE_SIZE: usize = 1_099_511_627_776; // 1 TB
 
+/// Number of LMDB databases.
+const NUM_LMDB_DATABASES: u32 = 13;
+
 /// Metadata store settings that are used for version control.
 #[derive(Clone, Default, Debug)]
 pub struct WatcherDbMetadataStoreSettings;
@@ -72,6 +75,15 @@ pub const LAST_SYNCED_DB_NAME: &str = "watcher_db:last_synced";
 /// Last known config database name.
 pub const CONFIG_DB_NAME: &str = "watcher_db:config";
 
+/// Alerts database name.
+pub const ALERTS_DB_NAME: &str = "watcher_db:alerts";
+
+/// Last seen MRENCLAVE by tx source url database name.
+pub const MR_ENCLAVES_BY_SRC_URL_DB_NAME: &str = "watcher_db:mr_enclaves_by_src_url";
+
+/// Block ranges signed by each block signer database name.
+pub const SIGNER_BLOCK_RANGES_DB_NAME: &str = "watcher_db:signer_block_ranges";
+
 /// Keys used by the `config` database.
 pub const CONFIG_DB_KEY_TX_SOURCE_URLS: &str = "tx_source_urls";
 
@@ -151,6 +163,15 @@ pub struct WatcherDB {
     /// the set of URLs currently being polled.
     config: Database,
 
+    /// Alert log: (block index, sequence number) -> Alert.
+    alerts: Database,
+
+    /// Tx source url -> MRENCLAVE of the last verification report seen for it.
+    mr_enclaves_by_src_url: Database,
+
+    /// (Block signer, tx source url) -> first and last block index it signed.
+    signer_block_ranges: Database,
+
     /// Were we opened in write mode?
     write_allowed: bool,
 
@@ -163,7 +184,7 @@ impl WatcherDB {
     pub fn open_ro(path: &Path, logger: Logger) -> Result<Self, WatcherDBError> {
         let env = Arc::new(
             Environment::new()
-                .set_max_dbs(10)
+                .set_max_dbs(NUM_LMDB_DATABASES)
                 .set_map_size(MAX_LMDB_FILE_SIZE)
                 // TODO - needed because currently our test cloud machines have slow disks.
                 .set_flags(EnvironmentFlags::NO_SYNC)
@@ -188,6 +209,9 @@ impl WatcherDB {
             env.open_db(Some(VERIFICATION_REPORTS_POLL_QUEUE_DB_NAME))?;
         let last_synced = env.open_db(Some(LAST_SYNCED_DB_NAME))?;
         let config = env.open_db(Some(CONFIG_DB_NAME))?;
+        let alerts = env.open_db(Some(ALERTS_DB_NAME))?;
+        let mr_enclaves_by_src_url = env.open_db(Some(MR_ENCLAVES_BY_SRC_URL_DB_NAME))?;
+        let signer_block_ranges = env.open_db(Some(SIGNER_BLOCK_RANGES_DB_NAME))?;
 
         let block_data_store = BlockDataStore::new(env.clone(), logger.clone())?;
 
@@ -200,6 +224,9 @@ impl WatcherDB {
             verification_reports_poll_queue,
             last_synced,
             config,
+            alerts,
+            mr_enclaves_by_src_url,
+            signer_block_ranges,
             write_allowed: false,
             logger,
         })
@@ -221,7 +248,7 @@ impl WatcherDB {
     pub fn create(path: &Path) -> Result<(), WatcherDBError> {
         let env = Arc::new(
             Environment::new()
-                .set_max_dbs(10)
+                .set_max_dbs(NUM_LMDB_DATABASES)
                 .set_map_size(MAX_LMDB_FILE_SIZE)
                 .open(path)?,
         );
@@ -243,6 +270,9 @@ impl WatcherDB {
         )?;
         env.create_db(Some(LAST_SYNCED_DB_NAME), DatabaseFlags::empty())?;
         env.create_db(Some(CONFIG_DB_NAME), DatabaseFlags::DUP_SORT)?;
+        env.create_db(Some(ALERTS_DB_NAME), DatabaseFlags::empty())?;
+        env.create_db(Some(MR_ENCLAVES_BY_SRC_URL_DB_NAME), DatabaseFlags::empty())?;
+        env.create_db(Some(SIGNER_BLOCK_RANGES_DB_NAME), DatabaseFlags::empty())?;
 
         BlockDataStore::create(env)?;
 
@@ -326,6 +356,14 @@ impl WatcherDB {
             WriteFlags::empty(),
         )?;
 
+        // Extend the range of blocks signed by the block signer.
+        self.extend_signer_block_range(
+            &mut db_txn,
+            signature_data.block_signature.signer(),
+            src_url,
+            block_index,
+        )?;
+
         // Add the block signer to our polling queue, unless we already have a report
         // for it.
         if !self.has_verification_report_for_signer_and_url(
@@ -1001,6 +1039,154 @@ impl WatcherDB {
         }
     }
 
+    /// Append an alert to the alert log.
+    pub fn add_alert(&self, alert: &Alert) -> Result<(), WatcherDBError> {
+        if !self.write_allowed {
+            return Err(WatcherDBError::ReadOnly);
+        }
+
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        // Alerts are keyed by block index, followed by a sequence number that
+        // orders alerts raised at the same block index.
+        let block_index_bytes = alert.block_index.to_be_bytes();
+        let mut cursor = db_txn.open_ro_cursor(self.alerts)?;
+        let sequence_number = cursor
+            .iter_from(&block_index_bytes)
+            .filter_map(Result::ok)
+            .take_while(|(key_bytes, _value_bytes)| key_bytes.starts_with(&block_index_bytes))
+            .count() as u64;
+        drop(cursor);
+
+        let mut key_bytes = block_index_bytes.to_vec();
+        key_bytes.extend(sequence_number.to_be_bytes());
+        db_txn.put(
+            self.alerts,
+            &key_bytes,
+            &encode(alert),
+            WriteFlags::NO_OVERWRITE,
+        )?;
+
+        db_txn.commit()?;
+        Ok(())
+    }
+
+    /// Get the alerts raised for blocks in the range
+    /// `[start_block_index, end_block_index)`, or from `start_block_index`
+    /// onwards if `end_block_index` is `None`.
+    pub fn get_alerts(
+        &self,
+        start_block_index: u64,
+        end_block_index: Option<u64>,
+    ) -> Result<Vec<Alert>, WatcherDBError> {
+        let db_txn = self.env.begin_ro_txn()?;
+        let mut cursor = db_txn.open_ro_cursor(self.alerts)?;
+
+        let mut alerts = Vec::new();
+        for (key_bytes, value_bytes) in cursor
+            .iter_from(&start_block_index.to_be_bytes())
+            .filter_map(Result::ok)
+        {
+            let block_index = u64::from_be_bytes(key_bytes[0..8].try_into().unwrap());
+            if end_block_index.map_or(false, |end| block_index >= end) {
+                break;
+            }
+            alerts.push(decode(value_bytes)?);
+        }
+        Ok(alerts)
+    }
+
+    /// Store the MRENCLAVE of the latest verification report seen for a tx
+    /// source url, returning the previously stored one (if any).
+    pub fn update_mr_enclave(
+        &self,
+        src_url: &Url,
+        mr_enclave: &[u8],
+    ) -> Result<Option<Vec<u8>>, WatcherDBError> {
+        if !self.write_allowed {
+            return Err(WatcherDBError::ReadOnly);
+        }
+
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        let previous_mr_enclave =
+            match db_txn.get(self.mr_enclaves_by_src_url, &src_url.as_str().as_bytes()) {
+                Ok(value_bytes) => Some(value_bytes.to_vec()),
+                Err(lmdb::Error::NotFound) => None,
+                Err(err) => return Err(err.into()),
+            };
+
+        db_txn.put(
+            self.mr_enclaves_by_src_url,
+            &src_url.as_str().as_bytes(),
+            &mr_enclave,
+            WriteFlags::empty(),
+        )?;
+
+        db_txn.commit()?;
+        Ok(previous_mr_enclave)
+    }
+
+    /// Get the first and last block index signed by a block signer in the
+    /// archive of a tx source url, if it signed any.
+    pub fn get_signer_block_range(
+        &self,
+        block_signer: &Ed25519Public,
+        src_url: &Url,
+    ) -> Result<Option<(u64, u64)>, WatcherDBError> {
+        let db_txn = self.env.begin_ro_txn()?;
+        self.get_signer_block_range_impl(&db_txn, block_signer, src_url)
+    }
+
+    fn get_signer_block_range_impl(
+        &self,
+        db_txn: &impl Transaction,
+        block_signer: &Ed25519Public,
+        src_url: &Url,
+    ) -> Result<Option<(u64, u64)>, WatcherDBError> {
+        let mut key_bytes = block_signer.to_bytes().to_vec();
+        key_bytes.extend(src_url.as_str().as_bytes());
+
+        match db_txn.get(self.signer_block_ranges, &key_bytes) {
+            Ok(value_bytes) if value_bytes.len() == 16 => {
+                let first = u64::from_be_bytes(value_bytes[..8].try_into().unwrap());
+                let last = u64::from_be_bytes(value_bytes[8..].try_into().unwrap());
+                Ok(Some((first, last)))
+            }
+            Ok(_) => Err(WatcherDBError::Deserialization),
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Extend the range of blocks signed by a block signer to include
+    /// `block_index`.
+    fn extend_signer_block_range<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        block_signer: &Ed25519Public,
+        src_url: &Url,
+        block_index: u64,
+    ) -> Result<(), WatcherDBError> {
+        let (first, last) = match self.get_signer_block_range_impl(db_txn, block_signer, src_url)? {
+            Some((first, last)) => (first.min(block_index), last.max(block_index)),
+            None => (block_index, block_index),
+        };
+
+        let mut key_bytes = block_signer.to_bytes().to_vec();
+        key_bytes.extend(src_url.as_str().as_bytes());
+        let mut value_bytes = first.to_be_bytes().to_vec();
+        value_bytes.extend(last.to_be_bytes());
+
+        db_txn.put(
+            self.signer_block_ranges,
+            &key_bytes,
+            &value_bytes,
+            WriteFlags::empty(),
+        )?;
+        Ok(())
+    }
+
     /// Remove all the data associated with a given source url.
     pub fn remove_all_for_source_url(&self, src_url: &Url) -> Result<(), WatcherDBError> {
         if !self.write_allowed {
@@ -1058,6 +1244,33 @@ impl WatcherDB {
         }
         drop(cursor);
 
+        // Remove signed block ranges.
+        let mut cursor = db_txn.open_rw_cursor(self.signer_block_ranges)?;
+        for (key_bytes, _value_bytes) in cursor.iter_start().filter_map(Result::ok) {
+            // The key format is 32 bytes signer public key followed by tx source url.
+            if key_bytes.len() < signer_key_size {
+                continue;
+            }
+
+            if &key_bytes[signer_key_size..] == src_url.as_str().as_bytes() {
+                cursor.del(WriteFlags::empty())?;
+            }
+        }
+        drop(cursor);
+
+        // Remove the last seen MRENCLAVE. Alerts are kept as a historical record.
+        match db_txn.del(
+            self.mr_enclaves_by_src_url,
+            &src_url.as_str().as_bytes(),
+            None,
+        ) {
+            Ok(()) => {}
+            Err(lmdb::Error::NotFound) => {}
+            Err(err) => {
+                return Err(err.into());
+            }
+        };
+
         // Done
         db_txn.commit()?;
         Ok(())
@@ -1099,6 +1312,7 @@ pub fn create_or_open_rw_watcher_db(
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::alerts::AlertKind;
     use mc_account_keys::AccountKey;
     use mc_attest_core::VerificationSignature;
     use mc_common::logger::{test_with_logger, Logger};
@@ -1169,6 +1383,61 @@ pub mod tests {
         assert_eq!(watcher_db.get_block_signatures(1).unwrap().len(), 2);
     }
 
+    // The range of blocks signed by each signer is tracked per source url.
+    #[test_with_logger]
+    fn test_signer_block_range(logger: Logger) {
+        let mut rng: Hc128Rng = Hc128Rng::from_seed([8u8; 32]);
+        let url1 = Url::parse("http://www.my_url1.com").unwrap();
+        let url2 = Url::parse("http://www.my_url2.com").unwrap();
+        let urls = vec![url1.clone(), url2.clone()];
+        let watcher_db = setup_watcher_db(&urls, logger);
+
+        let blocks = setup_blocks();
+
+        let signing_key_a = Ed25519Pair::from_random(&mut rng);
+        let signing_key_b = Ed25519Pair::from_random(&mut rng);
+
+        for (block_index, signing_key) in [
+            (1, &signing_key_a),
+            (2, &signing_key_a),
+            (3, &signing_key_b),
+        ] {
+            let signature =
+                BlockSignature::from_block_and_keypair(&blocks[block_index].0, signing_key)
+                    .unwrap();
+            watcher_db
+                .add_block_signature(&url1, block_index as u64, signature, "00/00".to_owned())
+                .unwrap();
+        }
+
+        assert_eq!(
+            watcher_db
+                .get_signer_block_range(&signing_key_a.public_key(), &url1)
+                .unwrap(),
+            Some((1, 2))
+        );
+        assert_eq!(
+            watcher_db
+                .get_signer_block_range(&signing_key_b.public_key(), &url1)
+                .unwrap(),
+            Some((3, 3))
+        );
+        assert_eq!(
+            watcher_db
+                .get_signer_block_range(&signing_key_a.public_key(), &url2)
+                .unwrap(),
+            None
+        );
+
+        watcher_db.remove_all_for_source_url(&url1).unwrap();
+        assert_eq!(
+            watcher_db
+                .get_signer_block_range(&signing_key_a.public_key(), &url1)
+                .unwrap(),
+            None
+        );
+    }
+
     // Highest synced block should return the minimum highest synced block for all
     // URLs
     #[test_with_logger]
@@ -2025,4 +2294,50 @@ pub mod tests {
             );
         })
     }
+
+    // The alert log should return alerts ordered by block index, within the
+    // requested range.
+    #[test_with_logger]
+    fn test_alerts(logger: Logger) {
+        let url1 = Url::parse("http://www.my_url1.com").unwrap();
+        let url2 = Url::parse("http://www.my_url2.com").unwrap();
+        let watcher_db = setup_watcher_db(&[url1.clone(), url2.clone()], logger);
+
+        let alert_a = Alert::new(AlertKind::MissingSignatures, 5, &url1, None, "a");
+        let alert_b = Alert::new(AlertKind::BlockContentsMismatch, 2, &url2, None, "b");
+        let alert_c = Alert::new(AlertKind::UnknownSigner, 5, &url2, None, "c");
+        for alert in [&alert_a, &alert_b, &alert_c] {
+            watcher_db.add_alert(alert).unwrap();
+        }
+
+        assert_eq!(
+            watcher_db.get_alerts(0, None).unwrap(),
+            vec![alert_b.clone(), alert_a.clone(), alert_c.clone()]
+        );
+        assert_eq!(watcher_db.get_alerts(0, Some(5)).unwrap(), vec![alert_b]);
+        assert_eq!(
+            watcher_db.get_alerts(3, None).unwrap(),
+            vec![alert_a, alert_c]
+        );
+        assert_eq!(watcher_db.get_alerts(6, None).unwrap(), vec![]);
+    }
+
+    // Storing an MRENCLAVE should return the previously stored one.
+    #[test_with_logger]
+    fn test_update_mr_enclave(logger: Logger) {
+        let url1 = Url::parse("http://www.my_url1.com").unwrap();
+        let url2 = Url::parse("http://www.my_url2.com").unwrap();
+        let watcher_db = setup_watcher_db(&[url1.clone(), url2.clone()], logger);
+
+        assert_eq!(watcher_db.update_mr_enclave(&url1, &[1; 32]).unwrap(), None);
+        assert_eq!(watcher_db.update_mr_enclave(&url2, &[2; 32]).unwrap(), None);
+        assert_eq!(
+            watcher_db.update_mr_enclave(&url1, &[3; 32]).unwrap(),
+            Some(vec![1; 32])
+        );
+        assert_eq!(
+            watcher_db.update_mr_enclave(&url1, &[3; 32]).unwrap(),
+            Some(vec![3; 32])
+        );
+    }
 }